@@ -1,6 +1,8 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.0
 
-use super::sea_orm_active_enums::{CrawlStatus, DublinMetadataFormat};
+use super::sea_orm_active_enums::{
+    ContentWarning, CrawlStatus, DublinMetadataFormat, PublicationState, ScanStatus,
+};
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -17,9 +19,19 @@ pub struct Model {
     pub org_id: Option<Uuid>,
     pub job_run_id: Option<String>,
     pub seed_url: String,
-    pub is_private: bool,
+    pub canonical_url: Option<String>,
     pub dublin_metadata_format: DublinMetadataFormat,
     pub s3_filename: Option<String>,
+    pub pdf_s3_filename: Option<String>,
+    pub scan_status: ScanStatus,
+    pub metadata_scrubbed: bool,
+    pub embargo_until: Option<DateTime>,
+    pub content_warning: Option<ContentWarning>,
+    pub publication_state: PublicationState,
+    pub revision: i32,
+    pub organization_id: i32,
+    pub doi: Option<String>,
+    pub crawl_quality_reason: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -40,6 +52,14 @@ pub enum Relation {
         on_delete = "NoAction"
     )]
     DublinMetadataEn,
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Restrict"
+    )]
+    Organization,
 }
 
 impl Related<super::dublin_metadata_ar::Entity> for Entity {
@@ -54,4 +74,10 @@ impl Related<super::dublin_metadata_en::Entity> for Entity {
     }
 }
 
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
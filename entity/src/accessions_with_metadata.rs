@@ -1,28 +1,51 @@
-use super::sea_orm_active_enums::{CrawlStatus, DublinMetadataFormat};
+use super::sea_orm_active_enums::{
+    ContentWarning, CrawlStatus, DublinMetadataFormat, PublicationState, ScanStatus,
+};
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// The view's `full_text_en` and `full_text_ar` search vectors are only filtered on, so they
+/// are left out here and never selected or sent back with list rows.
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "accessions_with_metadata")]
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
+    /// Not published yet or still under embargo
     pub is_private: bool,
+    pub publication_state: PublicationState,
+    pub embargo_until: Option<DateTime>,
+    pub content_warning: Option<ContentWarning>,
     pub crawl_status: CrawlStatus,
     pub crawl_timestamp: DateTime,
     pub crawl_id: Option<Uuid>,
     pub org_id: Option<Uuid>,
     pub job_run_id: Option<String>,
     pub seed_url: String,
+    pub canonical_url: Option<String>,
     pub dublin_metadata_date: DateTime,
     pub dublin_metadata_format: DublinMetadataFormat,
     pub s3_filename: Option<String>,
+    pub pdf_s3_filename: Option<String>,
+    pub scan_status: ScanStatus,
+    pub metadata_scrubbed: bool,
+    /// Bumped on every metadata edit, see `UpdateAccessionRequest::revision`
+    pub revision: i32,
+    pub organization_id: i32,
+    /// Minted through DataCite on demand, e.g. `10.1234/abcd-efgh`
+    pub doi: Option<String>,
+    /// Why the crawl was flagged as a bad capture, set alongside `CrawlStatus::BadCrawl`
+    pub crawl_quality_reason: Option<String>,
     pub title_en: Option<String>,
     pub description_en: Option<String>,
     pub subjects_en: Option<Vec<String>>,
     pub subjects_en_ids: Option<Vec<i32>>,
     pub title_ar: Option<String>,
     pub description_ar: Option<String>,
+    /// English metadata was drafted by machine translation and hasn't been edited since
+    pub machine_translated_en: bool,
+    /// Arabic metadata was drafted by machine translation and hasn't been edited since
+    pub machine_translated_ar: bool,
     pub subjects_ar: Option<Vec<String>>,
     pub subjects_ar_ids: Option<Vec<i32>>,
     pub has_english_metadata: bool,
@@ -1,6 +1,6 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.0
 
-use super::sea_orm_active_enums::Role;
+use super::sea_orm_active_enums::{EmailStatus, Role};
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
@@ -12,6 +12,11 @@ pub struct Model {
     pub email: String,
     pub is_active: bool,
     pub role: Role,
+    pub email_status: EmailStatus,
+    pub organization_id: i32,
+    /// Whether the user opted in to the weekly activity digest email
+    pub activity_digest: bool,
+    pub activity_digest_sent_at: Option<DateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -20,6 +25,14 @@ pub enum Relation {
     Session,
     #[sea_orm(has_many = "super::api_key::Entity")]
     ApiKey,
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Restrict"
+    )]
+    Organization,
 }
 
 impl Related<super::session::Entity> for Entity {
@@ -34,4 +47,10 @@ impl Related<super::api_key::Entity> for Entity {
     }
 }
 
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -10,6 +10,7 @@ pub struct Model {
     pub id: i32,
     pub title: String,
     pub description: Option<String>,
+    pub machine_translated: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
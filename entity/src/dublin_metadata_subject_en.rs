@@ -8,8 +8,13 @@ use serde::{Deserialize, Serialize};
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
-    #[sea_orm(unique)]
+    /// Unique within the organization
     pub subject: String,
+    pub organization_id: i32,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+    /// Email of the user who added the subject, unknown for subjects added before it was recorded
+    pub created_by: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -1,12 +1,28 @@
 pub mod accession;
+pub mod accession_content_digest;
+pub mod accession_derivative;
+pub mod accession_event;
+pub mod accession_provenance;
+pub mod accession_relation;
+pub mod accession_workflow_label;
 pub mod accessions_with_metadata;
 pub mod api_key;
 pub mod archive_user;
+pub mod audit_log;
+pub mod collection;
+pub mod collection_accession;
+pub mod comment;
+pub mod crawl_blocklist;
 pub mod dublin_metadata_ar;
 pub mod dublin_metadata_ar_subjects;
 pub mod dublin_metadata_en;
 pub mod dublin_metadata_en_subjects;
 pub mod dublin_metadata_subject_ar;
 pub mod dublin_metadata_subject_en;
+pub mod feature_flag;
+pub mod organization;
 pub mod sea_orm_active_enums;
 pub mod session;
+pub mod social_metadata;
+pub mod upload_session;
+pub mod workflow_label;
@@ -31,6 +31,19 @@ pub enum DublinMetadataFormat {
     #[sea_orm(string_value = "wacz")]
     #[serde(rename = "wacz")]
     Wacz,
+    #[sea_orm(string_value = "jpeg")]
+    #[serde(rename = "jpeg")]
+    Jpeg,
+    #[sea_orm(string_value = "png")]
+    #[serde(rename = "png")]
+    Png,
+    #[sea_orm(string_value = "mp4")]
+    #[serde(rename = "mp4")]
+    Mp4,
+    /// Plain or gzipped WARC files, stored as uploaded
+    #[sea_orm(string_value = "warc")]
+    #[serde(rename = "warc")]
+    Warc,
 }
 #[derive(
     Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
@@ -44,3 +57,154 @@ pub enum Role {
     #[sea_orm(string_value = "contributor")]
     Contributor,
 }
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "scan_status")]
+pub enum ScanStatus {
+    #[sea_orm(string_value = "not_scanned")]
+    NotScanned,
+    #[sea_orm(string_value = "clean")]
+    Clean,
+    #[sea_orm(string_value = "infected")]
+    Infected,
+    #[sea_orm(string_value = "failed")]
+    Failed,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "derivative_kind")]
+pub enum DerivativeKind {
+    #[sea_orm(string_value = "web_video")]
+    WebVideo,
+    #[sea_orm(string_value = "thumbnail")]
+    Thumbnail,
+}
+
+/// How one accession relates to another, read as "from <kind> to".
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "accession_relation_kind"
+)]
+pub enum AccessionRelationKind {
+    /// One part of a larger whole, e.g. a post in a testimony thread
+    #[sea_orm(string_value = "part_of")]
+    PartOf,
+    /// Cites or follows up on the other accession
+    #[sea_orm(string_value = "references")]
+    References,
+    /// A newer capture or correction that replaces the other accession
+    #[sea_orm(string_value = "supersedes")]
+    Supersedes,
+}
+
+/// Why an accession may be distressing to look at, so it can be shown behind a warning.
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "content_warning")]
+pub enum ContentWarning {
+    #[sea_orm(string_value = "graphic_violence")]
+    GraphicViolence,
+    #[sea_orm(string_value = "sexual_violence")]
+    SexualViolence,
+    #[sea_orm(string_value = "human_remains")]
+    HumanRemains,
+    #[sea_orm(string_value = "distressing")]
+    Distressing,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "accession_event_kind"
+)]
+pub enum AccessionEventKind {
+    /// Someone looked at the accession's details
+    #[sea_orm(string_value = "view")]
+    View,
+    /// Someone was given a URL to fetch the accession's WACZ or uploaded file
+    #[sea_orm(string_value = "wacz_url")]
+    WaczUrl,
+}
+
+/// Whether emails to a user are getting through, as reported by Postmark's webhooks.
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "email_status")]
+pub enum EmailStatus {
+    #[sea_orm(string_value = "deliverable")]
+    Deliverable,
+    /// A temporary failure, e.g. a full inbox, sends carry on
+    #[sea_orm(string_value = "soft_bounce")]
+    SoftBounce,
+    /// The address doesn't exist, sends to it are suppressed
+    #[sea_orm(string_value = "hard_bounce")]
+    HardBounce,
+    /// The user marked one of our emails as spam
+    #[sea_orm(string_value = "spam_complaint")]
+    SpamComplaint,
+}
+
+/// Where an accession is in the publishing workflow, only published accessions are public.
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "publication_state")]
+pub enum PublicationState {
+    /// Being worked on, only staff can see it
+    #[sea_orm(string_value = "draft")]
+    Draft,
+    /// Submitted for a researcher to check before publishing
+    #[sea_orm(string_value = "in_review")]
+    InReview,
+    /// Public, unless it is still under embargo
+    #[sea_orm(string_value = "published")]
+    Published,
+    /// Taken down after being published
+    #[sea_orm(string_value = "withdrawn")]
+    Withdrawn,
+}
+
+/// The kind of service a file hash was timestamped with, which decides how its proof is checked.
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "timestamp_method")]
+pub enum TimestampMethod {
+    /// Anchored in Bitcoin through an OpenTimestamps calendar, the proof is an `.ots` file
+    #[sea_orm(string_value = "open_timestamps")]
+    OpenTimestamps,
+    /// Signed by an RFC 3161 timestamping authority, the proof is its DER `TimeStampResp`
+    #[sea_orm(string_value = "rfc3161")]
+    Rfc3161,
+}
+
+/// The social media platform a crawled post was published on.
+#[derive(
+    Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "social_platform")]
+pub enum SocialPlatform {
+    #[sea_orm(string_value = "facebook")]
+    #[serde(rename = "facebook")]
+    Facebook,
+    #[sea_orm(string_value = "telegram")]
+    #[serde(rename = "telegram")]
+    Telegram,
+    /// Formerly Twitter, whose URLs are recognised too
+    #[sea_orm(string_value = "x")]
+    #[serde(rename = "x")]
+    X,
+}
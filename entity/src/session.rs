@@ -9,6 +9,8 @@ pub struct Model {
     pub id: Uuid,
     pub expiry_time: DateTime,
     pub user_id: Uuid,
+    /// What the client that asked for the login link is known by, as `IP_PRIVACY` allows
+    pub client: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
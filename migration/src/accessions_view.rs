@@ -0,0 +1,327 @@
+//! Versions of the `accessions_with_metadata` view.
+//!
+//! Postgres can't add columns to a view in place, nor drop a column a view selects, so most
+//! schema changes to accessions drop the view, alter the tables and create it again. Each
+//! version here builds on the one before it, so a migration that changes the view adds one
+//! version below and creates it in `up()`, then creates the previous version in `down()`.
+//!
+//! Migrations run in a transaction on Postgres, so readers never see the view missing while
+//! it's recreated. Migrations up to `m20260105_012142_optional_browsertrix_fields_in_accessions`
+//! had shipped before these versions existed and keep their own copy of the SQL they ran, so
+//! only migrations after them create the view from here.
+
+use sea_orm_migration::prelude::*;
+
+/// Subjects and whether each language has metadata, as versions before
+/// [`AccessionsView::ordered_subjects`] select them. Names and IDs are aggregated separately
+/// and in no particular order, so they aren't guaranteed to line up.
+const SUBJECT_COLUMNS: &str = r#"(
+        SELECT array_agg(dmse.subject)
+        FROM dublin_metadata_subject_en dmse
+        LEFT JOIN dublin_metadata_en_subjects dmes ON dmse.id = dmes.subject_id
+        LEFT JOIN dublin_metadata_en dme ON dme.id = dmes.metadata_id
+        WHERE dme.id = a.dublin_metadata_en
+        -- api validation limits 200 max subjects
+        LIMIT 200
+    ) AS subjects_en,
+    (
+        SELECT array_agg(dmse.id)
+        FROM dublin_metadata_subject_en dmse
+        LEFT JOIN dublin_metadata_en_subjects dmes ON dmse.id = dmes.subject_id
+        LEFT JOIN dublin_metadata_en dme ON dme.id = dmes.metadata_id
+        WHERE dme.id = a.dublin_metadata_en
+        -- api validation limits 200 max subjects
+        LIMIT 200
+    ) AS subjects_en_ids,
+    (
+        SELECT array_agg(dmsa.subject)
+        FROM dublin_metadata_subject_ar dmsa
+        LEFT JOIN dublin_metadata_ar_subjects dmas ON dmsa.id = dmas.subject_id
+        LEFT JOIN dublin_metadata_ar dma ON dma.id = dmas.metadata_id
+        WHERE dma.id = a.dublin_metadata_ar
+        -- api validation limits 200 max subjects
+        LIMIT 200
+    ) AS subjects_ar,
+    (
+        SELECT array_agg(dmsa.id)
+        FROM dublin_metadata_subject_ar dmsa
+        LEFT JOIN dublin_metadata_ar_subjects dmas ON dmsa.id = dmas.subject_id
+        LEFT JOIN dublin_metadata_ar dma ON dma.id = dmas.metadata_id
+        WHERE dma.id = a.dublin_metadata_ar
+        -- api validation limits 200 max subjects
+        LIMIT 200
+    ) AS subjects_ar_ids,
+    COALESCE((dme.id IS NOT NULL), FALSE) AS has_english_metadata,
+    COALESCE((dma.id IS NOT NULL), FALSE) AS has_arabic_metadata"#;
+
+/// Like [`SUBJECT_COLUMNS`] but aggregating names and IDs in subject ID order, so the name
+/// at each position belongs to the ID at the same position.
+const ORDERED_SUBJECT_COLUMNS: &str = r#"(
+        SELECT array_agg(dmse.subject ORDER BY dmse.id)
+        FROM dublin_metadata_subject_en dmse
+        LEFT JOIN dublin_metadata_en_subjects dmes ON dmse.id = dmes.subject_id
+        LEFT JOIN dublin_metadata_en dme ON dme.id = dmes.metadata_id
+        WHERE dme.id = a.dublin_metadata_en
+        -- api validation limits 200 max subjects
+        LIMIT 200
+    ) AS subjects_en,
+    (
+        SELECT array_agg(dmse.id ORDER BY dmse.id)
+        FROM dublin_metadata_subject_en dmse
+        LEFT JOIN dublin_metadata_en_subjects dmes ON dmse.id = dmes.subject_id
+        LEFT JOIN dublin_metadata_en dme ON dme.id = dmes.metadata_id
+        WHERE dme.id = a.dublin_metadata_en
+        -- api validation limits 200 max subjects
+        LIMIT 200
+    ) AS subjects_en_ids,
+    (
+        SELECT array_agg(dmsa.subject ORDER BY dmsa.id)
+        FROM dublin_metadata_subject_ar dmsa
+        LEFT JOIN dublin_metadata_ar_subjects dmas ON dmsa.id = dmas.subject_id
+        LEFT JOIN dublin_metadata_ar dma ON dma.id = dmas.metadata_id
+        WHERE dma.id = a.dublin_metadata_ar
+        -- api validation limits 200 max subjects
+        LIMIT 200
+    ) AS subjects_ar,
+    (
+        SELECT array_agg(dmsa.id ORDER BY dmsa.id)
+        FROM dublin_metadata_subject_ar dmsa
+        LEFT JOIN dublin_metadata_ar_subjects dmas ON dmsa.id = dmas.subject_id
+        LEFT JOIN dublin_metadata_ar dma ON dma.id = dmas.metadata_id
+        WHERE dma.id = a.dublin_metadata_ar
+        -- api validation limits 200 max subjects
+        LIMIT 200
+    ) AS subjects_ar_ids,
+    COALESCE((dme.id IS NOT NULL), FALSE) AS has_english_metadata,
+    COALESCE((dma.id IS NOT NULL), FALSE) AS has_arabic_metadata"#;
+
+/// Private when flagged so, or while under embargo.
+const EMBARGOED_IS_PRIVATE: &str = r#"(
+        a.is_private
+        OR COALESCE(a.embargo_until > (now() AT TIME ZONE 'UTC'), FALSE)
+    ) AS is_private"#;
+
+/// Private until its publication state is published, or while under embargo.
+const UNPUBLISHED_IS_PRIVATE: &str = r#"(
+        a.publication_state <> 'published'
+        OR COALESCE(a.embargo_until > (now() AT TIME ZONE 'UTC'), FALSE)
+    ) AS is_private"#;
+
+/// The columns one version of the view selects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessionsView {
+    /// Selected from the accession `a` and its English `dme` and Arabic `dma` metadata,
+    /// ahead of the subjects
+    columns: Vec<&'static str>,
+    /// Subjects and whether each language has metadata
+    subject_columns: &'static str,
+    /// Selected after the subjects
+    trailing_columns: Vec<&'static str>,
+}
+
+impl AccessionsView {
+    /// The view as `m20250921_203431_add_full_text_search` left it.
+    pub fn full_text_search() -> Self {
+        Self {
+            columns: vec![
+                "a.id",
+                "a.is_private",
+                "a.crawl_status",
+                "a.crawl_timestamp",
+                "a.crawl_id",
+                "a.org_id",
+                "a.job_run_id",
+                "a.seed_url",
+                "a.dublin_metadata_date",
+                "dme.title AS title_en",
+                "dme.description AS description_en",
+                "dma.title AS title_ar",
+                "dma.description AS description_ar",
+            ],
+            subject_columns: SUBJECT_COLUMNS,
+            trailing_columns: vec!["a.full_text_en", "a.full_text_ar"],
+        }
+    }
+
+    /// Adds the file format and S3 filename, see `m20251017_164508_add_s3_spaces_filename`.
+    pub fn s3_spaces_filename() -> Self {
+        Self::full_text_search().with_after(
+            "a.dublin_metadata_date",
+            &["a.dublin_metadata_format", "a.s3_filename"],
+        )
+    }
+
+    /// Adds the URL with tracking parameters removed, see `m20261016_090000_add_canonical_url`.
+    pub fn canonical_url() -> Self {
+        Self::s3_spaces_filename().with_after("a.seed_url", &["a.canonical_url"])
+    }
+
+    /// Adds the PDF rendering's S3 filename, see `m20261016_100000_add_pdf_derivative`.
+    pub fn pdf_derivative() -> Self {
+        Self::canonical_url().with_after("a.s3_filename", &["a.pdf_s3_filename"])
+    }
+
+    /// Adds the virus scan status, see `m20261016_130000_add_scan_status`.
+    pub fn scan_status() -> Self {
+        Self::pdf_derivative().with_after("a.pdf_s3_filename", &["a.scan_status"])
+    }
+
+    /// Adds whether uploaded files had their metadata scrubbed, see
+    /// `m20261016_140000_add_media_formats`.
+    pub fn media_formats() -> Self {
+        Self::scan_status().with_after("a.scan_status", &["a.metadata_scrubbed"])
+    }
+
+    /// Keeps accessions under embargo private, see `m20261016_170000_add_embargo_until`.
+    pub fn embargo_until() -> Self {
+        Self::media_formats()
+            .with_replaced("a.is_private", EMBARGOED_IS_PRIVATE)
+            .with_after(EMBARGOED_IS_PRIVATE, &["a.embargo_until"])
+    }
+
+    /// Adds the content warning, see `m20261016_180000_add_content_warning`.
+    pub fn content_warning() -> Self {
+        Self::embargo_until().with_after("a.embargo_until", &["a.content_warning"])
+    }
+
+    /// Derives privacy from the publication state, see `m20261016_210000_add_publication_state`.
+    pub fn publication_state() -> Self {
+        Self::content_warning()
+            .with_replaced(EMBARGOED_IS_PRIVATE, UNPUBLISHED_IS_PRIVATE)
+            .with_after(UNPUBLISHED_IS_PRIVATE, &["a.publication_state"])
+    }
+
+    /// Adds whether each language was machine translated, see
+    /// `m20261017_000000_add_machine_translated`.
+    pub fn machine_translated() -> Self {
+        Self::publication_state().with_after(
+            "dma.description AS description_ar",
+            &[
+                "COALESCE(dme.machine_translated, FALSE) AS machine_translated_en",
+                "COALESCE(dma.machine_translated, FALSE) AS machine_translated_ar",
+            ],
+        )
+    }
+
+    /// Adds the revision used to refuse stale edits, see
+    /// `m20261017_010000_add_accession_revision`.
+    pub fn accession_revision() -> Self {
+        Self::machine_translated().with_after("a.metadata_scrubbed", &["a.revision"])
+    }
+
+    /// Adds the organization the accession belongs to, see
+    /// `m20261017_030000_add_organizations`.
+    pub fn organizations() -> Self {
+        Self::accession_revision().with_after("a.revision", &["a.organization_id"])
+    }
+
+    /// Adds the DOI minted for the accession, see `m20261017_050000_add_accession_doi`.
+    pub fn accession_doi() -> Self {
+        Self::organizations().with_after("a.organization_id", &["a.doi"])
+    }
+
+    /// Adds why a crawl was flagged as a bad capture, see
+    /// `m20261017_080000_add_crawl_quality_reason`.
+    pub fn crawl_quality_reason() -> Self {
+        Self::accession_doi().with_after("a.doi", &["a.crawl_quality_reason"])
+    }
+
+    /// Aggregates subject names and IDs in the same order, see
+    /// `m20261017_100000_order_view_subjects`.
+    pub fn ordered_subjects() -> Self {
+        Self {
+            subject_columns: ORDERED_SUBJECT_COLUMNS,
+            ..Self::crawl_quality_reason()
+        }
+    }
+
+    /// Selects `columns` right after `existing`.
+    ///
+    /// # Panics
+    /// Panics if this version doesn't select `existing`
+    fn with_after(mut self, existing: &str, columns: &[&'static str]) -> Self {
+        let position = self.position(existing) + 1;
+        self.columns
+            .splice(position..position, columns.iter().copied());
+        self
+    }
+
+    /// Selects `column` in place of `existing`.
+    ///
+    /// # Panics
+    /// Panics if this version doesn't select `existing`
+    fn with_replaced(mut self, existing: &str, column: &'static str) -> Self {
+        let position = self.position(existing);
+        self.columns[position] = column;
+        self
+    }
+
+    fn position(&self, existing: &str) -> usize {
+        self.columns
+            .iter()
+            .position(|column| *column == existing)
+            .unwrap_or_else(|| panic!("accessions_with_metadata doesn't select {existing}"))
+    }
+
+    /// SQL that creates this version of the view.
+    pub fn create_statement(&self) -> String {
+        let selected = self
+            .columns
+            .iter()
+            .copied()
+            .chain([self.subject_columns])
+            .chain(self.trailing_columns.iter().copied())
+            .collect::<Vec<_>>()
+            .join(",\n    ");
+        format!(
+            "CREATE VIEW accessions_with_metadata AS
+SELECT
+    {selected}
+FROM accession a
+LEFT JOIN dublin_metadata_en dme ON a.dublin_metadata_en = dme.id
+LEFT JOIN dublin_metadata_ar dma ON a.dublin_metadata_ar = dma.id"
+        )
+    }
+
+    /// Creates this version of the view, which mustn't exist yet.
+    pub async fn create(&self, manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(&self.create_statement())
+            .await?;
+        Ok(())
+    }
+
+    /// Drops whichever version of the view exists, before changing the columns it selects.
+    pub async fn drop_existing(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("DROP VIEW IF EXISTS accessions_with_metadata;")
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latest_version_selects_each_column_once() {
+        let view = AccessionsView::ordered_subjects();
+        let mut columns = view.columns.clone();
+        columns.extend(&view.trailing_columns);
+        columns.sort_unstable();
+        columns.dedup();
+        assert_eq!(
+            columns.len(),
+            view.columns.len() + view.trailing_columns.len()
+        );
+        assert_eq!(view.columns[1], UNPUBLISHED_IS_PRIVATE);
+        assert!(view.columns.contains(&"a.revision"));
+        assert!(view.columns.contains(&"a.organization_id"));
+        assert!(view.columns.contains(&"a.doi"));
+        assert!(view.columns.contains(&"a.crawl_quality_reason"));
+        assert_eq!(view.subject_columns, ORDERED_SUBJECT_COLUMNS);
+    }
+}
@@ -1,4 +1,5 @@
 pub use sea_orm_migration::prelude::*;
+mod accessions_view;
 mod m20241224_163000_accessions;
 mod m20250212_014525_optional_metadata_description;
 mod m20250217_012314_subjects_more_like_tags;
@@ -10,6 +11,43 @@ mod m20251017_164508_add_s3_spaces_filename;
 mod m20251111_214709_add_api_keys;
 mod m20260105_012142_optional_browsertrix_fields_in_accessions;
 mod m20260111_121608_add_contributor_role;
+mod m20261016_090000_add_canonical_url;
+mod m20261016_100000_add_pdf_derivative;
+mod m20261016_110000_add_workflow_labels;
+mod m20261016_120000_add_upload_sessions;
+mod m20261016_130000_add_scan_status;
+mod m20261016_140000_add_media_formats;
+mod m20261016_150000_add_accession_derivatives;
+mod m20261016_160000_add_feature_flags;
+mod m20261016_170000_add_embargo_until;
+mod m20261016_180000_add_content_warning;
+mod m20261016_190000_add_accession_events;
+mod m20261016_200000_add_email_status;
+mod m20261016_210000_add_publication_state;
+mod m20261016_220000_add_audit_log;
+mod m20261016_223000_add_collections;
+mod m20261016_230000_add_warc_format;
+mod m20261017_000000_add_machine_translated;
+mod m20261017_010000_add_accession_revision;
+mod m20261017_020000_add_audit_log_impersonator;
+mod m20261017_030000_add_organizations;
+mod m20261017_040000_add_accession_relations;
+mod m20261017_050000_add_accession_doi;
+mod m20261017_060000_add_accession_provenance;
+mod m20261017_070000_add_provenance_timestamps;
+mod m20261017_080000_add_crawl_quality_reason;
+mod m20261017_090000_add_unaccent;
+mod m20261017_100000_order_view_subjects;
+mod m20261017_110000_add_social_metadata;
+mod m20261017_120000_add_accession_content_digest;
+mod m20261017_130000_add_crawl_blocklist;
+mod m20261017_140000_add_activity_digest;
+mod m20261017_150000_add_audit_log_actor_index;
+mod m20261017_160000_add_subject_pattern_indexes;
+mod m20261017_170000_add_subject_link_indexes;
+mod m20261017_180000_add_subject_provenance;
+mod m20261017_190000_add_comments;
+mod m20261017_200000_add_session_client;
 
 pub struct Migrator;
 
@@ -28,6 +66,43 @@ impl MigratorTrait for Migrator {
             Box::new(m20251111_214709_add_api_keys::Migration),
             Box::new(m20260105_012142_optional_browsertrix_fields_in_accessions::Migration),
             Box::new(m20260111_121608_add_contributor_role::Migration),
+            Box::new(m20261016_090000_add_canonical_url::Migration),
+            Box::new(m20261016_100000_add_pdf_derivative::Migration),
+            Box::new(m20261016_110000_add_workflow_labels::Migration),
+            Box::new(m20261016_120000_add_upload_sessions::Migration),
+            Box::new(m20261016_130000_add_scan_status::Migration),
+            Box::new(m20261016_140000_add_media_formats::Migration),
+            Box::new(m20261016_150000_add_accession_derivatives::Migration),
+            Box::new(m20261016_160000_add_feature_flags::Migration),
+            Box::new(m20261016_170000_add_embargo_until::Migration),
+            Box::new(m20261016_180000_add_content_warning::Migration),
+            Box::new(m20261016_190000_add_accession_events::Migration),
+            Box::new(m20261016_200000_add_email_status::Migration),
+            Box::new(m20261016_210000_add_publication_state::Migration),
+            Box::new(m20261016_220000_add_audit_log::Migration),
+            Box::new(m20261016_223000_add_collections::Migration),
+            Box::new(m20261016_230000_add_warc_format::Migration),
+            Box::new(m20261017_000000_add_machine_translated::Migration),
+            Box::new(m20261017_010000_add_accession_revision::Migration),
+            Box::new(m20261017_020000_add_audit_log_impersonator::Migration),
+            Box::new(m20261017_030000_add_organizations::Migration),
+            Box::new(m20261017_040000_add_accession_relations::Migration),
+            Box::new(m20261017_050000_add_accession_doi::Migration),
+            Box::new(m20261017_060000_add_accession_provenance::Migration),
+            Box::new(m20261017_070000_add_provenance_timestamps::Migration),
+            Box::new(m20261017_080000_add_crawl_quality_reason::Migration),
+            Box::new(m20261017_090000_add_unaccent::Migration),
+            Box::new(m20261017_100000_order_view_subjects::Migration),
+            Box::new(m20261017_110000_add_social_metadata::Migration),
+            Box::new(m20261017_120000_add_accession_content_digest::Migration),
+            Box::new(m20261017_130000_add_crawl_blocklist::Migration),
+            Box::new(m20261017_140000_add_activity_digest::Migration),
+            Box::new(m20261017_150000_add_audit_log_actor_index::Migration),
+            Box::new(m20261017_160000_add_subject_pattern_indexes::Migration),
+            Box::new(m20261017_170000_add_subject_link_indexes::Migration),
+            Box::new(m20261017_180000_add_subject_provenance::Migration),
+            Box::new(m20261017_190000_add_comments::Migration),
+            Box::new(m20261017_200000_add_session_client::Migration),
         ]
     }
 }
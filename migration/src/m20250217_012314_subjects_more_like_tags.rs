@@ -236,12 +236,16 @@ impl MigrationTrait for Migration {
         let db = manager.get_connection();
         db.execute_unprepared("DROP VIEW accessions_with_metadata")
             .await?;
+        // subjects were required before, existing metadata gets an empty one
         manager
             .alter_table(
                 Table::alter()
                     .table(DublinMetadataEn::Table)
                     .add_column_if_not_exists(
-                        ColumnDef::new(DublinMetadataEn::Subject).string().null(),
+                        ColumnDef::new(DublinMetadataEn::Subject)
+                            .string()
+                            .not_null()
+                            .default(""),
                     )
                     .to_owned(),
             )
@@ -252,11 +256,21 @@ impl MigrationTrait for Migration {
                 Table::alter()
                     .table(DublinMetadataAr::Table)
                     .add_column_if_not_exists(
-                        ColumnDef::new(DublinMetadataAr::Subject).string().null(),
+                        ColumnDef::new(DublinMetadataAr::Subject)
+                            .string()
+                            .not_null()
+                            .default(""),
                     )
                     .to_owned(),
             )
             .await?;
+        db.execute_unprepared(
+            r#"
+            ALTER TABLE dublin_metadata_en ALTER COLUMN subject DROP DEFAULT;
+            ALTER TABLE dublin_metadata_ar ALTER COLUMN subject DROP DEFAULT;
+            "#,
+        )
+        .await?;
 
         manager
             .drop_table(
@@ -137,6 +137,10 @@ impl MigrationTrait for Migration {
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         let db = manager.get_connection();
 
+        // The view selects the full-text columns, so it has to go before they do
+        db.execute_unprepared("DROP VIEW IF EXISTS accessions_with_metadata;")
+            .await?;
+
         // Drop the indexes first
         manager
             .drop_index(
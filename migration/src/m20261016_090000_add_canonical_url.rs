@@ -0,0 +1,73 @@
+use crate::accessions_view::AccessionsView;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .add_column(ColumnDef::new(Accession::CanonicalUrl).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing rows predate canonicalization so fall back to the seed url
+        db.execute_unprepared("UPDATE accession SET canonical_url = seed_url;")
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_accession_canonical_url")
+                    .table(Accession::Table)
+                    .col(Accession::CanonicalUrl)
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::canonical_url().create(manager).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_accession_canonical_url")
+                    .table(Accession::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .drop_column(Accession::CanonicalUrl)
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::s3_spaces_filename().create(manager).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    CanonicalUrl,
+}
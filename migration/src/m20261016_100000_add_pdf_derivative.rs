@@ -0,0 +1,48 @@
+use crate::accessions_view::AccessionsView;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .add_column(ColumnDef::new(Accession::PdfS3Filename).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::pdf_derivative().create(manager).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .drop_column(Accession::PdfS3Filename)
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::canonical_url().create(manager).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    PdfS3Filename,
+}
@@ -0,0 +1,110 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum WorkflowLabel {
+    Table,
+    Id,
+    Label,
+}
+
+#[derive(DeriveIden)]
+enum AccessionWorkflowLabel {
+    Table,
+    AccessionId,
+    LabelId,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WorkflowLabel::Table)
+                    .if_not_exists()
+                    .col(pk_auto(WorkflowLabel::Id))
+                    .col(string(WorkflowLabel::Label).unique_key())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessionWorkflowLabel::Table)
+                    .if_not_exists()
+                    .primary_key(
+                        Index::create()
+                            .name("link_accession_workflow_labels")
+                            .col(AccessionWorkflowLabel::AccessionId)
+                            .col(AccessionWorkflowLabel::LabelId),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionWorkflowLabel::AccessionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionWorkflowLabel::LabelId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_accession_workflow_label_accession_id")
+                            .from(
+                                AccessionWorkflowLabel::Table,
+                                AccessionWorkflowLabel::AccessionId,
+                            )
+                            .to(Accession::Table, Accession::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_accession_workflow_label_label_id")
+                            .from(
+                                AccessionWorkflowLabel::Table,
+                                AccessionWorkflowLabel::LabelId,
+                            )
+                            .to(WorkflowLabel::Table, WorkflowLabel::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_accession_workflow_label_label_id")
+                    .table(AccessionWorkflowLabel::Table)
+                    .col(AccessionWorkflowLabel::LabelId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AccessionWorkflowLabel::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(WorkflowLabel::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum UploadSession {
+    Table,
+    UploadId,
+    S3Key,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UploadSession::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UploadSession::UploadId)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(UploadSession::S3Key).string().not_null())
+                    .col(ColumnDef::new(UploadSession::CreatedBy).string().not_null())
+                    .col(
+                        ColumnDef::new(UploadSession::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UploadSession::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
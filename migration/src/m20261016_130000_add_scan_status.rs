@@ -0,0 +1,86 @@
+use crate::accessions_view::AccessionsView;
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ScanStatus::Enum)
+                    .values([
+                        ScanStatus::NotScanned,
+                        ScanStatus::Clean,
+                        ScanStatus::Infected,
+                        ScanStatus::Failed,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .add_column(
+                        ColumnDef::new(Accession::ScanStatus)
+                            .custom(ScanStatus::Enum)
+                            .not_null()
+                            .default("not_scanned"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::scan_status().create(manager).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .drop_column(Accession::ScanStatus)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(ScanStatus::Enum).to_owned())
+            .await?;
+
+        AccessionsView::pdf_derivative().create(manager).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ScanStatus {
+    #[sea_orm(iden = "scan_status")]
+    Enum,
+    #[sea_orm(iden = "not_scanned")]
+    NotScanned,
+    #[sea_orm(iden = "clean")]
+    Clean,
+    #[sea_orm(iden = "infected")]
+    Infected,
+    #[sea_orm(iden = "failed")]
+    Failed,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    ScanStatus,
+}
@@ -0,0 +1,97 @@
+use crate::accessions_view::AccessionsView;
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(DublinMetadataFormat::Enum)
+                    .add_value(DublinMetadataFormat::Jpeg)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(DublinMetadataFormat::Enum)
+                    .add_value(DublinMetadataFormat::Png)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(DublinMetadataFormat::Enum)
+                    .add_value(DublinMetadataFormat::Mp4)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .add_column(
+                        ColumnDef::new(Accession::MetadataScrubbed)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::media_formats().create(manager).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .drop_column(Accession::MetadataScrubbed)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Postgres can't drop values from an enum type without rebuilding it and every
+        // column using it, so the image and video formats are left in place
+
+        AccessionsView::scan_status().create(manager).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum DublinMetadataFormat {
+    #[sea_orm(iden = "dublin_metadata_format")]
+    Enum,
+    #[sea_orm(iden = "jpeg")]
+    Jpeg,
+    #[sea_orm(iden = "png")]
+    Png,
+    #[sea_orm(iden = "mp4")]
+    Mp4,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    MetadataScrubbed,
+}
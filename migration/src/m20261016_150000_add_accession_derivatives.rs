@@ -0,0 +1,119 @@
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum DerivativeKind {
+    #[sea_orm(iden = "derivative_kind")]
+    Enum,
+    #[sea_orm(iden = "web_video")]
+    WebVideo,
+    #[sea_orm(iden = "thumbnail")]
+    Thumbnail,
+}
+
+#[derive(DeriveIden)]
+enum AccessionDerivative {
+    Table,
+    Id,
+    AccessionId,
+    Kind,
+    S3Filename,
+    ContentType,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(DerivativeKind::Enum)
+                    .values([DerivativeKind::WebVideo, DerivativeKind::Thumbnail])
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessionDerivative::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessionDerivative::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionDerivative::AccessionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionDerivative::Kind)
+                            .custom(DerivativeKind::Enum)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionDerivative::S3Filename)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionDerivative::ContentType)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionDerivative::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_accession_derivative_accession_id")
+                            .from(AccessionDerivative::Table, AccessionDerivative::AccessionId)
+                            .to(Accession::Table, Accession::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // Each accession has at most one derivative of each kind; regenerating replaces it
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_accession_derivative_accession_id_kind")
+                    .table(AccessionDerivative::Table)
+                    .col(AccessionDerivative::AccessionId)
+                    .col(AccessionDerivative::Kind)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccessionDerivative::Table).to_owned())
+            .await?;
+        manager
+            .drop_type(Type::drop().name(DerivativeKind::Enum).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use crate::accessions_view::AccessionsView;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .add_column(ColumnDef::new(Accession::EmbargoUntil).timestamp().null())
+                    .to_owned(),
+            )
+            .await?;
+        db.execute_unprepared(
+            "CREATE INDEX idx_accession_embargo_until ON accession (embargo_until) WHERE embargo_until IS NOT NULL;",
+        )
+        .await?;
+
+        // Embargoed accessions count as private until the embargo lapses, even before the
+        // scheduled task gets round to flipping is_private
+        AccessionsView::embargo_until().create(manager).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .drop_column(Accession::EmbargoUntil)
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::media_formats().create(manager).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    EmbargoUntil,
+}
@@ -0,0 +1,85 @@
+use crate::accessions_view::AccessionsView;
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ContentWarning::Enum)
+                    .values([
+                        ContentWarning::GraphicViolence,
+                        ContentWarning::SexualViolence,
+                        ContentWarning::HumanRemains,
+                        ContentWarning::Distressing,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .add_column(
+                        ColumnDef::new(Accession::ContentWarning)
+                            .custom(ContentWarning::Enum)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::content_warning().create(manager).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .drop_column(Accession::ContentWarning)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(ContentWarning::Enum).to_owned())
+            .await?;
+
+        AccessionsView::embargo_until().create(manager).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ContentWarning {
+    #[sea_orm(iden = "content_warning")]
+    Enum,
+    #[sea_orm(iden = "graphic_violence")]
+    GraphicViolence,
+    #[sea_orm(iden = "sexual_violence")]
+    SexualViolence,
+    #[sea_orm(iden = "human_remains")]
+    HumanRemains,
+    #[sea_orm(iden = "distressing")]
+    Distressing,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    ContentWarning,
+}
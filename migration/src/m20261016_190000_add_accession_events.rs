@@ -0,0 +1,118 @@
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum AccessionEventKind {
+    #[sea_orm(iden = "accession_event_kind")]
+    Enum,
+    #[sea_orm(iden = "view")]
+    View,
+    #[sea_orm(iden = "wacz_url")]
+    WaczUrl,
+}
+
+#[derive(DeriveIden)]
+enum AccessionEvent {
+    Table,
+    Id,
+    AccessionId,
+    Kind,
+    Country,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(AccessionEventKind::Enum)
+                    .values([AccessionEventKind::View, AccessionEventKind::WaczUrl])
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessionEvent::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessionEvent::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionEvent::AccessionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionEvent::Kind)
+                            .custom(AccessionEventKind::Enum)
+                            .not_null(),
+                    )
+                    // ISO 3166 alpha-2 code, the only thing we keep about who asked
+                    .col(ColumnDef::new(AccessionEvent::Country).char_len(2).null())
+                    .col(
+                        ColumnDef::new(AccessionEvent::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_accession_event_accession_id")
+                            .from(AccessionEvent::Table, AccessionEvent::AccessionId)
+                            .to(Accession::Table, Accession::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_accession_event_accession_id_created_at")
+                    .table(AccessionEvent::Table)
+                    .col(AccessionEvent::AccessionId)
+                    .col(AccessionEvent::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_accession_event_kind_created_at")
+                    .table(AccessionEvent::Table)
+                    .col(AccessionEvent::Kind)
+                    .col(AccessionEvent::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccessionEvent::Table).to_owned())
+            .await?;
+        manager
+            .drop_type(Type::drop().name(AccessionEventKind::Enum).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(EmailStatus::Enum)
+                    .values([
+                        EmailStatus::Deliverable,
+                        EmailStatus::SoftBounce,
+                        EmailStatus::HardBounce,
+                        EmailStatus::SpamComplaint,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ArchiveUser::Table)
+                    .add_column(
+                        ColumnDef::new(ArchiveUser::EmailStatus)
+                            .custom(EmailStatus::Enum)
+                            .not_null()
+                            .default(Expr::cust("'deliverable'")),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ArchiveUser::Table)
+                    .drop_column(ArchiveUser::EmailStatus)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(EmailStatus::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum EmailStatus {
+    #[sea_orm(iden = "email_status")]
+    Enum,
+    #[sea_orm(iden = "deliverable")]
+    Deliverable,
+    #[sea_orm(iden = "soft_bounce")]
+    SoftBounce,
+    #[sea_orm(iden = "hard_bounce")]
+    HardBounce,
+    #[sea_orm(iden = "spam_complaint")]
+    SpamComplaint,
+}
+
+#[derive(DeriveIden)]
+enum ArchiveUser {
+    Table,
+    EmailStatus,
+}
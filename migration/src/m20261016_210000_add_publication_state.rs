@@ -0,0 +1,132 @@
+use crate::accessions_view::AccessionsView;
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(PublicationState::Enum)
+                    .values([
+                        PublicationState::Draft,
+                        PublicationState::InReview,
+                        PublicationState::Published,
+                        PublicationState::Withdrawn,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .add_column(
+                        ColumnDef::new(Accession::PublicationState)
+                            .custom(PublicationState::Enum)
+                            .not_null()
+                            .default(Expr::cust("'draft'")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // embargoes used to be stored in is_private too, those are published once lifted
+        db.execute_unprepared(
+            r#"
+            UPDATE accession
+            SET publication_state = 'published'
+            WHERE NOT is_private OR embargo_until IS NOT NULL
+            "#,
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .drop_column(Accession::IsPrivate)
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::publication_state().create(manager).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .add_column(
+                        ColumnDef::new(Accession::IsPrivate)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        db.execute_unprepared(
+            r#"
+            UPDATE accession
+            SET is_private = (publication_state <> 'published' OR embargo_until IS NOT NULL)
+            "#,
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .drop_column(Accession::PublicationState)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(PublicationState::Enum).to_owned())
+            .await?;
+
+        AccessionsView::content_warning().create(manager).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum PublicationState {
+    #[sea_orm(iden = "publication_state")]
+    Enum,
+    #[sea_orm(iden = "draft")]
+    Draft,
+    #[sea_orm(iden = "in_review")]
+    InReview,
+    #[sea_orm(iden = "published")]
+    Published,
+    #[sea_orm(iden = "withdrawn")]
+    Withdrawn,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    IsPrivate,
+    PublicationState,
+}
@@ -0,0 +1,120 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum Collection {
+    Table,
+    Id,
+    Title,
+    Description,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum CollectionAccession {
+    Table,
+    CollectionId,
+    AccessionId,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Collection::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Collection::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Collection::Title).text().not_null())
+                    .col(ColumnDef::new(Collection::Description).text().null())
+                    .col(ColumnDef::new(Collection::CreatedBy).text().not_null())
+                    .col(
+                        ColumnDef::new(Collection::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(CollectionAccession::Table)
+                    .if_not_exists()
+                    .primary_key(
+                        Index::create()
+                            .name("link_collection_accessions")
+                            .col(CollectionAccession::CollectionId)
+                            .col(CollectionAccession::AccessionId),
+                    )
+                    .col(
+                        ColumnDef::new(CollectionAccession::CollectionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(CollectionAccession::AccessionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_collection_accession_collection_id")
+                            .from(
+                                CollectionAccession::Table,
+                                CollectionAccession::CollectionId,
+                            )
+                            .to(Collection::Table, Collection::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_collection_accession_accession_id")
+                            .from(CollectionAccession::Table, CollectionAccession::AccessionId)
+                            .to(Accession::Table, Accession::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_collection_accession_accession_id")
+                    .table(CollectionAccession::Table)
+                    .col(CollectionAccession::AccessionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CollectionAccession::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Collection::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(DublinMetadataFormat::Enum)
+                    .add_value(DublinMetadataFormat::Warc)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // Postgres can't drop values from an enum type without rebuilding it and every
+        // column using it, so the warc format is left in place
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum DublinMetadataFormat {
+    #[sea_orm(iden = "dublin_metadata_format")]
+    Enum,
+    #[sea_orm(iden = "warc")]
+    Warc,
+}
@@ -0,0 +1,82 @@
+use crate::accessions_view::AccessionsView;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(DublinMetadataEn::Table)
+                    .add_column(
+                        ColumnDef::new(DublinMetadataEn::MachineTranslated)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(DublinMetadataAr::Table)
+                    .add_column(
+                        ColumnDef::new(DublinMetadataAr::MachineTranslated)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::machine_translated().create(manager).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(DublinMetadataEn::Table)
+                    .drop_column(DublinMetadataEn::MachineTranslated)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(DublinMetadataAr::Table)
+                    .drop_column(DublinMetadataAr::MachineTranslated)
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::publication_state().create(manager).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum DublinMetadataEn {
+    Table,
+    MachineTranslated,
+}
+
+#[derive(DeriveIden)]
+enum DublinMetadataAr {
+    Table,
+    MachineTranslated,
+}
@@ -0,0 +1,176 @@
+use crate::accessions_view::AccessionsView;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+/// Everything that existed before organizations belongs to the archive itself, which is
+/// the first organization and so gets this ID.
+const DEFAULT_ORGANIZATION_ID: i32 = 1;
+
+/// Tables whose rows belong to an organization.
+const SCOPED_TABLES: [&str; 5] = [
+    "archive_user",
+    "accession",
+    "dublin_metadata_subject_en",
+    "dublin_metadata_subject_ar",
+    "collection",
+];
+
+#[derive(DeriveIden)]
+enum Organization {
+    Table,
+    Id,
+    Slug,
+    Name,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+}
+
+/// The column each of [`SCOPED_TABLES`] gets.
+#[derive(DeriveIden)]
+enum Scoped {
+    OrganizationId,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Organization::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Organization::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Organization::Slug)
+                            .text()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Organization::Name).text().not_null())
+                    .col(
+                        ColumnDef::new(Organization::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(Organization::Table)
+                    .columns([Organization::Slug, Organization::Name])
+                    .values_panic([
+                        "sudan-digital-archive".into(),
+                        "Sudan Digital Archive".into(),
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        for table in SCOPED_TABLES {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .add_column(
+                            ColumnDef::new(Scoped::OrganizationId)
+                                .integer()
+                                .not_null()
+                                .default(DEFAULT_ORGANIZATION_ID),
+                        )
+                        .add_foreign_key(
+                            TableForeignKey::new()
+                                .name(format!("fk_{table}_organization"))
+                                .from_tbl(Alias::new(table))
+                                .from_col(Scoped::OrganizationId)
+                                .to_tbl(Organization::Table)
+                                .to_col(Organization::Id)
+                                .on_delete(ForeignKeyAction::Restrict),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_accession_organization_id")
+                    .table(Accession::Table)
+                    .col(Scoped::OrganizationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // each organization keeps its own vocabulary, so the same subject can exist in several
+        for table in ["dublin_metadata_subject_en", "dublin_metadata_subject_ar"] {
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    "ALTER TABLE {table} DROP CONSTRAINT {table}_subject_key;
+ALTER TABLE {table} ADD CONSTRAINT {table}_organization_id_subject_key UNIQUE (organization_id, subject);"
+                ))
+                .await?;
+        }
+
+        AccessionsView::organizations().create(manager).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        // subjects only one organization had would clash once the vocabularies are merged
+        for table in ["dublin_metadata_subject_en", "dublin_metadata_subject_ar"] {
+            manager
+                .get_connection()
+                .execute_unprepared(&format!(
+                    "ALTER TABLE {table} DROP CONSTRAINT {table}_organization_id_subject_key;
+ALTER TABLE {table} ADD CONSTRAINT {table}_subject_key UNIQUE (subject);"
+                ))
+                .await?;
+        }
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_accession_organization_id")
+                    .table(Accession::Table)
+                    .to_owned(),
+            )
+            .await?;
+        for table in SCOPED_TABLES {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .drop_foreign_key(Alias::new(format!("fk_{table}_organization")))
+                        .drop_column(Scoped::OrganizationId)
+                        .to_owned(),
+                )
+                .await?;
+        }
+        manager
+            .drop_table(Table::drop().table(Organization::Table).to_owned())
+            .await?;
+
+        AccessionsView::accession_revision().create(manager).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum AccessionRelationKind {
+    #[sea_orm(iden = "accession_relation_kind")]
+    Enum,
+    #[sea_orm(iden = "part_of")]
+    PartOf,
+    #[sea_orm(iden = "references")]
+    References,
+    #[sea_orm(iden = "supersedes")]
+    Supersedes,
+}
+
+#[derive(DeriveIden)]
+enum AccessionRelation {
+    Table,
+    Id,
+    FromAccessionId,
+    ToAccessionId,
+    Kind,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(AccessionRelationKind::Enum)
+                    .values([
+                        AccessionRelationKind::PartOf,
+                        AccessionRelationKind::References,
+                        AccessionRelationKind::Supersedes,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessionRelation::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessionRelation::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionRelation::FromAccessionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionRelation::ToAccessionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionRelation::Kind)
+                            .custom(AccessionRelationKind::Enum)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionRelation::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_accession_relation_from_accession_id")
+                            .from(AccessionRelation::Table, AccessionRelation::FromAccessionId)
+                            .to(Accession::Table, Accession::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_accession_relation_to_accession_id")
+                            .from(AccessionRelation::Table, AccessionRelation::ToAccessionId)
+                            .to(Accession::Table, Accession::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    // An accession can't be related to itself
+                    .check(
+                        Expr::col(AccessionRelation::FromAccessionId)
+                            .ne(Expr::col(AccessionRelation::ToAccessionId)),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_accession_relation_from_to_kind")
+                    .table(AccessionRelation::Table)
+                    .col(AccessionRelation::FromAccessionId)
+                    .col(AccessionRelation::ToAccessionId)
+                    .col(AccessionRelation::Kind)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        // Relations are looked up from both ends, the unique index covers the from end
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_accession_relation_to_accession_id")
+                    .table(AccessionRelation::Table)
+                    .col(AccessionRelation::ToAccessionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccessionRelation::Table).to_owned())
+            .await?;
+        manager
+            .drop_type(Type::drop().name(AccessionRelationKind::Enum).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
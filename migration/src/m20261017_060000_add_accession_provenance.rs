@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum AccessionProvenance {
+    Table,
+    AccessionId,
+    Sha256,
+    Signature,
+    SignerPublicKey,
+    RecordedAt,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessionProvenance::Table)
+                    .if_not_exists()
+                    // one file per accession, recorded when it's stored
+                    .col(
+                        ColumnDef::new(AccessionProvenance::AccessionId)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionProvenance::Sha256)
+                            .char_len(64)
+                            .not_null(),
+                    )
+                    // base64, both unset when no signing key was configured
+                    .col(ColumnDef::new(AccessionProvenance::Signature).text().null())
+                    .col(
+                        ColumnDef::new(AccessionProvenance::SignerPublicKey)
+                            .text()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionProvenance::RecordedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_accession_provenance_accession_id")
+                            .from(AccessionProvenance::Table, AccessionProvenance::AccessionId)
+                            .to(Accession::Table, Accession::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccessionProvenance::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum TimestampMethod {
+    #[sea_orm(iden = "timestamp_method")]
+    Enum,
+    #[sea_orm(iden = "open_timestamps")]
+    OpenTimestamps,
+    #[sea_orm(iden = "rfc3161")]
+    Rfc3161,
+}
+
+#[derive(DeriveIden)]
+enum AccessionProvenance {
+    Table,
+    TimestampMethod,
+    TimestampProof,
+    TimestampedAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(TimestampMethod::Enum)
+                    .values([TimestampMethod::OpenTimestamps, TimestampMethod::Rfc3161])
+                    .to_owned(),
+            )
+            .await?;
+        // all unset until the hash has been sent to a timestamping service
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AccessionProvenance::Table)
+                    .add_column(
+                        ColumnDef::new(AccessionProvenance::TimestampMethod)
+                            .custom(TimestampMethod::Enum)
+                            .null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(AccessionProvenance::TimestampProof)
+                            .binary()
+                            .null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(AccessionProvenance::TimestampedAt)
+                            .timestamp()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AccessionProvenance::Table)
+                    .drop_column(AccessionProvenance::TimestampMethod)
+                    .drop_column(AccessionProvenance::TimestampProof)
+                    .drop_column(AccessionProvenance::TimestampedAt)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_type(Type::drop().name(TimestampMethod::Enum).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use crate::accessions_view::AccessionsView;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        // Only set on crawls flagged as bad captures, cleared once a curator accepts them
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .add_column(ColumnDef::new(Accession::CrawlQualityReason).text().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::crawl_quality_reason()
+            .create(manager)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        AccessionsView::drop_existing(manager).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Accession::Table)
+                    .drop_column(Accession::CrawlQualityReason)
+                    .to_owned(),
+            )
+            .await?;
+
+        AccessionsView::accession_doi().create(manager).await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    CrawlQualityReason,
+}
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // unaccent() is only STABLE because its dictionary can be swapped out from under it.
+        // Pinning the dictionary makes the wrapper safe to mark IMMUTABLE, so it can be used
+        // when computing the full text columns.
+        db.execute_unprepared(
+            r#"
+            CREATE EXTENSION IF NOT EXISTS unaccent;
+
+            CREATE OR REPLACE FUNCTION f_unaccent(TEXT)
+            RETURNS TEXT AS $$
+                SELECT public.unaccent('public.unaccent', $1)
+            $$ LANGUAGE sql IMMUTABLE PARALLEL SAFE STRICT;
+
+            CREATE OR REPLACE FUNCTION get_dublin_metadata_en_text(metadata_id INT)
+            RETURNS TEXT AS $$
+            BEGIN
+                RETURN (
+                    SELECT f_unaccent(COALESCE(title, '') || ' ' || COALESCE(description, ''))
+                    FROM dublin_metadata_en
+                    WHERE id = metadata_id
+                );
+            END;
+            $$ LANGUAGE plpgsql STABLE;
+            "#,
+        )
+        .await?;
+
+        // Stored generated columns are only recomputed when their row is written
+        db.execute_unprepared(
+            r#"
+            UPDATE accession
+            SET dublin_metadata_en = dublin_metadata_en
+            WHERE dublin_metadata_en IS NOT NULL;
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"
+            CREATE OR REPLACE FUNCTION get_dublin_metadata_en_text(metadata_id INT)
+            RETURNS TEXT AS $$
+            BEGIN
+                RETURN (
+                    SELECT COALESCE(title, '') || ' ' || COALESCE(description, '')
+                    FROM dublin_metadata_en
+                    WHERE id = metadata_id
+                );
+            END;
+            $$ LANGUAGE plpgsql STABLE;
+
+            UPDATE accession
+            SET dublin_metadata_en = dublin_metadata_en
+            WHERE dublin_metadata_en IS NOT NULL;
+
+            DROP FUNCTION IF EXISTS f_unaccent(TEXT);
+            DROP EXTENSION IF EXISTS unaccent;
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
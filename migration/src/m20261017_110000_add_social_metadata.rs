@@ -0,0 +1,111 @@
+use crate::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum SocialPlatform {
+    #[sea_orm(iden = "social_platform")]
+    Enum,
+    #[sea_orm(iden = "facebook")]
+    Facebook,
+    #[sea_orm(iden = "telegram")]
+    Telegram,
+    #[sea_orm(iden = "x")]
+    X,
+}
+
+#[derive(DeriveIden)]
+enum SocialMetadata {
+    Table,
+    AccessionId,
+    Platform,
+    AuthorHandle,
+    AuthorName,
+    PostId,
+    PostedAt,
+    ExtractedAt,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(SocialPlatform::Enum)
+                    .values([
+                        SocialPlatform::Facebook,
+                        SocialPlatform::Telegram,
+                        SocialPlatform::X,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(SocialMetadata::Table)
+                    .if_not_exists()
+                    // only accessions crawled from a post have a row
+                    .col(
+                        ColumnDef::new(SocialMetadata::AccessionId)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SocialMetadata::Platform)
+                            .custom(SocialPlatform::Enum)
+                            .not_null(),
+                    )
+                    // whatever the URL and the platform's oEmbed endpoint gave away
+                    .col(ColumnDef::new(SocialMetadata::AuthorHandle).text().null())
+                    .col(ColumnDef::new(SocialMetadata::AuthorName).text().null())
+                    .col(ColumnDef::new(SocialMetadata::PostId).text().null())
+                    .col(ColumnDef::new(SocialMetadata::PostedAt).timestamp().null())
+                    .col(
+                        ColumnDef::new(SocialMetadata::ExtractedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_social_metadata_accession_id")
+                            .from(SocialMetadata::Table, SocialMetadata::AccessionId)
+                            .to(Accession::Table, Accession::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // filtering by handle ignores case, see `filter_builder`
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE INDEX idx_social_metadata_platform_author_handle \
+                 ON social_metadata (platform, lower(author_handle));",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SocialMetadata::Table).to_owned())
+            .await?;
+        manager
+            .drop_type(Type::drop().name(SocialPlatform::Enum).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum AccessionContentDigest {
+    Table,
+    AccessionId,
+    Digest,
+    ComputedAt,
+}
+
+#[derive(DeriveIden)]
+enum Accession {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessionContentDigest::Table)
+                    .if_not_exists()
+                    // only WACZ captures with a pages index get a row
+                    .col(
+                        ColumnDef::new(AccessionContentDigest::AccessionId)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionContentDigest::Digest)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AccessionContentDigest::ComputedAt)
+                            .timestamp()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_accession_content_digest_accession_id")
+                            .from(
+                                AccessionContentDigest::Table,
+                                AccessionContentDigest::AccessionId,
+                            )
+                            .to(Accession::Table, Accession::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_accession_content_digest_digest")
+                    .table(AccessionContentDigest::Table)
+                    .col(AccessionContentDigest::Digest)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(AccessionContentDigest::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ArchiveUser::Table)
+                    // users opt in to the weekly digest
+                    .add_column(
+                        ColumnDef::new(ArchiveUser::ActivityDigest)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column(
+                        ColumnDef::new(ArchiveUser::ActivityDigestSentAt)
+                            .timestamp()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ArchiveUser::Table)
+                    .drop_column(ArchiveUser::ActivityDigest)
+                    .drop_column(ArchiveUser::ActivityDigestSentAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ArchiveUser {
+    Table,
+    ActivityDigest,
+    ActivityDigestSentAt,
+}
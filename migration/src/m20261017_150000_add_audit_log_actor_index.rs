@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum AuditLog {
+    Table,
+    ActorEmail,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // admins search the audit log by who did something
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_log_actor_email")
+                    .table(AuditLog::Table)
+                    .col(AuditLog::ActorEmail)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_audit_log_actor_email")
+                    .table(AuditLog::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
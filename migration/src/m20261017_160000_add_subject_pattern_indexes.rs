@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Subject searches compare f_unaccent(lower(subject)) within an organization. Exact and
+        // prefix matches can use these indexes, text_pattern_ops lets LIKE 'term%' use them
+        // whatever the database collation is. Contains matches still scan.
+        db.execute_unprepared(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_dublin_metadata_subject_en_pattern
+            ON dublin_metadata_subject_en (organization_id, f_unaccent(lower(subject)) text_pattern_ops);
+
+            CREATE INDEX IF NOT EXISTS idx_dublin_metadata_subject_ar_pattern
+            ON dublin_metadata_subject_ar (organization_id, f_unaccent(lower(subject)) text_pattern_ops);
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            r#"
+            DROP INDEX IF EXISTS idx_dublin_metadata_subject_en_pattern;
+            DROP INDEX IF EXISTS idx_dublin_metadata_subject_ar_pattern;
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+enum DublinMetadataEnSubjects {
+    Table,
+    SubjectId,
+}
+
+#[derive(DeriveIden)]
+enum DublinMetadataArSubjects {
+    Table,
+    SubjectId,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // listing subjects by usage counts links per subject, which the primary keys can't
+        // serve since they lead with metadata_id
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_dublin_metadata_en_subjects_subject_id")
+                    .table(DublinMetadataEnSubjects::Table)
+                    .col(DublinMetadataEnSubjects::SubjectId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_dublin_metadata_ar_subjects_subject_id")
+                    .table(DublinMetadataArSubjects::Table)
+                    .col(DublinMetadataArSubjects::SubjectId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_dublin_metadata_ar_subjects_subject_id")
+                    .table(DublinMetadataArSubjects::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_dublin_metadata_en_subjects_subject_id")
+                    .table(DublinMetadataEnSubjects::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
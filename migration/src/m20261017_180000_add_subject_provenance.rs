@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [
+            DublinMetadataSubjectEn::Table.into_iden(),
+            DublinMetadataSubjectAr::Table.into_iden(),
+        ] {
+            // subjects written before this migration are dated to it and have no known creator
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(
+                            ColumnDef::new(SubjectProvenance::CreatedAt)
+                                .timestamp()
+                                .not_null()
+                                .default(Expr::current_timestamp()),
+                        )
+                        .add_column(
+                            ColumnDef::new(SubjectProvenance::UpdatedAt)
+                                .timestamp()
+                                .not_null()
+                                .default(Expr::current_timestamp()),
+                        )
+                        .add_column(ColumnDef::new(SubjectProvenance::CreatedBy).text().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [
+            DublinMetadataSubjectEn::Table.into_iden(),
+            DublinMetadataSubjectAr::Table.into_iden(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(SubjectProvenance::CreatedAt)
+                        .drop_column(SubjectProvenance::UpdatedAt)
+                        .drop_column(SubjectProvenance::CreatedBy)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum DublinMetadataSubjectEn {
+    Table,
+}
+
+#[derive(DeriveIden)]
+enum DublinMetadataSubjectAr {
+    Table,
+}
+
+#[derive(DeriveIden)]
+enum SubjectProvenance {
+    CreatedAt,
+    UpdatedAt,
+    CreatedBy,
+}
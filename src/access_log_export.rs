@@ -0,0 +1,409 @@
+//! Exports who accessed an accession and when, for legal and chain of custody requests.
+//!
+//! The log merges two sources, oldest first:
+//! * audit log entries about the accession, which name the signed in user behind each
+//!   private file access and every change made to it, such as replacing its file
+//! * usage events, which are anonymous and only keep a coarse country, see
+//!   [`crate::repos::accession_events_repo`]
+//!
+//! Exports are downloaded as a ZIP holding the log, as CSV or JSON, and a `manifest.json`
+//! describing it. The manifest has the log's SHA-256 and, when `PROVENANCE_SIGNING_KEY` is
+//! set, a signature over [`signed_message`] made with the key file provenance is signed
+//! with, so the export can be submitted as evidence and checked to be the one the archive
+//! handed out.
+
+use crate::audit_log_export::csv_field;
+use crate::provenance::{key_fingerprint, ProvenanceSigner, HASH_ALGORITHM, SIGNATURE_ALGORITHM};
+use ::entity::accession_event::Model as AccessionEventModel;
+use ::entity::audit_log::Model as AuditLogModel;
+use chrono::NaiveDateTime;
+use sea_orm::ActiveEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::io::{Cursor, Write};
+use utoipa::ToSchema;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Content type of exported access logs.
+pub const ACCESS_LOG_CONTENT_TYPE: &str = "application/zip";
+
+/// Name of the manifest in the ZIP.
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Identifies the manifest's layout and what its signature covers.
+const MANIFEST_VERSION: &str = "sudan-digital-archive-access-log-v1";
+
+/// Column names, in the order every row lists its fields.
+const HEADER: [&str; 7] = [
+    "recorded_at",
+    "source",
+    "event",
+    "actor_email",
+    "impersonator_email",
+    "country",
+    "details",
+];
+
+/// Formats the log in the ZIP can be exported in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessLogFormat {
+    /// One row per entry, for spreadsheets
+    #[default]
+    Csv,
+    /// An array of entries
+    Json,
+}
+
+impl AccessLogFormat {
+    /// Name of the log in the ZIP
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            AccessLogFormat::Csv => "access-log.csv",
+            AccessLogFormat::Json => "access-log.json",
+        }
+    }
+
+    /// Renders the entries in this format.
+    pub fn render(&self, entries: &[AccessLogEntry]) -> Vec<u8> {
+        match self {
+            AccessLogFormat::Csv => to_csv(entries).into_bytes(),
+            AccessLogFormat::Json => {
+                serde_json::to_vec_pretty(entries).expect("access log entries serialize")
+            }
+        }
+    }
+}
+
+/// Where an access log entry came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessLogSource {
+    AuditLog,
+    Usage,
+}
+
+impl AccessLogSource {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AccessLogSource::AuditLog => "audit_log",
+            AccessLogSource::Usage => "usage",
+        }
+    }
+}
+
+/// Something done with an accession, by a signed in user or an anonymous reader.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AccessLogEntry {
+    pub recorded_at: NaiveDateTime,
+    pub source: AccessLogSource,
+    /// e.g. `private_file_accessed`, or `view` for anonymous usage
+    pub event: String,
+    /// `None` for anonymous usage
+    pub actor_email: Option<String>,
+    pub impersonator_email: Option<String>,
+    /// Only kept for anonymous usage
+    pub country: Option<String>,
+    pub details: Option<Value>,
+}
+
+impl From<AuditLogModel> for AccessLogEntry {
+    fn from(entry: AuditLogModel) -> Self {
+        Self {
+            recorded_at: entry.created_at,
+            source: AccessLogSource::AuditLog,
+            event: entry.event,
+            actor_email: Some(entry.actor_email),
+            impersonator_email: entry.impersonator_email,
+            country: None,
+            details: entry.details,
+        }
+    }
+}
+
+impl From<AccessionEventModel> for AccessLogEntry {
+    fn from(event: AccessionEventModel) -> Self {
+        Self {
+            recorded_at: event.created_at,
+            source: AccessLogSource::Usage,
+            event: event.kind.to_value(),
+            actor_email: None,
+            impersonator_email: None,
+            country: event.country,
+            details: None,
+        }
+    }
+}
+
+/// Merges audit log entries and usage events into one log, oldest first.
+pub fn merge(
+    audit_log: Vec<AuditLogModel>,
+    usage: Vec<AccessionEventModel>,
+) -> Vec<AccessLogEntry> {
+    let mut entries: Vec<AccessLogEntry> = audit_log
+        .into_iter()
+        .map(AccessLogEntry::from)
+        .chain(usage.into_iter().map(AccessLogEntry::from))
+        .collect();
+    entries.sort_by_key(|entry| entry.recorded_at);
+    entries
+}
+
+/// Renders access log entries as CSV, with a header row, see [`crate::audit_log_export`].
+pub fn to_csv(entries: &[AccessLogEntry]) -> String {
+    let mut csv = HEADER.join(",");
+    csv.push_str("\r\n");
+    for entry in entries {
+        let fields = [
+            entry.recorded_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            entry.source.as_str().to_string(),
+            entry.event.clone(),
+            entry.actor_email.clone().unwrap_or_default(),
+            entry.impersonator_email.clone().unwrap_or_default(),
+            entry.country.clone().unwrap_or_default(),
+            entry
+                .details
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// What an export's signature covers, one field per line.
+pub fn signed_message(
+    accession_id: i32,
+    file_name: &str,
+    sha256: &str,
+    entries: usize,
+    generated_by: &str,
+    generated_at: NaiveDateTime,
+) -> String {
+    format!(
+        "{MANIFEST_VERSION}\naccession:{accession_id}\nfile:{file_name}\n{HASH_ALGORITHM}:{sha256}\nentries:{entries}\ngenerated_by:{generated_by}\ngenerated_at:{}",
+        generated_at.format("%Y-%m-%dT%H:%M:%SZ")
+    )
+}
+
+/// Describes an exported access log, so it can be checked later.
+///
+/// The signature can be checked by verifying `signed_message` against `signer_public_key`
+/// with Ed25519, and the log by comparing its SHA-256 with `sha256`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct AccessLogManifest {
+    /// Always `sudan-digital-archive-access-log-v1`
+    pub version: String,
+    pub accession_id: i32,
+    /// Name of the log in the ZIP
+    pub file_name: String,
+    /// How many entries the log holds
+    pub entries: usize,
+    /// Set when there were more entries than an export holds, and only the most recent
+    /// were kept
+    pub truncated: bool,
+    /// Email of the admin who exported the log
+    pub generated_by: String,
+    pub generated_at: NaiveDateTime,
+    /// Always `sha256`
+    pub hash_algorithm: String,
+    /// Hex hash of the log
+    pub sha256: String,
+    /// The exact text that was signed, see [`signed_message`]
+    pub signed_message: String,
+    /// Always `ed25519`, `None` when the export wasn't signed
+    pub signature_algorithm: Option<String>,
+    /// Base64 signature of `signed_message`
+    pub signature: Option<String>,
+    /// Base64 of the raw Ed25519 public key the signature was made with
+    pub signer_public_key: Option<String>,
+    /// Hex SHA-256 of the raw public key
+    pub signer_key_fingerprint: Option<String>,
+}
+
+impl AccessLogManifest {
+    /// Describes a rendered log, signing it when a signer is configured.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The accession the log is about
+    /// * `format` - The format the log was rendered in
+    /// * `log` - The rendered log
+    /// * `entries` - How many entries the log holds
+    /// * `truncated` - Whether entries were left out
+    /// * `generated_by` - Email of the admin exporting it
+    /// * `generated_at` - When it was exported
+    /// * `signer` - The archive's provenance signing key, if configured
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        accession_id: i32,
+        format: AccessLogFormat,
+        log: &[u8],
+        entries: usize,
+        truncated: bool,
+        generated_by: String,
+        generated_at: NaiveDateTime,
+        signer: Option<&ProvenanceSigner>,
+    ) -> Self {
+        let file_name = format.file_name();
+        let sha256 = format!("{:x}", Sha256::digest(log));
+        let message = signed_message(
+            accession_id,
+            file_name,
+            &sha256,
+            entries,
+            &generated_by,
+            generated_at,
+        );
+        let signer_public_key = signer.map(ProvenanceSigner::public_key);
+        Self {
+            version: MANIFEST_VERSION.to_string(),
+            accession_id,
+            file_name: file_name.to_string(),
+            entries,
+            truncated,
+            generated_by,
+            generated_at,
+            hash_algorithm: HASH_ALGORITHM.to_string(),
+            sha256,
+            signature_algorithm: signer.map(|_| SIGNATURE_ALGORITHM.to_string()),
+            signature: signer.map(|signer| signer.sign(&message)),
+            signer_key_fingerprint: signer_public_key.as_deref().and_then(key_fingerprint),
+            signer_public_key,
+            signed_message: message,
+        }
+    }
+}
+
+/// Packs a rendered log and its manifest into a ZIP.
+pub fn bundle(log: &[u8], manifest: &AccessLogManifest) -> Result<Vec<u8>, String> {
+    let manifest_json = serde_json::to_vec_pretty(manifest).map_err(|err| err.to_string())?;
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, contents) in [
+        (manifest.file_name.as_str(), log),
+        (MANIFEST_FILE_NAME, manifest_json.as_slice()),
+    ] {
+        writer
+            .start_file(name, options)
+            .map_err(|err| err.to_string())?;
+        writer.write_all(contents).map_err(|err| err.to_string())?;
+    }
+    let zip = writer.finish().map_err(|err| err.to_string())?;
+    Ok(zip.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provenance::verify;
+    use ::entity::sea_orm_active_enums::AccessionEventKind;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use std::io::Read;
+    use zip::ZipArchive;
+
+    /// RFC 8032 test 1
+    const SEED: &str = "nWGxne/9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A=";
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 16)
+            .unwrap()
+            .and_hms_opt(hour, 30, 0)
+            .unwrap()
+    }
+
+    fn entries() -> Vec<AccessLogEntry> {
+        let access = AuditLogModel {
+            id: 3,
+            actor_email: "researcher@example.com".to_string(),
+            impersonator_email: None,
+            event: "private_file_accessed".to_string(),
+            entity_type: "accession".to_string(),
+            entity_id: "7".to_string(),
+            details: Some(json!({"key": "some_file.wacz"})),
+            created_at: at(10),
+        };
+        let view = AccessionEventModel {
+            id: 1,
+            accession_id: 7,
+            kind: AccessionEventKind::View,
+            country: Some("SD".to_string()),
+            created_at: at(9),
+        };
+        merge(vec![access], vec![view])
+    }
+
+    #[test]
+    fn merges_sources_oldest_first() {
+        let entries = entries();
+        assert_eq!(entries[0].source, AccessLogSource::Usage);
+        assert_eq!(entries[0].event, "view");
+        assert_eq!(entries[1].source, AccessLogSource::AuditLog);
+        assert_eq!(
+            to_csv(&entries),
+            "recorded_at,source,event,actor_email,impersonator_email,country,details\r\n\
+             2026-10-16T09:30:00,usage,view,,,SD,\r\n\
+             2026-10-16T10:30:00,audit_log,private_file_accessed,researcher@example.com,,,\
+             \"{\"\"key\"\":\"\"some_file.wacz\"\"}\"\r\n"
+        );
+    }
+
+    #[test]
+    fn signs_and_bundles_exports() {
+        let signer = ProvenanceSigner::from_base64_seed(SEED).unwrap();
+        let entries = entries();
+        let log = AccessLogFormat::Csv.render(&entries);
+        let manifest = AccessLogManifest::new(
+            7,
+            AccessLogFormat::Csv,
+            &log,
+            entries.len(),
+            false,
+            "admin@example.com".to_string(),
+            at(11),
+            Some(&signer),
+        );
+        assert_eq!(manifest.sha256, format!("{:x}", Sha256::digest(&log)));
+        assert!(verify(
+            manifest.signer_public_key.as_deref().unwrap(),
+            &manifest.signed_message,
+            manifest.signature.as_deref().unwrap()
+        ));
+
+        let zip = bundle(&log, &manifest).unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(zip)).unwrap();
+        let mut bundled_log = Vec::new();
+        archive
+            .by_name("access-log.csv")
+            .unwrap()
+            .read_to_end(&mut bundled_log)
+            .unwrap();
+        assert_eq!(bundled_log, log);
+        let bundled_manifest: AccessLogManifest =
+            serde_json::from_reader(archive.by_name(MANIFEST_FILE_NAME).unwrap()).unwrap();
+        assert_eq!(bundled_manifest, manifest);
+    }
+
+    #[test]
+    fn leaves_exports_unsigned_without_a_key() {
+        let manifest = AccessLogManifest::new(
+            7,
+            AccessLogFormat::Json,
+            b"[]",
+            0,
+            false,
+            "admin@example.com".to_string(),
+            at(11),
+            None,
+        );
+        assert_eq!(manifest.file_name, "access-log.json");
+        assert_eq!(manifest.signature, None);
+        assert_eq!(manifest.signature_algorithm, None);
+    }
+}
@@ -0,0 +1,228 @@
+//! Weekly email summarizing a user's activity in the archive.
+//!
+//! Crawls run on background tasks, so the only word users get about them is the email
+//! sent when one succeeds. Users who opt in also get a digest each week with the crawls
+//! they requested that completed, the ones that failed or were held back as bad captures,
+//! and, for researchers, the accessions of their organization awaiting review. It is sent
+//! by [`crate::scheduled_tasks::ActivityDigestTask`].
+//!
+//! Crawl outcomes are read back from the audit log, where the crawl pipeline records them
+//! against the user who asked for the crawl as [`CRAWL_COMPLETED`] and [`CRAWL_FAILED`].
+
+use entity::audit_log::Model as AuditLogModel;
+use serde_json::Value;
+
+/// Audit log event recorded when a crawl a user requested is stored as an accession.
+pub const CRAWL_COMPLETED: &str = "crawl_completed";
+/// Audit log event recorded when a crawl a user requested fails.
+pub const CRAWL_FAILED: &str = "crawl_failed";
+
+/// Base URL of the archive's frontend, for links to accessions.
+const ARCHIVE_URL: &str = "https://sudandigitalarchive.com/archive";
+
+/// A crawl as the digest lists it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrawlOutcome {
+    /// The accession the crawl was stored as, if it got that far
+    pub accession_id: Option<i32>,
+    pub url: String,
+    /// Why the crawl needs attention, if it does
+    pub reason: Option<String>,
+}
+
+/// What a user's digest is about.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivityDigest {
+    pub completed: Vec<CrawlOutcome>,
+    /// Crawls that failed or were stored but held back as bad captures
+    pub needing_attention: Vec<CrawlOutcome>,
+    /// IDs of accessions awaiting review
+    pub awaiting_review: Vec<i32>,
+}
+
+impl ActivityDigest {
+    /// Builds a digest from a user's crawl activity in the audit log.
+    ///
+    /// # Arguments
+    /// * `entries` - The user's [`CRAWL_COMPLETED`] and [`CRAWL_FAILED`] audit log entries
+    /// * `awaiting_review` - Accessions for the user to review
+    pub fn new(entries: &[AuditLogModel], awaiting_review: Vec<i32>) -> Self {
+        let mut digest = ActivityDigest {
+            awaiting_review,
+            ..Default::default()
+        };
+        for entry in entries {
+            let detail = |key: &str| {
+                entry
+                    .details
+                    .as_ref()
+                    .and_then(|details| details.get(key))
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            };
+            let outcome = CrawlOutcome {
+                accession_id: (entry.entity_type == "accession")
+                    .then(|| entry.entity_id.parse().ok())
+                    .flatten(),
+                url: detail("url").unwrap_or_default(),
+                reason: detail("reason"),
+            };
+            match entry.event.as_str() {
+                CRAWL_COMPLETED if outcome.reason.is_none() => digest.completed.push(outcome),
+                CRAWL_COMPLETED | CRAWL_FAILED => digest.needing_attention.push(outcome),
+                _ => {}
+            }
+        }
+        digest
+    }
+
+    /// Whether there is nothing to tell the user about, so no email needs sending.
+    pub fn is_empty(&self) -> bool {
+        self.completed.is_empty()
+            && self.needing_attention.is_empty()
+            && self.awaiting_review.is_empty()
+    }
+
+    /// Subject line of the digest email.
+    pub fn subject(&self) -> String {
+        format!(
+            "Your week in the Sudan Digital Archive: {} crawls completed, {} need attention",
+            self.completed.len(),
+            self.needing_attention.len()
+        )
+    }
+
+    /// HTML body of the digest email.
+    pub fn html_body(&self) -> String {
+        let mut body = String::from("<h1>Your week in the Sudan Digital Archive</h1>");
+        body.push_str(&section(
+            "Crawls completed",
+            self.completed.iter().map(crawl_item),
+        ));
+        body.push_str(&section(
+            "Crawls needing attention",
+            self.needing_attention.iter().map(crawl_item),
+        ));
+        body.push_str(&section(
+            "Records awaiting review",
+            self.awaiting_review
+                .iter()
+                .map(|id| accession_link(*id, &format!("Accession {id}"))),
+        ));
+        body
+    }
+}
+
+/// Escapes text for HTML, since URLs and reasons come from users and crawled pages.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+pub fn accession_link(id: i32, text: &str) -> String {
+    format!("<a href='{ARCHIVE_URL}/{id}?isPrivate=true'>{text}</a>")
+}
+
+fn crawl_item(outcome: &CrawlOutcome) -> String {
+    let url = escape_html(&outcome.url);
+    let mut item = match outcome.accession_id {
+        Some(id) => accession_link(id, &url),
+        None => url,
+    };
+    if let Some(reason) = &outcome.reason {
+        item.push_str(&format!(": {}", escape_html(reason)));
+    }
+    item
+}
+
+/// A heading and a list of items, left out entirely when there are no items.
+fn section(heading: &str, items: impl Iterator<Item = String>) -> String {
+    let items: String = items.map(|item| format!("<li>{item}</li>")).collect();
+    if items.is_empty() {
+        return String::new();
+    }
+    format!("<h2>{heading}</h2><ul>{items}</ul>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn entry(event: &str, entity_type: &str, entity_id: &str, details: Value) -> AuditLogModel {
+        AuditLogModel {
+            id: 1,
+            actor_email: "researcher@example.com".to_string(),
+            impersonator_email: None,
+            event: event.to_string(),
+            entity_type: entity_type.to_string(),
+            entity_id: entity_id.to_string(),
+            details: Some(details),
+            created_at: Default::default(),
+        }
+    }
+
+    #[test]
+    fn sorts_crawls_by_outcome() {
+        let entries = [
+            entry(
+                CRAWL_COMPLETED,
+                "accession",
+                "7",
+                json!({"url": "https://example.com/story"}),
+            ),
+            entry(
+                CRAWL_COMPLETED,
+                "accession",
+                "8",
+                json!({"url": "https://example.com/login", "reason": "Looks like a login wall"}),
+            ),
+            entry(
+                CRAWL_FAILED,
+                "crawl",
+                "https://example.com/gone",
+                json!({"url": "https://example.com/gone", "reason": "Error occurred launching crawl"}),
+            ),
+        ];
+        let digest = ActivityDigest::new(&entries, vec![3]);
+        assert_eq!(
+            digest.completed,
+            vec![CrawlOutcome {
+                accession_id: Some(7),
+                url: "https://example.com/story".to_string(),
+                reason: None,
+            }]
+        );
+        assert_eq!(digest.needing_attention.len(), 2);
+        assert_eq!(digest.needing_attention[1].accession_id, None);
+        assert_eq!(
+            digest.subject(),
+            "Your week in the Sudan Digital Archive: 1 crawls completed, 2 need attention"
+        );
+        let body = digest.html_body();
+        assert!(body.contains("<a href='https://sudandigitalarchive.com/archive/7?isPrivate=true'>https://example.com/story</a>"));
+        assert!(body.contains("<li>https://example.com/gone: Error occurred launching crawl</li>"));
+        assert!(body.contains("Records awaiting review"));
+    }
+
+    #[test]
+    fn leaves_out_empty_sections_and_escapes() {
+        let entries = [entry(
+            CRAWL_FAILED,
+            "crawl",
+            "x",
+            json!({"url": "https://example.com/?q=<script>", "reason": "Timed out"}),
+        )];
+        let digest = ActivityDigest::new(&entries, vec![]);
+        let body = digest.html_body();
+        assert!(!body.contains("Crawls completed"));
+        assert!(!body.contains("awaiting review"));
+        assert!(body.contains("https://example.com/?q=&lt;script&gt;"));
+        assert!(ActivityDigest::new(&[], vec![]).is_empty());
+        assert!(!digest.is_empty());
+    }
+}
@@ -5,46 +5,164 @@
 //! - CORS configuration
 //! - Middleware stack (compression, timeout, tracing)
 //! - Route registration
+//! - API versioning
+//! - Per route class timeouts and body limits
 //!
 //! # Rate Limiting
 //! The application uses tower-governor for rate limiting with default configuration:
-//! - 32 requests per minute per IP address
+//! - 32 requests per minute per client
 //! - Regular cleanup of rate limiting storage every 60 seconds
 //!
 //! Note: Rate limiting is disabled in test mode.
+//!
+//! Signed in clients are told apart by who they are, the rest by their IP address as
+//! forwarded by the `TRUSTED_PROXY_HOPS` proxies in front of the API, or as much of it as
+//! `IP_PRIVACY` allows the API to keep, see [`crate::client_ip`]. The address is worked out
+//! once per request, before anything else runs, so the rate limiter, request logs and new
+//! sessions all see the same client.
+//!
+//! # Request logs
+//! Every request gets a structured JSON log line on top of the tracing spans, see
+//! [`crate::request_log`]. Which routes also have their bodies logged is set by
+//! `REQUEST_LOG_BODY_PATHS`.
+//!
+//! # Public routes
+//! The read-only routes under `/public/v1`, see [`crate::routes::public`], are for partner
+//! sites calling us straight from the browser. They allow any origin without credentials
+//! rather than our CORS allowlist, send a `Cache-Control` header so responses can be
+//! cached, and get a stricter rate limit of their own on top of the default one.
+//!
+//! The public statistics widget at `/api/v1/public/stats`, see [`crate::routes::stats`], is
+//! set up the same way but only has its own rate limit, so the counter on the public site
+//! doesn't use up its visitors' requests to the rest of the API.
+//!
+//! # Onion service
+//! When `ONION_URL` is set, clearnet responses point Tor Browser at the onion service and
+//! cookies set over it are made host only, see [`crate::onion`].
+//!
+//! # Versioning
+//! Routes are nested under a version prefix (`/api/v1`, `/api/v2`). Each version shares
+//! the same services in [`AppState`] but may use its own request and response models, so
+//! breaking changes to e.g. pagination or error formats can ship without affecting
+//! existing clients.
+//!
+//! Handlers often answer with plain text messages. Under `/api/v2`, and for clients
+//! sending `Accept: application/json`, these are sent as JSON instead, see
+//! [`crate::json_messages`].
+//!
+//! # Route classes
+//! Routes that stream uploaded files need bodies of hundreds of MB and minutes to arrive,
+//! while everything else is small JSON that should fail fast. Upload routes are registered
+//! separately so each class gets its own [`RouteLimits`] from [`AppConfig`].
+//!
+//! Bulk create and import routes take large JSON bodies, so they are a class of their own
+//! that also accepts gzip or deflate compressed bodies (`Content-Encoding: gzip`), which
+//! makes a big difference over slow connections. Their body limit is checked against the
+//! decompressed body, so a small compressed body can't be used to exhaust memory.
 
-use crate::config::AppConfig;
+use crate::client_ip::{resolve_client_ip, ClientKeyExtractor};
+use crate::clock::SharedClock;
+use crate::config::{AppConfig, RouteLimits};
+use crate::i18n::localize_messages;
+use crate::json_messages::wrap_plain_text;
+use crate::leader_election::SharedLeaderElection;
+use crate::onion::{serve_onion, OnionService};
 use crate::open_api_spec::ApiDoc;
-use crate::routes::accessions::get_accessions_routes;
+use crate::permissions::enforce_permissions;
+use crate::request_log::log_requests;
+use crate::routes::accession_relations::get_accession_relations_routes;
+use crate::routes::accessions::{
+    get_accession_bulk_routes, get_accession_upload_routes, get_accessions_routes,
+};
+use crate::routes::admin::get_admin_routes;
 use crate::routes::auth::get_auth_routes;
+use crate::routes::collections::get_collections_routes;
+use crate::routes::comments::get_comments_routes;
+use crate::routes::crawl_blocklist::get_crawl_blocklist_routes;
+use crate::routes::feature_flags::get_feature_flags_routes;
 use crate::routes::health::healthcheck;
+use crate::routes::memento::get_memento_routes;
+use crate::routes::organizations::get_organizations_routes;
+use crate::routes::public::get_public_routes;
+use crate::routes::stats::get_stats_routes;
 use crate::routes::subjects::get_subjects_routes;
+use crate::routes::uploads::{get_upload_part_routes, get_uploads_routes};
+use crate::routes::v2::accessions::get_accessions_routes as get_v2_accessions_routes;
+use crate::routes::webhooks::get_webhooks_routes;
+use crate::routes::workflow_labels::get_workflow_labels_routes;
+use crate::scheduler::SharedSchedulerMetrics;
 use crate::services::accessions_service::AccessionsService;
+use crate::services::audit_log_service::AuditLogService;
 use crate::services::auth_service::AuthService;
+use crate::services::collections_service::CollectionsService;
+use crate::services::crawl_blocklist_service::CrawlBlocklistService;
+use crate::services::flags_service::FlagsService;
+use crate::services::organizations_service::OrganizationsService;
+use crate::services::stats_service::StatsService;
 use crate::services::subjects_service::SubjectsService;
-use axum::extract::MatchedPath;
+use crate::services::uploads_service::UploadsService;
+use crate::services::workflow_labels_service::WorkflowLabelsService;
+use axum::body::Body;
+use axum::extract::{DefaultBodyLimit, MatchedPath};
 use axum::http::Request;
-use axum::response::Redirect;
+use axum::middleware::{from_fn, from_fn_with_state};
+use axum::response::{Redirect, Response};
 use axum::routing::get;
 use axum::Router;
-use http::header::CONTENT_TYPE;
+use http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use http::HeaderValue;
 use http::{Method, StatusCode};
 use std::sync::Arc;
 use std::time::Duration;
 use tower::ServiceBuilder;
-use tower_governor::{governor::GovernorConfig, GovernorLayer};
-use tower_http::cors::CorsLayer;
+use tower_governor::governor::GovernorConfigBuilder;
+use tower_governor::GovernorLayer;
+use tower_http::cors::{Any, CorsLayer};
+use tower_http::decompression::RequestDecompressionLayer;
+use tower_http::set_header::SetResponseHeaderLayer;
 use tower_http::{compression::CompressionLayer, timeout::TimeoutLayer, trace::TraceLayer};
-use tracing::info_span;
+use tracing::{info, info_span};
 use tracing_subscriber::util::SubscriberInitExt;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::{Config, SwaggerUi};
+
+/// How long browsers and CDNs may cache responses from the public routes.
+///
+/// Kept well under the hour that presigned file URLs in responses stay valid for.
+const PUBLIC_CACHE_CONTROL: &str = "public, max-age=300";
+
+/// Seconds it takes a client to regain one request to the public routes
+const PUBLIC_RATE_LIMIT_PERIOD_SECS: u64 = 2;
+
+/// Requests a client can make to the public routes in a burst
+const PUBLIC_RATE_LIMIT_BURST: u32 = 5;
+
+/// Seconds it takes a client to regain one request to the public statistics widget
+const STATS_RATE_LIMIT_PERIOD_SECS: u64 = 10;
+
+/// Requests a client can make to the public statistics widget in a burst
+const STATS_RATE_LIMIT_BURST: u32 = 3;
+
 /// Application state shared across routes
 #[derive(Clone)]
 pub struct AppState {
     pub accessions_service: AccessionsService,
     pub auth_service: AuthService,
     pub subjects_service: SubjectsService,
+    pub workflow_labels_service: WorkflowLabelsService,
+    pub collections_service: CollectionsService,
+    pub uploads_service: UploadsService,
+    pub flags_service: FlagsService,
+    pub crawl_blocklist_service: CrawlBlocklistService,
+    pub stats_service: StatsService,
+    pub audit_log_service: AuditLogService,
+    pub organizations_service: OrganizationsService,
+    pub scheduler_metrics: SharedSchedulerMetrics,
+    /// Whether this instance runs the background tasks, see [`crate::leader_election`]
+    pub leader_election: SharedLeaderElection,
+    /// Tells the time to extractors, such as checking how long a session has left, see
+    /// [`crate::clock`]
+    pub clock: SharedClock,
 }
 
 /// Creates and configures the main application router with middleware and routes.
@@ -68,43 +186,176 @@ pub fn create_app(app_state: AppState, app_config: AppConfig, test: bool) -> Rou
         subscriber.set_default();
     } else {
         subscriber.init();
+        info!("Effective config:\n{}", app_config.redacted_summary());
     }
-    let governor_conf = Arc::new(GovernorConfig::default());
+    let client_key_extractor = ClientKeyExtractor::new(
+        app_config.ip_privacy,
+        app_config.trusted_proxies.clone(),
+        app_state.auth_service.known_api_keys.clone(),
+    );
+    let governor_conf = Arc::new(
+        GovernorConfigBuilder::default()
+            .key_extractor(client_key_extractor.clone())
+            .finish()
+            .expect("Default rate limits should be non zero"),
+    );
+    let public_governor_conf = Arc::new(
+        GovernorConfigBuilder::default()
+            .key_extractor(client_key_extractor.clone())
+            .per_second(PUBLIC_RATE_LIMIT_PERIOD_SECS)
+            .burst_size(PUBLIC_RATE_LIMIT_BURST)
+            .finish()
+            .expect("Public rate limits should be non zero"),
+    );
+    let stats_governor_conf = Arc::new(
+        GovernorConfigBuilder::default()
+            .key_extractor(client_key_extractor.clone())
+            .per_second(STATS_RATE_LIMIT_PERIOD_SECS)
+            .burst_size(STATS_RATE_LIMIT_BURST)
+            .finish()
+            .expect("Stats rate limits should be non zero"),
+    );
     let governor_limiter = governor_conf.limiter().clone();
+    let public_governor_limiter = public_governor_conf.limiter().clone();
+    let stats_governor_limiter = stats_governor_conf.limiter().clone();
     std::thread::spawn(move || loop {
         std::thread::sleep(Duration::from_secs(60));
         tracing::info!("rate limiting storage size: {}", governor_limiter.len());
         governor_limiter.retain_recent();
+        public_governor_limiter.retain_recent();
+        stats_governor_limiter.retain_recent();
     });
     let cors = CorsLayer::new()
         .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::PUT])
         .allow_origin(app_config.cors_urls.clone())
         .allow_headers([CONTENT_TYPE])
         .allow_credentials(true);
-    let all_routes: Router<AppState> = build_routes(ApiDoc::openapi(), app_config);
-    let base_routes = all_routes.layer(cors);
+    let public_routes = build_public_routes(app_config.json_route_limits);
+    let stats_routes = build_stats_routes(app_config.json_route_limits);
     // rate limiting breaks tests *sigh* #security #pita
     if test {
-        base_routes.with_state(app_state)
+        build_routes(
+            app_state.clone(),
+            app_config,
+            client_key_extractor,
+            cors,
+            public_routes,
+            stats_routes,
+            |routes| routes,
+        )
+        .with_state(app_state)
     } else {
-        base_routes
-            .layer(GovernorLayer {
-                config: governor_conf,
-            })
-            .with_state(app_state)
+        let public_routes = public_routes.layer(GovernorLayer {
+            config: public_governor_conf,
+        });
+        let stats_routes = stats_routes.layer(GovernorLayer {
+            config: stats_governor_conf,
+        });
+        build_routes(
+            app_state.clone(),
+            app_config,
+            client_key_extractor,
+            cors,
+            public_routes,
+            stats_routes,
+            |routes| {
+                routes.layer(GovernorLayer {
+                    config: governor_conf,
+                })
+            },
+        )
+        .with_state(app_state)
     }
 }
 
+/// Builds the read-only routes partners embed, with their own CORS and caching headers.
+///
+/// Their rate limit is left to the caller since it can't be used in tests.
+fn build_public_routes(limits: RouteLimits) -> Router<AppState> {
+    with_public_headers(Router::new().nest("/public/v1", with_limits(get_public_routes(), limits)))
+}
+
+/// Builds the public statistics widget route, with the same headers as the public routes.
+///
+/// Its rate limit is left to the caller since it can't be used in tests.
+fn build_stats_routes(limits: RouteLimits) -> Router<AppState> {
+    with_public_headers(Router::new().nest("/api/v1", with_limits(get_stats_routes(), limits)))
+}
+
+/// Lets any origin call every route added to `routes` so far without credentials, and lets
+/// successful responses be cached unless they say otherwise.
+fn with_public_headers(routes: Router<AppState>) -> Router<AppState> {
+    routes
+        // errors shouldn't outlive whatever caused them
+        .layer(SetResponseHeaderLayer::if_not_present(
+            CACHE_CONTROL,
+            |response: &Response| {
+                response
+                    .status()
+                    .is_success()
+                    .then(|| HeaderValue::from_static(PUBLIC_CACHE_CONTROL))
+            },
+        ))
+        .layer(
+            CorsLayer::new()
+                .allow_methods([Method::GET])
+                .allow_origin(Any),
+        )
+}
+
+/// Applies a route class's timeout and body limit to every route added to `routes` so far.
+fn with_limits<S>(routes: Router<S>, limits: RouteLimits) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    routes
+        .layer(TimeoutLayer::with_status_code(
+            StatusCode::REQUEST_TIMEOUT,
+            limits.timeout,
+        ))
+        .layer(DefaultBodyLimit::max(limits.body_limit))
+}
+
+/// Lets every route added to `routes` so far take gzip or deflate compressed bodies.
+///
+/// Bodies with any other `Content-Encoding` are refused with a 415.
+fn with_request_decompression<S>(routes: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    routes.layer(
+        ServiceBuilder::new()
+            .layer(RequestDecompressionLayer::new().no_br().no_zstd())
+            .map_request(|request: Request<_>| request.map(Body::new)),
+    )
+}
+
 /// Builds the application routes with middleware stack.
 ///
 /// Configures:
 /// - Request tracing with method and path logging
-/// - 120 second timeout
+/// - Structured request logs, see [`crate::request_log`]
+/// - Timeouts and body limits per route class, see [`RouteLimits`]
 /// - Response compression
+/// - Arabic error and status messages, see [`crate::i18n`]
 /// - JSON content type validation
 /// - Health check endpoint
-/// - API routes
-fn build_routes(api: utoipa::openapi::OpenApi, app_config: AppConfig) -> Router<AppState> {
+/// - Role checks from the permission matrix, see [`crate::permissions`]
+/// - Versioned API routes, behind `cors`
+/// - The public routes, which bring their own CORS
+/// - `rate_limit` over all of the above
+/// - The public statistics widget, which brings its own CORS and rate limit
+/// - `Onion-Location` headers and onion cookies, see [`crate::onion`]
+/// - Where each request came from, by `client_key_extractor`, see [`crate::client_ip`]
+fn build_routes(
+    app_state: AppState,
+    app_config: AppConfig,
+    client_key_extractor: ClientKeyExtractor,
+    cors: CorsLayer,
+    public_routes: Router<AppState>,
+    stats_routes: Router<AppState>,
+    rate_limit: impl FnOnce(Router<AppState>) -> Router<AppState>,
+) -> Router<AppState> {
     let middleware = ServiceBuilder::new()
         .layer(
             TraceLayer::new_for_http().make_span_with(|request: &Request<_>| {
@@ -120,27 +371,52 @@ fn build_routes(api: utoipa::openapi::OpenApi, app_config: AppConfig) -> Router<
                 )
             }),
         )
-        .layer(TimeoutLayer::with_status_code(
-            StatusCode::REQUEST_TIMEOUT,
-            Duration::from_secs(120),
-        ))
         .layer(CompressionLayer::new());
-    let accessions_routes = get_accessions_routes(app_config.max_file_upload_size);
-    let subjects_routes = get_subjects_routes();
-    let auth_routes = get_auth_routes();
     let api_prefix = app_config.api_prefix.clone();
+    let request_log = Arc::new(app_config.request_log.clone());
+    // the URL was checked when the config was built
+    let onion = app_config
+        .onion_url
+        .as_deref()
+        .and_then(|url| OnionService::from_url(url).ok());
     let swagger_ui = SwaggerUi::new("/")
-        .url("/openapi.json", api.clone())
+        .url("/openapi.json", ApiDoc::openapi())
         .config(Config::from(format!(
             "{}/docs/openapi.json",
             app_config.api_prefix
         )));
 
-    let api_v1 = Router::new()
-        .merge(accessions_routes)
-        .merge(subjects_routes)
-        .merge(auth_routes);
-    Router::new()
+    let json_routes_v1 = Router::new()
+        .merge(get_accessions_routes())
+        .merge(get_subjects_routes())
+        .merge(get_workflow_labels_routes())
+        .merge(get_collections_routes())
+        .merge(get_accession_relations_routes())
+        .merge(get_comments_routes())
+        .merge(get_admin_routes())
+        .merge(get_organizations_routes())
+        .merge(get_feature_flags_routes())
+        .merge(get_crawl_blocklist_routes())
+        .merge(get_uploads_routes())
+        .merge(get_webhooks_routes())
+        .merge(get_memento_routes())
+        .merge(get_auth_routes());
+    let upload_routes_v1 = Router::new()
+        .merge(get_accession_upload_routes())
+        .merge(get_upload_part_routes());
+    let bulk_routes_v1 =
+        with_request_decompression(Router::new().merge(get_accession_bulk_routes()));
+    let api_v1 = with_limits(json_routes_v1, app_config.json_route_limits)
+        .merge(with_limits(bulk_routes_v1, app_config.bulk_route_limits))
+        .merge(with_limits(
+            upload_routes_v1,
+            app_config.upload_route_limits,
+        ));
+    let api_v2 = with_limits(
+        Router::new().merge(get_v2_accessions_routes()),
+        app_config.json_route_limits,
+    );
+    let routes = Router::new()
         .nest("/docs/", swagger_ui.into())
         .route(
             "/docs",
@@ -149,6 +425,95 @@ fn build_routes(api: utoipa::openapi::OpenApi, app_config: AppConfig) -> Router<
             get(move || async move { Redirect::to(&format!("{}/docs/", api_prefix)) }),
         )
         .nest("/api/v1", api_v1)
+        .nest("/api/v2", api_v2)
         .route("/health", get(healthcheck))
+        .layer(from_fn_with_state(app_state, enforce_permissions))
+        .layer(cors)
+        .merge(public_routes);
+    let routes = rate_limit(routes)
+        .merge(stats_routes)
+        .layer(from_fn(wrap_plain_text))
+        .layer(from_fn(localize_messages))
+        .layer(from_fn_with_state(request_log, log_requests));
+    let routes = match onion {
+        Some(onion) => routes.layer(from_fn_with_state(Arc::new(onion), serve_onion)),
+        None => routes,
+    };
+    routes
         .layer(middleware)
+        .layer(from_fn_with_state(client_key_extractor, resolve_client_ip))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::post;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use pretty_assertions::assert_eq;
+    use std::io::Write;
+    use tower::ServiceExt;
+
+    fn gzip(bytes: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(bytes).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// A bulk route that echoes how many bytes it received, with a 1KB body limit.
+    fn bulk_app() -> Router {
+        let routes = Router::new().route(
+            "/bulk",
+            post(|body: bytes::Bytes| async move { body.len().to_string() }),
+        );
+        with_limits(
+            with_request_decompression(routes),
+            RouteLimits {
+                timeout: Duration::from_secs(1),
+                body_limit: 1024,
+            },
+        )
+    }
+
+    fn bulk_request(content_encoding: &str, body: Vec<u8>) -> Request<Body> {
+        Request::builder()
+            .method(Method::POST)
+            .uri("/bulk")
+            .header(http::header::CONTENT_ENCODING, content_encoding)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn decompresses_gzip_bodies() {
+        let response = bulk_app()
+            .oneshot(bulk_request("gzip", gzip(&[b'a'; 1000])))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "1000");
+    }
+
+    #[tokio::test]
+    async fn limits_decompressed_size() {
+        let bomb = gzip(&[b'a'; 256 * 1024]);
+        assert!(bomb.len() < 1024);
+        let response = bulk_app()
+            .oneshot(bulk_request("gzip", bomb))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn refuses_unsupported_encodings() {
+        let response = bulk_app()
+            .oneshot(bulk_request("br", vec![1, 2, 3]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
 }
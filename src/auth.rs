@@ -1,34 +1,177 @@
+use crate::config::try_secret_var;
 use ::entity::sea_orm_active_enums::Role;
-use jsonwebtoken::{DecodingKey, EncodingKey};
+use jsonwebtoken::errors::{Error, ErrorKind};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, TokenData,
+    Validation,
+};
 use once_cell::sync::Lazy;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::env;
 use tracing::{error, info};
 
-pub struct JWTKeys {
-    pub encoding: EncodingKey,
-    pub decoding: DecodingKey,
+/// ID of the signing key when `JWT_KEY_ID` is unset
+const DEFAULT_KEY_ID: &str = "default";
+
+/// A secret tokens are signed or verified with, named by the `kid` in their headers.
+struct JWTKey {
+    id: String,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
 }
 
-impl JWTKeys {
-    fn new(secret: &[u8]) -> Self {
+impl JWTKey {
+    fn new(id: &str, secret: &[u8]) -> Self {
         Self {
+            id: id.to_string(),
             encoding: EncodingKey::from_secret(secret),
             decoding: DecodingKey::from_secret(secret),
         }
     }
 }
 
+/// The keys tokens are signed and verified with.
+///
+/// New tokens are signed with one key and name it in their `kid` header. To rotate it, give
+/// `JWT_SECRET` a new secret and `JWT_KEY_ID` a new ID, and move the old pair to
+/// `JWT_PREVIOUS_SECRETS`. Sessions signed with the old key keep working until they expire,
+/// then it can be dropped. A leaked key should be dropped straight away, which signs out
+/// only the sessions it signed.
+pub struct JWTKeys {
+    /// Algorithm every key signs and verifies with
+    pub algorithm: Algorithm,
+    signing: JWTKey,
+    /// Keys that are no longer signed with but whose tokens are still accepted
+    previous: Vec<JWTKey>,
+}
+
+impl JWTKeys {
+    /// Builds the keys from their environment variables' values.
+    ///
+    /// # Arguments
+    /// * `secret` - Secret new tokens are signed with, from `JWT_SECRET`
+    /// * `key_id` - ID of that secret, from `JWT_KEY_ID`
+    /// * `previous_secrets` - Comma separated `<key id>:<secret>` pairs still accepted, from
+    ///   `JWT_PREVIOUS_SECRETS`
+    /// * `algorithm` - One of HS256, HS384 or HS512, from `JWT_ALGORITHM`
+    fn from_vars(
+        secret: &str,
+        key_id: Option<&str>,
+        previous_secrets: Option<&str>,
+        algorithm: Option<&str>,
+    ) -> Result<Self, String> {
+        let algorithm = match algorithm.unwrap_or("HS256") {
+            "HS256" => Algorithm::HS256,
+            "HS384" => Algorithm::HS384,
+            "HS512" => Algorithm::HS512,
+            // the other algorithms need key pairs rather than secrets
+            other => return Err(format!("Unsupported JWT_ALGORITHM {other}")),
+        };
+        if secret.is_empty() {
+            return Err("JWT_SECRET is empty".to_string());
+        }
+        let signing = JWTKey::new(key_id.unwrap_or(DEFAULT_KEY_ID), secret.as_bytes());
+        let mut previous: Vec<JWTKey> = Vec::new();
+        for pair in previous_secrets
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let Some((id, secret)) = pair
+                .split_once(':')
+                .filter(|(id, secret)| !id.is_empty() && !secret.is_empty())
+            else {
+                return Err("JWT_PREVIOUS_SECRETS should be <key id>:<secret> pairs".to_string());
+            };
+            if id == signing.id || previous.iter().any(|key| key.id == id) {
+                return Err(format!("JWT key ID {id} is used more than once"));
+            }
+            previous.push(JWTKey::new(id, secret.as_bytes()));
+        }
+        Ok(Self {
+            algorithm,
+            signing,
+            previous,
+        })
+    }
+
+    /// Builds the keys from `JWT_SECRET` or `JWT_SECRET_FILE`, `JWT_KEY_ID`,
+    /// `JWT_PREVIOUS_SECRETS` or `JWT_PREVIOUS_SECRETS_FILE`, and `JWT_ALGORITHM`.
+    pub fn from_env() -> Result<Self, String> {
+        let secret = try_secret_var("JWT_SECRET")?
+            .ok_or_else(|| "JWT_SECRET or JWT_SECRET_FILE is missing".to_string())?;
+        let previous_secrets = try_secret_var("JWT_PREVIOUS_SECRETS")?;
+        Self::from_vars(
+            &secret,
+            env::var("JWT_KEY_ID").ok().as_deref(),
+            previous_secrets.as_deref(),
+            env::var("JWT_ALGORITHM").ok().as_deref(),
+        )
+    }
+
+    /// Signs claims with the current key.
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String, Error> {
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(self.signing.id.clone());
+        encode(&header, claims, &self.signing.encoding)
+    }
+
+    /// Verifies a token with the key named in its header, then checks its claims.
+    ///
+    /// Tokens signed before keys had IDs are tried against every key. Whatever algorithm
+    /// `validation` allows is replaced with the configured one, so a token can't pick its own.
+    pub fn decode<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        validation: &Validation,
+    ) -> Result<TokenData<T>, Error> {
+        let mut validation = validation.clone();
+        validation.algorithms = vec![self.algorithm];
+        let mut keys = std::iter::once(&self.signing).chain(&self.previous);
+        match decode_header(token)?.kid {
+            Some(kid) => {
+                let key = keys
+                    .find(|key| key.id == kid)
+                    .ok_or_else(|| Error::from(ErrorKind::InvalidToken))?;
+                decode(token, &key.decoding, &validation)
+            }
+            None => {
+                let mut result = Err(Error::from(ErrorKind::InvalidSignature));
+                for key in keys {
+                    result = decode(token, &key.decoding, &validation);
+                    let wrong_key = matches!(
+                        &result,
+                        Err(err) if matches!(err.kind(), ErrorKind::InvalidSignature)
+                    );
+                    if !wrong_key {
+                        break;
+                    }
+                }
+                result
+            }
+        }
+    }
+}
+
 pub static JWT_KEYS: Lazy<JWTKeys> = Lazy::new(|| {
     info!("Initializing JWT_KEYS...");
-    let secret = match env::var("JWT_SECRET") {
-        Ok(val) => val,
+    match JWTKeys::from_env() {
+        Ok(keys) => {
+            info!(
+                "Signing JWTs with key {} using {:?}, also accepting keys {:?}",
+                keys.signing.id,
+                keys.algorithm,
+                keys.previous.iter().map(|key| &key.id).collect::<Vec<_>>()
+            );
+            keys
+        }
         Err(e) => {
-            error!("Missing JWT_SECRET env var: {}", e);
-            panic!("Missing JWT_SECRET env var: {e}");
+            error!("Invalid JWT config: {}", e);
+            panic!("Invalid JWT config: {e}");
         }
-    };
-    let secret_bytes = secret.as_bytes();
-    JWTKeys::new(secret_bytes)
+    }
 });
 
 /// Validates that a user has at least researcher permissions.
@@ -44,8 +187,25 @@ pub fn validate_at_least_contributor(role: &Role) -> bool {
 }
 #[cfg(test)]
 mod tests {
-    use super::{validate_at_least_contributor, validate_at_least_researcher};
+    use super::{validate_at_least_contributor, validate_at_least_researcher, JWTKeys};
     use ::entity::sea_orm_active_enums::Role;
+    use jsonwebtoken::errors::ErrorKind;
+    use jsonwebtoken::{encode, Algorithm, EncodingKey, Header, Validation};
+    use pretty_assertions::assert_eq;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+    struct Claims {
+        sub: String,
+        exp: usize,
+    }
+
+    fn claims() -> Claims {
+        Claims {
+            sub: "someone@example.com".to_string(),
+            exp: chrono::Utc::now().timestamp() as usize + 60,
+        }
+    }
 
     #[test]
     fn test_validate_at_least_researcher() {
@@ -59,4 +219,73 @@ mod tests {
         assert_eq!(validate_at_least_contributor(&Role::Researcher), true);
         assert_eq!(validate_at_least_contributor(&Role::Contributor), true);
     }
+
+    #[test]
+    fn accepts_tokens_from_rotated_out_keys() {
+        let old = JWTKeys::from_vars("old-secret", Some("2024"), None, None).unwrap();
+        let token = old.encode(&claims()).unwrap();
+
+        let rotated =
+            JWTKeys::from_vars("new-secret", Some("2025"), Some("2024:old-secret"), None).unwrap();
+        let decoded = rotated
+            .decode::<Claims>(&token, &Validation::default())
+            .unwrap();
+        assert_eq!(decoded.claims, claims());
+        let token_now = rotated.encode(&claims()).unwrap();
+        assert_eq!(
+            jsonwebtoken::decode_header(&token_now).unwrap().kid,
+            Some("2025".to_string())
+        );
+
+        let dropped = JWTKeys::from_vars("new-secret", Some("2025"), None, None).unwrap();
+        let err = dropped
+            .decode::<Claims>(&token, &Validation::default())
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidToken));
+    }
+
+    #[test]
+    fn accepts_tokens_signed_before_keys_had_ids() {
+        let token = encode(
+            &Header::default(),
+            &claims(),
+            &EncodingKey::from_secret(b"old-secret"),
+        )
+        .unwrap();
+        let keys =
+            JWTKeys::from_vars("new-secret", Some("2025"), Some("2024:old-secret"), None).unwrap();
+        assert!(keys
+            .decode::<Claims>(&token, &Validation::default())
+            .is_ok());
+
+        let keys = JWTKeys::from_vars("new-secret", None, None, None).unwrap();
+        let err = keys
+            .decode::<Claims>(&token, &Validation::default())
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidSignature));
+    }
+
+    #[test]
+    fn only_accepts_the_configured_algorithm() {
+        let keys = JWTKeys::from_vars("secret", None, None, Some("HS512")).unwrap();
+        assert_eq!(keys.algorithm, Algorithm::HS512);
+        let token = keys.encode(&claims()).unwrap();
+        assert!(keys
+            .decode::<Claims>(&token, &Validation::default())
+            .is_ok());
+
+        let hs256 = JWTKeys::from_vars("secret", None, None, None).unwrap();
+        let err = hs256
+            .decode::<Claims>(&token, &Validation::default())
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidAlgorithm));
+    }
+
+    #[test]
+    fn rejects_bad_key_config() {
+        assert!(JWTKeys::from_vars("secret", None, None, Some("RS256")).is_err());
+        assert!(JWTKeys::from_vars("", None, None, None).is_err());
+        assert!(JWTKeys::from_vars("secret", None, Some("no-separator"), None).is_err());
+        assert!(JWTKeys::from_vars("secret", Some("a"), Some("a:other"), None).is_err());
+    }
 }
@@ -0,0 +1,167 @@
+//! Heuristics for spotting crawls that finished but didn't capture what was asked for.
+//!
+//! Browsertrix reports a crawl as complete even when the seed page was a login wall, a
+//! cookie banner or an error page, so every finished crawl is checked before it is
+//! published. Crawls that fail a check are kept as `CrawlStatus::BadCrawl` with the
+//! reasons, for a curator to accept or recrawl.
+
+use crate::url_canonicalizer::canonicalize_url;
+use crate::wacz::WaczPage;
+
+/// WACZ files smaller than this can't hold more than an empty page.
+const MIN_WACZ_SIZE: u64 = 16 * 1024;
+
+/// Lowercase fragments of the titles pages show instead of their content, e.g. while
+/// asking visitors to sign in, accept cookies or pass a bot check.
+const BLOCKED_TITLE_FRAGMENTS: [&str; 11] = [
+    "log in",
+    "login",
+    "sign in",
+    "sign up",
+    "cookie",
+    "consent",
+    "captcha",
+    "just a moment",
+    "access denied",
+    "attention required",
+    "are you a robot",
+];
+
+/// Checks a finished crawl for signs of a bad capture.
+///
+/// # Arguments
+/// * `wacz_size` - Size in bytes of the crawl's WACZ file
+/// * `pages` - The pages listed in the WACZ, or `None` if they couldn't be read
+/// * `seed_url` - The URL the crawl was started from
+///
+/// # Returns
+/// Why the capture looks bad, or `None` if it passed every check
+pub fn assess_capture(
+    wacz_size: u64,
+    pages: Option<&[WaczPage]>,
+    seed_url: &str,
+) -> Option<String> {
+    let mut reasons = vec![];
+    if wacz_size < MIN_WACZ_SIZE {
+        reasons.push(format!("WACZ file is only {wacz_size} bytes"));
+    }
+    match pages {
+        None => reasons.push("WACZ pages index could not be read".to_string()),
+        Some([]) => reasons.push("Crawl captured no pages".to_string()),
+        Some(pages) => {
+            let seed_page = seed_page(pages, seed_url);
+            if let Some(status) = seed_page.status.filter(|status| *status >= 400) {
+                reasons.push(format!("Seed URL returned HTTP {status}"));
+            }
+            match seed_page.title.as_deref().map(str::trim) {
+                None | Some("") => reasons.push("Seed page has no title".to_string()),
+                Some(title) if is_blocked_title(title) => reasons.push(format!(
+                    "Seed page looks like a login, consent or bot check page: {title}"
+                )),
+                Some(_) => {}
+            }
+        }
+    }
+    if reasons.is_empty() {
+        None
+    } else {
+        Some(reasons.join("; "))
+    }
+}
+
+/// The page captured from the seed URL, falling back to the first page since crawlers
+/// list the seed first but may have followed a redirect away from it.
+fn seed_page<'a>(pages: &'a [WaczPage], seed_url: &str) -> &'a WaczPage {
+    let seed_url = canonicalize_url(seed_url);
+    pages
+        .iter()
+        .find(|page| canonicalize_url(&page.url) == seed_url)
+        .unwrap_or(&pages[0])
+}
+
+fn is_blocked_title(title: &str) -> bool {
+    let title = title.to_lowercase();
+    BLOCKED_TITLE_FRAGMENTS
+        .iter()
+        .any(|fragment| title.contains(fragment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn page(url: &str, title: Option<&str>, status: Option<u16>) -> WaczPage {
+        WaczPage {
+            url: url.to_string(),
+            title: title.map(str::to_string),
+            ts: None,
+            status,
+        }
+    }
+
+    #[test]
+    fn passes_good_captures() {
+        let pages = [
+            page("https://example.com/", Some("Protest coverage"), Some(200)),
+            page("https://example.com/about", None, Some(404)),
+        ];
+        assert_eq!(
+            assess_capture(
+                1024 * 1024,
+                Some(&pages),
+                "https://example.com?utm_source=x"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn flags_tiny_and_unreadable_captures() {
+        assert_eq!(
+            assess_capture(100, None, "https://example.com"),
+            Some("WACZ file is only 100 bytes; WACZ pages index could not be read".to_string())
+        );
+        assert_eq!(
+            assess_capture(1024 * 1024, Some(&[]), "https://example.com"),
+            Some("Crawl captured no pages".to_string())
+        );
+    }
+
+    #[test]
+    fn flags_bad_seed_pages() {
+        let pages = [page(
+            "https://example.com/",
+            Some("Sign in to continue"),
+            Some(403),
+        )];
+        assert_eq!(
+            assess_capture(1024 * 1024, Some(&pages), "https://example.com"),
+            Some(
+                "Seed URL returned HTTP 403; Seed page looks like a login, consent or bot check page: Sign in to continue"
+                    .to_string()
+            )
+        );
+        let pages = [page("https://example.com/", Some("  "), None)];
+        assert_eq!(
+            assess_capture(1024 * 1024, Some(&pages), "https://example.com"),
+            Some("Seed page has no title".to_string())
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_first_page_as_the_seed() {
+        let pages = [
+            page(
+                "https://example.com/consent",
+                Some("Just a moment..."),
+                Some(200),
+            ),
+            page("https://example.com/news", Some("News"), Some(200)),
+        ];
+        assert!(
+            assess_capture(1024 * 1024, Some(&pages), "https://example.com/")
+                .is_some_and(|reason| reason.contains("Just a moment..."))
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! Exports accessions as references for reference managers such as Zotero.
+//!
+//! Two formats are supported: RIS, which nearly every reference manager imports, and
+//! CSL-JSON, which Zotero and citation processors read natively. Each accession becomes a
+//! web page reference to its page on the archive, issued when the original was published
+//! and accessed when it was captured, with the original URL in a note and its DOI if one
+//! was minted.
+//!
+//! The same metadata is registered with DataCite when a DOI is minted, see
+//! [`doi_metadata`].
+
+use crate::memento::memento_url;
+use crate::models::common::MetadataLanguage;
+use crate::repos::doi_repo::DoiMetadata;
+use ::entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
+use ::entity::sea_orm_active_enums::DublinMetadataFormat;
+use chrono::{Datelike, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Name of the archive, which reference managers show as where the source was found
+const ARCHIVE_NAME: &str = "Sudan Digital Archive";
+
+/// File formats references can be exported in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum CitationFormat {
+    /// Research Information Systems tagged format
+    Ris,
+    /// Citation Style Language JSON
+    CslJson,
+}
+
+impl CitationFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            CitationFormat::Ris => "application/x-research-info-systems",
+            CitationFormat::CslJson => "application/vnd.citationstyles.csl+json",
+        }
+    }
+
+    /// Name browsers save the export as
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            CitationFormat::Ris => "sudan-digital-archive.ris",
+            CitationFormat::CslJson => "sudan-digital-archive.json",
+        }
+    }
+}
+
+/// A date in CSL-JSON's `date-parts` form.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct CslDate {
+    #[serde(rename = "date-parts")]
+    pub date_parts: Vec<[i32; 3]>,
+}
+
+impl From<NaiveDateTime> for CslDate {
+    fn from(datetime: NaiveDateTime) -> Self {
+        Self {
+            date_parts: vec![[
+                datetime.year(),
+                datetime.month() as i32,
+                datetime.day() as i32,
+            ]],
+        }
+    }
+}
+
+/// One reference in CSL-JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct CslItem {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub item_type: &'static str,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#abstract: Option<String>,
+    /// Subjects, comma separated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyword: Option<String>,
+    pub language: String,
+    #[serde(rename = "URL")]
+    pub url: String,
+    #[serde(rename = "DOI", skip_serializing_if = "Option::is_none")]
+    pub doi: Option<String>,
+    pub archive: &'static str,
+    pub archive_location: String,
+    pub issued: CslDate,
+    pub accessed: CslDate,
+    pub note: String,
+}
+
+/// What a reference says about an accession, in one language.
+struct Reference<'a> {
+    id: i32,
+    title: &'a str,
+    description: Option<&'a str>,
+    subjects: &'a [String],
+    language: MetadataLanguage,
+    original_url: &'a str,
+    doi: Option<&'a str>,
+    published: NaiveDateTime,
+    captured: NaiveDateTime,
+}
+
+impl<'a> Reference<'a> {
+    /// Uses the accession's metadata in `lang`, or in the other language if it only has that.
+    fn new(accession: &'a AccessionWithMetadataModel, lang: MetadataLanguage) -> Self {
+        let language = match lang {
+            MetadataLanguage::English if !accession.has_english_metadata => {
+                MetadataLanguage::Arabic
+            }
+            MetadataLanguage::Arabic if !accession.has_arabic_metadata => MetadataLanguage::English,
+            lang => lang,
+        };
+        let (title, description, subjects) = match language {
+            MetadataLanguage::English => (
+                &accession.title_en,
+                &accession.description_en,
+                &accession.subjects_en,
+            ),
+            MetadataLanguage::Arabic => (
+                &accession.title_ar,
+                &accession.description_ar,
+                &accession.subjects_ar,
+            ),
+        };
+        Self {
+            id: accession.id,
+            title: title.as_deref().unwrap_or_default(),
+            description: description.as_deref(),
+            subjects: subjects.as_deref().unwrap_or_default(),
+            language,
+            original_url: accession
+                .canonical_url
+                .as_deref()
+                .unwrap_or(&accession.seed_url),
+            doi: accession.doi.as_deref(),
+            published: accession.dublin_metadata_date,
+            captured: accession.crawl_timestamp,
+        }
+    }
+
+    fn ris(&self) -> String {
+        let mut lines = vec![
+            ris_line("TY", "ELEC"),
+            ris_line("ID", &format!("sda-{}", self.id)),
+            ris_line("TI", self.title),
+        ];
+        if let Some(description) = self.description {
+            lines.push(ris_line("AB", description));
+        }
+        lines.extend(self.subjects.iter().map(|subject| ris_line("KW", subject)));
+        lines.extend([
+            ris_line("DA", &ris_date(self.published)),
+            ris_line("Y2", &ris_date(self.captured)),
+            ris_line("UR", &memento_url(self.id)),
+        ]);
+        if let Some(doi) = self.doi {
+            lines.push(ris_line("DO", doi));
+        }
+        lines.extend([
+            ris_line("DB", ARCHIVE_NAME),
+            ris_line("AN", &self.id.to_string()),
+            ris_line("LA", &self.language.to_string()),
+            ris_line("N1", &format!("Original URL: {}", self.original_url)),
+            "ER  - ".to_string(),
+        ]);
+        lines.join("\r\n") + "\r\n"
+    }
+
+    fn csl_json(&self) -> CslItem {
+        CslItem {
+            id: format!("sda-{}", self.id),
+            item_type: "webpage",
+            title: self.title.to_string(),
+            r#abstract: self.description.map(str::to_string),
+            keyword: (!self.subjects.is_empty()).then(|| self.subjects.join(", ")),
+            language: self.language.to_string(),
+            url: memento_url(self.id),
+            doi: self.doi.map(str::to_string),
+            archive: ARCHIVE_NAME,
+            archive_location: self.id.to_string(),
+            issued: self.published.into(),
+            accessed: self.captured.into(),
+            note: format!("Original URL: {}", self.original_url),
+        }
+    }
+}
+
+/// RIS fields are one line each, so line breaks in values are flattened to spaces.
+fn ris_line(tag: &str, value: &str) -> String {
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!("{tag}  - {value}")
+}
+
+fn ris_date(datetime: NaiveDateTime) -> String {
+    datetime.format("%Y/%m/%d/").to_string()
+}
+
+/// Renders accessions as RIS references.
+///
+/// # Arguments
+/// * `accessions` - The accessions to cite
+/// * `lang` - Language to cite accessions in, where they have metadata in it
+pub fn to_ris(accessions: &[AccessionWithMetadataModel], lang: MetadataLanguage) -> String {
+    accessions
+        .iter()
+        .map(|accession| Reference::new(accession, lang).ris())
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+/// Renders accessions as CSL-JSON references.
+///
+/// # Arguments
+/// * `accessions` - The accessions to cite
+/// * `lang` - Language to cite accessions in, where they have metadata in it
+pub fn to_csl_json(
+    accessions: &[AccessionWithMetadataModel],
+    lang: MetadataLanguage,
+) -> Vec<CslItem> {
+    accessions
+        .iter()
+        .map(|accession| Reference::new(accession, lang).csl_json())
+        .collect()
+}
+
+/// Describes an accession for DataCite, in every language it has metadata in.
+///
+/// # Arguments
+/// * `accession` - The accession the DOI will identify
+/// * `publication_year` - The year the DOI is minted, which is when the archive publishes it
+pub fn doi_metadata(accession: &AccessionWithMetadataModel, publication_year: i32) -> DoiMetadata {
+    let languages = [
+        (
+            "en",
+            accession.has_english_metadata,
+            &accession.title_en,
+            &accession.description_en,
+            &accession.subjects_en,
+        ),
+        (
+            "ar",
+            accession.has_arabic_metadata,
+            &accession.title_ar,
+            &accession.description_ar,
+            &accession.subjects_ar,
+        ),
+    ];
+    let mut metadata = DoiMetadata {
+        titles: vec![],
+        descriptions: vec![],
+        subjects: vec![],
+        publisher: ARCHIVE_NAME,
+        publication_year,
+        resource_type: match accession.dublin_metadata_format {
+            DublinMetadataFormat::Jpeg | DublinMetadataFormat::Png => "Image",
+            DublinMetadataFormat::Mp4 => "Audiovisual",
+            DublinMetadataFormat::Wacz | DublinMetadataFormat::Warc => "InteractiveResource",
+        },
+        url: memento_url(accession.id),
+    };
+    for (lang, has_metadata, title, description, subjects) in languages {
+        if !has_metadata {
+            continue;
+        }
+        if let Some(title) = title {
+            metadata.titles.push((title.clone(), lang));
+        }
+        if let Some(description) = description {
+            metadata.descriptions.push((description.clone(), lang));
+        }
+        metadata.subjects.extend(subjects.iter().flatten().cloned());
+    }
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::mock_one_accession_with_metadata;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn accession() -> AccessionWithMetadataModel {
+        AccessionWithMetadataModel {
+            dublin_metadata_date: NaiveDate::from_ymd_opt(2023, 4, 15)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+            crawl_timestamp: NaiveDate::from_ymd_opt(2024, 5, 2)
+                .unwrap()
+                .and_hms_opt(9, 30, 0)
+                .unwrap(),
+            description_en: Some("Fire at the\nmarket".to_string()),
+            ..mock_one_accession_with_metadata()
+        }
+    }
+
+    #[test]
+    fn renders_ris() {
+        let expected = [
+            "TY  - ELEC",
+            "ID  - sda-1",
+            "TI  - English Title",
+            "AB  - Fire at the market",
+            "KW  - archive",
+            "DA  - 2023/04/15/",
+            "Y2  - 2024/05/02/",
+            "UR  - https://sudandigitalarchive.com/archive/1",
+            "DO  - 10.1234/abcd-efgh",
+            "DB  - Sudan Digital Archive",
+            "AN  - 1",
+            "LA  - en",
+            "N1  - Original URL: https://example.com/",
+            "ER  - ",
+            "",
+        ]
+        .join("\r\n");
+        let accession = AccessionWithMetadataModel {
+            doi: Some("10.1234/abcd-efgh".to_string()),
+            ..accession()
+        };
+        assert_eq!(to_ris(&[accession], MetadataLanguage::English), expected);
+    }
+
+    #[test]
+    fn renders_csl_json() {
+        let items = to_csl_json(&[accession()], MetadataLanguage::Arabic);
+        assert_eq!(
+            serde_json::to_value(items).unwrap(),
+            json!([{
+                "id": "sda-1",
+                "type": "webpage",
+                "title": "Arabic Title",
+                "abstract": "Arabic Description",
+                "keyword": "mrhaba archive",
+                "language": "ar",
+                "URL": "https://sudandigitalarchive.com/archive/1",
+                "archive": "Sudan Digital Archive",
+                "archive_location": "1",
+                "issued": {"date-parts": [[2023, 4, 15]]},
+                "accessed": {"date-parts": [[2024, 5, 2]]},
+                "note": "Original URL: https://example.com/"
+            }])
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_language_an_accession_has() {
+        let accession = AccessionWithMetadataModel {
+            has_english_metadata: false,
+            title_en: None,
+            description_en: None,
+            subjects_en: None,
+            ..accession()
+        };
+        let items = to_csl_json(&[accession], MetadataLanguage::English);
+        assert_eq!(items[0].title, "Arabic Title");
+        assert_eq!(items[0].language, "ar");
+    }
+
+    #[test]
+    fn describes_accessions_for_datacite_in_both_languages() {
+        let metadata = doi_metadata(&accession(), 2026);
+        assert_eq!(
+            metadata,
+            DoiMetadata {
+                titles: vec![
+                    ("English Title".to_string(), "en"),
+                    ("Arabic Title".to_string(), "ar")
+                ],
+                descriptions: vec![
+                    ("Fire at the\nmarket".to_string(), "en"),
+                    ("Arabic Description".to_string(), "ar")
+                ],
+                subjects: vec!["archive".to_string(), "mrhaba archive".to_string()],
+                publisher: "Sudan Digital Archive",
+                publication_year: 2026,
+                resource_type: "InteractiveResource",
+                url: "https://sudandigitalarchive.com/archive/1".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Coarse location of whoever sent a request, for usage analytics.
+//!
+//! We never look at IP addresses ourselves. Instead we trust the country code the CDN in
+//! front of the API adds to each request, which is as much as we want to know about
+//! readers. Cloudflare sets [`COUNTRY_HEADER`] out of the box; other proxies can be
+//! configured to send the same header.
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use std::convert::Infallible;
+
+/// Header carrying the ISO 3166 alpha-2 country code of the client.
+pub const COUNTRY_HEADER: &str = "CF-IPCountry";
+
+/// Country the request came from, None if the header was missing or not a country.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCountry(pub Option<String>);
+
+/// Normalises a country code header value.
+///
+/// Cloudflare uses `XX` for unknown countries and `T1` for Tor, neither of which is a
+/// country so both are dropped along with anything that isn't two letters.
+fn parse_country(value: &str) -> Option<String> {
+    let value = value.trim().to_ascii_uppercase();
+    let is_code = value.len() == 2 && value.chars().all(|c| c.is_ascii_uppercase());
+    (is_code && value != "XX").then_some(value)
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ClientCountry {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let country = parts
+            .headers
+            .get(COUNTRY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_country);
+        Ok(ClientCountry(country))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn keeps_only_country_codes() {
+        assert_eq!(parse_country("sd"), Some("SD".to_string()));
+        assert_eq!(parse_country(" GB "), Some("GB".to_string()));
+        assert_eq!(parse_country("XX"), None);
+        assert_eq!(parse_country("T1"), None);
+        assert_eq!(parse_country("SDN"), None);
+        assert_eq!(parse_country(""), None);
+    }
+}
@@ -0,0 +1,598 @@
+//! Keeping readers' IP addresses out of what the API holds on to.
+//!
+//! The rate limiter has to tell clients apart and keys its buckets by their address, which
+//! it keeps in memory for as long as they're recent, and the request log and sessions note
+//! where requests came from. Connection metadata of archive users can itself be sensitive,
+//! so `IP_PRIVACY` sets what clients are known by instead, see [`IpPrivacy`]. Anything else
+//! that needs to tell clients apart should go through [`ClientKeyExtractor`] too.
+//!
+//! Behind Cloudflare and the load balancer every request comes from one of a few proxy
+//! addresses. [`resolve_client_ip`] works out the address each request really came from,
+//! see [`TrustedProxies`], and adds it to the request as [`ClientIp`] so the rate limiter,
+//! the request log and new sessions all agree on it. The rate limiter keys signed in users
+//! by who they are rather than where they connect from.
+
+use crate::auth::JWT_KEYS;
+use crate::models::auth::JWTClaims;
+use crate::request_log::REDACTED;
+use axum::extract::{ConnectInfo, Request as AxumRequest, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use axum_extra::extract::CookieJar;
+use http::{HeaderMap, Request};
+use ipnet::IpNet;
+use jsonwebtoken::Validation;
+use lru::LruCache;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tower_governor::key_extractor::KeyExtractor;
+use tower_governor::GovernorError;
+
+/// Header each proxy in front of the API appends the address it got a request from to.
+const FORWARDED_FOR_HEADER: &str = "X-Forwarded-For";
+
+/// Most API keys [`KnownApiKeys`] remembers the owners of.
+const KNOWN_API_KEYS_CAPACITY: usize = 10_000;
+
+/// How much of a client's IP address the API keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IpPrivacy {
+    /// Clients are keyed by their full address
+    #[default]
+    Off,
+    /// Clients are keyed by their /24 IPv4 or /48 IPv6 network, so neighbours on the same
+    /// network share a rate limit
+    Truncate,
+    /// Clients are keyed by a salted hash of their address. The salt is random and never
+    /// leaves memory, so it changes on every restart and the hashes can't be reversed by
+    /// hashing every IPv4 address.
+    Hash,
+}
+
+impl FromStr for IpPrivacy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(IpPrivacy::Off),
+            "truncate" => Ok(IpPrivacy::Truncate),
+            "hash" => Ok(IpPrivacy::Hash),
+            other => Err(format!("Unknown IP privacy mode: {other}")),
+        }
+    }
+}
+
+/// Networks proxies in front of the API connect from, as a comma separated list of
+/// addresses and CIDR networks, e.g. `10.110.0.0/20, 10.120.0.5`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxyNetworks(pub Vec<IpNet>);
+
+impl FromStr for ProxyNetworks {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|network| !network.is_empty())
+            .map(|network| {
+                network
+                    .parse::<IpNet>()
+                    .or_else(|_| network.parse::<IpAddr>().map(IpNet::from))
+                    .map_err(|_| format!("Not an address or network: {network}"))
+            })
+            .collect::<Result<_, _>>()
+            .map(ProxyNetworks)
+    }
+}
+
+/// The proxies in front of the API, whose `X-Forwarded-For` entries can be trusted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustedProxies {
+    /// How many proxies append to `X-Forwarded-For`, 0 when clients connect directly
+    pub hops: usize,
+    /// Networks the nearest proxy connects from, which must be set when `hops` is above 0
+    pub networks: ProxyNetworks,
+}
+
+impl TrustedProxies {
+    /// Refuses proxy hops without the networks the proxies connect from, which would let
+    /// any client connecting directly pick its own address with `X-Forwarded-For`.
+    pub fn check(&self) -> Result<(), String> {
+        if self.hops > 0 && self.networks.0.is_empty() {
+            return Err(format!(
+                "TRUSTED_PROXY_HOPS is {} but TRUSTED_PROXIES is empty, it should list the \
+                 networks the nearest proxy connects from",
+                self.hops
+            ));
+        }
+        Ok(())
+    }
+
+    /// The address a request came from.
+    ///
+    /// Each of the `hops` proxies in front of the API appends the address it got the request
+    /// from to `X-Forwarded-For`, so the client's address is that many entries from the end.
+    /// Entries before it were sent by the client and can't be trusted. A request that didn't
+    /// connect from one of `networks` or didn't pass through every proxy is put down to the
+    /// address it connected from.
+    ///
+    /// # Arguments
+    /// * `headers` - The request's headers
+    /// * `peer` - The address the request connected from
+    pub fn client_ip(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> Option<IpAddr> {
+        if self.hops == 0 {
+            return peer;
+        }
+        // a request straight to the API can put anything in its headers
+        let from_proxy = peer.is_some_and(|peer| {
+            self.networks
+                .0
+                .iter()
+                .any(|network| network.contains(&peer))
+        });
+        if !from_proxy {
+            return peer;
+        }
+        let forwarded: Vec<&str> = headers
+            .get_all(FORWARDED_FOR_HEADER)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect();
+        forwarded
+            .len()
+            .checked_sub(self.hops)
+            .and_then(|client| forwarded[client].parse().ok())
+            .or(peer)
+    }
+}
+
+/// Where a request came from, added to every request by [`resolve_client_ip`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp {
+    /// The address the request really came from, which mustn't be logged or stored
+    pub ip: IpAddr,
+    /// What the client is known by instead, as [`IpPrivacy`] allows
+    pub key: ClientKey,
+}
+
+/// What a client is known by, in place of their IP address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    Ip(IpAddr),
+    /// Start of the salted SHA-256 hash of the address
+    Hashed([u8; 16]),
+    /// Start of the salted SHA-256 hash of a signed in user's email
+    User([u8; 16]),
+}
+
+/// The address, or the hash in hex, as logs and sessions record it.
+impl fmt::Display for ClientKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientKey::Ip(ip) => write!(f, "{ip}"),
+            ClientKey::Hashed(hashed) | ClientKey::User(hashed) => {
+                hashed.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+            }
+        }
+    }
+}
+
+/// API keys that have authenticated a request, along with the email of their owner, so the
+/// rate limiter can tell whose a key is without a database lookup.
+///
+/// Only keys [`crate::services::auth_service::AuthService::verify_api_key`] accepted are
+/// remembered, so made up keys can't get a client fresh rate limits. Keys are held as their
+/// SHA-256 digest. At most [`KNOWN_API_KEYS_CAPACITY`] keys are remembered, the least
+/// recently used are forgotten first and go back to being limited by address until they
+/// authenticate again. A revoked key can be remembered, but fails authentication.
+#[derive(Clone)]
+pub struct KnownApiKeys {
+    owners: Arc<Mutex<LruCache<[u8; 32], String>>>,
+}
+
+impl Default for KnownApiKeys {
+    fn default() -> Self {
+        Self::with_capacity(KNOWN_API_KEYS_CAPACITY)
+    }
+}
+
+impl KnownApiKeys {
+    fn with_capacity(capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).expect("Capacity must be non zero");
+        Self {
+            owners: Arc::new(Mutex::new(LruCache::new(capacity))),
+        }
+    }
+
+    /// Remembers that `api_key` belongs to the user with `email`.
+    pub fn remember(&self, api_key: &str, email: &str) {
+        self.owners
+            .lock()
+            .unwrap()
+            .put(Sha256::digest(api_key).into(), email.to_string());
+    }
+
+    /// Email of the user `api_key` belongs to, if it has authenticated a request before.
+    pub fn owner(&self, api_key: &str) -> Option<String> {
+        let digest: [u8; 32] = Sha256::digest(api_key).into();
+        self.owners.lock().unwrap().get(&digest).cloned()
+    }
+}
+
+// neither emails nor key digests belong in logs
+impl fmt::Debug for KnownApiKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KnownApiKeys")
+            .field("count", &self.owners.lock().unwrap().len())
+            .finish()
+    }
+}
+
+/// Keys rate limits by signed in user, or otherwise by client without holding on to more
+/// of their address than [`IpPrivacy`] allows.
+#[derive(Clone)]
+pub struct ClientKeyExtractor {
+    privacy: IpPrivacy,
+    trusted_proxies: TrustedProxies,
+    known_api_keys: KnownApiKeys,
+    salt: [u8; 32],
+}
+
+impl ClientKeyExtractor {
+    pub fn new(
+        privacy: IpPrivacy,
+        trusted_proxies: TrustedProxies,
+        known_api_keys: KnownApiKeys,
+    ) -> Self {
+        let mut salt = [0; 32];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Self {
+            privacy,
+            trusted_proxies,
+            known_api_keys,
+            salt,
+        }
+    }
+
+    /// Where a request came from, or `None` if neither its headers nor its connection say.
+    fn resolve<T>(&self, req: &Request<T>) -> Option<ClientIp> {
+        let peer = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let ip = self.trusted_proxies.client_ip(req.headers(), peer)?;
+        Some(ClientIp {
+            ip,
+            key: self.client_key(ip),
+        })
+    }
+
+    /// Start of the SHA-256 hash of `bytes` salted with this process's salt.
+    fn salted_hash(&self, bytes: &[u8]) -> [u8; 16] {
+        let digest = Sha256::new()
+            .chain_update(self.salt)
+            .chain_update(bytes)
+            .finalize();
+        let mut hashed = [0; 16];
+        hashed.copy_from_slice(&digest[..16]);
+        hashed
+    }
+
+    /// The key for the signed in user with `email`, hashed whatever [`IpPrivacy`] is set to
+    /// since emails are no less sensitive than addresses.
+    fn user_key(&self, email: &str) -> ClientKey {
+        ClientKey::User(self.salted_hash(email.to_lowercase().as_bytes()))
+    }
+
+    /// Email of the user a request is signed in as, by its API key if it has one or else
+    /// its session cookie, the same way requests are authenticated.
+    ///
+    /// API keys that haven't authenticated a request yet don't count, so the first request
+    /// with a new key is keyed by address.
+    fn signed_in_user(&self, headers: &HeaderMap) -> Option<String> {
+        if let Some(api_key) = headers.get("X-Api-Key") {
+            return api_key
+                .to_str()
+                .ok()
+                .and_then(|api_key| self.known_api_keys.owner(api_key));
+        }
+        let token = CookieJar::from_headers(headers)
+            .get("jwt")?
+            .value()
+            .to_string();
+        JWT_KEYS
+            .decode::<JWTClaims>(&token, &Validation::default())
+            .ok()
+            .map(|token_data| token_data.claims.sub)
+    }
+
+    /// The key for a client connecting from `ip`.
+    pub fn client_key(&self, ip: IpAddr) -> ClientKey {
+        match self.privacy {
+            IpPrivacy::Off => ClientKey::Ip(ip),
+            IpPrivacy::Truncate => ClientKey::Ip(truncate(ip)),
+            IpPrivacy::Hash => {
+                let ip_bytes = match ip {
+                    IpAddr::V4(ip) => ip.octets().to_vec(),
+                    IpAddr::V6(ip) => ip.octets().to_vec(),
+                };
+                ClientKey::Hashed(self.salted_hash(&ip_bytes))
+            }
+        }
+    }
+}
+
+// the salt would undo the hashing if it ended up in a log
+impl fmt::Debug for ClientKeyExtractor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientKeyExtractor")
+            .field("privacy", &self.privacy)
+            .field("trusted_proxies", &self.trusted_proxies)
+            .field("known_api_keys", &self.known_api_keys)
+            .field("salt", &REDACTED)
+            .finish()
+    }
+}
+
+/// Middleware adding [`ClientIp`] to every request, so everything after it agrees on where
+/// a request came from.
+pub async fn resolve_client_ip(
+    State(extractor): State<ClientKeyExtractor>,
+    mut request: AxumRequest,
+    next: Next,
+) -> Response {
+    if let Some(client_ip) = extractor.resolve(&request) {
+        request.extensions_mut().insert(client_ip);
+    }
+    next.run(request).await
+}
+
+/// The network an address is on, the way analytics tools anonymize addresses.
+fn truncate(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            IpAddr::V4(Ipv4Addr::new(a, b, c, 0))
+        }
+        IpAddr::V6(ip) => {
+            let [a, b, c, ..] = ip.segments();
+            IpAddr::V6(Ipv6Addr::new(a, b, c, 0, 0, 0, 0, 0))
+        }
+    }
+}
+
+impl KeyExtractor for ClientKeyExtractor {
+    type Key = ClientKey;
+
+    fn extract<T>(&self, req: &Request<T>) -> Result<Self::Key, GovernorError> {
+        if let Some(email) = self.signed_in_user(req.headers()) {
+            return Ok(self.user_key(&email));
+        }
+        req.extensions()
+            .get::<ClientIp>()
+            .copied()
+            .or_else(|| self.resolve(req))
+            .map(|client_ip| client_ip.key)
+            .ok_or(GovernorError::UnableToExtractKey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::get_mock_jwt;
+    use axum::body::Body;
+    use axum::middleware::from_fn_with_state;
+    use axum::routing::get;
+    use axum::{Extension, Router};
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use tower::ServiceExt;
+
+    fn extractor(privacy: IpPrivacy) -> ClientKeyExtractor {
+        ClientKeyExtractor::new(privacy, TrustedProxies::default(), KnownApiKeys::default())
+    }
+
+    fn forwarded_for(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(FORWARDED_FOR_HEADER, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_ip_privacy_from_str() {
+        assert_eq!("HASH".parse::<IpPrivacy>(), Ok(IpPrivacy::Hash));
+        assert_eq!("truncate".parse::<IpPrivacy>(), Ok(IpPrivacy::Truncate));
+        assert!("anonymous".parse::<IpPrivacy>().is_err());
+    }
+
+    #[test]
+    fn truncates_addresses_to_their_network() {
+        let extractor = extractor(IpPrivacy::Truncate);
+        assert_eq!(
+            extractor.client_key("197.252.12.34".parse().unwrap()),
+            ClientKey::Ip("197.252.12.0".parse().unwrap())
+        );
+        assert_eq!(
+            extractor.client_key("2c0f:fe38:2401:1::5".parse().unwrap()),
+            ClientKey::Ip("2c0f:fe38:2401::".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn hashes_addresses_with_a_salt() {
+        let hashing = extractor(IpPrivacy::Hash);
+        let ip: IpAddr = "197.252.12.34".parse().unwrap();
+        let key = hashing.client_key(ip);
+        assert!(matches!(key, ClientKey::Hashed(_)));
+        assert_eq!(hashing.client_key(ip), key);
+        assert_ne!(hashing.client_key("197.252.12.35".parse().unwrap()), key);
+        // another process gets another salt
+        assert_ne!(extractor(IpPrivacy::Hash).client_key(ip), key);
+    }
+
+    #[test]
+    fn extracts_the_key_from_the_connection() {
+        let extractor = extractor(IpPrivacy::Off);
+        let mut request = Request::new(());
+        assert!(extractor.extract(&request).is_err());
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 5000))));
+        assert_eq!(
+            extractor.extract(&request).unwrap(),
+            ClientKey::Ip("10.0.0.1".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn trusts_only_the_forwarded_addresses_proxies_added() {
+        let peer = Some("10.0.0.1".parse().unwrap());
+        let proxies = TrustedProxies {
+            hops: 2,
+            networks: "10.0.0.0/24".parse().unwrap(),
+        };
+        // the client made up the first entry, Cloudflare added the second and the load
+        // balancer the third
+        let headers = forwarded_for("6.6.6.6, 197.252.12.34, 172.64.0.1");
+        assert_eq!(
+            proxies.client_ip(&headers, peer),
+            Some("197.252.12.34".parse().unwrap())
+        );
+        assert_eq!(TrustedProxies::default().client_ip(&headers, peer), peer);
+        // didn't pass through every proxy
+        assert_eq!(proxies.client_ip(&forwarded_for("172.64.0.1"), peer), peer);
+        assert_eq!(
+            proxies.client_ip(&forwarded_for("nonsense, 172.64.0.1"), peer),
+            peer
+        );
+    }
+
+    #[test]
+    fn trusts_forwarded_addresses_only_from_proxy_networks() {
+        let proxies = TrustedProxies {
+            hops: 1,
+            networks: "10.0.0.0/24, 10.1.0.7".parse().unwrap(),
+        };
+        let headers = forwarded_for("197.252.12.34");
+        for proxy in ["10.0.0.1", "10.1.0.7"] {
+            assert_eq!(
+                proxies.client_ip(&headers, Some(proxy.parse().unwrap())),
+                Some("197.252.12.34".parse().unwrap())
+            );
+        }
+        let direct = Some("6.6.6.6".parse().unwrap());
+        assert_eq!(proxies.client_ip(&headers, direct), direct);
+        assert!("10.0.0.0/24, somewhere".parse::<ProxyNetworks>().is_err());
+        assert_eq!("".parse::<ProxyNetworks>(), Ok(ProxyNetworks::default()));
+    }
+
+    #[test]
+    fn refuses_proxy_hops_without_proxy_networks() {
+        let proxies = TrustedProxies {
+            hops: 2,
+            networks: ProxyNetworks::default(),
+        };
+        assert!(proxies.check().is_err());
+        // without networks nothing counts as having come through the proxies
+        let peer = Some("6.6.6.6".parse().unwrap());
+        let headers = forwarded_for("1.2.3.4, 197.252.12.34, 172.64.0.1");
+        assert_eq!(proxies.client_ip(&headers, peer), peer);
+        assert_eq!(TrustedProxies::default().check(), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn resolves_the_client_for_everything_after_it() {
+        let extractor = ClientKeyExtractor::new(
+            IpPrivacy::Truncate,
+            TrustedProxies {
+                hops: 1,
+                networks: "10.0.0.0/24".parse().unwrap(),
+            },
+            KnownApiKeys::default(),
+        );
+        let app = Router::new()
+            .route(
+                "/",
+                get(|Extension(client_ip): Extension<ClientIp>| async move {
+                    client_ip.key.to_string()
+                }),
+            )
+            .layer(from_fn_with_state(extractor, resolve_client_ip));
+        let mut request = axum::http::Request::builder()
+            .uri("/")
+            .header(FORWARDED_FOR_HEADER, "197.252.12.34")
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 5000))));
+        let response = app.oneshot(request).await.unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"197.252.12.0");
+    }
+
+    #[test]
+    fn shows_hashed_keys_in_hex() {
+        assert_eq!(ClientKey::Hashed([0xab; 16]).to_string(), "ab".repeat(16));
+    }
+
+    #[test]
+    fn keys_signed_in_users_by_who_they_are() {
+        let known_api_keys = KnownApiKeys::default();
+        let extractor = ClientKeyExtractor::new(
+            IpPrivacy::Off,
+            TrustedProxies::default(),
+            known_api_keys.clone(),
+        );
+        let user = extractor.user_key("someuser@gmail.com");
+        let mut request = Request::new(());
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 5000))));
+        request.headers_mut().insert(
+            http::header::COOKIE,
+            format!("jwt={}", get_mock_jwt()).parse().unwrap(),
+        );
+        assert_eq!(extractor.extract(&request).unwrap(), user);
+
+        // a key nobody has authenticated with yet doesn't count
+        request
+            .headers_mut()
+            .insert("X-Api-Key", "made-up-key".parse().unwrap());
+        assert_eq!(
+            extractor.extract(&request).unwrap(),
+            ClientKey::Ip("10.0.0.1".parse().unwrap())
+        );
+        known_api_keys.remember("made-up-key", "SomeUser@gmail.com");
+        assert_eq!(extractor.extract(&request).unwrap(), user);
+    }
+
+    #[test]
+    fn forgets_the_least_recently_used_api_keys() {
+        let known_api_keys = KnownApiKeys::with_capacity(2);
+        known_api_keys.remember("first-key", "first@example.com");
+        known_api_keys.remember("second-key", "second@example.com");
+        assert_eq!(
+            known_api_keys.owner("first-key"),
+            Some("first@example.com".to_string())
+        );
+        known_api_keys.remember("third-key", "third@example.com");
+        assert_eq!(known_api_keys.owner("second-key"), None);
+        assert_eq!(
+            known_api_keys.owner("first-key"),
+            Some("first@example.com".to_string())
+        );
+        assert_eq!(
+            known_api_keys.owner("third-key"),
+            Some("third@example.com".to_string())
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Where the API gets the current time and new IDs from.
+//!
+//! Session expiry, embargoes and scheduled runs depend on what time it is, and sessions and
+//! stored files get random UUIDs. Rather than calling `Utc::now` and `Uuid::new_v4` inline,
+//! services, repos, crawlers, the pipeline metrics and the scheduler ask a [`Clock`] and an
+//! [`IdGen`], so tests can pin both down. One of each is created at
+//! startup and handed to all of them; extractors reach the clock through
+//! [`AppState`](crate::app_factory::AppState).
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Tells the time.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// The time as stored in the database, which keeps UTC timestamps without a zone
+    fn naive_now(&self) -> NaiveDateTime {
+        self.now().naive_utc()
+    }
+}
+
+/// Generates IDs for new records and files.
+pub trait IdGen: Send + Sync {
+    fn new_id(&self) -> Uuid;
+}
+
+/// The system's clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Random version 4 UUIDs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomIds;
+
+impl IdGen for RandomIds {
+    fn new_id(&self) -> Uuid {
+        Uuid::new_v4()
+    }
+}
+
+/// A [`Clock`] shared between services, the system's unless a test swaps it.
+#[derive(Clone)]
+pub struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        Self(Arc::new(clock))
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+impl Deref for SharedClock {
+    type Target = dyn Clock;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Debug for SharedClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SharedClock").field(&self.now()).finish()
+    }
+}
+
+/// An [`IdGen`] shared between services, random UUIDs unless a test swaps it.
+#[derive(Clone)]
+pub struct SharedIdGen(Arc<dyn IdGen>);
+
+impl SharedIdGen {
+    pub fn new(ids: impl IdGen + 'static) -> Self {
+        Self(Arc::new(ids))
+    }
+}
+
+impl Default for SharedIdGen {
+    fn default() -> Self {
+        Self::new(RandomIds)
+    }
+}
+
+impl Deref for SharedIdGen {
+    type Target = dyn IdGen;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl fmt::Debug for SharedIdGen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedIdGen")
+    }
+}
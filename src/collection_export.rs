@@ -0,0 +1,380 @@
+//! Export of a collection as a single ZIP, so its captures can be handed out offline, e.g.
+//! copied onto USB drives where the internet is cut off.
+//!
+//! The ZIP holds each exported accession's file under `captures/`, named after the
+//! accession, e.g. `captures/12.wacz`, which ReplayWeb.page can replay without a network.
+//! Next to them `manifest.json`, a [`CollectionExportManifest`], describes the collection
+//! and every accession with its SHA-256, so copies can be checked against the archive.
+//!
+//! Only public accessions are exported since the ZIP is meant to leave the archive. A
+//! curator starts an export, which is put together on a background task and stored in S3
+//! under [`COLLECTION_EXPORT_PREFIX`]. They are emailed a link to it when it's ready, see
+//! [`ExportReadyEmail`].
+//!
+//! Files are copied out of S3 into a temporary directory, the ZIP is written next to them and
+//! uploaded in parts, so an export never has to fit in memory. Collections whose files add
+//! up to more than [`MAX_EXPORT_BYTES`] can't be exported, and only
+//! [`MAX_CONCURRENT_EXPORTS`] are put together at once so exports can't fill the disk.
+
+use crate::activity_digest::escape_html;
+use ::entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
+use ::entity::collection::Model as CollectionModel;
+use chrono::NaiveDateTime;
+use sea_orm::ActiveEnum;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Where in S3 exports are stored
+pub const COLLECTION_EXPORT_PREFIX: &str = "collection-exports";
+
+/// Largest total size of the files in an export
+pub const MAX_EXPORT_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// How many exports are put together at once, each taking up to twice [`MAX_EXPORT_BYTES`]
+/// of disk
+pub const MAX_CONCURRENT_EXPORTS: usize = 2;
+
+/// Size of the parts exports are uploaded in, S3 needs at least 5 MB for all but the last
+pub const EXPORT_PART_SIZE: usize = 16 * 1024 * 1024;
+
+/// Feature flag that turns exports on, so they can be tried out in one environment or
+/// for admins before everyone gets them
+pub const COLLECTION_EXPORTS_FLAG: &str = "collection-exports";
+
+/// How long the emailed link works for, the longest S3 allows
+pub const EXPORT_LINK_EXPIRY_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+/// Content type of exports.
+pub const COLLECTION_EXPORT_CONTENT_TYPE: &str = "application/zip";
+
+/// Name of the manifest in the ZIP.
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Identifies the manifest's layout.
+const MANIFEST_VERSION: &str = "sudan-digital-archive-collection-export-v1";
+
+/// Limits how many exports are put together at once, shared by every request.
+pub type ExportPermits = Arc<Semaphore>;
+
+/// Creates the permits for [`MAX_CONCURRENT_EXPORTS`] exports.
+pub fn new_export_permits() -> ExportPermits {
+    Arc::new(Semaphore::new(MAX_CONCURRENT_EXPORTS))
+}
+
+/// S3 key of an export, unique per export so earlier links keep working.
+pub fn export_key(collection_id: i32, id: Uuid) -> String {
+    format!("{COLLECTION_EXPORT_PREFIX}/{collection_id}/{id}.zip")
+}
+
+/// Name of an accession's file in the ZIP, keeping the extension it is stored with.
+pub fn capture_file_name(accession_id: i32, s3_key: &str) -> String {
+    match Path::new(s3_key).extension() {
+        Some(extension) => format!(
+            "captures/{accession_id}.{}",
+            extension.to_string_lossy().to_lowercase()
+        ),
+        None => format!("captures/{accession_id}"),
+    }
+}
+
+/// A file of an export, copied out of S3 onto disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportFile {
+    /// Name in the ZIP, see [`capture_file_name`]
+    pub name: String,
+    pub path: PathBuf,
+    /// Hex hash of the file
+    pub sha256: String,
+}
+
+/// An accession in an export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedAccession {
+    pub id: i32,
+    pub title_en: Option<String>,
+    pub description_en: Option<String>,
+    pub title_ar: Option<String>,
+    pub description_ar: Option<String>,
+    pub original_url: String,
+    pub captured_at: NaiveDateTime,
+    /// Dublin Core date of the accession
+    pub date: NaiveDateTime,
+    /// Dublin Core format, e.g. `wacz`
+    pub format: String,
+    /// Where the file is in the ZIP, `None` if the accession has no stored file
+    pub file_name: Option<String>,
+    /// Hex hash of the file
+    pub sha256: Option<String>,
+}
+
+impl ExportedAccession {
+    /// Describes an accession and, if it has one, its file as stored in the ZIP.
+    pub fn new(accession: &AccessionWithMetadataModel, file: Option<&ExportFile>) -> Self {
+        Self {
+            id: accession.id,
+            title_en: accession.title_en.clone(),
+            description_en: accession.description_en.clone(),
+            title_ar: accession.title_ar.clone(),
+            description_ar: accession.description_ar.clone(),
+            original_url: accession.seed_url.clone(),
+            captured_at: accession.crawl_timestamp,
+            date: accession.dublin_metadata_date,
+            format: accession.dublin_metadata_format.to_value(),
+            file_name: file.map(|file| file.name.clone()),
+            sha256: file.map(|file| file.sha256.clone()),
+        }
+    }
+}
+
+/// Describes an export, so it can be browsed and checked without the archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectionExportManifest {
+    /// Always `sudan-digital-archive-collection-export-v1`
+    pub version: String,
+    pub collection_id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    /// Email of the curator who exported the collection
+    pub generated_by: String,
+    pub generated_at: NaiveDateTime,
+    pub accessions: Vec<ExportedAccession>,
+}
+
+impl CollectionExportManifest {
+    pub fn new(
+        collection: &CollectionModel,
+        accessions: Vec<ExportedAccession>,
+        generated_by: String,
+        generated_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            version: MANIFEST_VERSION.to_string(),
+            collection_id: collection.id,
+            title: collection.title.clone(),
+            description: collection.description.clone(),
+            generated_by,
+            generated_at,
+            accessions,
+        }
+    }
+}
+
+/// Packs the exported files and the manifest into a ZIP written to `out`.
+///
+/// Files are stored as they are, since WACZ files and media are compressed already, and are
+/// copied from disk a piece at a time. This blocks, so run it with `spawn_blocking`.
+///
+/// # Arguments
+/// * `files` - The files to pack
+/// * `manifest` - Describes the files
+/// * `out` - Where to write the ZIP
+pub fn bundle<W: Write + Seek>(
+    files: &[ExportFile],
+    manifest: &CollectionExportManifest,
+    out: W,
+) -> Result<W, String> {
+    let manifest_json = serde_json::to_vec_pretty(manifest).map_err(|err| err.to_string())?;
+    let stored = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .large_file(true);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut writer = ZipWriter::new(out);
+    for file in files {
+        writer
+            .start_file(file.name.as_str(), stored)
+            .map_err(|err| err.to_string())?;
+        let mut contents = File::open(&file.path).map_err(|err| err.to_string())?;
+        std::io::copy(&mut contents, &mut writer).map_err(|err| err.to_string())?;
+    }
+    writer
+        .start_file(MANIFEST_FILE_NAME, deflated)
+        .map_err(|err| err.to_string())?;
+    writer
+        .write_all(&manifest_json)
+        .map_err(|err| err.to_string())?;
+    writer.finish().map_err(|err| err.to_string())
+}
+
+/// Email telling a curator their export is ready to download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportReadyEmail {
+    pub title: String,
+    /// Presigned link to the ZIP
+    pub url: String,
+    /// How many accessions are in the ZIP
+    pub exported: usize,
+    /// How many private accessions were left out
+    pub left_out: usize,
+}
+
+impl ExportReadyEmail {
+    pub fn subject(&self) -> String {
+        format!("Your export of {} is ready", self.title)
+    }
+
+    pub fn html_body(&self) -> String {
+        let mut body = format!(
+            "<p>The export of {} with {} accessions is ready to <a href='{}'>download</a>. The link works for {} days.</p>",
+            escape_html(&self.title),
+            self.exported,
+            escape_html(&self.url),
+            EXPORT_LINK_EXPIRY_SECONDS / (24 * 60 * 60)
+        );
+        if self.left_out > 0 {
+            body.push_str(&format!(
+                "<p>{} private accessions were left out.</p>",
+                self.left_out
+            ));
+        }
+        body
+    }
+}
+
+/// Email telling a curator their export couldn't be made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportFailedEmail {
+    pub title: String,
+}
+
+impl ExportFailedEmail {
+    pub fn subject(&self) -> String {
+        format!("Your export of {} failed", self.title)
+    }
+
+    pub fn html_body(&self) -> String {
+        format!(
+            "<p>The export of {} could not be made. Please try again later.</p>",
+            escape_html(&self.title)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::{build_test_collections_service, mock_one_accession_with_metadata};
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+    use std::io::{Cursor, Read};
+    use zip::ZipArchive;
+
+    fn collection() -> CollectionModel {
+        let created_at = NaiveDate::from_ymd_opt(2026, 10, 16)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        CollectionModel {
+            id: 3,
+            organization_id: 1,
+            title: "El Fasher siege".to_string(),
+            description: None,
+            created_by: "someuser@gmail.com".to_string(),
+            created_at,
+        }
+    }
+
+    #[test]
+    fn names_captures_after_their_accessions() {
+        assert_eq!(
+            capture_file_name(12, "1/abc_Market-fire.WACZ"),
+            "captures/12.wacz"
+        );
+        assert_eq!(capture_file_name(12, "no-extension"), "captures/12");
+        assert_eq!(
+            export_key(3, Uuid::from_u128(1)),
+            "collection-exports/3/00000000-0000-0000-0000-000000000001.zip"
+        );
+    }
+
+    #[test]
+    fn bundles_captures_with_a_manifest() {
+        let accession = mock_one_accession_with_metadata();
+        let dir = tempfile::tempdir().unwrap();
+        let file = ExportFile {
+            name: capture_file_name(accession.id, "1/capture.wacz"),
+            path: dir.path().join("capture.wacz"),
+            sha256: "abc".to_string(),
+        };
+        std::fs::write(&file.path, b"wacz bytes").unwrap();
+        let exported = ExportedAccession::new(&accession, Some(&file));
+        assert_eq!(exported.file_name.as_deref(), Some("captures/1.wacz"));
+        assert_eq!(exported.sha256.as_deref(), Some("abc"));
+        let manifest = CollectionExportManifest::new(
+            &collection(),
+            vec![exported],
+            "someuser@gmail.com".to_string(),
+            collection().created_at,
+        );
+
+        let zip = bundle(
+            std::slice::from_ref(&file),
+            &manifest,
+            Cursor::new(Vec::new()),
+        )
+        .unwrap();
+        let mut archive = ZipArchive::new(zip).unwrap();
+        let mut bundled_file = Vec::new();
+        archive
+            .by_name(&file.name)
+            .unwrap()
+            .read_to_end(&mut bundled_file)
+            .unwrap();
+        assert_eq!(bundled_file, b"wacz bytes");
+        let bundled_manifest: CollectionExportManifest =
+            serde_json::from_reader(archive.by_name(MANIFEST_FILE_NAME).unwrap()).unwrap();
+        assert_eq!(bundled_manifest, manifest);
+    }
+
+    #[test]
+    fn lists_accessions_without_a_file() {
+        let accession = mock_one_accession_with_metadata();
+        let exported = ExportedAccession::new(&accession, None);
+        assert_eq!((exported.file_name, exported.sha256), (None, None));
+    }
+
+    #[tokio::test]
+    async fn exports_public_accessions_to_s3() {
+        let service = build_test_collections_service();
+        let public = AccessionWithMetadataModel {
+            is_private: false,
+            ..mock_one_accession_with_metadata()
+        };
+        let accessions = vec![
+            public.clone(),
+            AccessionWithMetadataModel {
+                id: 2,
+                s3_filename: None,
+                ..public
+            },
+        ];
+        let url = service
+            .export(&collection(), &accessions, "someuser@gmail.com")
+            .await
+            .unwrap();
+        assert_eq!(url, "my url");
+    }
+
+    #[test]
+    fn escapes_titles_in_emails() {
+        let email = ExportReadyEmail {
+            title: "<b>Siege</b>".to_string(),
+            url: "https://bucket.example.com/export.zip?a=1&b=2".to_string(),
+            exported: 2,
+            left_out: 1,
+        };
+        assert_eq!(email.subject(), "Your export of <b>Siege</b> is ready");
+        assert_eq!(
+            email.html_body(),
+            "<p>The export of &lt;b&gt;Siege&lt;/b&gt; with 2 accessions is ready to \
+             <a href='https://bucket.example.com/export.zip?a=1&amp;b=2'>download</a>. The \
+             link works for 7 days.</p><p>1 private accessions were left out.</p>"
+        );
+    }
+}
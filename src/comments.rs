@@ -0,0 +1,127 @@
+//! Mentions and rendering rules for curators' comments on accessions.
+//!
+//! Curators mention each other by writing `@` and the other curator's email, e.g.
+//! "@amna@sudandigitalarchive.com can you check the date?". Mentioned researchers and
+//! admins of the accession's organization get an email pointing them to the comment,
+//! see [`MentionEmail`].
+//!
+//! Comments flagged as markdown are rendered as markdown by the frontend, so they must not
+//! carry raw HTML the renderer would pass through, see [`contains_html`]. Plain text
+//! comments are shown as they are and can hold anything.
+
+use crate::activity_digest::{accession_link, escape_html};
+
+/// Characters that can follow a mention without being part of the email.
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', ')', ']', '\'', '"'];
+
+/// Emails mentioned in a comment, lowercased and without duplicates, in the order they
+/// first appear.
+///
+/// A mention is `@` followed by an email at the start of a word, so email addresses
+/// written without the `@` in front, like in "write to amna@example.com", don't count.
+pub fn find_mentions(body: &str) -> Vec<String> {
+    let mut mentions: Vec<String> = vec![];
+    for word in body.split_whitespace() {
+        let Some(email) = word.trim_start_matches(['(', '[']).strip_prefix('@') else {
+            continue;
+        };
+        let email = email.trim_end_matches(TRAILING_PUNCTUATION).to_lowercase();
+        if is_email(&email) && !mentions.contains(&email) {
+            mentions.push(email);
+        }
+    }
+    mentions
+}
+
+/// Rough check that `text` is an email, enough to tell mentions from other words.
+fn is_email(text: &str) -> bool {
+    match text.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+        }
+        None => false,
+    }
+}
+
+/// Whether text has an HTML tag or comment in it, which a markdown renderer would pass
+/// through as markup. A lone `<`, as in "fewer than < 10 photos", is fine.
+pub fn contains_html(body: &str) -> bool {
+    body.as_bytes().windows(2).any(|pair| {
+        pair[0] == b'<' && (pair[1].is_ascii_alphabetic() || matches!(pair[1], b'/' | b'!' | b'?'))
+    })
+}
+
+/// Email telling a curator they were mentioned in a comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MentionEmail {
+    pub accession_id: i32,
+    pub author_email: String,
+    pub body: String,
+}
+
+impl MentionEmail {
+    pub fn subject(&self) -> String {
+        format!(
+            "{} mentioned you on accession {}",
+            self.author_email, self.accession_id
+        )
+    }
+
+    /// The email's HTML, quoting the comment as plain text since it is written by users.
+    pub fn html_body(&self) -> String {
+        format!(
+            "<p>{} mentioned you in a comment on {}:</p><blockquote style='white-space: pre-wrap'>{}</blockquote>",
+            escape_html(&self.author_email),
+            accession_link(
+                self.accession_id,
+                &format!("accession {}", self.accession_id)
+            ),
+            escape_html(&self.body)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn finds_mentions() {
+        assert_eq!(
+            find_mentions(
+                "@Amna@sda.org can you check the date? cc (@omer@sda.org), \
+                 @amna@sda.org again, @omer and write to hiba@sda.org"
+            ),
+            vec!["amna@sda.org", "omer@sda.org"]
+        );
+        assert!(find_mentions("@ @@ @a@b @x@.org").is_empty());
+    }
+
+    #[test]
+    fn spots_html() {
+        assert!(contains_html("Looks <b>bold</b>"));
+        assert!(contains_html("<!-- hidden -->"));
+        assert!(contains_html("</script>"));
+        assert!(!contains_html("fewer than < 10 photos, 3<4"));
+        assert!(!contains_html("**bold** and [link](https://sda.org)"));
+    }
+
+    #[test]
+    fn escapes_mention_emails() {
+        let email = MentionEmail {
+            accession_id: 7,
+            author_email: "omer@sda.org".to_string(),
+            body: "@amna@sda.org is this <real>?".to_string(),
+        };
+        assert_eq!(email.subject(), "omer@sda.org mentioned you on accession 7");
+        assert!(email
+            .html_body()
+            .contains("@amna@sda.org is this &lt;real&gt;?"));
+        assert!(email.html_body().contains("/archive/7?isPrivate=true"));
+    }
+}
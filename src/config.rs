@@ -1,10 +1,23 @@
 //! Configuration module for Browsertrix web archiving integration and application settings.
 //! Handles environment variables and configuration structures for the archiving service.
 
-use crate::models::common::BrowserProfile;
+use crate::auth::JWTKeys;
+use crate::client_ip::{IpPrivacy, TrustedProxies};
+use crate::models::common::{BrowserProfile, CrawlerBackend};
+use crate::onion::OnionService;
+use crate::provenance::ProvenanceSigner;
+use crate::request_log::{RequestLogConfig, REDACTED};
+use crate::s3_keys::S3KeyScheme;
 use http::HeaderValue;
+use reqwest::Url;
 use serde::Serialize;
 use std::env;
+use std::fmt;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 use uuid::Uuid;
 
 /// Configuration for Browsertrix web archiving service
@@ -18,11 +31,137 @@ pub struct BrowsertrixConfig {
     pub create_crawl_url: String,
 }
 
+/// browsertrix-crawler run on the API's host, see [`crate::repos::local_crawler_repo`]
+#[derive(Debug, Clone, Default)]
+pub struct LocalCrawlerConfig {
+    /// Command running the crawler's `crawl` entrypoint
+    pub command: String,
+    /// Where the crawler writes its collections on this host
+    pub collections_dir: PathBuf,
+}
+
+/// Credentials for minting DOIs through DataCite
+#[derive(Debug, Clone, Default)]
+pub struct DataCiteConfig {
+    pub api_url: String,
+    pub repository_id: String,
+    pub password: String,
+    /// The archive's DOI prefix, e.g. `10.1234`
+    pub prefix: String,
+}
+
+/// Kind of external service file hashes are timestamped with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampService {
+    /// An OpenTimestamps calendar, which anchors hashes in Bitcoin
+    #[default]
+    OpenTimestamps,
+    /// An RFC 3161 timestamping authority
+    Rfc3161,
+}
+
+impl FromStr for TimestampService {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "opentimestamps" => Ok(TimestampService::OpenTimestamps),
+            "rfc3161" => Ok(TimestampService::Rfc3161),
+            other => Err(format!("Unknown timestamp service: {other}")),
+        }
+    }
+}
+
+/// External service file hashes are timestamped with
+#[derive(Debug, Clone, Default)]
+pub struct TimestampConfig {
+    pub service: TimestampService,
+    /// The calendar or timestamping authority's URL
+    pub url: String,
+}
+
+/// What to do with uploaded files that fail virus scanning
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScanEnforcement {
+    /// Reject the upload and delete the file
+    #[default]
+    Block,
+    /// Keep the file but mark the accession's `scan_status` and make it private for review
+    Flag,
+}
+
+impl FromStr for ScanEnforcement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "block" => Ok(ScanEnforcement::Block),
+            "flag" => Ok(ScanEnforcement::Flag),
+            other => Err(format!("Unknown scan enforcement: {other}")),
+        }
+    }
+}
+
+/// How long requests to a class of routes may take and how large their bodies may be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteLimits {
+    pub timeout: Duration,
+    pub body_limit: usize,
+}
+
+impl RouteLimits {
+    /// Metadata CRUD and other JSON routes, which should be quick and small
+    pub const JSON: RouteLimits = RouteLimits {
+        timeout: Duration::from_secs(30),
+        body_limit: 1024 * 1024,
+    };
+    /// Bulk create and import routes, which take many records in one JSON body that
+    /// clients may compress
+    pub const BULK: RouteLimits = RouteLimits {
+        timeout: Duration::from_secs(2 * 60),
+        body_limit: 20 * 1024 * 1024,
+    };
+    /// Routes that stream uploaded files, which can be hundreds of MB over slow connections
+    pub const UPLOAD: RouteLimits = RouteLimits {
+        timeout: Duration::from_secs(15 * 60),
+        body_limit: 200 * 1024 * 1024,
+    };
+
+    /// Reads limits from `<prefix>_REQUEST_TIMEOUT` (seconds) and `<prefix>_BODY_LIMIT`
+    /// (bytes), falling back to `defaults` for either one that is unset.
+    fn from_env(prefix: &str, defaults: RouteLimits, reader: &mut ConfigReader) -> RouteLimits {
+        let timeout = reader.parsed(
+            &format!("{prefix}_REQUEST_TIMEOUT"),
+            &defaults.timeout.as_secs().to_string(),
+            "a number of seconds",
+        );
+        let body_limit = reader.parsed(
+            &format!("{prefix}_BODY_LIMIT"),
+            &defaults.body_limit.to_string(),
+            "a number of bytes",
+        );
+        RouteLimits {
+            timeout: Duration::from_secs(timeout),
+            body_limit,
+        }
+    }
+}
+
+impl Default for RouteLimits {
+    fn default() -> Self {
+        RouteLimits::JSON
+    }
+}
+
 /// Global application configuration
 #[derive(Debug, Clone, Default)]
 pub struct AppConfig {
     pub archive_sender_email: String,
     pub browsertrix: BrowsertrixConfig,
+    /// Crawler used when a crawl request doesn't ask for one
+    pub crawler: CrawlerBackend,
+    /// browsertrix-crawler run on this host; the local crawler is unavailable when unset
+    pub local_crawler: Option<LocalCrawlerConfig>,
     pub cors_urls: Vec<HeaderValue>,
     pub postgres_url: String,
     pub listener_address: String,
@@ -30,33 +169,371 @@ pub struct AppConfig {
     pub jwt_cookie_domain: String,
     pub postmark_api_base: String,
     pub postmark_api_key: String,
+    /// Password Postmark sends with its webhooks; the webhook is turned off when unset
+    pub postmark_webhook_secret: Option<String>,
     pub digital_ocean_spaces_endpoint_url: String,
     pub digital_ocean_spaces_bucket: String,
     pub digital_ocean_spaces_access_key: String,
     pub digital_ocean_spaces_secret_key: String,
-    pub max_file_upload_size: usize,
+    /// Limits for JSON routes, i.e. everything but file uploads
+    pub json_route_limits: RouteLimits,
+    /// Limits for bulk routes; the body limit applies after decompression
+    pub bulk_route_limits: RouteLimits,
+    /// Limits for routes that stream uploaded files; the body limit is the largest file accepted
+    pub upload_route_limits: RouteLimits,
     pub s3_operation_timeout: u64,
     pub s3_operation_attempt_timeout: u64,
     pub s3_connect_timeout: u64,
     pub api_prefix: String,
+    /// URL clients reach the API at, including any prefix, for links that must be absolute
+    pub public_api_url: String,
+    /// URL of the API on its Tor onion service, including any prefix; no `Onion-Location`
+    /// header is sent when unset
+    pub onion_url: Option<String>,
+    /// Base URL of the headless PDF renderer; PDF derivatives are skipped when unset
+    pub pdf_renderer_url: Option<String>,
+    /// Host and port of the ClamAV daemon; uploads are not scanned when unset
+    pub clamav_address: Option<String>,
+    pub scan_enforcement: ScanEnforcement,
+    /// How keys of files the API writes to S3 are built
+    pub s3_key_scheme: S3KeyScheme,
+    /// Path to the ffmpeg binary; image and video derivatives are skipped when unset
+    pub ffmpeg_path: Option<String>,
+    /// Path to the yt-dlp binary; video captures are unavailable when unset
+    pub ytdlp_path: Option<String>,
+    /// Base URL of the LibreTranslate service; machine translation is unavailable when unset
+    pub translation_api_url: Option<String>,
+    pub translation_api_key: Option<String>,
+    /// Base URL of the NER service; subject suggestions are unavailable when unset
+    pub ner_api_url: Option<String>,
+    /// DataCite repository DOIs are minted in; DOIs are unavailable when unset
+    pub datacite: Option<DataCiteConfig>,
+    /// Base64 Ed25519 seed file hashes are signed with; hashes are recorded unsigned when unset
+    pub provenance_signing_key: Option<String>,
+    /// Service file hashes are timestamped with; hashes aren't timestamped when unset
+    pub timestamping: Option<TimestampConfig>,
+    /// Name of the environment, e.g. `staging`, that feature flags can be scoped to
+    pub environment: String,
+    /// How many crawls may run in Browsertrix at once, the rest wait in a queue
+    pub max_active_crawls: usize,
+    pub request_log: RequestLogConfig,
+    /// How much of clients' IP addresses the rate limiter keeps
+    pub ip_privacy: IpPrivacy,
+    /// The proxies in front of the API, whose `X-Forwarded-For` entries are trusted
+    pub trusted_proxies: TrustedProxies,
+    /// Name this instance goes by in the election of the one running background tasks
+    pub instance_name: String,
+}
+
+/// Reads a secret from the file named by `<name>_FILE`, or failing that from `<name>`.
+///
+/// Env vars end up in process listings and crash dumps, so secrets can instead be mounted
+/// as files, the way Docker and Kubernetes secrets are and Vault Agent or the DO and Vault
+/// secret store CSI drivers write them. A trailing newline in the file is ignored.
+///
+/// # Returns
+/// The secret, `None` if neither variable is set, or an error if both are or the file
+/// can't be read
+pub fn try_secret_var(name: &str) -> Result<Option<String>, String> {
+    let file_var = format!("{name}_FILE");
+    match (env::var(&file_var), env::var(name)) {
+        (Ok(_), Ok(_)) => Err(format!("Only one of {name} and {file_var} should be set")),
+        (Ok(path), Err(_)) => {
+            let secret = fs::read_to_string(&path)
+                .map_err(|err| format!("Could not read {file_var} at {path}: {err}"))?;
+            Ok(Some(secret.trim_end_matches(['\r', '\n']).to_string()))
+        }
+        (Err(_), Ok(secret)) => Ok(Some(secret)),
+        (Err(_), Err(_)) => Ok(None),
+    }
+}
+
+/// Like [`try_secret_var`], for one off commands with nothing to recover to.
+///
+/// # Panics
+/// Panics if both variables are set or the file can't be read
+pub fn secret_var(name: &str) -> Option<String> {
+    try_secret_var(name).unwrap_or_else(|err| panic!("{err}"))
+}
+
+/// Everything wrong with the configuration, so it can all be fixed before restarting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigErrors(pub Vec<String>);
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration:")?;
+        for error in &self.0 {
+            write!(f, "\n  - {error}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads settings from the environment, collecting what's wrong with them rather than
+/// stopping at the first problem. Settings that can't be read come back as their defaults,
+/// which are never used since [`ConfigReader::finish`] then fails.
+#[derive(Debug, Default)]
+struct ConfigReader {
+    errors: Vec<String>,
 }
 
-/// Builds application configuration from environment variables
-pub fn build_app_config() -> AppConfig {
-    let postgres_url = env::var("POSTGRES_URL").expect("Missing POSTGRES_URL env var");
-    let archive_sender_email =
-        env::var("ARCHIVE_SENDER_EMAIL").expect("Missing ARCHIVE_SENDER_EMAIL env var");
-    let postmark_api_base =
-        env::var("POSTMARK_API_BASE").expect("Missing POSTMARK_API_BASE env var");
-    let postmark_api_key = env::var("POSTMARK_API_KEY").expect("Missing POSTMARK_API_KEY env var");
-    let username = env::var("BROWSERTRIX_USERNAME").expect("Missing BROWSERTRIX_USERNAME env var");
-    let password = env::var("BROWSERTRIX_PASSWORD").expect("Missing BROWSERTRIX_PASSWORD env var");
-    let org_id = env::var("BROWSERTRIX_ORGID").expect("Missing BROWSERTRIX_ORGID env var");
-    let org_uuid = Uuid::parse_str(&org_id).expect("Could not parse browsertrix org id to uuid");
-    let base_url = env::var("BROWSERTRIX_BROWSERTRIX_URL")
-        .expect("Missing BROWSERTRIX_BROWSERTRIX_URL env var");
+impl ConfigReader {
+    fn fail<T: Default>(&mut self, error: String) -> T {
+        self.errors.push(error);
+        T::default()
+    }
+
+    fn optional(&self, name: &str) -> Option<String> {
+        env::var(name).ok()
+    }
+
+    fn required(&mut self, name: &str) -> String {
+        env::var(name).unwrap_or_else(|_| self.fail(format!("{name} is missing")))
+    }
+
+    /// Reads a secret, see [`try_secret_var`]
+    fn secret(&mut self, name: &str) -> Option<String> {
+        try_secret_var(name).unwrap_or_else(|err| self.fail(err))
+    }
+
+    fn required_secret(&mut self, name: &str) -> String {
+        match try_secret_var(name) {
+            Ok(Some(secret)) => secret,
+            Ok(None) => self.fail(format!("{name} or {name}_FILE is missing")),
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// Parses a setting, or `default` if it is unset.
+    ///
+    /// # Arguments
+    /// * `expected` - What the setting should be, for the error if it isn't
+    fn parsed<T: FromStr + Default>(&mut self, name: &str, default: &str, expected: &str) -> T {
+        let value = env::var(name).unwrap_or_else(|_| default.to_string());
+        value
+            .parse()
+            .unwrap_or_else(|_| self.fail(format!("{name} should be {expected}, got {value}")))
+    }
+
+    fn required_parsed<T: FromStr + Default>(&mut self, name: &str, expected: &str) -> T {
+        match env::var(name) {
+            Ok(value) => value
+                .parse()
+                .unwrap_or_else(|_| self.fail(format!("{name} should be {expected}, got {value}"))),
+            Err(_) => self.fail(format!("{name} is missing")),
+        }
+    }
+
+    /// Checks a setting is an absolute URL with one of `schemes`.
+    fn check_url(&mut self, name: &str, value: &str, schemes: &[&str]) {
+        match Url::parse(value) {
+            Ok(url) if schemes.contains(&url.scheme()) => {}
+            Ok(url) => self.errors.push(format!(
+                "{name} should be a {} URL, got a {} one",
+                schemes.join(" or "),
+                url.scheme()
+            )),
+            Err(err) => self.errors.push(format!("{name} should be a URL: {err}")),
+        }
+    }
+
+    /// Checks a setting is a `host:port` pair.
+    fn check_host_and_port(&mut self, name: &str, value: &str) {
+        let valid = value
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+        if !valid {
+            self.errors
+                .push(format!("{name} should be host:port, got {value}"));
+        }
+    }
+
+    fn check_positive(&mut self, name: &str, value: i64) {
+        if value <= 0 {
+            self.errors
+                .push(format!("{name} should be more than 0, got {value}"));
+        }
+    }
+
+    fn finish(self, config: AppConfig) -> Result<AppConfig, ConfigErrors> {
+        if self.errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigErrors(self.errors))
+        }
+    }
+}
+
+/// Builds application configuration from environment variables and secret files.
+///
+/// # Returns
+/// The configuration, or every setting that is missing or invalid
+pub fn build_app_config() -> Result<AppConfig, ConfigErrors> {
+    let mut reader = ConfigReader::default();
+    let postgres_url = reader.required_secret("POSTGRES_URL");
+    if !postgres_url.is_empty() {
+        reader.check_url("POSTGRES_URL", &postgres_url, &["postgres", "postgresql"]);
+    }
+    let archive_sender_email = reader.required("ARCHIVE_SENDER_EMAIL");
+    let postmark_api_base = reader.required("POSTMARK_API_BASE");
+    let postmark_api_key = reader.required_secret("POSTMARK_API_KEY");
+    let postmark_webhook_secret = reader.secret("POSTMARK_WEBHOOK_SECRET");
+    let username = reader.required_secret("BROWSERTRIX_USERNAME");
+    let password = reader.required_secret("BROWSERTRIX_PASSWORD");
+    let org_uuid: Uuid = reader.required_parsed("BROWSERTRIX_ORGID", "a UUID");
+    let base_url = reader.required("BROWSERTRIX_BROWSERTRIX_URL");
     let login_url = format!("{base_url}/auth/jwt/login");
     let create_crawl_url = format!("{base_url}/orgs/{org_uuid}/crawlconfigs/");
+    let jwt_cookie_domain = reader.required("JWT_COOKIE_DOMAIN");
+    let mut cors_urls = Vec::new();
+    for origin in reader
+        .required("CORS_URL")
+        .split(",")
+        .filter(|origin| !origin.is_empty())
+    {
+        reader.check_url("CORS_URL", origin, &["http", "https"]);
+        match HeaderValue::from_str(origin) {
+            Ok(origin) => cors_urls.push(origin),
+            Err(_) => reader
+                .errors
+                .push(format!("CORS_URL has an invalid origin {origin}")),
+        }
+    }
+    let listener_address = reader.required("LISTENER_ADDRESS");
+    if !listener_address.is_empty() && listener_address.parse::<SocketAddr>().is_err() {
+        reader.errors.push(format!(
+            "LISTENER_ADDRESS should be an address like 0.0.0.0:5000, got {listener_address}"
+        ));
+    }
+    let jwt_expiry_hours = reader.required_parsed("JWT_EXPIRY_HOURS", "a number of hours");
+    reader.check_positive("JWT_EXPIRY_HOURS", jwt_expiry_hours);
+    // the keys are only loaded once a token is signed or checked, so they're checked here
+    // to fail at boot rather than on the first request
+    if let Err(err) = JWTKeys::from_env() {
+        reader.errors.push(err);
+    }
+    let digital_ocean_spaces_endpoint_url = reader.required("DO_SPACES_ENDPOINT_URL");
+    let digital_ocean_spaces_bucket = reader.required("DO_SPACES_BUCKET");
+    let digital_ocean_spaces_access_key = reader.required_secret("DO_SPACES_ACCESS_KEY");
+    let digital_ocean_spaces_secret_key = reader.required_secret("DO_SPACES_SECRET_KEY");
+    let json_route_limits = RouteLimits::from_env("JSON", RouteLimits::JSON, &mut reader);
+    let bulk_route_limits = RouteLimits::from_env("BULK", RouteLimits::BULK, &mut reader);
+    let upload_route_limits = RouteLimits::from_env("UPLOAD", RouteLimits::UPLOAD, &mut reader);
+    let s3_operation_timeout = reader.parsed("S3_OPERATION_TIMEOUT", "30", "a number of seconds");
+    let s3_operation_attempt_timeout =
+        reader.parsed("S3_OPERATION_ATTEMPT_TIMEOUT", "10", "a number of seconds");
+    let s3_connect_timeout = reader.parsed("S3_CONNECT_TIMEOUT", "3", "a number of seconds");
+    let api_prefix = reader.optional("API_PREFIX").unwrap_or_default();
+    let public_api_url = reader.required("PUBLIC_API_URL");
+    let onion_url = reader.optional("ONION_URL");
+    if let Some(url) = &onion_url {
+        if let Err(err) = OnionService::from_url(url) {
+            reader.errors.push(err);
+        }
+    }
+    let pdf_renderer_url = reader.optional("PDF_RENDERER_URL");
+    let clamav_address = reader.optional("CLAMAV_ADDRESS");
+    if let Some(address) = &clamav_address {
+        reader.check_host_and_port("CLAMAV_ADDRESS", address);
+    }
+    let scan_enforcement = reader.parsed("CLAMAV_ENFORCEMENT", "block", "block or flag");
+    let s3_key_scheme = reader.parsed("S3_KEY_SCHEME", "dated", "dated or uuid");
+    let ffmpeg_path = reader.optional("FFMPEG_PATH");
+    let ytdlp_path = reader.optional("YTDLP_PATH");
+    let translation_api_url = reader.optional("TRANSLATION_API_URL");
+    let translation_api_key = reader.secret("TRANSLATION_API_KEY");
+    let ner_api_url = reader.optional("NER_API_URL");
+    let datacite_api_url = reader.optional("DATACITE_API_URL");
+    let datacite = datacite_api_url.as_ref().map(|api_url| DataCiteConfig {
+        api_url: api_url.clone(),
+        repository_id: reader.required("DATACITE_REPOSITORY_ID"),
+        password: reader.required_secret("DATACITE_PASSWORD"),
+        prefix: reader.required("DATACITE_PREFIX"),
+    });
+    let provenance_signing_key = reader.secret("PROVENANCE_SIGNING_KEY");
+    if let Some(key) = &provenance_signing_key {
+        if let Err(err) = ProvenanceSigner::from_base64_seed(key) {
+            reader.errors.push(err);
+        }
+    }
+    let timestamp_url = reader.optional("TIMESTAMP_URL");
+    let timestamp_service = reader.parsed(
+        "TIMESTAMP_SERVICE",
+        "opentimestamps",
+        "opentimestamps or rfc3161",
+    );
+    let timestamping = timestamp_url.clone().map(|url| TimestampConfig {
+        service: timestamp_service,
+        url,
+    });
+    for (name, url) in [
+        ("POSTMARK_API_BASE", Some(&postmark_api_base)),
+        ("BROWSERTRIX_BROWSERTRIX_URL", Some(&base_url)),
+        (
+            "DO_SPACES_ENDPOINT_URL",
+            Some(&digital_ocean_spaces_endpoint_url),
+        ),
+        ("PUBLIC_API_URL", Some(&public_api_url)),
+        ("PDF_RENDERER_URL", pdf_renderer_url.as_ref()),
+        ("TRANSLATION_API_URL", translation_api_url.as_ref()),
+        ("NER_API_URL", ner_api_url.as_ref()),
+        ("DATACITE_API_URL", datacite_api_url.as_ref()),
+        ("TIMESTAMP_URL", timestamp_url.as_ref()),
+    ] {
+        // missing required URLs have already been reported
+        if let Some(url) = url.filter(|url| !url.is_empty()) {
+            reader.check_url(name, url, &["http", "https"]);
+        }
+    }
+    let environment = reader
+        .optional("APP_ENVIRONMENT")
+        .unwrap_or("production".to_string());
+    let max_active_crawls: usize = reader.parsed("MAX_ACTIVE_CRAWLS", "3", "a number");
+    reader.check_positive("MAX_ACTIVE_CRAWLS", max_active_crawls as i64);
+    let ip_privacy = reader.parsed("IP_PRIVACY", "off", "off, truncate or hash");
+    let trusted_proxies = TrustedProxies {
+        hops: reader.parsed("TRUSTED_PROXY_HOPS", "0", "a number"),
+        networks: reader.parsed(
+            "TRUSTED_PROXIES",
+            "",
+            "a comma separated list of addresses and CIDR networks",
+        ),
+    };
+    if let Err(err) = trusted_proxies.check() {
+        reader.errors.push(err);
+    }
+    // containers get their hostname from the orchestrator, which tells replicas apart
+    let instance_name = reader
+        .optional("INSTANCE_NAME")
+        .or_else(|| reader.optional("HOSTNAME"))
+        .unwrap_or_else(|| format!("api-{}", &Uuid::new_v4().simple().to_string()[..8]));
+    let crawler = reader.parsed("CRAWLER", "browsertrix", "browsertrix or local");
+    let local_crawler =
+        reader
+            .optional("LOCAL_CRAWLER_COMMAND")
+            .map(|command| LocalCrawlerConfig {
+                command,
+                collections_dir: reader.required("LOCAL_CRAWLER_COLLECTIONS_DIR").into(),
+            });
+    if crawler == CrawlerBackend::Local && local_crawler.is_none() {
+        reader
+            .errors
+            .push("CRAWLER is local but LOCAL_CRAWLER_COMMAND is missing".to_string());
+    }
+    let request_log = RequestLogConfig {
+        enabled: reader.parsed("REQUEST_LOG_ENABLED", "true", "true or false"),
+        body_paths: reader
+            .optional("REQUEST_LOG_BODY_PATHS")
+            .map(|paths| {
+                paths
+                    .split(",")
+                    .map(|path| path.trim().to_string())
+                    .filter(|path| !path.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    };
     let browsertrix = BrowsertrixConfig {
         username,
         password,
@@ -65,46 +542,11 @@ pub fn build_app_config() -> AppConfig {
         login_url,
         create_crawl_url,
     };
-    let jwt_cookie_domain =
-        env::var("JWT_COOKIE_DOMAIN").expect("Missing JWT_COOKIE_DOMAIN env var");
-    let cors_urls_env_var = env::var("CORS_URL").expect("Missing CORS_URL env var");
-    let cors_urls = cors_urls_env_var
-        .split(",")
-        .map(|s| {
-            HeaderValue::from_str(s)
-                .expect("CORS_URL env var should contain comma separated origins")
-        })
-        .collect();
-    let listener_address = env::var("LISTENER_ADDRESS").expect("Missing LISTENER_ADDRESS env var");
-    let jwt_expiry_hours = env::var("JWT_EXPIRY_HOURS")
-        .expect("Missing JWT_EXPIRY_HOURS env var")
-        .parse()
-        .expect("JWT_EXPIRY_HOURS should be a number");
-    let digital_ocean_spaces_endpoint_url =
-        env::var("DO_SPACES_ENDPOINT_URL").expect("Missing DO_SPACES_ENDPOINT_URL env var");
-    let digital_ocean_spaces_bucket =
-        env::var("DO_SPACES_BUCKET").expect("Missing DO_SPACES_BUCKET env var");
-    let digital_ocean_spaces_access_key =
-        env::var("DO_SPACES_ACCESS_KEY").expect("Missing DO_SPACES_ACCESS_KEY env var");
-    let digital_ocean_spaces_secret_key =
-        env::var("DO_SPACES_SECRET_KEY").expect("Missing DO_SPACES_SECRET_KEY env var");
-    let max_file_upload_size = 200 * 1024 * 1024;
-    let s3_operation_timeout = env::var("S3_OPERATION_TIMEOUT")
-        .unwrap_or("30".to_string())
-        .parse()
-        .expect("S3_OPERATION_TIMEOUT should be a number");
-    let s3_operation_attempt_timeout = env::var("S3_OPERATION_ATTEMPT_TIMEOUT")
-        .unwrap_or("10".to_string())
-        .parse()
-        .expect("S3_OPERATION_ATTEMPT_TIMEOUT should be a number");
-    let s3_connect_timeout = env::var("S3_CONNECT_TIMEOUT")
-        .unwrap_or("3".to_string())
-        .parse()
-        .expect("S3_CONNECT_TIMEOUT should be a number");
-    let api_prefix = env::var("API_PREFIX").unwrap_or("".to_string());
-    AppConfig {
+    reader.finish(AppConfig {
         archive_sender_email,
         browsertrix,
+        crawler,
+        local_crawler,
         cors_urls,
         postgres_url,
         listener_address,
@@ -112,15 +554,201 @@ pub fn build_app_config() -> AppConfig {
         jwt_cookie_domain,
         postmark_api_base,
         postmark_api_key,
+        postmark_webhook_secret,
         digital_ocean_spaces_endpoint_url,
         digital_ocean_spaces_bucket,
         digital_ocean_spaces_access_key,
         digital_ocean_spaces_secret_key,
-        max_file_upload_size,
+        json_route_limits,
+        bulk_route_limits,
+        upload_route_limits,
         s3_operation_timeout,
         s3_operation_attempt_timeout,
         s3_connect_timeout,
         api_prefix,
+        public_api_url,
+        onion_url,
+        pdf_renderer_url,
+        clamav_address,
+        scan_enforcement,
+        s3_key_scheme,
+        ffmpeg_path,
+        ytdlp_path,
+        translation_api_url,
+        translation_api_key,
+        ner_api_url,
+        datacite,
+        provenance_signing_key,
+        timestamping,
+        environment,
+        max_active_crawls,
+        request_log,
+        ip_privacy,
+        trusted_proxies,
+        instance_name,
+    })
+}
+
+impl AppConfig {
+    /// The configuration in effect with secrets left out, for logging at boot.
+    pub fn redacted_summary(&self) -> String {
+        let secret = |value: &str| {
+            if value.is_empty() {
+                "unset".to_string()
+            } else {
+                REDACTED.to_string()
+            }
+        };
+        let optional_secret = |value: &Option<String>| secret(value.as_deref().unwrap_or(""));
+        let optional = |value: &Option<String>| value.clone().unwrap_or("unset".to_string());
+        let limits = |limits: &RouteLimits| {
+            format!("{}s, {} bytes", limits.timeout.as_secs(), limits.body_limit)
+        };
+        // Url::set_password would percent-encode the brackets around REDACTED
+        let postgres_url = match Url::parse(&self.postgres_url) {
+            Ok(url) => match url.password() {
+                Some(password) => {
+                    url.as_str()
+                        .replacen(&format!(":{password}@"), &format!(":{REDACTED}@"), 1)
+                }
+                None => url.to_string(),
+            },
+            Err(_) => secret(&self.postgres_url),
+        };
+        let cors_urls = self
+            .cors_urls
+            .iter()
+            .map(|origin| origin.to_str().unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(",");
+        let datacite = self.datacite.as_ref();
+        let timestamping = self.timestamping.as_ref();
+        let local_crawler = self.local_crawler.as_ref();
+        [
+            ("postgres_url", postgres_url),
+            ("listener_address", self.listener_address.clone()),
+            ("api_prefix", self.api_prefix.clone()),
+            ("public_api_url", self.public_api_url.clone()),
+            ("onion_url", optional(&self.onion_url)),
+            ("environment", self.environment.clone()),
+            ("instance_name", self.instance_name.clone()),
+            ("cors_urls", cors_urls),
+            ("jwt_cookie_domain", self.jwt_cookie_domain.clone()),
+            ("jwt_expiry_hours", self.jwt_expiry_hours.to_string()),
+            ("archive_sender_email", self.archive_sender_email.clone()),
+            ("postmark_api_base", self.postmark_api_base.clone()),
+            ("postmark_api_key", secret(&self.postmark_api_key)),
+            (
+                "postmark_webhook_secret",
+                optional_secret(&self.postmark_webhook_secret),
+            ),
+            ("browsertrix.base_url", self.browsertrix.base_url.clone()),
+            ("browsertrix.org_id", self.browsertrix.org_id.to_string()),
+            ("browsertrix.username", secret(&self.browsertrix.username)),
+            ("browsertrix.password", secret(&self.browsertrix.password)),
+            (
+                "do_spaces.endpoint_url",
+                self.digital_ocean_spaces_endpoint_url.clone(),
+            ),
+            ("do_spaces.bucket", self.digital_ocean_spaces_bucket.clone()),
+            (
+                "do_spaces.access_key",
+                secret(&self.digital_ocean_spaces_access_key),
+            ),
+            (
+                "do_spaces.secret_key",
+                secret(&self.digital_ocean_spaces_secret_key),
+            ),
+            (
+                "s3_timeouts",
+                format!(
+                    "{}s operation, {}s attempt, {}s connect",
+                    self.s3_operation_timeout,
+                    self.s3_operation_attempt_timeout,
+                    self.s3_connect_timeout
+                ),
+            ),
+            ("s3_key_scheme", format!("{:?}", self.s3_key_scheme)),
+            ("json_route_limits", limits(&self.json_route_limits)),
+            ("bulk_route_limits", limits(&self.bulk_route_limits)),
+            ("upload_route_limits", limits(&self.upload_route_limits)),
+            ("pdf_renderer_url", optional(&self.pdf_renderer_url)),
+            ("clamav_address", optional(&self.clamav_address)),
+            ("scan_enforcement", format!("{:?}", self.scan_enforcement)),
+            ("ffmpeg_path", optional(&self.ffmpeg_path)),
+            ("ytdlp_path", optional(&self.ytdlp_path)),
+            ("translation_api_url", optional(&self.translation_api_url)),
+            (
+                "translation_api_key",
+                optional_secret(&self.translation_api_key),
+            ),
+            ("ner_api_url", optional(&self.ner_api_url)),
+            (
+                "datacite.api_url",
+                optional(&datacite.map(|datacite| datacite.api_url.clone())),
+            ),
+            (
+                "datacite.repository_id",
+                optional(&datacite.map(|datacite| datacite.repository_id.clone())),
+            ),
+            (
+                "datacite.password",
+                optional_secret(&datacite.map(|datacite| datacite.password.clone())),
+            ),
+            (
+                "datacite.prefix",
+                optional(&datacite.map(|datacite| datacite.prefix.clone())),
+            ),
+            (
+                "provenance_signing_key",
+                optional_secret(&self.provenance_signing_key),
+            ),
+            (
+                "timestamping.service",
+                optional(&timestamping.map(|timestamping| format!("{:?}", timestamping.service))),
+            ),
+            (
+                "timestamping.url",
+                optional(&timestamping.map(|timestamping| timestamping.url.clone())),
+            ),
+            ("crawler", self.crawler.to_string()),
+            (
+                "local_crawler.command",
+                optional(&local_crawler.map(|local_crawler| local_crawler.command.clone())),
+            ),
+            (
+                "local_crawler.collections_dir",
+                optional(
+                    &local_crawler
+                        .map(|local_crawler| local_crawler.collections_dir.display().to_string()),
+                ),
+            ),
+            ("max_active_crawls", self.max_active_crawls.to_string()),
+            ("ip_privacy", format!("{:?}", self.ip_privacy)),
+            (
+                "trusted_proxies.hops",
+                self.trusted_proxies.hops.to_string(),
+            ),
+            (
+                "trusted_proxies.networks",
+                self.trusted_proxies
+                    .networks
+                    .0
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            ("request_log.enabled", self.request_log.enabled.to_string()),
+            (
+                "request_log.body_paths",
+                self.request_log.body_paths.join(","),
+            ),
+        ]
+        .into_iter()
+        .map(|(name, value)| format!("{name} = {value}"))
+        .collect::<Vec<_>>()
+        .join("\n")
     }
 }
 
@@ -195,14 +823,9 @@ impl BrowsertrixCrawlConfig {
             exclude: vec![],
             behaviors: "autoscroll,autoplay,autofetch,siteSpecific".to_string(),
         };
-        let mut profileid = "".to_string();
-        if let Some(browser_profile_name) = browser_profile {
-            // profile ids here are from Browsertrix API
-            // to get them you need to do list profiles
-            profileid = match browser_profile_name {
-                BrowserProfile::Facebook => "b1cd3192-a554-41e1-9509-0cbff3b3df16".to_string(),
-            };
-        }
+        let profileid = browser_profile
+            .map(|profile| profile.profile_id().to_string())
+            .unwrap_or_default();
         BrowsertrixCrawlConfig {
             job_type: "custom".to_string(),
             name: "".to_string(),
@@ -238,4 +861,112 @@ mod tests {
         assert_eq!(config2.config.seeds[0].url, "https://different.com");
         assert_ne!(config1.config.seeds[0].url, config2.config.seeds[0].url);
     }
+
+    #[test]
+    fn test_route_limits_from_env() {
+        env::set_var("TEST_LIMITS_REQUEST_TIMEOUT", "5");
+        let mut reader = ConfigReader::default();
+        let limits = RouteLimits::from_env("TEST_LIMITS", RouteLimits::UPLOAD, &mut reader);
+        assert_eq!(limits.timeout, Duration::from_secs(5));
+        assert_eq!(limits.body_limit, RouteLimits::UPLOAD.body_limit);
+        assert!(reader.errors.is_empty());
+    }
+
+    #[test]
+    fn collects_every_config_error() {
+        env::set_var("TEST_CONFIG_NUMBER", "ten");
+        let mut reader = ConfigReader::default();
+        let number: u64 = reader.parsed("TEST_CONFIG_NUMBER", "3", "a number");
+        let missing = reader.required("TEST_CONFIG_MISSING");
+        reader.check_url("TEST_CONFIG_URL", "not a url", &["http", "https"]);
+        reader.check_url("TEST_CONFIG_DB", "mysql://db/archive", &["postgres"]);
+        reader.check_host_and_port("TEST_CONFIG_ADDRESS", "clamav");
+        assert_eq!((number, missing.as_str()), (0, ""));
+
+        let errors = reader.finish(AppConfig::default()).unwrap_err();
+        assert_eq!(
+            errors.0,
+            vec![
+                "TEST_CONFIG_NUMBER should be a number, got ten",
+                "TEST_CONFIG_MISSING is missing",
+                "TEST_CONFIG_URL should be a URL: relative URL without a base",
+                "TEST_CONFIG_DB should be a postgres URL, got a mysql one",
+                "TEST_CONFIG_ADDRESS should be host:port, got clamav",
+            ]
+        );
+        assert!(errors
+            .to_string()
+            .starts_with("Invalid configuration:\n  - TEST_CONFIG_NUMBER"));
+    }
+
+    #[test]
+    fn summarizes_config_without_secrets() {
+        let config = AppConfig {
+            postgres_url: "postgres://archivist:hunter2@db:5432/archive".to_string(),
+            postmark_api_key: "postmark-key".to_string(),
+            digital_ocean_spaces_secret_key: "spaces-secret".to_string(),
+            translation_api_url: Some("https://translate.example.com".to_string()),
+            datacite: Some(DataCiteConfig {
+                api_url: "https://api.test.datacite.org".to_string(),
+                repository_id: "SDA.ARCHIVE".to_string(),
+                password: "datacite-password".to_string(),
+                prefix: "10.1234".to_string(),
+            }),
+            provenance_signing_key: Some(
+                "nWGxne/9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A=".to_string(),
+            ),
+            timestamping: Some(TimestampConfig {
+                service: TimestampService::Rfc3161,
+                url: "https://freetsa.org/tsr".to_string(),
+            }),
+            ..Default::default()
+        };
+        let summary = config.redacted_summary();
+        assert!(!summary.contains("hunter2"));
+        assert!(!summary.contains("postmark-key"));
+        assert!(!summary.contains("spaces-secret"));
+        assert!(!summary.contains("datacite-password"));
+        assert!(!summary.contains("nWGxne"));
+        let lines: Vec<&str> = summary.lines().collect();
+        assert!(lines.contains(&"postgres_url = postgres://archivist:[redacted]@db:5432/archive"));
+        assert!(lines.contains(&"postmark_api_key = [redacted]"));
+        assert!(lines.contains(&"postmark_webhook_secret = unset"));
+        assert!(lines.contains(&"translation_api_url = https://translate.example.com"));
+        assert!(lines.contains(&"datacite.prefix = 10.1234"));
+        assert!(lines.contains(&"timestamping.service = Rfc3161"));
+    }
+
+    #[test]
+    fn reads_secrets_from_files() {
+        let path = env::temp_dir().join(format!("{}.secret", Uuid::new_v4()));
+        fs::write(&path, "from-a-file\n").unwrap();
+        env::set_var("TEST_FILE_SECRET_FILE", &path);
+        assert_eq!(
+            secret_var("TEST_FILE_SECRET"),
+            Some("from-a-file".to_string())
+        );
+        fs::remove_file(path).unwrap();
+
+        env::set_var("TEST_ENV_SECRET", "from-the-env");
+        assert_eq!(
+            secret_var("TEST_ENV_SECRET"),
+            Some("from-the-env".to_string())
+        );
+        assert_eq!(secret_var("TEST_UNSET_SECRET"), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only one of TEST_BOTH_SECRET and TEST_BOTH_SECRET_FILE")]
+    fn refuses_secrets_set_twice() {
+        env::set_var("TEST_BOTH_SECRET", "one");
+        env::set_var("TEST_BOTH_SECRET_FILE", "/run/secrets/two");
+        secret_var("TEST_BOTH_SECRET");
+    }
+
+    #[test]
+    fn test_scan_enforcement_from_str() {
+        assert_eq!("block".parse(), Ok(ScanEnforcement::Block));
+        assert_eq!("FLAG".parse(), Ok(ScanEnforcement::Flag));
+        assert!("ignore".parse::<ScanEnforcement>().is_err());
+    }
 }
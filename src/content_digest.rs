@@ -0,0 +1,195 @@
+//! Digests of what a capture shows, to find the same content captured from different URLs.
+//!
+//! Mirrors and AMP pages serve the same article under other URLs, so the same content gets
+//! archived more than once. A WACZ's own hash can't tell, since it covers capture times,
+//! request headers and every ad and tracker the page loaded. The content digest only covers
+//! the key payloads of a capture, its pages:
+//!
+//! * The text the crawler extracted from a page, with whitespace collapsed, when
+//!   `pages/pages.jsonl` has it. Hosted Browsertrix extracts text by default, and it is the
+//!   same for an AMP page and the article it mirrors.
+//! * Otherwise the payload digest the CDXJ index in `indexes/` records for the page's
+//!   response, which is the same for byte identical mirrors.
+//!
+//! The keys of a capture's pages are sorted so page order doesn't matter and hashed together,
+//! leaving URLs out. Pages with neither, like redirects, don't count.
+
+use crate::repos::s3_repo::S3Repo;
+use crate::wacz::{read_wacz_entries, PAGES_ENTRY_NAME};
+use flate2::read::MultiGzDecoder;
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::Read;
+
+/// Path of the CDXJ index inside a WACZ.
+const CDX_ENTRY_NAME: &str = "indexes/index.cdx";
+/// Path of the CDXJ index when the crawler gzipped it in blocks, for large crawls.
+const GZIPPED_CDX_ENTRY_NAME: &str = "indexes/index.cdx.gz";
+/// Refuse to decompress indices larger than this to protect our memory budget.
+const MAX_CDX_SIZE: u64 = 256 * 1024 * 1024;
+
+/// A line of `pages.jsonl`, with the text the crawler extracted if it did.
+#[derive(Debug, Deserialize)]
+struct PageText {
+    url: String,
+    text: Option<String>,
+}
+
+/// The parts of a CDXJ index line the digest needs.
+#[derive(Debug, Deserialize)]
+struct CdxRecord {
+    url: String,
+    mime: Option<String>,
+    status: Option<Value>,
+    digest: Option<String>,
+}
+
+impl CdxRecord {
+    /// Whether the record is a response the page was served with, rather than a redirect,
+    /// an error or a revisit of a response recorded earlier.
+    fn is_payload(&self) -> bool {
+        let status = match &self.status {
+            Some(Value::String(status)) => status.parse().ok(),
+            Some(Value::Number(status)) => status.as_u64(),
+            _ => None,
+        };
+        matches!(status, Some(200..=299)) && self.mime.as_deref() != Some("warc/revisit")
+    }
+}
+
+/// Parses a CDXJ index into the payload digests of each URL's responses.
+fn payload_digests(cdx: &str) -> HashMap<String, String> {
+    cdx.lines()
+        .filter_map(|line| line.splitn(3, ' ').nth(2))
+        .filter_map(|json| serde_json::from_str::<CdxRecord>(json).ok())
+        .filter(CdxRecord::is_payload)
+        .filter_map(|record| Some((record.url, record.digest?)))
+        .collect()
+}
+
+/// Computes the content digest of a WACZ, see the module docs.
+///
+/// # Arguments
+/// * `pages_jsonl` - Contents of the WACZ's `pages/pages.jsonl`
+/// * `cdx` - Contents of its CDXJ index, decompressed, if it has one
+///
+/// # Returns
+/// The digest prefixed with `sha256:`, or `None` if no page had text or a payload
+pub fn content_digest(pages_jsonl: &str, cdx: Option<&str>) -> Option<String> {
+    let payload_digests = cdx.map(payload_digests).unwrap_or_default();
+    let mut keys: Vec<String> = pages_jsonl
+        .lines()
+        .filter_map(|line| serde_json::from_str::<PageText>(line).ok())
+        .filter_map(|page| {
+            let text = page
+                .text
+                .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|text| !text.is_empty());
+            match text {
+                Some(text) => Some(format!("text:{:x}", Sha256::digest(text))),
+                None => payload_digests
+                    .get(&page.url)
+                    .map(|digest| format!("payload:{digest}")),
+            }
+        })
+        .collect();
+    if keys.is_empty() {
+        return None;
+    }
+    keys.sort();
+    keys.dedup();
+    Some(format!("sha256:{:x}", Sha256::digest(keys.join("\n"))))
+}
+
+/// Computes the content digest of a WACZ stored in S3.
+///
+/// # Arguments
+/// * `s3_repo` - Storage containing the WACZ
+/// * `key` - The WACZ object key
+///
+/// # Returns
+/// The digest, see [`content_digest`]
+///
+/// # Errors
+/// Returns Error if the object is not a valid WACZ, has no pages index or cannot be read
+pub async fn read_wacz_content_digest(
+    s3_repo: &dyn S3Repo,
+    key: &str,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let [pages, cdx, gzipped_cdx] = read_wacz_entries(
+        s3_repo,
+        key,
+        [PAGES_ENTRY_NAME, CDX_ENTRY_NAME, GZIPPED_CDX_ENTRY_NAME],
+    )
+    .await?;
+    let pages = pages.ok_or("WACZ has no pages index")?;
+    let cdx = match (cdx, gzipped_cdx) {
+        (Some(cdx), _) => Some(cdx),
+        (None, Some(gzipped_cdx)) => {
+            let mut cdx = Vec::new();
+            MultiGzDecoder::new(gzipped_cdx.as_slice())
+                .take(MAX_CDX_SIZE)
+                .read_to_end(&mut cdx)?;
+            Some(cdx)
+        }
+        (None, None) => None,
+    };
+    Ok(content_digest(
+        &String::from_utf8_lossy(&pages),
+        cdx.as_deref().map(String::from_utf8_lossy).as_deref(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const HEADER: &str = "{\"format\": \"json-pages-1.0\", \"id\": \"pages\"}";
+
+    #[test]
+    fn matches_mirrors_by_page_text() {
+        let article = format!(
+            "{HEADER}\n{{\"url\": \"https://sudantribune.com/article/1\", \"text\": \"Market fire\\n in Omdurman\"}}\n"
+        );
+        let amp = format!(
+            "{HEADER}\n{{\"url\": \"https://sudantribune.com/amp/article/1\", \"text\": \"Market fire in   Omdurman \"}}\n"
+        );
+        let other = format!(
+            "{HEADER}\n{{\"url\": \"https://sudantribune.com/article/2\", \"text\": \"Ceasefire talks in Jeddah\"}}\n"
+        );
+        let digest = content_digest(&article, None);
+        assert!(digest.as_deref().unwrap().starts_with("sha256:"));
+        assert_eq!(content_digest(&amp, None), digest);
+        assert_ne!(content_digest(&other, None), digest);
+    }
+
+    #[test]
+    fn falls_back_to_payload_digests() {
+        let pages = format!("{HEADER}\n{{\"url\": \"https://mirror.example/article\"}}\n");
+        let cdx = "example,mirror)/article 20240101000000 {\"url\": \"https://mirror.example/article\", \"mime\": \"text/html\", \"status\": \"200\", \"digest\": \"sha256:abc\"}\n\
+                   example,mirror)/ad.js 20240101000001 {\"url\": \"https://mirror.example/ad.js\", \"mime\": \"text/javascript\", \"status\": 200, \"digest\": \"sha256:def\"}\n";
+        let other_cdx = cdx.replace("https://mirror.example/ad.js", "https://ads.example/ad.js");
+        let digest = content_digest(&pages, Some(cdx));
+        assert!(digest.is_some());
+        // only the pages' payloads count
+        assert_eq!(content_digest(&pages, Some(&other_cdx)), digest);
+        assert_ne!(
+            content_digest(&pages, Some(&cdx.replace("sha256:abc", "sha256:xyz"))),
+            digest
+        );
+    }
+
+    #[test]
+    fn skips_redirects_and_revisits() {
+        let pages = format!("{HEADER}\n{{\"url\": \"https://example.com/\"}}\n");
+        let redirect = "com,example)/ 20240101000000 {\"url\": \"https://example.com/\", \"status\": \"301\", \"digest\": \"sha256:abc\"}";
+        let revisit = "com,example)/ 20240101000000 {\"url\": \"https://example.com/\", \"mime\": \"warc/revisit\", \"status\": \"200\", \"digest\": \"sha256:abc\"}";
+        assert_eq!(content_digest(&pages, Some(redirect)), None);
+        assert_eq!(content_digest(&pages, Some(revisit)), None);
+        assert_eq!(content_digest(&pages, None), None);
+    }
+}
@@ -0,0 +1,151 @@
+//! Matching URLs against the global crawl blocklist.
+//!
+//! Admins keep a list of URL patterns that must never be crawled, for legal or safety
+//! reasons, see [`crate::routes::crawl_blocklist`]. A pattern is a host, optionally followed
+//! by a path prefix:
+//!
+//! * `example.com` blocks every page on `example.com`
+//! * `*.example.com` also blocks its subdomains, e.g. `news.example.com`
+//! * `example.com/private` only blocks paths starting with `/private`
+//!
+//! Schemes and a leading `www.` are ignored on both sides, so `https://www.example.com/`
+//! and `example.com` are the same pattern. Hosts are compared case insensitively, paths
+//! are not.
+
+use entity::crawl_blocklist::Model as CrawlBlocklistModel;
+use reqwest::Url;
+
+/// Strips what patterns and URLs may differ by without meaning a different site.
+fn strip_www(host: &str) -> &str {
+    host.strip_prefix("www.").unwrap_or(host)
+}
+
+/// Returns the normalized form of a blocklist pattern, or `None` if it isn't one.
+///
+/// # Arguments
+/// * `pattern` - Pattern as typed by an admin, possibly with a scheme or trailing slash
+pub fn normalize_pattern(pattern: &str) -> Option<String> {
+    let pattern = pattern.trim();
+    let pattern = ["https://", "http://"]
+        .iter()
+        .find_map(|scheme| pattern.strip_prefix(scheme))
+        .unwrap_or(pattern);
+    let (host, path) = match pattern.find('/') {
+        Some(slash) => pattern.split_at(slash),
+        None => (pattern, ""),
+    };
+    let host = host.to_lowercase();
+    let (wildcard, bare_host) = match host.strip_prefix("*.") {
+        Some(bare_host) => ("*.", bare_host),
+        None => ("", host.as_str()),
+    };
+    let bare_host = strip_www(bare_host);
+    let valid_host = !bare_host.is_empty()
+        && !bare_host.starts_with('.')
+        && !bare_host.ends_with('.')
+        && bare_host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    if !valid_host || path.contains(char::is_whitespace) {
+        return None;
+    }
+    let path = if path == "/" { "" } else { path };
+    Some(format!("{wildcard}{bare_host}{path}"))
+}
+
+/// Whether a URL is blocked by a normalized pattern, see [`normalize_pattern`].
+///
+/// URLs that cannot be parsed never match, since request validation rejects them.
+pub fn pattern_matches(pattern: &str, url: &str) -> bool {
+    let Ok(parsed) = Url::parse(url.trim()) else {
+        return false;
+    };
+    let Some(url_host) = parsed.host_str() else {
+        return false;
+    };
+    let url_host = strip_www(url_host);
+    let (pattern_host, pattern_path) = match pattern.find('/') {
+        Some(slash) => pattern.split_at(slash),
+        None => (pattern, ""),
+    };
+    let host_matches = match pattern_host.strip_prefix("*.") {
+        Some(domain) => {
+            url_host == domain
+                || url_host
+                    .strip_suffix(domain)
+                    .is_some_and(|subdomain| subdomain.ends_with('.'))
+        }
+        None => url_host == pattern_host,
+    };
+    host_matches && parsed.path().starts_with(pattern_path)
+}
+
+/// Finds the first blocklist entry blocking a URL.
+///
+/// # Arguments
+/// * `entries` - The blocklist
+/// * `url` - URL about to be crawled
+pub fn find_blocking_entry<'a>(
+    entries: &'a [CrawlBlocklistModel],
+    url: &str,
+) -> Option<&'a CrawlBlocklistModel> {
+    entries
+        .iter()
+        .find(|entry| pattern_matches(&entry.pattern, url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn normalizes_patterns() {
+        assert_eq!(
+            normalize_pattern(" https://www.Example.com/ "),
+            Some("example.com".to_string())
+        );
+        assert_eq!(
+            normalize_pattern("*.Example.com/Private"),
+            Some("*.example.com/Private".to_string())
+        );
+        assert_eq!(normalize_pattern(""), None);
+        assert_eq!(normalize_pattern("/private"), None);
+        assert_eq!(normalize_pattern("exa mple.com"), None);
+        assert_eq!(normalize_pattern("example.*"), None);
+    }
+
+    #[test]
+    fn matches_hosts_and_subdomains() {
+        assert!(pattern_matches(
+            "example.com",
+            "https://www.EXAMPLE.com/story"
+        ));
+        assert!(!pattern_matches(
+            "example.com",
+            "https://news.example.com/story"
+        ));
+        assert!(pattern_matches(
+            "*.example.com",
+            "https://news.example.com/story"
+        ));
+        assert!(pattern_matches("*.example.com", "http://example.com"));
+        assert!(!pattern_matches(
+            "*.example.com",
+            "https://notexample.com/story"
+        ));
+        assert!(!pattern_matches("example.com", "not a url"));
+    }
+
+    #[test]
+    fn matches_path_prefixes() {
+        assert!(pattern_matches(
+            "example.com/private",
+            "https://example.com/private/report?id=1"
+        ));
+        assert!(!pattern_matches(
+            "example.com/private",
+            "https://example.com/public/report"
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+//! Limits how many crawls run in Browsertrix at once.
+//!
+//! Browsertrix only has capacity for a handful of crawls, and a burst of submissions
+//! overwhelms it. Every crawl task waits its turn in a [`CrawlQueue`] before launching
+//! and holds its place until it has finished, so at most `max_active` crawls are ever
+//! launched or being polled. Waiting crawls are let in first come first served, and their
+//! positions show up in the admin pipeline status. The queue lives in memory, so crawls
+//! still waiting when the server restarts are lost, the same as crawls being polled.
+
+use chrono::{NaiveDateTime, Utc};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use uuid::Uuid;
+
+/// A crawl waiting for its turn to launch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedCrawl {
+    pub ticket: Uuid,
+    pub url: String,
+    pub queued_at: NaiveDateTime,
+}
+
+/// Point in time view of the queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrawlQueueSnapshot {
+    pub max_active: usize,
+    /// Waiting crawls, next to launch first
+    pub queued: Vec<QueuedCrawl>,
+}
+
+/// First come first served limit on the number of active crawls.
+#[derive(Debug)]
+pub struct CrawlQueue {
+    max_active: usize,
+    permits: Arc<Semaphore>,
+    waiting: Mutex<VecDeque<QueuedCrawl>>,
+}
+
+pub type SharedCrawlQueue = Arc<CrawlQueue>;
+
+/// Creates a queue letting `max_active` crawls run at once to share between services.
+pub fn new_crawl_queue(max_active: usize) -> SharedCrawlQueue {
+    Arc::new(CrawlQueue::new(max_active))
+}
+
+impl CrawlQueue {
+    pub fn new(max_active: usize) -> Self {
+        Self {
+            max_active,
+            permits: Arc::new(Semaphore::new(max_active)),
+            waiting: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Waits until fewer than `max_active` crawls are running.
+    ///
+    /// The crawl counts as active until the returned permit is dropped.
+    pub async fn wait_for_turn(&self, url: &str) -> OwnedSemaphorePermit {
+        let ticket = Uuid::new_v4();
+        self.waiting.lock().unwrap().push_back(QueuedCrawl {
+            ticket,
+            url: url.to_string(),
+            queued_at: Utc::now().naive_utc(),
+        });
+        // tokio's semaphore is fair, so crawls get in in the order they queued up
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("Crawl queue semaphore is never closed");
+        self.waiting
+            .lock()
+            .unwrap()
+            .retain(|crawl| crawl.ticket != ticket);
+        permit
+    }
+
+    pub fn snapshot(&self) -> CrawlQueueSnapshot {
+        CrawlQueueSnapshot {
+            max_active: self.max_active,
+            queued: self.waiting.lock().unwrap().iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn queues_crawls_over_the_limit_in_order() {
+        let queue = new_crawl_queue(1);
+        let first = queue.wait_for_turn("https://example.com/1").await;
+
+        let waiting = queue.clone();
+        let second =
+            tokio::spawn(async move { waiting.wait_for_turn("https://example.com/2").await });
+        tokio::task::yield_now().await;
+        let waiting = queue.clone();
+        let third =
+            tokio::spawn(async move { waiting.wait_for_turn("https://example.com/3").await });
+        tokio::task::yield_now().await;
+
+        let snapshot = queue.snapshot();
+        assert_eq!(queue.permits.available_permits(), 0);
+        let urls: Vec<&str> = snapshot.queued.iter().map(|c| c.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://example.com/2", "https://example.com/3"]);
+
+        drop(first);
+        let second = timeout(Duration::from_secs(1), second)
+            .await
+            .unwrap()
+            .unwrap();
+        let snapshot = queue.snapshot();
+        assert_eq!(queue.permits.available_permits(), 0);
+        assert_eq!(snapshot.queued.len(), 1);
+        assert_eq!(snapshot.queued[0].url, "https://example.com/3");
+
+        drop(second);
+        drop(
+            timeout(Duration::from_secs(1), third)
+                .await
+                .unwrap()
+                .unwrap(),
+        );
+        assert_eq!(queue.permits.available_permits(), 1);
+    }
+}
@@ -0,0 +1,570 @@
+//! The steps of archiving a URL with a crawler, as an explicit state machine.
+//!
+//! Creating an accession from a crawl launches the crawl, polls it until it completes,
+//! downloads its archive, uploads that to S3, writes the accession and then tells whoever
+//! asked for it. [`CrawlStateMachine`] moves through those as [`CrawlState`]s, one transition
+//! per [`CrawlStateMachine::step`], and leaves the side effects of each to a [`CrawlSteps`]
+//! implementation. That keeps the order of the steps, when to give up and what counts as a
+//! failure in one place that can be tested without a crawler, S3 or a database. Crawlers
+//! themselves plug in through [`crate::repos::crawler_repo::CrawlerRepo`].
+
+use async_trait::async_trait;
+use entity::sea_orm_active_enums::DublinMetadataFormat;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Times a crawl's status is checked before it is given up on
+pub const MAX_POLLS: u32 = 31;
+/// How long to wait between status checks, so crawls get about half an hour to complete
+pub const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A crawl the crawler has accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchedCrawl {
+    pub id: Uuid,
+    /// ID of the job running the crawl, which its archive is downloaded by
+    pub job_run_id: String,
+    /// Browsertrix organization the crawl was made in, stored with the accession so it can be
+    /// replayed from Browsertrix. `None` for crawls made elsewhere.
+    pub org_id: Option<Uuid>,
+}
+
+/// A crawl's archive once it is stored in S3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredCapture {
+    pub s3_filename: String,
+    /// Whether the archive is a WACZ, a WARC or a downloaded video
+    pub format: DublinMetadataFormat,
+    /// Hex SHA-256 of the archive, for provenance
+    pub sha256: String,
+    /// Why the capture looks bad, if it does, see [`crate::capture_quality`]
+    pub quality_reason: Option<String>,
+}
+
+/// What checking on a running crawl found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollOutcome {
+    Complete,
+    Running,
+    /// The status couldn't be read, so it's checked again after the usual wait
+    Errored,
+}
+
+/// Why a crawl didn't make it into the archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrawlError {
+    LaunchFailed,
+    /// The crawl didn't complete within [`MAX_POLLS`] checks. It may still be running.
+    TimedOut,
+    DownloadFailed,
+    UploadFailed,
+    WriteFailed,
+}
+
+impl CrawlError {
+    /// Reason shown to admins in the pipeline status.
+    pub fn reason(self) -> &'static str {
+        match self {
+            CrawlError::LaunchFailed => "Error occurred launching crawl",
+            CrawlError::TimedOut => "Crawl did not complete within polling window",
+            CrawlError::DownloadFailed => "Error occurred downloading WACZ file",
+            CrawlError::UploadFailed => "Error occurred uploading WACZ file to S3",
+            CrawlError::WriteFailed => "Error occurred writing crawl result to db",
+        }
+    }
+}
+
+/// Where a crawl is in being archived. `W` is the downloaded archive the crawler hands over.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrawlState<W> {
+    Launching,
+    /// Waiting on the crawl to complete, having checked on it `polls` times so far
+    Polling {
+        crawl: LaunchedCrawl,
+        polls: u32,
+    },
+    Downloading {
+        crawl: LaunchedCrawl,
+    },
+    Uploading {
+        crawl: LaunchedCrawl,
+        wacz: W,
+    },
+    Writing {
+        crawl: LaunchedCrawl,
+        capture: StoredCapture,
+    },
+    Notifying {
+        crawl: LaunchedCrawl,
+        accession_id: i32,
+        capture: StoredCapture,
+    },
+    /// The accession was written and whoever asked for it told
+    Done,
+    Failed {
+        /// `None` when the crawler never accepted the crawl
+        crawl_id: Option<Uuid>,
+        error: CrawlError,
+    },
+}
+
+impl<W> CrawlState<W> {
+    /// Whether there are no more steps to take.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, CrawlState::Done | CrawlState::Failed { .. })
+    }
+}
+
+/// The side effects of archiving one crawl.
+///
+/// Implementations log and record metrics for their own steps; failures are reported once,
+/// through [`CrawlSteps::failed`].
+#[async_trait]
+pub trait CrawlSteps: Send + Sync {
+    /// The crawl's archive as downloaded, before it is stored
+    type Wacz: Send;
+
+    /// Asks the crawler to start crawling.
+    async fn launch(&self) -> Option<LaunchedCrawl>;
+
+    /// Checks whether the crawl has completed.
+    ///
+    /// # Arguments
+    /// * `crawl` - The crawl to check on
+    /// * `poll` - Which check this is, starting from 1
+    async fn poll(&self, crawl: &LaunchedCrawl, poll: u32) -> PollOutcome;
+
+    /// Waits before checking on the crawl again.
+    async fn wait(&self);
+
+    /// Downloads the completed crawl's archive.
+    async fn download(&self, crawl: &LaunchedCrawl) -> Option<Self::Wacz>;
+
+    /// Stores the archive and assesses its quality.
+    async fn upload(&self, wacz: Self::Wacz) -> Option<StoredCapture>;
+
+    /// Writes a stored capture to the accession the crawl was requested as.
+    ///
+    /// # Returns
+    /// The accession's ID
+    async fn write(&self, crawl: &LaunchedCrawl, capture: &StoredCapture) -> Option<i32>;
+
+    /// Tells whoever asked for the accession that it's archived and does anything else that
+    /// follows from it being written. Nothing here can fail the crawl.
+    async fn notify(&self, crawl: &LaunchedCrawl, accession_id: i32, capture: StoredCapture);
+
+    /// Records why the crawl didn't make it into the archive.
+    async fn failed(&self, crawl_id: Option<Uuid>, error: CrawlError);
+}
+
+/// Drives a crawl from launch to the archive, see the module docs.
+pub struct CrawlStateMachine<S: CrawlSteps> {
+    steps: S,
+}
+
+impl<S: CrawlSteps> CrawlStateMachine<S> {
+    pub fn new(steps: S) -> Self {
+        Self { steps }
+    }
+
+    /// Takes the step `state` calls for.
+    ///
+    /// # Returns
+    /// The state the crawl is in afterwards. Finished states are returned as they are.
+    pub async fn step(&self, state: CrawlState<S::Wacz>) -> CrawlState<S::Wacz> {
+        match state {
+            CrawlState::Launching => match self.steps.launch().await {
+                Some(crawl) => CrawlState::Polling { crawl, polls: 0 },
+                None => CrawlState::Failed {
+                    crawl_id: None,
+                    error: CrawlError::LaunchFailed,
+                },
+            },
+            CrawlState::Polling { crawl, polls } if polls >= MAX_POLLS => CrawlState::Failed {
+                crawl_id: Some(crawl.id),
+                error: CrawlError::TimedOut,
+            },
+            CrawlState::Polling { crawl, polls } => {
+                let polls = polls + 1;
+                match self.steps.poll(&crawl, polls).await {
+                    PollOutcome::Complete => CrawlState::Downloading { crawl },
+                    PollOutcome::Running | PollOutcome::Errored => {
+                        self.steps.wait().await;
+                        CrawlState::Polling { crawl, polls }
+                    }
+                }
+            }
+            CrawlState::Downloading { crawl } => match self.steps.download(&crawl).await {
+                Some(wacz) => CrawlState::Uploading { crawl, wacz },
+                None => CrawlState::Failed {
+                    crawl_id: Some(crawl.id),
+                    error: CrawlError::DownloadFailed,
+                },
+            },
+            CrawlState::Uploading { crawl, wacz } => match self.steps.upload(wacz).await {
+                Some(capture) => CrawlState::Writing { crawl, capture },
+                None => CrawlState::Failed {
+                    crawl_id: Some(crawl.id),
+                    error: CrawlError::UploadFailed,
+                },
+            },
+            CrawlState::Writing { crawl, capture } => {
+                match self.steps.write(&crawl, &capture).await {
+                    Some(accession_id) => CrawlState::Notifying {
+                        crawl,
+                        accession_id,
+                        capture,
+                    },
+                    None => CrawlState::Failed {
+                        crawl_id: Some(crawl.id),
+                        error: CrawlError::WriteFailed,
+                    },
+                }
+            }
+            CrawlState::Notifying {
+                crawl,
+                accession_id,
+                capture,
+            } => {
+                self.steps.notify(&crawl, accession_id, capture).await;
+                CrawlState::Done
+            }
+            finished @ (CrawlState::Done | CrawlState::Failed { .. }) => finished,
+        }
+    }
+
+    /// Takes every step from launching the crawl until it is archived or has failed, reporting
+    /// failures through [`CrawlSteps::failed`].
+    ///
+    /// # Returns
+    /// The state the crawl finished in
+    pub async fn run(&self) -> CrawlState<S::Wacz> {
+        let mut state = CrawlState::Launching;
+        while !state.is_finished() {
+            state = self.step(state).await;
+        }
+        if let CrawlState::Failed { crawl_id, error } = &state {
+            self.steps.failed(*crawl_id, *error).await;
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::sync::Mutex;
+
+    /// Steps that succeed unless told otherwise, recording what was done.
+    #[derive(Default)]
+    struct FakeSteps {
+        launch_fails: bool,
+        /// Polls answered before the crawl completes; it never does if `None`
+        polls_until_complete: Option<u32>,
+        poll_errors: bool,
+        download_fails: bool,
+        upload_fails: bool,
+        write_fails: bool,
+        log: Mutex<Vec<String>>,
+    }
+
+    impl FakeSteps {
+        fn record(&self, step: impl Into<String>) {
+            self.log.lock().unwrap().push(step.into());
+        }
+
+        fn log(&self) -> Vec<String> {
+            self.log.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl CrawlSteps for FakeSteps {
+        type Wacz = String;
+
+        async fn launch(&self) -> Option<LaunchedCrawl> {
+            self.record("launch");
+            (!self.launch_fails).then(crawl)
+        }
+
+        async fn poll(&self, _crawl: &LaunchedCrawl, poll: u32) -> PollOutcome {
+            self.record(format!("poll {poll}"));
+            match self.polls_until_complete {
+                Some(complete_at) if poll >= complete_at => PollOutcome::Complete,
+                _ if self.poll_errors => PollOutcome::Errored,
+                _ => PollOutcome::Running,
+            }
+        }
+
+        async fn wait(&self) {
+            self.record("wait");
+        }
+
+        async fn download(&self, crawl: &LaunchedCrawl) -> Option<String> {
+            self.record("download");
+            (!self.download_fails).then(|| format!("{}.wacz", crawl.job_run_id))
+        }
+
+        async fn upload(&self, wacz: String) -> Option<StoredCapture> {
+            self.record(format!("upload {wacz}"));
+            (!self.upload_fails).then(capture)
+        }
+
+        async fn write(&self, _crawl: &LaunchedCrawl, _capture: &StoredCapture) -> Option<i32> {
+            self.record("write");
+            (!self.write_fails).then_some(7)
+        }
+
+        async fn notify(&self, _crawl: &LaunchedCrawl, accession_id: i32, _: StoredCapture) {
+            self.record(format!("notify {accession_id}"));
+        }
+
+        async fn failed(&self, _crawl_id: Option<Uuid>, error: CrawlError) {
+            self.record(format!("failed {error:?}"));
+        }
+    }
+
+    fn crawl() -> LaunchedCrawl {
+        LaunchedCrawl {
+            id: Uuid::nil(),
+            job_run_id: "job".to_string(),
+            org_id: None,
+        }
+    }
+
+    fn capture() -> StoredCapture {
+        StoredCapture {
+            s3_filename: "job.wacz".to_string(),
+            format: DublinMetadataFormat::Wacz,
+            sha256: "abc".to_string(),
+            quality_reason: None,
+        }
+    }
+
+    fn build_machine(steps: FakeSteps) -> CrawlStateMachine<FakeSteps> {
+        CrawlStateMachine::new(steps)
+    }
+
+    #[tokio::test]
+    async fn launching_starts_polling() {
+        let machine = build_machine(FakeSteps::default());
+        assert_eq!(
+            machine.step(CrawlState::Launching).await,
+            CrawlState::Polling {
+                crawl: crawl(),
+                polls: 0
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn failing_to_launch_has_no_crawl() {
+        let machine = build_machine(FakeSteps {
+            launch_fails: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            machine.step(CrawlState::Launching).await,
+            CrawlState::Failed {
+                crawl_id: None,
+                error: CrawlError::LaunchFailed
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn polling_waits_until_complete() {
+        let machine = build_machine(FakeSteps {
+            polls_until_complete: Some(2),
+            ..Default::default()
+        });
+        let state = machine
+            .step(CrawlState::Polling {
+                crawl: crawl(),
+                polls: 0,
+            })
+            .await;
+        assert_eq!(
+            state,
+            CrawlState::Polling {
+                crawl: crawl(),
+                polls: 1
+            }
+        );
+        assert_eq!(
+            machine.step(state).await,
+            CrawlState::Downloading { crawl: crawl() }
+        );
+        assert_eq!(machine.steps.log(), vec!["poll 1", "wait", "poll 2"]);
+    }
+
+    #[tokio::test]
+    async fn poll_errors_are_retried() {
+        let machine = build_machine(FakeSteps {
+            poll_errors: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            machine
+                .step(CrawlState::Polling {
+                    crawl: crawl(),
+                    polls: 3,
+                })
+                .await,
+            CrawlState::Polling {
+                crawl: crawl(),
+                polls: 4
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn polling_gives_up_after_the_last_poll() {
+        let machine = build_machine(FakeSteps::default());
+        assert_eq!(
+            machine
+                .step(CrawlState::Polling {
+                    crawl: crawl(),
+                    polls: MAX_POLLS,
+                })
+                .await,
+            CrawlState::Failed {
+                crawl_id: Some(crawl().id),
+                error: CrawlError::TimedOut
+            }
+        );
+        assert!(machine.steps.log().is_empty());
+    }
+
+    #[tokio::test]
+    async fn downloading_hands_the_wacz_to_uploading() {
+        let machine = build_machine(FakeSteps::default());
+        assert_eq!(
+            machine
+                .step(CrawlState::Downloading { crawl: crawl() })
+                .await,
+            CrawlState::Uploading {
+                crawl: crawl(),
+                wacz: "job.wacz".to_string()
+            }
+        );
+
+        let machine = build_machine(FakeSteps {
+            download_fails: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            machine
+                .step(CrawlState::Downloading { crawl: crawl() })
+                .await,
+            CrawlState::Failed {
+                crawl_id: Some(crawl().id),
+                error: CrawlError::DownloadFailed
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn uploading_then_writing_then_notifying() {
+        let machine = build_machine(FakeSteps::default());
+        let state = machine
+            .step(CrawlState::Uploading {
+                crawl: crawl(),
+                wacz: "job.wacz".to_string(),
+            })
+            .await;
+        assert_eq!(
+            state,
+            CrawlState::Writing {
+                crawl: crawl(),
+                capture: capture()
+            }
+        );
+        let state = machine.step(state).await;
+        assert_eq!(
+            state,
+            CrawlState::Notifying {
+                crawl: crawl(),
+                accession_id: 7,
+                capture: capture()
+            }
+        );
+        assert_eq!(machine.step(state).await, CrawlState::Done);
+    }
+
+    #[tokio::test]
+    async fn failing_to_upload_or_write_fails_the_crawl() {
+        let machine = build_machine(FakeSteps {
+            upload_fails: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            machine
+                .step(CrawlState::Uploading {
+                    crawl: crawl(),
+                    wacz: "job.wacz".to_string(),
+                })
+                .await,
+            CrawlState::Failed {
+                crawl_id: Some(crawl().id),
+                error: CrawlError::UploadFailed
+            }
+        );
+
+        let machine = build_machine(FakeSteps {
+            write_fails: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            machine
+                .step(CrawlState::Writing {
+                    crawl: crawl(),
+                    capture: capture(),
+                })
+                .await,
+            CrawlState::Failed {
+                crawl_id: Some(crawl().id),
+                error: CrawlError::WriteFailed
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn runs_a_crawl_into_the_archive() {
+        let machine = build_machine(FakeSteps {
+            polls_until_complete: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(machine.run().await, CrawlState::Done);
+        assert_eq!(
+            machine.steps.log(),
+            vec![
+                "launch",
+                "poll 1",
+                "download",
+                "upload job.wacz",
+                "write",
+                "notify 7"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_failures_once() {
+        let machine = build_machine(FakeSteps::default());
+        assert_eq!(
+            machine.run().await,
+            CrawlState::Failed {
+                crawl_id: Some(crawl().id),
+                error: CrawlError::TimedOut
+            }
+        );
+        let log = machine.steps.log();
+        assert_eq!(
+            log.iter().filter(|step| step.starts_with("poll")).count(),
+            MAX_POLLS as usize
+        );
+        assert_eq!(log.last().unwrap(), "failed TimedOut");
+    }
+}
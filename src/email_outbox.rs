@@ -0,0 +1,213 @@
+//! Retries for emails that failed to send.
+//!
+//! [`OutboxEmailsRepo`] wraps the real emails repo and queues any email it fails to send
+//! in an [`EmailOutbox`], which a scheduled task drains with [`EmailOutbox::retry`]. The
+//! outbox lives in memory, so emails still waiting to be retried are lost on restart.
+
+use crate::repos::emails_repo::EmailsRepo;
+use async_trait::async_trait;
+use reqwest::Error;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tracing::{error, warn};
+
+/// Give up on an email after this many failed retries.
+const MAX_RETRIES: u32 = 5;
+
+/// Stop queueing emails past this many, so an email provider outage can't eat memory.
+const MAX_QUEUED_EMAILS: usize = 1000;
+
+/// An email waiting to be retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingEmail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    pub retries: u32,
+}
+
+/// Counts of what a call to [`EmailOutbox::retry`] did.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RetrySummary {
+    pub sent: usize,
+    pub requeued: usize,
+    pub dropped: usize,
+}
+
+/// Thread safe queue of emails that failed to send.
+#[derive(Debug, Clone, Default)]
+pub struct EmailOutbox {
+    pending: Arc<Mutex<VecDeque<PendingEmail>>>,
+}
+
+impl EmailOutbox {
+    /// Queues an email to be retried later.
+    pub fn push(&self, email: PendingEmail) {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.len() >= MAX_QUEUED_EMAILS {
+            error!(
+                "Email outbox is full, dropping email with subject {}",
+                email.subject
+            );
+            return;
+        }
+        pending.push_back(email);
+    }
+
+    /// How many emails are waiting to be retried.
+    pub fn pending_count(&self) -> usize {
+        self.pending.lock().unwrap().len()
+    }
+
+    /// Tries to send every queued email once. Emails that fail again are queued for the
+    /// next retry, unless they have used up their retries.
+    ///
+    /// # Arguments
+    /// * `emails_repo` - Repo to send with, this must not be an [`OutboxEmailsRepo`] or
+    ///   failed retries would be queued twice
+    pub async fn retry(&self, emails_repo: &dyn EmailsRepo) -> RetrySummary {
+        let batch: Vec<PendingEmail> = self.pending.lock().unwrap().drain(..).collect();
+        let mut summary = RetrySummary::default();
+        for mut email in batch {
+            let result = emails_repo
+                .send_email(email.to.clone(), email.subject.clone(), email.body.clone())
+                .await;
+            match result {
+                Ok(()) => summary.sent += 1,
+                Err(err) => {
+                    email.retries += 1;
+                    if email.retries >= MAX_RETRIES {
+                        error!(%err, "Giving up on email with subject {} after {} retries", email.subject, email.retries);
+                        summary.dropped += 1;
+                    } else {
+                        warn!(%err, "Retrying email with subject {} failed", email.subject);
+                        summary.requeued += 1;
+                        self.push(email);
+                    }
+                }
+            }
+        }
+        summary
+    }
+}
+
+/// Emails repo that queues failed emails in an [`EmailOutbox`] to be retried.
+///
+/// Errors are still returned so callers can log them as before.
+#[derive(Clone)]
+pub struct OutboxEmailsRepo {
+    pub emails_repo: Arc<dyn EmailsRepo>,
+    pub outbox: EmailOutbox,
+}
+
+#[async_trait]
+impl EmailsRepo for OutboxEmailsRepo {
+    async fn send_email(&self, to: String, subject: String, email: String) -> Result<(), Error> {
+        let result = self
+            .emails_repo
+            .send_email(to.clone(), subject.clone(), email.clone())
+            .await;
+        if result.is_err() {
+            self.outbox.push(PendingEmail {
+                to,
+                subject,
+                body: email,
+                retries: 0,
+            });
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Fails the first `failures` sends then succeeds.
+    #[derive(Default)]
+    struct FlakyEmailsRepo {
+        failures: usize,
+        attempts: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EmailsRepo for FlakyEmailsRepo {
+        async fn send_email(
+            &self,
+            _to: String,
+            _subject: String,
+            _email: String,
+        ) -> Result<(), Error> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+                // an invalid URL is the easiest way to get a reqwest::Error
+                Err(reqwest::get("not a url").await.unwrap_err())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn pending_email(retries: u32) -> PendingEmail {
+        PendingEmail {
+            to: "someone@example.com".to_string(),
+            subject: "Hello".to_string(),
+            body: "<p>Hi</p>".to_string(),
+            retries,
+        }
+    }
+
+    #[tokio::test]
+    async fn queues_failed_emails() {
+        let outbox = EmailOutbox::default();
+        let emails_repo = OutboxEmailsRepo {
+            emails_repo: Arc::new(FlakyEmailsRepo {
+                failures: 1,
+                ..Default::default()
+            }),
+            outbox: outbox.clone(),
+        };
+        let first = emails_repo
+            .send_email(
+                "someone@example.com".into(),
+                "Hello".into(),
+                "<p>Hi</p>".into(),
+            )
+            .await;
+        let second = emails_repo
+            .send_email(
+                "someone@example.com".into(),
+                "Hello".into(),
+                "<p>Hi</p>".into(),
+            )
+            .await;
+
+        assert!(first.is_err());
+        assert!(second.is_ok());
+        assert_eq!(outbox.pending_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_and_eventually_drops_emails() {
+        let outbox = EmailOutbox::default();
+        outbox.push(pending_email(0));
+        outbox.push(pending_email(MAX_RETRIES - 1));
+        let emails_repo = FlakyEmailsRepo {
+            failures: 2,
+            ..Default::default()
+        };
+
+        let summary = outbox.retry(&emails_repo).await;
+        assert_eq!(
+            summary,
+            RetrySummary {
+                sent: 0,
+                requeued: 1,
+                dropped: 1
+            }
+        );
+        assert_eq!(outbox.retry(&emails_repo).await.sent, 1);
+        assert_eq!(outbox.pending_count(), 0);
+    }
+}
@@ -0,0 +1,81 @@
+//! Stops sending emails to addresses that hard bounced.
+//!
+//! Postmark tells us about bounces through its webhook, which records them as the user's
+//! `email_status`. Sending to an address that doesn't exist again only hurts the archive's
+//! sender reputation, so [`SuppressingEmailsRepo`] drops those emails before they reach
+//! Postmark.
+
+use crate::repos::auth_repo::AuthRepo;
+use crate::repos::emails_repo::EmailsRepo;
+use ::entity::sea_orm_active_enums::EmailStatus;
+use async_trait::async_trait;
+use reqwest::Error;
+use std::sync::Arc;
+use tracing::{error, warn};
+
+/// Emails repo that skips addresses which hard bounced.
+///
+/// Suppressed emails count as sent, so callers don't retry or report them as failures.
+/// If the address can't be looked up the email is sent anyway.
+#[derive(Clone)]
+pub struct SuppressingEmailsRepo {
+    pub emails_repo: Arc<dyn EmailsRepo>,
+    pub auth_repo: Arc<dyn AuthRepo>,
+}
+
+#[async_trait]
+impl EmailsRepo for SuppressingEmailsRepo {
+    async fn send_email(&self, to: String, subject: String, email: String) -> Result<(), Error> {
+        match self.auth_repo.get_email_status(to.clone()).await {
+            Ok(Some(EmailStatus::HardBounce)) => {
+                warn!("Not sending email with subject {subject} since {to} hard bounced");
+                return Ok(());
+            }
+            Ok(_) => {}
+            Err(err) => error!(%err, "Could not check email status of {to}, sending anyway"),
+        }
+        self.emails_repo.send_email(to, subject, email).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::InMemoryAuthRepo;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct CountingEmailsRepo {
+        sent: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EmailsRepo for CountingEmailsRepo {
+        async fn send_email(
+            &self,
+            _to: String,
+            _subject: String,
+            _email: String,
+        ) -> Result<(), Error> {
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn skips_hard_bounced_addresses() {
+        let counting_repo = Arc::new(CountingEmailsRepo::default());
+        let emails_repo = SuppressingEmailsRepo {
+            emails_repo: counting_repo.clone(),
+            auth_repo: Arc::new(InMemoryAuthRepo::default()),
+        };
+        for to in ["bounced@example.com", "test@example.com"] {
+            emails_repo
+                .send_email(to.into(), "Hello".into(), "<p>Hi</p>".into())
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(counting_repo.sent.load(Ordering::SeqCst), 1);
+    }
+}
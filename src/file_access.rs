@@ -0,0 +1,176 @@
+//! Short-lived links to the files of private accessions.
+//!
+//! A presigned S3 URL works for whoever holds it, so once one is shared it leaks the private
+//! capture until it expires. Private accessions instead link to the file proxy with a token
+//! naming the user it was issued to. The proxy only streams the file back to that same user,
+//! signed in, and only until the token or the session it was issued in expires, whichever
+//! comes first.
+//!
+//! Tokens are JWTs signed with the session key. They carry an audience and no role, so a
+//! file token can't be passed off as a session and a session can't be passed off as a file
+//! token.
+
+use crate::auth::JWT_KEYS;
+use crate::models::auth::AuthenticatedUser;
+use chrono::Utc;
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::Validation;
+use serde::{Deserialize, Serialize};
+
+/// How long a file link works for, in seconds
+pub const FILE_TOKEN_TTL_SECONDS: usize = 300;
+/// Audit log event for a private file being streamed through the proxy
+pub const PRIVATE_FILE_ACCESSED: &str = "private_file_accessed";
+const FILE_TOKEN_AUDIENCE: &str = "private-file";
+
+/// What a file token grants access to, and to whom.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileTokenClaims {
+    /// The user the token was issued to
+    pub sub: String,
+    pub aud: String,
+    pub exp: usize,
+    pub accession_id: i32,
+    /// S3 key of the file
+    pub key: String,
+}
+
+/// Why a file token was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileTokenError {
+    /// Not a file token, or not one we signed
+    Invalid,
+    /// The token or the session it was issued in has expired
+    Expired,
+    /// Issued to someone other than the user presenting it
+    WrongUser,
+}
+
+/// Issues a token for `user` to fetch one of an accession's files through the proxy.
+///
+/// # Arguments
+/// * `user` - The signed in user the link is for
+/// * `accession_id` - The accession the file belongs to
+/// * `key` - S3 key of the file
+pub fn issue_file_token(
+    user: &AuthenticatedUser,
+    accession_id: i32,
+    key: &str,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let ttl_expiry = Utc::now().timestamp() as usize + FILE_TOKEN_TTL_SECONDS;
+    // API keys don't expire, so their links only last the TTL
+    let exp = user
+        .expiry
+        .map_or(ttl_expiry, |expiry| expiry.min(ttl_expiry));
+    let claims = FileTokenClaims {
+        sub: user.user_id.clone(),
+        aud: FILE_TOKEN_AUDIENCE.to_string(),
+        exp,
+        accession_id,
+        key: key.to_string(),
+    };
+    JWT_KEYS.encode(&claims)
+}
+
+/// Checks a file token was issued to `user` and hasn't expired.
+///
+/// # Returns
+/// What the token grants access to
+pub fn verify_file_token(
+    token: &str,
+    user: &AuthenticatedUser,
+) -> Result<FileTokenClaims, FileTokenError> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[FILE_TOKEN_AUDIENCE]);
+    // a link is only good for as long as the token says, with no grace period
+    validation.leeway = 0;
+    let claims = JWT_KEYS
+        .decode::<FileTokenClaims>(token, &validation)
+        .map_err(|err| match err.kind() {
+            ErrorKind::ExpiredSignature => FileTokenError::Expired,
+            _ => FileTokenError::Invalid,
+        })?
+        .claims;
+    if claims.sub != user.user_id {
+        return Err(FileTokenError::WrongUser);
+    }
+    Ok(claims)
+}
+
+/// Link to the file proxy for a token.
+///
+/// # Arguments
+/// * `public_api_url` - Base of absolute links to the API
+/// * `token` - A token from [`issue_file_token`]
+pub fn file_proxy_url(public_api_url: &str, token: &str) -> String {
+    format!(
+        "{}/api/v1/accessions/private/files/{token}",
+        public_api_url.trim_end_matches('/')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::auth::{AuthMethod, JWTClaims};
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use ::entity::sea_orm_active_enums::Role;
+    use pretty_assertions::assert_eq;
+
+    fn user(user_id: &str, expiry: Option<usize>) -> AuthenticatedUser {
+        AuthenticatedUser {
+            user_id: user_id.to_string(),
+            expiry,
+            role: Role::Researcher,
+            impersonator: None,
+            organization_id: DEFAULT_ORGANIZATION_ID,
+            auth_method: AuthMethod::Cookie,
+        }
+    }
+
+    #[test]
+    fn verifies_tokens_for_the_user_they_were_issued_to() {
+        let researcher = user("researcher@example.com", None);
+        let token = issue_file_token(&researcher, 7, "some_file.wacz").unwrap();
+        let claims = verify_file_token(&token, &researcher).unwrap();
+        assert_eq!(claims.accession_id, 7);
+        assert_eq!(claims.key, "some_file.wacz");
+        assert_eq!(
+            verify_file_token(&token, &user("someone@example.com", None)),
+            Err(FileTokenError::WrongUser)
+        );
+    }
+
+    #[test]
+    fn tokens_expire_with_the_session() {
+        let session_expiry = Utc::now().timestamp() as usize + 10;
+        let researcher = user("researcher@example.com", Some(session_expiry));
+        let token = issue_file_token(&researcher, 7, "some_file.wacz").unwrap();
+        assert_eq!(
+            verify_file_token(&token, &researcher).unwrap().exp,
+            session_expiry
+        );
+
+        let lapsed = user("researcher@example.com", Some(1));
+        let token = issue_file_token(&lapsed, 7, "some_file.wacz").unwrap();
+        assert_eq!(
+            verify_file_token(&token, &lapsed),
+            Err(FileTokenError::Expired)
+        );
+    }
+
+    #[test]
+    fn sessions_and_file_tokens_are_not_interchangeable() {
+        let researcher = user("researcher@example.com", None);
+        let file_token = issue_file_token(&researcher, 7, "some_file.wacz").unwrap();
+        assert!(JWT_KEYS
+            .decode::<JWTClaims>(&file_token, &Validation::default())
+            .is_err());
+
+        let session = crate::test_tools::get_mock_jwt();
+        assert_eq!(
+            verify_file_token(&session, &researcher),
+            Err(FileTokenError::Invalid)
+        );
+    }
+}
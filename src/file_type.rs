@@ -0,0 +1,205 @@
+//! File type detection for uploads.
+//!
+//! The `Content-Type` a client sends is whatever it claims, so uploads are checked by
+//! sniffing the magic bytes at the start of the file instead. Each
+//! [`DublinMetadataFormat`] accepts a fixed set of file types.
+
+use entity::sea_orm_active_enums::DublinMetadataFormat;
+use flate2::read::GzDecoder;
+use std::io::Read;
+
+/// Number of leading bytes needed to tell every known file type apart.
+pub const SNIFF_LENGTH: usize = 12;
+
+/// Number of leading bytes read to check a gzipped file holds a WARC, see [`is_warc`].
+pub const WARC_SNIFF_LENGTH: usize = 1024;
+
+/// File types recognised from their magic bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    /// Zip archives, which includes WACZ files
+    Zip,
+    /// Gzip compressed files, e.g. `.warc.gz`
+    Gzip,
+    /// Uncompressed WARC files
+    Warc,
+    Pdf,
+    Jpeg,
+    Png,
+    Gif,
+    /// ISO base media files, e.g. MP4 and MOV
+    Mp4,
+    /// Matroska and WebM video
+    WebM,
+}
+
+impl FileType {
+    /// A human readable name for error messages.
+    pub fn description(&self) -> &'static str {
+        match self {
+            FileType::Zip => "a zip archive",
+            FileType::Gzip => "a gzip file",
+            FileType::Warc => "a WARC file",
+            FileType::Pdf => "a PDF document",
+            FileType::Jpeg => "a JPEG image",
+            FileType::Png => "a PNG image",
+            FileType::Gif => "a GIF image",
+            FileType::Mp4 => "an MP4 video",
+            FileType::WebM => "a WebM video",
+        }
+    }
+}
+
+/// Works out a file's type from its first bytes, see [`SNIFF_LENGTH`].
+///
+/// Returns `None` for anything unrecognised, including plain text.
+pub fn sniff_file_type(bytes: &[u8]) -> Option<FileType> {
+    if bytes.starts_with(b"PK\x03\x04") {
+        Some(FileType::Zip)
+    } else if bytes.starts_with(b"\x1f\x8b") {
+        Some(FileType::Gzip)
+    } else if bytes.starts_with(b"WARC/") {
+        Some(FileType::Warc)
+    } else if bytes.starts_with(b"%PDF-") {
+        Some(FileType::Pdf)
+    } else if bytes.starts_with(b"\xff\xd8\xff") {
+        Some(FileType::Jpeg)
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(FileType::Png)
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some(FileType::Gif)
+    } else if bytes.get(4..8) == Some(b"ftyp") {
+        Some(FileType::Mp4)
+    } else if bytes.starts_with(b"\x1a\x45\xdf\xa3") {
+        Some(FileType::WebM)
+    } else {
+        None
+    }
+}
+
+/// The file types a metadata format accepts.
+pub fn allowed_file_types(metadata_format: &DublinMetadataFormat) -> &'static [FileType] {
+    match metadata_format {
+        DublinMetadataFormat::Wacz => &[FileType::Zip],
+        DublinMetadataFormat::Jpeg => &[FileType::Jpeg],
+        DublinMetadataFormat::Png => &[FileType::Png],
+        DublinMetadataFormat::Mp4 => &[FileType::Mp4],
+        DublinMetadataFormat::Warc => &[FileType::Warc, FileType::Gzip],
+    }
+}
+
+/// Checks a file starts with a WARC record, looking inside it when it is gzipped.
+///
+/// Sniffing alone accepts any gzip file for the WARC format, so this is the stricter check
+/// for when more of the file can be read, see [`WARC_SNIFF_LENGTH`].
+pub fn is_warc(head: &[u8]) -> bool {
+    match sniff_file_type(head) {
+        Some(FileType::Warc) => true,
+        Some(FileType::Gzip) => {
+            let mut version = [0; 5];
+            GzDecoder::new(head).read_exact(&mut version).is_ok() && &version == b"WARC/"
+        }
+        _ => false,
+    }
+}
+
+/// Checks a file's first bytes match its metadata format.
+///
+/// # Errors
+/// Returns a message describing the mismatch, suitable for showing to the uploader
+pub fn check_file_type(metadata_format: &DublinMetadataFormat, bytes: &[u8]) -> Result<(), String> {
+    let allowed = allowed_file_types(metadata_format);
+    let sniffed = sniff_file_type(bytes);
+    if sniffed.is_some_and(|file_type| allowed.contains(&file_type)) {
+        return Ok(());
+    }
+    let found = sniffed.map_or("an unrecognised file type", |file_type| {
+        file_type.description()
+    });
+    let expected = allowed
+        .iter()
+        .map(|file_type| file_type.description())
+        .collect::<Vec<_>>()
+        .join(" or ");
+    let format_name = format!("{metadata_format:?}").to_lowercase();
+    Err(format!(
+        "File is {found} but metadata format {format_name} expects {expected}"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn sniffs_known_file_types() {
+        assert_eq!(sniff_file_type(b"PK\x03\x04\x14\x00"), Some(FileType::Zip));
+        assert_eq!(sniff_file_type(b"\x1f\x8b\x08\x00"), Some(FileType::Gzip));
+        assert_eq!(sniff_file_type(b"WARC/1.1\r\n"), Some(FileType::Warc));
+        assert_eq!(sniff_file_type(b"%PDF-1.7\n"), Some(FileType::Pdf));
+        assert_eq!(sniff_file_type(b"\xff\xd8\xff\xe0"), Some(FileType::Jpeg));
+        assert_eq!(
+            sniff_file_type(b"\x89PNG\r\n\x1a\n\x00"),
+            Some(FileType::Png)
+        );
+        assert_eq!(sniff_file_type(b"GIF89a\x01\x00"), Some(FileType::Gif));
+        assert_eq!(
+            sniff_file_type(b"\x00\x00\x00\x18ftypmp42"),
+            Some(FileType::Mp4)
+        );
+        assert_eq!(
+            sniff_file_type(b"\x1a\x45\xdf\xa3\x01"),
+            Some(FileType::WebM)
+        );
+    }
+
+    #[test]
+    fn does_not_sniff_unknown_or_short_files() {
+        assert_eq!(sniff_file_type(b"hello world"), None);
+        assert_eq!(sniff_file_type(b"PK"), None);
+        assert_eq!(sniff_file_type(b""), None);
+    }
+
+    #[test]
+    fn accepts_zip_for_wacz() {
+        assert_eq!(
+            check_file_type(&DublinMetadataFormat::Wacz, b"PK\x03\x04\x14\x00"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn accepts_plain_and_gzipped_warcs() {
+        let record = b"WARC/1.1\r\nWARC-Type: warcinfo\r\n";
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, record).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        for head in [&record[..], &gzipped] {
+            assert_eq!(check_file_type(&DublinMetadataFormat::Warc, head), Ok(()));
+            assert!(is_warc(head));
+        }
+
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, b"just some text").unwrap();
+        assert!(!is_warc(&encoder.finish().unwrap()));
+        assert!(!is_warc(b"PK\x03\x04\x14\x00"));
+    }
+
+    #[test]
+    fn describes_mismatches() {
+        assert_eq!(
+            check_file_type(&DublinMetadataFormat::Wacz, b"%PDF-1.7\n"),
+            Err(
+                "File is a PDF document but metadata format wacz expects a zip archive".to_string()
+            )
+        );
+        assert_eq!(
+            check_file_type(&DublinMetadataFormat::Wacz, b"hello"),
+            Err(
+                "File is an unrecognised file type but metadata format wacz expects a zip archive"
+                    .to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,452 @@
+//! Arabic translations of the API's error and status messages.
+//!
+//! Handlers write their messages in English. [`localize_messages`] runs over every
+//! response and, going by the request's `Accept-Language`, swaps plain text messages for
+//! their Arabic translation. Structured error bodies, see [`crate::models::error`], always
+//! get both languages in `messages` so the bilingual frontend can show either without a
+//! mapping table of its own, and so do plain text messages sent as JSON, see
+//! [`crate::json_messages`]. Messages missing from [`MESSAGES`] are left in English.
+//!
+//! Handlers returning accessions take the reader's [`PreferredLanguage`] to fill in their
+//! display title and description, see [`crate::models::response::DisplayMetadata`].
+
+use crate::json_messages::WrappedMessage;
+use crate::models::common::MetadataLanguage;
+use crate::models::error::LocalizedMessages;
+use axum::body::{to_bytes, Body, HttpBody};
+use axum::extract::{FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::Response;
+use http::header::{ACCEPT_LANGUAGE, CONTENT_LANGUAGE, CONTENT_LENGTH, CONTENT_TYPE, VARY};
+use http::{HeaderMap, HeaderValue};
+use serde_json::Value;
+use std::convert::Infallible;
+
+/// Largest body looked at for messages; anything bigger isn't a message
+const MAX_MESSAGE_BODY: u64 = 64 * 1024;
+
+/// English messages and their Arabic translations.
+///
+/// `{}` stands for a part of the message that changes, such as a name or an ID, and is
+/// carried over into the translation in the same order.
+const MESSAGES: &[(&str, &str)] = &[
+    ("Internal database error", "خطأ داخلي في قاعدة البيانات"),
+    ("Internal server error", "خطأ داخلي في الخادم"),
+    ("Request failed validation", "فشل التحقق من صحة الطلب"),
+    ("Invalid token", "رمز الدخول غير صالح"),
+    ("Token expired", "انتهت صلاحية رمز الدخول"),
+    ("Account deactivated", "تم تعطيل الحساب"),
+    ("Missing credentials", "بيانات الدخول مفقودة"),
+    ("User deactivated", "تم تعطيل المستخدم"),
+    ("Insufficient permissions", "صلاحيات غير كافية"),
+    (
+        "Must have at least researcher role",
+        "يجب أن يكون لديك دور باحث على الأقل",
+    ),
+    (
+        "Must have at least contributor role",
+        "يجب أن يكون لديك دور مساهم على الأقل",
+    ),
+    (
+        "Only admins can create API keys",
+        "يمكن للمشرفين فقط إنشاء مفاتيح API",
+    ),
+    ("Login email sent", "تم إرسال بريد تسجيل الدخول"),
+    ("No such record", "السجل غير موجود"),
+    ("Not found", "غير موجود"),
+    ("Accession deleted", "تم حذف المادة الأرشيفية"),
+    (
+        "Accession's file changed in the meantime",
+        "تغير ملف المادة الأرشيفية في هذه الأثناء",
+    ),
+    ("File must not be empty", "يجب ألا يكون الملف فارغاً"),
+    ("Comment removed", "تم حذف التعليق"),
+    (
+        "Only the author can edit a comment",
+        "يمكن لكاتب التعليق فقط تعديله",
+    ),
+    (
+        "Only the author or an admin can remove a comment",
+        "يمكن لكاتب التعليق أو المشرف فقط حذفه",
+    ),
+    (
+        "Can't tag more than {} accessions at once, narrow the filter",
+        "لا يمكن إضافة الموضوع إلى أكثر من {} مادة أرشيفية دفعة واحدة، يرجى تضييق عوامل التصفية",
+    ),
+    (
+        "Accession created with id: {}",
+        "تم إنشاء المادة الأرشيفية بالمعرف: {}",
+    ),
+    (
+        "Accession cannot move from {} to {}",
+        "لا يمكن نقل المادة الأرشيفية من {} إلى {}",
+    ),
+    (
+        "Accession was changed by someone else, try again",
+        "عدّل شخص آخر المادة الأرشيفية، حاول مرة أخرى",
+    ),
+    (
+        "A title is needed to add metadata in a new language",
+        "يلزم عنوان لإضافة بيانات وصفية بلغة جديدة",
+    ),
+    ("Accession is not an image", "المادة الأرشيفية ليست صورة"),
+    (
+        "Accession already has metadata in both languages",
+        "للمادة الأرشيفية بيانات وصفية باللغتين بالفعل",
+    ),
+    (
+        "Accession has no metadata to translate",
+        "لا توجد بيانات وصفية للمادة الأرشيفية لترجمتها",
+    ),
+    (
+        "No archived WACZ file for this accession",
+        "لا يوجد ملف WACZ مؤرشف لهذه المادة",
+    ),
+    (
+        "Select accessions with either ids or url_filter",
+        "اختر المواد الأرشيفية إما بـ ids أو url_filter",
+    ),
+    ("Crawler is not configured", "أداة الأرشفة غير مفعلة"),
+    ("Video capture is not configured", "أرشفة الفيديو غير مفعلة"),
+    (
+        "URL is on the crawl blocklist: {}",
+        "الرابط مدرج في قائمة الروابط المحظور أرشفتها: {}",
+    ),
+    ("Subjects do not exist", "الموضوعات غير موجودة"),
+    ("Subject deleted", "تم حذف الموضوع"),
+    ("Subject {} already exists", "الموضوع {} موجود بالفعل"),
+    (
+        "Subject with id {} is being referenced by another table",
+        "الموضوع ذو المعرف {} مستخدم في جدول آخر",
+    ),
+    ("Workflow label added", "تمت إضافة تصنيف سير العمل"),
+    ("Workflow label removed", "تمت إزالة تصنيف سير العمل"),
+    ("Workflow label deleted", "تم حذف تصنيف سير العمل"),
+    (
+        "Workflow label {} already exists",
+        "تصنيف سير العمل {} موجود بالفعل",
+    ),
+    (
+        "No such accession or workflow label",
+        "المادة الأرشيفية أو تصنيف سير العمل غير موجود",
+    ),
+    (
+        "Accession added to collection",
+        "تمت إضافة المادة الأرشيفية إلى المجموعة",
+    ),
+    (
+        "Accession removed from collection",
+        "تمت إزالة المادة الأرشيفية من المجموعة",
+    ),
+    (
+        "Accession belongs to another organization",
+        "المادة الأرشيفية تابعة لمؤسسة أخرى",
+    ),
+    (
+        "Collection has no public accessions to export",
+        "لا تحتوي المجموعة على مواد أرشيفية عامة للتصدير",
+    ),
+    (
+        "Collection is too large to export",
+        "المجموعة أكبر من أن تُصدَّر",
+    ),
+    (
+        "Error occurred reading collection files",
+        "خطأ أثناء قراءة ملفات المجموعة",
+    ),
+    ("Feature flag deleted", "تم حذف علامة الميزة"),
+    (
+        "Feature flag {} already exists",
+        "علامة الميزة {} موجودة بالفعل",
+    ),
+    ("No captures of this URL", "لا توجد لقطات مؤرشفة لهذا الرابط"),
+    (
+        "Accept-Datetime must be an HTTP date",
+        "يجب أن تكون قيمة Accept-Datetime تاريخ HTTP",
+    ),
+    (
+        "A backfill is already running",
+        "عملية النقل إلى S3 قيد التشغيل بالفعل",
+    ),
+    (
+        "Machine translation is not configured",
+        "الترجمة الآلية غير مفعلة",
+    ),
+    ("Translation service error", "خطأ في خدمة الترجمة"),
+    (
+        "Subject suggestions are not configured",
+        "اقتراح الموضوعات غير مفعل",
+    ),
+    (
+        "Entity recognition service error",
+        "خطأ في خدمة التعرف على الكيانات",
+    ),
+    ("File failed virus scan", "لم يجتز الملف فحص الفيروسات"),
+    (
+        "Unable to virus scan file",
+        "تعذر فحص الملف بحثًا عن الفيروسات",
+    ),
+    ("Uploaded file is empty", "الملف المرفوع فارغ"),
+    ("Uploaded file not found", "الملف المرفوع غير موجود"),
+    (
+        "Uploaded file does not match metadata format",
+        "الملف المرفوع لا يطابق الصيغة المحددة في البيانات الوصفية",
+    ),
+    ("Failed to upload file", "فشل رفع الملف"),
+    ("Failed to read file stream", "فشلت قراءة الملف"),
+    ("Malformed multipart request", "طلب متعدد الأجزاء غير صالح"),
+    (
+        "Upload belongs to another user",
+        "عملية الرفع تخص مستخدمًا آخر",
+    ),
+    ("No such upload", "عملية الرفع غير موجودة"),
+    ("No such upload in progress", "لا توجد عملية رفع جارية كهذه"),
+    ("Upload has no parts", "لا تحتوي عملية الرفع على أي أجزاء"),
+    ("Part must not be empty", "يجب ألا يكون الجزء فارغًا"),
+    ("Upload aborted", "تم إلغاء عملية الرفع"),
+];
+
+/// Picks the language to answer in from an `Accept-Language` header.
+///
+/// Takes whichever of Arabic and English the client weights highest, English if it
+/// names neither.
+pub fn preferred_language(accept_language: &str) -> MetadataLanguage {
+    let mut preferred = (MetadataLanguage::English, 0.0);
+    for range in accept_language.split(',') {
+        let mut params = range.split(';');
+        let tag = params.next().unwrap_or_default().trim().to_lowercase();
+        let quality = params
+            .find_map(|param| param.trim().strip_prefix("q="))
+            .and_then(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        let language = match tag.split('-').next() {
+            Some("ar") => MetadataLanguage::Arabic,
+            Some("en") => MetadataLanguage::English,
+            _ => continue,
+        };
+        if quality > preferred.1 {
+            preferred = (language, quality);
+        }
+    }
+    preferred.0
+}
+
+/// Language a request's `Accept-Language` asks for, English if it has none.
+fn request_language(headers: &HeaderMap) -> MetadataLanguage {
+    headers
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(preferred_language)
+        .unwrap_or_default()
+}
+
+/// Language the reader would rather read metadata in, see [`preferred_language`].
+///
+/// Responses that depend on it should say so with `Vary: accept-language`, so caches keep
+/// a copy per language.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PreferredLanguage(pub MetadataLanguage);
+
+impl<S: Send + Sync> FromRequestParts<S> for PreferredLanguage {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(PreferredLanguage(request_language(&parts.headers)))
+    }
+}
+
+/// Matches `message` against a template, returning the parts standing in for `{}`.
+fn match_template<'a>(template: &str, message: &'a str) -> Option<Vec<&'a str>> {
+    let mut pieces = template.split("{}");
+    let first = pieces.next().unwrap_or_default();
+    let pieces: Vec<&str> = pieces.collect();
+    if pieces.is_empty() {
+        return (template == message).then(Vec::new);
+    }
+    let mut rest = message.strip_prefix(first)?;
+    let mut captures = vec![];
+    for (index, piece) in pieces.iter().enumerate() {
+        let end = if index + 1 == pieces.len() {
+            rest.strip_suffix(piece)?.len()
+        } else {
+            rest.find(piece)?
+        };
+        captures.push(&rest[..end]);
+        rest = &rest[end + piece.len()..];
+    }
+    Some(captures)
+}
+
+/// Translates an English message into Arabic, or `None` if it isn't in [`MESSAGES`].
+pub fn arabic_message(message: &str) -> Option<String> {
+    MESSAGES.iter().find_map(|(english, arabic)| {
+        let captures = match_template(english, message)?;
+        let mut translation = String::new();
+        let mut pieces = arabic.split("{}");
+        translation.push_str(pieces.next().unwrap_or_default());
+        for (capture, piece) in captures.iter().zip(pieces) {
+            translation.push_str(capture);
+            translation.push_str(piece);
+        }
+        Some(translation)
+    })
+}
+
+/// Both languages of a message, falling back to English when there's no translation.
+pub fn localized_messages(message: &str) -> LocalizedMessages {
+    LocalizedMessages {
+        en: message.to_string(),
+        ar: arabic_message(message).unwrap_or_else(|| message.to_string()),
+    }
+}
+
+fn pick(messages: &LocalizedMessages, language: MetadataLanguage) -> &str {
+    match language {
+        MetadataLanguage::English => &messages.en,
+        MetadataLanguage::Arabic => &messages.ar,
+    }
+}
+
+/// Rewrites a JSON message body; `{"error": {"message": ...}}`, the auth errors'
+/// `{"error": "..."}` and [`crate::models::response::MessageResponse`]'s
+/// `{"message": ...}` are understood.
+fn localize_json(body: &[u8], language: MetadataLanguage) -> Option<Vec<u8>> {
+    let mut json: Value = serde_json::from_slice(body).ok()?;
+    if json.get("error").is_none() {
+        let message = json.get("message")?.as_str()?;
+        let messages = localized_messages(message);
+        json["message"] = Value::String(pick(&messages, language).to_string());
+        json["messages"] = serde_json::to_value(&messages).ok()?;
+        return serde_json::to_vec(&json).ok();
+    }
+    let error = json.get_mut("error")?;
+    if let Some(message) = error.as_str() {
+        let messages = localized_messages(message);
+        *error = Value::String(pick(&messages, language).to_string());
+        json["messages"] = serde_json::to_value(&messages).ok()?;
+    } else {
+        let message = error.get("message")?.as_str()?;
+        let messages = localized_messages(message);
+        error["message"] = Value::String(pick(&messages, language).to_string());
+        error["messages"] = serde_json::to_value(&messages).ok()?;
+    }
+    serde_json::to_vec(&json).ok()
+}
+
+/// Middleware localizing the messages in responses, see the module docs.
+pub async fn localize_messages(request: Request, next: Next) -> Response {
+    let language = request_language(request.headers());
+    let response = next.run(request).await;
+
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let is_plain_text = content_type.starts_with("text/plain");
+    let is_json_message = content_type.starts_with("application/json")
+        && (response.status().is_client_error()
+            || response.status().is_server_error()
+            || response.extensions().get::<WrappedMessage>().is_some());
+    let small = response
+        .body()
+        .size_hint()
+        .upper()
+        .is_some_and(|size| size <= MAX_MESSAGE_BODY);
+    if !(is_plain_text || is_json_message) || !small {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(body) = to_bytes(body, MAX_MESSAGE_BODY as usize).await else {
+        return Response::from_parts(parts, Body::empty());
+    };
+    let localized = if is_plain_text {
+        std::str::from_utf8(&body)
+            .ok()
+            .filter(|_| language == MetadataLanguage::Arabic)
+            .and_then(arabic_message)
+            .map(String::into_bytes)
+    } else {
+        localize_json(&body, language)
+    };
+    parts
+        .headers
+        .append(VARY, HeaderValue::from_static("accept-language"));
+    let body = match localized {
+        Some(localized) => {
+            parts.headers.remove(CONTENT_LENGTH);
+            parts.headers.insert(
+                CONTENT_LANGUAGE,
+                HeaderValue::from_static(match language {
+                    MetadataLanguage::English => "en",
+                    MetadataLanguage::Arabic => "ar",
+                }),
+            );
+            Body::from(localized)
+        }
+        None => Body::from(body),
+    };
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn prefers_the_highest_weighted_language() {
+        assert_eq!(
+            preferred_language("ar-SD,ar;q=0.9,en;q=0.8"),
+            MetadataLanguage::Arabic
+        );
+        assert_eq!(
+            preferred_language("fr, en;q=0.9, ar;q=0.5"),
+            MetadataLanguage::English
+        );
+        assert_eq!(preferred_language("fr, *"), MetadataLanguage::English);
+    }
+
+    #[test]
+    fn translates_messages_with_changing_parts() {
+        assert_eq!(
+            arabic_message("No such record").as_deref(),
+            Some("السجل غير موجود")
+        );
+        assert_eq!(
+            arabic_message("Accession cannot move from draft to withdrawn").as_deref(),
+            Some("لا يمكن نقل المادة الأرشيفية من draft إلى withdrawn")
+        );
+        assert_eq!(arabic_message("No such record!"), None);
+        assert_eq!(arabic_message("Something new"), None);
+    }
+
+    #[test]
+    fn localizes_both_json_error_shapes() {
+        let structured = localize_json(
+            br#"{"error": {"code": "not_found", "message": "No such record"}}"#,
+            MetadataLanguage::Arabic,
+        )
+        .unwrap();
+        let structured: Value = serde_json::from_slice(&structured).unwrap();
+        assert_eq!(structured["error"]["message"], "السجل غير موجود");
+        assert_eq!(structured["error"]["messages"]["en"], "No such record");
+
+        let auth =
+            localize_json(br#"{"error": "Invalid token"}"#, MetadataLanguage::English).unwrap();
+        let auth: Value = serde_json::from_slice(&auth).unwrap();
+        assert_eq!(auth["error"], "Invalid token");
+        assert_eq!(auth["messages"]["ar"], "رمز الدخول غير صالح");
+
+        let message = localize_json(
+            br#"{"code": "ok", "message": "Subject deleted"}"#,
+            MetadataLanguage::Arabic,
+        )
+        .unwrap();
+        let message: Value = serde_json::from_slice(&message).unwrap();
+        assert_eq!(message["message"], "تم حذف الموضوع");
+        assert_eq!(message["messages"]["en"], "Subject deleted");
+    }
+}
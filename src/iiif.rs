@@ -0,0 +1,346 @@
+//! IIIF Presentation API v3 manifests for image accessions.
+//!
+//! Manifests let libraries and universities show our images in their own IIIF viewers,
+//! e.g. Mirador or Universal Viewer. Each image accession becomes a manifest with a single
+//! canvas painted with the stored image, see <https://iiif.io/api/presentation/3.0/>.
+//!
+//! Canvases must say how big they are, and we don't store image dimensions, so they are
+//! read from the start of the image file with [`image_dimensions`].
+
+use ::entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
+use entity::sea_orm_active_enums::DublinMetadataFormat;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+pub const PRESENTATION_CONTEXT: &str = "http://iiif.io/api/presentation/3/context.json";
+
+/// Content type IIIF viewers expect manifests to be served with
+pub const MANIFEST_CONTENT_TYPE: &str =
+    "application/ld+json;profile=\"http://iiif.io/api/presentation/3/context.json\"";
+
+/// Number of leading bytes read to find an image's dimensions.
+///
+/// PNG puts them in the first chunk, but JPEG frame headers come after any EXIF or colour
+/// profile segments, which are usually well under this.
+pub const DIMENSIONS_READ_LENGTH: u64 = 256 * 1024;
+
+/// Text in one or more languages, keyed by BCP 47 language code or `none`
+pub type LanguageMap = BTreeMap<String, Vec<String>>;
+
+/// A IIIF v3 manifest describing one accession
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Manifest {
+    #[serde(rename = "@context")]
+    pub context: String,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub label: LanguageMap,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<LanguageMap>,
+    pub metadata: Vec<MetadataEntry>,
+    pub items: Vec<Canvas>,
+}
+
+/// A label and value pair shown alongside the manifest in viewers
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct MetadataEntry {
+    pub label: LanguageMap,
+    pub value: LanguageMap,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Canvas {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub width: u32,
+    pub height: u32,
+    pub items: Vec<AnnotationPage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct AnnotationPage {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub items: Vec<Annotation>,
+}
+
+/// Paints an image onto a canvas
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Annotation {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub motivation: String,
+    pub body: ImageBody,
+    pub target: String,
+}
+
+/// The image itself
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct ImageBody {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// An image file that can be put on a canvas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageResource {
+    /// Where viewers can fetch the image from
+    pub url: String,
+    /// MIME type of the image
+    pub format: &'static str,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// MIME type of images in the given format, or `None` for formats that aren't images.
+pub fn image_format(format: &DublinMetadataFormat) -> Option<&'static str> {
+    match format {
+        DublinMetadataFormat::Jpeg => Some("image/jpeg"),
+        DublinMetadataFormat::Png => Some("image/png"),
+        DublinMetadataFormat::Wacz | DublinMetadataFormat::Warc | DublinMetadataFormat::Mp4 => None,
+    }
+}
+
+/// Reads the width and height of a PNG or JPEG image from its first bytes.
+///
+/// Returns `None` for other file types, or when the dimensions aren't within `head`.
+pub fn image_dimensions(head: &[u8]) -> Option<(u32, u32)> {
+    if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png_dimensions(head)
+    } else if head.starts_with(b"\xff\xd8") {
+        jpeg_dimensions(head)
+    } else {
+        None
+    }
+}
+
+/// PNGs always start with the IHDR chunk, which begins with the width and height.
+fn png_dimensions(head: &[u8]) -> Option<(u32, u32)> {
+    if head.len() < 24 || &head[12..16] != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(head[16..20].try_into().ok()?);
+    let height = u32::from_be_bytes(head[20..24].try_into().ok()?);
+    Some((width, height))
+}
+
+/// Walks the JPEG segments until the start of frame, which holds the dimensions.
+fn jpeg_dimensions(head: &[u8]) -> Option<(u32, u32)> {
+    let mut offset = 2;
+    while offset + 9 <= head.len() {
+        if head[offset] != 0xff {
+            return None;
+        }
+        let marker = head[offset + 1];
+        match marker {
+            // padding before a marker
+            0xff => offset += 1,
+            // markers without a segment
+            0x01 | 0xd0..=0xd7 => offset += 2,
+            // start of frame, apart from the Huffman, arithmetic coding and JPEG-LS markers
+            0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                let height = u16::from_be_bytes([head[offset + 5], head[offset + 6]]);
+                let width = u16::from_be_bytes([head[offset + 7], head[offset + 8]]);
+                return Some((width.into(), height.into()));
+            }
+            _ => {
+                let segment_length = u16::from_be_bytes([head[offset + 2], head[offset + 3]]);
+                offset += 2 + usize::from(segment_length);
+            }
+        }
+    }
+    None
+}
+
+fn language_map(entries: &[(&str, &Option<String>)]) -> Option<LanguageMap> {
+    let map: LanguageMap = entries
+        .iter()
+        .filter_map(|(language, text)| {
+            let text = text.as_deref()?.trim();
+            (!text.is_empty()).then(|| (language.to_string(), vec![text.to_string()]))
+        })
+        .collect();
+    (!map.is_empty()).then_some(map)
+}
+
+fn metadata_entry(label: &str, value: LanguageMap) -> MetadataEntry {
+    MetadataEntry {
+        label: LanguageMap::from([("en".to_string(), vec![label.to_string()])]),
+        value,
+    }
+}
+
+/// Builds the manifest for an image accession.
+///
+/// # Arguments
+/// * `accession` - The accession the image belongs to
+/// * `manifest_url` - Absolute URL the manifest is served from, which is also its ID
+/// * `image` - The accession's image
+pub fn build_manifest(
+    accession: &AccessionWithMetadataModel,
+    manifest_url: &str,
+    image: ImageResource,
+) -> Manifest {
+    let base_url = manifest_url
+        .strip_suffix("/manifest.json")
+        .unwrap_or(manifest_url);
+    let canvas_id = format!("{base_url}/canvas/1");
+    let page_id = format!("{base_url}/page/1");
+    let annotation_id = format!("{base_url}/annotation/1");
+
+    let label = language_map(&[("en", &accession.title_en), ("ar", &accession.title_ar)])
+        .unwrap_or_else(|| {
+            LanguageMap::from([(
+                "none".to_string(),
+                vec![format!("Accession {}", accession.id)],
+            )])
+        });
+    let summary = language_map(&[
+        ("en", &accession.description_en),
+        ("ar", &accession.description_ar),
+    ]);
+
+    let mut metadata = vec![metadata_entry(
+        "Date",
+        LanguageMap::from([(
+            "none".to_string(),
+            vec![accession.dublin_metadata_date.date().to_string()],
+        )]),
+    )];
+    let mut subjects = LanguageMap::new();
+    for (language, terms) in [
+        ("en", &accession.subjects_en),
+        ("ar", &accession.subjects_ar),
+    ] {
+        if let Some(terms) = terms.as_ref().filter(|terms| !terms.is_empty()) {
+            subjects.insert(language.to_string(), terms.clone());
+        }
+    }
+    if !subjects.is_empty() {
+        metadata.push(metadata_entry("Subjects", subjects));
+    }
+    metadata.push(metadata_entry(
+        "Source",
+        LanguageMap::from([("none".to_string(), vec![accession.seed_url.clone()])]),
+    ));
+
+    Manifest {
+        context: PRESENTATION_CONTEXT.to_string(),
+        id: manifest_url.to_string(),
+        kind: "Manifest".to_string(),
+        label,
+        summary,
+        metadata,
+        items: vec![Canvas {
+            id: canvas_id.clone(),
+            kind: "Canvas".to_string(),
+            width: image.width,
+            height: image.height,
+            items: vec![AnnotationPage {
+                id: page_id,
+                kind: "AnnotationPage".to_string(),
+                items: vec![Annotation {
+                    id: annotation_id,
+                    kind: "Annotation".to_string(),
+                    motivation: "painting".to_string(),
+                    body: ImageBody {
+                        id: image.url,
+                        kind: "Image".to_string(),
+                        format: image.format.to_string(),
+                        width: image.width,
+                        height: image.height,
+                    },
+                    target: canvas_id,
+                }],
+            }],
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::mock_one_accession_with_metadata;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn reads_png_dimensions() {
+        let mut head = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+        head.extend_from_slice(&640u32.to_be_bytes());
+        head.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(image_dimensions(&head), Some((640, 480)));
+        assert_eq!(image_dimensions(&head[..20]), None);
+    }
+
+    #[test]
+    fn reads_jpeg_dimensions_after_other_segments() {
+        let mut head = vec![0xff, 0xd8];
+        // APP0 segment with a 16 byte body
+        head.extend_from_slice(&[0xff, 0xe0, 0x00, 0x10]);
+        head.extend_from_slice(&[0; 14]);
+        // Huffman table, which shares the SOF marker range
+        head.extend_from_slice(&[0xff, 0xc4, 0x00, 0x04, 0x00, 0x00]);
+        // baseline start of frame, 8 bit, 1200 high and 1600 wide
+        head.extend_from_slice(&[0xff, 0xc0, 0x00, 0x11, 0x08, 0x04, 0xb0, 0x06, 0x40]);
+        assert_eq!(image_dimensions(&head), Some((1600, 1200)));
+        assert_eq!(image_dimensions(&head[..head.len() - 4]), None);
+        assert_eq!(image_dimensions(b"PK\x03\x04"), None);
+    }
+
+    #[test]
+    fn builds_a_manifest_with_one_painted_canvas() {
+        let mut accession = mock_one_accession_with_metadata();
+        accession.title_ar = Some("عنوان".to_string());
+        let url = "https://api.example.org/api/v1/accessions/1/iiif/manifest.json";
+        let manifest = build_manifest(
+            &accession,
+            url,
+            ImageResource {
+                url: "https://files.example.org/image.jpg".to_string(),
+                format: "image/jpeg",
+                width: 1600,
+                height: 1200,
+            },
+        );
+        let manifest = serde_json::to_value(manifest).unwrap();
+        assert_eq!(manifest["@context"], json!(PRESENTATION_CONTEXT));
+        assert_eq!(manifest["id"], json!(url));
+        assert_eq!(manifest["type"], json!("Manifest"));
+        assert_eq!(
+            manifest["label"],
+            json!({"ar": ["عنوان"], "en": [accession.title_en.unwrap()]})
+        );
+        let canvas = &manifest["items"][0];
+        assert_eq!(
+            canvas["id"],
+            json!("https://api.example.org/api/v1/accessions/1/iiif/canvas/1")
+        );
+        assert_eq!(
+            (canvas["width"].clone(), canvas["height"].clone()),
+            (json!(1600), json!(1200))
+        );
+        let annotation = &canvas["items"][0]["items"][0];
+        assert_eq!(annotation["motivation"], json!("painting"));
+        assert_eq!(annotation["target"], canvas["id"]);
+        assert_eq!(
+            annotation["body"],
+            json!({
+                "id": "https://files.example.org/image.jpg",
+                "type": "Image",
+                "format": "image/jpeg",
+                "width": 1600,
+                "height": 1200
+            })
+        );
+    }
+}
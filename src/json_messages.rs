@@ -0,0 +1,167 @@
+//! JSON envelopes for the API's plain text messages.
+//!
+//! Many handlers answer with a bare string, e.g. "Subject deleted", "No such record" or
+//! the result of verifying a login. [`wrap_plain_text`] turns those into JSON for clients
+//! that ask for it, in the `{code, message, details}` shape structured errors already use:
+//!
+//! * error statuses get an [`ErrorResponse`], the same as handlers returning an
+//!   [`crate::models::error::ApiError`]
+//! * everything else gets a [`MessageResponse`]
+//!
+//! `code` is the status's reason phrase in snake case, e.g. `not_found` or `created`.
+//!
+//! Clients that don't ask keep getting plain text, so nothing changes for existing ones.
+//! JSON is sent for everything under `/api/v2`, and elsewhere when the `Accept` header
+//! names `application/json` but not `text/plain`. Messages in the envelope are translated
+//! like any other, see [`crate::i18n`].
+
+use crate::models::error::{ErrorDetail, ErrorResponse};
+use crate::models::response::MessageResponse;
+use axum::body::{to_bytes, HttpBody};
+use axum::extract::Request;
+use axum::http::{HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use http::header::{ACCEPT, CONTENT_TYPE, VARY};
+use http::HeaderValue;
+use tracing::error;
+
+/// Largest plain text body wrapped; anything bigger isn't a message
+const MAX_MESSAGE_BODY: u64 = 64 * 1024;
+
+/// Marks a response whose body was wrapped, so later middleware can tell a
+/// [`MessageResponse`] from other JSON.
+#[derive(Debug, Clone, Copy)]
+pub struct WrappedMessage;
+
+/// Whether a request should get plain text messages as JSON, see the module docs.
+fn wants_json(path: &str, headers: &HeaderMap) -> bool {
+    if path.starts_with("/api/v2/") {
+        return true;
+    }
+    let accept = headers
+        .get(ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    accept.contains("application/json") && !accept.contains("text/plain")
+}
+
+/// Machine readable code for a status, e.g. `not_found`.
+fn status_code(status: StatusCode) -> String {
+    if status == StatusCode::INTERNAL_SERVER_ERROR {
+        // what ApiError::internal reports
+        return "internal_error".to_string();
+    }
+    match status.canonical_reason() {
+        Some(reason) => reason.to_lowercase().replace([' ', '-'], "_"),
+        None => status.as_str().to_string(),
+    }
+}
+
+/// Builds the JSON envelope for a plain text message sent with `status`.
+fn envelope(status: StatusCode, message: String) -> Response {
+    let code = status_code(status);
+    let mut response = if status.is_client_error() || status.is_server_error() {
+        let body = ErrorResponse {
+            error: ErrorDetail {
+                code,
+                message,
+                details: None,
+                messages: None,
+            },
+        };
+        (status, Json(body)).into_response()
+    } else {
+        let body = MessageResponse {
+            code,
+            message,
+            details: None,
+            messages: None,
+        };
+        (status, Json(body)).into_response()
+    };
+    response.extensions_mut().insert(WrappedMessage);
+    response
+}
+
+/// Middleware wrapping plain text messages in JSON for clients that ask, see the module docs.
+pub async fn wrap_plain_text(request: Request, next: Next) -> Response {
+    let wants_json = wants_json(request.uri().path(), request.headers());
+    let response = next.run(request).await;
+    if !wants_json {
+        return response;
+    }
+
+    let is_plain_text = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("text/plain"));
+    let small = response
+        .body()
+        .size_hint()
+        .upper()
+        .is_some_and(|size| size <= MAX_MESSAGE_BODY);
+    if !is_plain_text || !small {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, MAX_MESSAGE_BODY as usize).await {
+        Ok(body) => body,
+        Err(err) => {
+            error!("Could not read plain text message to wrap in JSON: {}", err);
+            return envelope(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not read response".to_string(),
+            );
+        }
+    };
+    let message = String::from_utf8_lossy(&body).into_owned();
+    let mut wrapped = envelope(parts.status, message);
+    for (name, value) in parts.headers.iter() {
+        if name != CONTENT_TYPE && name != http::header::CONTENT_LENGTH {
+            wrapped.headers_mut().append(name, value.clone());
+        }
+    }
+    wrapped
+        .headers_mut()
+        .append(VARY, HeaderValue::from_static("accept"));
+    wrapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn accepting(accept: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_str(accept).unwrap());
+        headers
+    }
+
+    #[test]
+    fn negotiates_json() {
+        assert!(wants_json("/api/v1/auth", &accepting("application/json")));
+        assert!(wants_json("/api/v2/accessions/1", &HeaderMap::new()));
+        assert!(!wants_json("/api/v1/auth", &HeaderMap::new()));
+        assert!(!wants_json("/api/v1/auth", &accepting("*/*")));
+        assert!(!wants_json(
+            "/api/v1/auth",
+            &accepting("text/plain, application/json")
+        ));
+    }
+
+    #[test]
+    fn names_statuses() {
+        assert_eq!(status_code(StatusCode::NOT_FOUND), "not_found");
+        assert_eq!(status_code(StatusCode::CREATED), "created");
+        assert_eq!(status_code(StatusCode::OK), "ok");
+        assert_eq!(
+            status_code(StatusCode::INTERNAL_SERVER_ERROR),
+            "internal_error"
+        );
+    }
+}
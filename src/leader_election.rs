@@ -0,0 +1,141 @@
+//! Making sure only one instance of the API runs the background tasks.
+//!
+//! Every instance runs a [`Scheduler`](crate::scheduler::Scheduler), so with several
+//! replicas behind the load balancer fixity checks, link rot checks and digests would run
+//! once per replica. Instead the instances elect a leader through a Postgres advisory lock,
+//! see [`crate::repos::leader_election_repo`], and the scheduler skips runs on every
+//! instance but the leader.
+//!
+//! Each instance tries to take the lock every [`LEADER_ELECTION_INTERVAL`], and the leader
+//! checks it still holds it. When the leader stops or loses its database Postgres lets go
+//! of the lock and another instance takes over on its next attempt, so tasks carry on
+//! after at most one interval. An instance that can't reach the database steps down, so
+//! two instances never both think they're the leader.
+
+use crate::repos::leader_election_repo::LeaderElectionRepo;
+use sea_orm::DbErr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use tracing::{error, info};
+
+/// How often instances try to become the leader, and the leader checks it still is
+pub const LEADER_ELECTION_INTERVAL: Duration = Duration::from_secs(15);
+
+/// This instance's part in the election.
+pub struct LeaderElection {
+    instance: String,
+    leading: AtomicBool,
+    repo: Arc<dyn LeaderElectionRepo>,
+}
+
+pub type SharedLeaderElection = Arc<LeaderElection>;
+
+impl LeaderElection {
+    /// # Arguments
+    /// * `instance` - Name of this instance, see [`crate::config::AppConfig::instance_name`]
+    /// * `repo` - Where the election is held
+    pub fn new(instance: String, repo: Arc<dyn LeaderElectionRepo>) -> Self {
+        Self {
+            instance,
+            leading: AtomicBool::new(false),
+            repo,
+        }
+    }
+
+    pub fn instance(&self) -> &str {
+        &self.instance
+    }
+
+    /// Whether this instance was the leader when it last checked.
+    pub fn is_leader(&self) -> bool {
+        self.leading.load(Ordering::SeqCst)
+    }
+
+    /// Name of the instance that is the leader, `None` while no instance is.
+    pub async fn current_leader(&self) -> Result<Option<String>, DbErr> {
+        self.repo.current_leader().await
+    }
+
+    /// Tries to become or stay the leader once, stepping down if the database can't say.
+    ///
+    /// # Returns
+    /// Whether this instance is now the leader
+    pub async fn campaign(&self) -> bool {
+        let leading = self
+            .repo
+            .try_lead(&self.instance)
+            .await
+            .unwrap_or_else(|err| {
+                error!(%err, "Could not check leadership, not running background tasks");
+                false
+            });
+        let was_leading = self.leading.swap(leading, Ordering::SeqCst);
+        match (was_leading, leading) {
+            (false, true) => info!("Instance {} is now running background tasks", self.instance),
+            (true, false) => info!(
+                "Instance {} stopped running background tasks",
+                self.instance
+            ),
+            _ => {}
+        }
+        leading
+    }
+
+    /// Spawns a loop campaigning every [`LEADER_ELECTION_INTERVAL`], starting right away.
+    ///
+    /// # Returns
+    /// Handle of the loop, which runs until the server shuts down
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                self.campaign().await;
+                sleep(LEADER_ELECTION_INTERVAL).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::InMemoryLeaderElectionRepo;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn elects_one_leader_and_fails_over() {
+        let repo = Arc::new(InMemoryLeaderElectionRepo::default());
+        let first = LeaderElection::new("api-1".to_string(), repo.clone());
+        let second = LeaderElection::new("api-2".to_string(), repo.clone());
+        assert!(!first.is_leader());
+
+        assert!(first.campaign().await);
+        assert!(first.campaign().await);
+        assert!(!second.campaign().await);
+        assert!(first.is_leader() && !second.is_leader());
+        assert_eq!(
+            second.current_leader().await.unwrap(),
+            Some("api-1".to_string())
+        );
+
+        // the first instance's session ended
+        repo.release();
+        assert!(second.campaign().await);
+        assert!(!first.campaign().await);
+        assert!(!first.is_leader());
+    }
+
+    #[tokio::test]
+    async fn steps_down_when_the_database_is_unreachable() {
+        let repo = Arc::new(InMemoryLeaderElectionRepo::default());
+        let election = LeaderElection::new("api-1".to_string(), repo.clone());
+        assert!(election.campaign().await);
+
+        repo.set_unreachable(true);
+        assert!(!election.campaign().await);
+        assert!(!election.is_leader());
+        assert!(election.current_leader().await.is_err());
+    }
+}
@@ -0,0 +1,157 @@
+//! Drafting metadata in the language an accession is missing.
+//!
+//! Accessions should be described in both English and Arabic, but are usually catalogued
+//! in only one. A translation service drafts the missing language's title and description,
+//! which are saved through the normal update path flagged as machine translated, so they
+//! can be told apart until someone reviews and edits them. Subjects are kept per language
+//! and aren't translated, so drafts start without any.
+
+use crate::models::common::MetadataLanguage;
+use crate::models::request::{UpdateAccessionRequest, UpdateMetadataRequest};
+use ::entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
+
+/// Longest title accepted by [`UpdateMetadataRequest`]
+const TITLE_MAX_CHARS: usize = 200;
+
+/// Longest description accepted by [`UpdateMetadataRequest`]
+const DESCRIPTION_MAX_CHARS: usize = 2000;
+
+/// What to send to the translation service for one accession
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranslationSource {
+    /// ISO 639-1 code of the language the accession is described in
+    pub source: &'static str,
+    /// ISO 639-1 code of the language it is missing
+    pub target: &'static str,
+    pub target_language: MetadataLanguage,
+    /// The title, followed by the description if there is one
+    pub texts: Vec<String>,
+}
+
+/// Works out which language an accession is missing and what to translate into it.
+///
+/// # Returns
+/// The texts to translate, or why there is nothing to translate
+pub fn translation_source(
+    accession: &AccessionWithMetadataModel,
+) -> Result<TranslationSource, &'static str> {
+    let (source, target, target_language, title, description) = match (
+        accession.has_english_metadata,
+        accession.has_arabic_metadata,
+    ) {
+        (true, false) => (
+            "en",
+            "ar",
+            MetadataLanguage::Arabic,
+            &accession.title_en,
+            &accession.description_en,
+        ),
+        (false, true) => (
+            "ar",
+            "en",
+            MetadataLanguage::English,
+            &accession.title_ar,
+            &accession.description_ar,
+        ),
+        (true, true) => return Err("Accession already has metadata in both languages"),
+        (false, false) => return Err("Accession has no metadata to translate"),
+    };
+    let mut texts = vec![title.clone().unwrap_or_default()];
+    texts.extend(description.clone());
+    Ok(TranslationSource {
+        source,
+        target,
+        target_language,
+        texts,
+    })
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    text.trim().chars().take(max_chars).collect()
+}
+
+/// Builds the update that saves a translation as the accession's missing language.
+///
+/// Only the title and description are set, so everything else is kept as it is, and
+/// translations are cut to the lengths the update endpoint accepts.
+///
+/// # Arguments
+/// * `target_language` - The language the translation is in
+/// * `translations` - The translated title, followed by the description if there is one
+pub fn draft_update(
+    target_language: MetadataLanguage,
+    translations: &[String],
+) -> UpdateAccessionRequest {
+    let metadata = UpdateMetadataRequest {
+        title: Some(
+            translations
+                .first()
+                .map(|title| truncate(title, TITLE_MAX_CHARS))
+                .unwrap_or_default(),
+        ),
+        description: Some(
+            translations
+                .get(1)
+                .map(|description| truncate(description, DESCRIPTION_MAX_CHARS)),
+        ),
+        machine_translated: true,
+        ..Default::default()
+    };
+    match target_language {
+        MetadataLanguage::English => UpdateAccessionRequest {
+            metadata_en: Some(metadata),
+            ..Default::default()
+        },
+        MetadataLanguage::Arabic => UpdateAccessionRequest {
+            metadata_ar: Some(metadata),
+            ..Default::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::mock_one_accession_with_metadata;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn translates_into_the_missing_language() {
+        let mut accession = mock_one_accession_with_metadata();
+        accession.has_english_metadata = false;
+        accession.description_ar = None;
+        assert_eq!(
+            translation_source(&accession),
+            Ok(TranslationSource {
+                source: "ar",
+                target: "en",
+                target_language: MetadataLanguage::English,
+                texts: vec!["Arabic Title".to_string()],
+            })
+        );
+
+        accession.has_english_metadata = true;
+        assert_eq!(
+            translation_source(&accession),
+            Err("Accession already has metadata in both languages")
+        );
+    }
+
+    #[test]
+    fn drafts_a_flagged_update_within_length_limits() {
+        let draft = draft_update(
+            MetadataLanguage::Arabic,
+            &[" عنوان ".to_string(), "و".repeat(2100)],
+        );
+        assert!(draft.metadata_en.is_none());
+        let metadata = draft.metadata_ar.unwrap();
+        assert!(metadata.machine_translated);
+        assert_eq!(metadata.title.as_deref(), Some("عنوان"));
+        assert_eq!(
+            metadata.description.flatten().map(|d| d.chars().count()),
+            Some(2000)
+        );
+        assert_eq!(metadata.subjects, None);
+        assert_eq!(draft.metadata_time, None);
+    }
+}
@@ -1,54 +1,243 @@
+mod access_log_export;
+mod activity_digest;
 mod app_factory;
+mod audit_log_export;
 mod auth;
+mod capture_quality;
+mod citation_export;
+mod client_country;
+mod client_ip;
+mod clock;
+mod collection_export;
+mod comments;
 mod config;
+mod content_digest;
+mod crawl_blocklist;
+mod crawl_queue;
+mod crawl_state_machine;
+mod email_outbox;
+mod email_suppression;
+mod file_access;
+mod file_type;
+mod i18n;
+mod iiif;
+mod json_messages;
+mod leader_election;
+mod machine_translation;
+mod memento;
+mod metadata_scrubber;
 mod models;
+mod onion;
 mod open_api_spec;
+mod permissions;
+mod pipeline_metrics;
+mod provenance;
+mod publication_feed;
+mod publication_workflow;
+mod quick_capture;
+mod reindex;
 mod repos;
+mod request_log;
 mod routes;
+mod s3_backfill;
+mod s3_keys;
+mod scheduled_tasks;
+mod scheduler;
+mod seed;
 mod services;
+mod social_metadata;
+mod static_export;
+mod subject_suggestions;
+#[cfg(test)]
+mod test_db;
 #[cfg(test)]
 mod test_tools;
+mod upload_progress;
+mod url_canonicalizer;
+mod wacz;
 
 use crate::app_factory::{create_app, AppState};
-use crate::config::build_app_config;
-use crate::repos::accessions_repo::DBAccessionsRepo;
-use crate::repos::auth_repo::DBAuthRepo;
+use crate::client_ip::KnownApiKeys;
+use crate::clock::{SharedClock, SharedIdGen};
+use crate::collection_export::new_export_permits;
+use crate::config::{build_app_config, TimestampService};
+use crate::crawl_queue::new_crawl_queue;
+use crate::email_outbox::{EmailOutbox, OutboxEmailsRepo};
+use crate::email_suppression::SuppressingEmailsRepo;
+use crate::leader_election::LeaderElection;
+use crate::pipeline_metrics::new_pipeline_metrics;
+use crate::provenance::ProvenanceSigner;
+use crate::publication_feed::PublicationFeed;
+use crate::reindex::ReindexProgress;
+use crate::repos::accession_events_repo::DBAccessionEventsRepo;
+use crate::repos::accession_relations_repo::DBAccessionRelationsRepo;
+use crate::repos::accessions_repo::{AccessionsRepo, DBAccessionsRepo};
+use crate::repos::activity_digest_repo::DBActivityDigestRepo;
+use crate::repos::audit_log_repo::{AuditLogRepo, DBAuditLogRepo};
+use crate::repos::auth_repo::{AuthRepo, DBAuthRepo};
 use crate::repos::browsertrix_repo::{BrowsertrixRepo, HTTPBrowsertrixRepo};
-use crate::repos::emails_repo::PostmarkEmailsRepo;
+use crate::repos::collections_repo::DBCollectionsRepo;
+use crate::repos::comments_repo::DBCommentsRepo;
+use crate::repos::content_digest_repo::DBContentDigestRepo;
+use crate::repos::crawl_blocklist_repo::DBCrawlBlocklistRepo;
+use crate::repos::crawler_repo::Crawlers;
+use crate::repos::doi_repo::{DataCiteDoiRepo, DoiRepo};
+use crate::repos::emails_repo::{EmailsRepo, PostmarkEmailsRepo};
+use crate::repos::entity_extractor_repo::{EntityExtractorRepo, HTTPEntityExtractorRepo};
+use crate::repos::feature_flags_repo::DBFeatureFlagsRepo;
+use crate::repos::leader_election_repo::DBLeaderElectionRepo;
+use crate::repos::local_crawler_repo::LocalCrawlerRepo;
+use crate::repos::media_transcoder_repo::{FfmpegMediaTranscoderRepo, MediaTranscoderRepo};
+use crate::repos::oembed_repo::HTTPOembedRepo;
+use crate::repos::organizations_repo::DBOrganizationsRepo;
+use crate::repos::pdf_renderer_repo::{HTTPPdfRendererRepo, PdfRendererRepo};
+use crate::repos::provenance_repo::{DBProvenanceRepo, ProvenanceRepo};
 use crate::repos::s3_repo::{DigitalOceanSpacesRepo, S3Repo};
-use crate::repos::subjects_repo::DBSubjectsRepo;
+use crate::repos::social_metadata_repo::DBSocialMetadataRepo;
+use crate::repos::subjects_repo::{DBSubjectsRepo, SubjectsRepo};
+use crate::repos::timestamp_repo::{OpenTimestampsRepo, Rfc3161TimestampRepo, TimestampRepo};
+use crate::repos::translation_repo::{HTTPTranslationRepo, TranslationRepo};
+use crate::repos::uploads_repo::DBUploadsRepo;
+use crate::repos::video_downloader_repo::{VideoDownloaderRepo, YtDlpVideoDownloaderRepo};
+use crate::repos::virus_scanner_repo::{ClamdVirusScannerRepo, VirusScannerRepo};
+use crate::repos::workflow_labels_repo::DBWorkflowLabelsRepo;
+use crate::s3_backfill::S3BackfillProgress;
+use crate::scheduled_tasks::{
+    ActivityDigestTask, EmailRetryTask, EmbargoLiftTask, FixityCheckTask, LinkRotCheckTask,
+    SessionCleanupTask, TimestampTask,
+};
+use crate::scheduler::{new_scheduler_metrics, Scheduler};
+use crate::seed::run_seed;
 use crate::services::accessions_service::AccessionsService;
+use crate::services::audit_log_service::AuditLogService;
 use crate::services::auth_service::AuthService;
+use crate::services::collections_service::CollectionsService;
+use crate::services::crawl_blocklist_service::CrawlBlocklistService;
+use crate::services::flags_service::{new_feature_flags_cache, FlagsService};
+use crate::services::organizations_service::OrganizationsService;
+use crate::services::stats_service::{new_public_stats_cache, StatsService};
 use crate::services::subjects_service::SubjectsService;
+use crate::services::uploads_service::UploadsService;
+use crate::services::workflow_labels_service::WorkflowLabelsService;
+use crate::static_export::StaticExportProgress;
+use crate::upload_progress::UploadProgressRegistry;
+use crate::wacz::new_wacz_pages_cache;
 use reqwest::Client;
-use sea_orm::Database;
+use sea_orm::{ConnectOptions, Database};
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tokio::sync::RwLock;
 use tracing::info;
 
 #[tokio::main]
 async fn main() {
-    let app_config = build_app_config();
+    if std::env::args().nth(1).as_deref() == Some("seed") {
+        run_seed().await;
+        return;
+    }
+    let app_config = build_app_config().unwrap_or_else(|errors| {
+        eprintln!("{errors}");
+        std::process::exit(1);
+    });
     let dolly_the_app_config = app_config.clone();
+    // the leader lock belongs to the connection that took it, so it gets one of its own
+    // that is never recycled
+    let mut leader_election_options = ConnectOptions::new(app_config.postgres_url.clone());
+    leader_election_options
+        .max_connections(1)
+        .min_connections(1)
+        .max_lifetime(Duration::from_secs(365 * 24 * 60 * 60))
+        .sqlx_logging(false);
+    let leader_election_db = Database::connect(leader_election_options)
+        .await
+        .expect("Could not connect to db for leader election");
     let db_session = Database::connect(app_config.postgres_url)
         .await
         .expect("Could not connect to db");
-    let accessions_repo = DBAccessionsRepo {
+    let clock = SharedClock::default();
+    let ids = SharedIdGen::default();
+    let accessions_repo: Arc<dyn AccessionsRepo> = Arc::new(DBAccessionsRepo {
         db_session: db_session.clone(),
-    };
-    let auth_repo = DBAuthRepo {
+        clock: clock.clone(),
+    });
+    let auth_repo: Arc<dyn AuthRepo> = Arc::new(DBAuthRepo {
         db_session: db_session.clone(),
         expiry_hours: app_config.jwt_expiry_hours,
-    };
-    let emails_repo = PostmarkEmailsRepo {
+        clock: clock.clone(),
+        ids: ids.clone(),
+    });
+    let postmark_emails_repo: Arc<dyn EmailsRepo> = Arc::new(PostmarkEmailsRepo {
         client: Client::new(),
         archive_sender_email: app_config.archive_sender_email,
         api_key: app_config.postmark_api_key,
         postmark_api_base: app_config.postmark_api_base,
+    });
+    let email_outbox = EmailOutbox::default();
+    let emails_repo: Arc<dyn EmailsRepo> = Arc::new(SuppressingEmailsRepo {
+        emails_repo: Arc::new(OutboxEmailsRepo {
+            emails_repo: postmark_emails_repo.clone(),
+            outbox: email_outbox.clone(),
+        }),
+        auth_repo: auth_repo.clone(),
+    });
+    let subjects_repo = DBSubjectsRepo {
+        db_session: db_session.clone(),
+        clock: clock.clone(),
+    };
+    let workflow_labels_repo = DBWorkflowLabelsRepo {
+        db_session: db_session.clone(),
+    };
+    let collections_repo = DBCollectionsRepo {
+        db_session: db_session.clone(),
+        clock: clock.clone(),
+    };
+    let organizations_repo = DBOrganizationsRepo {
+        db_session: db_session.clone(),
+        clock: clock.clone(),
+    };
+    let feature_flags_repo = DBFeatureFlagsRepo {
+        db_session: db_session.clone(),
+        clock: clock.clone(),
+    };
+    let crawl_blocklist_repo = DBCrawlBlocklistRepo {
+        db_session: db_session.clone(),
+        clock: clock.clone(),
+    };
+    let activity_digest_repo = DBActivityDigestRepo {
+        db_session: db_session.clone(),
+    };
+    let accession_events_repo = DBAccessionEventsRepo {
+        db_session: db_session.clone(),
+        clock: clock.clone(),
+    };
+    let accession_relations_repo = DBAccessionRelationsRepo {
+        db_session: db_session.clone(),
+        clock: clock.clone(),
+    };
+    let comments_repo = DBCommentsRepo {
+        db_session: db_session.clone(),
+        clock: clock.clone(),
+    };
+    let provenance_repo: Arc<dyn ProvenanceRepo> = Arc::new(DBProvenanceRepo {
+        db_session: db_session.clone(),
+    });
+    let content_digest_repo = DBContentDigestRepo {
+        db_session: db_session.clone(),
+        clock: clock.clone(),
+    };
+    let social_metadata_repo = DBSocialMetadataRepo {
+        db_session: db_session.clone(),
+    };
+    let audit_log_repo: Arc<dyn AuditLogRepo> = Arc::new(DBAuditLogRepo {
+        db_session: db_session.clone(),
+        clock: clock.clone(),
+    });
+    let uploads_repo = DBUploadsRepo {
+        db_session,
+        clock: clock.clone(),
     };
-    let subjects_repo = DBSubjectsRepo { db_session };
     let mut http_btrix_repo = HTTPBrowsertrixRepo {
         client: Client::new(),
         login_url: app_config.browsertrix.login_url,
@@ -60,6 +249,15 @@ async fn main() {
         create_crawl_url: app_config.browsertrix.create_crawl_url,
     };
     http_btrix_repo.initialize().await;
+    let http_btrix_repo = Arc::new(http_btrix_repo);
+    let mut crawlers = Crawlers::new(app_config.crawler).with(http_btrix_repo.clone());
+    if let Some(local_crawler) = app_config.local_crawler {
+        crawlers = crawlers.with(Arc::new(LocalCrawlerRepo::new(
+            local_crawler.command,
+            local_crawler.collections_dir,
+            ids.clone(),
+        )));
+    }
     let digital_ocean_spaces_repo = DigitalOceanSpacesRepo::new(
         app_config.digital_ocean_spaces_bucket,
         &app_config.digital_ocean_spaces_endpoint_url,
@@ -71,24 +269,205 @@ async fn main() {
     )
     .await
     .expect("Could not create DigitalOcean Spaces repo");
+    let s3_repo: Arc<dyn S3Repo> = Arc::new(digital_ocean_spaces_repo);
+    let pdf_renderer_repo = app_config.pdf_renderer_url.map(|base_url| {
+        Arc::new(HTTPPdfRendererRepo {
+            client: Client::new(),
+            base_url,
+        }) as Arc<dyn PdfRendererRepo>
+    });
+    let virus_scanner_repo = app_config
+        .clamav_address
+        .map(|address| Arc::new(ClamdVirusScannerRepo { address }) as Arc<dyn VirusScannerRepo>);
+    let media_transcoder_repo = app_config.ffmpeg_path.map(|ffmpeg_path| {
+        Arc::new(FfmpegMediaTranscoderRepo { ffmpeg_path }) as Arc<dyn MediaTranscoderRepo>
+    });
+    let video_downloader_repo = app_config.ytdlp_path.map(|ytdlp_path| {
+        Arc::new(YtDlpVideoDownloaderRepo { ytdlp_path }) as Arc<dyn VideoDownloaderRepo>
+    });
+    let translation_api_key = app_config.translation_api_key;
+    let translation_repo = app_config.translation_api_url.map(|base_url| {
+        Arc::new(HTTPTranslationRepo {
+            client: Client::new(),
+            base_url,
+            api_key: translation_api_key,
+        }) as Arc<dyn TranslationRepo>
+    });
+    let doi_repo = app_config.datacite.map(|datacite| {
+        Arc::new(DataCiteDoiRepo {
+            client: Client::new(),
+            base_url: datacite.api_url,
+            repository_id: datacite.repository_id,
+            password: datacite.password,
+            prefix: datacite.prefix,
+        }) as Arc<dyn DoiRepo>
+    });
+    let provenance_signer = app_config.provenance_signing_key.map(|key| {
+        Arc::new(ProvenanceSigner::from_base64_seed(&key).expect("checked when config was read"))
+    });
+    let publication_feed = PublicationFeed::default();
     let accessions_service = AccessionsService {
-        accessions_repo: Arc::new(accessions_repo),
-        browsertrix_repo: Arc::new(http_btrix_repo),
-        emails_repo: Arc::new(emails_repo.clone()),
-        s3_repo: Arc::new(digital_ocean_spaces_repo),
+        accessions_repo: accessions_repo.clone(),
+        browsertrix_repo: http_btrix_repo,
+        crawlers,
+        emails_repo: emails_repo.clone(),
+        s3_repo: s3_repo.clone(),
+        pdf_renderer_repo,
+        media_transcoder_repo,
+        video_downloader_repo,
+        virus_scanner_repo,
+        translation_repo,
+        doi_repo,
+        scan_enforcement: app_config.scan_enforcement,
+        s3_key_scheme: app_config.s3_key_scheme,
+        public_api_url: app_config.public_api_url,
+        wacz_pages_cache: new_wacz_pages_cache(),
+        pipeline_metrics: new_pipeline_metrics(clock.clone()),
+        crawl_queue: new_crawl_queue(app_config.max_active_crawls),
+        upload_progress: UploadProgressRegistry::default(),
+        accession_events_repo: Arc::new(accession_events_repo),
+        accession_relations_repo: Arc::new(accession_relations_repo),
+        comments_repo: Arc::new(comments_repo),
+        provenance_repo: provenance_repo.clone(),
+        provenance_signer,
+        social_metadata_repo: Arc::new(social_metadata_repo),
+        content_digest_repo: Arc::new(content_digest_repo),
+        oembed_repo: Arc::new(HTTPOembedRepo::default()),
+        audit_log_repo: audit_log_repo.clone(),
+        publication_feed: publication_feed.clone(),
+        s3_backfill: S3BackfillProgress::default(),
+        static_export: StaticExportProgress::default(),
+        reindex: ReindexProgress::default(),
+        clock: clock.clone(),
+        ids: ids.clone(),
+    };
+    let collections_service = CollectionsService {
+        collections_repo: Arc::new(collections_repo),
+        accessions_repo: accessions_repo.clone(),
+        s3_repo: s3_repo.clone(),
+        emails_repo: emails_repo.clone(),
+        clock: clock.clone(),
+        ids: ids.clone(),
+        export_permits: new_export_permits(),
     };
     let auth_service = AuthService {
-        auth_repo: Arc::new(auth_repo),
-        emails_repo: Arc::new(emails_repo),
+        auth_repo: auth_repo.clone(),
+        emails_repo: emails_repo.clone(),
+        audit_log_repo: audit_log_repo.clone(),
         jwt_cookie_domain: app_config.jwt_cookie_domain,
+        postmark_webhook_secret: app_config.postmark_webhook_secret,
+        known_api_keys: KnownApiKeys::default(),
+        clock: clock.clone(),
     };
+    let entity_extractor_repo = app_config.ner_api_url.map(|base_url| {
+        Arc::new(HTTPEntityExtractorRepo {
+            client: Client::new(),
+            base_url,
+        }) as Arc<dyn EntityExtractorRepo>
+    });
+    let subjects_repo: Arc<dyn SubjectsRepo> = Arc::new(subjects_repo);
     let subjects_service = SubjectsService {
-        subjects_repo: Arc::new(subjects_repo),
+        subjects_repo: subjects_repo.clone(),
+        entity_extractor_repo,
+    };
+    let workflow_labels_service = WorkflowLabelsService {
+        workflow_labels_repo: Arc::new(workflow_labels_repo),
+    };
+    let organizations_service = OrganizationsService {
+        organizations_repo: Arc::new(organizations_repo),
+    };
+    let uploads_service = UploadsService {
+        uploads_repo: Arc::new(uploads_repo),
+        s3_repo: s3_repo.clone(),
+        ids: ids.clone(),
     };
+    let flags_service = FlagsService {
+        feature_flags_repo: Arc::new(feature_flags_repo),
+        environment: app_config.environment,
+        flags_cache: new_feature_flags_cache(),
+    };
+    let crawl_blocklist_service = CrawlBlocklistService {
+        crawl_blocklist_repo: Arc::new(crawl_blocklist_repo),
+    };
+    let audit_log_service = AuditLogService { audit_log_repo };
+    let stats_service = StatsService {
+        accessions_repo: accessions_repo.clone(),
+        subjects_repo,
+        s3_repo: s3_repo.clone(),
+        stats_cache: new_public_stats_cache(),
+    };
+    let timestamp_repo = app_config.timestamping.map(|timestamping| {
+        let client = Client::new();
+        match timestamping.service {
+            TimestampService::OpenTimestamps => Arc::new(OpenTimestampsRepo {
+                client,
+                calendar_url: timestamping.url,
+            }) as Arc<dyn TimestampRepo>,
+            TimestampService::Rfc3161 => Arc::new(Rfc3161TimestampRepo {
+                client,
+                tsa_url: timestamping.url,
+            }) as Arc<dyn TimestampRepo>,
+        }
+    });
+    let scheduler_metrics = new_scheduler_metrics();
+    let leader_election = Arc::new(LeaderElection::new(
+        app_config.instance_name,
+        Arc::new(DBLeaderElectionRepo {
+            db_session: leader_election_db,
+        }),
+    ));
+    leader_election.clone().start();
+    let mut scheduler = Scheduler::new(
+        scheduler_metrics.clone(),
+        clock.clone(),
+        leader_election.clone(),
+    )
+    .register(Arc::new(SessionCleanupTask { auth_repo }))
+    .register(Arc::new(FixityCheckTask::new(
+        accessions_repo.clone(),
+        s3_repo,
+    )))
+    .register(Arc::new(LinkRotCheckTask::new(
+        accessions_repo.clone(),
+        Client::new(),
+    )))
+    .register(Arc::new(EmbargoLiftTask {
+        accessions_repo,
+        publication_feed,
+        clock: clock.clone(),
+    }))
+    .register(Arc::new(EmailRetryTask {
+        emails_repo: postmark_emails_repo,
+        outbox: email_outbox,
+    }))
+    .register(Arc::new(ActivityDigestTask {
+        activity_digest_repo: Arc::new(activity_digest_repo),
+        emails_repo,
+        clock: clock.clone(),
+    }));
+    if let Some(timestamp_repo) = timestamp_repo {
+        scheduler = scheduler.register(Arc::new(TimestampTask {
+            provenance_repo,
+            timestamp_repo,
+            clock: clock.clone(),
+        }));
+    }
+    scheduler.start();
     let app_state = AppState {
         accessions_service,
         auth_service,
         subjects_service,
+        workflow_labels_service,
+        collections_service,
+        organizations_service,
+        uploads_service,
+        flags_service,
+        crawl_blocklist_service,
+        stats_service,
+        audit_log_service,
+        scheduler_metrics,
+        leader_election,
+        clock,
     };
     let app = create_app(app_state, dolly_the_app_config, false);
 
@@ -0,0 +1,205 @@
+//! Memento protocol support, see RFC 7089.
+//!
+//! Memento lets clients ask a web archive for its captures of a URL without knowing how
+//! the archive is organised: a TimeGate redirects to the capture closest to a datetime
+//! and a TimeMap lists every capture. Captures are matched by canonical URL, see
+//! [`crate::url_canonicalizer`], and dated by when they were crawled. Each capture's
+//! memento is its page on the archive's website.
+
+use chrono::{DateTime, NaiveDateTime};
+
+/// Base of the public archive pages mementos point at
+const ARCHIVE_PAGE_URL: &str = "https://sudandigitalarchive.com/archive";
+
+/// Content type of TimeMaps
+pub const LINK_FORMAT_CONTENT_TYPE: &str = "application/link-format";
+
+/// A public capture of a URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capture {
+    pub accession_id: i32,
+    pub captured_at: NaiveDateTime,
+}
+
+/// Absolute URLs of the Memento resources for one original URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MementoUrls {
+    pub original: String,
+    pub timegate: String,
+    pub timemap: String,
+}
+
+impl MementoUrls {
+    /// # Arguments
+    /// * `api_url` - Public URL of the API's v1 routes, without a trailing slash
+    /// * `original` - The original URL as the client asked for it
+    pub fn new(api_url: &str, original: &str) -> Self {
+        Self {
+            original: original.to_string(),
+            timegate: format!("{api_url}/timegate/{original}"),
+            timemap: format!("{api_url}/timemap/link/{original}"),
+        }
+    }
+
+    /// `Link` header a TimeGate sends with its redirect
+    pub fn timegate_link_header(&self) -> String {
+        format!(
+            "<{}>; rel=\"original\", <{}>; rel=\"timemap\"; type=\"{LINK_FORMAT_CONTENT_TYPE}\"",
+            self.original, self.timemap
+        )
+    }
+
+    /// Renders a TimeMap in link format.
+    ///
+    /// # Arguments
+    /// * `captures` - Every capture of the URL, oldest first; must not be empty
+    pub fn timemap(&self, captures: &[Capture]) -> String {
+        let mut links = vec![
+            format!("<{}>; rel=\"original\"", self.original),
+            format!(
+                "<{}>; rel=\"self\"; type=\"{LINK_FORMAT_CONTENT_TYPE}\"; from=\"{}\"; until=\"{}\"",
+                self.timemap,
+                captures.first().map_or(String::new(), |c| http_date(c.captured_at)),
+                captures.last().map_or(String::new(), |c| http_date(c.captured_at)),
+            ),
+            format!("<{}>; rel=\"timegate\"", self.timegate),
+        ];
+        let last = captures.len().saturating_sub(1);
+        for (index, capture) in captures.iter().enumerate() {
+            let rel = match (index == 0, index == last) {
+                (true, true) => "first last memento",
+                (true, false) => "first memento",
+                (false, true) => "last memento",
+                (false, false) => "memento",
+            };
+            links.push(format!(
+                "<{}>; rel=\"{rel}\"; datetime=\"{}\"",
+                memento_url(capture.accession_id),
+                http_date(capture.captured_at)
+            ));
+        }
+        links.join(",\n") + "\n"
+    }
+}
+
+/// URL of the memento for an accession
+pub fn memento_url(accession_id: i32) -> String {
+    format!("{ARCHIVE_PAGE_URL}/{accession_id}")
+}
+
+/// Formats a UTC datetime as an HTTP date, e.g. `Tue, 20 Jun 2000 18:02:59 GMT`.
+pub fn http_date(datetime: NaiveDateTime) -> String {
+    datetime.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parses an `Accept-Datetime` header, which is an HTTP date.
+///
+/// Returns `None` if the header isn't a valid date.
+pub fn parse_accept_datetime(value: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|datetime| datetime.naive_utc())
+}
+
+/// Picks the capture a TimeGate redirects to.
+///
+/// That is the capture closest to `datetime`, preferring the earlier one on a tie, or the
+/// latest capture when no datetime was asked for.
+pub fn closest_capture(captures: &[Capture], datetime: Option<NaiveDateTime>) -> Option<&Capture> {
+    let Some(datetime) = datetime else {
+        return captures.last();
+    };
+    captures
+        .iter()
+        .min_by_key(|capture| (capture.captured_at - datetime).abs())
+}
+
+/// Restores the original URL from a TimeGate or TimeMap path.
+///
+/// Proxies sometimes merge the double slash after the scheme, so `http:/example.com` is
+/// taken to mean `http://example.com`. Query strings aren't part of the path, so they
+/// are passed separately.
+pub fn original_url(path: &str, query: Option<&str>) -> String {
+    let mut url = path.to_string();
+    for scheme in ["http:/", "https:/"] {
+        if url.starts_with(scheme) && !url[scheme.len()..].starts_with('/') {
+            url.insert(scheme.len(), '/');
+        }
+    }
+    if let Some(query) = query.filter(|query| !query.is_empty()) {
+        url = format!("{url}?{query}");
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    fn capture(accession_id: i32, day: u32) -> Capture {
+        Capture {
+            accession_id,
+            captured_at: NaiveDate::from_ymd_opt(2024, 5, day)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn parses_and_formats_http_dates() {
+        let datetime = parse_accept_datetime("Thu, 02 May 2024 12:00:00 GMT").unwrap();
+        assert_eq!(datetime, capture(1, 2).captured_at);
+        assert_eq!(http_date(datetime), "Thu, 02 May 2024 12:00:00 GMT");
+        assert_eq!(parse_accept_datetime("2024-05-02"), None);
+    }
+
+    #[test]
+    fn picks_the_closest_capture() {
+        let captures = [capture(1, 1), capture(2, 10), capture(3, 20)];
+        assert_eq!(closest_capture(&captures, None), Some(&captures[2]));
+        let on = |day| Some(capture(0, day).captured_at);
+        assert_eq!(closest_capture(&captures, on(4)), Some(&captures[0]));
+        assert_eq!(closest_capture(&captures, on(15)), Some(&captures[1]));
+        assert_eq!(closest_capture(&captures, on(31)), Some(&captures[2]));
+        assert_eq!(closest_capture(&[], None), None);
+    }
+
+    #[test]
+    fn renders_timemaps() {
+        let urls = MementoUrls::new("https://api.example.org/api/v1", "https://example.com/");
+        let timemap = urls.timemap(&[capture(1, 1), capture(2, 10), capture(3, 20)]);
+        assert_eq!(
+            timemap,
+            "<https://example.com/>; rel=\"original\",\n\
+             <https://api.example.org/api/v1/timemap/link/https://example.com/>; rel=\"self\"; \
+             type=\"application/link-format\"; from=\"Wed, 01 May 2024 12:00:00 GMT\"; \
+             until=\"Mon, 20 May 2024 12:00:00 GMT\",\n\
+             <https://api.example.org/api/v1/timegate/https://example.com/>; rel=\"timegate\",\n\
+             <https://sudandigitalarchive.com/archive/1>; rel=\"first memento\"; \
+             datetime=\"Wed, 01 May 2024 12:00:00 GMT\",\n\
+             <https://sudandigitalarchive.com/archive/2>; rel=\"memento\"; \
+             datetime=\"Fri, 10 May 2024 12:00:00 GMT\",\n\
+             <https://sudandigitalarchive.com/archive/3>; rel=\"last memento\"; \
+             datetime=\"Mon, 20 May 2024 12:00:00 GMT\"\n"
+        );
+        assert!(urls
+            .timemap(&[capture(1, 1)])
+            .contains("rel=\"first last memento\""));
+    }
+
+    #[test]
+    fn restores_original_urls() {
+        assert_eq!(
+            original_url("https:/example.com/a", Some("b=1")),
+            "https://example.com/a?b=1"
+        );
+        assert_eq!(
+            original_url("http://example.com/", None),
+            "http://example.com/"
+        );
+        assert_eq!(original_url("example.com", Some("")), "example.com");
+    }
+}
@@ -0,0 +1,553 @@
+//! Strips identifying metadata from images and videos as they are uploaded.
+//!
+//! Photos and videos taken on phones usually carry GPS coordinates and device details,
+//! which can put contributors at risk. Files are scrubbed while they stream to S3 so
+//! the original metadata never gets stored:
+//! - JPEG: EXIF and XMP (`APP1`), Photoshop/IPTC (`APP13`) and comment segments are dropped
+//! - PNG: `eXIf`, text and timestamp chunks are dropped
+//! - MP4: `udta` and `meta` boxes are blanked out into `free` boxes of the same size, since
+//!   shrinking the `moov` box would break the absolute sample offsets that point into `mdat`
+//!
+//! Everything else, including the image and video data itself, is passed through untouched.
+
+use bytes::Bytes;
+use entity::sea_orm_active_enums::DublinMetadataFormat;
+use futures::{Stream, StreamExt};
+use std::fmt::Display;
+
+/// Largest MP4 metadata box that will be buffered for scrubbing. The `moov` box holds
+/// the sample tables so grows with video length, but stays far below this in practice.
+const MAX_BUFFERED_BOX_SIZE: u64 = 64 * 1024 * 1024;
+
+/// PNG chunks that carry metadata rather than image data.
+const PNG_METADATA_CHUNKS: [&[u8; 4]; 5] = [b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+/// MP4 boxes that may contain other boxes, and so need searching for metadata.
+const MP4_CONTAINER_BOXES: [&[u8; 4]; 5] = [b"moov", b"trak", b"mdia", b"minf", b"edts"];
+
+/// MP4 boxes holding user metadata such as `©xyz` location tags.
+const MP4_METADATA_BOXES: [&[u8; 4]; 2] = [b"udta", b"meta"];
+
+/// What to do with the next record of a file.
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    /// Pass this many bytes through
+    Copy(u64),
+    /// Drop this many bytes
+    Skip(u64),
+    /// Buffer this many bytes and blank out any metadata inside
+    Blank(u64),
+    /// Pass everything that's left through
+    CopyRest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Jpeg,
+    Png,
+    Mp4,
+}
+
+enum State {
+    /// Waiting for enough bytes to parse the next record header
+    Header,
+    Copy(u64),
+    Skip(u64),
+    Blank(u64),
+    CopyRest,
+}
+
+/// Incrementally scrubs a file that arrives in chunks.
+pub struct MetadataScrubber {
+    format: Format,
+    state: State,
+    buffer: Vec<u8>,
+    /// Whether the file signature has been checked yet
+    started: bool,
+}
+
+impl MetadataScrubber {
+    /// Creates a scrubber for files of the given format, or `None` when the format has
+    /// no metadata that can be scrubbed.
+    pub fn for_format(metadata_format: &DublinMetadataFormat) -> Option<Self> {
+        let format = match metadata_format {
+            DublinMetadataFormat::Jpeg => Format::Jpeg,
+            DublinMetadataFormat::Png => Format::Png,
+            DublinMetadataFormat::Mp4 => Format::Mp4,
+            // Rewriting a web archive would break its integrity, so WACZ and WARC files are
+            // left alone
+            DublinMetadataFormat::Wacz | DublinMetadataFormat::Warc => return None,
+        };
+        Some(Self {
+            format,
+            state: State::Header,
+            buffer: Vec::new(),
+            started: false,
+        })
+    }
+
+    /// Scrubs the next chunk of the file, returning the bytes to keep.
+    ///
+    /// # Errors
+    /// Returns Error if the file is malformed
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Bytes, String> {
+        // A record header split across chunks is carried over in the buffer
+        let carried;
+        let mut input = chunk;
+        if matches!(self.state, State::Header) && !self.buffer.is_empty() {
+            let mut buffer = std::mem::take(&mut self.buffer);
+            buffer.extend_from_slice(chunk);
+            carried = buffer;
+            input = &carried;
+        }
+        let mut output = Vec::with_capacity(input.len());
+        while !input.is_empty() {
+            match self.state {
+                State::CopyRest => {
+                    output.extend_from_slice(input);
+                    input = &[];
+                }
+                State::Copy(remaining) => {
+                    let taken = take(&mut input, remaining);
+                    output.extend_from_slice(taken);
+                    self.state = next_state(State::Copy, remaining, taken.len());
+                }
+                State::Skip(remaining) => {
+                    let taken = take(&mut input, remaining);
+                    self.state = next_state(State::Skip, remaining, taken.len());
+                }
+                State::Blank(length) => {
+                    let taken = take(&mut input, length - self.buffer.len() as u64);
+                    self.buffer.extend_from_slice(taken);
+                    if self.buffer.len() as u64 == length {
+                        blank_mp4_metadata(&mut self.buffer);
+                        output.append(&mut self.buffer);
+                        self.state = State::Header;
+                    }
+                }
+                State::Header => match self.next_action(input)? {
+                    None => {
+                        self.buffer.extend_from_slice(input);
+                        input = &[];
+                    }
+                    Some(action) => {
+                        self.state = match action {
+                            Action::Copy(length) => State::Copy(length),
+                            Action::Skip(length) => State::Skip(length),
+                            Action::Blank(length) => State::Blank(length),
+                            Action::CopyRest => State::CopyRest,
+                        };
+                    }
+                },
+            }
+        }
+        Ok(Bytes::from(output))
+    }
+
+    /// Checks the whole file was consumed.
+    ///
+    /// # Errors
+    /// Returns Error if the file ended part way through a record
+    pub fn finish(self) -> Result<(), String> {
+        match self.state {
+            State::Header if self.buffer.is_empty() => Ok(()),
+            State::CopyRest => Ok(()),
+            _ => Err("File ended unexpectedly".to_string()),
+        }
+    }
+
+    /// Works out what to do with the record at the start of `header`, or `None` if more
+    /// bytes are needed to tell.
+    fn next_action(&mut self, header: &[u8]) -> Result<Option<Action>, String> {
+        if !self.started {
+            let signature: &[u8] = match self.format {
+                Format::Jpeg => b"\xff\xd8",
+                Format::Png => b"\x89PNG\r\n\x1a\n",
+                Format::Mp4 => b"",
+            };
+            if header.len() < signature.len() {
+                return Ok(None);
+            }
+            if !header.starts_with(signature) {
+                return Err("File does not start with the expected signature".to_string());
+            }
+            self.started = true;
+            if !signature.is_empty() {
+                return Ok(Some(Action::Copy(signature.len() as u64)));
+            }
+        }
+        match self.format {
+            Format::Jpeg => jpeg_action(header),
+            Format::Png => png_action(header),
+            Format::Mp4 => mp4_action(header),
+        }
+    }
+}
+
+/// Takes up to `limit` bytes off the front of `input`.
+fn take<'a>(input: &mut &'a [u8], limit: u64) -> &'a [u8] {
+    let length = usize::try_from(limit)
+        .unwrap_or(usize::MAX)
+        .min(input.len());
+    let (taken, rest) = input.split_at(length);
+    *input = rest;
+    taken
+}
+
+fn next_state(state: fn(u64) -> State, remaining: u64, taken: usize) -> State {
+    let remaining = remaining - taken as u64;
+    if remaining == 0 {
+        State::Header
+    } else {
+        state(remaining)
+    }
+}
+
+fn jpeg_action(header: &[u8]) -> Result<Option<Action>, String> {
+    let Some(&[first, marker]) = header.get(..2) else {
+        return Ok(None);
+    };
+    if first != 0xff {
+        return Err("Malformed JPEG segment".to_string());
+    }
+    match marker {
+        // Fill byte before a marker
+        0xff => Ok(Some(Action::Copy(1))),
+        // Markers without a length: TEM, RSTn, SOI and EOI
+        0x01 | 0xd0..=0xd9 => Ok(Some(Action::Copy(2))),
+        // Start of scan; compressed image data follows, with no metadata after it
+        0xda => Ok(Some(Action::CopyRest)),
+        _ => {
+            let Some(&[high, low]) = header.get(2..4) else {
+                return Ok(None);
+            };
+            let length = 2 + u64::from(u16::from_be_bytes([high, low]));
+            match marker {
+                // APP1 holds EXIF and XMP, APP13 Photoshop and IPTC, 0xfe comments
+                0xe1 | 0xed | 0xfe => Ok(Some(Action::Skip(length))),
+                _ => Ok(Some(Action::Copy(length))),
+            }
+        }
+    }
+}
+
+fn png_action(header: &[u8]) -> Result<Option<Action>, String> {
+    let Some(header) = header.get(..8) else {
+        return Ok(None);
+    };
+    let data_length = u64::from(u32::from_be_bytes([
+        header[0], header[1], header[2], header[3],
+    ]));
+    // Length, type and CRC fields come on top of the data
+    let length = 12 + data_length;
+    if PNG_METADATA_CHUNKS
+        .iter()
+        .any(|chunk| **chunk == header[4..8])
+    {
+        Ok(Some(Action::Skip(length)))
+    } else {
+        Ok(Some(Action::Copy(length)))
+    }
+}
+
+fn mp4_action(header: &[u8]) -> Result<Option<Action>, String> {
+    let Some((length, box_type)) = read_mp4_box_header(header)? else {
+        return Ok(None);
+    };
+    let Some(length) = length else {
+        // A zero size box runs to the end of the file, which only makes sense for `mdat`
+        return Ok(Some(Action::CopyRest));
+    };
+    if box_type == *b"moov" || MP4_METADATA_BOXES.contains(&&box_type) {
+        if length > MAX_BUFFERED_BOX_SIZE {
+            return Err("MP4 metadata box is too large to scrub".to_string());
+        }
+        Ok(Some(Action::Blank(length)))
+    } else {
+        Ok(Some(Action::Copy(length)))
+    }
+}
+
+/// Reads an MP4 box header, returning its total length (`None` if it runs to the end of
+/// the file) and type, or `None` if more bytes are needed.
+#[allow(clippy::type_complexity)]
+fn read_mp4_box_header(header: &[u8]) -> Result<Option<(Option<u64>, [u8; 4])>, String> {
+    let Some(header_start) = header.get(..8) else {
+        return Ok(None);
+    };
+    let size = u32::from_be_bytes([
+        header_start[0],
+        header_start[1],
+        header_start[2],
+        header_start[3],
+    ]);
+    let box_type = [
+        header_start[4],
+        header_start[5],
+        header_start[6],
+        header_start[7],
+    ];
+    let length = match size {
+        0 => None,
+        // The real size follows as a 64 bit number
+        1 => {
+            let Some(large_size) = header.get(8..16) else {
+                return Ok(None);
+            };
+            let large_size = u64::from_be_bytes(large_size.try_into().expect("slice is 8 bytes"));
+            if large_size < 16 {
+                return Err("Malformed MP4 box size".to_string());
+            }
+            Some(large_size)
+        }
+        2..=7 => return Err("Malformed MP4 box size".to_string()),
+        size => Some(u64::from(size)),
+    };
+    Ok(Some((length, box_type)))
+}
+
+/// Turns metadata boxes inside an MP4 box into `free` boxes full of zeros, keeping every
+/// box the same size.
+fn blank_mp4_metadata(mp4_box: &mut [u8]) {
+    let Ok(Some((_, box_type))) = read_mp4_box_header(mp4_box) else {
+        return;
+    };
+    let header_length = if mp4_box.get(..4) == Some(&1u32.to_be_bytes()) {
+        16
+    } else {
+        8
+    };
+    if MP4_METADATA_BOXES.contains(&&box_type) {
+        mp4_box[4..8].copy_from_slice(b"free");
+        mp4_box[header_length..].fill(0);
+        return;
+    }
+    if !MP4_CONTAINER_BOXES.contains(&&box_type) {
+        return;
+    }
+    let mut offset = header_length;
+    while offset < mp4_box.len() {
+        let child_length = match read_mp4_box_header(&mp4_box[offset..]) {
+            Ok(Some((Some(length), _))) => length,
+            Ok(Some((None, _))) => (mp4_box.len() - offset) as u64,
+            // Leave malformed boxes alone rather than guess where they end
+            _ => return,
+        };
+        let Some(end) = usize::try_from(child_length)
+            .ok()
+            .and_then(|length| offset.checked_add(length))
+            .filter(|end| *end <= mp4_box.len())
+        else {
+            return;
+        };
+        blank_mp4_metadata(&mut mp4_box[offset..end]);
+        offset = end;
+    }
+}
+
+/// Why a scrubbed stream failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScrubError {
+    /// The underlying stream failed
+    Read(String),
+    /// The file could not be parsed, so its metadata could not be found
+    Malformed(String),
+}
+
+impl Display for ScrubError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScrubError::Read(err) => write!(f, "{err}"),
+            ScrubError::Malformed(err) => write!(f, "Could not scrub metadata: {err}"),
+        }
+    }
+}
+
+/// Scrubs a stream of file chunks, failing the stream if the file can't be parsed.
+pub fn scrub_stream<S, E>(
+    stream: S,
+    scrubber: MetadataScrubber,
+) -> impl Stream<Item = Result<Bytes, ScrubError>>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: Display,
+{
+    futures::stream::unfold(
+        (stream, Some(scrubber)),
+        |(mut stream, scrubber)| async move {
+            let mut scrubber = scrubber?;
+            let scrubbed = match stream.next().await {
+                Some(Ok(chunk)) => scrubber.push(&chunk).map_err(ScrubError::Malformed),
+                Some(Err(err)) => Err(ScrubError::Read(err.to_string())),
+                None => {
+                    let finished = scrubber.finish().map_err(ScrubError::Malformed);
+                    return Some((finished.map(|()| Bytes::new()), (stream, None)));
+                }
+            };
+            let scrubber = scrubbed.is_ok().then_some(scrubber);
+            Some((scrubbed, (stream, scrubber)))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Scrubs a file fed in chunks of the given size.
+    fn scrub(format: DublinMetadataFormat, file: &[u8], chunk_size: usize) -> Vec<u8> {
+        let mut scrubber = MetadataScrubber::for_format(&format).unwrap();
+        let mut output = Vec::new();
+        for chunk in file.chunks(chunk_size) {
+            output.extend_from_slice(&scrubber.push(chunk).unwrap());
+        }
+        scrubber.finish().unwrap();
+        output
+    }
+
+    fn jpeg_segment(marker: u8, data: &[u8]) -> Vec<u8> {
+        let mut segment = vec![0xff, marker];
+        segment.extend_from_slice(&((data.len() + 2) as u16).to_be_bytes());
+        segment.extend_from_slice(data);
+        segment
+    }
+
+    fn png_chunk(chunk_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(chunk_type);
+        chunk.extend_from_slice(data);
+        chunk.extend_from_slice(&[0, 0, 0, 0]);
+        chunk
+    }
+
+    fn mp4_box(box_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut mp4_box = ((data.len() + 8) as u32).to_be_bytes().to_vec();
+        mp4_box.extend_from_slice(box_type);
+        mp4_box.extend_from_slice(data);
+        mp4_box
+    }
+
+    #[test]
+    fn strips_jpeg_exif() {
+        let jfif = jpeg_segment(0xe0, b"JFIF\0");
+        let exif = jpeg_segment(0xe1, b"Exif\0\0GPS 15.5007 32.5599");
+        let scan = [
+            &jpeg_segment(0xda, b"scan")[..],
+            b"\x12\xff\x00\x34\xff\xd9",
+        ]
+        .concat();
+        let file = [&b"\xff\xd8"[..], &jfif, &exif, &scan].concat();
+        let expected = [&b"\xff\xd8"[..], &jfif, &scan].concat();
+        for chunk_size in [1, 3, file.len()] {
+            assert_eq!(
+                scrub(DublinMetadataFormat::Jpeg, &file, chunk_size),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn strips_png_text_chunks() {
+        let signature = b"\x89PNG\r\n\x1a\n";
+        let ihdr = png_chunk(b"IHDR", &[0; 13]);
+        let exif = png_chunk(b"eXIf", b"GPS 15.5007 32.5599");
+        let text = png_chunk(b"tEXt", b"Author\0someone");
+        let idat = png_chunk(b"IDAT", &[7; 20]);
+        let iend = png_chunk(b"IEND", b"");
+        let file = [&signature[..], &ihdr, &exif, &idat, &text, &iend].concat();
+        let expected = [&signature[..], &ihdr, &idat, &iend].concat();
+        for chunk_size in [1, 5, file.len()] {
+            assert_eq!(
+                scrub(DublinMetadataFormat::Png, &file, chunk_size),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn blanks_mp4_user_data() {
+        let ftyp = mp4_box(b"ftyp", b"isom\0\0\0\0");
+        let mvhd = mp4_box(b"mvhd", &[3; 12]);
+        let udta = mp4_box(b"udta", &mp4_box(b"\xa9xyz", b"+15.5007+032.5599/"));
+        let trak = mp4_box(b"trak", &mp4_box(b"tkhd", &[4; 8]));
+        let moov = mp4_box(
+            b"moov",
+            &[mvhd.clone(), trak.clone(), udta.clone()].concat(),
+        );
+        let mdat = mp4_box(b"mdat", &[9; 32]);
+        let file = [ftyp.clone(), moov, mdat.clone()].concat();
+
+        let free = mp4_box(b"free", &vec![0; udta.len() - 8]);
+        let expected_moov = mp4_box(b"moov", &[mvhd, trak, free].concat());
+        let expected = [ftyp, expected_moov, mdat].concat();
+        for chunk_size in [1, 7, file.len()] {
+            assert_eq!(
+                scrub(DublinMetadataFormat::Mp4, &file, chunk_size),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn leaves_wacz_alone() {
+        assert!(MetadataScrubber::for_format(&DublinMetadataFormat::Wacz).is_none());
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let mut scrubber = MetadataScrubber::for_format(&DublinMetadataFormat::Png).unwrap();
+        scrubber
+            .push(
+                &[
+                    &b"\x89PNG\r\n\x1a\n"[..],
+                    &png_chunk(b"IHDR", &[0; 13])[..10],
+                ]
+                .concat(),
+            )
+            .unwrap();
+        assert!(scrubber.finish().is_err());
+    }
+
+    #[tokio::test]
+    async fn scrubs_streams() {
+        let file = [
+            &b"\xff\xd8"[..],
+            &jpeg_segment(0xe1, b"Exif\0\0"),
+            b"\xff\xd9",
+        ]
+        .concat();
+        let chunks = file
+            .chunks(3)
+            .map(|chunk| Ok::<_, String>(Bytes::copy_from_slice(chunk)))
+            .collect::<Vec<_>>();
+        let scrubber = MetadataScrubber::for_format(&DublinMetadataFormat::Jpeg).unwrap();
+        let scrubbed = scrub_stream(futures::stream::iter(chunks), scrubber)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+            .concat();
+        assert_eq!(scrubbed, b"\xff\xd8\xff\xd9");
+    }
+
+    #[tokio::test]
+    async fn fails_streams_of_malformed_files() {
+        let scrubber = MetadataScrubber::for_format(&DublinMetadataFormat::Png).unwrap();
+        let chunks = vec![Ok::<_, String>(Bytes::from_static(
+            b"GIF89a\x01\x00\x01\x00",
+        ))];
+        let results = scrub_stream(futures::stream::iter(chunks), scrubber)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], Err(ScrubError::Malformed(_))));
+    }
+
+    #[test]
+    fn rejects_wrong_signature() {
+        let mut scrubber = MetadataScrubber::for_format(&DublinMetadataFormat::Jpeg).unwrap();
+        assert!(scrubber.push(b"PK\x03\x04").is_err());
+    }
+}
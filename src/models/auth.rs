@@ -1,26 +1,45 @@
 use crate::app_factory::AppState;
 use crate::auth::JWT_KEYS;
+use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+use crate::request_log::RequestUser;
 use ::entity::sea_orm_active_enums::Role;
 use axum::response::{IntoResponse, Response};
 use axum::{
-    extract::FromRequestParts, http::request::Parts, http::StatusCode, Json, RequestPartsExt,
+    extract::{FromRequestParts, MatchedPath},
+    http::request::Parts,
+    http::StatusCode,
+    Json, RequestPartsExt,
 };
 use axum_extra::extract::CookieJar;
 use jsonwebtoken::errors::ErrorKind::ExpiredSignature;
-use jsonwebtoken::{decode, Validation};
+use jsonwebtoken::Validation;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::fmt;
+use tracing::error;
 #[derive(Debug)]
 pub enum AuthError {
+    /// The request has neither an API key nor a session cookie
+    MissingCredentials,
     InvalidToken,
     TokenExpired,
+    /// An impersonated request couldn't be written to the audit log, so it isn't let through
+    AuditFailed,
+    /// The session belongs to a user who has since been deactivated
+    Deactivated,
+    /// Whether the user is still active couldn't be checked
+    LookupFailed,
 }
 impl IntoResponse for AuthError {
     fn into_response(self) -> Response {
         let (status, error_message) = match self {
+            AuthError::MissingCredentials => (StatusCode::UNAUTHORIZED, "Missing credentials"),
             AuthError::InvalidToken => (StatusCode::BAD_REQUEST, "Invalid token"),
             AuthError::TokenExpired => (StatusCode::UNAUTHORIZED, "Token expired"),
+            AuthError::Deactivated => (StatusCode::UNAUTHORIZED, "Account deactivated"),
+            AuthError::AuditFailed | AuthError::LookupFailed => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+            }
         };
         let body = Json(json!({
             "error": error_message,
@@ -33,30 +52,98 @@ pub struct JWTClaims {
     pub sub: String,
     pub exp: usize,
     pub role: Role,
+    /// Email of the admin signed in as `sub` in support mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impersonator: Option<String>,
+    /// Organization `sub` belongs to, sessions from before organizations are in the default one
+    #[serde(default = "default_organization_id")]
+    pub organization_id: i32,
+}
+
+fn default_organization_id() -> i32 {
+    DEFAULT_ORGANIZATION_ID
 }
+
+/// Sessions with less than this many seconds left aren't checked against the user's
+/// account, which saves a lookup per request for short ones such as support sessions.
+/// Longer ones are, so deactivating a user signs them out rather than leaving them in
+/// until their session expires.
+const ACTIVE_CHECK_MIN_TTL_SECONDS: i64 = 15 * 60;
+
 impl fmt::Display for JWTClaims {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Sub: {}\nExp: {}\nRole: {:?}",
-            self.sub, self.exp, self.role
+            "Sub: {}\nExp: {}\nRole: {:?}\nImpersonator: {:?}\nOrganization: {}",
+            self.sub, self.exp, self.role, self.impersonator, self.organization_id
         )
     }
 }
 
+/// How a request was authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMethod {
+    /// An `X-Api-Key` header, see [`crate::repos::auth_repo::AuthRepo::verify_api_key`]
+    ApiKey,
+    /// A `jwt` session cookie
+    Cookie,
+}
+
+impl AuthMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthMethod::ApiKey => "api_key",
+            AuthMethod::Cookie => "cookie",
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AuthenticatedUser {
     pub user_id: String,
     pub expiry: Option<usize>,
     pub role: Role,
+    /// Email of the admin acting as this user in support mode, see
+    /// [`crate::services::auth_service::AuthService::impersonate`]
+    pub impersonator: Option<String>,
+    /// The organization the user belongs to, which scopes what they can see and change
+    pub organization_id: i32,
+    /// How the request was authenticated, for the audit and request logs
+    pub auth_method: AuthMethod,
+}
+
+impl AuthenticatedUser {
+    /// Admins of the archive's own organization, who manage organizations and run jobs
+    /// that span the whole archive.
+    pub fn is_platform_admin(&self) -> bool {
+        self.role == Role::Admin && self.organization_id == DEFAULT_ORGANIZATION_ID
+    }
+
+    /// Whether the user can see and work on private records of an organization, which is
+    /// only their own unless they are a platform admin.
+    pub fn can_access_organization(&self, organization_id: i32) -> bool {
+        self.organization_id == organization_id || self.is_platform_admin()
+    }
+
+    /// The organization the user's listings are limited to, `None` for platform admins who
+    /// can see every organization's.
+    pub fn organization_scope(&self) -> Option<i32> {
+        (!self.is_platform_admin()).then_some(self.organization_id)
+    }
 }
 
 impl fmt::Display for AuthenticatedUser {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "UserId: {}\nExpiry: {:?}\nRole: {:?}",
-            self.user_id, self.expiry, self.role
+            "UserId: {}\nExpiry: {:?}\nRole: {:?}\nImpersonator: {:?}\nOrganization: {}\nAuth method: {}",
+            self.user_id,
+            self.expiry,
+            self.role,
+            self.impersonator,
+            self.organization_id,
+            self.auth_method.as_str()
         )
     }
 }
@@ -67,51 +154,96 @@ impl FromRequestParts<AppState> for AuthenticatedUser {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        if let Some(auth_header) = parts.headers.get("X-Api-Key") {
-            if let Ok(api_key) = auth_header.to_str() {
-                let verify_result = state.auth_service.verify_api_key(api_key.to_string()).await;
-
-                match verify_result {
-                    Ok(Some(user_info)) => {
-                        return Ok(AuthenticatedUser {
-                            user_id: user_info.email,
-                            expiry: None,
-                            role: user_info.role,
-                        });
-                    }
-                    _ => {
-                        return Err(AuthError::InvalidToken);
-                    }
-                }
-            }
+        // already authenticated, audited and logged by the permission check
+        if let Some(user) = parts.extensions.get::<AuthenticatedUser>() {
+            return Ok(user.clone());
+        }
+        let user = authenticate(parts, state).await?;
+        // lets the request log say who made the request
+        if let Some(request_user) = parts.extensions.get::<RequestUser>() {
+            request_user.set(&user);
         }
+        if user.impersonator.is_some() && !parts.method.is_safe() {
+            let route = parts
+                .extensions
+                .get::<MatchedPath>()
+                .map(|path| path.as_str().to_string());
+            state
+                .auth_service
+                .audit_impersonated_request(&user, parts.method.as_str(), parts.uri.path(), route)
+                .await
+                .map_err(|err| {
+                    error!(%err, "Error occurred auditing impersonated request");
+                    AuthError::AuditFailed
+                })?;
+        }
+        Ok(user)
+    }
+}
+
+/// Authenticates a request by its `X-Api-Key` header if it has one, otherwise by its
+/// `jwt` cookie.
+///
+/// A request with an API key is only ever authenticated by it, so a bad key fails the
+/// request even if a valid session cookie came along with it.
+async fn authenticate(parts: &mut Parts, state: &AppState) -> Result<AuthenticatedUser, AuthError> {
+    if let Some(auth_header) = parts.headers.get("X-Api-Key") {
+        let api_key = auth_header.to_str().map_err(|_| AuthError::InvalidToken)?;
+        let verify_result = state.auth_service.verify_api_key(api_key.to_string()).await;
+        return match verify_result {
+            Ok(Some(user_info)) => Ok(AuthenticatedUser {
+                user_id: user_info.email,
+                expiry: None,
+                role: user_info.role,
+                impersonator: None,
+                organization_id: user_info.organization_id,
+                auth_method: AuthMethod::ApiKey,
+            }),
+            _ => Err(AuthError::InvalidToken),
+        };
+    }
+
+    let cookie_jar = parts
+        .extract::<CookieJar>()
+        .await
+        .map_err(|_| AuthError::InvalidToken)?;
+
+    let token = cookie_jar
+        .get("jwt")
+        .map(|cookie| cookie.value().to_string())
+        .ok_or(AuthError::MissingCredentials)?;
 
-        let cookie_jar = parts
-            .extract::<CookieJar>()
+    let mut validation = Validation::default();
+    validation.validate_exp = true;
+
+    let token_data = JWT_KEYS
+        .decode::<JWTClaims>(&token, &validation)
+        .map_err(|e| match e.kind() {
+            ExpiredSignature => AuthError::TokenExpired,
+            _ => AuthError::InvalidToken,
+        })?;
+
+    let claims = token_data.claims;
+    let ttl = claims.exp as i64 - state.clock.now().timestamp();
+    if ttl > ACTIVE_CHECK_MIN_TTL_SECONDS {
+        let is_active = state
+            .auth_service
+            .is_user_active(claims.sub.clone())
             .await
-            .map_err(|_| AuthError::InvalidToken)?;
-
-        let token = cookie_jar
-            .get("jwt")
-            .map(|cookie| cookie.value().to_string())
-            .ok_or(AuthError::InvalidToken)?;
-
-        let mut validation = Validation::default();
-        validation.validate_exp = true;
-
-        let token_data =
-            decode::<JWTClaims>(&token, &JWT_KEYS.decoding, &validation).map_err(|e| {
-                match e.kind() {
-                    ExpiredSignature => AuthError::TokenExpired,
-                    _ => AuthError::InvalidToken,
-                }
+            .map_err(|err| {
+                error!(%err, "Error occurred checking user is active");
+                AuthError::LookupFailed
             })?;
-
-        let claims = token_data.claims;
-        Ok(AuthenticatedUser {
-            user_id: claims.sub,
-            expiry: Some(claims.exp),
-            role: claims.role,
-        })
+        if !is_active {
+            return Err(AuthError::Deactivated);
+        }
     }
+    Ok(AuthenticatedUser {
+        user_id: claims.sub,
+        expiry: Some(claims.exp),
+        role: claims.role,
+        impersonator: claims.impersonator,
+        organization_id: claims.organization_id,
+        auth_method: AuthMethod::Cookie,
+    })
 }
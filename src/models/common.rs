@@ -2,10 +2,11 @@
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
 use utoipa::ToSchema;
 
 /// Supported languages for metadata content.
-#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, ToSchema)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum MetadataLanguage {
     #[default]
@@ -13,6 +14,32 @@ pub enum MetadataLanguage {
     Arabic,
 }
 
+impl MetadataLanguage {
+    /// The other of the two languages.
+    pub fn other(self) -> Self {
+        match self {
+            MetadataLanguage::English => MetadataLanguage::Arabic,
+            MetadataLanguage::Arabic => MetadataLanguage::English,
+        }
+    }
+
+    /// Which way text in the language is read.
+    pub fn direction(self) -> TextDirection {
+        match self {
+            MetadataLanguage::English => TextDirection::Ltr,
+            MetadataLanguage::Arabic => TextDirection::Rtl,
+        }
+    }
+}
+
+/// Which way text is read, as HTML's `dir` attribute takes it.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TextDirection {
+    Ltr,
+    Rtl,
+}
+
 /// Supported browser profiles for hard to archive sites
 #[derive(Clone, Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
@@ -20,6 +47,162 @@ pub enum BrowserProfile {
     Facebook,
 }
 
+/// Crawlers a URL can be archived with, see [`crate::repos::crawler_repo`]
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CrawlerBackend {
+    /// The hosted Browsertrix API
+    #[default]
+    Browsertrix,
+    /// browsertrix-crawler run on the API's own host
+    Local,
+}
+
+impl FromStr for CrawlerBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "browsertrix" => Ok(CrawlerBackend::Browsertrix),
+            "local" => Ok(CrawlerBackend::Local),
+            other => Err(format!("Unknown crawler: {other}")),
+        }
+    }
+}
+
+impl fmt::Display for CrawlerBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CrawlerBackend::Browsertrix => write!(f, "browsertrix"),
+            CrawlerBackend::Local => write!(f, "local"),
+        }
+    }
+}
+
+/// How a URL is captured, see [`crate::quick_capture`] and
+/// [`crate::repos::video_capture_repo`]
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureMode {
+    /// Crawl the page in a browser
+    #[default]
+    Full,
+    /// Fetch the page directly into a WARC, falling back to a crawl if it needs a browser
+    Quick,
+    /// Download the video on the page with yt-dlp, falling back to a crawl if there is none
+    Video,
+}
+
+/// Whether to strip embedded metadata, such as EXIF GPS coordinates, from uploaded
+/// images and videos. See [`crate::metadata_scrubber`].
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MetadataScrubbing {
+    #[default]
+    Scrub,
+    /// Store the file exactly as uploaded
+    Keep,
+}
+
+/// Whether a bulk visibility change makes accessions public or private.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetVisibility {
+    /// Publish every selected accession that isn't published
+    Public,
+    /// Withdraw every selected accession that is published
+    Private,
+}
+
+/// How much of each accession a listing shows, depending on who it is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingVisibility {
+    /// Anyone, so operational fields are left out, see
+    /// [`crate::models::response::PublicAccessionsWithMetadataResponse`]
+    Public,
+    /// Signed in users, who also see internal Browsertrix identifiers
+    Authenticated,
+}
+
+/// Which end of a relation the accession being viewed is on.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RelationDirection {
+    /// The viewed accession is part of, references or supersedes the related one
+    Outgoing,
+    /// The related accession is part of, references or supersedes the viewed one
+    Incoming,
+}
+
+/// Why something is in the admin review queue.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewQueueItemKind {
+    /// An accession whose crawl was flagged as a bad capture
+    BadCapture,
+    /// A crawl that failed before it could be stored as an accession
+    FailedCrawl,
+    /// An accession submitted for a researcher to check before publishing
+    InReview,
+}
+
+/// How a subject search term has to match subjects.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SubjectMatch {
+    /// The whole subject is the term
+    Exact,
+    /// The subject starts with the term, for autocomplete
+    Prefix,
+    /// The term appears anywhere in the subject
+    #[default]
+    Contains,
+}
+
+/// What order subjects are listed in.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SubjectSort {
+    /// By subject, A to Z
+    #[default]
+    Alpha,
+    /// Subjects on the most accessions first
+    Usage,
+    /// The most recently added subjects first
+    Recent,
+}
+
+/// How wide each bucket of the accession timeline is.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TimelineInterval {
+    #[default]
+    Month,
+    /// Weeks start on Monday
+    Week,
+}
+
+impl TimelineInterval {
+    /// The Postgres `date_trunc` field that rounds dates down to the start of their bucket.
+    pub fn date_trunc_field(&self) -> &'static str {
+        match self {
+            TimelineInterval::Month => "month",
+            TimelineInterval::Week => "week",
+        }
+    }
+}
+
+impl BrowserProfile {
+    /// Returns the Browsertrix profile id to crawl with.
+    ///
+    /// Profile ids here are from Browsertrix API; to get them you need to do list profiles.
+    pub fn profile_id(&self) -> &'static str {
+        match self {
+            BrowserProfile::Facebook => "b1cd3192-a554-41e1-9509-0cbff3b3df16",
+        }
+    }
+}
+
 /// Display implementation for MetadataLanguage. Mostly exists
 /// for string interpolation, logging and debugging.
 impl fmt::Display for MetadataLanguage {
@@ -0,0 +1,95 @@
+//! Structured error bodies returned by the API.
+//!
+//! All of `/api/v2` and the stricter `/api/v1` validation paths report failures as an
+//! [`ErrorResponse`] so that clients can act on a stable `code` and per-field `details`.
+//! Messages are translated into Arabic on the way out, see [`crate::i18n`]. Plain text
+//! error messages are sent in the same shape to clients asking for JSON, see
+//! [`crate::json_messages`].
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::ValidationErrors;
+
+/// The error object nested in every error response.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ErrorDetail {
+    /// Stable, machine readable error code e.g. `not_found`
+    pub code: String,
+    /// Human readable description of what went wrong
+    pub message: String,
+    /// Optional extra information such as per-field validation errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    /// `message` in both languages, `message` itself follows `Accept-Language`.
+    /// Filled in on the way out by [`crate::i18n::localize_messages`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub messages: Option<LocalizedMessages>,
+}
+
+/// A message in English and Arabic
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct LocalizedMessages {
+    pub en: String,
+    /// Same as `en` when there is no translation yet
+    pub ar: String,
+}
+
+/// Body returned when a request fails.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: ErrorDetail,
+}
+
+/// Error type for handlers which renders as an [`ErrorResponse`].
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub detail: ErrorDetail,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            detail: ErrorDetail {
+                code: code.to_string(),
+                message: message.into(),
+                details: None,
+                messages: None,
+            },
+        }
+    }
+
+    pub fn not_found() -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", "No such record")
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "forbidden", message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", message)
+    }
+
+    pub fn validation(errors: ValidationErrors) -> Self {
+        let mut api_error = Self::new(
+            StatusCode::BAD_REQUEST,
+            "validation_error",
+            "Request failed validation",
+        );
+        // errors in nested objects, such as one language's metadata, are nested the same way
+        api_error.detail.details = serde_json::to_value(&errors).ok();
+        api_error
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorResponse { error: self.detail };
+        (self.status, Json(body)).into_response()
+    }
+}
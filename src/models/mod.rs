@@ -1,4 +1,6 @@
 pub mod auth;
 pub mod common;
+pub mod error;
 pub mod request;
 pub mod response;
+pub mod v2;
@@ -3,34 +3,250 @@
 //! This module contains all the request structures used by the API endpoints,
 //! including validation rules for incoming data.
 
-use crate::models::common::{BrowserProfile, MetadataLanguage};
-use chrono::NaiveDateTime;
-use entity::sea_orm_active_enums::DublinMetadataFormat;
-use serde::Deserialize;
+use crate::access_log_export::AccessLogFormat;
+use crate::citation_export::CitationFormat;
+use crate::comments::contains_html;
+use crate::crawl_blocklist::normalize_pattern;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::common::{
+    BrowserProfile, CaptureMode, CrawlerBackend, MetadataLanguage, MetadataScrubbing, SubjectMatch,
+    SubjectSort, TargetVisibility, TimelineInterval,
+};
+use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+use crate::repos::pagination::{DEFAULT_PER_PAGE, MAX_PAGE, MAX_PER_PAGE};
+use crate::s3_keys::slugify;
+use chrono::{Duration, NaiveDateTime, Utc};
+use entity::sea_orm_active_enums::{
+    AccessionEventKind, AccessionRelationKind, ContentWarning, DublinMetadataFormat,
+    PublicationState, Role, SocialPlatform,
+};
+use serde::{Deserialize, Deserializer};
+use std::collections::HashSet;
 use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
+
+/// How far into the future `metadata_time` may be, to allow for clock skew and time zones.
+const METADATA_TIME_FUTURE_TOLERANCE_HOURS: i64 = 24;
 
 /// Request for creating a new accession with crawl + metadata.
+///
+/// Metadata is given per language in `metadata_en` and `metadata_ar`, at least one of
+/// which is needed. The older flat shape with `metadata_language`, `metadata_title`,
+/// `metadata_description` and `metadata_subjects` is still accepted and becomes the block
+/// for that language.
+///
+/// Validation failures are reported per field, see [`crate::models::error::ApiError::validation`].
 #[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+#[serde(try_from = "CreateAccessionRequestCompat")]
+#[validate(schema(function = "validate_has_metadata"))]
 pub struct CreateAccessionRequest {
-    #[validate(url)]
+    #[validate(length(max = 2000), url, custom(function = "validate_http_scheme"))]
     pub url: String,
-    pub metadata_language: MetadataLanguage,
-    #[validate(length(min = 1, max = 200))]
-    pub metadata_title: String,
-    #[validate(length(min = 1, max = 2000))]
-    pub metadata_description: Option<String>,
+    #[validate(nested)]
+    pub metadata_en: Option<CreateMetadataRequest>,
+    #[validate(nested)]
+    pub metadata_ar: Option<CreateMetadataRequest>,
+    #[validate(custom(function = "validate_not_far_future"))]
     pub metadata_time: NaiveDateTime,
     pub browser_profile: Option<BrowserProfile>,
-    #[validate(length(min = 1, max = 200))]
-    #[schema(example = json!([1, 2, 3]))]
-    pub metadata_subjects: Vec<i32>,
+    /// Crawler to archive the URL with, the configured default when unset
+    #[serde(default)]
+    pub crawler: Option<CrawlerBackend>,
+    /// Whether a static page may be fetched directly rather than crawled
+    #[serde(default)]
+    pub capture_mode: CaptureMode,
     pub is_private: bool,
+    /// Keep the accession private until this time, after which it is made public
+    #[serde(default)]
+    #[validate(custom(function = "validate_future"))]
+    pub embargo_until: Option<NaiveDateTime>,
+    /// Show the accession behind a warning, it stays listed either way
+    #[serde(default)]
+    pub content_warning: Option<ContentWarning>,
     pub metadata_format: DublinMetadataFormat,
     pub s3_filename: Option<String>,
 }
 
+/// An accession's metadata in one language.
+///
+/// The aliases let the older flat fields of [`CreateAccessionRequest`] be read as a block.
+#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+pub struct CreateMetadataRequest {
+    #[serde(alias = "metadata_title")]
+    #[validate(length(min = 1, max = 200), custom(function = "validate_not_blank"))]
+    pub title: String,
+    #[serde(default, alias = "metadata_description")]
+    #[validate(length(min = 1, max = 2000))]
+    pub description: Option<String>,
+    #[serde(alias = "metadata_subjects")]
+    #[validate(
+        length(min = 1, max = 50),
+        custom(function = "validate_unique_subjects")
+    )]
+    #[schema(example = json!([1, 2, 3]))]
+    pub subjects: Vec<i32>,
+}
+
+/// What [`CreateAccessionRequest`] is read from, so clients still sending the flat
+/// metadata fields keep working.
+#[derive(Deserialize)]
+struct CreateAccessionRequestCompat {
+    url: String,
+    metadata_en: Option<CreateMetadataRequest>,
+    metadata_ar: Option<CreateMetadataRequest>,
+    metadata_language: Option<MetadataLanguage>,
+    #[serde(flatten)]
+    flat_metadata: Option<CreateMetadataRequest>,
+    metadata_time: NaiveDateTime,
+    browser_profile: Option<BrowserProfile>,
+    #[serde(default)]
+    crawler: Option<CrawlerBackend>,
+    #[serde(default)]
+    capture_mode: CaptureMode,
+    is_private: bool,
+    #[serde(default)]
+    embargo_until: Option<NaiveDateTime>,
+    #[serde(default)]
+    content_warning: Option<ContentWarning>,
+    metadata_format: DublinMetadataFormat,
+    s3_filename: Option<String>,
+}
+
+impl TryFrom<CreateAccessionRequestCompat> for CreateAccessionRequest {
+    type Error = &'static str;
+
+    fn try_from(compat: CreateAccessionRequestCompat) -> Result<Self, Self::Error> {
+        let (mut metadata_en, mut metadata_ar) = (compat.metadata_en, compat.metadata_ar);
+        if let Some(flat_metadata) = compat.flat_metadata {
+            if metadata_en.is_some() || metadata_ar.is_some() {
+                return Err("use either metadata_en and metadata_ar or the flat metadata fields");
+            }
+            match compat.metadata_language {
+                Some(MetadataLanguage::English) => metadata_en = Some(flat_metadata),
+                Some(MetadataLanguage::Arabic) => metadata_ar = Some(flat_metadata),
+                None => return Err("missing field `metadata_language`"),
+            }
+        }
+        Ok(Self {
+            url: compat.url,
+            metadata_en,
+            metadata_ar,
+            metadata_time: compat.metadata_time,
+            browser_profile: compat.browser_profile,
+            crawler: compat.crawler,
+            capture_mode: compat.capture_mode,
+            is_private: compat.is_private,
+            embargo_until: compat.embargo_until,
+            content_warning: compat.content_warning,
+            metadata_format: compat.metadata_format,
+            s3_filename: compat.s3_filename,
+        })
+    }
+}
+
+impl CreateAccessionRequest {
+    /// The language the accession is mainly described in, English if it has both.
+    pub fn primary_language(&self) -> MetadataLanguage {
+        if self.metadata_en.is_some() {
+            MetadataLanguage::English
+        } else {
+            MetadataLanguage::Arabic
+        }
+    }
+
+    /// The metadata given for `language`, if any.
+    pub fn metadata(&self, language: MetadataLanguage) -> Option<&CreateMetadataRequest> {
+        match language {
+            MetadataLanguage::English => self.metadata_en.as_ref(),
+            MetadataLanguage::Arabic => self.metadata_ar.as_ref(),
+        }
+    }
+
+    /// Trims titles and descriptions before they are stored.
+    pub fn trimmed(mut self) -> Self {
+        self.metadata_en = self.metadata_en.map(CreateMetadataRequest::trimmed);
+        self.metadata_ar = self.metadata_ar.map(CreateMetadataRequest::trimmed);
+        self
+    }
+}
+
+impl CreateMetadataRequest {
+    fn trimmed(mut self) -> Self {
+        self.title = self.title.trim().to_string();
+        self.description = self
+            .description
+            .map(|description| description.trim().to_string());
+        self
+    }
+}
+
+fn validate_has_metadata(request: &CreateAccessionRequest) -> Result<(), ValidationError> {
+    if request.metadata_en.is_none() && request.metadata_ar.is_none() {
+        Err(ValidationError::new("missing_metadata")
+            .with_message("Metadata is needed in English, Arabic or both".into()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Markdown comments are rendered as markdown, so raw HTML in them would end up as markup.
+fn validate_markdown_safe(request: &CommentRequest) -> Result<(), ValidationError> {
+    if request.markdown && contains_html(&request.body) {
+        Err(ValidationError::new("markdown_html")
+            .with_message("Markdown comments can't contain HTML".into()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Only web pages can be crawled, so reject `ftp://`, `mailto:` and friends.
+fn validate_http_scheme(url: &str) -> Result<(), ValidationError> {
+    let lowered = url.to_ascii_lowercase();
+    if lowered.starts_with("http://") || lowered.starts_with("https://") {
+        Ok(())
+    } else {
+        Err(ValidationError::new("http_scheme").with_message("URL must use http or https".into()))
+    }
+}
+
+fn validate_not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        Err(ValidationError::new("blank").with_message("Must not be blank".into()))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_not_far_future(value: &NaiveDateTime) -> Result<(), ValidationError> {
+    let latest_allowed =
+        Utc::now().naive_utc() + Duration::hours(METADATA_TIME_FUTURE_TOLERANCE_HOURS);
+    if *value > latest_allowed {
+        Err(ValidationError::new("future_date")
+            .with_message("Date must not be in the future".into()))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_future(value: &NaiveDateTime) -> Result<(), ValidationError> {
+    if *value <= Utc::now().naive_utc() {
+        Err(ValidationError::new("past_date").with_message("Date must be in the future".into()))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_unique_subjects(subjects: &[i32]) -> Result<(), ValidationError> {
+    let mut seen = HashSet::with_capacity(subjects.len());
+    if subjects.iter().all(|id| seen.insert(*id)) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("duplicate_subjects")
+            .with_message("Subjects must not contain duplicates".into()))
+    }
+}
+
 /// Request for creating a new accession from raw file + metadata.
 #[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
 pub struct CreateAccessionRequestRaw {
@@ -44,10 +260,20 @@ pub struct CreateAccessionRequestRaw {
     #[schema(example = json!([1, 2, 3]))]
     pub metadata_subjects: Vec<i32>,
     pub is_private: bool,
+    /// Keep the accession private until this time, after which it is made public
+    #[serde(default)]
+    #[validate(custom(function = "validate_future"))]
+    pub embargo_until: Option<NaiveDateTime>,
+    /// Show the accession behind a warning, it stays listed either way
+    #[serde(default)]
+    pub content_warning: Option<ContentWarning>,
     pub metadata_format: DublinMetadataFormat,
     #[validate(url)]
     pub original_url: String,
     pub s3_filename: String,
+    /// Strip embedded metadata from images and videos before storing them
+    #[serde(default)]
+    pub metadata_scrubbing: MetadataScrubbing,
 }
 
 /// Request for creating a new accession from raw file + metadata via multipart upload.
@@ -64,6 +290,30 @@ pub struct CreateAccessionRawMultipartRequest {
     pub file: Vec<u8>,
 }
 
+/// Query parameters for creating an accession from a crawl.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[serde(default)]
+pub struct CreateAccessionCrawlQuery {
+    /// Validate the request and report what would happen without launching a crawl
+    pub dry_run: bool,
+}
+
+/// Request for starting a client-driven multipart upload.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct InitiateUploadRequest {
+    pub metadata_format: DublinMetadataFormat,
+}
+
+/// Request for presigned URLs to upload a file directly to storage.
+#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+pub struct PresignUploadRequest {
+    pub metadata_format: DublinMetadataFormat,
+    /// Number of parts to upload the file in. Leave empty to upload it with a single PUT,
+    /// which S3 limits to 5GB.
+    #[validate(range(min = 1, max = 10000))]
+    pub part_count: Option<i32>,
+}
+
 /// Request for initiating a new Browsertrix crawl.
 #[derive(Debug, Validate, Deserialize, ToSchema)]
 pub struct CreateCrawlRequest {
@@ -76,9 +326,10 @@ pub struct CreateCrawlRequest {
 #[derive(Debug, Clone, Deserialize, Validate, IntoParams, ToSchema)]
 #[serde(default)]
 pub struct AccessionPagination {
-    #[schema(default = 0)]
+    #[validate(range(max = MAX_PAGE))]
+    #[schema(default = 0, maximum = 10_000)]
     pub page: u64,
-    #[validate(range(min = 1, max = 200))]
+    #[validate(range(min = 1, max = MAX_PER_PAGE))]
     #[schema(default = 20, minimum = 1, maximum = 200)]
     pub per_page: u64,
     pub lang: MetadataLanguage,
@@ -91,13 +342,25 @@ pub struct AccessionPagination {
     pub url_filter: Option<String>,
     pub date_from: Option<NaiveDateTime>,
     pub date_to: Option<NaiveDateTime>,
+    /// Only accessions with (true) or without (false) a content warning
+    pub has_content_warning: Option<bool>,
+    /// Set to false to skip counting the total pages, e.g. for infinite scroll
+    #[schema(default = true)]
+    pub count: bool,
+    /// Only accessions belonging to this organization
+    pub organization_id: Option<i32>,
+    /// Only accessions crawled from a post on this social media platform
+    pub social_platform: Option<SocialPlatform>,
+    /// Only accessions crawled from a post by this account, ignoring case and a leading `@`
+    #[validate(length(min = 1, max = 200))]
+    pub author_handle: Option<String>,
 }
 
 impl Default for AccessionPagination {
     fn default() -> Self {
         Self {
             page: 0,
-            per_page: 20,
+            per_page: DEFAULT_PER_PAGE,
             lang: MetadataLanguage::English,
             metadata_subjects: [].to_vec(),
             metadata_subjects_inclusive_filter: None,
@@ -105,17 +368,27 @@ impl Default for AccessionPagination {
             url_filter: None,
             date_from: None,
             date_to: None,
+            has_content_warning: None,
+            count: true,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         }
     }
 }
 
-/// Pagination and filtering parameters for listing accessions, including private ones.
+/// Pagination and filtering parameters for listing private accessions.
+///
+/// Which accessions the listing can return follows from who is asking, see
+/// [`PrivateAccessionPagination::into_list_params`], so there is nothing here to widen it.
+/// Parameters that used to, such as `is_private`, are ignored.
 #[derive(Debug, Clone, Deserialize, Validate, IntoParams, ToSchema)]
 #[serde(default)]
-pub struct AccessionPaginationWithPrivate {
-    #[schema(default = 0)]
+pub struct PrivateAccessionPagination {
+    #[validate(range(max = MAX_PAGE))]
+    #[schema(default = 0, maximum = 10_000)]
     pub page: u64,
-    #[validate(range(min = 1, max = 200))]
+    #[validate(range(min = 1, max = MAX_PER_PAGE))]
     #[schema(default = 20, minimum = 1, maximum = 200)]
     pub per_page: u64,
     pub lang: MetadataLanguage,
@@ -128,14 +401,114 @@ pub struct AccessionPaginationWithPrivate {
     pub url_filter: Option<String>,
     pub date_from: Option<NaiveDateTime>,
     pub date_to: Option<NaiveDateTime>,
+    /// Only accessions with (true) or without (false) a content warning
+    pub has_content_warning: Option<bool>,
+    /// Set to false to skip counting the total pages, e.g. for infinite scroll
+    #[schema(default = true)]
+    pub count: bool,
+    /// Only accessions in this publication state, e.g. those waiting for review
+    pub publication_state: Option<PublicationState>,
+    /// Internal workflow label ids; matches accessions carrying any of them
+    #[schema(example = json!([1, 2]))]
+    pub workflow_labels: Vec<i32>,
+    /// Only accessions belonging to this organization. Ignored unless you are a platform
+    /// admin, since everyone else only sees their own organization's accessions.
+    pub organization_id: Option<i32>,
+    /// Only accessions crawled from a post on this social media platform
+    pub social_platform: Option<SocialPlatform>,
+    /// Only accessions crawled from a post by this account, ignoring case and a leading `@`
+    #[validate(length(min = 1, max = 200))]
+    pub author_handle: Option<String>,
+}
+
+impl Default for PrivateAccessionPagination {
+    fn default() -> Self {
+        Self {
+            page: 0,
+            per_page: DEFAULT_PER_PAGE,
+            lang: MetadataLanguage::English,
+            metadata_subjects: [].to_vec(),
+            metadata_subjects_inclusive_filter: None,
+            query_term: None,
+            url_filter: None,
+            date_from: None,
+            date_to: None,
+            has_content_warning: None,
+            count: true,
+            publication_state: None,
+            workflow_labels: [].to_vec(),
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
+        }
+    }
+}
+
+impl PrivateAccessionPagination {
+    /// Converts the query into listing parameters for `viewer`.
+    ///
+    /// The listing only ever returns private accessions, and only those of the viewer's
+    /// own organization unless they are a platform admin.
+    pub fn into_list_params(self, viewer: &AuthenticatedUser) -> AccessionPaginationWithPrivate {
+        AccessionPaginationWithPrivate {
+            page: self.page,
+            per_page: self.per_page,
+            lang: self.lang,
+            metadata_subjects: self.metadata_subjects,
+            metadata_subjects_inclusive_filter: self.metadata_subjects_inclusive_filter,
+            query_term: self.query_term,
+            url_filter: self.url_filter,
+            date_from: self.date_from,
+            date_to: self.date_to,
+            has_content_warning: self.has_content_warning,
+            count: self.count,
+            is_private: true,
+            publication_state: self.publication_state,
+            workflow_labels: self.workflow_labels,
+            organization_id: viewer.organization_scope().or(self.organization_id),
+            social_platform: self.social_platform,
+            author_handle: self.author_handle,
+        }
+    }
+}
+
+/// Pagination and filtering parameters for listing accessions, including private ones.
+///
+/// Built by the server from a route's query, never deserialized from one, since it
+/// decides which accessions can be seen.
+#[derive(Debug, Clone)]
+pub struct AccessionPaginationWithPrivate {
+    pub page: u64,
+    pub per_page: u64,
+    pub lang: MetadataLanguage,
+    pub metadata_subjects: Vec<i32>,
+    pub metadata_subjects_inclusive_filter: Option<bool>,
+    pub query_term: Option<String>,
+    pub url_filter: Option<String>,
+    pub date_from: Option<NaiveDateTime>,
+    pub date_to: Option<NaiveDateTime>,
+    /// Only accessions with (true) or without (false) a content warning
+    pub has_content_warning: Option<bool>,
+    /// Set to false to skip counting the total pages, e.g. for infinite scroll
+    pub count: bool,
     pub is_private: bool,
+    /// Only private accessions in this publication state, e.g. those waiting for review
+    pub publication_state: Option<PublicationState>,
+    /// Internal workflow label ids; matches accessions carrying any of them
+    pub workflow_labels: Vec<i32>,
+    /// Only accessions belonging to this organization
+    pub organization_id: Option<i32>,
+    /// Only accessions crawled from a post on this social media platform
+    pub social_platform: Option<SocialPlatform>,
+    /// Only accessions crawled from a post by this account, ignoring case and a leading `@`
+    pub author_handle: Option<String>,
 }
 
 impl Default for AccessionPaginationWithPrivate {
     fn default() -> Self {
         Self {
             page: 0,
-            per_page: 20,
+            per_page: DEFAULT_PER_PAGE,
             lang: MetadataLanguage::English,
             metadata_subjects: [].to_vec(),
             metadata_subjects_inclusive_filter: None,
@@ -143,7 +516,175 @@ impl Default for AccessionPaginationWithPrivate {
             url_filter: None,
             date_from: None,
             date_to: None,
+            has_content_warning: None,
+            count: true,
+            is_private: false,
+            publication_state: None,
+            workflow_labels: [].to_vec(),
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
+        }
+    }
+}
+
+/// Lists only public accessions with the same filters.
+impl From<AccessionPagination> for AccessionPaginationWithPrivate {
+    fn from(pagination: AccessionPagination) -> Self {
+        Self {
+            page: pagination.page,
+            per_page: pagination.per_page,
+            lang: pagination.lang,
+            metadata_subjects: pagination.metadata_subjects,
+            metadata_subjects_inclusive_filter: pagination.metadata_subjects_inclusive_filter,
+            query_term: pagination.query_term,
+            url_filter: pagination.url_filter,
+            date_from: pagination.date_from,
+            date_to: pagination.date_to,
+            has_content_warning: pagination.has_content_warning,
+            count: pagination.count,
             is_private: false,
+            publication_state: None,
+            workflow_labels: [].to_vec(),
+            organization_id: pagination.organization_id,
+            social_platform: pagination.social_platform,
+            author_handle: pagination.author_handle,
+        }
+    }
+}
+
+/// Query parameters for exporting accessions as references.
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+pub struct ExportAccessionsQuery {
+    pub format: CitationFormat,
+}
+
+/// Query parameters for exporting who accessed an accession.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[serde(default)]
+pub struct AccessLogQuery {
+    /// Format of the log in the ZIP, the manifest is always JSON
+    #[param(default = "csv")]
+    pub format: AccessLogFormat,
+}
+
+/// Query parameters for the accession timeline.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[serde(default)]
+pub struct AccessionTimelineQuery {
+    /// How wide each bucket is
+    #[param(default = "month")]
+    pub interval: TimelineInterval,
+}
+
+/// Query parameters for an accession's usage stats.
+#[derive(Debug, Clone, Default, Deserialize, Validate, IntoParams)]
+#[serde(default)]
+pub struct AccessionStatsQuery {
+    /// Only count usage from the last this many days, or all time if empty
+    #[validate(range(min = 1, max = 3650))]
+    pub days: Option<i64>,
+}
+
+/// Query parameters for the most used accessions across the archive.
+#[derive(Debug, Clone, Deserialize, Validate, IntoParams)]
+#[serde(default)]
+pub struct TopAccessionsQuery {
+    /// What sort of usage to rank accessions by
+    pub kind: AccessionEventKind,
+    #[validate(range(min = 1, max = 100))]
+    #[param(default = 10, minimum = 1, maximum = 100)]
+    pub limit: u64,
+    /// Only count usage from the last this many days, or all time if empty
+    #[validate(range(min = 1, max = 3650))]
+    pub days: Option<i64>,
+}
+
+impl Default for TopAccessionsQuery {
+    fn default() -> Self {
+        Self {
+            kind: AccessionEventKind::View,
+            limit: 10,
+            days: None,
+        }
+    }
+}
+
+/// Pagination parameters for the queue of captures flagged as bad.
+#[derive(Debug, Clone, Validate, Deserialize, IntoParams)]
+#[serde(default)]
+pub struct BadCapturesPagination {
+    #[validate(range(max = MAX_PAGE))]
+    #[param(default = 0, maximum = 10_000)]
+    pub page: u64,
+    #[validate(range(min = 1, max = MAX_PER_PAGE))]
+    #[param(default = 20, minimum = 1, maximum = 200)]
+    pub per_page: u64,
+}
+
+impl Default for BadCapturesPagination {
+    fn default() -> Self {
+        Self {
+            page: 0,
+            per_page: DEFAULT_PER_PAGE,
+        }
+    }
+}
+
+/// Pagination and filtering parameters for searching the audit log.
+#[derive(Debug, Clone, Validate, Deserialize, IntoParams)]
+#[serde(default)]
+pub struct AuditLogPagination {
+    #[validate(range(max = MAX_PAGE))]
+    #[param(default = 0, maximum = 10_000)]
+    pub page: u64,
+    #[validate(range(min = 1, max = MAX_PER_PAGE))]
+    #[param(default = 20, minimum = 1, maximum = 200)]
+    pub per_page: u64,
+    /// Only entries about this kind of thing, e.g. `accession`
+    #[validate(length(min = 1, max = 100))]
+    pub entity_type: Option<String>,
+    /// Only entries by the user with this email, or by an admin impersonating someone
+    #[validate(length(min = 1, max = 320))]
+    pub actor: Option<String>,
+    /// Only entries for this event, e.g. `private_file_accessed`
+    #[validate(length(min = 1, max = 100))]
+    pub event: Option<String>,
+    pub date_from: Option<NaiveDateTime>,
+    pub date_to: Option<NaiveDateTime>,
+}
+
+impl Default for AuditLogPagination {
+    fn default() -> Self {
+        Self {
+            page: 0,
+            per_page: DEFAULT_PER_PAGE,
+            entity_type: None,
+            actor: None,
+            event: None,
+            date_from: None,
+            date_to: None,
+        }
+    }
+}
+
+/// Pagination parameters for the admin review queue.
+#[derive(Debug, Clone, Validate, Deserialize, IntoParams)]
+#[serde(default)]
+pub struct ReviewQueuePagination {
+    #[validate(range(max = MAX_PAGE))]
+    #[param(default = 0, maximum = 10_000)]
+    pub page: u64,
+    #[validate(range(min = 1, max = MAX_PER_PAGE))]
+    #[param(default = 20, minimum = 1, maximum = 200)]
+    pub per_page: u64,
+}
+
+impl Default for ReviewQueuePagination {
+    fn default() -> Self {
+        Self {
+            page: 0,
+            per_page: DEFAULT_PER_PAGE,
         }
     }
 }
@@ -160,23 +701,34 @@ pub struct CreateSubjectRequest {
 #[derive(Debug, Clone, Validate, Deserialize, IntoParams, ToSchema)]
 #[serde(default)]
 pub struct SubjectPagination {
-    #[schema(default = 0)]
+    #[validate(range(max = MAX_PAGE))]
+    #[schema(default = 0, maximum = 10_000)]
     pub page: u64,
-    #[validate(range(min = 1, max = 200))]
+    #[validate(range(min = 1, max = MAX_PER_PAGE))]
     #[schema(default = 20, minimum = 1, maximum = 200)]
     pub per_page: u64,
     pub lang: MetadataLanguage,
     #[validate(length(min = 1, max = 500))]
     pub query_term: Option<String>,
+    /// How `query_term` has to match subjects, ignoring case and accents either way
+    #[serde(rename = "match")]
+    pub match_mode: SubjectMatch,
+    pub sort: SubjectSort,
+    /// The organization whose subjects to list, the archive's own by default
+    #[schema(default = 1)]
+    pub organization_id: i32,
 }
 
 impl Default for SubjectPagination {
     fn default() -> Self {
         Self {
             page: 0,
-            per_page: 20,
+            per_page: DEFAULT_PER_PAGE,
             lang: MetadataLanguage::English,
             query_term: None,
+            match_mode: SubjectMatch::default(),
+            sort: SubjectSort::default(),
+            organization_id: DEFAULT_ORGANIZATION_ID,
         }
     }
 }
@@ -194,18 +746,303 @@ pub struct AuthorizeRequest {
     pub user_id: Uuid,
 }
 
-#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+/// Request for opting in to or out of the weekly activity digest email.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdateActivityDigestRequest {
+    pub enabled: bool,
+}
+
+/// Bounce or spam complaint webhook sent by Postmark.
+///
+/// Only the fields we act on are modelled; Postmark sends many more, and other record
+/// types such as deliveries and opens, which are ignored.
+/// See <https://postmarkapp.com/developer/webhooks/bounce-webhook>.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(rename_all = "PascalCase")]
+pub struct PostmarkWebhookRequest {
+    /// E.g. `Bounce` or `SpamComplaint`
+    pub record_type: String,
+    /// Kind of bounce, e.g. `HardBounce`, only sent for bounces and spam complaints
+    #[serde(default, rename = "Type")]
+    pub bounce_type: Option<String>,
+    /// The address the email was sent to, missing for some record types
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Postmark's ID of the email the report is about
+    #[serde(default, rename = "MessageID")]
+    pub message_id: Option<String>,
+}
+
+/// Request for updating an accession. Only the fields given are changed.
+///
+/// Metadata is edited per language through `metadata_en` and `metadata_ar`, so a
+/// bilingual record can have both translations corrected in one request, which are saved
+/// together or not at all. A language left out keeps its metadata as it is. Fields that
+/// can be cleared, such as the description, are cleared by setting them to null rather
+/// than leaving them out.
+#[derive(Debug, Clone, Default, Validate, Deserialize, ToSchema)]
 pub struct UpdateAccessionRequest {
-    pub metadata_language: MetadataLanguage,
-    #[validate(length(min = 1, max = 200))]
-    pub metadata_title: String,
+    #[validate(nested)]
+    pub metadata_en: Option<UpdateMetadataRequest>,
+    #[validate(nested)]
+    pub metadata_ar: Option<UpdateMetadataRequest>,
+    #[validate(custom(function = "validate_not_far_future"))]
+    pub metadata_time: Option<NaiveDateTime>,
+    /// Keep the accession private until this time, after which it is made public
+    #[serde(default, deserialize_with = "double_option")]
+    #[validate(custom(function = "validate_future"))]
+    #[schema(value_type = Option<NaiveDateTime>)]
+    pub embargo_until: Option<Option<NaiveDateTime>>,
+    /// Show the accession behind a warning, it stays listed either way
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<ContentWarning>)]
+    pub content_warning: Option<Option<ContentWarning>>,
+    /// The URL an accession was captured from can't be changed, so this must be left out
+    #[validate(custom(function = "validate_immutable"))]
+    pub url: Option<String>,
+    /// The accession's `revision` when it was read; if it has been edited since, the
+    /// update is refused with 409 rather than overwriting those edits. Required, so an
+    /// update can't skip the check
+    pub revision: i32,
+}
+
+/// Changes to an accession's metadata in one language.
+#[derive(Debug, Clone, Default, Validate, Deserialize, ToSchema)]
+pub struct UpdateMetadataRequest {
+    /// Needed when adding metadata in a language the accession doesn't have yet
+    #[validate(length(min = 1, max = 200), custom(function = "validate_not_blank"))]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "double_option")]
     #[validate(length(min = 1, max = 2000))]
-    pub metadata_description: Option<String>,
-    pub metadata_time: NaiveDateTime,
-    #[validate(length(min = 1, max = 200))]
+    #[schema(value_type = Option<String>)]
+    pub description: Option<Option<String>>,
+    #[validate(
+        length(min = 1, max = 50),
+        custom(function = "validate_unique_subjects")
+    )]
     #[schema(example = json!([1, 2, 3]))]
-    pub metadata_subjects: Vec<i32>,
-    pub is_private: bool,
+    pub subjects: Option<Vec<i32>>,
+    /// Only set when drafting a translation, so editing metadata marks it as reviewed
+    #[serde(skip)]
+    pub machine_translated: bool,
+}
+
+impl UpdateAccessionRequest {
+    /// The metadata changes given for `language`, if any.
+    pub fn metadata(&self, language: MetadataLanguage) -> Option<&UpdateMetadataRequest> {
+        match language {
+            MetadataLanguage::English => self.metadata_en.as_ref(),
+            MetadataLanguage::Arabic => self.metadata_ar.as_ref(),
+        }
+    }
+
+    /// Trims titles and descriptions the same way creating an accession does.
+    pub fn trimmed(mut self) -> Self {
+        self.metadata_en = self.metadata_en.map(UpdateMetadataRequest::trimmed);
+        self.metadata_ar = self.metadata_ar.map(UpdateMetadataRequest::trimmed);
+        self
+    }
+}
+
+impl UpdateMetadataRequest {
+    fn trimmed(mut self) -> Self {
+        self.title = self.title.map(|title| title.trim().to_string());
+        self.description = self
+            .description
+            .map(|description| description.map(|description| description.trim().to_string()));
+        self
+    }
+}
+
+/// New metadata is written the same way as an update to metadata that doesn't exist yet.
+impl From<CreateMetadataRequest> for UpdateMetadataRequest {
+    fn from(metadata: CreateMetadataRequest) -> Self {
+        Self {
+            title: Some(metadata.title),
+            description: Some(metadata.description),
+            subjects: Some(metadata.subjects),
+            machine_translated: false,
+        }
+    }
+}
+
+/// Tells a field that was left out (`None`) apart from one set to null (`Some(None)`).
+fn double_option<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+fn validate_immutable(_value: &str) -> Result<(), ValidationError> {
+    Err(ValidationError::new("immutable").with_message("Cannot be changed".into()))
+}
+
+/// Request for moving an accession to another publication state.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdatePublicationStateRequest {
+    pub publication_state: PublicationState,
+}
+
+/// Request for making many accessions public or private at once.
+///
+/// Accessions are picked either by `ids` or by `url_filter`, not both.
+#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+pub struct BulkVisibilityRequest {
+    #[validate(length(min = 1, max = 1000))]
+    #[schema(example = json!([1, 2, 3]))]
+    pub ids: Option<Vec<i32>>,
+    /// Every accession whose seed URL starts with this, e.g. all captures of one source
+    #[validate(length(min = 1, max = 2000))]
+    pub url_filter: Option<String>,
+    pub visibility: TargetVisibility,
+    /// Why the change was made, kept in the audit log
+    #[validate(length(min = 1, max = 500))]
+    pub reason: Option<String>,
+}
+
+/// Request for creating a new internal workflow label.
+#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+pub struct CreateWorkflowLabelRequest {
+    #[validate(length(min = 1, max = 50))]
+    #[schema(example = "needs-translation")]
+    pub label: String,
+}
+
+/// Request for creating a collection.
+#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+pub struct CreateCollectionRequest {
+    #[validate(length(min = 1, max = 200), custom(function = "validate_not_blank"))]
+    #[schema(example = "El Fasher siege")]
+    pub title: String,
+    #[validate(length(min = 1, max = 2000))]
+    pub description: Option<String>,
+}
+
+/// Request for relating the accession in the path to another accession.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateAccessionRelationRequest {
+    /// How the accession in the path relates to `accession_id`, e.g. `part_of` when it is
+    /// part of it
+    pub kind: AccessionRelationKind,
+    /// The ID of the related accession
+    pub accession_id: i32,
+}
+
+/// Request for commenting on an accession or editing a comment.
+#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+#[validate(schema(function = "validate_markdown_safe"))]
+pub struct CommentRequest {
+    /// The comment, where `@` followed by a curator's email mentions them
+    #[validate(length(min = 1, max = 10000), custom(function = "validate_not_blank"))]
+    #[schema(example = "@amna@sudandigitalarchive.com can you check the date?")]
+    pub body: String,
+    /// Whether the body is markdown, which can't contain raw HTML
+    #[serde(default)]
+    pub markdown: bool,
+}
+
+/// Request for creating a new organization.
+#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+pub struct CreateOrganizationRequest {
+    /// Short, unique name for the organization
+    #[validate(length(min = 1, max = 50), custom(function = "validate_slug"))]
+    #[schema(example = "sudanese-diaspora-archive")]
+    pub slug: String,
+    #[validate(length(min = 1, max = 200), custom(function = "validate_not_blank"))]
+    #[schema(example = "Sudanese Diaspora Archive")]
+    pub name: String,
+}
+
+/// Request for renaming an organization.
+#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+pub struct RenameOrganizationRequest {
+    #[validate(length(min = 1, max = 200), custom(function = "validate_not_blank"))]
+    pub name: String,
+}
+
+/// Organization slugs end up in URLs, so keep them to what [`slugify`] would make.
+fn validate_slug(slug: &str) -> Result<(), ValidationError> {
+    if slugify(slug) == slug {
+        Ok(())
+    } else {
+        Err(ValidationError::new("slug").with_message(
+            "Must be lowercase letters and digits, with single hyphens between words".into(),
+        ))
+    }
+}
+
+/// Request for creating a feature flag.
+///
+/// Empty `environments` or `roles` mean the flag applies to every environment or role.
+#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+pub struct CreateFeatureFlagRequest {
+    #[validate(length(min = 1, max = 50), custom(function = "validate_flag_name"))]
+    #[schema(example = "wacz-full-text")]
+    pub name: String,
+    #[validate(length(max = 500))]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    #[schema(example = json!(["staging"]))]
+    pub environments: Vec<String>,
+    #[serde(default)]
+    pub roles: Vec<Role>,
+}
+
+/// Request for replacing the settings of an existing feature flag.
+#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+pub struct UpdateFeatureFlagRequest {
+    #[validate(length(max = 500))]
+    pub description: Option<String>,
+    pub enabled: bool,
+    #[serde(default)]
+    #[schema(example = json!(["staging"]))]
+    pub environments: Vec<String>,
+    #[serde(default)]
+    pub roles: Vec<Role>,
+}
+
+/// Request for adding a URL pattern to the global crawl blocklist.
+///
+/// See [`crate::crawl_blocklist`] for what patterns look like.
+#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+pub struct CreateCrawlBlocklistEntryRequest {
+    #[validate(
+        length(min = 1, max = 500),
+        custom(function = "validate_blocklist_pattern")
+    )]
+    #[schema(example = "*.example.com/private")]
+    pub pattern: String,
+    #[validate(length(min = 1, max = 500))]
+    #[schema(example = "Court order")]
+    pub reason: String,
+}
+
+fn validate_blocklist_pattern(pattern: &str) -> Result<(), ValidationError> {
+    match normalize_pattern(pattern) {
+        Some(_) => Ok(()),
+        None => Err(
+            ValidationError::new("invalid_blocklist_pattern").with_message(
+                "Must be a host, optionally starting with *. and followed by a path prefix".into(),
+            ),
+        ),
+    }
+}
+
+/// Flag names end up in URLs and code, so keep them to lowercase kebab case.
+fn validate_flag_name(name: &str) -> Result<(), ValidationError> {
+    if name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("flag_name")
+            .with_message("Must only contain lowercase letters, digits and hyphens".into()))
+    }
 }
 
 /// Request for deleting a subject category.
@@ -213,3 +1050,63 @@ pub struct UpdateAccessionRequest {
 pub struct DeleteSubjectRequest {
     pub lang: MetadataLanguage,
 }
+
+/// Request for attaching a subject to every accession matching a filter, public or not.
+///
+/// At least one of `query_term`, `date_from`, `date_to` and `domain` must be given so a
+/// subject can't be slapped on the whole archive by mistake. Only accessions with
+/// metadata in `lang` are considered.
+#[derive(Debug, Clone, Validate, Deserialize, ToSchema)]
+#[validate(schema(function = "validate_apply_subject_filter"))]
+pub struct ApplySubjectRequest {
+    pub lang: MetadataLanguage,
+    /// Full text search over titles and descriptions, as for accession search
+    #[validate(length(min = 1, max = 200), custom(function = "validate_not_blank"))]
+    pub query_term: Option<String>,
+    pub date_from: Option<NaiveDateTime>,
+    pub date_to: Option<NaiveDateTime>,
+    /// Accessions crawled from this domain or its subdomains, e.g. `bbc.co.uk`
+    #[validate(length(min = 3, max = 253), custom(function = "validate_domain"))]
+    pub domain: Option<String>,
+    /// Only count the matching accessions without tagging them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+fn validate_apply_subject_filter(request: &ApplySubjectRequest) -> Result<(), ValidationError> {
+    if request.query_term.is_none()
+        && request.date_from.is_none()
+        && request.date_to.is_none()
+        && request.domain.is_none()
+    {
+        return Err(ValidationError::new("missing_filter").with_message(
+            "At least one of query_term, date_from, date_to or domain is needed".into(),
+        ));
+    }
+    if let (Some(from), Some(to)) = (request.date_from, request.date_to) {
+        if from > to {
+            return Err(ValidationError::new("date_range")
+                .with_message("date_from must not be after date_to".into()));
+        }
+    }
+    Ok(())
+}
+
+/// A bare host name such as `www.bbc.co.uk`, without scheme, port or path.
+fn validate_domain(domain: &str) -> Result<(), ValidationError> {
+    let valid_chars = domain
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-');
+    if valid_chars
+        && domain.contains('.')
+        && !domain.starts_with(['.', '-'])
+        && !domain.ends_with(['.', '-'])
+        && !domain.contains("..")
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("domain").with_message(
+            "Must be a lowercase domain name such as example.com, without scheme or path".into(),
+        ))
+    }
+}
@@ -3,63 +3,404 @@
 //! This module contains all the response structures used by the API endpoints,
 //! including authentication, crawl operations, and accession management.
 
-use ::entity::sea_orm_active_enums::CrawlStatus;
+use crate::crawl_queue::CrawlQueueSnapshot;
+use crate::models::common::{
+    CrawlerBackend, MetadataLanguage, RelationDirection, ReviewQueueItemKind, TextDirection,
+    TimelineInterval,
+};
+use crate::models::error::LocalizedMessages;
+use crate::pipeline_metrics::{CrawlFailure, InProgressCrawl, PipelineSnapshot};
+use crate::provenance::{key_fingerprint, signed_message, HASH_ALGORITHM, SIGNATURE_ALGORITHM};
+use crate::reindex::ReindexSnapshot;
+use crate::repos::accession_events_repo::{AccessionEventTotal, EventCount};
+use crate::repos::accession_relations_repo::RelatedAccession;
+use crate::repos::accessions_repo::TimelineBucket;
+use crate::s3_backfill::{BackfillFailure, BackfillSnapshot};
+use crate::scheduler::TaskStats;
+use crate::static_export::StaticExportSnapshot;
+use crate::upload_progress::UploadProgress;
+use crate::wacz::WaczPage;
+use ::entity::sea_orm_active_enums::{
+    AccessionEventKind, AccessionRelationKind, ContentWarning, CrawlStatus, DerivativeKind,
+    EmailStatus, PublicationState, Role, ScanStatus, SocialPlatform, TimestampMethod,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use chrono::NaiveDateTime;
+use entity::accession_provenance::Model as AccessionProvenanceModel;
 use entity::accessions_with_metadata::Model as AccessionsWithMetadataModel;
+use entity::archive_user::Model as ArchiveUserModel;
+use entity::audit_log::Model as AuditLogModel;
+use entity::collection::Model as CollectionModel;
+use entity::comment::Model as CommentModel;
+use entity::crawl_blocklist::Model as CrawlBlocklistModel;
 use entity::dublin_metadata_subject_ar::Model as DublinMetadataSubjectArModel;
 use entity::dublin_metadata_subject_en::Model as DublinMetadataSubjectEnModel;
+use entity::feature_flag::Model as FeatureFlagModel;
+use entity::organization::Model as OrganizationModel;
 use entity::sea_orm_active_enums::DublinMetadataFormat;
+use entity::social_metadata::Model as SocialMetadataModel;
+use entity::workflow_label::Model as WorkflowLabelModel;
+use sea_orm::ActiveEnum;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+/// A subject an accession is tagged with.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct AccessionSubjectResponse {
+    pub id: i32,
+    pub subject: String,
+    pub lang: MetadataLanguage,
+}
+
+impl AccessionSubjectResponse {
+    /// Pairs up the subject names and IDs the view selects for each language, which it
+    /// aggregates in the same order.
+    fn from_model(model: &AccessionsWithMetadataModel) -> Vec<Self> {
+        [
+            (
+                MetadataLanguage::English,
+                &model.subjects_en_ids,
+                &model.subjects_en,
+            ),
+            (
+                MetadataLanguage::Arabic,
+                &model.subjects_ar_ids,
+                &model.subjects_ar,
+            ),
+        ]
+        .into_iter()
+        .flat_map(|(lang, ids, subjects)| {
+            ids.iter()
+                .flatten()
+                .zip(subjects.iter().flatten())
+                .map(move |(id, subject)| Self {
+                    id: *id,
+                    subject: subject.clone(),
+                    lang,
+                })
+        })
+        .collect()
+    }
+}
+
+/// An accession's title and description in the reader's preferred language, so clients
+/// don't each have to fall back to the other language themselves.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DisplayMetadata {
+    pub display_title: Option<String>,
+    pub display_description: Option<String>,
+    /// The language picked with `Accept-Language`, unless only the other language has a title
+    pub display_lang: MetadataLanguage,
+    /// Which way the display title and description are read
+    pub display_dir: TextDirection,
+}
+
+impl DisplayMetadata {
+    /// Picks the title and description to show a reader preferring `preferred`.
+    fn pick(
+        title_en: &Option<String>,
+        description_en: &Option<String>,
+        title_ar: &Option<String>,
+        description_ar: &Option<String>,
+        preferred: MetadataLanguage,
+    ) -> Self {
+        let has_title = |language| match language {
+            MetadataLanguage::English => title_en.is_some(),
+            MetadataLanguage::Arabic => title_ar.is_some(),
+        };
+        let language = if has_title(preferred) || !has_title(preferred.other()) {
+            preferred
+        } else {
+            preferred.other()
+        };
+        let (title, description) = match language {
+            MetadataLanguage::English => (title_en, description_en),
+            MetadataLanguage::Arabic => (title_ar, description_ar),
+        };
+        Self {
+            display_title: title.clone(),
+            display_description: description.clone(),
+            display_lang: language,
+            display_dir: language.direction(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub struct AccessionsWithMetadataResponse {
     pub id: i32,
     pub is_private: bool,
+    /// Where the accession is in the publishing workflow
+    pub publication_state: PublicationState,
+    /// When the accession stops being private, if it is under embargo
+    pub embargo_until: Option<NaiveDateTime>,
+    /// Set when the accession should be shown behind a warning
+    pub content_warning: Option<ContentWarning>,
     pub crawl_status: CrawlStatus,
     pub crawl_timestamp: NaiveDateTime,
     pub crawl_id: Option<Uuid>,
     pub org_id: Option<Uuid>,
     pub job_run_id: Option<String>,
     pub seed_url: String,
+    /// `seed_url` with tracking parameters removed, see [`crate::url_canonicalizer`]
+    pub canonical_url: Option<String>,
     pub dublin_metadata_date: NaiveDateTime,
     pub dublin_metadata_format: DublinMetadataFormat,
     pub title_en: Option<String>,
     pub description_en: Option<String>,
-    pub subjects_en: Option<Vec<String>>,
-    pub subjects_en_ids: Option<Vec<i32>>,
     pub title_ar: Option<String>,
     pub description_ar: Option<String>,
-    pub subjects_ar: Option<Vec<String>>,
-    pub subjects_ar_ids: Option<Vec<i32>>,
+    /// English subjects followed by Arabic ones
+    pub subjects: Vec<AccessionSubjectResponse>,
+    #[serde(flatten)]
+    pub display: DisplayMetadata,
     pub has_english_metadata: bool,
     pub has_arabic_metadata: bool,
+    /// English metadata was drafted by machine translation and awaits review
+    pub machine_translated_en: bool,
+    /// Arabic metadata was drafted by machine translation and awaits review
+    pub machine_translated_ar: bool,
+    /// Result of virus scanning the uploaded file; crawls are not scanned
+    pub scan_status: ScanStatus,
+    /// Whether embedded metadata such as GPS coordinates was stripped from the uploaded file
+    pub metadata_scrubbed: bool,
+    /// Send this back when updating the accession so edits made since aren't overwritten
+    pub revision: i32,
+    /// Minted on demand through DataCite, e.g. `10.1234/abcd-efgh`
+    pub doi: Option<String>,
+    /// Why the crawl was flagged as a bad capture, set when `crawl_status` is `BadCrawl`
+    pub crawl_quality_reason: Option<String>,
 }
 
 impl From<AccessionsWithMetadataModel> for AccessionsWithMetadataResponse {
     fn from(model: AccessionsWithMetadataModel) -> Self {
+        let subjects = AccessionSubjectResponse::from_model(&model);
+        let display = DisplayMetadata::pick(
+            &model.title_en,
+            &model.description_en,
+            &model.title_ar,
+            &model.description_ar,
+            MetadataLanguage::default(),
+        );
         Self {
             id: model.id,
             is_private: model.is_private,
+            publication_state: model.publication_state,
+            embargo_until: model.embargo_until,
+            content_warning: model.content_warning,
             crawl_status: model.crawl_status,
             crawl_timestamp: model.crawl_timestamp,
             crawl_id: model.crawl_id,
             org_id: model.org_id,
             job_run_id: model.job_run_id,
             seed_url: model.seed_url,
+            canonical_url: model.canonical_url,
+            dublin_metadata_date: model.dublin_metadata_date,
+            dublin_metadata_format: model.dublin_metadata_format,
+            title_en: model.title_en,
+            description_en: model.description_en,
+            title_ar: model.title_ar,
+            description_ar: model.description_ar,
+            subjects,
+            display,
+            has_english_metadata: model.has_english_metadata,
+            has_arabic_metadata: model.has_arabic_metadata,
+            machine_translated_en: model.machine_translated_en,
+            machine_translated_ar: model.machine_translated_ar,
+            scan_status: model.scan_status,
+            metadata_scrubbed: model.metadata_scrubbed,
+            revision: model.revision,
+            doi: model.doi,
+            crawl_quality_reason: model.crawl_quality_reason,
+        }
+    }
+}
+
+impl AccessionsWithMetadataResponse {
+    /// Fills in the display fields for a reader preferring `language`.
+    pub fn displayed_in(mut self, language: MetadataLanguage) -> Self {
+        self.display = DisplayMetadata::pick(
+            &self.title_en,
+            &self.description_en,
+            &self.title_ar,
+            &self.description_ar,
+            language,
+        );
+        self
+    }
+}
+
+/// Accession as shown to anonymous users.
+///
+/// Mirrors [`AccessionsWithMetadataResponse`] but leaves out the Browsertrix
+/// `crawl_id`, `org_id` and `job_run_id`, which are only useful to staff.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PublicAccessionsWithMetadataResponse {
+    pub id: i32,
+    pub is_private: bool,
+    /// Set when the accession should be shown behind a warning
+    pub content_warning: Option<ContentWarning>,
+    pub crawl_status: CrawlStatus,
+    pub crawl_timestamp: NaiveDateTime,
+    pub seed_url: String,
+    /// `seed_url` with tracking parameters removed, see [`crate::url_canonicalizer`]
+    pub canonical_url: Option<String>,
+    pub dublin_metadata_date: NaiveDateTime,
+    pub dublin_metadata_format: DublinMetadataFormat,
+    pub title_en: Option<String>,
+    pub description_en: Option<String>,
+    pub title_ar: Option<String>,
+    pub description_ar: Option<String>,
+    /// English subjects followed by Arabic ones
+    pub subjects: Vec<AccessionSubjectResponse>,
+    #[serde(flatten)]
+    pub display: DisplayMetadata,
+    pub has_english_metadata: bool,
+    pub has_arabic_metadata: bool,
+    /// English metadata was drafted by machine translation and awaits review
+    pub machine_translated_en: bool,
+    /// Arabic metadata was drafted by machine translation and awaits review
+    pub machine_translated_ar: bool,
+    /// Minted on demand through DataCite, e.g. `10.1234/abcd-efgh`
+    pub doi: Option<String>,
+}
+
+impl From<AccessionsWithMetadataModel> for PublicAccessionsWithMetadataResponse {
+    fn from(model: AccessionsWithMetadataModel) -> Self {
+        let subjects = AccessionSubjectResponse::from_model(&model);
+        let display = DisplayMetadata::pick(
+            &model.title_en,
+            &model.description_en,
+            &model.title_ar,
+            &model.description_ar,
+            MetadataLanguage::default(),
+        );
+        Self {
+            id: model.id,
+            is_private: model.is_private,
+            content_warning: model.content_warning,
+            crawl_status: model.crawl_status,
+            crawl_timestamp: model.crawl_timestamp,
+            seed_url: model.seed_url,
+            canonical_url: model.canonical_url,
             dublin_metadata_date: model.dublin_metadata_date,
             dublin_metadata_format: model.dublin_metadata_format,
             title_en: model.title_en,
             description_en: model.description_en,
-            subjects_en: model.subjects_en,
-            subjects_en_ids: model.subjects_en_ids,
             title_ar: model.title_ar,
             description_ar: model.description_ar,
-            subjects_ar: model.subjects_ar,
-            subjects_ar_ids: model.subjects_ar_ids,
+            subjects,
+            display,
             has_english_metadata: model.has_english_metadata,
             has_arabic_metadata: model.has_arabic_metadata,
+            machine_translated_en: model.machine_translated_en,
+            machine_translated_ar: model.machine_translated_ar,
+            doi: model.doi,
+        }
+    }
+}
+
+impl PublicAccessionsWithMetadataResponse {
+    /// Fills in the display fields for a reader preferring `language`.
+    pub fn displayed_in(mut self, language: MetadataLanguage) -> Self {
+        self.display = DisplayMetadata::pick(
+            &self.title_en,
+            &self.description_en,
+            &self.title_ar,
+            &self.description_ar,
+            language,
+        );
+        self
+    }
+}
+
+/// A DOI minted for an accession.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DoiResponse {
+    /// e.g. `10.1234/abcd-efgh`
+    pub doi: String,
+    /// Where the DOI resolves from, for linking
+    pub url: String,
+}
+
+impl DoiResponse {
+    pub fn new(doi: String) -> Self {
+        Self {
+            url: format!("https://doi.org/{doi}"),
+            doi,
+        }
+    }
+}
+
+/// An accession's file after it was replaced with a corrected one.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ReplaceAccessionFileResponse {
+    /// S3 key of the replacement
+    pub s3_filename: String,
+    /// Hex SHA-256 of the replacement
+    pub sha256: String,
+    /// Where the previous file was moved to, `None` if there wasn't one or it couldn't be
+    /// moved
+    pub quarantined_s3_filename: Option<String>,
+}
+
+/// The recorded hash of an accession's file and, when the archive signs them, its signature.
+///
+/// The signature can be checked by verifying `signed_message` against `signer_public_key`
+/// with Ed25519.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ProvenanceResponse {
+    pub accession_id: i32,
+    /// Always `sha256`
+    pub hash_algorithm: String,
+    /// Hex hash of the file as it was stored
+    pub sha256: String,
+    /// Always `ed25519`, `None` when the hash wasn't signed
+    pub signature_algorithm: Option<String>,
+    /// The exact text that was signed, `None` when the hash wasn't signed
+    pub signed_message: Option<String>,
+    /// Base64 signature of `signed_message`
+    pub signature: Option<String>,
+    /// Base64 of the raw Ed25519 public key the signature was made with
+    pub signer_public_key: Option<String>,
+    /// Hex SHA-256 of the raw public key
+    pub signer_key_fingerprint: Option<String>,
+    pub recorded_at: NaiveDateTime,
+    /// `None` until the hash has been sent to a timestamping service
+    pub timestamp_method: Option<TimestampMethod>,
+    /// Base64 of an `.ots` file for `OpenTimestamps`, or of a DER `TimeStampResp` for `Rfc3161`
+    pub timestamp_proof: Option<String>,
+    pub timestamped_at: Option<NaiveDateTime>,
+}
+
+/// Other accessions whose capture has the same content, see [`crate::content_digest`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DuplicateAccessionsResponse {
+    pub accession_id: i32,
+    /// Digest of the capture's key payloads, prefixed with its algorithm
+    pub content_digest: String,
+    /// Accessions sharing the digest, oldest first
+    pub duplicate_accession_ids: Vec<i32>,
+}
+
+impl From<AccessionProvenanceModel> for ProvenanceResponse {
+    fn from(model: AccessionProvenanceModel) -> Self {
+        let signed = model.signature.is_some();
+        Self {
+            accession_id: model.accession_id,
+            hash_algorithm: HASH_ALGORITHM.to_string(),
+            signature_algorithm: signed.then(|| SIGNATURE_ALGORITHM.to_string()),
+            signed_message: signed
+                .then(|| signed_message(model.accession_id, &model.sha256, model.recorded_at)),
+            signer_key_fingerprint: model.signer_public_key.as_deref().and_then(key_fingerprint),
+            sha256: model.sha256,
+            signature: model.signature,
+            signer_public_key: model.signer_public_key,
+            recorded_at: model.recorded_at,
+            timestamp_method: model.timestamp_method,
+            timestamp_proof: model.timestamp_proof.map(|proof| STANDARD.encode(proof)),
+            timestamped_at: model.timestamped_at,
         }
     }
 }
@@ -68,6 +409,11 @@ impl From<AccessionsWithMetadataModel> for AccessionsWithMetadataResponse {
 pub struct DublinMetadataSubjectArResponse {
     pub id: i32,
     pub subject: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    /// Email of the user who added the subject, `null` for subjects added before it was
+    /// recorded
+    pub created_by: Option<String>,
 }
 
 impl From<DublinMetadataSubjectArModel> for DublinMetadataSubjectArResponse {
@@ -75,6 +421,9 @@ impl From<DublinMetadataSubjectArModel> for DublinMetadataSubjectArResponse {
         Self {
             id: model.id,
             subject: model.subject,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+            created_by: model.created_by,
         }
     }
 }
@@ -83,6 +432,11 @@ impl From<DublinMetadataSubjectArModel> for DublinMetadataSubjectArResponse {
 pub struct DublinMetadataSubjectEnResponse {
     pub id: i32,
     pub subject: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    /// Email of the user who added the subject, `null` for subjects added before it was
+    /// recorded
+    pub created_by: Option<String>,
 }
 
 impl From<DublinMetadataSubjectEnModel> for DublinMetadataSubjectEnResponse {
@@ -90,6 +444,9 @@ impl From<DublinMetadataSubjectEnModel> for DublinMetadataSubjectEnResponse {
         Self {
             id: model.id,
             subject: model.subject,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+            created_by: model.created_by,
         }
     }
 }
@@ -132,23 +489,876 @@ pub struct WaczItem {
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
 pub struct GetOneAccessionResponse {
     pub accession: AccessionsWithMetadataResponse,
+    /// For private accessions this and the other file URLs are short-lived links to the
+    /// file proxy that only work for the user who fetched the accession
     pub wacz_url: String,
+    /// Presigned URL of a PDF rendering of the captured page, when one has been generated
+    pub pdf_url: Option<String>,
+    /// Web friendly renditions of uploaded images and videos
+    pub derivatives: Vec<DerivativeResponse>,
+    /// Accessions this one is connected to, including private ones the viewer can see
+    pub relations: Vec<AccessionRelationResponse>,
+    /// Who posted the social media post the accession was crawled from, if it was one
+    pub social_metadata: Option<SocialMetadataResponse>,
+}
+
+/// A web friendly rendition of an accession's file, such as a thumbnail.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct DerivativeResponse {
+    pub kind: DerivativeKind,
+    pub content_type: String,
+    /// URL to fetch the derivative from, presigned unless the accession is private
+    pub url: String,
+}
+
+/// An accession related to the one being viewed.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct AccessionRelationResponse {
+    /// ID of the relation, used to remove it
+    pub id: i32,
+    pub kind: AccessionRelationKind,
+    pub direction: RelationDirection,
+    /// ID of the related accession
+    pub accession_id: i32,
+    pub title_en: Option<String>,
+    pub title_ar: Option<String>,
+}
+
+impl From<RelatedAccession> for AccessionRelationResponse {
+    fn from(related: RelatedAccession) -> Self {
+        Self {
+            id: related.relation_id,
+            kind: related.kind,
+            direction: related.direction,
+            accession_id: related.accession.id,
+            title_en: related.accession.title_en,
+            title_ar: related.accession.title_ar,
+        }
+    }
+}
+
+/// Who posted a crawled social media post and when, see [`crate::social_metadata`].
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct SocialMetadataResponse {
+    pub platform: SocialPlatform,
+    /// Handle of the account that posted, without a leading `@`
+    pub author_handle: Option<String>,
+    pub author_name: Option<String>,
+    /// The platform's ID of the post
+    pub post_id: Option<String>,
+    /// When the post was published, in UTC
+    pub posted_at: Option<NaiveDateTime>,
+}
+
+impl From<SocialMetadataModel> for SocialMetadataResponse {
+    fn from(model: SocialMetadataModel) -> Self {
+        Self {
+            platform: model.platform,
+            author_handle: model.author_handle,
+            author_name: model.author_name,
+            post_id: model.post_id,
+            posted_at: model.posted_at,
+        }
+    }
+}
+
+/// Response for listing the accessions related to an accession.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct ListAccessionRelationsResponse {
+    pub items: Vec<AccessionRelationResponse>,
+}
+
+/// A curator's comment on an accession.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct CommentResponse {
+    pub id: i32,
+    pub accession_id: i32,
+    pub author_email: String,
+    pub body: String,
+    /// Whether to render the body as markdown rather than plain text, markdown bodies
+    /// never contain raw HTML
+    pub markdown: bool,
+    pub created_at: NaiveDateTime,
+    /// Later than `created_at` once the comment has been edited
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<CommentModel> for CommentResponse {
+    fn from(model: CommentModel) -> Self {
+        Self {
+            id: model.id,
+            accession_id: model.accession_id,
+            author_email: model.author_email,
+            body: model.body,
+            markdown: model.markdown,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        }
+    }
+}
+
+/// Response for listing the comments on an accession, oldest first.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct ListCommentsResponse {
+    pub items: Vec<CommentResponse>,
 }
 
 /// Response for listing accessions with pagination.
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
 pub struct ListAccessionsResponse {
     pub items: Vec<AccessionsWithMetadataResponse>,
+    /// Empty when the request opted out of counting
+    pub num_pages: Option<u64>,
+    pub page: u64,
+    pub per_page: u64,
+}
+
+/// The archive's totals for the public site's counter.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct PublicStatsResponse {
+    pub public_accessions: u64,
+    /// Subject terms of every organization, counting English and Arabic terms separately
+    pub subjects: u64,
+    /// Bytes stored across every accession, public or private, and their derivatives
+    pub storage_bytes: u64,
+}
+
+/// Response for retrieving a single public accession as an anonymous user.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct GetOnePublicAccessionResponse {
+    pub accession: PublicAccessionsWithMetadataResponse,
+    pub wacz_url: String,
+    /// Presigned URL of a PDF rendering of the captured page, when one has been generated
+    pub pdf_url: Option<String>,
+    /// Web friendly renditions of uploaded images and videos
+    pub derivatives: Vec<DerivativeResponse>,
+    /// Public accessions this one is connected to
+    pub relations: Vec<AccessionRelationResponse>,
+    /// Who posted the social media post the accession was crawled from, if it was one
+    pub social_metadata: Option<SocialMetadataResponse>,
+}
+
+/// Response for listing public accessions as an anonymous user.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct ListPublicAccessionsResponse {
+    pub items: Vec<PublicAccessionsWithMetadataResponse>,
+    /// Empty when the request opted out of counting
+    pub num_pages: Option<u64>,
+    pub page: u64,
+    pub per_page: u64,
+}
+
+/// A page captured within an accession's WACZ file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct WaczPageResponse {
+    pub url: String,
+    pub title: Option<String>,
+    /// Capture timestamp as recorded by the crawler
+    pub ts: Option<String>,
+}
+
+impl From<WaczPage> for WaczPageResponse {
+    fn from(page: WaczPage) -> Self {
+        Self {
+            url: page.url,
+            title: page.title,
+            ts: page.ts,
+        }
+    }
+}
+
+/// Response for listing the pages captured in an accession.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ListAccessionPagesResponse {
+    pub accession_id: i32,
+    pub pages: Vec<WaczPageResponse>,
+}
+
+/// Response from starting a client-driven multipart upload.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct InitiateUploadResponse {
+    pub upload_id: String,
+    /// The object key the file will be stored under once the upload completes
+    pub key: String,
+    /// Every part except the last must be at least this many bytes
+    pub min_part_size: u64,
+}
+
+/// A part uploaded as part of a multipart upload.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct UploadPartResponse {
+    pub part_number: i32,
+    pub etag: String,
+    pub size: u64,
+}
+
+/// Response listing the parts uploaded so far.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ListUploadPartsResponse {
+    pub upload_id: String,
+    pub key: String,
+    pub parts: Vec<UploadPartResponse>,
+}
+
+/// A presigned URL for uploading one part of a multipart upload.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct PresignedPartUrlResponse {
+    pub part_number: i32,
+    pub url: String,
+}
+
+/// Response with presigned URLs for uploading a file directly to storage.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct PresignUploadResponse {
+    /// The object key to pass to `POST /api/v1/accessions/from-file` once uploaded
+    pub key: String,
+    /// The multipart upload ID, only set when uploading in parts
+    pub upload_id: Option<String>,
+    /// Uploads must send this as their `Content-Type` header
+    pub content_type: String,
+    /// URL for uploading the whole file with a single PUT, only set when not uploading in parts
+    pub url: Option<String>,
+    pub part_urls: Vec<PresignedPartUrlResponse>,
+    /// Seconds until the URLs expire
+    pub expires_in: u64,
+}
+
+/// Response from completing a multipart upload.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct CompleteUploadResponse {
+    pub key: String,
+}
+
+/// Progress of a multipart upload, sent as server-sent events.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct UploadProgressResponse {
+    /// Bytes uploaded to storage so far
+    pub bytes_transferred: u64,
+    pub parts_completed: u32,
+    pub complete: bool,
+    pub failed: bool,
+}
+
+impl From<UploadProgress> for UploadProgressResponse {
+    fn from(progress: UploadProgress) -> Self {
+        Self {
+            bytes_transferred: progress.bytes_transferred,
+            parts_completed: progress.parts_completed,
+            complete: progress.complete,
+            failed: progress.failed,
+        }
+    }
+}
+
+/// Body of a plain text message sent as JSON, see [`crate::json_messages`].
+///
+/// Failures are sent as an [`crate::models::error::ErrorResponse`] instead.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct MessageResponse {
+    /// The response status in snake case e.g. `created`
+    pub code: String,
+    pub message: String,
+    /// Optional extra information about the outcome
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    /// `message` in both languages, `message` itself follows `Accept-Language`.
+    /// Filled in on the way out by [`crate::i18n::localize_messages`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub messages: Option<LocalizedMessages>,
+}
+
+/// Response describing what creating an accession would do, returned for dry runs.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct DryRunAccessionResponse {
+    /// The URL as submitted, stored as the seed URL
+    pub url: String,
+    /// The URL the crawler would crawl
+    pub canonical_url: String,
+    /// The crawler that would crawl the URL
+    pub crawler: CrawlerBackend,
+    /// The Browsertrix profile the crawl would use, if any
+    pub browser_profile_id: Option<String>,
+    /// Existing accessions captured from the same canonical URL
+    pub duplicate_accession_ids: Vec<i32>,
+}
+
+/// Response to a crawl request, sent once its accession is created and before the crawl runs.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct CreateAccessionCrawlResponse {
+    /// The accession the crawl will be stored as, pending until the crawl finishes
+    pub accession_id: i32,
+    /// Where to check on the crawl, returning an [`AccessionCrawlStatusResponse`]
+    pub status_url: String,
+}
+
+/// Response describing how the crawl of an accession went.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct AccessionCrawlStatusResponse {
+    pub accession_id: i32,
+    /// `pending` while the crawl runs, then `complete`, `bad_crawl` or `error`
+    pub crawl_status: CrawlStatus,
+    /// Why the capture looks bad, for bad crawls
+    pub crawl_quality_reason: Option<String>,
+}
+
+/// Response listing the accessions a bulk visibility change moved.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct BulkVisibilityResponse {
+    /// Accessions whose publication state changed; ones that already had the requested
+    /// visibility are left out
+    pub updated_ids: Vec<i32>,
+}
+
+/// Usage of an accession from one country.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct CountryUsageResponse {
+    /// ISO 3166 alpha-2 code, None when the country wasn't known
+    pub country: Option<String>,
+    pub views: i64,
+    pub wacz_urls: i64,
+}
+
+/// Response summarising how much an accession has been used.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct AccessionStatsResponse {
+    pub accession_id: i32,
+    pub views: i64,
+    /// How many times a URL to the accession's WACZ or uploaded file was handed out
+    pub wacz_urls: i64,
+    /// Usage broken down by country, busiest first
+    pub countries: Vec<CountryUsageResponse>,
+}
+
+impl AccessionStatsResponse {
+    /// Totals up per kind and country event counts.
+    pub fn from_counts(accession_id: i32, counts: Vec<EventCount>) -> Self {
+        let mut countries: Vec<CountryUsageResponse> = vec![];
+        for count in counts {
+            let index = match countries.iter().position(|c| c.country == count.country) {
+                Some(index) => index,
+                None => {
+                    countries.push(CountryUsageResponse {
+                        country: count.country.clone(),
+                        views: 0,
+                        wacz_urls: 0,
+                    });
+                    countries.len() - 1
+                }
+            };
+            match count.kind {
+                AccessionEventKind::View => countries[index].views += count.count,
+                AccessionEventKind::WaczUrl => countries[index].wacz_urls += count.count,
+            }
+        }
+        countries.sort_by(|a, b| {
+            (b.views + b.wacz_urls)
+                .cmp(&(a.views + a.wacz_urls))
+                .then_with(|| a.country.cmp(&b.country))
+        });
+        Self {
+            accession_id,
+            views: countries.iter().map(|c| c.views).sum(),
+            wacz_urls: countries.iter().map(|c| c.wacz_urls).sum(),
+            countries,
+        }
+    }
+}
+
+/// One of the most used accessions.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct TopAccessionResponse {
+    pub accession_id: i32,
+    pub count: i64,
+}
+
+impl From<AccessionEventTotal> for TopAccessionResponse {
+    fn from(total: AccessionEventTotal) -> Self {
+        Self {
+            accession_id: total.accession_id,
+            count: total.count,
+        }
+    }
+}
+
+/// Response listing the most used accessions across the archive.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct TopAccessionsResponse {
+    pub kind: AccessionEventKind,
+    /// Accessions with the most events of `kind`, busiest first
+    pub items: Vec<TopAccessionResponse>,
+}
+
+/// How many accessions are dated within one bucket of the timeline.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct TimelineBucketResponse {
+    /// Start of the bucket, midnight on the first day of the month or Monday of the week
+    pub starts_at: NaiveDateTime,
+    pub count: i64,
+}
+
+impl From<TimelineBucket> for TimelineBucketResponse {
+    fn from(bucket: TimelineBucket) -> Self {
+        Self {
+            starts_at: bucket.starts_at,
+            count: bucket.count,
+        }
+    }
+}
+
+/// Response counting accessions by their Dublin Core date, for a coverage timeline.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct AccessionTimelineResponse {
+    pub interval: TimelineInterval,
+    /// Buckets with at least one accession, oldest first; empty buckets are left out
+    pub buckets: Vec<TimelineBucketResponse>,
+}
+
+/// A crawl currently being polled by the pipeline.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct InProgressCrawlResponse {
+    pub crawl_id: Uuid,
+    pub url: String,
+    pub started_at: NaiveDateTime,
+    pub polls: u32,
+}
+
+impl From<InProgressCrawl> for InProgressCrawlResponse {
+    fn from(crawl: InProgressCrawl) -> Self {
+        Self {
+            crawl_id: crawl.crawl_id,
+            url: crawl.url,
+            started_at: crawl.started_at,
+            polls: crawl.polls,
+        }
+    }
+}
+
+/// A crawl waiting for Browsertrix capacity before it launches.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct QueuedCrawlResponse {
+    /// 1 for the crawl that launches next
+    pub position: u64,
+    pub url: String,
+    pub queued_at: NaiveDateTime,
+}
+
+/// A crawl that recently failed and why.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct CrawlFailureResponse {
+    pub crawl_id: Option<Uuid>,
+    pub url: String,
+    pub reason: String,
+    pub failed_at: NaiveDateTime,
+}
+
+impl From<CrawlFailure> for CrawlFailureResponse {
+    fn from(failure: CrawlFailure) -> Self {
+        Self {
+            crawl_id: failure.crawl_id,
+            url: failure.url,
+            reason: failure.reason,
+            failed_at: failure.failed_at,
+        }
+    }
+}
+
+/// An entry in the audit log.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct AuditLogEntryResponse {
+    pub id: i64,
+    pub actor_email: String,
+    /// Email of the admin impersonating the actor, if they were
+    pub impersonator_email: Option<String>,
+    pub event: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<serde_json::Value>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<AuditLogModel> for AuditLogEntryResponse {
+    fn from(model: AuditLogModel) -> Self {
+        Self {
+            id: model.id,
+            actor_email: model.actor_email,
+            impersonator_email: model.impersonator_email,
+            event: model.event,
+            entity_type: model.entity_type,
+            entity_id: model.entity_id,
+            details: model.details,
+            created_at: model.created_at,
+        }
+    }
+}
+
+/// Response for searching the audit log, most recent entries first.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ListAuditLogResponse {
+    pub items: Vec<AuditLogEntryResponse>,
     pub num_pages: u64,
     pub page: u64,
     pub per_page: u64,
 }
 
-/// Response containing a single subject with its identifier.
-#[derive(Debug, Deserialize, Serialize, ToSchema)]
+/// An endpoint to act on a review queue item with, so admins needn't look it up first.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct QuickActionResponse {
+    /// e.g. `accept_capture`
+    pub action: String,
+    pub method: String,
+    pub href: String,
+}
+
+impl QuickActionResponse {
+    fn new(action: &str, method: &str, href: String) -> Self {
+        Self {
+            action: action.to_string(),
+            method: method.to_string(),
+            href,
+        }
+    }
+}
+
+/// Something in the admin review queue that needs a person to look at it.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ReviewQueueItemResponse {
+    pub kind: ReviewQueueItemKind,
+    /// Null for crawls that failed before they were stored
+    pub accession_id: Option<i32>,
+    pub url: String,
+    pub title: Option<String>,
+    /// Why the crawl was flagged or failed
+    pub reason: Option<String>,
+    /// When the accession was crawled or the crawl failed
+    pub occurred_at: NaiveDateTime,
+    pub actions: Vec<QuickActionResponse>,
+}
+
+impl From<AccessionsWithMetadataModel> for ReviewQueueItemResponse {
+    fn from(model: AccessionsWithMetadataModel) -> Self {
+        let id = model.id;
+        let bad_capture = model.crawl_status == CrawlStatus::BadCrawl;
+        let mut actions = vec![QuickActionResponse::new(
+            "view",
+            "GET",
+            format!("/api/v1/accessions/private/{id}"),
+        )];
+        if bad_capture {
+            actions.push(QuickActionResponse::new(
+                "accept_capture",
+                "DELETE",
+                format!("/api/v1/accessions/{id}/bad-capture"),
+            ));
+        }
+        if model.publication_state == PublicationState::InReview {
+            actions.push(QuickActionResponse::new(
+                "set_publication_state",
+                "PUT",
+                format!("/api/v1/accessions/{id}/publication-state"),
+            ));
+        }
+        actions.push(QuickActionResponse::new(
+            "delete",
+            "DELETE",
+            format!("/api/v1/accessions/{id}"),
+        ));
+        Self {
+            kind: if bad_capture {
+                ReviewQueueItemKind::BadCapture
+            } else {
+                ReviewQueueItemKind::InReview
+            },
+            accession_id: Some(id),
+            url: model.seed_url,
+            title: model.title_en.or(model.title_ar),
+            reason: model.crawl_quality_reason,
+            occurred_at: model.crawl_timestamp,
+            actions,
+        }
+    }
+}
+
+impl From<CrawlFailure> for ReviewQueueItemResponse {
+    fn from(failure: CrawlFailure) -> Self {
+        Self {
+            kind: ReviewQueueItemKind::FailedCrawl,
+            accession_id: None,
+            url: failure.url,
+            title: None,
+            reason: Some(failure.reason),
+            occurred_at: failure.failed_at,
+            actions: vec![QuickActionResponse::new(
+                "recrawl",
+                "POST",
+                "/api/v1/accessions/crawl".to_string(),
+            )],
+        }
+    }
+}
+
+/// Response for the admin review queue.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ReviewQueueResponse {
+    /// Bad captures and accessions in review, most recently crawled first
+    pub items: Vec<ReviewQueueItemResponse>,
+    /// Crawls that failed since the server started, most recent first. Not paginated.
+    pub failed_crawls: Vec<ReviewQueueItemResponse>,
+    pub num_pages: u64,
+    pub page: u64,
+    pub per_page: u64,
+}
+
+/// An accession the S3 backfill could not copy and why.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct BackfillFailureResponse {
+    /// Null when the backfill couldn't list the accessions to copy at all
+    pub accession_id: Option<i32>,
+    pub reason: String,
+}
+
+impl From<BackfillFailure> for BackfillFailureResponse {
+    fn from(failure: BackfillFailure) -> Self {
+        Self {
+            accession_id: failure.accession_id,
+            reason: failure.reason,
+        }
+    }
+}
+
+/// Progress of the running or most recent copy of legacy WACZ files into S3.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct S3BackfillStatusResponse {
+    pub running: bool,
+    /// Accessions the backfill set out to copy
+    pub total: u64,
+    pub transferred: u64,
+    pub failures: Vec<BackfillFailureResponse>,
+    /// Null if no backfill has run since the server started
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+impl From<BackfillSnapshot> for S3BackfillStatusResponse {
+    fn from(snapshot: BackfillSnapshot) -> Self {
+        Self {
+            running: snapshot.running,
+            total: snapshot.total,
+            transferred: snapshot.transferred,
+            failures: snapshot.failures.into_iter().map(Into::into).collect(),
+            started_at: snapshot.started_at,
+            finished_at: snapshot.finished_at,
+        }
+    }
+}
+
+/// Progress of the running or most recent static export of the public archive.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct StaticExportStatusResponse {
+    pub running: bool,
+    /// Accessions written to page files so far
+    pub exported: u64,
+    pub pages: u64,
+    /// Why the export stopped early, if it did
+    pub error: Option<String>,
+    /// Null if no export has run since the server started
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+impl From<StaticExportSnapshot> for StaticExportStatusResponse {
+    fn from(snapshot: StaticExportSnapshot) -> Self {
+        Self {
+            running: snapshot.running,
+            exported: snapshot.exported,
+            pages: snapshot.pages,
+            error: snapshot.error,
+            started_at: snapshot.started_at,
+            finished_at: snapshot.finished_at,
+        }
+    }
+}
+
+/// Progress of the running or most recent rebuild of the full text search columns and indexes.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ReindexStatusResponse {
+    pub running: bool,
+    /// Accessions there were when the reindex started
+    pub total: u64,
+    pub refreshed: u64,
+    /// Whether the indexes have been rebuilt, which happens once every row is refreshed
+    pub indexes_rebuilt: bool,
+    /// Why the reindex stopped early, if it did
+    pub error: Option<String>,
+    /// Null if no reindex has run since the server started
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+impl From<ReindexSnapshot> for ReindexStatusResponse {
+    fn from(snapshot: ReindexSnapshot) -> Self {
+        Self {
+            running: snapshot.running,
+            total: snapshot.total,
+            refreshed: snapshot.refreshed,
+            indexes_rebuilt: snapshot.indexes_rebuilt,
+            error: snapshot.error,
+            started_at: snapshot.started_at,
+            finished_at: snapshot.finished_at,
+        }
+    }
+}
+
+/// Response summarizing the health of the crawl pipeline since the server started.
+#[derive(Debug, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct PipelineStatusResponse {
+    pub crawls_in_progress: Vec<InProgressCrawlResponse>,
+    pub completed_crawls: u64,
+    pub average_crawl_duration_secs: Option<f64>,
+    pub poll_retries: u64,
+    /// How many crawls may run in Browsertrix at once
+    pub max_active_crawls: u64,
+    /// Number of crawls waiting to be launched
+    pub queue_depth: Option<u64>,
+    /// Crawls waiting to be launched, next to launch first
+    pub queued_crawls: Vec<QueuedCrawlResponse>,
+    pub recent_failures: Vec<CrawlFailureResponse>,
+}
+
+impl From<(PipelineSnapshot, CrawlQueueSnapshot)> for PipelineStatusResponse {
+    fn from((snapshot, queue): (PipelineSnapshot, CrawlQueueSnapshot)) -> Self {
+        Self {
+            crawls_in_progress: snapshot.in_progress.into_iter().map(Into::into).collect(),
+            completed_crawls: snapshot.completed_crawls,
+            average_crawl_duration_secs: snapshot.average_crawl_duration_secs,
+            poll_retries: snapshot.poll_retries,
+            max_active_crawls: queue.max_active as u64,
+            queue_depth: Some(queue.queued.len() as u64),
+            queued_crawls: queue
+                .queued
+                .into_iter()
+                .enumerate()
+                .map(|(index, crawl)| QueuedCrawlResponse {
+                    position: index as u64 + 1,
+                    url: crawl.url,
+                    queued_at: crawl.queued_at,
+                })
+                .collect(),
+            recent_failures: snapshot
+                .recent_failures
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+/// Runs of one scheduled background task since the server started.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ScheduledTaskResponse {
+    pub name: String,
+    pub runs: u64,
+    pub failures: u64,
+    pub last_started_at: Option<NaiveDateTime>,
+    pub last_duration_ms: Option<u64>,
+    /// Summary of the last successful run
+    pub last_result: Option<String>,
+    /// Error from the last failed run
+    pub last_error: Option<String>,
+}
+
+impl From<(&'static str, TaskStats)> for ScheduledTaskResponse {
+    fn from((name, stats): (&'static str, TaskStats)) -> Self {
+        Self {
+            name: name.to_string(),
+            runs: stats.runs,
+            failures: stats.failures,
+            last_started_at: stats.last_started_at,
+            last_duration_ms: stats.last_duration_ms,
+            last_result: stats.last_result,
+            last_error: stats.last_error,
+        }
+    }
+}
+
+/// Response describing the scheduled background tasks.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct SchedulerStatusResponse {
+    pub tasks: Vec<ScheduledTaskResponse>,
+}
+
+/// Response saying which instance of the API runs the scheduled background tasks.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct SchedulerLeaderResponse {
+    /// Name of the instance that answered
+    pub instance: String,
+    /// Whether the instance that answered runs the background tasks
+    pub is_leader: bool,
+    /// Name of the instance running the background tasks, `null` while none is, e.g.
+    /// between the leader going down and another taking over
+    pub leader: Option<String>,
+}
+
+/// Response containing a single subject with its identifier and who added it when.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
 pub struct SubjectResponse {
     pub id: i32,
     pub subject: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    /// Email of the user who added the subject, `null` for subjects added before it was
+    /// recorded
+    pub created_by: Option<String>,
+}
+
+impl From<DublinMetadataSubjectEnModel> for SubjectResponse {
+    fn from(model: DublinMetadataSubjectEnModel) -> Self {
+        Self {
+            id: model.id,
+            subject: model.subject,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+            created_by: model.created_by,
+        }
+    }
+}
+
+impl From<DublinMetadataSubjectArModel> for SubjectResponse {
+    fn from(model: DublinMetadataSubjectArModel) -> Self {
+        Self {
+            id: model.id,
+            subject: model.subject,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+            created_by: model.created_by,
+        }
+    }
+}
+
+/// Subjects suggested for an accession in one language.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct SubjectSuggestions {
+    /// Existing subjects the accession isn't tagged with yet
+    pub existing: Vec<SubjectResponse>,
+    /// Terms that don't match any subject, which could be added as new subjects
+    pub new_terms: Vec<String>,
+}
+
+/// Subjects suggested for an accession from the entities named in its metadata.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct SuggestedSubjectsResponse {
+    pub english: SubjectSuggestions,
+    pub arabic: SubjectSuggestions,
+}
+
+/// Outcome of attaching a subject to every accession matching a filter.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ApplySubjectResponse {
+    pub dry_run: bool,
+    /// Accessions matching the filter, whether or not they already had the subject
+    pub matched: u64,
+    /// Accessions the subject was attached to, or would be on a dry run
+    pub tagged: u64,
 }
 
 /// Response for listing Arabic language subjects with pagination.
@@ -169,6 +1379,202 @@ pub struct ListSubjectsEnResponse {
     pub per_page: u64,
 }
 
+/// Response containing a single internal workflow label.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct WorkflowLabelResponse {
+    pub id: i32,
+    pub label: String,
+}
+
+impl From<WorkflowLabelModel> for WorkflowLabelResponse {
+    fn from(model: WorkflowLabelModel) -> Self {
+        Self {
+            id: model.id,
+            label: model.label,
+        }
+    }
+}
+
+/// Response for listing internal workflow labels.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ListWorkflowLabelsResponse {
+    pub items: Vec<WorkflowLabelResponse>,
+}
+
+/// A collection of accessions and what's in it.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct CollectionResponse {
+    pub id: i32,
+    pub organization_id: i32,
+    pub title: String,
+    pub description: Option<String>,
+    /// Email of the curator who made the collection
+    pub created_by: String,
+    pub created_at: NaiveDateTime,
+    /// IDs of the accessions in the collection, public or not
+    pub accession_ids: Vec<i32>,
+}
+
+impl CollectionResponse {
+    pub fn new(model: CollectionModel, accession_ids: Vec<i32>) -> Self {
+        Self {
+            id: model.id,
+            organization_id: model.organization_id,
+            title: model.title,
+            description: model.description,
+            created_by: model.created_by,
+            created_at: model.created_at,
+            accession_ids,
+        }
+    }
+}
+
+/// Response for starting a collection export, see [`crate::collection_export`].
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+pub struct CollectionExportResponse {
+    pub collection_id: i32,
+    /// How many public accessions will be exported
+    pub accessions: usize,
+    /// How many private accessions are left out
+    pub left_out: usize,
+    /// Where the link to the export will be emailed
+    pub email: String,
+}
+
+/// Response containing a single feature flag.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct FeatureFlagResponse {
+    pub name: String,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub environments: Vec<String>,
+    pub roles: Vec<Role>,
+    pub updated_at: NaiveDateTime,
+}
+
+impl From<FeatureFlagModel> for FeatureFlagResponse {
+    fn from(model: FeatureFlagModel) -> Self {
+        Self {
+            name: model.name,
+            description: model.description,
+            enabled: model.enabled,
+            environments: model.environments,
+            roles: model
+                .roles
+                .iter()
+                .filter_map(|role| Role::try_from_value(role).ok())
+                .collect(),
+            updated_at: model.updated_at,
+        }
+    }
+}
+
+/// Response for listing feature flags.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ListFeatureFlagsResponse {
+    pub items: Vec<FeatureFlagResponse>,
+}
+
+/// Response containing a single crawl blocklist entry.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct CrawlBlocklistEntryResponse {
+    pub id: i32,
+    pub pattern: String,
+    pub reason: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<CrawlBlocklistModel> for CrawlBlocklistEntryResponse {
+    fn from(model: CrawlBlocklistModel) -> Self {
+        Self {
+            id: model.id,
+            pattern: model.pattern,
+            reason: model.reason,
+            created_at: model.created_at,
+        }
+    }
+}
+
+/// Response for listing the crawl blocklist.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ListCrawlBlocklistResponse {
+    pub items: Vec<CrawlBlocklistEntryResponse>,
+}
+
+/// A user as shown to admins.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct UserResponse {
+    pub id: Uuid,
+    pub email: String,
+    pub role: Role,
+    pub is_active: bool,
+    /// Whether emails to the user are getting through, from Postmark's webhooks
+    pub email_status: EmailStatus,
+    pub organization_id: i32,
+}
+
+impl From<ArchiveUserModel> for UserResponse {
+    fn from(model: ArchiveUserModel) -> Self {
+        Self {
+            id: model.id,
+            email: model.email,
+            role: model.role,
+            is_active: model.is_active,
+            email_status: model.email_status,
+            organization_id: model.organization_id,
+        }
+    }
+}
+
+/// Response containing a single organization.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct OrganizationResponse {
+    pub id: i32,
+    pub slug: String,
+    pub name: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<OrganizationModel> for OrganizationResponse {
+    fn from(model: OrganizationModel) -> Self {
+        Self {
+            id: model.id,
+            slug: model.slug,
+            name: model.name,
+            created_at: model.created_at,
+        }
+    }
+}
+
+/// Response for listing organizations.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ListOrganizationsResponse {
+    pub items: Vec<OrganizationResponse>,
+}
+
+/// The user an admin is signed in as in support mode.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ImpersonationResponse {
+    pub user_id: Uuid,
+    pub email: String,
+    pub role: Role,
+    /// When the support session ends and the admin has to sign in again as themselves
+    pub expires_at: NaiveDateTime,
+}
+
+/// Response for listing users.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ListUsersResponse {
+    pub items: Vec<UserResponse>,
+}
+
+/// Response listing the feature flags that are on for the caller.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct EnabledFeatureFlagsResponse {
+    #[schema(example = json!(["wacz-full-text"]))]
+    pub names: Vec<String>,
+}
+
 /// Response containing the created API key secret.
 #[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct CreateApiKeyResponse {
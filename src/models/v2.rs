@@ -0,0 +1,159 @@
+//! Request and response models for the `/api/v2` namespace.
+//!
+//! Version 2 of the API shares services with version 1 but differs in how it
+//! paginates (1-indexed pages with a nested pagination block) and how it reports
+//! errors (a structured [`crate::models::error::ErrorResponse`] rather than a plain string).
+
+use crate::models::common::MetadataLanguage;
+use crate::models::request::AccessionPaginationWithPrivate;
+use crate::models::response::{
+    AccessionRelationResponse, AccessionsWithMetadataResponse, DerivativeResponse,
+    PublicAccessionsWithMetadataResponse, SocialMetadataResponse,
+};
+use crate::repos::pagination::{DEFAULT_PER_PAGE, MAX_PAGE, MAX_PER_PAGE};
+use chrono::NaiveDateTime;
+use entity::sea_orm_active_enums::SocialPlatform;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+/// Deepest 1-indexed page v2 accepts, the same depth as [`MAX_PAGE`] in v1.
+const MAX_PAGE_V2: u64 = MAX_PAGE + 1;
+
+/// Pagination and filtering parameters for listing accessions in v2.
+///
+/// Pages are 1-indexed, unlike v1 where the first page is 0.
+#[derive(Debug, Clone, Deserialize, Validate, IntoParams, ToSchema)]
+#[serde(default)]
+pub struct AccessionPaginationV2 {
+    #[validate(range(min = 1, max = MAX_PAGE_V2))]
+    #[schema(default = 1, minimum = 1, maximum = 10_001)]
+    pub page: u64,
+    #[validate(range(min = 1, max = MAX_PER_PAGE))]
+    #[schema(default = 20, minimum = 1, maximum = 200)]
+    pub per_page: u64,
+    pub lang: MetadataLanguage,
+    #[schema(example = json!([1, 2, 3]))]
+    pub metadata_subjects: Vec<i32>,
+    pub metadata_subjects_inclusive_filter: Option<bool>,
+    #[validate(length(min = 1, max = 500))]
+    pub query_term: Option<String>,
+    #[validate(length(min = 1, max = 2000))]
+    pub url_filter: Option<String>,
+    pub date_from: Option<NaiveDateTime>,
+    pub date_to: Option<NaiveDateTime>,
+    /// Only accessions with (true) or without (false) a content warning
+    pub has_content_warning: Option<bool>,
+    /// Set to false to skip counting the total pages, e.g. for infinite scroll
+    #[schema(default = true)]
+    pub count: bool,
+    /// Only accessions belonging to this organization
+    pub organization_id: Option<i32>,
+    /// Only accessions crawled from a post on this social media platform
+    pub social_platform: Option<SocialPlatform>,
+    /// Only accessions crawled from a post by this account, ignoring case and a leading `@`
+    #[validate(length(min = 1, max = 200))]
+    pub author_handle: Option<String>,
+}
+
+impl Default for AccessionPaginationV2 {
+    fn default() -> Self {
+        Self {
+            page: 1,
+            per_page: DEFAULT_PER_PAGE,
+            lang: MetadataLanguage::English,
+            metadata_subjects: [].to_vec(),
+            metadata_subjects_inclusive_filter: None,
+            query_term: None,
+            url_filter: None,
+            date_from: None,
+            date_to: None,
+            has_content_warning: None,
+            count: true,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
+        }
+    }
+}
+
+impl AccessionPaginationV2 {
+    /// Converts the v2 query into the shared, 0-indexed listing parameters used by the services.
+    pub fn into_list_params(self, is_private: bool) -> AccessionPaginationWithPrivate {
+        AccessionPaginationWithPrivate {
+            page: self.page.saturating_sub(1),
+            per_page: self.per_page,
+            lang: self.lang,
+            metadata_subjects: self.metadata_subjects,
+            metadata_subjects_inclusive_filter: self.metadata_subjects_inclusive_filter,
+            query_term: self.query_term,
+            url_filter: self.url_filter,
+            date_from: self.date_from,
+            date_to: self.date_to,
+            has_content_warning: self.has_content_warning,
+            count: self.count,
+            is_private,
+            publication_state: None,
+            workflow_labels: [].to_vec(),
+            organization_id: self.organization_id,
+            social_platform: self.social_platform,
+            author_handle: self.author_handle,
+        }
+    }
+}
+
+/// Pagination details returned alongside every v2 list response.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct PaginationMeta {
+    /// The current page, starting from 1
+    pub page: u64,
+    pub per_page: u64,
+    /// Empty when the request opted out of counting
+    pub total_pages: Option<u64>,
+}
+
+/// Response for listing accessions in v2.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ListAccessionsV2Response {
+    pub items: Vec<AccessionsWithMetadataResponse>,
+    pub pagination: PaginationMeta,
+}
+
+/// Response for listing public accessions in v2 as an anonymous user.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct ListPublicAccessionsV2Response {
+    pub items: Vec<PublicAccessionsWithMetadataResponse>,
+    pub pagination: PaginationMeta,
+}
+
+/// Response for retrieving a single accession in v2.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct GetOneAccessionV2Response {
+    pub accession: AccessionsWithMetadataResponse,
+    /// For private accessions this and the other file URLs are short-lived links to the
+    /// file proxy that only work for the user who fetched the accession
+    pub wacz_url: String,
+    /// Presigned URL of a PDF rendering of the captured page, when one has been generated
+    pub pdf_url: Option<String>,
+    /// Web friendly renditions of uploaded images and videos
+    pub derivatives: Vec<DerivativeResponse>,
+    /// Accessions this one is connected to, including private ones the viewer can see
+    pub relations: Vec<AccessionRelationResponse>,
+    /// Who posted the social media post the accession was crawled from, if it was one
+    pub social_metadata: Option<SocialMetadataResponse>,
+}
+
+/// Response for retrieving a single public accession in v2 as an anonymous user.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+pub struct GetOnePublicAccessionV2Response {
+    pub accession: PublicAccessionsWithMetadataResponse,
+    pub wacz_url: String,
+    /// Presigned URL of a PDF rendering of the captured page, when one has been generated
+    pub pdf_url: Option<String>,
+    /// Web friendly renditions of uploaded images and videos
+    pub derivatives: Vec<DerivativeResponse>,
+    /// Public accessions this one is connected to
+    pub relations: Vec<AccessionRelationResponse>,
+    /// Who posted the social media post the accession was crawled from, if it was one
+    pub social_metadata: Option<SocialMetadataResponse>,
+}
@@ -0,0 +1,211 @@
+//! Serving the API as a Tor onion service as well as on the clearnet.
+//!
+//! Readers inside Sudan may only be able to reach the archive over Tor. When `ONION_URL` is
+//! set, [`serve_onion`] adds an `Onion-Location` header to clearnet responses pointing at the
+//! same path on the onion service, which Tor Browser uses to offer switching to it.
+//!
+//! Requests that arrive at the onion address get their session cookies without a `Domain`,
+//! since the clearnet `JWT_COOKIE_DOMAIN` doesn't match the onion address and browsers would
+//! drop them. The cookies stay `Secure` and `SameSite=Strict`: Tor Browser treats onion
+//! addresses as secure even over plain HTTP, and the frontend's onion service is expected to
+//! serve the API under its own address, so cookies are same site and no CORS is needed.
+
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use http::header::{HOST, SET_COOKIE};
+use http::{HeaderName, HeaderValue, Uri};
+use reqwest::Url;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Header Tor Browser reads to offer the onion service, see
+/// <https://community.torproject.org/onion-services/advanced/onion-location/>
+pub const ONION_LOCATION: HeaderName = HeaderName::from_static("onion-location");
+
+/// Where the API is reachable over Tor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OnionService {
+    /// URL of the API on the onion service, including any prefix, without a trailing slash
+    url: String,
+    /// The onion address requests to the onion service carry in their `Host` header
+    host: String,
+}
+
+impl OnionService {
+    /// Reads the onion service's URL.
+    ///
+    /// # Returns
+    /// The onion service, or an error if the URL isn't an HTTP(S) URL on a `.onion` host
+    pub fn from_url(url: &str) -> Result<Self, String> {
+        let parsed = Url::parse(url).map_err(|err| format!("ONION_URL should be a URL: {err}"))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!(
+                "ONION_URL should be a http or https URL, got a {} one",
+                parsed.scheme()
+            ));
+        }
+        match parsed.host_str() {
+            Some(host) if host.ends_with(".onion") => Ok(Self {
+                url: url.trim_end_matches('/').to_string(),
+                host: host.to_string(),
+            }),
+            _ => Err(format!(
+                "ONION_URL should be on a .onion address, got {url}"
+            )),
+        }
+    }
+
+    /// Where the resource at `uri` is on the onion service.
+    fn location(&self, uri: &Uri) -> String {
+        let path = uri.path_and_query().map_or("/", |path| path.as_str());
+        format!("{}{path}", self.url)
+    }
+
+    /// Whether a request came in through the onion service rather than the clearnet.
+    fn is_onion_request(&self, request: &Request) -> bool {
+        request
+            .headers()
+            .get(HOST)
+            .and_then(|host| host.to_str().ok())
+            // the Host header may carry a port
+            .map(|host| host.rsplit_once(':').map_or(host, |(host, _)| host))
+            .is_some_and(|host| host.eq_ignore_ascii_case(&self.host))
+    }
+}
+
+/// Drops the `Domain` attribute from a `Set-Cookie` value, so the cookie is only sent back to
+/// the host that set it.
+fn host_only_cookie(cookie: &str) -> String {
+    cookie
+        .split(';')
+        .filter(|attribute| {
+            !attribute
+                .trim_start()
+                .to_ascii_lowercase()
+                .starts_with("domain=")
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Points clearnet responses at the onion service and makes cookies set over the onion
+/// service host only.
+pub async fn serve_onion(
+    State(onion): State<Arc<OnionService>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let via_onion = onion.is_onion_request(&request);
+    let location = onion.location(request.uri());
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    if !via_onion {
+        match HeaderValue::from_str(&location) {
+            Ok(location) => {
+                headers.insert(ONION_LOCATION, location);
+            }
+            Err(err) => warn!(%err, "Could not build Onion-Location header"),
+        }
+        return response;
+    }
+    let cookies: Vec<HeaderValue> = headers
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|cookie| cookie.to_str().ok())
+        .filter_map(|cookie| HeaderValue::from_str(&host_only_cookie(cookie)).ok())
+        .collect();
+    headers.remove(SET_COOKIE);
+    for cookie in cookies {
+        headers.append(SET_COOKIE, cookie);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::middleware::from_fn_with_state;
+    use axum::routing::get;
+    use axum::Router;
+    use pretty_assertions::assert_eq;
+    use tower::ServiceExt;
+
+    const ONION_URL: &str = "http://sdaexampleonionaddress.onion/api/";
+
+    fn onion_app() -> Router {
+        let onion = Arc::new(OnionService::from_url(ONION_URL).unwrap());
+        Router::new()
+            .route(
+                "/login",
+                get(|| async {
+                    (
+                        [(
+                            SET_COOKIE,
+                            "jwt=abc; HttpOnly; Secure; Domain=sudandigitalarchive.com; Path=/",
+                        )],
+                        "ok",
+                    )
+                }),
+            )
+            .layer(from_fn_with_state(onion, serve_onion))
+    }
+
+    fn login_request(host: &str) -> Request<Body> {
+        Request::builder()
+            .uri("/login?lang=ar")
+            .header(HOST, host)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn only_accepts_onion_addresses() {
+        assert!(OnionService::from_url(ONION_URL).is_ok());
+        assert!(OnionService::from_url("https://sudandigitalarchive.com").is_err());
+        assert!(OnionService::from_url("ftp://sdaexampleonionaddress.onion").is_err());
+        assert!(OnionService::from_url("not a url").is_err());
+    }
+
+    #[test]
+    fn drops_the_cookie_domain() {
+        assert_eq!(
+            host_only_cookie("jwt=abc; HttpOnly; Secure; Domain=example.com; Path=/"),
+            "jwt=abc; HttpOnly; Secure; Path=/"
+        );
+        assert_eq!(
+            host_only_cookie("logged_in=true; Path=/"),
+            "logged_in=true; Path=/"
+        );
+    }
+
+    #[tokio::test]
+    async fn points_clearnet_responses_at_the_onion_service() {
+        let response = onion_app()
+            .oneshot(login_request("api.sudandigitalarchive.com"))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[ONION_LOCATION],
+            "http://sdaexampleonionaddress.onion/api/login?lang=ar"
+        );
+        assert!(response.headers()[SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .contains("Domain=sudandigitalarchive.com"));
+    }
+
+    #[tokio::test]
+    async fn sets_host_only_cookies_over_the_onion_service() {
+        let response = onion_app()
+            .oneshot(login_request("sdaexampleonionaddress.onion:80"))
+            .await
+            .unwrap();
+        assert!(response.headers().get(ONION_LOCATION).is_none());
+        assert_eq!(
+            response.headers()[SET_COOKIE],
+            "jwt=abc; HttpOnly; Secure; Path=/"
+        );
+    }
+}
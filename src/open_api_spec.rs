@@ -1,13 +1,49 @@
+use crate::access_log_export::{AccessLogFormat, AccessLogManifest};
+use crate::citation_export::{CitationFormat, CslDate, CslItem};
+use crate::iiif::{Annotation, AnnotationPage, Canvas, ImageBody, Manifest, MetadataEntry};
+use crate::models::common::{ReviewQueueItemKind, SubjectMatch, SubjectSort, TimelineInterval};
+use crate::models::error::{ErrorResponse, LocalizedMessages};
 use crate::models::request::{
-    AccessionPagination, AccessionPaginationWithPrivate, AuthorizeRequest, CreateAccessionRequest,
-    CreateAccessionRequestRaw, CreateSubjectRequest, DeleteSubjectRequest, LoginRequest,
-    SubjectPagination, UpdateAccessionRequest,
+    AccessionPagination, ApplySubjectRequest, AuthorizeRequest, BulkVisibilityRequest,
+    CommentRequest, CreateAccessionRelationRequest, CreateAccessionRequest,
+    CreateAccessionRequestRaw, CreateCollectionRequest, CreateCrawlBlocklistEntryRequest,
+    CreateFeatureFlagRequest, CreateMetadataRequest, CreateOrganizationRequest,
+    CreateSubjectRequest, CreateWorkflowLabelRequest, DeleteSubjectRequest, InitiateUploadRequest,
+    LoginRequest, PostmarkWebhookRequest, PresignUploadRequest, PrivateAccessionPagination,
+    RenameOrganizationRequest, SubjectPagination, UpdateAccessionRequest,
+    UpdateActivityDigestRequest, UpdateFeatureFlagRequest, UpdateMetadataRequest,
+    UpdatePublicationStateRequest,
 };
 use crate::models::response::{
-    CreateApiKeyResponse, GetOneAccessionResponse, ListAccessionsResponse, ListSubjectsArResponse,
-    ListSubjectsEnResponse, SubjectResponse,
+    AccessionCrawlStatusResponse, AccessionRelationResponse, AccessionStatsResponse,
+    AccessionSubjectResponse, AccessionTimelineResponse, ApplySubjectResponse,
+    AuditLogEntryResponse, BackfillFailureResponse, BulkVisibilityResponse,
+    CollectionExportResponse, CollectionResponse, CommentResponse, CompleteUploadResponse,
+    CountryUsageResponse, CrawlBlocklistEntryResponse, CrawlFailureResponse,
+    CreateAccessionCrawlResponse, CreateApiKeyResponse, DisplayMetadata, DoiResponse,
+    DryRunAccessionResponse, DuplicateAccessionsResponse, EnabledFeatureFlagsResponse,
+    FeatureFlagResponse, GetOneAccessionResponse, GetOnePublicAccessionResponse,
+    ImpersonationResponse, InProgressCrawlResponse, InitiateUploadResponse,
+    ListAccessionPagesResponse, ListAccessionRelationsResponse, ListAccessionsResponse,
+    ListAuditLogResponse, ListCommentsResponse, ListCrawlBlocklistResponse,
+    ListFeatureFlagsResponse, ListOrganizationsResponse, ListPublicAccessionsResponse,
+    ListSubjectsArResponse, ListSubjectsEnResponse, ListUploadPartsResponse, ListUsersResponse,
+    ListWorkflowLabelsResponse, MessageResponse, OrganizationResponse, PipelineStatusResponse,
+    PresignUploadResponse, PresignedPartUrlResponse, ProvenanceResponse,
+    PublicAccessionsWithMetadataResponse, PublicStatsResponse, QueuedCrawlResponse,
+    QuickActionResponse, ReindexStatusResponse, ReplaceAccessionFileResponse,
+    ReviewQueueItemResponse, ReviewQueueResponse, S3BackfillStatusResponse, ScheduledTaskResponse,
+    SchedulerLeaderResponse, SchedulerStatusResponse, SocialMetadataResponse,
+    StaticExportStatusResponse, SubjectResponse, SubjectSuggestions, SuggestedSubjectsResponse,
+    TimelineBucketResponse, TopAccessionResponse, TopAccessionsResponse, UploadPartResponse,
+    UploadProgressResponse, UserResponse, WaczPageResponse, WorkflowLabelResponse,
 };
-use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use crate::models::v2::{
+    AccessionPaginationV2, GetOneAccessionV2Response, GetOnePublicAccessionV2Response,
+    ListAccessionsV2Response, ListPublicAccessionsV2Response,
+};
+use crate::permissions::PermissionsAddon;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
 use utoipa::{Modify, OpenApi};
 
 struct SecurityAddon;
@@ -23,6 +59,10 @@ impl Modify for SecurityAddon {
             "api_key_auth",
             SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Key"))),
         );
+        components.add_security_scheme(
+            "postmark_basic_auth",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Basic).build()),
+        );
     }
 }
 
@@ -32,48 +72,256 @@ impl Modify for SecurityAddon {
     paths(
         crate::routes::health::healthcheck,
         crate::routes::accessions::create_accession_crawl,
+        crate::routes::accessions::get_accession_crawl_status,
         crate::routes::accessions::create_accession_raw,
+        crate::routes::accessions::create_accession_from_file,
         crate::routes::accessions::get_one_accession,
+        crate::routes::accessions::get_accession_iiif_manifest,
+        crate::routes::accessions::list_accession_pages,
+        crate::routes::accessions::get_accession_stats,
+        crate::routes::accessions::list_top_accessions,
         crate::routes::accessions::get_one_private_accession,
+        crate::routes::accessions::stream_private_file,
         crate::routes::accessions::list_accessions,
         crate::routes::accessions::list_accessions_private,
+        crate::routes::accessions::list_bad_captures,
+        crate::routes::accessions::clear_bad_capture,
+        crate::routes::accessions::stream_published_accessions,
+        crate::routes::accessions::export_accessions,
+        crate::routes::accessions::get_accessions_timeline,
         crate::routes::accessions::delete_accession,
         crate::routes::accessions::update_accession,
+        crate::routes::accessions::update_accession_publication_state,
+        crate::routes::accessions::translate_accession_metadata,
+        crate::routes::accessions::mint_accession_doi,
+        crate::routes::accessions::replace_accession_file,
+        crate::routes::accessions::get_accession_provenance,
+        crate::routes::accessions::get_private_accession_provenance,
+        crate::routes::accessions::get_accession_duplicates,
+        crate::routes::accessions::get_suggested_subjects,
+        crate::routes::accessions::bulk_update_visibility,
+        crate::routes::admin::get_pipeline_status,
+        crate::routes::admin::get_scheduler_status,
+        crate::routes::admin::get_scheduler_leader,
+        crate::routes::stats::get_public_stats,
+        crate::routes::admin::get_review_queue,
+        crate::routes::admin::list_audit_log,
+        crate::routes::admin::export_audit_log,
+        crate::routes::admin::export_access_log,
+        crate::routes::admin::list_users,
+        crate::routes::admin::deactivate_user,
+        crate::routes::admin::impersonate_user,
+        crate::routes::admin::start_s3_backfill,
+        crate::routes::admin::get_s3_backfill_status,
+        crate::routes::admin::start_static_export,
+        crate::routes::admin::get_static_export_status,
+        crate::routes::admin::start_reindex,
+        crate::routes::admin::get_reindex_status,
+        crate::routes::organizations::list_organizations,
+        crate::routes::organizations::create_organization,
+        crate::routes::organizations::rename_organization,
+        crate::routes::organizations::assign_user_to_organization,
+        crate::routes::webhooks::handle_postmark_webhook,
+        crate::routes::memento::memento_timegate,
+        crate::routes::memento::memento_timemap,
+        crate::routes::public::list_accessions,
+        crate::routes::public::get_one_accession,
+        crate::routes::public::list_subjects,
+        crate::routes::feature_flags::list_enabled_feature_flags,
+        crate::routes::feature_flags::create_feature_flag,
+        crate::routes::feature_flags::list_feature_flags,
+        crate::routes::feature_flags::update_feature_flag,
+        crate::routes::feature_flags::delete_feature_flag,
+        crate::routes::crawl_blocklist::create_crawl_blocklist_entry,
+        crate::routes::crawl_blocklist::list_crawl_blocklist,
+        crate::routes::crawl_blocklist::delete_crawl_blocklist_entry,
+        crate::routes::uploads::initiate_upload,
+        crate::routes::uploads::presign_upload,
+        crate::routes::uploads::upload_part,
+        crate::routes::uploads::list_upload_parts,
+        crate::routes::uploads::complete_upload,
+        crate::routes::uploads::abort_upload,
+        crate::routes::uploads::get_upload_progress,
         crate::routes::auth::login,
         crate::routes::auth::authorize,
         crate::routes::auth::verify,
         crate::routes::auth::create_api_key,
+        crate::routes::auth::set_activity_digest,
         crate::routes::subjects::create_subject,
         crate::routes::subjects::list_subjects,
-        crate::routes::subjects::delete_subject
+        crate::routes::subjects::delete_subject,
+        crate::routes::subjects::apply_subject,
+        crate::routes::workflow_labels::create_workflow_label,
+        crate::routes::workflow_labels::list_workflow_labels,
+        crate::routes::workflow_labels::delete_workflow_label,
+        crate::routes::workflow_labels::list_accession_workflow_labels,
+        crate::routes::workflow_labels::add_accession_workflow_label,
+        crate::routes::workflow_labels::remove_accession_workflow_label,
+        crate::routes::collections::create_collection,
+        crate::routes::collections::get_collection,
+        crate::routes::collections::add_collection_accession,
+        crate::routes::collections::remove_collection_accession,
+        crate::routes::collections::export_collection,
+        crate::routes::accession_relations::list_accession_relations,
+        crate::routes::accession_relations::create_accession_relation,
+        crate::routes::accession_relations::delete_accession_relation,
+        crate::routes::comments::list_comments,
+        crate::routes::comments::create_comment,
+        crate::routes::comments::update_comment,
+        crate::routes::comments::delete_comment,
+        crate::routes::v2::accessions::list_accessions,
+        crate::routes::v2::accessions::list_accessions_private,
+        crate::routes::v2::accessions::get_one_accession,
+        crate::routes::v2::accessions::get_one_private_accession
     ),
     components(
         schemas(
             AccessionPagination,
-            AccessionPaginationWithPrivate,
+            CitationFormat,
+            CslDate,
+            CslItem,
+            PrivateAccessionPagination,
             CreateAccessionRequest,
+            CreateMetadataRequest,
             CreateAccessionRequestRaw,
             UpdateAccessionRequest,
+            UpdateMetadataRequest,
+            UpdatePublicationStateRequest,
+            BulkVisibilityRequest,
+            BulkVisibilityResponse,
             GetOneAccessionResponse,
+            GetOnePublicAccessionResponse,
+            DoiResponse,
+            ReplaceAccessionFileResponse,
+            AccessLogFormat,
+            AccessLogManifest,
+            ProvenanceResponse,
+            DuplicateAccessionsResponse,
+            Manifest,
+            MetadataEntry,
+            Canvas,
+            AnnotationPage,
+            Annotation,
+            ImageBody,
+            ListAccessionPagesResponse,
+            WaczPageResponse,
+            AccessionStatsResponse,
+            CountryUsageResponse,
+            TopAccessionsResponse,
+            TopAccessionResponse,
+            TimelineInterval,
+            AccessionTimelineResponse,
+            TimelineBucketResponse,
+            PublicAccessionsWithMetadataResponse,
+            AccessionSubjectResponse,
+            DisplayMetadata,
             ListAccessionsResponse,
+            ListPublicAccessionsResponse,
             LoginRequest,
             AuthorizeRequest,
+            UpdateActivityDigestRequest,
             CreateApiKeyResponse,
             CreateSubjectRequest,
             DeleteSubjectRequest,
+            ApplySubjectRequest,
+            ApplySubjectResponse,
             SubjectPagination,
+            SubjectMatch,
+            SubjectSort,
             SubjectResponse,
+            SubjectSuggestions,
+            SuggestedSubjectsResponse,
             ListSubjectsEnResponse,
-            ListSubjectsArResponse
+            ListSubjectsArResponse,
+            CreateWorkflowLabelRequest,
+            WorkflowLabelResponse,
+            ListWorkflowLabelsResponse,
+            CreateCollectionRequest,
+            CollectionResponse,
+            CollectionExportResponse,
+            CreateAccessionRelationRequest,
+            AccessionRelationResponse,
+            SocialMetadataResponse,
+            ListAccessionRelationsResponse,
+            CommentRequest,
+            CommentResponse,
+            ListCommentsResponse,
+            DryRunAccessionResponse,
+            CreateAccessionCrawlResponse,
+            AccessionCrawlStatusResponse,
+            InitiateUploadRequest,
+            InitiateUploadResponse,
+            PresignUploadRequest,
+            PresignUploadResponse,
+            PresignedPartUrlResponse,
+            UploadPartResponse,
+            ListUploadPartsResponse,
+            CompleteUploadResponse,
+            UploadProgressResponse,
+            PipelineStatusResponse,
+            PublicStatsResponse,
+            ReviewQueueResponse,
+            ListAuditLogResponse,
+            AuditLogEntryResponse,
+            ReviewQueueItemResponse,
+            ReviewQueueItemKind,
+            QuickActionResponse,
+            S3BackfillStatusResponse,
+            BackfillFailureResponse,
+            StaticExportStatusResponse,
+            ReindexStatusResponse,
+            SchedulerStatusResponse,
+            SchedulerLeaderResponse,
+            ScheduledTaskResponse,
+            UserResponse,
+            ListUsersResponse,
+            ImpersonationResponse,
+            CreateOrganizationRequest,
+            RenameOrganizationRequest,
+            OrganizationResponse,
+            ListOrganizationsResponse,
+            PostmarkWebhookRequest,
+            CreateFeatureFlagRequest,
+            UpdateFeatureFlagRequest,
+            FeatureFlagResponse,
+            ListFeatureFlagsResponse,
+            EnabledFeatureFlagsResponse,
+            CreateCrawlBlocklistEntryRequest,
+            CrawlBlocklistEntryResponse,
+            ListCrawlBlocklistResponse,
+            InProgressCrawlResponse,
+            QueuedCrawlResponse,
+            CrawlFailureResponse,
+            AccessionPaginationV2,
+            ListAccessionsV2Response,
+            ListPublicAccessionsV2Response,
+            GetOneAccessionV2Response,
+            GetOnePublicAccessionV2Response,
+            ErrorResponse,
+            LocalizedMessages,
+            MessageResponse
         )
     ),
     tags(
         (name = "Healthcheck", description = "Health check endpoints"),
         (name = "Accessions", description = "Accession management endpoints"),
         (name = "Auth", description = "User authentication endpoints"),
-        (name = "Subjects", description = "Subject management endpoints")
+        (name = "Admin", description = "Archive administration endpoints"),
+        (name = "Organizations", description = "Partner organization management endpoints"),
+        (name = "Feature flags", description = "Feature flag management endpoints"),
+        (name = "Crawl blocklist", description = "Endpoints managing URL patterns that must never be crawled"),
+        (name = "Uploads", description = "File upload endpoints"),
+        (name = "Subjects", description = "Subject management endpoints"),
+        (name = "Workflow labels", description = "Internal workflow label endpoints"),
+        (name = "Collections", description = "Curated collection endpoints"),
+        (name = "Accession relations", description = "Endpoints linking connected accessions"),
+        (name = "Comments", description = "Internal discussion of accessions between curators"),
+        (name = "Webhooks", description = "Webhooks called by third party services"),
+        (name = "Memento", description = "Memento TimeGate and TimeMap endpoints, see RFC 7089"),
+        (name = "Accessions v2", description = "Version 2 accession endpoints"),
+        (name = "Public", description = "Read-only endpoints for embedding archive search, no auth needed")
     ),
-    modifiers(&SecurityAddon),
+    modifiers(&SecurityAddon, &PermissionsAddon),
     servers(
         // Deployed on Digital Ocean spaces which has a HTTP request config that slaps on this sda-api prefix
         (url = "/sda-api", description = "Production deployment with prefix"),
@@ -0,0 +1,626 @@
+//! The permission matrix: who may call each route of the API.
+//!
+//! [`PERMISSIONS`] records who may call each route in one place. [`enforce_permissions`]
+//! refuses everyone else before the handler runs, and [`PermissionsAddon`] publishes the
+//! same matrix in the OpenAPI spec as an `x-permissions` extension on every operation, so
+//! the frontend and integrators can hide actions the current user can't take:
+//!
+//! ```json
+//! "x-permissions": {"access": "researcher", "authenticated": true, "roles": ["admin", "researcher"]}
+//! ```
+//!
+//! `access` is one of the [`Access`] levels in snake case. A test checks every route in the
+//! spec has an entry, so new routes have to be added here. Handlers still make the checks
+//! the matrix can't, such as which organization's records a user may see.
+
+use crate::app_factory::AppState;
+use crate::auth::{validate_at_least_contributor, validate_at_least_researcher};
+use crate::models::auth::AuthenticatedUser;
+use ::entity::sea_orm_active_enums::Role;
+use axum::extract::{FromRequestParts, MatchedPath, Request, State};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde_json::json;
+use utoipa::openapi::extensions::ExtensionsBuilder;
+use utoipa::openapi::path::Operation;
+use utoipa::Modify;
+
+/// Who may call a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Anyone, signed in or not
+    Public,
+    /// Only the third party service calling the webhook, with its own credentials
+    Webhook,
+    /// Any signed in user; the route itself limits what they can see
+    SignedIn,
+    /// Contributors, researchers and admins
+    Contributor,
+    /// Researchers and admins
+    Researcher,
+    /// Admins of any organization, acting on their own organization
+    Admin,
+    /// Admins of the archive's own organization, see [`AuthenticatedUser::is_platform_admin`]
+    PlatformAdmin,
+}
+
+impl Access {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Access::Public => "public",
+            Access::Webhook => "webhook",
+            Access::SignedIn => "signed_in",
+            Access::Contributor => "contributor",
+            Access::Researcher => "researcher",
+            Access::Admin => "admin",
+            Access::PlatformAdmin => "platform_admin",
+        }
+    }
+
+    /// The roles that may call a route, empty if it doesn't take a user's credentials.
+    pub fn roles(&self) -> Vec<Role> {
+        let all = [Role::Admin, Role::Researcher, Role::Contributor];
+        all.into_iter()
+            .filter(|role| match self {
+                Access::Public | Access::Webhook => false,
+                Access::SignedIn => true,
+                Access::Contributor => validate_at_least_contributor(role),
+                Access::Researcher => validate_at_least_researcher(role),
+                Access::Admin | Access::PlatformAdmin => *role == Role::Admin,
+            })
+            .collect()
+    }
+
+    /// Whether a caller may use a route with this access level.
+    pub fn allows(&self, user: Option<&AuthenticatedUser>) -> bool {
+        match (self, user) {
+            (Access::Public, _) => true,
+            (Access::PlatformAdmin, Some(user)) => user.is_platform_admin(),
+            (_, Some(user)) => self.roles().contains(&user.role),
+            (_, None) => false,
+        }
+    }
+}
+
+/// What each route needs, keyed by HTTP method and OpenAPI path.
+pub const PERMISSIONS: &[(&str, &str, Access)] = &[
+    (
+        "GET",
+        "/api/v1/accessions/{accession_id}/relations",
+        Access::Researcher,
+    ),
+    (
+        "POST",
+        "/api/v1/accessions/{accession_id}/relations",
+        Access::Researcher,
+    ),
+    (
+        "DELETE",
+        "/api/v1/accessions/{accession_id}/relations/{relation_id}",
+        Access::Researcher,
+    ),
+    (
+        "GET",
+        "/api/v1/accessions/{accession_id}/comments",
+        Access::Researcher,
+    ),
+    (
+        "POST",
+        "/api/v1/accessions/{accession_id}/comments",
+        Access::Researcher,
+    ),
+    (
+        "PUT",
+        "/api/v1/accessions/{accession_id}/comments/{comment_id}",
+        Access::Researcher,
+    ),
+    (
+        "DELETE",
+        "/api/v1/accessions/{accession_id}/comments/{comment_id}",
+        Access::Researcher,
+    ),
+    ("POST", "/api/v1/collections", Access::Researcher),
+    (
+        "GET",
+        "/api/v1/collections/{collection_id}",
+        Access::Researcher,
+    ),
+    (
+        "PUT",
+        "/api/v1/collections/{collection_id}/accessions/{accession_id}",
+        Access::Researcher,
+    ),
+    (
+        "DELETE",
+        "/api/v1/collections/{collection_id}/accessions/{accession_id}",
+        Access::Researcher,
+    ),
+    (
+        "POST",
+        "/api/v1/collections/{collection_id}/export",
+        Access::Researcher,
+    ),
+    ("POST", "/api/v1/accessions/raw", Access::Contributor),
+    ("POST", "/api/v1/accessions/from-file", Access::Contributor),
+    ("POST", "/api/v1/accessions/crawl", Access::Contributor),
+    (
+        "GET",
+        "/api/v1/accessions/{accession_id}/status",
+        Access::SignedIn,
+    ),
+    ("GET", "/api/v1/accessions/{accession_id}", Access::Public),
+    (
+        "GET",
+        "/api/v1/accessions/{accession_id}/pages",
+        Access::Public,
+    ),
+    (
+        "GET",
+        "/api/v1/accessions/{accession_id}/iiif/manifest.json",
+        Access::Public,
+    ),
+    (
+        "GET",
+        "/api/v1/accessions/private/{accession_id}",
+        Access::Researcher,
+    ),
+    (
+        "GET",
+        "/api/v1/accessions/{accession_id}/provenance",
+        Access::Public,
+    ),
+    (
+        "GET",
+        "/api/v1/accessions/private/{accession_id}/provenance",
+        Access::Researcher,
+    ),
+    (
+        "GET",
+        "/api/v1/accessions/{accession_id}/duplicates",
+        Access::Researcher,
+    ),
+    (
+        "GET",
+        "/api/v1/accessions/private/files/{token}",
+        Access::SignedIn,
+    ),
+    (
+        "GET",
+        "/api/v1/accessions/{accession_id}/stats",
+        Access::Researcher,
+    ),
+    ("GET", "/api/v1/accessions/stats", Access::Researcher),
+    ("GET", "/api/v1/accessions/stream", Access::Public),
+    ("GET", "/api/v1/accessions", Access::Public),
+    ("GET", "/api/v1/accessions/export", Access::Public),
+    ("GET", "/api/v1/accessions/timeline", Access::Public),
+    ("GET", "/api/v1/accessions/private", Access::Researcher),
+    ("GET", "/api/v1/accessions/bad-captures", Access::Researcher),
+    (
+        "DELETE",
+        "/api/v1/accessions/{accession_id}/bad-capture",
+        Access::Researcher,
+    ),
+    ("DELETE", "/api/v1/accessions/{accession_id}", Access::Admin),
+    (
+        "PUT",
+        "/api/v1/accessions/{accession_id}",
+        Access::Researcher,
+    ),
+    (
+        "POST",
+        "/api/v1/accessions/{accession_id}/translate-metadata",
+        Access::Researcher,
+    ),
+    (
+        "POST",
+        "/api/v1/accessions/{accession_id}/doi",
+        Access::Admin,
+    ),
+    (
+        "PUT",
+        "/api/v1/accessions/{accession_id}/file",
+        Access::Admin,
+    ),
+    (
+        "GET",
+        "/api/v1/accessions/{accession_id}/suggested-subjects",
+        Access::Researcher,
+    ),
+    (
+        "PUT",
+        "/api/v1/accessions/{accession_id}/publication-state",
+        Access::Contributor,
+    ),
+    ("POST", "/api/v1/accessions/bulk-visibility", Access::Admin),
+    ("GET", "/api/v1/admin/pipeline", Access::PlatformAdmin),
+    ("GET", "/api/v1/admin/review-queue", Access::PlatformAdmin),
+    ("GET", "/api/v1/admin/audit-log", Access::PlatformAdmin),
+    (
+        "GET",
+        "/api/v1/admin/audit-log/export",
+        Access::PlatformAdmin,
+    ),
+    (
+        "GET",
+        "/api/v1/admin/accessions/{accession_id}/access-log",
+        Access::Admin,
+    ),
+    ("GET", "/api/v1/admin/scheduler", Access::PlatformAdmin),
+    (
+        "GET",
+        "/api/v1/admin/scheduler/leader",
+        Access::PlatformAdmin,
+    ),
+    ("GET", "/api/v1/admin/users", Access::Admin),
+    (
+        "POST",
+        "/api/v1/admin/users/{user_id}/deactivate",
+        Access::Admin,
+    ),
+    ("POST", "/api/v1/admin/impersonate/{user_id}", Access::Admin),
+    ("POST", "/api/v1/admin/backfill-s3", Access::PlatformAdmin),
+    ("GET", "/api/v1/admin/backfill-s3", Access::PlatformAdmin),
+    ("POST", "/api/v1/admin/static-export", Access::PlatformAdmin),
+    ("GET", "/api/v1/admin/static-export", Access::PlatformAdmin),
+    ("POST", "/api/v1/admin/reindex", Access::PlatformAdmin),
+    ("GET", "/api/v1/admin/reindex", Access::PlatformAdmin),
+    ("POST", "/api/v1/auth", Access::Public),
+    ("POST", "/api/v1/auth/authorize", Access::Public),
+    ("GET", "/api/v1/auth", Access::SignedIn),
+    ("PUT", "/api/v1/auth/activity-digest", Access::SignedIn),
+    ("POST", "/api/v1/auth/{user_id}/api-key", Access::Admin),
+    (
+        "POST",
+        "/api/v1/admin/crawl-blocklist",
+        Access::PlatformAdmin,
+    ),
+    (
+        "GET",
+        "/api/v1/admin/crawl-blocklist",
+        Access::PlatformAdmin,
+    ),
+    (
+        "DELETE",
+        "/api/v1/admin/crawl-blocklist/{entry_id}",
+        Access::PlatformAdmin,
+    ),
+    ("GET", "/api/v1/feature-flags", Access::SignedIn),
+    ("POST", "/api/v1/admin/feature-flags", Access::Admin),
+    ("GET", "/api/v1/admin/feature-flags", Access::Admin),
+    ("PUT", "/api/v1/admin/feature-flags/{name}", Access::Admin),
+    (
+        "DELETE",
+        "/api/v1/admin/feature-flags/{name}",
+        Access::Admin,
+    ),
+    ("GET", "/health", Access::Public),
+    ("GET", "/api/v1/timegate/{url}", Access::Public),
+    ("GET", "/api/v1/timemap/link/{url}", Access::Public),
+    ("GET", "/api/v1/admin/organizations", Access::PlatformAdmin),
+    ("POST", "/api/v1/admin/organizations", Access::PlatformAdmin),
+    (
+        "PUT",
+        "/api/v1/admin/organizations/{organization_id}",
+        Access::PlatformAdmin,
+    ),
+    (
+        "PUT",
+        "/api/v1/admin/organizations/{organization_id}/users/{user_id}",
+        Access::PlatformAdmin,
+    ),
+    ("GET", "/public/v1/accessions", Access::Public),
+    (
+        "GET",
+        "/public/v1/accessions/{accession_id}",
+        Access::Public,
+    ),
+    ("GET", "/public/v1/metadata-subjects", Access::Public),
+    ("GET", "/api/v1/public/stats", Access::Public),
+    ("POST", "/api/v1/metadata-subjects", Access::Contributor),
+    ("GET", "/api/v1/metadata-subjects", Access::Public),
+    (
+        "DELETE",
+        "/api/v1/metadata-subjects/{subject_id}",
+        Access::Admin,
+    ),
+    (
+        "POST",
+        "/api/v1/metadata-subjects/{subject_id}/apply",
+        Access::Researcher,
+    ),
+    ("POST", "/api/v1/uploads", Access::Contributor),
+    ("POST", "/api/v1/uploads/presign", Access::Contributor),
+    (
+        "PUT",
+        "/api/v1/uploads/{upload_id}/parts/{part_number}",
+        Access::Contributor,
+    ),
+    ("GET", "/api/v1/uploads/{upload_id}", Access::Contributor),
+    (
+        "POST",
+        "/api/v1/uploads/{upload_id}/complete",
+        Access::Contributor,
+    ),
+    ("DELETE", "/api/v1/uploads/{upload_id}", Access::Contributor),
+    (
+        "GET",
+        "/api/v1/uploads/{upload_id}/progress",
+        Access::Contributor,
+    ),
+    ("GET", "/api/v2/accessions", Access::Public),
+    ("GET", "/api/v2/accessions/private", Access::Researcher),
+    ("GET", "/api/v2/accessions/{accession_id}", Access::Public),
+    (
+        "GET",
+        "/api/v2/accessions/private/{accession_id}",
+        Access::Researcher,
+    ),
+    ("POST", "/api/v1/webhooks/postmark", Access::Webhook),
+    ("POST", "/api/v1/workflow-labels", Access::Researcher),
+    ("GET", "/api/v1/workflow-labels", Access::Researcher),
+    (
+        "DELETE",
+        "/api/v1/workflow-labels/{label_id}",
+        Access::Researcher,
+    ),
+    (
+        "GET",
+        "/api/v1/accessions/{accession_id}/workflow-labels",
+        Access::Researcher,
+    ),
+    (
+        "PUT",
+        "/api/v1/accessions/{accession_id}/workflow-labels/{label_id}",
+        Access::Researcher,
+    ),
+    (
+        "DELETE",
+        "/api/v1/accessions/{accession_id}/workflow-labels/{label_id}",
+        Access::Researcher,
+    ),
+];
+
+/// The access a route needs, or `None` if it isn't in [`PERMISSIONS`].
+pub fn required_access(method: &str, path: &str) -> Option<Access> {
+    PERMISSIONS
+        .iter()
+        .find(|(route_method, route_path, _)| {
+            route_method.eq_ignore_ascii_case(method) && *route_path == path
+        })
+        .map(|(_, _, access)| *access)
+}
+
+/// Routes that tell refused callers more than "Insufficient permissions", keyed like
+/// [`PERMISSIONS`].
+const REFUSALS: &[(&str, &str, &str)] = &[(
+    "POST",
+    "/api/v1/auth/{user_id}/api-key",
+    "Only admins can create API keys",
+)];
+
+/// The message a caller gets when [`enforce_permissions`] refuses them a route.
+fn refusal(method: &str, path: &str) -> &'static str {
+    REFUSALS
+        .iter()
+        .find(|(route_method, route_path, _)| {
+            route_method.eq_ignore_ascii_case(method) && *route_path == path
+        })
+        .map_or("Insufficient permissions", |(_, _, message)| message)
+}
+
+/// Middleware refusing callers [`PERMISSIONS`] doesn't let use the route they matched.
+///
+/// Public and webhook routes, and routes missing from the matrix, are left to their
+/// handlers. The caller is kept in the request's extensions, so handlers extracting
+/// [`AuthenticatedUser`] don't authenticate the request a second time.
+pub async fn enforce_permissions(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(path) = request.extensions().get::<MatchedPath>().cloned() else {
+        return next.run(request).await;
+    };
+    let method = request.method().clone();
+    let access = required_access(method.as_str(), path.as_str());
+    let Some(access) = access.filter(|access| !matches!(access, Access::Public | Access::Webhook))
+    else {
+        return next.run(request).await;
+    };
+    let (mut parts, body) = request.into_parts();
+    let user = match AuthenticatedUser::from_request_parts(&mut parts, &state).await {
+        Ok(user) => user,
+        Err(err) => return err.into_response(),
+    };
+    if !access.allows(Some(&user)) {
+        return (
+            StatusCode::FORBIDDEN,
+            refusal(method.as_str(), path.as_str()),
+        )
+            .into_response();
+    }
+    parts.extensions.insert(user);
+    next.run(Request::from_parts(parts, body)).await
+}
+
+/// Adds the `x-permissions` extension from [`PERMISSIONS`] to every operation in the spec.
+pub struct PermissionsAddon;
+
+impl Modify for PermissionsAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            let operations = [
+                ("GET", &mut item.get),
+                ("PUT", &mut item.put),
+                ("POST", &mut item.post),
+                ("DELETE", &mut item.delete),
+                ("PATCH", &mut item.patch),
+            ];
+            for (method, operation) in operations {
+                if let Some(operation) = operation {
+                    annotate(operation, required_access(method, path));
+                }
+            }
+        }
+    }
+}
+
+fn annotate(operation: &mut Operation, access: Option<Access>) {
+    let Some(access) = access else {
+        return;
+    };
+    let roles: Vec<String> = access
+        .roles()
+        .iter()
+        .map(|role| format!("{role:?}").to_lowercase())
+        .collect();
+    let permissions = json!({
+        "access": access.as_str(),
+        "authenticated": !matches!(access, Access::Public | Access::Webhook),
+        "roles": roles,
+    });
+    let extensions = ExtensionsBuilder::new()
+        .add("x-permissions", permissions)
+        .build();
+    match operation.extensions.as_mut() {
+        Some(existing) => existing.merge(extensions),
+        None => operation.extensions = Some(extensions),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::JWT_KEYS;
+    use crate::models::auth::{AuthMethod, JWTClaims};
+    use crate::open_api_spec::ApiDoc;
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::test_tools::build_test_app;
+    use axum::body::Body;
+    use axum::http::Request;
+    use chrono::Utc;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeSet;
+    use tower::ServiceExt;
+    use utoipa::OpenApi;
+
+    fn spec_routes() -> BTreeSet<(String, String)> {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let mut routes = BTreeSet::new();
+        for (path, item) in spec["paths"].as_object().unwrap() {
+            for method in ["get", "put", "post", "delete", "patch"] {
+                if item.get(method).is_some() {
+                    routes.insert((method.to_uppercase(), path.clone()));
+                }
+            }
+        }
+        routes
+    }
+
+    #[test]
+    fn every_route_has_permissions() {
+        let matrix: BTreeSet<(String, String)> = PERMISSIONS
+            .iter()
+            .map(|(method, path, _)| (method.to_string(), path.to_string()))
+            .collect();
+        assert_eq!(matrix.len(), PERMISSIONS.len(), "Routes listed twice");
+        assert_eq!(spec_routes(), matrix);
+    }
+
+    #[test]
+    fn publishes_permissions_in_the_spec() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        assert_eq!(
+            spec["paths"]["/api/v1/accessions/private"]["get"]["x-permissions"],
+            json!({"access": "researcher", "authenticated": true, "roles": ["admin", "researcher"]})
+        );
+        assert_eq!(
+            spec["paths"]["/api/v1/accessions"]["get"]["x-permissions"],
+            json!({"access": "public", "authenticated": false, "roles": []})
+        );
+    }
+
+    fn user(role: Role, organization_id: i32) -> AuthenticatedUser {
+        AuthenticatedUser {
+            user_id: "someone@example.com".to_string(),
+            expiry: None,
+            role,
+            impersonator: None,
+            organization_id,
+            auth_method: AuthMethod::Cookie,
+        }
+    }
+
+    fn jwt(user: &AuthenticatedUser) -> String {
+        let claims = JWTClaims {
+            sub: user.user_id.clone(),
+            exp: (Utc::now() + chrono::Duration::hours(1)).timestamp() as usize,
+            role: user.role.clone(),
+            impersonator: None,
+            organization_id: user.organization_id,
+        };
+        JWT_KEYS.encode(&claims).unwrap()
+    }
+
+    #[test]
+    fn allows_callers_by_role_and_organization() {
+        let contributor = user(Role::Contributor, DEFAULT_ORGANIZATION_ID);
+        let partner_admin = user(Role::Admin, 2);
+        let platform_admin = user(Role::Admin, DEFAULT_ORGANIZATION_ID);
+
+        assert!(Access::Public.allows(None));
+        assert!(!Access::SignedIn.allows(None));
+        assert!(Access::Contributor.allows(Some(&contributor)));
+        assert!(!Access::Researcher.allows(Some(&contributor)));
+        assert!(Access::Admin.allows(Some(&partner_admin)));
+        assert!(!Access::PlatformAdmin.allows(Some(&partner_admin)));
+        assert!(Access::PlatformAdmin.allows(Some(&platform_admin)));
+        assert!(!Access::Webhook.allows(Some(&platform_admin)));
+    }
+
+    #[tokio::test]
+    async fn routes_refuse_callers_the_matrix_does_not_allow() {
+        let callers = [
+            (None, StatusCode::UNAUTHORIZED),
+            (
+                Some(user(Role::Contributor, DEFAULT_ORGANIZATION_ID)),
+                StatusCode::FORBIDDEN,
+            ),
+            (
+                Some(user(Role::Researcher, DEFAULT_ORGANIZATION_ID)),
+                StatusCode::FORBIDDEN,
+            ),
+            (Some(user(Role::Admin, 2)), StatusCode::FORBIDDEN),
+        ];
+        for (method, path, access) in PERMISSIONS {
+            if matches!(access, Access::Public | Access::Webhook) {
+                continue;
+            }
+            let uri = path
+                .split('/')
+                .map(|part| if part.starts_with('{') { "1" } else { part })
+                .collect::<Vec<_>>()
+                .join("/");
+            for (user, refused_with) in &callers {
+                if access.allows(user.as_ref()) {
+                    continue;
+                }
+                let mut request = Request::builder().method(*method).uri(&uri);
+                if let Some(user) = user {
+                    request = request.header(http::header::COOKIE, format!("jwt={}", jwt(user)));
+                }
+                let response = build_test_app()
+                    .oneshot(request.body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    response.status(),
+                    *refused_with,
+                    "{method} {path} as {user:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! In-memory metrics for the crawl pipeline.
+//!
+//! Crawls are launched on background tasks that poll Browsertrix until the capture is
+//! ready, so nothing about a crawl reaches the database until it has finished. These
+//! metrics track what the background tasks are doing so admins can see the state of the
+//! pipeline without reading logs. They reset whenever the server restarts.
+
+use crate::clock::SharedClock;
+use chrono::NaiveDateTime;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// How many recent failures to keep around for the pipeline dashboard.
+const MAX_RECENT_FAILURES: usize = 20;
+
+/// A crawl that has been launched but not yet finished or failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InProgressCrawl {
+    pub crawl_id: Uuid,
+    pub url: String,
+    pub started_at: NaiveDateTime,
+    pub polls: u32,
+}
+
+/// A crawl that failed, with the reason it failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrawlFailure {
+    pub crawl_id: Option<Uuid>,
+    pub url: String,
+    pub reason: String,
+    pub failed_at: NaiveDateTime,
+}
+
+/// Point in time view of the pipeline metrics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineSnapshot {
+    pub in_progress: Vec<InProgressCrawl>,
+    pub completed_crawls: u64,
+    pub average_crawl_duration_secs: Option<f64>,
+    pub poll_retries: u64,
+    pub recent_failures: Vec<CrawlFailure>,
+}
+
+#[derive(Debug, Default)]
+struct PipelineMetricsInner {
+    in_progress: HashMap<Uuid, InProgressCrawl>,
+    completed_crawls: u64,
+    total_crawl_duration_secs: i64,
+    poll_retries: u64,
+    recent_failures: VecDeque<CrawlFailure>,
+}
+
+impl PipelineMetricsInner {
+    fn push_failure(&mut self, failure: CrawlFailure) {
+        if self.recent_failures.len() == MAX_RECENT_FAILURES {
+            self.recent_failures.pop_back();
+        }
+        self.recent_failures.push_front(failure);
+    }
+}
+
+/// Thread safe recorder for crawl pipeline events.
+#[derive(Debug, Default)]
+pub struct PipelineMetrics {
+    /// Tells the time crawls start and fail at
+    clock: SharedClock,
+    inner: Mutex<PipelineMetricsInner>,
+}
+
+pub type SharedPipelineMetrics = Arc<PipelineMetrics>;
+
+/// Creates empty pipeline metrics to share between services.
+pub fn new_pipeline_metrics(clock: SharedClock) -> SharedPipelineMetrics {
+    Arc::new(PipelineMetrics {
+        clock,
+        inner: Mutex::default(),
+    })
+}
+
+impl PipelineMetrics {
+    /// Records a crawl that has been launched in Browsertrix.
+    pub fn crawl_started(&self, crawl_id: Uuid, url: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.in_progress.insert(
+            crawl_id,
+            InProgressCrawl {
+                crawl_id,
+                url: url.to_string(),
+                started_at: self.clock.naive_now(),
+                polls: 0,
+            },
+        );
+    }
+
+    /// Records a status poll for an in progress crawl.
+    pub fn crawl_polled(&self, crawl_id: Uuid) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(crawl) = inner.in_progress.get_mut(&crawl_id) {
+            crawl.polls += 1;
+        }
+    }
+
+    /// Records a status poll that errored and will be retried.
+    pub fn poll_retried(&self) {
+        self.inner.lock().unwrap().poll_retries += 1;
+    }
+
+    /// Records a crawl that completed and was written to the archive.
+    pub fn crawl_completed(&self, crawl_id: Uuid) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(crawl) = inner.in_progress.remove(&crawl_id) {
+            let duration = self.clock.naive_now() - crawl.started_at;
+            inner.completed_crawls += 1;
+            inner.total_crawl_duration_secs += duration.num_seconds();
+        }
+    }
+
+    /// Records a crawl that failed. `crawl_id` is `None` when Browsertrix never
+    /// accepted the crawl in the first place.
+    pub fn crawl_failed(&self, crawl_id: Option<Uuid>, url: &str, reason: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(crawl_id) = crawl_id {
+            inner.in_progress.remove(&crawl_id);
+        }
+        inner.push_failure(CrawlFailure {
+            crawl_id,
+            url: url.to_string(),
+            reason: reason.to_string(),
+            failed_at: self.clock.naive_now(),
+        });
+    }
+
+    /// Records a crawl as failed if it is still in progress, e.g. once polling gives up.
+    pub fn crawl_abandoned(&self, crawl_id: Uuid, reason: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(crawl) = inner.in_progress.remove(&crawl_id) {
+            inner.push_failure(CrawlFailure {
+                crawl_id: Some(crawl_id),
+                url: crawl.url,
+                reason: reason.to_string(),
+                failed_at: self.clock.naive_now(),
+            });
+        }
+    }
+
+    /// Returns the current state of the pipeline, oldest in progress crawls first.
+    pub fn snapshot(&self) -> PipelineSnapshot {
+        let inner = self.inner.lock().unwrap();
+        let mut in_progress: Vec<InProgressCrawl> = inner.in_progress.values().cloned().collect();
+        in_progress.sort_by_key(|crawl| crawl.started_at);
+        let average_crawl_duration_secs = if inner.completed_crawls == 0 {
+            None
+        } else {
+            Some(inner.total_crawl_duration_secs as f64 / inner.completed_crawls as f64)
+        };
+        PipelineSnapshot {
+            in_progress,
+            completed_crawls: inner.completed_crawls,
+            average_crawl_duration_secs,
+            poll_retries: inner.poll_retries,
+            recent_failures: inner.recent_failures.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::FixedClock;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn tracks_crawl_lifecycle() {
+        let clock = FixedClock::default();
+        let metrics = new_pipeline_metrics(SharedClock::new(clock.clone()));
+        let done = Uuid::new_v4();
+        let running = Uuid::new_v4();
+        metrics.crawl_started(done, "https://example.com/done");
+        metrics.crawl_started(running, "https://example.com/running");
+        metrics.crawl_polled(running);
+        metrics.crawl_polled(running);
+        metrics.poll_retried();
+        clock.advance(chrono::Duration::seconds(90));
+        metrics.crawl_completed(done);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.completed_crawls, 1);
+        assert_eq!(snapshot.poll_retries, 1);
+        assert_eq!(snapshot.average_crawl_duration_secs, Some(90.0));
+        assert_eq!(snapshot.in_progress.len(), 1);
+        assert_eq!(snapshot.in_progress[0].crawl_id, running);
+        assert_eq!(snapshot.in_progress[0].polls, 2);
+    }
+
+    #[test]
+    fn abandoning_only_fails_in_progress_crawls() {
+        let metrics = PipelineMetrics::default();
+        let crawl_id = Uuid::new_v4();
+        metrics.crawl_started(crawl_id, "https://example.com");
+        metrics.crawl_completed(crawl_id);
+        metrics.crawl_abandoned(crawl_id, "Timed out");
+        assert!(metrics.snapshot().recent_failures.is_empty());
+
+        let crawl_id = Uuid::new_v4();
+        metrics.crawl_started(crawl_id, "https://example.com");
+        metrics.crawl_abandoned(crawl_id, "Timed out");
+        let snapshot = metrics.snapshot();
+        assert!(snapshot.in_progress.is_empty());
+        assert_eq!(snapshot.recent_failures[0].reason, "Timed out");
+        assert_eq!(snapshot.recent_failures[0].url, "https://example.com");
+    }
+
+    #[test]
+    fn keeps_most_recent_failures_first() {
+        let metrics = PipelineMetrics::default();
+        for i in 0..MAX_RECENT_FAILURES + 5 {
+            metrics.crawl_failed(None, &format!("https://example.com/{i}"), "Launch failed");
+        }
+        let failures = metrics.snapshot().recent_failures;
+        assert_eq!(failures.len(), MAX_RECENT_FAILURES);
+        assert_eq!(
+            failures[0].url,
+            format!("https://example.com/{}", MAX_RECENT_FAILURES + 4)
+        );
+    }
+}
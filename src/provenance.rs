@@ -0,0 +1,142 @@
+//! Chain of custody signatures for archived files.
+//!
+//! Every file is hashed with SHA-256 as it's stored. When a signing key is configured the
+//! hash is also signed with Ed25519, so anyone with the archive's public key can check that
+//! a file they were handed is the one the archive took in, and when. The signature covers
+//! [`signed_message`], which ties the hash to the accession and the time it was recorded so
+//! it can't be passed off as another accession's.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::NaiveDateTime;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use sha2::{Digest, Sha256};
+
+/// Hash files are identified by
+pub const HASH_ALGORITHM: &str = "sha256";
+
+/// Algorithm hashes are signed with
+pub const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// Signs file hashes with the archive's Ed25519 key.
+#[derive(Debug)]
+pub struct ProvenanceSigner {
+    key_pair: Ed25519KeyPair,
+}
+
+impl ProvenanceSigner {
+    /// # Arguments
+    /// * `seed` - Base64 of the key's 32 byte seed, e.g. from `openssl rand -base64 32`
+    pub fn from_base64_seed(seed: &str) -> Result<Self, String> {
+        let seed = STANDARD
+            .decode(seed.trim())
+            .map_err(|err| format!("PROVENANCE_SIGNING_KEY should be base64: {err}"))?;
+        let key_pair = Ed25519KeyPair::from_seed_unchecked(&seed).map_err(|err| {
+            format!("PROVENANCE_SIGNING_KEY should be a 32 byte Ed25519 seed: {err}")
+        })?;
+        Ok(Self { key_pair })
+    }
+
+    /// Base64 of the raw public key signatures are checked with
+    pub fn public_key(&self) -> String {
+        STANDARD.encode(self.key_pair.public_key().as_ref())
+    }
+
+    /// Base64 of the message's signature
+    pub fn sign(&self, message: &str) -> String {
+        STANDARD.encode(self.key_pair.sign(message.as_bytes()).as_ref())
+    }
+}
+
+/// What gets signed for a file, one field per line.
+///
+/// The time is in whole seconds since that survives being stored and read back.
+pub fn signed_message(accession_id: i32, sha256: &str, recorded_at: NaiveDateTime) -> String {
+    format!(
+        "sudan-digital-archive-provenance-v1\naccession:{accession_id}\n{HASH_ALGORITHM}:{sha256}\nrecorded_at:{}",
+        recorded_at.format("%Y-%m-%dT%H:%M:%SZ")
+    )
+}
+
+/// Hex SHA-256 of a base64 public key's raw bytes, short enough to publish and compare.
+pub fn key_fingerprint(public_key: &str) -> Option<String> {
+    let public_key = STANDARD.decode(public_key).ok()?;
+    Some(format!("{:x}", Sha256::digest(public_key)))
+}
+
+/// Raw bytes of a recorded hex SHA-256, `None` if it isn't one.
+pub fn sha256_bytes(sha256: &str) -> Option<[u8; 32]> {
+    if sha256.len() != 64 || !sha256.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (byte, pair) in bytes.iter_mut().zip(sha256.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// Checks a base64 signature of a message against a base64 public key, the way anyone
+/// holding the archive's key checks what `GET /api/v1/accessions/{id}/provenance` returns.
+/// The API never needs to check its own signatures, so only tests do.
+#[cfg(test)]
+pub fn verify(public_key: &str, message: &str, signature: &str) -> bool {
+    use ring::signature::{UnparsedPublicKey, ED25519};
+
+    let (Ok(public_key), Ok(signature)) = (STANDARD.decode(public_key), STANDARD.decode(signature))
+    else {
+        return false;
+    };
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(message.as_bytes(), &signature)
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    /// RFC 8032 test 1
+    const SEED: &str = "nWGxne/9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A=";
+
+    #[test]
+    fn signs_hashes_verifiably() {
+        let signer = ProvenanceSigner::from_base64_seed(SEED).unwrap();
+        assert_eq!(
+            signer.public_key(),
+            "11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo="
+        );
+        let recorded_at = NaiveDate::from_ymd_opt(2026, 10, 17)
+            .unwrap()
+            .and_hms_micro_opt(9, 30, 0, 123_456)
+            .unwrap();
+        let message = signed_message(7, "abc123", recorded_at);
+        assert_eq!(
+            message,
+            "sudan-digital-archive-provenance-v1\naccession:7\nsha256:abc123\nrecorded_at:2026-10-17T09:30:00Z"
+        );
+        let signature = signer.sign(&message);
+        assert!(verify(&signer.public_key(), &message, &signature));
+        assert!(!verify(
+            &signer.public_key(),
+            &signed_message(8, "abc123", recorded_at),
+            &signature
+        ));
+        assert_eq!(key_fingerprint(&signer.public_key()).unwrap().len(), 64);
+    }
+
+    #[test]
+    fn decodes_hex_hashes() {
+        let sha256 = format!("{:x}", Sha256::digest(b"test"));
+        assert_eq!(sha256_bytes(&sha256), Some(Sha256::digest(b"test").into()));
+        assert_eq!(sha256_bytes("abc123"), None);
+        assert_eq!(sha256_bytes(&"zz".repeat(32)), None);
+    }
+
+    #[test]
+    fn rejects_keys_that_are_not_seeds() {
+        assert!(ProvenanceSigner::from_base64_seed("not base64!").is_err());
+        assert!(ProvenanceSigner::from_base64_seed("c2hvcnQ=").is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! Live feed of accessions as they become public.
+//!
+//! Services publish an accession here once it is created public, moved to the published
+//! state or has its embargo lifted, and every open `/accessions/stream` connection
+//! gets a copy. The feed only lives in memory, so each instance only sees what it
+//! published itself and subscribers get nothing from before they connected.
+
+use ::entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
+use futures::stream::{self, Stream};
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// How many accessions a slow subscriber can fall behind before it starts missing some.
+const FEED_CAPACITY: usize = 64;
+
+/// Shared sender for newly published accessions.
+#[derive(Debug, Clone)]
+pub struct PublicationFeed {
+    sender: broadcast::Sender<AccessionWithMetadataModel>,
+}
+
+impl Default for PublicationFeed {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(FEED_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl PublicationFeed {
+    /// Sends a newly published accession to every subscriber.
+    ///
+    /// Private accessions are dropped, so callers don't have to check before publishing.
+    pub fn publish(&self, accession: AccessionWithMetadataModel) {
+        if accession.is_private {
+            return;
+        }
+        // errors only mean nobody is listening right now
+        let _ = self.sender.send(accession);
+    }
+
+    /// Subscribes to accessions published from now on.
+    ///
+    /// Subscribers that fall too far behind skip the accessions they missed rather than
+    /// being disconnected.
+    pub fn subscribe(&self) -> impl Stream<Item = AccessionWithMetadataModel> {
+        stream::unfold(self.sender.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(accession) => return Some((accession, receiver)),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(
+                            "Publication feed subscriber fell behind, skipped {skipped} accessions"
+                        );
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::mock_one_accession_with_metadata;
+    use futures::StreamExt;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn sends_public_accessions_to_subscribers() {
+        let feed = PublicationFeed::default();
+        let mut accessions = Box::pin(feed.subscribe());
+        feed.publish(AccessionWithMetadataModel {
+            id: 1,
+            is_private: true,
+            ..mock_one_accession_with_metadata()
+        });
+        feed.publish(AccessionWithMetadataModel {
+            id: 2,
+            is_private: false,
+            ..mock_one_accession_with_metadata()
+        });
+
+        assert_eq!(accessions.next().await.map(|a| a.id), Some(2));
+    }
+
+    #[tokio::test]
+    async fn skips_what_slow_subscribers_missed() {
+        let feed = PublicationFeed::default();
+        let mut accessions = Box::pin(feed.subscribe());
+        for id in 0..(FEED_CAPACITY as i32 + 5) {
+            feed.publish(AccessionWithMetadataModel {
+                id,
+                is_private: false,
+                ..mock_one_accession_with_metadata()
+            });
+        }
+
+        assert_eq!(accessions.next().await.map(|a| a.id), Some(5));
+    }
+}
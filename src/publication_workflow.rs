@@ -0,0 +1,144 @@
+//! Lifecycle of an accession from draft to published.
+//!
+//! Accessions move between [`PublicationState`]s through explicit transitions rather than by
+//! flipping a privacy flag, so every move can be checked against the caller's role:
+//! contributors submit drafts for review, researchers decide what gets published or
+//! withdrawn, and only admins can put a withdrawn accession back up.
+
+use crate::auth::{validate_at_least_contributor, validate_at_least_researcher};
+use crate::models::common::TargetVisibility;
+use ::entity::sea_orm_active_enums::{PublicationState, Role};
+
+/// Why an accession can't be moved to a state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransitionError {
+    /// No role can make this move, e.g. from draft straight to withdrawn
+    NotAllowed,
+    /// The move exists but the caller's role can't make it
+    InsufficientRole,
+}
+
+/// State an accession starts in when it is created.
+///
+/// Creation requests still say whether the accession should be private, which puts it in
+/// draft, otherwise it is published straight away.
+pub fn initial_state(is_private: bool) -> PublicationState {
+    if is_private {
+        PublicationState::Draft
+    } else {
+        PublicationState::Published
+    }
+}
+
+/// Checks whether a user with `role` may move an accession from `from` to `to`.
+pub fn check_transition(
+    from: &PublicationState,
+    to: &PublicationState,
+    role: &Role,
+) -> Result<(), TransitionError> {
+    use PublicationState::*;
+    let role_allowed = match (from, to) {
+        (Draft, InReview) => validate_at_least_contributor(role),
+        (InReview, Draft)
+        | (Draft, Published)
+        | (InReview, Published)
+        | (Published, Withdrawn)
+        | (Withdrawn, Draft) => validate_at_least_researcher(role),
+        (Withdrawn, Published) => *role == Role::Admin,
+        _ => return Err(TransitionError::NotAllowed),
+    };
+    if role_allowed {
+        Ok(())
+    } else {
+        Err(TransitionError::InsufficientRole)
+    }
+}
+
+/// States a bulk visibility change moves accessions out of, and the state it moves them to.
+///
+/// Making accessions private withdraws the published ones, making them public publishes
+/// everything else. Only admins make bulk changes, and they can make all of these moves.
+pub fn bulk_visibility_transition(
+    visibility: TargetVisibility,
+) -> (Vec<PublicationState>, PublicationState) {
+    match visibility {
+        TargetVisibility::Private => (
+            vec![PublicationState::Published],
+            PublicationState::Withdrawn,
+        ),
+        TargetVisibility::Public => (
+            vec![
+                PublicationState::Draft,
+                PublicationState::InReview,
+                PublicationState::Withdrawn,
+            ],
+            PublicationState::Published,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn contributors_can_only_submit_for_review() {
+        let role = Role::Contributor;
+        assert_eq!(
+            check_transition(&PublicationState::Draft, &PublicationState::InReview, &role),
+            Ok(())
+        );
+        assert_eq!(
+            check_transition(
+                &PublicationState::InReview,
+                &PublicationState::Published,
+                &role
+            ),
+            Err(TransitionError::InsufficientRole)
+        );
+        assert_eq!(
+            check_transition(
+                &PublicationState::Published,
+                &PublicationState::Withdrawn,
+                &role
+            ),
+            Err(TransitionError::InsufficientRole)
+        );
+    }
+
+    #[test]
+    fn only_admins_republish_withdrawn_accessions() {
+        let (from, to) = (PublicationState::Withdrawn, PublicationState::Published);
+        assert_eq!(
+            check_transition(&from, &to, &Role::Researcher),
+            Err(TransitionError::InsufficientRole)
+        );
+        assert_eq!(check_transition(&from, &to, &Role::Admin), Ok(()));
+    }
+
+    #[test]
+    fn rejects_transitions_outside_the_workflow() {
+        for (from, to) in [
+            (PublicationState::Draft, PublicationState::Draft),
+            (PublicationState::Draft, PublicationState::Withdrawn),
+            (PublicationState::Published, PublicationState::InReview),
+            (PublicationState::Withdrawn, PublicationState::InReview),
+        ] {
+            assert_eq!(
+                check_transition(&from, &to, &Role::Admin),
+                Err(TransitionError::NotAllowed)
+            );
+        }
+    }
+
+    #[test]
+    fn bulk_visibility_changes_follow_the_workflow() {
+        for visibility in [TargetVisibility::Public, TargetVisibility::Private] {
+            let (from_states, to) = bulk_visibility_transition(visibility);
+            for from in from_states {
+                assert_eq!(check_transition(&from, &to, &Role::Admin), Ok(()));
+            }
+        }
+    }
+}
@@ -0,0 +1,334 @@
+//! Quick captures of single static pages, without a crawler.
+//!
+//! Crawling a page starts a browser, runs its behaviours and waits out the post load delay,
+//! which takes minutes even for a plain HTML article or a PDF. With `capture_mode` set to
+//! `quick`, the page is instead fetched straight from the API and written into a WARC holding
+//! the request and response, which replays the same way a crawler's capture does. Pages that
+//! only render with JavaScript would replay blank, so [`needs_browser`] picks those out and
+//! they are crawled as usual, see [`crate::repos::quick_capture_repo`].
+
+use bytes::Bytes;
+use chrono::{DateTime, SecondsFormat, Utc};
+use http::header::{CONTENT_TYPE, TRANSFER_ENCODING};
+use http::{HeaderMap, StatusCode};
+use reqwest::Url;
+use std::net::IpAddr;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How long fetching a page may take before it's left to the crawler
+pub const QUICK_CAPTURE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Pages larger than this are left to the crawler
+pub const MAX_QUICK_CAPTURE_SIZE: usize = 20 * 1024 * 1024;
+/// Sent with quick capture requests so site owners can tell who is archiving them
+pub const QUICK_CAPTURE_USER_AGENT: &str = concat!(
+    "Mozilla/5.0 (compatible; SudanDigitalArchive/",
+    env!("CARGO_PKG_VERSION"),
+    "; +https://sudandigitalarchive.com)"
+);
+/// HTML pages with scripts and fewer visible characters than this are assumed to be rendered
+/// by JavaScript
+const MIN_STATIC_TEXT_LENGTH: usize = 200;
+/// Elements whose contents aren't visible text
+const INVISIBLE_ELEMENTS: [&str; 4] = ["script", "style", "noscript", "template"];
+
+/// A page as fetched, before it is written to a WARC.
+#[derive(Debug, Clone)]
+pub struct FetchedPage {
+    pub url: Url,
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Whether a page has to be crawled in a browser to be captured properly.
+///
+/// Anything other than a successful response goes to the crawler, including redirects, which
+/// the crawler follows and records every hop of. Successful responses that aren't HTML are
+/// captured as they are. HTML needs a browser when it has scripts but next to no text, the
+/// shape of a page a JavaScript app fills in.
+pub fn needs_browser(page: &FetchedPage) -> bool {
+    if !page.status.is_success() {
+        return true;
+    }
+    let content_type = page
+        .headers
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if !(content_type.starts_with("text/html") || content_type.starts_with("application/xhtml")) {
+        return false;
+    }
+    let html = String::from_utf8_lossy(&page.body).to_ascii_lowercase();
+    html.contains("<script") && visible_text_length(&html) < MIN_STATIC_TEXT_LENGTH
+}
+
+/// Counts the characters of lowercased HTML a reader would see, leaving out tags, the
+/// contents of [`INVISIBLE_ELEMENTS`] and whitespace.
+fn visible_text_length(html: &str) -> usize {
+    let count = |text: &str| text.chars().filter(|c| !c.is_whitespace()).count();
+    let mut length = 0;
+    let mut rest = html;
+    while let Some(tag_start) = rest.find('<') {
+        length += count(&rest[..tag_start]);
+        rest = &rest[tag_start..];
+        let tag_end = match INVISIBLE_ELEMENTS
+            .iter()
+            .find(|element| rest[1..].starts_with(*element))
+        {
+            Some(element) => rest.find(&format!("</{element}")).and_then(|close| {
+                rest[close..]
+                    .find('>')
+                    .map(|close_end| close + close_end + 1)
+            }),
+            None => rest.find('>').map(|end| end + 1),
+        };
+        match tag_end {
+            Some(tag_end) => rest = &rest[tag_end..],
+            // an unclosed tag, nothing after it is visible
+            None => return length,
+        }
+    }
+    length + count(rest)
+}
+
+/// Whether an address is out on the internet, so a page there can be fetched from the API.
+///
+/// The API runs next to the database, the crawler and the cloud metadata service at
+/// 169.254.169.254, so quick captures must not reach loopback, private, link-local or other
+/// special purpose addresses. IPv6 addresses mapping IPv4 ones are judged by the IPv4 address.
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                // "this network", carrier grade NAT, benchmarking and reserved ranges
+                || first == 0
+                || (first == 100 && (64..128).contains(&second))
+                || (first == 198 && (18..20).contains(&second))
+                || first >= 240)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_address(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_multicast()
+                    // unique local and link-local
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Writes a WARC 1.1 file holding a `warcinfo` record and the page's request and response.
+pub fn write_warc(page: &FetchedPage) -> Vec<u8> {
+    let date = page.fetched_at.to_rfc3339_opts(SecondsFormat::Secs, true);
+    let request_id = record_id();
+    let response_id = record_id();
+    let mut warc = Vec::new();
+    let info = format!(
+        "software: sudan-digital-archive-api/{}\r\nformat: WARC File Format 1.1\r\n",
+        env!("CARGO_PKG_VERSION")
+    );
+    write_record(
+        &mut warc,
+        &[
+            ("WARC-Type", "warcinfo".to_string()),
+            ("WARC-Record-ID", record_id()),
+            ("WARC-Date", date.clone()),
+            ("Content-Type", "application/warc-fields".to_string()),
+        ],
+        info.as_bytes(),
+    );
+    write_record(
+        &mut warc,
+        &[
+            ("WARC-Type", "request".to_string()),
+            ("WARC-Record-ID", request_id.clone()),
+            ("WARC-Date", date.clone()),
+            ("WARC-Target-URI", page.url.to_string()),
+            ("WARC-Concurrent-To", response_id.clone()),
+            (
+                "Content-Type",
+                "application/http;msgtype=request".to_string(),
+            ),
+        ],
+        &http_request(&page.url),
+    );
+    write_record(
+        &mut warc,
+        &[
+            ("WARC-Type", "response".to_string()),
+            ("WARC-Record-ID", response_id),
+            ("WARC-Date", date),
+            ("WARC-Target-URI", page.url.to_string()),
+            ("WARC-Concurrent-To", request_id),
+            (
+                "Content-Type",
+                "application/http;msgtype=response".to_string(),
+            ),
+        ],
+        &http_response(page),
+    );
+    warc
+}
+
+fn record_id() -> String {
+    format!("<urn:uuid:{}>", Uuid::new_v4())
+}
+
+fn write_record(warc: &mut Vec<u8>, headers: &[(&str, String)], block: &[u8]) {
+    warc.extend_from_slice(b"WARC/1.1\r\n");
+    for (name, value) in headers {
+        warc.extend_from_slice(format!("{name}: {value}\r\n").as_bytes());
+    }
+    warc.extend_from_slice(format!("Content-Length: {}\r\n\r\n", block.len()).as_bytes());
+    warc.extend_from_slice(block);
+    warc.extend_from_slice(b"\r\n\r\n");
+}
+
+/// The request the page was fetched with, as it went over the wire.
+fn http_request(url: &Url) -> Vec<u8> {
+    let path = match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    };
+    let host = match url.port() {
+        Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    format!("GET {path} HTTP/1.1\r\nHost: {host}\r\nUser-Agent: {QUICK_CAPTURE_USER_AGENT}\r\n\r\n")
+        .into_bytes()
+}
+
+/// The response the page came in. The body is stored decoded from any chunking, so
+/// `Transfer-Encoding` is left out for replay tools not to try to undo it again.
+fn http_response(page: &FetchedPage) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 {} {}\r\n",
+        page.status.as_u16(),
+        page.status.canonical_reason().unwrap_or_default()
+    )
+    .into_bytes();
+    for (name, value) in &page.headers {
+        if *name == TRANSFER_ENCODING {
+            continue;
+        }
+        response.extend_from_slice(name.as_str().as_bytes());
+        response.extend_from_slice(b": ");
+        response.extend_from_slice(value.as_bytes());
+        response.extend_from_slice(b"\r\n");
+    }
+    response.extend_from_slice(b"\r\n");
+    response.extend_from_slice(&page.body);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_type::is_warc;
+    use http::HeaderValue;
+    use pretty_assertions::assert_eq;
+
+    fn page(status: StatusCode, content_type: &str, body: &str) -> FetchedPage {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_str(content_type).unwrap());
+        headers.insert(TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+        FetchedPage {
+            url: Url::parse("https://example.com/news/story?id=4").unwrap(),
+            status,
+            headers,
+            body: Bytes::from(body.to_string()),
+            fetched_at: DateTime::from_timestamp(1_730_000_000, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn only_fetches_public_addresses() {
+        for ip in ["93.184.215.14", "2606:2800:21f:cb07:6820:80da:af6b:8b2c"] {
+            assert!(is_public_address(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "127.0.0.1",
+            "169.254.169.254",
+            "10.0.0.5",
+            "172.16.3.4",
+            "192.168.1.1",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_address(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn counts_only_visible_text() {
+        let html = "<html><head><style>p { color: red }</style><script>let a = 1 < 2;</script>\
+            </head><body><p>Burnt market</p><noscript>Enable JavaScript</noscript></body></html>";
+        assert_eq!(visible_text_length(html), "Burntmarket".len());
+        assert_eq!(visible_text_length("<p>unclosed <b"), "unclosed".len());
+    }
+
+    #[test]
+    fn sends_javascript_apps_and_failures_to_the_crawler() {
+        let article = format!(
+            "<html><script src=\"/analytics.js\"></script><p>{}</p></html>",
+            "Residents of El Fasher describe the siege. ".repeat(10)
+        );
+        assert!(!needs_browser(&page(
+            StatusCode::OK,
+            "text/html; charset=utf-8",
+            &article
+        )));
+        let app_shell = "<html><body><div id=\"root\"></div><script src=\"/app.js\"></script>\
+            <noscript>You need to enable JavaScript to run this app.</noscript></body></html>";
+        assert!(needs_browser(&page(StatusCode::OK, "text/html", app_shell)));
+        assert!(!needs_browser(&page(
+            StatusCode::OK,
+            "application/pdf",
+            "%PDF-1.7"
+        )));
+        assert!(needs_browser(&page(
+            StatusCode::FOUND,
+            "text/html",
+            &article
+        )));
+        assert!(needs_browser(&page(
+            StatusCode::FORBIDDEN,
+            "text/html",
+            &article
+        )));
+    }
+
+    #[test]
+    fn writes_the_request_and_response_to_a_warc() {
+        let warc = write_warc(&page(StatusCode::OK, "text/html", "<p>Hello</p>"));
+        assert!(is_warc(&warc));
+        let warc = String::from_utf8(warc).unwrap();
+        assert_eq!(warc.matches("WARC/1.1\r\n").count(), 3);
+        assert!(warc.contains("WARC-Target-URI: https://example.com/news/story?id=4\r\n"));
+        assert!(warc.contains("WARC-Date: 2024-10-27T03:33:20Z\r\n"));
+        assert!(warc.contains("GET /news/story?id=4 HTTP/1.1\r\nHost: example.com\r\n"));
+        let response = "HTTP/1.1 200 OK\r\ncontent-type: text/html\r\n\r\n<p>Hello</p>";
+        assert!(warc.contains(&format!(
+            "Content-Length: {}\r\n\r\n{response}\r\n\r\n",
+            response.len()
+        )));
+    }
+}
@@ -0,0 +1,116 @@
+//! Progress of rebuilding the full text search columns and indexes.
+//!
+//! The `full_text_en` and `full_text_ar` columns of accessions are stored generated columns,
+//! which Postgres only recomputes when their row is written. After bulk imports, or changes
+//! to the text search configuration or collation, an admin can start a reindex that rewrites
+//! every accession's row a batch at a time, so no lock is held for long, and then rebuilds the
+//! GIN indexes concurrently. This tracks how far it has got so admins can check on it. Only
+//! one reindex runs at a time and progress resets whenever the server restarts.
+
+use chrono::{NaiveDateTime, Utc};
+use std::sync::{Arc, Mutex};
+
+/// Accessions refreshed per statement
+pub const REINDEX_BATCH_SIZE: u64 = 500;
+
+/// Point in time view of the latest reindex.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReindexSnapshot {
+    pub running: bool,
+    /// Accessions there were when the reindex started
+    pub total: u64,
+    pub refreshed: u64,
+    /// Whether the indexes have been rebuilt, which happens once every row is refreshed
+    pub indexes_rebuilt: bool,
+    /// Why the reindex stopped early, if it did
+    pub error: Option<String>,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+/// Thread safe tracker for the reindex, shared between requests.
+#[derive(Debug, Clone, Default)]
+pub struct ReindexProgress {
+    inner: Arc<Mutex<ReindexSnapshot>>,
+}
+
+impl ReindexProgress {
+    /// Marks a reindex as started, unless one is already running.
+    ///
+    /// # Returns
+    /// Whether the caller should go ahead with the reindex
+    pub fn try_start(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.running {
+            return false;
+        }
+        *inner = ReindexSnapshot {
+            running: true,
+            started_at: Some(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+        true
+    }
+
+    /// Records how many accessions the running reindex has to refresh.
+    pub fn set_total(&self, total: u64) {
+        self.inner.lock().unwrap().total = total;
+    }
+
+    /// Records a batch of accessions whose columns were recomputed.
+    pub fn refreshed(&self, accessions: usize) {
+        self.inner.lock().unwrap().refreshed += accessions as u64;
+    }
+
+    /// Records that the indexes have been rebuilt.
+    pub fn indexes_rebuilt(&self) {
+        self.inner.lock().unwrap().indexes_rebuilt = true;
+    }
+
+    /// Records why the running reindex stopped early.
+    pub fn failed(&self, reason: &str) {
+        self.inner.lock().unwrap().error = Some(reason.to_string());
+    }
+
+    /// Marks the running reindex as finished.
+    pub fn finish(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.running = false;
+        inner.finished_at = Some(Utc::now().naive_utc());
+    }
+
+    /// Returns the progress of the running or most recent reindex.
+    pub fn snapshot(&self) -> ReindexSnapshot {
+        self.inner.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn tracks_one_reindex_at_a_time() {
+        let progress = ReindexProgress::default();
+        assert!(progress.try_start());
+        assert!(!progress.try_start());
+        progress.set_total(3);
+        progress.refreshed(2);
+        progress.refreshed(1);
+        progress.indexes_rebuilt();
+        progress.finish();
+
+        let snapshot = progress.snapshot();
+        assert!(!snapshot.running);
+        assert_eq!((snapshot.total, snapshot.refreshed), (3, 3));
+        assert!(snapshot.indexes_rebuilt);
+        assert!(snapshot.finished_at.is_some());
+
+        assert!(progress.try_start());
+        let snapshot = progress.snapshot();
+        assert_eq!((snapshot.total, snapshot.refreshed), (0, 0));
+        assert!(!snapshot.indexes_rebuilt);
+        assert_eq!(snapshot.finished_at, None);
+    }
+}
@@ -0,0 +1,327 @@
+//! Repository module for accession usage events.
+//!
+//! Events record that an accession was viewed or had a WACZ URL issued for it, so
+//! curators can see what material is being used. The only thing kept about the person
+//! asking is a coarse country code.
+
+use crate::clock::SharedClock;
+use ::entity::accession_event::ActiveModel as AccessionEventActiveModel;
+use ::entity::accession_event::Entity as AccessionEvent;
+use ::entity::accession_event::Model as AccessionEventModel;
+use ::entity::sea_orm_active_enums::AccessionEventKind;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use entity::accession_event;
+use sea_orm::sea_query::{Expr, Order};
+use sea_orm::{
+    ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, FromQueryResult, QueryFilter,
+    QueryOrder, QuerySelect,
+};
+
+/// Repository implementation for database operations on accession events.
+#[derive(Debug, Clone, Default)]
+pub struct DBAccessionEventsRepo {
+    pub db_session: DatabaseConnection,
+    /// Tells the time events happen at
+    pub clock: SharedClock,
+}
+
+/// Number of events of one kind from one country.
+#[derive(Debug, Clone, PartialEq, Eq, FromQueryResult)]
+pub struct EventCount {
+    pub kind: AccessionEventKind,
+    pub country: Option<String>,
+    pub count: i64,
+}
+
+/// Number of events of one kind for one accession.
+#[derive(Debug, Clone, PartialEq, Eq, FromQueryResult)]
+pub struct AccessionEventTotal {
+    pub accession_id: i32,
+    pub count: i64,
+}
+
+/// Defines the interface for accession event database operations.
+#[async_trait]
+pub trait AccessionEventsRepo: Send + Sync {
+    /// Records events for an accession.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    /// * `kinds` - What happened, one event is written per kind
+    /// * `country` - ISO 3166 alpha-2 code of where the request came from, if known
+    async fn record(
+        &self,
+        accession_id: i32,
+        kinds: Vec<AccessionEventKind>,
+        country: Option<String>,
+    ) -> Result<(), DbErr>;
+
+    /// Lists an accession's events, most recent first.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    /// * `limit` - The most events to list
+    async fn list_for_accession(
+        &self,
+        accession_id: i32,
+        limit: u64,
+    ) -> Result<Vec<AccessionEventModel>, DbErr>;
+
+    /// Counts an accession's events by kind and country.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    /// * `since` - Only count events after this time, or all of them if None
+    async fn count_for_accession(
+        &self,
+        accession_id: i32,
+        since: Option<NaiveDateTime>,
+    ) -> Result<Vec<EventCount>, DbErr>;
+
+    /// Finds the accessions with the most events of a kind across the archive.
+    ///
+    /// # Arguments
+    /// * `kind` - The kind of event to count
+    /// * `since` - Only count events after this time, or all of them if None
+    /// * `limit` - How many accessions to return
+    ///
+    /// # Returns
+    /// Accessions ordered by most events first
+    async fn top_accessions(
+        &self,
+        kind: AccessionEventKind,
+        since: Option<NaiveDateTime>,
+        limit: u64,
+    ) -> Result<Vec<AccessionEventTotal>, DbErr>;
+}
+
+#[async_trait]
+impl AccessionEventsRepo for DBAccessionEventsRepo {
+    async fn record(
+        &self,
+        accession_id: i32,
+        kinds: Vec<AccessionEventKind>,
+        country: Option<String>,
+    ) -> Result<(), DbErr> {
+        let created_at = self.clock.naive_now();
+        let events = kinds.into_iter().map(|kind| AccessionEventActiveModel {
+            id: Default::default(),
+            accession_id: ActiveValue::Set(accession_id),
+            kind: ActiveValue::Set(kind),
+            country: ActiveValue::Set(country.clone()),
+            created_at: ActiveValue::Set(created_at),
+        });
+        AccessionEvent::insert_many(events)
+            .exec_without_returning(&self.db_session)
+            .await?;
+        Ok(())
+    }
+
+    async fn list_for_accession(
+        &self,
+        accession_id: i32,
+        limit: u64,
+    ) -> Result<Vec<AccessionEventModel>, DbErr> {
+        AccessionEvent::find()
+            .filter(accession_event::Column::AccessionId.eq(accession_id))
+            .order_by_desc(accession_event::Column::CreatedAt)
+            .order_by_desc(accession_event::Column::Id)
+            .limit(limit)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn count_for_accession(
+        &self,
+        accession_id: i32,
+        since: Option<NaiveDateTime>,
+    ) -> Result<Vec<EventCount>, DbErr> {
+        let mut query = AccessionEvent::find()
+            .select_only()
+            .column(accession_event::Column::Kind)
+            .column(accession_event::Column::Country)
+            .column_as(accession_event::Column::Id.count(), "count")
+            .filter(accession_event::Column::AccessionId.eq(accession_id));
+        if let Some(since) = since {
+            query = query.filter(accession_event::Column::CreatedAt.gte(since));
+        }
+        query
+            .group_by(accession_event::Column::Kind)
+            .group_by(accession_event::Column::Country)
+            .order_by_asc(accession_event::Column::Kind)
+            .order_by_asc(accession_event::Column::Country)
+            .into_model::<EventCount>()
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn top_accessions(
+        &self,
+        kind: AccessionEventKind,
+        since: Option<NaiveDateTime>,
+        limit: u64,
+    ) -> Result<Vec<AccessionEventTotal>, DbErr> {
+        let mut query = AccessionEvent::find()
+            .select_only()
+            .column(accession_event::Column::AccessionId)
+            .column_as(accession_event::Column::Id.count(), "count")
+            .filter(accession_event::Column::Kind.eq(kind));
+        if let Some(since) = since {
+            query = query.filter(accession_event::Column::CreatedAt.gte(since));
+        }
+        query
+            .group_by(accession_event::Column::AccessionId)
+            .order_by(Expr::col(accession_event::Column::Id).count(), Order::Desc)
+            .order_by_asc(accession_event::Column::AccessionId)
+            .limit(limit)
+            .into_model::<AccessionEventTotal>()
+            .all(&self.db_session)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::common::{MetadataLanguage, MetadataScrubbing};
+    use crate::models::request::{CreateAccessionRequestRaw, CreateSubjectRequest};
+    use crate::repos::accessions_repo::{AccessionsRepo, DBAccessionsRepo};
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::repos::subjects_repo::{DBSubjectsRepo, SubjectsRepo};
+    use crate::test_db::migrated_test_db;
+    use chrono::Utc;
+    use entity::sea_orm_active_enums::{DublinMetadataFormat, ScanStatus};
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    async fn write_accession(accessions_repo: &DBAccessionsRepo) -> i32 {
+        let subjects_repo = DBSubjectsRepo {
+            db_session: accessions_repo.db_session.clone(),
+            ..Default::default()
+        };
+        let subject = subjects_repo
+            .write_one(
+                CreateSubjectRequest {
+                    metadata_subject: Uuid::new_v4().to_string(),
+                    lang: MetadataLanguage::English,
+                },
+                DEFAULT_ORGANIZATION_ID,
+                None,
+            )
+            .await
+            .unwrap();
+        accessions_repo
+            .write_one_raw(
+                CreateAccessionRequestRaw {
+                    metadata_language: MetadataLanguage::English,
+                    metadata_title: "Market fire".to_string(),
+                    metadata_description: None,
+                    metadata_time: Default::default(),
+                    metadata_subjects: vec![subject.id],
+                    is_private: false,
+                    embargo_until: None,
+                    content_warning: None,
+                    metadata_format: DublinMetadataFormat::Jpeg,
+                    original_url: "https://example.com".to_string(),
+                    s3_filename: "file.jpg".to_string(),
+                    metadata_scrubbing: MetadataScrubbing::Scrub,
+                },
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn counts_and_ranks_events() {
+        let db = migrated_test_db().await;
+        let db_session = db.clone();
+        let accessions_repo = DBAccessionsRepo {
+            db_session: db_session.clone(),
+            ..Default::default()
+        };
+        let repo = DBAccessionEventsRepo {
+            db_session,
+            ..Default::default()
+        };
+        let popular = write_accession(&accessions_repo).await;
+        let quiet = write_accession(&accessions_repo).await;
+        let both = vec![AccessionEventKind::View, AccessionEventKind::WaczUrl];
+        repo.record(popular, both.clone(), Some("SD".to_string()))
+            .await
+            .unwrap();
+        repo.record(popular, both, Some("SD".to_string()))
+            .await
+            .unwrap();
+        repo.record(popular, vec![AccessionEventKind::View], None)
+            .await
+            .unwrap();
+        repo.record(
+            quiet,
+            vec![AccessionEventKind::View],
+            Some("EG".to_string()),
+        )
+        .await
+        .unwrap();
+
+        let counts = repo.count_for_accession(popular, None).await.unwrap();
+        assert_eq!(
+            counts,
+            vec![
+                EventCount {
+                    kind: AccessionEventKind::View,
+                    country: Some("SD".to_string()),
+                    count: 2,
+                },
+                EventCount {
+                    kind: AccessionEventKind::View,
+                    country: None,
+                    count: 1,
+                },
+                EventCount {
+                    kind: AccessionEventKind::WaczUrl,
+                    country: Some("SD".to_string()),
+                    count: 2,
+                },
+            ]
+        );
+        let listed = repo.list_for_accession(quiet, 10).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].country.as_deref(), Some("EG"));
+        assert_eq!(repo.list_for_accession(popular, 2).await.unwrap().len(), 2);
+
+        let tomorrow = Utc::now().naive_utc() + chrono::Duration::days(1);
+        assert!(repo
+            .count_for_accession(popular, Some(tomorrow))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let top = repo
+            .top_accessions(AccessionEventKind::View, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            top,
+            vec![
+                AccessionEventTotal {
+                    accession_id: popular,
+                    count: 3,
+                },
+                AccessionEventTotal {
+                    accession_id: quiet,
+                    count: 1,
+                },
+            ]
+        );
+        let top = repo
+            .top_accessions(AccessionEventKind::WaczUrl, None, 1)
+            .await
+            .unwrap();
+        assert_eq!(top.len(), 1);
+    }
+}
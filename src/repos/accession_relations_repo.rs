@@ -0,0 +1,263 @@
+//! Repository module for relations between accessions.
+//!
+//! Relations link connected captures, such as the posts of a testimony thread or an
+//! article and its follow up, so researchers can move between them. Each relation is read
+//! as "from <kind> to", e.g. a post is part of its thread.
+
+use crate::clock::SharedClock;
+use crate::models::common::RelationDirection;
+use ::entity::accession_relation::ActiveModel as AccessionRelationActiveModel;
+use ::entity::accession_relation::Entity as AccessionRelation;
+use ::entity::accession_relation::Model as AccessionRelationModel;
+use ::entity::accessions_with_metadata::Entity as AccessionWithMetadata;
+use ::entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
+use ::entity::sea_orm_active_enums::AccessionRelationKind;
+use async_trait::async_trait;
+use entity::{accession_relation, accessions_with_metadata};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder,
+};
+use std::collections::HashMap;
+
+/// Repository implementation for database operations on accession relations.
+#[derive(Debug, Clone, Default)]
+pub struct DBAccessionRelationsRepo {
+    pub db_session: DatabaseConnection,
+    /// Tells the time relations are made at
+    pub clock: SharedClock,
+}
+
+/// An accession on the other end of a relation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelatedAccession {
+    pub relation_id: i32,
+    pub kind: AccessionRelationKind,
+    pub direction: RelationDirection,
+    pub accession: AccessionWithMetadataModel,
+}
+
+/// Defines the interface for accession relation database operations.
+#[async_trait]
+pub trait AccessionRelationsRepo: Send + Sync {
+    /// Relates two accessions.
+    ///
+    /// # Arguments
+    /// * `from_accession_id` - The accession that is part of, references or supersedes the other
+    /// * `to_accession_id` - The accession it relates to
+    /// * `kind` - How they relate
+    async fn write_one(
+        &self,
+        from_accession_id: i32,
+        to_accession_id: i32,
+        kind: AccessionRelationKind,
+    ) -> Result<AccessionRelationModel, DbErr>;
+
+    /// Deletes a relation if either end of it is the given accession.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of one of the related accessions
+    /// * `relation_id` - The ID of the relation to delete
+    async fn delete_one(&self, accession_id: i32, relation_id: i32) -> Result<Option<()>, DbErr>;
+
+    /// Lists the accessions related to an accession in either direction, oldest relation first.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    /// * `include_private` - Whether to include related accessions that aren't public
+    async fn list_for_accession(
+        &self,
+        accession_id: i32,
+        include_private: bool,
+    ) -> Result<Vec<RelatedAccession>, DbErr>;
+}
+
+#[async_trait]
+impl AccessionRelationsRepo for DBAccessionRelationsRepo {
+    async fn write_one(
+        &self,
+        from_accession_id: i32,
+        to_accession_id: i32,
+        kind: AccessionRelationKind,
+    ) -> Result<AccessionRelationModel, DbErr> {
+        let relation = AccessionRelationActiveModel {
+            id: Default::default(),
+            from_accession_id: ActiveValue::Set(from_accession_id),
+            to_accession_id: ActiveValue::Set(to_accession_id),
+            kind: ActiveValue::Set(kind),
+            created_at: ActiveValue::Set(self.clock.naive_now()),
+        };
+        relation.insert(&self.db_session).await
+    }
+
+    async fn delete_one(&self, accession_id: i32, relation_id: i32) -> Result<Option<()>, DbErr> {
+        let deletion = AccessionRelation::delete_many()
+            .filter(accession_relation::Column::Id.eq(relation_id))
+            .filter(
+                Condition::any()
+                    .add(accession_relation::Column::FromAccessionId.eq(accession_id))
+                    .add(accession_relation::Column::ToAccessionId.eq(accession_id)),
+            )
+            .exec(&self.db_session)
+            .await?;
+        if deletion.rows_affected > 0 {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn list_for_accession(
+        &self,
+        accession_id: i32,
+        include_private: bool,
+    ) -> Result<Vec<RelatedAccession>, DbErr> {
+        let relations = AccessionRelation::find()
+            .filter(
+                Condition::any()
+                    .add(accession_relation::Column::FromAccessionId.eq(accession_id))
+                    .add(accession_relation::Column::ToAccessionId.eq(accession_id)),
+            )
+            .order_by_asc(accession_relation::Column::Id)
+            .all(&self.db_session)
+            .await?;
+        if relations.is_empty() {
+            return Ok(vec![]);
+        }
+        let related_ids: Vec<i32> = relations
+            .iter()
+            .map(|relation| other_end(relation, accession_id))
+            .collect();
+        let mut query = AccessionWithMetadata::find()
+            .filter(accessions_with_metadata::Column::Id.is_in(related_ids));
+        if !include_private {
+            query = query.filter(accessions_with_metadata::Column::IsPrivate.eq(false));
+        }
+        let accessions: HashMap<i32, AccessionWithMetadataModel> = query
+            .all(&self.db_session)
+            .await?
+            .into_iter()
+            .map(|accession| (accession.id, accession))
+            .collect();
+        Ok(relations
+            .into_iter()
+            .filter_map(|relation| {
+                // the same accession can be on the other end of several relations, and
+                // ones that aren't visible are left out
+                let accession = accessions.get(&other_end(&relation, accession_id))?.clone();
+                Some(RelatedAccession {
+                    relation_id: relation.id,
+                    direction: if relation.from_accession_id == accession_id {
+                        RelationDirection::Outgoing
+                    } else {
+                        RelationDirection::Incoming
+                    },
+                    kind: relation.kind,
+                    accession,
+                })
+            })
+            .collect())
+    }
+}
+
+/// The ID of the accession at the other end of a relation from `accession_id`.
+fn other_end(relation: &AccessionRelationModel, accession_id: i32) -> i32 {
+    if relation.from_accession_id == accession_id {
+        relation.to_accession_id
+    } else {
+        relation.from_accession_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::common::{MetadataLanguage, MetadataScrubbing};
+    use crate::models::request::CreateAccessionRequestRaw;
+    use crate::repos::accessions_repo::{AccessionsRepo, DBAccessionsRepo};
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::test_db::migrated_test_db;
+    use entity::sea_orm_active_enums::{DublinMetadataFormat, ScanStatus};
+    use pretty_assertions::assert_eq;
+
+    async fn write_accession(accessions_repo: &DBAccessionsRepo, is_private: bool) -> i32 {
+        accessions_repo
+            .write_one_raw(
+                CreateAccessionRequestRaw {
+                    metadata_language: MetadataLanguage::English,
+                    metadata_title: "Testimony".to_string(),
+                    metadata_description: None,
+                    metadata_time: Default::default(),
+                    metadata_subjects: vec![],
+                    is_private,
+                    embargo_until: None,
+                    content_warning: None,
+                    metadata_format: DublinMetadataFormat::Jpeg,
+                    original_url: "https://example.com".to_string(),
+                    s3_filename: "file.jpg".to_string(),
+                    metadata_scrubbing: MetadataScrubbing::Scrub,
+                },
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn lists_relations_from_both_ends() {
+        let db = migrated_test_db().await;
+        let db_session = db.clone();
+        let accessions_repo = DBAccessionsRepo {
+            db_session: db_session.clone(),
+            ..Default::default()
+        };
+        let repo = DBAccessionRelationsRepo {
+            db_session,
+            ..Default::default()
+        };
+        let thread = write_accession(&accessions_repo, false).await;
+        let post = write_accession(&accessions_repo, false).await;
+        let draft = write_accession(&accessions_repo, true).await;
+        let part_of = repo
+            .write_one(post, thread, AccessionRelationKind::PartOf)
+            .await
+            .unwrap();
+        repo.write_one(draft, thread, AccessionRelationKind::References)
+            .await
+            .unwrap();
+        assert!(repo
+            .write_one(post, thread, AccessionRelationKind::PartOf)
+            .await
+            .is_err());
+        assert!(repo
+            .write_one(post, post, AccessionRelationKind::References)
+            .await
+            .is_err());
+
+        let from_post = repo.list_for_accession(post, false).await.unwrap();
+        assert_eq!(from_post.len(), 1);
+        assert_eq!(from_post[0].relation_id, part_of.id);
+        assert_eq!(from_post[0].direction, RelationDirection::Outgoing);
+        assert_eq!(from_post[0].accession.id, thread);
+
+        let to_thread = repo.list_for_accession(thread, false).await.unwrap();
+        assert_eq!(to_thread.len(), 1);
+        assert_eq!(to_thread[0].direction, RelationDirection::Incoming);
+        assert_eq!(to_thread[0].accession.id, post);
+        assert_eq!(
+            repo.list_for_accession(thread, true).await.unwrap().len(),
+            2
+        );
+
+        assert_eq!(repo.delete_one(draft, part_of.id).await.unwrap(), None);
+        assert_eq!(repo.delete_one(thread, part_of.id).await.unwrap(), Some(()));
+        assert!(repo
+            .list_for_accession(post, true)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
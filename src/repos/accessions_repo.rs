@@ -3,21 +3,34 @@
 //! This module provides functionality for creating, retrieving, and listing
 //! accession records with their associated metadata in both Arabic and English.
 
-use crate::models::common::MetadataLanguage;
+use crate::clock::SharedClock;
+use crate::memento::Capture;
+use crate::models::common::{MetadataLanguage, TimelineInterval};
 use crate::models::request::{
     AccessionPaginationWithPrivate, CreateAccessionRequest, CreateAccessionRequestRaw,
-    UpdateAccessionRequest,
+    CreateMetadataRequest, UpdateAccessionRequest, UpdateMetadataRequest,
 };
-use crate::repos::filter_builder::{build_filter_expression, FilterParams, MetadataSubjects};
+use crate::publication_workflow::initial_state;
+use crate::repos::filter_builder::{
+    build_filter_expression, FilterParams, MetadataSubjects, Visibility,
+};
+use crate::repos::pagination::PageWindow;
+use crate::url_canonicalizer::canonicalize_url;
 use async_trait::async_trait;
-use chrono::Utc;
 use entity::accession::ActiveModel as AccessionActiveModel;
 use entity::accession::Entity as Accession;
 use entity::accession::Model as AccessionModel;
 
+use entity::accession;
+use entity::accession_derivative;
+use entity::accession_derivative::ActiveModel as AccessionDerivativeActiveModel;
+use entity::accession_derivative::Entity as AccessionDerivative;
+use entity::accession_derivative::Model as AccessionDerivativeModel;
 use entity::accessions_with_metadata;
 use entity::accessions_with_metadata::Entity as AccessionWithMetadata;
 use entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
+use entity::audit_log::ActiveModel as AuditLogActiveModel;
+use entity::audit_log::Entity as AuditLog;
 use entity::dublin_metadata_ar::ActiveModel as DublinMetadataArActiveModel;
 use entity::dublin_metadata_ar::Entity as DublinMetadataAr;
 use entity::dublin_metadata_ar_subjects::ActiveModel as DublinMetadataSubjectsArActiveModel;
@@ -26,18 +39,97 @@ use entity::dublin_metadata_en::ActiveModel as DublinMetadataEnActiveModel;
 use entity::dublin_metadata_en::Entity as DublinMetadataEn;
 use entity::dublin_metadata_en_subjects::ActiveModel as DublinMetadataSubjectsEnActiveModel;
 use entity::dublin_metadata_en_subjects::Entity as DublinMetadataSubjectsEn;
-use entity::sea_orm_active_enums::{CrawlStatus, DublinMetadataFormat};
+use entity::sea_orm_active_enums::{
+    ContentWarning, CrawlStatus, DerivativeKind, DublinMetadataFormat, PublicationState, ScanStatus,
+};
+use sea_orm::sea_query::{Expr, OnConflict};
 use sea_orm::{
-    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
-    PaginatorTrait, QueryFilter, TransactionTrait, TryIntoModel,
+    ActiveEnum, ActiveModelTrait, ActiveValue, ColumnTrait, Condition, ConnectionTrait,
+    DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait, FromQueryResult, PaginatorTrait,
+    QueryFilter, QueryOrder, QueryResult, QuerySelect, Select, TransactionTrait, TryIntoModel,
 };
 
+use serde_json::json;
 use uuid::Uuid;
 
+/// GIN indexes over the full text search columns of accessions
+const FULL_TEXT_INDEXES: [&str; 2] = [
+    "idx_gin_accession_full_text_en",
+    "idx_gin_accession_full_text_ar",
+];
+
+/// A view row alongside how many rows matched the listing's filter in total.
+struct CountedAccessionRow {
+    row: AccessionWithMetadataModel,
+    total_count: i64,
+}
+
+impl FromQueryResult for CountedAccessionRow {
+    fn from_query_result(res: &QueryResult, pre: &str) -> Result<Self, DbErr> {
+        Ok(Self {
+            row: AccessionWithMetadataModel::from_query_result(res, pre)?,
+            total_count: res.try_get(pre, "total_count")?,
+        })
+    }
+}
+
+/// How many accessions are dated within one bucket of the timeline.
+#[derive(Debug, Clone, PartialEq, Eq, FromQueryResult)]
+pub struct TimelineBucket {
+    pub starts_at: chrono::NaiveDateTime,
+    pub count: i64,
+}
+
+/// Builds the query for every view row matching a listing's filters, before paging.
+///
+/// Only the entity's columns are selected, which leaves out the view's full text search
+/// vectors.
+fn filtered_accessions(params: AccessionPaginationWithPrivate) -> Select<AccessionWithMetadata> {
+    let metadata_subjects = if params.metadata_subjects.is_empty() {
+        None
+    } else {
+        Some(MetadataSubjects {
+            metadata_subjects: params.metadata_subjects,
+            metadata_subjects_inclusive_filter: params
+                .metadata_subjects_inclusive_filter
+                .unwrap_or(true),
+        })
+    };
+    let filter_params = FilterParams {
+        metadata_language: params.lang,
+        metadata_subjects,
+        query_term: params.query_term,
+        url_filter: params.url_filter,
+        date_from: params.date_from,
+        date_to: params.date_to,
+        has_content_warning: params.has_content_warning,
+        visibility: if params.is_private {
+            Visibility::Private(params.publication_state)
+        } else {
+            Visibility::Public
+        },
+        workflow_labels: if params.workflow_labels.is_empty() {
+            None
+        } else {
+            Some(params.workflow_labels)
+        },
+        organization_id: params.organization_id,
+        social_platform: params.social_platform,
+        author_handle: params.author_handle,
+    };
+    let query = AccessionWithMetadata::find();
+    match build_filter_expression(filter_params) {
+        Some(query_filter) => query.filter(query_filter),
+        None => query,
+    }
+}
+
 /// Repository implementation for database operations on accessions.
 #[derive(Debug, Clone, Default)]
 pub struct DBAccessionsRepo {
     pub db_session: DatabaseConnection,
+    /// Tells the time accessions are crawled and changed at
+    pub clock: SharedClock,
 }
 
 /// Defines the interface for accession-related database operations.
@@ -50,26 +142,73 @@ pub trait AccessionsRepo: Send + Sync {
     ///
     /// # Arguments
     /// * `create_accession_request` - The request containing accession and metadata details
-    /// * `org_id` - The Browsertrix organization ID associated with the accession
+    /// * `org_id` - The Browsertrix organization ID associated with the accession, `None` when
+    ///   another crawler made the crawl
     /// * `crawl_id` - The ID of the crawl operation
     /// * `job_run_id` - The ID of the job run
     /// * `crawl_status` - The status of the crawl operation
+    /// * `organization_id` - The organization the accession belongs to
     async fn write_one(
         &self,
         create_accession_request: CreateAccessionRequest,
-        org_id: Uuid,
+        org_id: Option<Uuid>,
         crawl_id: Uuid,
         job_run_id: String,
         crawl_status: CrawlStatus,
+        organization_id: i32,
+    ) -> Result<i32, DbErr>;
+
+    /// Creates the accession for a crawl that has only just been requested, so its ID can be
+    /// handed back straight away. It stays pending and private until the crawl is stored with
+    /// [`AccessionsRepo::complete_crawl`] or given up on with [`AccessionsRepo::fail_crawl`].
+    ///
+    /// # Arguments
+    /// * `create_accession_request` - The crawl request with the accession's metadata
+    /// * `organization_id` - The organization the accession belongs to
+    async fn write_pending(
+        &self,
+        create_accession_request: CreateAccessionRequest,
+        organization_id: i32,
     ) -> Result<i32, DbErr>;
 
+    /// Records the stored crawl of a pending accession and marks it complete, publishing it
+    /// unless the request asked for it to be private.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the pending accession
+    /// * `create_accession_request` - The crawl request, with the stored file's format and key
+    /// * `org_id` - The Browsertrix organization ID associated with the accession, `None` when
+    ///   another crawler made the crawl
+    /// * `crawl_id` - The ID of the crawl operation
+    /// * `job_run_id` - The ID of the job run
+    async fn complete_crawl(
+        &self,
+        id: i32,
+        create_accession_request: CreateAccessionRequest,
+        org_id: Option<Uuid>,
+        crawl_id: Uuid,
+        job_run_id: String,
+    ) -> Result<(), DbErr>;
+
+    /// Marks the crawl of a pending accession as errored.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the pending accession
+    async fn fail_crawl(&self, id: i32) -> Result<(), DbErr>;
+
     /// Creates a new accession record from a raw file upload (without a web crawl).
     ///
     /// # Arguments
     /// * `create_accession_request` - The request containing accession and metadata details for raw upload
+    /// * `scan_status` - The result of virus scanning the uploaded file
+    /// * `metadata_scrubbed` - Whether embedded metadata was stripped from the file before storing it
+    /// * `organization_id` - The organization the accession belongs to
     async fn write_one_raw(
         &self,
         create_accession_request: CreateAccessionRequestRaw,
+        scan_status: ScanStatus,
+        metadata_scrubbed: bool,
+        organization_id: i32,
     ) -> Result<i32, DbErr>;
 
     /// Retrieves an accession record by its ID along with associated metadata.
@@ -81,12 +220,29 @@ pub trait AccessionsRepo: Send + Sync {
 
     /// Lists accessions with pagination and filtering options.
     ///
+    /// Returns the page alongside the total number of pages, or `None` for the
+    /// total if `params.count` is false.
+    ///
     /// # Arguments
     /// * `params` - Parameters for filtering and pagination
     async fn list_paginated(
         &self,
         params: AccessionPaginationWithPrivate,
-    ) -> Result<(Vec<AccessionWithMetadataModel>, u64), DbErr>;
+    ) -> Result<(Vec<AccessionWithMetadataModel>, Option<u64>), DbErr>;
+
+    /// Counts the accessions a listing would return by their Dublin Core date.
+    ///
+    /// # Arguments
+    /// * `params` - Filters to apply, the same as for listing; paging is ignored
+    /// * `interval` - How wide each bucket is
+    ///
+    /// # Returns
+    /// Buckets with at least one accession, oldest first
+    async fn timeline(
+        &self,
+        params: AccessionPaginationWithPrivate,
+        interval: TimelineInterval,
+    ) -> Result<Vec<TimelineBucket>, DbErr>;
 
     /// Deletes an accession record by its ID.
     ///
@@ -96,6 +252,9 @@ pub trait AccessionsRepo: Send + Sync {
 
     /// Updates an existing accession record with new metadata.
     ///
+    /// Fails with [`DbErr::RecordNotUpdated`] if the request is based on an older
+    /// revision of the accession than the one stored.
+    ///
     /// # Arguments
     /// * `id` - The ID of the accession to update
     /// * `update_accession_request` - The request containing updated metadata details
@@ -104,8 +263,247 @@ pub trait AccessionsRepo: Send + Sync {
         id: i32,
         update_accession_request: UpdateAccessionRequest,
     ) -> Result<Option<AccessionWithMetadataModel>, DbErr>;
+
+    /// Records the S3 key of a PDF rendering generated for an accession.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the accession
+    /// * `pdf_s3_filename` - The S3 key of the uploaded PDF
+    async fn set_pdf_s3_filename(&self, id: i32, pdf_s3_filename: String) -> Result<(), DbErr>;
+
+    /// Records the DOI minted for an accession, unless it already has one.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the accession
+    /// * `doi` - The DOI, e.g. `10.1234/abcd-efgh`
+    ///
+    /// # Returns
+    /// `None` if the accession doesn't exist or already has a DOI
+    async fn set_doi(&self, id: i32, doi: String) -> Result<Option<()>, DbErr>;
+
+    /// Flags a finished crawl as a bad capture for curators to review.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the accession
+    /// * `reason` - Why the capture looks bad, see [`crate::capture_quality::assess_capture`]
+    async fn flag_bad_crawl(&self, id: i32, reason: String) -> Result<(), DbErr>;
+
+    /// Accepts a capture flagged as bad, marking its crawl complete again.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the accession
+    ///
+    /// # Returns
+    /// `None` if the accession doesn't exist or isn't flagged as a bad capture
+    async fn clear_bad_crawl(&self, id: i32) -> Result<Option<()>, DbErr>;
+
+    /// Lists captures flagged as bad, public or private, most recent crawl first.
+    ///
+    /// # Arguments
+    /// * `window` - The page to list
+    /// * `organization_id` - Only list captures of this organization, every one if `None`
+    ///
+    /// # Returns
+    /// The page alongside the total number of pages
+    async fn list_bad_crawls(
+        &self,
+        window: PageWindow,
+        organization_id: Option<i32>,
+    ) -> Result<(Vec<AccessionWithMetadataModel>, u64), DbErr>;
+
+    /// Lists accessions of every organization that need a person to look at them: bad
+    /// captures and accessions in review, most recent crawl first.
+    ///
+    /// # Arguments
+    /// * `window` - The page to list
+    ///
+    /// # Returns
+    /// The page alongside the total number of pages
+    async fn list_review_queue(
+        &self,
+        window: PageWindow,
+    ) -> Result<(Vec<AccessionWithMetadataModel>, u64), DbErr>;
+
+    /// Finds accessions that were captured from the given canonical URL.
+    ///
+    /// # Arguments
+    /// * `canonical_url` - The canonicalized URL to look up
+    ///
+    /// # Returns
+    /// IDs of matching accessions, oldest first
+    async fn find_ids_by_canonical_url(&self, canonical_url: &str) -> Result<Vec<i32>, DbErr>;
+
+    /// Lists the public, completed captures of the given canonical URL, for Memento.
+    ///
+    /// # Arguments
+    /// * `canonical_url` - The canonicalized URL to look up
+    ///
+    /// # Returns
+    /// The captures, oldest first
+    async fn list_public_captures(&self, canonical_url: &str) -> Result<Vec<Capture>, DbErr>;
+
+    /// Lists WACZ accessions that are still served by Browsertrix because their file was
+    /// never copied to S3, oldest first.
+    async fn list_missing_s3_files(&self) -> Result<Vec<AccessionWithMetadataModel>, DbErr>;
+
+    /// Records the S3 key of an accession's file.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the accession
+    /// * `s3_filename` - The S3 key of the uploaded file
+    async fn set_s3_filename(&self, id: i32, s3_filename: String) -> Result<(), DbErr>;
+
+    /// Points an accession at a replacement file, unless its file changed since it was read.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the accession
+    /// * `previous` - The S3 key the accession had when it was read, if any
+    /// * `s3_filename` - The S3 key of the replacement file
+    ///
+    /// # Returns
+    /// `None` if the accession doesn't exist or no longer has `previous` as its file
+    async fn replace_s3_filename(
+        &self,
+        id: i32,
+        previous: Option<String>,
+        s3_filename: String,
+    ) -> Result<Option<()>, DbErr>;
+
+    /// Records a derivative generated for an accession, replacing any earlier one of the
+    /// same kind.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    /// * `kind` - What sort of derivative it is
+    /// * `s3_filename` - The S3 key of the uploaded derivative
+    /// * `content_type` - The MIME type of the derivative
+    async fn write_derivative(
+        &self,
+        accession_id: i32,
+        kind: DerivativeKind,
+        s3_filename: String,
+        content_type: String,
+    ) -> Result<(), DbErr>;
+
+    /// Lists the derivatives generated for an accession.
+    async fn list_derivatives(
+        &self,
+        accession_id: i32,
+    ) -> Result<Vec<AccessionDerivativeModel>, DbErr>;
+
+    /// Lists accessions in ID order, for background jobs that work through the whole
+    /// archive a batch at a time.
+    ///
+    /// # Arguments
+    /// * `after_id` - Only return accessions with a greater ID than this
+    /// * `limit` - The maximum number of accessions to return
+    async fn list_batch_after_id(
+        &self,
+        after_id: i32,
+        limit: u64,
+    ) -> Result<Vec<AccessionModel>, DbErr>;
+
+    /// Lists public accessions with their metadata in ID order, a batch at a time, for
+    /// exports of everything the public can see.
+    ///
+    /// # Arguments
+    /// * `after_id` - Only return accessions with a greater ID than this
+    /// * `limit` - The maximum number of accessions to return
+    async fn list_public_batch_after_id(
+        &self,
+        after_id: i32,
+        limit: u64,
+    ) -> Result<Vec<AccessionWithMetadataModel>, DbErr>;
+
+    /// Counts every accession, public or private.
+    async fn count_all(&self) -> Result<u64, DbErr>;
+
+    /// Counts the accessions anyone can see, for the public statistics widget.
+    async fn count_public(&self) -> Result<u64, DbErr>;
+
+    /// Recomputes the stored full text columns of a batch of accessions, in ID order.
+    ///
+    /// # Arguments
+    /// * `after_id` - Only refresh accessions with a greater ID than this
+    /// * `limit` - The maximum number of accessions to refresh
+    ///
+    /// # Returns
+    /// IDs of the refreshed accessions, empty once there are none left
+    async fn refresh_full_text_batch(&self, after_id: i32, limit: u64) -> Result<Vec<i32>, DbErr>;
+
+    /// Rebuilds the full text indexes without blocking writes to accessions.
+    async fn rebuild_full_text_indexes(&self) -> Result<(), DbErr>;
+
+    /// Clears embargoes that have lapsed, which makes published accessions public.
+    ///
+    /// # Arguments
+    /// * `now` - The time embargoes lapse by
+    ///
+    /// # Returns
+    /// IDs of the accessions whose embargo was lifted
+    async fn lift_lapsed_embargoes(&self, now: chrono::NaiveDateTime) -> Result<Vec<i32>, DbErr>;
+
+    /// Looks up where an accession is in the publishing workflow.
+    ///
+    /// # Returns
+    /// The accession's publication state, or `None` if there is no such accession
+    async fn get_publication_state(&self, id: i32) -> Result<Option<PublicationState>, DbErr>;
+
+    /// Moves an accession to another publication state.
+    ///
+    /// The move only happens if the accession is still in `from`, so two curators acting
+    /// on the same accession at once can't both succeed.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the accession
+    /// * `from` - The state the caller saw the accession in
+    /// * `to` - The state to move it to
+    ///
+    /// # Returns
+    /// The updated accession, or `None` if it no longer exists or had already moved
+    async fn set_publication_state(
+        &self,
+        id: i32,
+        from: PublicationState,
+        to: PublicationState,
+    ) -> Result<Option<AccessionWithMetadataModel>, DbErr>;
+
+    /// Moves many accessions to one publication state in a single transaction, writing an
+    /// audit log entry for each accession moved.
+    ///
+    /// # Arguments
+    /// * `selection` - Which accessions to consider
+    /// * `from` - Only accessions in one of these states are moved
+    /// * `to` - The state to move them to
+    /// * `actor_email` - Email of the user making the change
+    /// * `reason` - Why the change was made, kept in the audit log
+    /// * `organization_id` - Only accessions of this organization are moved, or those of
+    ///   every organization when `None`
+    ///
+    /// # Returns
+    /// IDs of the accessions that were moved
+    async fn bulk_set_publication_state(
+        &self,
+        selection: AccessionSelection,
+        from: Vec<PublicationState>,
+        to: PublicationState,
+        actor_email: String,
+        reason: Option<String>,
+        organization_id: Option<i32>,
+    ) -> Result<Vec<i32>, DbErr>;
 }
 
+/// Accessions picked out for a bulk change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessionSelection {
+    Ids(Vec<i32>),
+    /// Accessions whose seed URL starts with this
+    UrlPrefix(String),
+}
+
+/// Audit log event for an accession moving between publication states.
+pub const PUBLICATION_STATE_CHANGED: &str = "publication_state_changed";
+
 /// A private struct that mirrors the fields required to create an accession
 /// in the database.
 ///
@@ -114,19 +512,23 @@ pub trait AccessionsRepo: Send + Sync {
 /// (for raw file uploads), decoupling the public-facing request models from
 /// the internal database repository logic.
 struct CreateAccessionData {
-    metadata_language: MetadataLanguage,
-    metadata_title: String,
-    metadata_description: Option<String>,
-    metadata_subjects: Vec<i32>,
+    metadata_en: Option<CreateMetadataRequest>,
+    metadata_ar: Option<CreateMetadataRequest>,
     metadata_time: chrono::NaiveDateTime,
     crawl_status: CrawlStatus,
     org_id: Option<Uuid>,
     crawl_id: Option<Uuid>,
     job_run_id: Option<String>,
     seed_url: String,
+    canonical_url: String,
     is_private: bool,
+    embargo_until: Option<chrono::NaiveDateTime>,
+    content_warning: Option<ContentWarning>,
     metadata_format: DublinMetadataFormat,
     s3_filename: Option<String>,
+    scan_status: ScanStatus,
+    metadata_scrubbed: bool,
+    organization_id: i32,
 }
 
 impl DBAccessionsRepo {
@@ -137,53 +539,16 @@ impl DBAccessionsRepo {
     /// `write_one` and `write_one_raw` methods.
     async fn _create_one(&self, accession_data: CreateAccessionData) -> Result<i32, DbErr> {
         let txn = self.db_session.begin().await?;
-        let (dublin_metadata_en_id, dublin_metadata_ar_id) = match accession_data.metadata_language
-        {
-            MetadataLanguage::English => {
-                let metadata = DublinMetadataEnActiveModel {
-                    id: Default::default(),
-                    title: ActiveValue::Set(accession_data.metadata_title),
-                    description: ActiveValue::Set(accession_data.metadata_description),
-                };
-                let inserted_metadata = metadata.save(&txn).await?;
-                let metadata_id = inserted_metadata.try_into_model()?.id;
-                let mut subject_links: Vec<DublinMetadataSubjectsEnActiveModel> = vec![];
-                for subject_id in accession_data.metadata_subjects.iter() {
-                    let subjects_link = DublinMetadataSubjectsEnActiveModel {
-                        metadata_id: ActiveValue::Set(metadata_id),
-                        subject_id: ActiveValue::Set(*subject_id),
-                    };
-                    subject_links.push(subjects_link);
-                }
-                DublinMetadataSubjectsEn::insert_many(subject_links)
-                    .exec(&txn)
-                    .await?;
-                (Some(metadata_id), None)
-            }
-            MetadataLanguage::Arabic => {
-                let metadata = DublinMetadataArActiveModel {
-                    id: Default::default(),
-                    title: ActiveValue::Set(accession_data.metadata_title),
-                    description: ActiveValue::Set(accession_data.metadata_description),
-                };
-                let inserted_metadata = metadata.save(&txn).await?;
-                let metadata_id = inserted_metadata.try_into_model()?.id;
-                let mut subject_links: Vec<DublinMetadataSubjectsArActiveModel> = vec![];
-                for subject_id in accession_data.metadata_subjects.iter() {
-                    let subjects_link = DublinMetadataSubjectsArActiveModel {
-                        metadata_id: ActiveValue::Set(metadata_id),
-                        subject_id: ActiveValue::Set(*subject_id),
-                    };
-                    subject_links.push(subjects_link);
-                }
-                DublinMetadataSubjectsAr::insert_many(subject_links)
-                    .exec(&txn)
-                    .await?;
-                (None, Some(metadata_id))
-            }
+        let dublin_metadata_en_id = match &accession_data.metadata_en {
+            Some(metadata) => Some(update_metadata_en(&txn, None, &metadata.clone().into()).await?),
+            None => None,
+        };
+        let dublin_metadata_ar_id = match &accession_data.metadata_ar {
+            Some(metadata) => Some(update_metadata_ar(&txn, None, &metadata.clone().into()).await?),
+            None => None,
         };
 
-        let utc_now = Utc::now();
+        let utc_now = self.clock.now();
         let i_hate_timezones = utc_now.naive_utc();
         let accession = AccessionActiveModel {
             id: Default::default(),
@@ -196,9 +561,19 @@ impl DBAccessionsRepo {
             crawl_id: ActiveValue::Set(accession_data.crawl_id),
             job_run_id: ActiveValue::Set(accession_data.job_run_id),
             seed_url: ActiveValue::Set(accession_data.seed_url),
-            is_private: ActiveValue::Set(accession_data.is_private),
+            canonical_url: ActiveValue::Set(Some(accession_data.canonical_url)),
+            publication_state: ActiveValue::Set(initial_state(accession_data.is_private)),
+            embargo_until: ActiveValue::Set(accession_data.embargo_until),
+            content_warning: ActiveValue::Set(accession_data.content_warning),
             dublin_metadata_format: ActiveValue::Set(accession_data.metadata_format),
             s3_filename: ActiveValue::Set(accession_data.s3_filename),
+            pdf_s3_filename: ActiveValue::Set(None),
+            scan_status: ActiveValue::Set(accession_data.scan_status),
+            metadata_scrubbed: ActiveValue::Set(accession_data.metadata_scrubbed),
+            revision: ActiveValue::Set(0),
+            organization_id: ActiveValue::Set(accession_data.organization_id),
+            doi: ActiveValue::Set(None),
+            crawl_quality_reason: ActiveValue::Set(None),
         };
         let saved_accession = accession.clone().save(&txn).await?;
         txn.commit().await?;
@@ -206,52 +581,242 @@ impl DBAccessionsRepo {
     }
 }
 
+/// Applies the English block of an update to an accession's English metadata, creating
+/// it if the accession has none yet.
+///
+/// Returns the ID of the metadata. Creating metadata without a title fails, callers
+/// should check for one first.
+async fn update_metadata_en(
+    txn: &DatabaseTransaction,
+    metadata_id: Option<i32>,
+    update: &UpdateMetadataRequest,
+) -> Result<i32, DbErr> {
+    let metadata = DublinMetadataEnActiveModel {
+        id: match metadata_id {
+            Some(id) => ActiveValue::Unchanged(id),
+            None => Default::default(),
+        },
+        title: match (&update.title, metadata_id) {
+            (Some(title), _) => ActiveValue::Set(title.clone()),
+            (None, Some(_)) => ActiveValue::NotSet,
+            (None, None) => {
+                return Err(DbErr::Custom(
+                    "New English metadata needs a title".to_string(),
+                ))
+            }
+        },
+        description: match &update.description {
+            Some(description) => ActiveValue::Set(description.clone()),
+            None => ActiveValue::NotSet,
+        },
+        machine_translated: ActiveValue::Set(update.machine_translated),
+    };
+    let metadata_id = metadata.save(txn).await?.try_into_model()?.id;
+    if let Some(subjects) = &update.subjects {
+        DublinMetadataSubjectsEn::delete_many()
+            .filter(
+                <entity::dublin_metadata_en_subjects::Entity as EntityTrait>::Column::MetadataId
+                    .eq(metadata_id),
+            )
+            .exec(txn)
+            .await?;
+        let subject_links: Vec<DublinMetadataSubjectsEnActiveModel> = subjects
+            .iter()
+            .map(|subject_id| DublinMetadataSubjectsEnActiveModel {
+                metadata_id: ActiveValue::Set(metadata_id),
+                subject_id: ActiveValue::Set(*subject_id),
+            })
+            .collect();
+        if !subject_links.is_empty() {
+            DublinMetadataSubjectsEn::insert_many(subject_links)
+                .exec(txn)
+                .await?;
+        }
+    }
+    Ok(metadata_id)
+}
+
+/// Arabic counterpart of [`update_metadata_en`].
+async fn update_metadata_ar(
+    txn: &DatabaseTransaction,
+    metadata_id: Option<i32>,
+    update: &UpdateMetadataRequest,
+) -> Result<i32, DbErr> {
+    let metadata = DublinMetadataArActiveModel {
+        id: match metadata_id {
+            Some(id) => ActiveValue::Unchanged(id),
+            None => Default::default(),
+        },
+        title: match (&update.title, metadata_id) {
+            (Some(title), _) => ActiveValue::Set(title.clone()),
+            (None, Some(_)) => ActiveValue::NotSet,
+            (None, None) => {
+                return Err(DbErr::Custom(
+                    "New Arabic metadata needs a title".to_string(),
+                ))
+            }
+        },
+        description: match &update.description {
+            Some(description) => ActiveValue::Set(description.clone()),
+            None => ActiveValue::NotSet,
+        },
+        machine_translated: ActiveValue::Set(update.machine_translated),
+    };
+    let metadata_id = metadata.save(txn).await?.try_into_model()?.id;
+    if let Some(subjects) = &update.subjects {
+        DublinMetadataSubjectsAr::delete_many()
+            .filter(
+                <entity::dublin_metadata_ar_subjects::Entity as EntityTrait>::Column::MetadataId
+                    .eq(metadata_id),
+            )
+            .exec(txn)
+            .await?;
+        let subject_links: Vec<DublinMetadataSubjectsArActiveModel> = subjects
+            .iter()
+            .map(|subject_id| DublinMetadataSubjectsArActiveModel {
+                metadata_id: ActiveValue::Set(metadata_id),
+                subject_id: ActiveValue::Set(*subject_id),
+            })
+            .collect();
+        if !subject_links.is_empty() {
+            DublinMetadataSubjectsAr::insert_many(subject_links)
+                .exec(txn)
+                .await?;
+        }
+    }
+    Ok(metadata_id)
+}
+
 #[async_trait]
 impl AccessionsRepo for DBAccessionsRepo {
     async fn write_one(
         &self,
         create_accession_request: CreateAccessionRequest,
-        org_id: Uuid,
+        org_id: Option<Uuid>,
         crawl_id: Uuid,
         job_run_id: String,
         crawl_status: CrawlStatus,
+        organization_id: i32,
     ) -> Result<i32, DbErr> {
         let accession_data = CreateAccessionData {
-            metadata_language: create_accession_request.metadata_language,
-            metadata_title: create_accession_request.metadata_title,
-            metadata_description: create_accession_request.metadata_description,
-            metadata_subjects: create_accession_request.metadata_subjects,
+            metadata_en: create_accession_request.metadata_en,
+            metadata_ar: create_accession_request.metadata_ar,
             metadata_time: create_accession_request.metadata_time,
             crawl_status,
-            org_id: Some(org_id),
+            org_id,
             crawl_id: Some(crawl_id),
             job_run_id: Some(job_run_id),
+            canonical_url: canonicalize_url(&create_accession_request.url),
             seed_url: create_accession_request.url,
             is_private: create_accession_request.is_private,
+            embargo_until: create_accession_request.embargo_until,
+            content_warning: create_accession_request.content_warning,
             metadata_format: create_accession_request.metadata_format,
             s3_filename: create_accession_request.s3_filename,
+            // Crawls come from crawlers rather than users so aren't scanned
+            scan_status: ScanStatus::NotScanned,
+            metadata_scrubbed: false,
+            organization_id,
+        };
+        self._create_one(accession_data).await
+    }
+
+    async fn write_pending(
+        &self,
+        create_accession_request: CreateAccessionRequest,
+        organization_id: i32,
+    ) -> Result<i32, DbErr> {
+        let accession_data = CreateAccessionData {
+            metadata_en: create_accession_request.metadata_en,
+            metadata_ar: create_accession_request.metadata_ar,
+            metadata_time: create_accession_request.metadata_time,
+            crawl_status: CrawlStatus::Pending,
+            org_id: None,
+            crawl_id: None,
+            job_run_id: None,
+            canonical_url: canonicalize_url(&create_accession_request.url),
+            seed_url: create_accession_request.url,
+            // published by complete_crawl, so there's never a public accession with no file
+            is_private: true,
+            embargo_until: create_accession_request.embargo_until,
+            content_warning: create_accession_request.content_warning,
+            metadata_format: create_accession_request.metadata_format,
+            s3_filename: None,
+            scan_status: ScanStatus::NotScanned,
+            metadata_scrubbed: false,
+            organization_id,
         };
         self._create_one(accession_data).await
     }
 
+    async fn complete_crawl(
+        &self,
+        id: i32,
+        create_accession_request: CreateAccessionRequest,
+        org_id: Option<Uuid>,
+        crawl_id: Uuid,
+        job_run_id: String,
+    ) -> Result<(), DbErr> {
+        let accession = AccessionActiveModel {
+            id: ActiveValue::Unchanged(id),
+            crawl_status: ActiveValue::Set(CrawlStatus::Complete),
+            crawl_timestamp: ActiveValue::Set(self.clock.naive_now()),
+            org_id: ActiveValue::Set(org_id),
+            crawl_id: ActiveValue::Set(Some(crawl_id)),
+            job_run_id: ActiveValue::Set(Some(job_run_id)),
+            dublin_metadata_format: ActiveValue::Set(create_accession_request.metadata_format),
+            s3_filename: ActiveValue::Set(create_accession_request.s3_filename),
+            publication_state: ActiveValue::Set(initial_state(create_accession_request.is_private)),
+            ..Default::default()
+        };
+        accession.update(&self.db_session).await?;
+        Ok(())
+    }
+
+    async fn fail_crawl(&self, id: i32) -> Result<(), DbErr> {
+        Accession::update_many()
+            .col_expr(accession::Column::CrawlStatus, CrawlStatus::Error.as_enum())
+            .filter(accession::Column::Id.eq(id))
+            .filter(accession::Column::CrawlStatus.eq(CrawlStatus::Pending))
+            .exec(&self.db_session)
+            .await?;
+        Ok(())
+    }
+
     async fn write_one_raw(
         &self,
         create_accession_request: CreateAccessionRequestRaw,
+        scan_status: ScanStatus,
+        metadata_scrubbed: bool,
+        organization_id: i32,
     ) -> Result<i32, DbErr> {
+        let metadata = CreateMetadataRequest {
+            title: create_accession_request.metadata_title,
+            description: create_accession_request.metadata_description,
+            subjects: create_accession_request.metadata_subjects,
+        };
+        let (metadata_en, metadata_ar) = match create_accession_request.metadata_language {
+            MetadataLanguage::English => (Some(metadata), None),
+            MetadataLanguage::Arabic => (None, Some(metadata)),
+        };
         let accession_data = CreateAccessionData {
-            metadata_language: create_accession_request.metadata_language,
-            metadata_title: create_accession_request.metadata_title,
-            metadata_description: create_accession_request.metadata_description,
-            metadata_subjects: create_accession_request.metadata_subjects,
+            metadata_en,
+            metadata_ar,
             metadata_time: create_accession_request.metadata_time,
             crawl_status: CrawlStatus::Complete,
             org_id: None,
             crawl_id: None,
             job_run_id: None,
+            canonical_url: canonicalize_url(&create_accession_request.original_url),
             seed_url: create_accession_request.original_url,
             is_private: create_accession_request.is_private,
+            embargo_until: create_accession_request.embargo_until,
+            content_warning: create_accession_request.content_warning,
             metadata_format: create_accession_request.metadata_format,
             s3_filename: Some(create_accession_request.s3_filename),
+            scan_status,
+            metadata_scrubbed,
+            organization_id,
         };
         self._create_one(accession_data).await
     }
@@ -263,7 +828,7 @@ impl AccessionsRepo for DBAccessionsRepo {
     ) -> Result<Option<AccessionWithMetadataModel>, DbErr> {
         let accession = AccessionWithMetadata::find()
             .filter(accessions_with_metadata::Column::Id.eq(id))
-            .filter(accessions_with_metadata::Column::IsPrivate.eq(private))
+            .filter(Visibility::from_private(private).condition())
             .one(&self.db_session)
             .await?;
         Ok(accession)
@@ -272,38 +837,54 @@ impl AccessionsRepo for DBAccessionsRepo {
     async fn list_paginated(
         &self,
         params: AccessionPaginationWithPrivate,
-    ) -> Result<(Vec<AccessionWithMetadataModel>, u64), DbErr> {
-        let metadata_subjects = if params.metadata_subjects.is_empty() {
-            None
-        } else {
-            Some(MetadataSubjects {
-                metadata_subjects: params.metadata_subjects,
-                metadata_subjects_inclusive_filter: params
-                    .metadata_subjects_inclusive_filter
-                    .unwrap_or(true),
-            })
-        };
-        let filter_params = FilterParams {
-            metadata_language: params.lang,
-            metadata_subjects,
-            query_term: params.query_term,
-            url_filter: params.url_filter,
-            date_from: params.date_from,
-            date_to: params.date_to,
-            is_private: params.is_private,
-        };
-        let filter_expression = build_filter_expression(filter_params);
-        let accession_pages;
-        if let Some(query_filter) = filter_expression {
-            accession_pages = AccessionWithMetadata::find()
-                .filter(query_filter)
-                .paginate(&self.db_session, params.per_page);
-        } else {
-            accession_pages =
-                AccessionWithMetadata::find().paginate(&self.db_session, params.per_page);
+    ) -> Result<(Vec<AccessionWithMetadataModel>, Option<u64>), DbErr> {
+        let window = PageWindow::new(params.page, params.per_page);
+        let count = params.count;
+        let filtered = filtered_accessions(params);
+        let page_query = filtered
+            .clone()
+            .limit(window.per_page)
+            .offset(window.page * window.per_page);
+        if !count {
+            return Ok((page_query.all(&self.db_session).await?, None));
         }
-        let num_pages = accession_pages.num_pages().await?;
-        Ok((accession_pages.fetch_page(params.page).await?, num_pages))
+
+        // count in the same pass as the fetch instead of running the filter a second time
+        let counted_rows = page_query
+            .expr_as(Expr::cust("COUNT(*) OVER ()"), "total_count")
+            .into_model::<CountedAccessionRow>()
+            .all(&self.db_session)
+            .await?;
+        let num_items = match counted_rows.first() {
+            Some(row) => row.total_count as u64,
+            // past the last page there are no rows to carry the count
+            None if window.page > 0 => filtered.count(&self.db_session).await?,
+            None => 0,
+        };
+        let rows = counted_rows.into_iter().map(|row| row.row).collect();
+        Ok((rows, Some(num_items.div_ceil(window.per_page))))
+    }
+
+    async fn timeline(
+        &self,
+        params: AccessionPaginationWithPrivate,
+        interval: TimelineInterval,
+    ) -> Result<Vec<TimelineBucket>, DbErr> {
+        // the field comes from the enum rather than the request, so it's safe to inline, and
+        // has to be since a bound parameter wouldn't match between select and group by
+        let starts_at = Expr::cust(format!(
+            "date_trunc('{}', dublin_metadata_date)",
+            interval.date_trunc_field()
+        ));
+        filtered_accessions(params)
+            .select_only()
+            .expr_as(starts_at.clone(), "starts_at")
+            .expr_as(Expr::cust("COUNT(*)"), "count")
+            .group_by(starts_at.clone())
+            .order_by_asc(starts_at)
+            .into_model::<TimelineBucket>()
+            .all(&self.db_session)
+            .await
     }
 
     async fn delete_one(&self, id: i32) -> Result<Option<AccessionModel>, DbErr> {
@@ -348,83 +929,1500 @@ impl AccessionsRepo for DBAccessionsRepo {
         update_accession_request: UpdateAccessionRequest,
     ) -> Result<Option<AccessionWithMetadataModel>, DbErr> {
         let txn = self.db_session.begin().await?;
-        let accession = Accession::find_by_id(id).one(&self.db_session).await?;
-        match accession {
-            Some(accession) => {
-                let mut accession_active: AccessionActiveModel = accession.clone().into();
-                match update_accession_request.metadata_language {
-                    MetadataLanguage::English => {
-                        let metadata = DublinMetadataEnActiveModel {
-                            id: match accession.dublin_metadata_en {
-                                Some(id) => ActiveValue::Set(id),
-                                None => Default::default(),
-                            },
-                            title: ActiveValue::Set(update_accession_request.metadata_title),
-                            description: ActiveValue::Set(
-                                update_accession_request.metadata_description,
-                            ),
-                        };
-                        let inserted_metadata = metadata.save(&txn).await?;
-                        let metadata_id = inserted_metadata.try_into_model()?.id;
-                        let mut new_subject_links: Vec<DublinMetadataSubjectsEnActiveModel> =
-                            vec![];
-                        for subject_id in update_accession_request.metadata_subjects.iter() {
-                            let subjects_link = DublinMetadataSubjectsEnActiveModel {
-                                metadata_id: ActiveValue::Set(metadata_id),
-                                subject_id: ActiveValue::Set(*subject_id),
-                            };
-                            new_subject_links.push(subjects_link);
-                        }
-                        DublinMetadataSubjectsEn::delete_many().filter(<entity::dublin_metadata_en_subjects::Entity as EntityTrait>::Column::MetadataId.eq(metadata_id))
-                            .exec(&txn)
-                            .await?;
-                        DublinMetadataSubjectsEn::insert_many(new_subject_links)
-                            .exec(&txn)
-                            .await?;
-                        accession_active.dublin_metadata_en = ActiveValue::Set(Some(metadata_id));
-                    }
-                    MetadataLanguage::Arabic => {
-                        let metadata = DublinMetadataArActiveModel {
-                            id: match accession.dublin_metadata_ar {
-                                Some(id) => ActiveValue::Set(id),
-                                None => Default::default(),
-                            },
-                            title: ActiveValue::Set(update_accession_request.metadata_title),
-                            description: ActiveValue::Set(
-                                update_accession_request.metadata_description,
-                            ),
-                        };
-                        let inserted_metadata = metadata.save(&txn).await?;
-                        let metadata_id = inserted_metadata.try_into_model()?.id;
-                        let mut new_subject_links: Vec<DublinMetadataSubjectsArActiveModel> =
-                            vec![];
-                        for subject_id in update_accession_request.metadata_subjects.iter() {
-                            let subjects_link = DublinMetadataSubjectsArActiveModel {
-                                metadata_id: ActiveValue::Set(metadata_id),
-                                subject_id: ActiveValue::Set(*subject_id),
-                            };
-                            new_subject_links.push(subjects_link);
-                        }
-                        DublinMetadataSubjectsAr::delete_many().filter(<entity::dublin_metadata_ar_subjects::Entity as EntityTrait>::Column::MetadataId.eq(metadata_id))
-                            .exec(&txn)
-                            .await?;
-                        DublinMetadataSubjectsAr::insert_many(new_subject_links)
-                            .exec(&txn)
-                            .await?;
-                        accession_active.dublin_metadata_ar = ActiveValue::Set(Some(metadata_id));
-                    }
-                };
-                accession_active.dublin_metadata_date =
-                    ActiveValue::Set(update_accession_request.metadata_time);
-                accession_active.is_private = ActiveValue::Set(update_accession_request.is_private);
-                accession_active.update(&txn).await?;
-                txn.commit().await?;
-                let accession = AccessionWithMetadata::find_by_id(id)
-                    .one(&self.db_session)
-                    .await?;
-                Ok(accession)
+        // lock the accession so concurrent edits wait for this one rather than interleaving
+        let accession = Accession::find_by_id(id).lock_exclusive().one(&txn).await?;
+        let Some(accession) = accession else {
+            return Ok(None);
+        };
+        if update_accession_request.revision != accession.revision {
+            return Err(DbErr::RecordNotUpdated);
+        }
+        let mut accession_active: AccessionActiveModel = accession.clone().into();
+        // both languages are written in the same transaction, so a failure in one
+        // leaves the other untouched too
+        if let Some(metadata_en) = &update_accession_request.metadata_en {
+            let metadata_id =
+                update_metadata_en(&txn, accession.dublin_metadata_en, metadata_en).await?;
+            accession_active.dublin_metadata_en = ActiveValue::Set(Some(metadata_id));
+        }
+        if let Some(metadata_ar) = &update_accession_request.metadata_ar {
+            let metadata_id =
+                update_metadata_ar(&txn, accession.dublin_metadata_ar, metadata_ar).await?;
+            accession_active.dublin_metadata_ar = ActiveValue::Set(Some(metadata_id));
+        }
+        if let Some(metadata_time) = update_accession_request.metadata_time {
+            accession_active.dublin_metadata_date = ActiveValue::Set(metadata_time);
+        }
+        if let Some(embargo_until) = update_accession_request.embargo_until {
+            accession_active.embargo_until = ActiveValue::Set(embargo_until);
+        }
+        if let Some(content_warning) = update_accession_request.content_warning {
+            accession_active.content_warning = ActiveValue::Set(content_warning);
+        }
+        accession_active.revision = ActiveValue::Set(accession.revision + 1);
+        accession_active.update(&txn).await?;
+        txn.commit().await?;
+        AccessionWithMetadata::find_by_id(id)
+            .one(&self.db_session)
+            .await
+    }
+
+    async fn set_pdf_s3_filename(&self, id: i32, pdf_s3_filename: String) -> Result<(), DbErr> {
+        let accession = AccessionActiveModel {
+            id: ActiveValue::Unchanged(id),
+            pdf_s3_filename: ActiveValue::Set(Some(pdf_s3_filename)),
+            ..Default::default()
+        };
+        accession.update(&self.db_session).await?;
+        Ok(())
+    }
+
+    async fn set_doi(&self, id: i32, doi: String) -> Result<Option<()>, DbErr> {
+        let update = Accession::update_many()
+            .col_expr(accession::Column::Doi, Expr::value(doi))
+            .filter(accession::Column::Id.eq(id))
+            .filter(accession::Column::Doi.is_null())
+            .exec(&self.db_session)
+            .await?;
+        if update.rows_affected > 0 {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn flag_bad_crawl(&self, id: i32, reason: String) -> Result<(), DbErr> {
+        let accession = AccessionActiveModel {
+            id: ActiveValue::Unchanged(id),
+            crawl_status: ActiveValue::Set(CrawlStatus::BadCrawl),
+            crawl_quality_reason: ActiveValue::Set(Some(reason)),
+            ..Default::default()
+        };
+        accession.update(&self.db_session).await?;
+        Ok(())
+    }
+
+    async fn clear_bad_crawl(&self, id: i32) -> Result<Option<()>, DbErr> {
+        let update = Accession::update_many()
+            .col_expr(
+                accession::Column::CrawlStatus,
+                CrawlStatus::Complete.as_enum(),
+            )
+            .col_expr(
+                accession::Column::CrawlQualityReason,
+                Expr::value(Option::<String>::None),
+            )
+            .filter(accession::Column::Id.eq(id))
+            .filter(accession::Column::CrawlStatus.eq(CrawlStatus::BadCrawl))
+            .exec(&self.db_session)
+            .await?;
+        if update.rows_affected > 0 {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn list_bad_crawls(
+        &self,
+        window: PageWindow,
+        organization_id: Option<i32>,
+    ) -> Result<(Vec<AccessionWithMetadataModel>, u64), DbErr> {
+        let mut query = AccessionWithMetadata::find()
+            .filter(accessions_with_metadata::Column::CrawlStatus.eq(CrawlStatus::BadCrawl));
+        if let Some(organization_id) = organization_id {
+            query =
+                query.filter(accessions_with_metadata::Column::OrganizationId.eq(organization_id));
+        }
+        let paginator = query
+            .order_by_desc(accessions_with_metadata::Column::CrawlTimestamp)
+            .order_by_desc(accessions_with_metadata::Column::Id)
+            .paginate(&self.db_session, window.per_page);
+        let num_pages = paginator.num_pages().await?;
+        Ok((paginator.fetch_page(window.page).await?, num_pages))
+    }
+
+    async fn list_review_queue(
+        &self,
+        window: PageWindow,
+    ) -> Result<(Vec<AccessionWithMetadataModel>, u64), DbErr> {
+        let paginator = AccessionWithMetadata::find()
+            .filter(
+                Condition::any()
+                    .add(accessions_with_metadata::Column::CrawlStatus.eq(CrawlStatus::BadCrawl))
+                    .add(
+                        accessions_with_metadata::Column::PublicationState
+                            .eq(PublicationState::InReview),
+                    ),
+            )
+            .order_by_desc(accessions_with_metadata::Column::CrawlTimestamp)
+            .order_by_desc(accessions_with_metadata::Column::Id)
+            .paginate(&self.db_session, window.per_page);
+        let num_pages = paginator.num_pages().await?;
+        Ok((paginator.fetch_page(window.page).await?, num_pages))
+    }
+
+    async fn find_ids_by_canonical_url(&self, canonical_url: &str) -> Result<Vec<i32>, DbErr> {
+        Accession::find()
+            .select_only()
+            .column(accession::Column::Id)
+            .filter(accession::Column::CanonicalUrl.eq(canonical_url))
+            .order_by_asc(accession::Column::Id)
+            .into_tuple()
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn list_missing_s3_files(&self) -> Result<Vec<AccessionWithMetadataModel>, DbErr> {
+        AccessionWithMetadata::find()
+            .filter(accessions_with_metadata::Column::S3Filename.is_null())
+            .filter(accessions_with_metadata::Column::JobRunId.is_not_null())
+            .filter(
+                accessions_with_metadata::Column::DublinMetadataFormat
+                    .eq(DublinMetadataFormat::Wacz),
+            )
+            .order_by_asc(accessions_with_metadata::Column::Id)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn set_s3_filename(&self, id: i32, s3_filename: String) -> Result<(), DbErr> {
+        let accession = AccessionActiveModel {
+            id: ActiveValue::Unchanged(id),
+            s3_filename: ActiveValue::Set(Some(s3_filename)),
+            ..Default::default()
+        };
+        accession.update(&self.db_session).await?;
+        Ok(())
+    }
+
+    async fn replace_s3_filename(
+        &self,
+        id: i32,
+        previous: Option<String>,
+        s3_filename: String,
+    ) -> Result<Option<()>, DbErr> {
+        let unchanged = match previous {
+            Some(previous) => accession::Column::S3Filename.eq(previous),
+            None => accession::Column::S3Filename.is_null(),
+        };
+        let update = Accession::update_many()
+            .col_expr(accession::Column::S3Filename, Expr::value(s3_filename))
+            .filter(accession::Column::Id.eq(id))
+            .filter(unchanged)
+            .exec(&self.db_session)
+            .await?;
+        if update.rows_affected > 0 {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn list_public_captures(&self, canonical_url: &str) -> Result<Vec<Capture>, DbErr> {
+        let captures: Vec<(i32, chrono::NaiveDateTime)> = AccessionWithMetadata::find()
+            .select_only()
+            .column(accessions_with_metadata::Column::Id)
+            .column(accessions_with_metadata::Column::CrawlTimestamp)
+            .filter(accessions_with_metadata::Column::CanonicalUrl.eq(canonical_url))
+            .filter(accessions_with_metadata::Column::CrawlStatus.eq(CrawlStatus::Complete))
+            .filter(Visibility::Public.condition())
+            .order_by_asc(accessions_with_metadata::Column::CrawlTimestamp)
+            .order_by_asc(accessions_with_metadata::Column::Id)
+            .into_tuple()
+            .all(&self.db_session)
+            .await?;
+        Ok(captures
+            .into_iter()
+            .map(|(accession_id, captured_at)| Capture {
+                accession_id,
+                captured_at,
+            })
+            .collect())
+    }
+
+    async fn write_derivative(
+        &self,
+        accession_id: i32,
+        kind: DerivativeKind,
+        s3_filename: String,
+        content_type: String,
+    ) -> Result<(), DbErr> {
+        let derivative = AccessionDerivativeActiveModel {
+            id: Default::default(),
+            accession_id: ActiveValue::Set(accession_id),
+            kind: ActiveValue::Set(kind),
+            s3_filename: ActiveValue::Set(s3_filename),
+            content_type: ActiveValue::Set(content_type),
+            created_at: ActiveValue::Set(self.clock.naive_now()),
+        };
+        AccessionDerivative::insert(derivative)
+            .on_conflict(
+                OnConflict::columns([
+                    accession_derivative::Column::AccessionId,
+                    accession_derivative::Column::Kind,
+                ])
+                .update_columns([
+                    accession_derivative::Column::S3Filename,
+                    accession_derivative::Column::ContentType,
+                    accession_derivative::Column::CreatedAt,
+                ])
+                .to_owned(),
+            )
+            .exec(&self.db_session)
+            .await?;
+        Ok(())
+    }
+
+    async fn list_derivatives(
+        &self,
+        accession_id: i32,
+    ) -> Result<Vec<AccessionDerivativeModel>, DbErr> {
+        AccessionDerivative::find()
+            .filter(accession_derivative::Column::AccessionId.eq(accession_id))
+            .order_by_asc(accession_derivative::Column::Id)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn list_batch_after_id(
+        &self,
+        after_id: i32,
+        limit: u64,
+    ) -> Result<Vec<AccessionModel>, DbErr> {
+        Accession::find()
+            .filter(accession::Column::Id.gt(after_id))
+            .order_by_asc(accession::Column::Id)
+            .limit(limit)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn list_public_batch_after_id(
+        &self,
+        after_id: i32,
+        limit: u64,
+    ) -> Result<Vec<AccessionWithMetadataModel>, DbErr> {
+        AccessionWithMetadata::find()
+            .filter(accessions_with_metadata::Column::Id.gt(after_id))
+            .filter(Visibility::Public.condition())
+            .order_by_asc(accessions_with_metadata::Column::Id)
+            .limit(limit)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn count_all(&self) -> Result<u64, DbErr> {
+        Accession::find().count(&self.db_session).await
+    }
+
+    async fn count_public(&self) -> Result<u64, DbErr> {
+        AccessionWithMetadata::find()
+            .filter(Visibility::Public.condition())
+            .count(&self.db_session)
+            .await
+    }
+
+    async fn refresh_full_text_batch(&self, after_id: i32, limit: u64) -> Result<Vec<i32>, DbErr> {
+        let ids: Vec<i32> = Accession::find()
+            .select_only()
+            .column(accession::Column::Id)
+            .filter(accession::Column::Id.gt(after_id))
+            .order_by_asc(accession::Column::Id)
+            .limit(limit)
+            .into_tuple()
+            .all(&self.db_session)
+            .await?;
+        if ids.is_empty() {
+            return Ok(ids);
+        }
+        // stored generated columns are only recomputed when their row is written, so the
+        // metadata references are written back as they are
+        Accession::update_many()
+            .col_expr(
+                accession::Column::DublinMetadataEn,
+                Expr::col(accession::Column::DublinMetadataEn).into(),
+            )
+            .col_expr(
+                accession::Column::DublinMetadataAr,
+                Expr::col(accession::Column::DublinMetadataAr).into(),
+            )
+            .filter(accession::Column::Id.is_in(ids.clone()))
+            .exec(&self.db_session)
+            .await?;
+        Ok(ids)
+    }
+
+    async fn rebuild_full_text_indexes(&self) -> Result<(), DbErr> {
+        for index in FULL_TEXT_INDEXES {
+            // REINDEX CONCURRENTLY can't run inside a transaction, which a multi statement
+            // query would implicitly be, so each index gets its own
+            self.db_session
+                .execute_unprepared(&format!("REINDEX INDEX CONCURRENTLY {index}"))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn lift_lapsed_embargoes(&self, now: chrono::NaiveDateTime) -> Result<Vec<i32>, DbErr> {
+        let lifted = Accession::update_many()
+            .col_expr(
+                accession::Column::EmbargoUntil,
+                Expr::value(Option::<chrono::NaiveDateTime>::None),
+            )
+            .filter(accession::Column::EmbargoUntil.lte(now))
+            .exec_with_returning(&self.db_session)
+            .await?;
+        Ok(lifted.into_iter().map(|accession| accession.id).collect())
+    }
+
+    async fn get_publication_state(&self, id: i32) -> Result<Option<PublicationState>, DbErr> {
+        Accession::find_by_id(id)
+            .select_only()
+            .column(accession::Column::PublicationState)
+            .into_tuple()
+            .one(&self.db_session)
+            .await
+    }
+
+    async fn set_publication_state(
+        &self,
+        id: i32,
+        from: PublicationState,
+        to: PublicationState,
+    ) -> Result<Option<AccessionWithMetadataModel>, DbErr> {
+        let result = Accession::update_many()
+            .col_expr(accession::Column::PublicationState, to.as_enum())
+            .filter(accession::Column::Id.eq(id))
+            .filter(accession::Column::PublicationState.eq(from))
+            .exec(&self.db_session)
+            .await?;
+        if result.rows_affected == 0 {
+            return Ok(None);
+        }
+        AccessionWithMetadata::find_by_id(id)
+            .one(&self.db_session)
+            .await
+    }
+
+    async fn bulk_set_publication_state(
+        &self,
+        selection: AccessionSelection,
+        from: Vec<PublicationState>,
+        to: PublicationState,
+        actor_email: String,
+        reason: Option<String>,
+        organization_id: Option<i32>,
+    ) -> Result<Vec<i32>, DbErr> {
+        let txn = self.db_session.begin().await?;
+        let mut selected = match selection {
+            AccessionSelection::Ids(ids) => accession::Column::Id.is_in(ids),
+            AccessionSelection::UrlPrefix(prefix) => {
+                accession::Column::SeedUrl.like(format!("{prefix}%"))
             }
-            None => Ok(None),
+        };
+        if let Some(organization_id) = organization_id {
+            selected = selected.and(accession::Column::OrganizationId.eq(organization_id));
+        }
+        // lock the rows so the states written to the audit log are the ones replaced
+        let moved: Vec<(i32, PublicationState)> = Accession::find()
+            .select_only()
+            .column(accession::Column::Id)
+            .column(accession::Column::PublicationState)
+            .filter(selected)
+            .filter(accession::Column::PublicationState.is_in(from))
+            .order_by_asc(accession::Column::Id)
+            .lock_exclusive()
+            .into_tuple()
+            .all(&txn)
+            .await?;
+        if moved.is_empty() {
+            return Ok(vec![]);
+        }
+        let ids: Vec<i32> = moved.iter().map(|(id, _)| *id).collect();
+        Accession::update_many()
+            .col_expr(accession::Column::PublicationState, to.clone().as_enum())
+            .filter(accession::Column::Id.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+        let created_at = self.clock.naive_now();
+        let entries = moved.into_iter().map(|(id, from)| AuditLogActiveModel {
+            id: Default::default(),
+            actor_email: ActiveValue::Set(actor_email.clone()),
+            impersonator_email: ActiveValue::Set(None),
+            event: ActiveValue::Set(PUBLICATION_STATE_CHANGED.to_string()),
+            entity_type: ActiveValue::Set("accession".to_string()),
+            entity_id: ActiveValue::Set(id.to_string()),
+            details: ActiveValue::Set(Some(json!({
+                "from": from.to_value(),
+                "to": to.to_value(),
+                "reason": reason,
+            }))),
+            created_at: ActiveValue::Set(created_at),
+        });
+        AuditLog::insert_many(entries).exec(&txn).await?;
+        txn.commit().await?;
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::common::MetadataScrubbing;
+    use crate::models::request::CreateSubjectRequest;
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::repos::subjects_repo::{DBSubjectsRepo, SubjectsRepo};
+    use crate::test_db::{migrated_test_db, TestDb};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{DbBackend, QueryTrait};
+
+    async fn build_repo() -> (TestDb, DBAccessionsRepo) {
+        let db = migrated_test_db().await;
+        let repo = DBAccessionsRepo {
+            db_session: db.clone(),
+            ..Default::default()
+        };
+        (db, repo)
+    }
+
+    async fn write_subject(repo: &DBAccessionsRepo, subject: &str) -> i32 {
+        write_subject_in(repo, subject, MetadataLanguage::English).await
+    }
+
+    async fn write_subject_in(
+        repo: &DBAccessionsRepo,
+        subject: &str,
+        lang: MetadataLanguage,
+    ) -> i32 {
+        let subjects_repo = DBSubjectsRepo {
+            db_session: repo.db_session.clone(),
+            ..Default::default()
+        };
+        subjects_repo
+            .write_one(
+                CreateSubjectRequest {
+                    metadata_subject: subject.to_string(),
+                    lang,
+                },
+                DEFAULT_ORGANIZATION_ID,
+                None,
+            )
+            .await
+            .unwrap()
+            .id
+    }
+
+    fn raw_request(title: &str, subjects: Vec<i32>, is_private: bool) -> CreateAccessionRequestRaw {
+        CreateAccessionRequestRaw {
+            metadata_language: MetadataLanguage::English,
+            metadata_title: title.to_string(),
+            metadata_description: Some(format!("{title} description")),
+            metadata_time: Default::default(),
+            metadata_subjects: subjects,
+            is_private,
+            embargo_until: None,
+            content_warning: None,
+            metadata_format: DublinMetadataFormat::Wacz,
+            original_url: "https://example.com/page?utm_source=feed".to_string(),
+            s3_filename: "file.wacz".to_string(),
+            metadata_scrubbing: MetadataScrubbing::Scrub,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn writes_and_reads_raw_accessions() {
+        let (_db, repo) = build_repo().await;
+        let subject_id = write_subject(&repo, "Darfur").await;
+        let id = repo
+            .write_one_raw(
+                raw_request("Market fire", vec![subject_id], false),
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+
+        let accession = repo.get_one(id, false).await.unwrap().unwrap();
+        assert_eq!(accession.title_en.as_deref(), Some("Market fire"));
+        assert_eq!(accession.subjects_en, Some(vec!["Darfur".to_string()]));
+        assert_eq!(accession.subjects_en_ids, Some(vec![subject_id]));
+        assert_eq!(
+            accession.canonical_url.as_deref(),
+            Some("https://example.com/page")
+        );
+        assert_eq!(accession.scan_status, ScanStatus::Clean);
+        assert!(accession.metadata_scrubbed);
+        assert!(accession.has_english_metadata);
+        assert!(!accession.has_arabic_metadata);
+        assert!(repo.get_one(id, true).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn records_one_doi_per_accession() {
+        let (_db, repo) = build_repo().await;
+        let id = repo
+            .write_one_raw(
+                raw_request("Market fire", vec![], false),
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+
+        let doi = "10.1234/abcd-efgh".to_string();
+        assert_eq!(repo.set_doi(id, doi.clone()).await.unwrap(), Some(()));
+        assert_eq!(
+            repo.set_doi(id, "10.1234/other".to_string()).await.unwrap(),
+            None
+        );
+        let accession = repo.get_one(id, false).await.unwrap().unwrap();
+        assert_eq!(accession.doi, Some(doi));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn replaces_files_unless_they_changed() {
+        let (_db, repo) = build_repo().await;
+        let id = repo
+            .write_one_raw(
+                raw_request("Market fire", vec![], false),
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+
+        let replaced = repo
+            .replace_s3_filename(
+                id,
+                Some("file.wacz".to_string()),
+                "2026/10/corrected.wacz".to_string(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(replaced, Some(()));
+        let stale = repo
+            .replace_s3_filename(
+                id,
+                Some("file.wacz".to_string()),
+                "2026/10/other.wacz".to_string(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(stale, None);
+        let accession = repo.get_one(id, false).await.unwrap().unwrap();
+        assert_eq!(
+            accession.s3_filename.as_deref(),
+            Some("2026/10/corrected.wacz")
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn flags_and_clears_bad_crawls() {
+        let (_db, repo) = build_repo().await;
+        let id = repo
+            .write_one_raw(
+                raw_request("Market fire", vec![], true),
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        let window = PageWindow::new(0, 20);
+        assert!(repo
+            .list_bad_crawls(window, None)
+            .await
+            .unwrap()
+            .0
+            .is_empty());
+        assert_eq!(repo.clear_bad_crawl(id).await.unwrap(), None);
+
+        repo.flag_bad_crawl(id, "Crawl captured no pages".to_string())
+            .await
+            .unwrap();
+        let (flagged, num_pages) = repo.list_bad_crawls(window, None).await.unwrap();
+        assert_eq!(num_pages, 1);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].crawl_status, CrawlStatus::BadCrawl);
+        assert_eq!(
+            flagged[0].crawl_quality_reason,
+            Some("Crawl captured no pages".to_string())
+        );
+        assert!(repo
+            .list_bad_crawls(window, Some(DEFAULT_ORGANIZATION_ID + 1))
+            .await
+            .unwrap()
+            .0
+            .is_empty());
+        let (queue, _) = repo.list_review_queue(window).await.unwrap();
+        assert!(queue.iter().any(|accession| accession.id == id));
+
+        assert_eq!(repo.clear_bad_crawl(id).await.unwrap(), Some(()));
+        let accession = repo.get_one(id, true).await.unwrap().unwrap();
+        assert_eq!(accession.crawl_status, CrawlStatus::Complete);
+        assert_eq!(accession.crawl_quality_reason, None);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn rolls_back_failed_writes() {
+        let (_db, repo) = build_repo().await;
+        let result = repo
+            .write_one_raw(
+                raw_request("Missing subject", vec![9999], false),
+                ScanStatus::NotScanned,
+                false,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await;
+
+        assert!(result.is_err());
+        let metadata_rows = DublinMetadataEn::find()
+            .count(&repo.db_session)
+            .await
+            .unwrap();
+        assert_eq!(metadata_rows, 0);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn finds_and_backfills_accessions_missing_s3_files() {
+        let (_db, repo) = build_repo().await;
+        let subject_id = write_subject(&repo, "Omdurman").await;
+        let legacy_id = repo
+            .write_one(
+                CreateAccessionRequest {
+                    url: "https://example.com/legacy".to_string(),
+                    metadata_en: Some(CreateMetadataRequest {
+                        title: "Legacy crawl".to_string(),
+                        description: None,
+                        subjects: vec![subject_id],
+                    }),
+                    metadata_ar: None,
+                    metadata_time: Default::default(),
+                    browser_profile: None,
+                    crawler: None,
+                    capture_mode: Default::default(),
+                    is_private: false,
+                    embargo_until: None,
+                    content_warning: None,
+                    metadata_format: DublinMetadataFormat::Wacz,
+                    s3_filename: None,
+                },
+                Some(Uuid::new_v4()),
+                Uuid::new_v4(),
+                "job-run".to_string(),
+                CrawlStatus::Complete,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        repo.write_one_raw(
+            raw_request("Already in S3", vec![subject_id], false),
+            ScanStatus::Clean,
+            false,
+            DEFAULT_ORGANIZATION_ID,
+        )
+        .await
+        .unwrap();
+
+        let missing = repo.list_missing_s3_files().await.unwrap();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].id, legacy_id);
+        assert_eq!(missing[0].job_run_id.as_deref(), Some("job-run"));
+
+        repo.set_s3_filename(legacy_id, "2026/10/legacy.wacz".to_string())
+            .await
+            .unwrap();
+        assert!(repo.list_missing_s3_files().await.unwrap().is_empty());
+        let accession = repo.get_one(legacy_id, false).await.unwrap().unwrap();
+        assert_eq!(
+            accession.s3_filename.as_deref(),
+            Some("2026/10/legacy.wacz")
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn lists_public_captures_of_a_url() {
+        let (_db, repo) = build_repo().await;
+        let subject_id = write_subject(&repo, "Khartoum").await;
+        let mut ids = vec![];
+        for (title, is_private) in [("First", false), ("Hidden", true), ("Second", false)] {
+            let id = repo
+                .write_one_raw(
+                    raw_request(title, vec![subject_id], is_private),
+                    ScanStatus::Clean,
+                    false,
+                    DEFAULT_ORGANIZATION_ID,
+                )
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+
+        let captures = repo
+            .list_public_captures("https://example.com/page")
+            .await
+            .unwrap();
+        let capture_ids: Vec<i32> = captures.iter().map(|c| c.accession_id).collect();
+        assert_eq!(capture_ids, vec![ids[0], ids[2]]);
+        assert!(repo
+            .list_public_captures("https://example.com/other")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn filters_listed_accessions() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        let khartoum = write_subject(&repo, "Khartoum").await;
+        let market = repo
+            .write_one_raw(
+                raw_request("Market fire", vec![darfur], false),
+                ScanStatus::NotScanned,
+                false,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        repo.write_one_raw(
+            raw_request("Bridge closure", vec![khartoum], false),
+            ScanStatus::NotScanned,
+            false,
+            DEFAULT_ORGANIZATION_ID,
+        )
+        .await
+        .unwrap();
+        repo.write_one_raw(
+            raw_request("Private market notes", vec![darfur], true),
+            ScanStatus::NotScanned,
+            false,
+            DEFAULT_ORGANIZATION_ID,
+        )
+        .await
+        .unwrap();
+
+        let ids = |rows: Vec<AccessionWithMetadataModel>| {
+            rows.into_iter().map(|row| row.id).collect::<Vec<_>>()
+        };
+        let (rows, num_pages) = repo
+            .list_paginated(AccessionPaginationWithPrivate {
+                metadata_subjects: vec![darfur],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(ids(rows), vec![market]);
+        assert_eq!(num_pages, Some(1));
+
+        let (rows, _) = repo
+            .list_paginated(AccessionPaginationWithPrivate {
+                query_term: Some("market".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(ids(rows), vec![market]);
+
+        let geneina = repo
+            .write_one_raw(
+                raw_request("Displacement from Genēina", vec![], false),
+                ScanStatus::NotScanned,
+                false,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        let (rows, _) = repo
+            .list_paginated(AccessionPaginationWithPrivate {
+                query_term: Some("Geneina".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(ids(rows), vec![geneina]);
+
+        let (rows, _) = repo
+            .list_paginated(AccessionPaginationWithPrivate {
+                is_private: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn buckets_listed_accessions_by_date() {
+        let (_db, repo) = build_repo().await;
+        let dated = |title: &str, date: &str| CreateAccessionRequestRaw {
+            metadata_time: chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+                .unwrap(),
+            ..raw_request(title, vec![], false)
+        };
+        for request in [
+            dated("Market fire", "2023-04-03 10:00:00"),
+            dated("Market reopens", "2023-04-28 10:00:00"),
+            dated("Market rebuilt", "2023-06-01 10:00:00"),
+            dated("Bridge closure", "2023-04-10 10:00:00"),
+        ] {
+            repo.write_one_raw(
+                request,
+                ScanStatus::NotScanned,
+                false,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        }
+        let market = || AccessionPaginationWithPrivate {
+            query_term: Some("market".to_string()),
+            ..Default::default()
+        };
+        let midnight = |date: &str| {
+            chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        };
+
+        assert_eq!(
+            repo.timeline(market(), TimelineInterval::Month)
+                .await
+                .unwrap(),
+            vec![
+                TimelineBucket {
+                    starts_at: midnight("2023-04-01"),
+                    count: 2
+                },
+                TimelineBucket {
+                    starts_at: midnight("2023-06-01"),
+                    count: 1
+                },
+            ]
+        );
+        let weeks = repo
+            .timeline(market(), TimelineInterval::Week)
+            .await
+            .unwrap();
+        assert_eq!(
+            weeks
+                .iter()
+                .map(|bucket| bucket.starts_at)
+                .collect::<Vec<_>>(),
+            vec![
+                midnight("2023-04-03"),
+                midnight("2023-04-24"),
+                midnight("2023-05-29")
+            ]
+        );
+    }
+
+    #[test]
+    fn listing_leaves_out_full_text_columns() {
+        let sql = filtered_accessions(AccessionPaginationWithPrivate {
+            query_term: Some("market".to_string()),
+            ..Default::default()
+        })
+        .build(DbBackend::Postgres)
+        .to_string();
+        let (selected, filtered) = sql.split_once(" FROM ").unwrap();
+        assert!(!selected.contains("full_text"));
+        assert!(filtered.contains("full_text_en"));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn counts_pages_while_listing() {
+        let (_db, repo) = build_repo().await;
+        let subject_id = write_subject(&repo, "Khartoum").await;
+        for title in ["First", "Second", "Third"] {
+            repo.write_one_raw(
+                raw_request(title, vec![subject_id], false),
+                ScanStatus::NotScanned,
+                false,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        }
+        let page = |page, count| AccessionPaginationWithPrivate {
+            page,
+            per_page: 2,
+            count,
+            ..Default::default()
+        };
+
+        let (rows, num_pages) = repo.list_paginated(page(0, true)).await.unwrap();
+        assert_eq!((rows.len(), num_pages), (2, Some(2)));
+        let (rows, num_pages) = repo.list_paginated(page(1, false)).await.unwrap();
+        assert_eq!((rows.len(), num_pages), (1, None));
+        let (rows, num_pages) = repo.list_paginated(page(5, true)).await.unwrap();
+        assert_eq!((rows.len(), num_pages), (0, Some(2)));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn updates_accession_metadata() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        let khartoum = write_subject(&repo, "Khartoum").await;
+        let id = repo
+            .write_one_raw(
+                raw_request("Market fire", vec![darfur], false),
+                ScanStatus::NotScanned,
+                false,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+
+        let updated = repo
+            .update_one(
+                id,
+                UpdateAccessionRequest {
+                    metadata_en: Some(UpdateMetadataRequest {
+                        title: Some("Market fire, day two".to_string()),
+                        description: Some(None),
+                        subjects: Some(vec![khartoum]),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.title_en.as_deref(), Some("Market fire, day two"));
+        assert_eq!(updated.description_en, None);
+        assert_eq!(updated.subjects_en_ids, Some(vec![khartoum]));
+        // metadata edits leave the publication state alone
+        assert_eq!(updated.publication_state, PublicationState::Published);
+        assert!(!updated.is_private);
+
+        // fields left out of an update are kept
+        let updated = repo
+            .update_one(
+                id,
+                UpdateAccessionRequest {
+                    metadata_en: Some(UpdateMetadataRequest {
+                        description: Some(Some("Second day of the fire".to_string())),
+                        ..Default::default()
+                    }),
+                    revision: 1,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.title_en.as_deref(), Some("Market fire, day two"));
+        assert_eq!(
+            updated.description_en.as_deref(),
+            Some("Second day of the fire")
+        );
+        assert_eq!(updated.subjects_en_ids, Some(vec![khartoum]));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn refuses_updates_based_on_a_stale_revision() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        let id = repo
+            .write_one_raw(
+                raw_request("Market fire", vec![darfur], false),
+                ScanStatus::NotScanned,
+                false,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        let retitle = |title: &str| UpdateAccessionRequest {
+            metadata_en: Some(UpdateMetadataRequest {
+                title: Some(title.to_string()),
+                ..Default::default()
+            }),
+            revision: 0,
+            ..Default::default()
+        };
+
+        let updated = repo
+            .update_one(id, retitle("Market fire in Nyala"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.revision, 1);
+
+        // a second curator who also read revision 0 doesn't overwrite the first
+        let stale = repo.update_one(id, retitle("Nyala market fire")).await;
+        assert!(matches!(stale, Err(DbErr::RecordNotUpdated)));
+        let unchanged = repo.get_one(id, false).await.unwrap().unwrap();
+        assert_eq!(unchanged.title_en.as_deref(), Some("Market fire in Nyala"));
+        assert_eq!(unchanged.revision, 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn updates_both_languages_of_a_mixed_language_accession() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        let darfur_ar = write_subject_in(&repo, "دارفور", MetadataLanguage::Arabic).await;
+        let id = repo
+            .write_one_raw(
+                raw_request("Market fire", vec![darfur], false),
+                ScanStatus::NotScanned,
+                false,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+
+        // adds the Arabic metadata and corrects the English title in one go
+        let updated = repo
+            .update_one(
+                id,
+                UpdateAccessionRequest {
+                    metadata_en: Some(UpdateMetadataRequest {
+                        title: Some("Market fire in Nyala".to_string()),
+                        ..Default::default()
+                    }),
+                    metadata_ar: Some(UpdateMetadataRequest {
+                        title: Some("حريق سوق نيالا".to_string()),
+                        subjects: Some(vec![darfur_ar]),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.title_en.as_deref(), Some("Market fire in Nyala"));
+        assert_eq!(updated.subjects_en_ids, Some(vec![darfur]));
+        assert_eq!(updated.title_ar.as_deref(), Some("حريق سوق نيالا"));
+        assert_eq!(updated.subjects_ar_ids, Some(vec![darfur_ar]));
+
+        // a failure in one language rolls back the other
+        let failed = repo
+            .update_one(
+                id,
+                UpdateAccessionRequest {
+                    metadata_en: Some(UpdateMetadataRequest {
+                        title: Some("Nyala market fire".to_string()),
+                        ..Default::default()
+                    }),
+                    metadata_ar: Some(UpdateMetadataRequest {
+                        subjects: Some(vec![-1]),
+                        ..Default::default()
+                    }),
+                    revision: 1,
+                    ..Default::default()
+                },
+            )
+            .await;
+        assert!(failed.is_err());
+        let unchanged = repo.get_one(id, false).await.unwrap().unwrap();
+        assert_eq!(unchanged.title_en.as_deref(), Some("Market fire in Nyala"));
+        assert_eq!(unchanged.subjects_ar_ids, Some(vec![darfur_ar]));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn flags_machine_translated_metadata_until_edited() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        let id = repo
+            .write_one_raw(
+                raw_request("Market fire", vec![darfur], false),
+                ScanStatus::NotScanned,
+                false,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        let draft = |machine_translated, revision| UpdateAccessionRequest {
+            metadata_ar: Some(UpdateMetadataRequest {
+                title: Some("حريق السوق".to_string()),
+                machine_translated,
+                ..Default::default()
+            }),
+            revision,
+            ..Default::default()
+        };
+
+        let translated = repo.update_one(id, draft(true, 0)).await.unwrap().unwrap();
+        assert!(translated.has_arabic_metadata);
+        // adding Arabic metadata leaves the English metadata alone
+        assert_eq!(translated.title_en.as_deref(), Some("Market fire"));
+        assert_eq!(translated.subjects_en_ids, Some(vec![darfur]));
+        assert!(translated.machine_translated_ar);
+        assert!(!translated.machine_translated_en);
+        assert_eq!(translated.subjects_ar_ids, None);
+
+        let reviewed = repo.update_one(id, draft(false, 1)).await.unwrap().unwrap();
+        assert!(!reviewed.machine_translated_ar);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn moves_accessions_between_publication_states() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        let id = repo
+            .write_one_raw(
+                raw_request("Testimony", vec![darfur], true),
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_publication_state(id).await.unwrap(),
+            Some(PublicationState::Draft)
+        );
+
+        let published = repo
+            .set_publication_state(id, PublicationState::Draft, PublicationState::Published)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(published.publication_state, PublicationState::Published);
+        assert!(!published.is_private);
+        assert!(repo.get_one(id, false).await.unwrap().is_some());
+
+        // the accession already left draft, so a second curator's move is refused
+        let stale = repo
+            .set_publication_state(id, PublicationState::Draft, PublicationState::InReview)
+            .await
+            .unwrap();
+        assert_eq!(stale, None);
+        assert_eq!(repo.get_publication_state(-1).await.unwrap(), None);
+
+        let (in_review, _) = repo
+            .list_paginated(AccessionPaginationWithPrivate {
+                is_private: true,
+                publication_state: Some(PublicationState::InReview),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(in_review.is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn bulk_moves_accessions_with_an_audit_entry_each() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        let mut ids = vec![];
+        for (url, is_private) in [
+            ("https://unsafe.example.com/a", false),
+            ("https://unsafe.example.com/b", true),
+            ("https://other.example.com/c", false),
+        ] {
+            ids.push(
+                repo.write_one_raw(
+                    CreateAccessionRequestRaw {
+                        original_url: url.to_string(),
+                        ..raw_request("Eyewitness video", vec![darfur], is_private)
+                    },
+                    ScanStatus::Clean,
+                    true,
+                    DEFAULT_ORGANIZATION_ID,
+                )
+                .await
+                .unwrap(),
+            );
+        }
+
+        let moved = repo
+            .bulk_set_publication_state(
+                AccessionSelection::UrlPrefix("https://unsafe.example.com/".to_string()),
+                vec![PublicationState::Published],
+                PublicationState::Withdrawn,
+                "admin@example.com".to_string(),
+                Some("Source at risk".to_string()),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(moved, vec![ids[0]]);
+        assert_eq!(
+            repo.get_publication_state(ids[0]).await.unwrap(),
+            Some(PublicationState::Withdrawn)
+        );
+        assert_eq!(
+            repo.get_publication_state(ids[2]).await.unwrap(),
+            Some(PublicationState::Published)
+        );
+
+        let entries = AuditLog::find().all(&repo.db_session).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].actor_email, "admin@example.com");
+        assert_eq!(entries[0].event, PUBLICATION_STATE_CHANGED);
+        assert_eq!(entries[0].entity_id, ids[0].to_string());
+        assert_eq!(
+            entries[0].details,
+            Some(json!({"from": "published", "to": "withdrawn", "reason": "Source at risk"}))
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn deletes_accessions_with_their_metadata() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        let id = repo
+            .write_one_raw(
+                raw_request("Market fire", vec![darfur], false),
+                ScanStatus::NotScanned,
+                false,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+
+        assert!(repo.delete_one(id).await.unwrap().is_some());
+        assert!(repo.get_one(id, false).await.unwrap().is_none());
+        let metadata_rows = DublinMetadataEn::find()
+            .count(&repo.db_session)
+            .await
+            .unwrap();
+        assert_eq!(metadata_rows, 0);
+        assert!(repo.delete_one(id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn replaces_derivatives_of_the_same_kind() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        let id = repo
+            .write_one_raw(
+                raw_request("Market fire", vec![darfur], false),
+                ScanStatus::NotScanned,
+                false,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        for key in ["first.webp", "second.webp"] {
+            repo.write_derivative(
+                id,
+                DerivativeKind::Thumbnail,
+                key.to_string(),
+                "image/webp".to_string(),
+            )
+            .await
+            .unwrap();
+        }
+
+        let derivatives = repo.list_derivatives(id).await.unwrap();
+        assert_eq!(derivatives.len(), 1);
+        assert_eq!(derivatives[0].s3_filename, "second.webp");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn lists_batches_in_id_order() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        let mut ids = vec![];
+        for title in ["First", "Second", "Third"] {
+            ids.push(
+                repo.write_one_raw(
+                    raw_request(title, vec![darfur], title == "Second"),
+                    ScanStatus::NotScanned,
+                    false,
+                    DEFAULT_ORGANIZATION_ID,
+                )
+                .await
+                .unwrap(),
+            );
         }
+
+        let batch_ids =
+            |batch: Vec<AccessionModel>| batch.into_iter().map(|row| row.id).collect::<Vec<_>>();
+        let first = repo.list_batch_after_id(0, 2).await.unwrap();
+        assert_eq!(batch_ids(first), ids[..2].to_vec());
+        let rest = repo.list_batch_after_id(ids[1], 2).await.unwrap();
+        assert_eq!(batch_ids(rest), ids[2..].to_vec());
+        assert!(repo
+            .list_batch_after_id(ids[2], 2)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let public = repo.list_public_batch_after_id(0, 2).await.unwrap();
+        assert_eq!(
+            public.iter().map(|row| row.id).collect::<Vec<_>>(),
+            vec![ids[0], ids[2]]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn refreshes_full_text_columns_in_batches() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        let mut ids = vec![];
+        for title in ["First", "Second", "Third"] {
+            ids.push(
+                repo.write_one_raw(
+                    raw_request(title, vec![darfur], false),
+                    ScanStatus::NotScanned,
+                    false,
+                    DEFAULT_ORGANIZATION_ID,
+                )
+                .await
+                .unwrap(),
+            );
+        }
+        assert_eq!(repo.count_all().await.unwrap(), 3);
+
+        assert_eq!(
+            repo.refresh_full_text_batch(0, 2).await.unwrap(),
+            ids[..2].to_vec()
+        );
+        assert_eq!(
+            repo.refresh_full_text_batch(ids[1], 2).await.unwrap(),
+            ids[2..].to_vec()
+        );
+        assert!(repo
+            .refresh_full_text_batch(ids[2], 2)
+            .await
+            .unwrap()
+            .is_empty());
+        repo.rebuild_full_text_indexes().await.unwrap();
+
+        let (rows, _) = repo
+            .list_paginated(AccessionPaginationWithPrivate {
+                query_term: Some("second".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].id, ids[1]);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn hides_embargoed_accessions_until_the_embargo_lapses() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        // the accessions view compares embargoes with the database's own clock
+        let now = chrono::Utc::now().naive_utc();
+        let mut ids = vec![];
+        for embargo_until in [
+            now + chrono::Duration::days(1),
+            now - chrono::Duration::days(1),
+        ] {
+            ids.push(
+                repo.write_one_raw(
+                    CreateAccessionRequestRaw {
+                        embargo_until: Some(embargo_until),
+                        ..raw_request("Source statement", vec![darfur], false)
+                    },
+                    ScanStatus::Clean,
+                    true,
+                    DEFAULT_ORGANIZATION_ID,
+                )
+                .await
+                .unwrap(),
+            );
+        }
+        let (pending, lapsed) = (ids[0], ids[1]);
+        assert!(repo.get_one(pending, false).await.unwrap().is_none());
+        // visibility follows the embargo time, lifting only clears it
+        assert!(repo.get_one(lapsed, false).await.unwrap().is_some());
+        let (public_rows, _) = repo
+            .list_paginated(AccessionPaginationWithPrivate::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            public_rows.iter().map(|row| row.id).collect::<Vec<_>>(),
+            vec![lapsed]
+        );
+
+        assert_eq!(repo.lift_lapsed_embargoes(now).await.unwrap(), vec![lapsed]);
+        let lifted = repo.get_one(lapsed, false).await.unwrap().unwrap();
+        assert_eq!(lifted.embargo_until, None);
+        let still_embargoed = repo.get_one(pending, true).await.unwrap().unwrap();
+        assert!(still_embargoed.embargo_until.is_some());
+        assert!(repo.lift_lapsed_embargoes(now).await.unwrap().is_empty());
+        assert_eq!(
+            repo.lift_lapsed_embargoes(now + chrono::Duration::days(2))
+                .await
+                .unwrap(),
+            vec![pending]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn filters_accessions_by_content_warning() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur").await;
+        let flagged = repo
+            .write_one_raw(
+                CreateAccessionRequestRaw {
+                    content_warning: Some(ContentWarning::GraphicViolence),
+                    ..raw_request("Aftermath", vec![darfur], false)
+                },
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        let unflagged = repo
+            .write_one_raw(
+                raw_request("Market day", vec![darfur], false),
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+
+        let list_ids = |has_content_warning| {
+            let repo = repo.clone();
+            async move {
+                let (rows, _) = repo
+                    .list_paginated(AccessionPaginationWithPrivate {
+                        has_content_warning,
+                        ..Default::default()
+                    })
+                    .await
+                    .unwrap();
+                let mut ids: Vec<i32> = rows.into_iter().map(|row| row.id).collect();
+                ids.sort();
+                ids
+            }
+        };
+        assert_eq!(list_ids(None).await, vec![flagged, unflagged]);
+        assert_eq!(list_ids(Some(true)).await, vec![flagged]);
+        assert_eq!(list_ids(Some(false)).await, vec![unflagged]);
+        let accession = repo.get_one(flagged, false).await.unwrap().unwrap();
+        assert_eq!(
+            accession.content_warning,
+            Some(ContentWarning::GraphicViolence)
+        );
     }
 }
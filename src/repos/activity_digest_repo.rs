@@ -0,0 +1,188 @@
+//! Repository module for the weekly activity digest.
+//!
+//! See [`crate::activity_digest`] for what the digest covers. Whether a user gets it is
+//! set with [`crate::repos::auth_repo::AuthRepo::set_activity_digest`].
+
+use crate::activity_digest::{CRAWL_COMPLETED, CRAWL_FAILED};
+use ::entity::archive_user::Entity as ArchiveUser;
+use ::entity::archive_user::Model as ArchiveUserModel;
+use ::entity::audit_log::Entity as AuditLog;
+use ::entity::audit_log::Model as AuditLogModel;
+use ::entity::sea_orm_active_enums::PublicationState;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use entity::{accession, archive_user, audit_log};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
+use uuid::Uuid;
+
+/// Repository implementation for database operations on activity digests.
+#[derive(Debug, Clone, Default)]
+pub struct DBActivityDigestRepo {
+    pub db_session: DatabaseConnection,
+}
+
+/// Defines the interface for activity digest database operations.
+#[async_trait]
+pub trait ActivityDigestRepo: Send + Sync {
+    /// Lists active users who opted in to the digest and haven't had one since a time.
+    ///
+    /// # Arguments
+    /// * `sent_before` - Users last sent a digest after this aren't due another yet
+    async fn list_due_users(
+        &self,
+        sent_before: NaiveDateTime,
+    ) -> Result<Vec<ArchiveUserModel>, DbErr>;
+
+    /// Lists the outcomes of the crawls a user requested since a time, oldest first.
+    ///
+    /// # Arguments
+    /// * `email` - Email of the user who requested the crawls
+    /// * `since` - Only list crawls that finished after this
+    async fn list_crawl_activity(
+        &self,
+        email: &str,
+        since: NaiveDateTime,
+    ) -> Result<Vec<AuditLogModel>, DbErr>;
+
+    /// Lists the accessions of an organization that are waiting for a researcher to review.
+    ///
+    /// # Arguments
+    /// * `organization_id` - The organization to list accessions of
+    ///
+    /// # Returns
+    /// The accession IDs, oldest first
+    async fn list_awaiting_review(&self, organization_id: i32) -> Result<Vec<i32>, DbErr>;
+
+    /// Records that a user was sent their digest.
+    ///
+    /// # Arguments
+    /// * `user_id` - The ID of the user
+    /// * `sent_at` - When the digest was sent
+    async fn mark_sent(&self, user_id: Uuid, sent_at: NaiveDateTime) -> Result<(), DbErr>;
+}
+
+#[async_trait]
+impl ActivityDigestRepo for DBActivityDigestRepo {
+    async fn list_due_users(
+        &self,
+        sent_before: NaiveDateTime,
+    ) -> Result<Vec<ArchiveUserModel>, DbErr> {
+        ArchiveUser::find()
+            .filter(archive_user::Column::IsActive.eq(true))
+            .filter(archive_user::Column::ActivityDigest.eq(true))
+            .filter(
+                Condition::any()
+                    .add(archive_user::Column::ActivityDigestSentAt.is_null())
+                    .add(archive_user::Column::ActivityDigestSentAt.lt(sent_before)),
+            )
+            .order_by_asc(archive_user::Column::Email)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn list_crawl_activity(
+        &self,
+        email: &str,
+        since: NaiveDateTime,
+    ) -> Result<Vec<AuditLogModel>, DbErr> {
+        AuditLog::find()
+            .filter(audit_log::Column::ActorEmail.eq(email))
+            .filter(audit_log::Column::Event.is_in([CRAWL_COMPLETED, CRAWL_FAILED]))
+            .filter(audit_log::Column::CreatedAt.gt(since))
+            .order_by_asc(audit_log::Column::Id)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn list_awaiting_review(&self, organization_id: i32) -> Result<Vec<i32>, DbErr> {
+        accession::Entity::find()
+            .select_only()
+            .column(accession::Column::Id)
+            .filter(accession::Column::OrganizationId.eq(organization_id))
+            .filter(accession::Column::PublicationState.eq(PublicationState::InReview))
+            .order_by_asc(accession::Column::Id)
+            .into_tuple()
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn mark_sent(&self, user_id: Uuid, sent_at: NaiveDateTime) -> Result<(), DbErr> {
+        ArchiveUser::update_many()
+            .col_expr(
+                archive_user::Column::ActivityDigestSentAt,
+                Expr::value(sent_at),
+            )
+            .filter(archive_user::Column::Id.eq(user_id))
+            .exec(&self.db_session)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repos::audit_log_repo::{AuditEntry, AuditLogRepo, DBAuditLogRepo};
+    use crate::test_db::migrated_test_db;
+    use ::entity::archive_user::ActiveModel as ArchiveUserActiveModel;
+    use ::entity::sea_orm_active_enums::Role;
+    use chrono::{Duration, Utc};
+    use pretty_assertions::assert_eq;
+    use sea_orm::{ActiveModelTrait, ActiveValue};
+    use serde_json::json;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn lists_due_users_and_their_crawls() {
+        let db = migrated_test_db().await;
+        let db_session = db.clone();
+        let repo = DBActivityDigestRepo {
+            db_session: db_session.clone(),
+        };
+        let email = format!("{}@example.com", Uuid::new_v4());
+        let user_id = ArchiveUserActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            email: ActiveValue::Set(email.clone()),
+            is_active: ActiveValue::Set(true),
+            role: ActiveValue::Set(Role::Researcher),
+            email_status: ActiveValue::NotSet,
+            organization_id: ActiveValue::NotSet,
+            activity_digest: ActiveValue::Set(true),
+            activity_digest_sent_at: ActiveValue::NotSet,
+        }
+        .insert(&db_session)
+        .await
+        .unwrap()
+        .id;
+        let week_ago = Utc::now().naive_utc() - Duration::days(7);
+        let due = |users: Vec<ArchiveUserModel>| users.iter().any(|user| user.id == user_id);
+        assert!(due(repo.list_due_users(week_ago).await.unwrap()));
+
+        DBAuditLogRepo {
+            db_session,
+            ..Default::default()
+        }
+        .record(AuditEntry {
+            actor_email: email.clone(),
+            impersonator_email: None,
+            event: CRAWL_FAILED,
+            entity_type: "crawl",
+            entity_id: "https://example.com".to_string(),
+            details: Some(json!({"url": "https://example.com", "reason": "Timed out"})),
+        })
+        .await
+        .unwrap();
+        let activity = repo.list_crawl_activity(&email, week_ago).await.unwrap();
+        assert_eq!(activity.len(), 1);
+        assert_eq!(activity[0].event, CRAWL_FAILED);
+
+        repo.mark_sent(user_id, Utc::now().naive_utc())
+            .await
+            .unwrap();
+        assert!(!due(repo.list_due_users(week_ago).await.unwrap()));
+    }
+}
@@ -0,0 +1,251 @@
+//! Repository module for the audit log.
+//!
+//! Entries record who did something sensitive to what. In support mode the user an admin
+//! is impersonating is the actor, and the admin is kept alongside them so every action can
+//! be traced to both.
+//!
+//! Admins search the log through [`AuditLogRepo::list`] and [`AuditLogRepo::export`].
+
+use crate::clock::SharedClock;
+use crate::repos::pagination::PageWindow;
+use ::entity::audit_log::ActiveModel as AuditLogActiveModel;
+use ::entity::audit_log::Entity as AuditLog;
+use ::entity::audit_log::Model as AuditLogModel;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use entity::audit_log;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select,
+};
+use serde_json::Value;
+
+/// Repository implementation for database operations on the audit log.
+#[derive(Debug, Clone, Default)]
+pub struct DBAuditLogRepo {
+    pub db_session: DatabaseConnection,
+    /// Tells the time entries are recorded at
+    pub clock: SharedClock,
+}
+
+/// One thing that happened, for the audit log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// Email of the user who did it
+    pub actor_email: String,
+    /// Email of the admin impersonating the actor, if they were
+    pub impersonator_email: Option<String>,
+    pub event: &'static str,
+    /// What kind of thing it happened to, e.g. `accession`
+    pub entity_type: &'static str,
+    pub entity_id: String,
+    /// Anything else worth keeping about it
+    pub details: Option<Value>,
+}
+
+/// Which audit log entries to search for. Unset fields match every entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditLogFilter {
+    pub entity_type: Option<String>,
+    /// Only useful together with `entity_type`, since IDs of different kinds of things overlap
+    pub entity_id: Option<String>,
+    /// Matches the actor or the admin impersonating them
+    pub actor: Option<String>,
+    pub event: Option<String>,
+    pub date_from: Option<NaiveDateTime>,
+    pub date_to: Option<NaiveDateTime>,
+}
+
+impl AuditLogFilter {
+    fn apply(&self, query: Select<AuditLog>) -> Select<AuditLog> {
+        let mut condition = Condition::all();
+        if let Some(entity_type) = &self.entity_type {
+            condition = condition.add(audit_log::Column::EntityType.eq(entity_type));
+        }
+        if let Some(entity_id) = &self.entity_id {
+            condition = condition.add(audit_log::Column::EntityId.eq(entity_id));
+        }
+        if let Some(actor) = &self.actor {
+            condition = condition.add(
+                Condition::any()
+                    .add(audit_log::Column::ActorEmail.eq(actor))
+                    .add(audit_log::Column::ImpersonatorEmail.eq(actor)),
+            );
+        }
+        if let Some(event) = &self.event {
+            condition = condition.add(audit_log::Column::Event.eq(event));
+        }
+        if let Some(date_from) = self.date_from {
+            condition = condition.add(audit_log::Column::CreatedAt.gte(date_from));
+        }
+        if let Some(date_to) = self.date_to {
+            condition = condition.add(audit_log::Column::CreatedAt.lte(date_to));
+        }
+        query
+            .filter(condition)
+            .order_by_desc(audit_log::Column::CreatedAt)
+            .order_by_desc(audit_log::Column::Id)
+    }
+}
+
+/// Defines the interface for audit log database operations.
+#[async_trait]
+pub trait AuditLogRepo: Send + Sync {
+    /// Writes an entry to the audit log.
+    async fn record(&self, entry: AuditEntry) -> Result<(), DbErr>;
+
+    /// Lists entries matching a filter, most recent first.
+    ///
+    /// # Arguments
+    /// * `filter` - Which entries to list
+    /// * `window` - The page to list
+    ///
+    /// # Returns
+    /// The page alongside the total number of pages
+    async fn list(
+        &self,
+        filter: AuditLogFilter,
+        window: PageWindow,
+    ) -> Result<(Vec<AuditLogModel>, u64), DbErr>;
+
+    /// Lists entries matching a filter for exporting, most recent first.
+    ///
+    /// # Arguments
+    /// * `filter` - Which entries to list
+    /// * `limit` - The most entries to list
+    async fn export(&self, filter: AuditLogFilter, limit: u64)
+        -> Result<Vec<AuditLogModel>, DbErr>;
+}
+
+#[async_trait]
+impl AuditLogRepo for DBAuditLogRepo {
+    async fn record(&self, entry: AuditEntry) -> Result<(), DbErr> {
+        AuditLogActiveModel {
+            id: Default::default(),
+            actor_email: ActiveValue::Set(entry.actor_email),
+            impersonator_email: ActiveValue::Set(entry.impersonator_email),
+            event: ActiveValue::Set(entry.event.to_string()),
+            entity_type: ActiveValue::Set(entry.entity_type.to_string()),
+            entity_id: ActiveValue::Set(entry.entity_id),
+            details: ActiveValue::Set(entry.details),
+            created_at: ActiveValue::Set(self.clock.naive_now()),
+        }
+        .insert(&self.db_session)
+        .await?;
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        filter: AuditLogFilter,
+        window: PageWindow,
+    ) -> Result<(Vec<AuditLogModel>, u64), DbErr> {
+        let paginator = filter
+            .apply(AuditLog::find())
+            .paginate(&self.db_session, window.per_page);
+        let num_pages = paginator.num_pages().await?;
+        Ok((paginator.fetch_page(window.page).await?, num_pages))
+    }
+
+    async fn export(
+        &self,
+        filter: AuditLogFilter,
+        limit: u64,
+    ) -> Result<Vec<AuditLogModel>, DbErr> {
+        filter
+            .apply(AuditLog::find())
+            .limit(limit)
+            .all(&self.db_session)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db::migrated_test_db;
+    use chrono::Utc;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn records_entries_with_both_identities() {
+        let db = migrated_test_db().await;
+        let repo = DBAuditLogRepo {
+            db_session: db.clone(),
+            ..Default::default()
+        };
+        repo.record(AuditEntry {
+            actor_email: "researcher@example.com".to_string(),
+            impersonator_email: Some("admin@example.com".to_string()),
+            event: "private_file_accessed",
+            entity_type: "accession",
+            entity_id: "7".to_string(),
+            details: Some(json!({"key": "some_file.wacz"})),
+        })
+        .await
+        .unwrap();
+
+        let entries = AuditLog::find().all(&repo.db_session).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].actor_email, "researcher@example.com");
+        assert_eq!(
+            entries[0].impersonator_email.as_deref(),
+            Some("admin@example.com")
+        );
+        assert_eq!(entries[0].event, "private_file_accessed");
+        assert_eq!(entries[0].entity_type, "accession");
+        assert_eq!(entries[0].entity_id, "7");
+        assert_eq!(entries[0].details, Some(json!({"key": "some_file.wacz"})));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn filters_entries() {
+        let db = migrated_test_db().await;
+        let repo = DBAuditLogRepo {
+            db_session: db.clone(),
+            ..Default::default()
+        };
+        let actor = format!("{}@example.com", Uuid::new_v4());
+        for event in ["accession_deleted", "private_file_accessed"] {
+            repo.record(AuditEntry {
+                actor_email: actor.clone(),
+                impersonator_email: None,
+                event,
+                entity_type: "accession",
+                entity_id: "7".to_string(),
+                details: None,
+            })
+            .await
+            .unwrap();
+        }
+        let filter = AuditLogFilter {
+            actor: Some(actor.clone()),
+            ..Default::default()
+        };
+        let window = PageWindow::new(0, 1);
+        let (entries, num_pages) = repo.list(filter.clone(), window).await.unwrap();
+        assert_eq!(num_pages, 2);
+        assert_eq!(entries[0].event, "private_file_accessed");
+
+        let filter = AuditLogFilter {
+            event: Some("accession_deleted".to_string()),
+            date_from: Some(Utc::now().naive_utc() - chrono::Duration::hours(1)),
+            ..filter
+        };
+        let entries = repo.export(filter, 10).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].actor_email, actor);
+
+        let filter = AuditLogFilter {
+            actor: Some(actor.clone()),
+            entity_type: Some("accession".to_string()),
+            entity_id: Some("8".to_string()),
+            ..Default::default()
+        };
+        assert!(repo.export(filter, 10).await.unwrap().is_empty());
+    }
+}
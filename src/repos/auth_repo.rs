@@ -1,33 +1,40 @@
+use crate::clock::{SharedClock, SharedIdGen};
 use crate::models::request::AuthorizeRequest;
 use ::entity::api_key::ActiveModel as ApiKeyActiveModel;
 use ::entity::api_key::Entity as ApiKey;
 use ::entity::archive_user::Entity as ArchiveUser;
 use ::entity::archive_user::Model as ArchiveUserModel;
-use ::entity::sea_orm_active_enums::Role;
+use ::entity::sea_orm_active_enums::{EmailStatus, Role};
 use ::entity::session::ActiveModel as SessionActiveModel;
 use ::entity::session::Entity as Session;
 use async_trait::async_trait;
 use base64::{engine::general_purpose::URL_SAFE, Engine as _};
-use chrono::{Duration, NaiveDateTime, Utc};
+use chrono::{Duration, NaiveDateTime};
 use entity::{api_key, archive_user, session};
 use rand::Rng;
-use sea_orm::{ActiveModelTrait, ActiveValue};
-use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ActiveEnum, ActiveModelTrait, ActiveValue};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, TransactionTrait,
+};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use tracing::{error, info};
 use uuid::Uuid;
 
-/// Response containing user email and role from API key verification.
+/// Response containing user email, role and organization from API key verification.
 ///
 /// This struct is returned when an API key is successfully verified and contains
-/// the associated user's email address and their role in the system.
+/// the associated user's email address, their role in the system and the organization
+/// they belong to.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiKeyUserInfo {
     /// The email address of the user associated with the verified API key
     pub email: String,
     /// The role of the user (e.g., researcher, admin)
     pub role: Role,
+    /// The organization the user belongs to
+    pub organization_id: i32,
 }
 
 /// Database-backed implementation of authentication operations.
@@ -40,6 +47,10 @@ pub struct DBAuthRepo {
     pub db_session: DatabaseConnection,
     /// Session expiration time in hours
     pub expiry_hours: i64,
+    /// Tells the time sessions and API keys expire by
+    pub clock: SharedClock,
+    /// Generates session and API key IDs
+    pub ids: SharedIdGen,
 }
 
 /// Trait defining the interface for authentication repository operations.
@@ -62,10 +73,11 @@ pub trait AuthRepo: Send + Sync {
     ///
     /// # Arguments
     /// * `user_id` - The ID of the user to create a session for
+    /// * `client` - What the client asking for it is known by, see [`crate::client_ip`]
     ///
     /// # Returns
     /// Returns `Ok(session_id)` containing the newly created session ID, or `Err` on database failure.
-    async fn create_session(&self, user_id: Uuid) -> Result<Uuid, DbErr>;
+    async fn create_session(&self, user_id: Uuid, client: Option<String>) -> Result<Uuid, DbErr>;
 
     /// Deletes all expired sessions from the database.
     ///
@@ -128,6 +140,79 @@ pub trait AuthRepo: Send + Sync {
     /// This function should be called periodically (e.g., via a background task) to clean up
     /// expired API key records. It logs success or errors but does not return a result.
     async fn delete_expired_api_keys(&self);
+
+    /// Lists users, active or not, ordered by email.
+    ///
+    /// # Arguments
+    /// * `organization_id` - Only list users in this organization, or every user if `None`
+    ///
+    /// # Returns
+    /// Returns `Ok(users)` or `Err` on database failure.
+    async fn list_users(
+        &self,
+        organization_id: Option<i32>,
+    ) -> Result<Vec<ArchiveUserModel>, DbErr>;
+
+    /// Records whether emails to an address are getting through.
+    ///
+    /// A soft bounce is only recorded for addresses that were deliverable, so a temporary
+    /// failure can't hide an earlier hard bounce or spam complaint.
+    ///
+    /// # Arguments
+    /// * `email` - The address Postmark reported on
+    /// * `email_status` - The new status of the address
+    ///
+    /// # Returns
+    /// Returns `Ok(true)` if a user's status changed, `Ok(false)` if no user has that
+    /// address or the status was kept, or `Err` on database failure.
+    async fn set_email_status(
+        &self,
+        email: String,
+        email_status: EmailStatus,
+    ) -> Result<bool, DbErr>;
+
+    /// Retrieves the email status of the user with an address.
+    ///
+    /// # Arguments
+    /// * `email` - The address to look up
+    ///
+    /// # Returns
+    /// Returns `Ok(Some(status))` if a user has the address, `Ok(None)` if not, or `Err`
+    /// on database failure.
+    async fn get_email_status(&self, email: String) -> Result<Option<EmailStatus>, DbErr>;
+
+    /// Opts a user in to or out of the weekly activity digest, see
+    /// [`crate::activity_digest`].
+    ///
+    /// # Arguments
+    /// * `email` - The user's address
+    /// * `enabled` - Whether they want the digest
+    ///
+    /// # Returns
+    /// Returns `Ok(true)` if a user has the address, `Ok(false)` if not, or `Err` on
+    /// database failure.
+    async fn set_activity_digest(&self, email: String, enabled: bool) -> Result<bool, DbErr>;
+
+    /// Deactivates a user, deleting their sessions and revoking their API keys so that
+    /// nothing they were already signed in with keeps working.
+    ///
+    /// # Arguments
+    /// * `user_id` - The ID of the user to deactivate
+    ///
+    /// # Returns
+    /// Returns `Ok(true)` if an active user was deactivated, `Ok(false)` if there is no such
+    /// active user, or `Err` on database failure.
+    async fn deactivate_user(&self, user_id: Uuid) -> Result<bool, DbErr>;
+
+    /// Checks whether the user with an address is still active.
+    ///
+    /// # Arguments
+    /// * `email` - The user's address
+    ///
+    /// # Returns
+    /// Returns `Ok(true)` if an active user has the address, `Ok(false)` if not, or `Err`
+    /// on database failure.
+    async fn is_active(&self, email: String) -> Result<bool, DbErr>;
 }
 
 #[async_trait]
@@ -159,17 +244,19 @@ impl AuthRepo for DBAuthRepo {
     ///
     /// # Arguments
     /// * `user_id` - The user to create a session for
+    /// * `client` - What the client asking for it is known by
     ///
     /// # Returns
     /// Returns the newly created session ID.
-    async fn create_session(&self, user_id: Uuid) -> Result<Uuid, DbErr> {
-        let session_id = Uuid::new_v4();
-        let now = Utc::now();
+    async fn create_session(&self, user_id: Uuid, client: Option<String>) -> Result<Uuid, DbErr> {
+        let session_id = self.ids.new_id();
+        let now = self.clock.now();
         let expiry_time = now + Duration::hours(self.expiry_hours);
         let session = SessionActiveModel {
             id: ActiveValue::Set(session_id),
             expiry_time: ActiveValue::Set(expiry_time.naive_utc()),
             user_id: ActiveValue::Set(user_id),
+            client: ActiveValue::Set(client),
         };
         let session = session.insert(&self.db_session).await?;
         Ok(session.id)
@@ -180,7 +267,7 @@ impl AuthRepo for DBAuthRepo {
     /// Finds all sessions where the expiry time is less than or equal to the current time
     /// and removes them. Logs the result or any errors that occur.
     async fn delete_expired_sessions(&self) {
-        let now = Utc::now().naive_utc();
+        let now = self.clock.naive_now();
         let delete_result = Session::delete_many()
             .filter(session::Column::ExpiryTime.lte(now))
             .exec(&self.db_session)
@@ -211,7 +298,7 @@ impl AuthRepo for DBAuthRepo {
         let session = Session::find()
             .filter(session::Column::UserId.eq(authorize_request.user_id))
             .filter(session::Column::Id.eq(authorize_request.session_id))
-            .filter(session::Column::ExpiryTime.gt(Utc::now().naive_utc()))
+            .filter(session::Column::ExpiryTime.gt(self.clock.naive_now()))
             .one(&self.db_session)
             .await?;
         match session {
@@ -264,8 +351,8 @@ impl AuthRepo for DBAuthRepo {
         let key_hash = hasher.finalize();
         let key_hash_hex = format!("{key_hash:x}");
 
-        let api_key_id = Uuid::new_v4();
-        let now = Utc::now();
+        let api_key_id = self.ids.new_id();
+        let now = self.clock.now();
         let expires_at = now + Duration::days(90);
 
         let api_key = ApiKeyActiveModel {
@@ -307,7 +394,7 @@ impl AuthRepo for DBAuthRepo {
         let api_key_record = ApiKey::find()
             .filter(api_key::Column::KeyHash.eq(key_hash_hex))
             .filter(api_key::Column::IsRevoked.eq(false))
-            .filter(api_key::Column::ExpiresAt.gt(Utc::now().naive_utc()))
+            .filter(api_key::Column::ExpiresAt.gt(self.clock.naive_now()))
             .one(&self.db_session)
             .await?;
 
@@ -323,6 +410,7 @@ impl AuthRepo for DBAuthRepo {
                     Some(user) => Ok(Some(ApiKeyUserInfo {
                         email: user.email,
                         role: user.role,
+                        organization_id: user.organization_id,
                     })),
                     None => Ok(None),
                 }
@@ -336,7 +424,7 @@ impl AuthRepo for DBAuthRepo {
     /// Finds all API keys where the expiration time has passed and removes them.
     /// Logs the result or any errors that occur. Useful as a periodic cleanup task.
     async fn delete_expired_api_keys(&self) {
-        let now = Utc::now().naive_utc();
+        let now = self.clock.naive_now();
         let delete_result = ApiKey::delete_many()
             .filter(api_key::Column::ExpiresAt.lte(now))
             .exec(&self.db_session)
@@ -351,4 +439,273 @@ impl AuthRepo for DBAuthRepo {
             }
         }
     }
+
+    async fn list_users(
+        &self,
+        organization_id: Option<i32>,
+    ) -> Result<Vec<ArchiveUserModel>, DbErr> {
+        let mut query = ArchiveUser::find();
+        if let Some(organization_id) = organization_id {
+            query = query.filter(archive_user::Column::OrganizationId.eq(organization_id));
+        }
+        query
+            .order_by_asc(archive_user::Column::Email)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn set_email_status(
+        &self,
+        email: String,
+        email_status: EmailStatus,
+    ) -> Result<bool, DbErr> {
+        let mut update = ArchiveUser::update_many()
+            .col_expr(archive_user::Column::EmailStatus, email_status.as_enum())
+            .filter(archive_user::Column::Email.eq(email));
+        if email_status == EmailStatus::SoftBounce {
+            update = update.filter(archive_user::Column::EmailStatus.eq(EmailStatus::Deliverable));
+        }
+        let result = update.exec(&self.db_session).await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    async fn get_email_status(&self, email: String) -> Result<Option<EmailStatus>, DbErr> {
+        let user = ArchiveUser::find()
+            .filter(archive_user::Column::Email.eq(email))
+            .one(&self.db_session)
+            .await?;
+        Ok(user.map(|user| user.email_status))
+    }
+
+    async fn set_activity_digest(&self, email: String, enabled: bool) -> Result<bool, DbErr> {
+        let result = ArchiveUser::update_many()
+            .col_expr(archive_user::Column::ActivityDigest, Expr::value(enabled))
+            .filter(archive_user::Column::Email.eq(email))
+            .exec(&self.db_session)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    async fn deactivate_user(&self, user_id: Uuid) -> Result<bool, DbErr> {
+        let txn = self.db_session.begin().await?;
+        let result = ArchiveUser::update_many()
+            .col_expr(archive_user::Column::IsActive, Expr::value(false))
+            .filter(archive_user::Column::Id.eq(user_id))
+            .filter(archive_user::Column::IsActive.eq(true))
+            .exec(&txn)
+            .await?;
+        if result.rows_affected == 0 {
+            return Ok(false);
+        }
+        Session::delete_many()
+            .filter(session::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+        ApiKey::update_many()
+            .col_expr(api_key::Column::IsRevoked, Expr::value(true))
+            .filter(api_key::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(true)
+    }
+
+    async fn is_active(&self, email: String) -> Result<bool, DbErr> {
+        Ok(self.get_user_by_email(email).await?.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+    use crate::test_db::{migrated_test_db, TestDb};
+    use crate::test_tools::{FixedClock, SequentialIds};
+    use ::entity::archive_user::ActiveModel as ArchiveUserActiveModel;
+    use pretty_assertions::assert_eq;
+
+    async fn build_repo() -> (TestDb, DBAuthRepo) {
+        let db = migrated_test_db().await;
+        let repo = DBAuthRepo {
+            db_session: db.clone(),
+            expiry_hours: 1,
+            ..Default::default()
+        };
+        (db, repo)
+    }
+
+    async fn write_user(repo: &DBAuthRepo, email: &str, is_active: bool) -> Uuid {
+        let user = ArchiveUserActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            email: ActiveValue::Set(email.to_string()),
+            is_active: ActiveValue::Set(is_active),
+            role: ActiveValue::Set(Role::Researcher),
+            email_status: ActiveValue::NotSet,
+            activity_digest: ActiveValue::NotSet,
+            activity_digest_sent_at: ActiveValue::NotSet,
+            organization_id: ActiveValue::NotSet,
+        };
+        user.insert(&repo.db_session).await.unwrap().id
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn finds_only_active_users() {
+        let (_db, repo) = build_repo().await;
+        let active_id = write_user(&repo, "active@example.com", true).await;
+        let inactive_id = write_user(&repo, "inactive@example.com", false).await;
+
+        assert_eq!(
+            repo.get_user_by_email("active@example.com".to_string())
+                .await
+                .unwrap(),
+            Some(active_id)
+        );
+        assert_eq!(
+            repo.get_user_by_email("inactive@example.com".to_string())
+                .await
+                .unwrap(),
+            None
+        );
+        assert!(repo.get_one(inactive_id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn creates_and_checks_sessions() {
+        let clock = FixedClock::default();
+        let (_db, repo) = build_repo().await;
+        let repo = DBAuthRepo {
+            clock: SharedClock::new(clock.clone()),
+            ids: SharedIdGen::new(SequentialIds::default()),
+            ..repo
+        };
+        let user_id = write_user(&repo, "researcher@example.com", true).await;
+        let session_id = repo
+            .create_session(user_id, Some("197.252.12.0".to_string()))
+            .await
+            .unwrap();
+        let session = Session::find_by_id(session_id)
+            .one(&repo.db_session)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session_id, Uuid::from_u128(1));
+        assert_eq!(session.client.as_deref(), Some("197.252.12.0"));
+
+        let expiry = repo
+            .get_session_expiry(AuthorizeRequest {
+                session_id,
+                user_id,
+            })
+            .await
+            .unwrap();
+        assert_eq!(expiry, Some((clock.now() + Duration::hours(1)).naive_utc()));
+
+        let other_user_id = write_user(&repo, "other@example.com", true).await;
+        let expiry = repo
+            .get_session_expiry(AuthorizeRequest {
+                session_id,
+                user_id: other_user_id,
+            })
+            .await
+            .unwrap();
+        assert_eq!(expiry, None);
+
+        clock.advance(Duration::hours(2));
+        let expiry = repo
+            .get_session_expiry(AuthorizeRequest {
+                session_id,
+                user_id,
+            })
+            .await
+            .unwrap();
+        assert_eq!(expiry, None);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn creates_and_verifies_api_keys() {
+        let (_db, repo) = build_repo().await;
+        let user_id = write_user(&repo, "researcher@example.com", true).await;
+        let api_key = repo.create_api_key_for_user(user_id).await.unwrap();
+
+        let user_info = repo.verify_api_key(api_key).await.unwrap().unwrap();
+        assert_eq!(user_info.email, "researcher@example.com");
+        assert_eq!(user_info.role, Role::Researcher);
+        assert!(repo
+            .verify_api_key(URL_SAFE.encode([0u8; 32]))
+            .await
+            .unwrap()
+            .is_none());
+        assert!(repo
+            .verify_api_key("not base64!".to_string())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn deactivating_users_signs_them_out() {
+        let (_db, repo) = build_repo().await;
+        let email = format!("{}@example.com", Uuid::new_v4());
+        let user_id = write_user(&repo, &email, true).await;
+        let session_id = repo.create_session(user_id, None).await.unwrap();
+        let api_key = repo.create_api_key_for_user(user_id).await.unwrap();
+        assert!(repo.is_active(email.clone()).await.unwrap());
+
+        assert!(repo.deactivate_user(user_id).await.unwrap());
+        assert!(!repo.deactivate_user(user_id).await.unwrap());
+        assert!(!repo.is_active(email).await.unwrap());
+        let expiry = repo
+            .get_session_expiry(AuthorizeRequest {
+                session_id,
+                user_id,
+            })
+            .await
+            .unwrap();
+        assert_eq!(expiry, None);
+        let revoked = ApiKey::find()
+            .filter(api_key::Column::UserId.eq(user_id))
+            .one(&repo.db_session)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(revoked.is_revoked);
+        assert!(repo.verify_api_key(api_key).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn soft_bounces_do_not_hide_hard_bounces() {
+        let (_db, repo) = build_repo().await;
+        write_user(&repo, "bouncy@example.com", true).await;
+        let email = || "bouncy@example.com".to_string();
+        assert_eq!(
+            repo.get_email_status(email()).await.unwrap(),
+            Some(EmailStatus::Deliverable)
+        );
+
+        assert!(repo
+            .set_email_status(email(), EmailStatus::HardBounce)
+            .await
+            .unwrap());
+        assert!(!repo
+            .set_email_status(email(), EmailStatus::SoftBounce)
+            .await
+            .unwrap());
+        assert_eq!(
+            repo.get_email_status(email()).await.unwrap(),
+            Some(EmailStatus::HardBounce)
+        );
+        assert!(!repo
+            .set_email_status("nobody@example.com".to_string(), EmailStatus::HardBounce)
+            .await
+            .unwrap());
+        assert_eq!(
+            repo.list_users(None).await.unwrap()[0].email_status,
+            EmailStatus::HardBounce
+        );
+    }
 }
@@ -5,11 +5,16 @@
 //! files from completed crawl operations.
 
 use crate::config::BrowsertrixCrawlConfig;
+use crate::crawl_state_machine::LaunchedCrawl;
+use crate::models::common::CrawlerBackend;
 use crate::models::request::CreateCrawlRequest;
 use crate::models::response::{
     AuthResponse, CreateCrawlResponse, GetCrawlResponse, GetWaczUrlResponse,
 };
+use crate::repos::crawler_repo::{CrawlArchive, CrawlerError, CrawlerRepo};
 use async_trait::async_trait;
+use entity::sea_orm_active_enums::DublinMetadataFormat;
+use futures::{StreamExt, TryStreamExt};
 use reqwest::{Client, Error, RequestBuilder, Response, StatusCode};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -39,9 +44,6 @@ pub struct HTTPBrowsertrixRepo {
 /// and retrieving archived content from completed crawls.
 #[async_trait]
 pub trait BrowsertrixRepo: Send + Sync {
-    /// Retrieves the organization ID for Browsertrix operations.
-    fn get_org_id(&self) -> Uuid;
-
     /// Refreshes the authentication token used for Browsertrix API calls.
     async fn refresh_auth(&self);
 
@@ -89,10 +91,6 @@ pub trait BrowsertrixRepo: Send + Sync {
 
 #[async_trait]
 impl BrowsertrixRepo for HTTPBrowsertrixRepo {
-    fn get_org_id(&self) -> Uuid {
-        self.org_id
-    }
-
     async fn refresh_auth(&self) {
         let new_access_token = self
             .authenticate()
@@ -189,3 +187,36 @@ impl BrowsertrixRepo for HTTPBrowsertrixRepo {
         self.make_request(req).await
     }
 }
+
+#[async_trait]
+impl CrawlerRepo for HTTPBrowsertrixRepo {
+    fn backend(&self) -> CrawlerBackend {
+        CrawlerBackend::Browsertrix
+    }
+
+    async fn launch(
+        &self,
+        create_crawl_request: CreateCrawlRequest,
+    ) -> Result<LaunchedCrawl, CrawlerError> {
+        let resp = self.create_crawl(create_crawl_request).await?;
+        Ok(LaunchedCrawl {
+            id: resp.id,
+            job_run_id: resp.run_now_job,
+            org_id: Some(self.org_id),
+        })
+    }
+
+    async fn is_finished(&self, crawl: &LaunchedCrawl) -> Result<bool, CrawlerError> {
+        let status = self.get_crawl_status(crawl.id).await?;
+        Ok(status == "complete")
+    }
+
+    async fn download(&self, crawl: &LaunchedCrawl) -> Result<CrawlArchive, CrawlerError> {
+        let resp = self.download_wacz_stream(&crawl.job_run_id).await?;
+        Ok(CrawlArchive {
+            format: DublinMetadataFormat::Wacz,
+            stream: resp.bytes_stream().map_err(CrawlerError::from).boxed(),
+            metadata_json: None,
+        })
+    }
+}
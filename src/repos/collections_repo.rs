@@ -0,0 +1,274 @@
+//! Repository module for curated collections of accessions.
+//!
+//! A collection groups accessions, e.g. every capture about a single event, so they can be
+//! handed out together. Unlike subjects a collection isn't per language, and unlike workflow
+//! labels it is meant to be shared outside the archive.
+
+use crate::clock::SharedClock;
+use ::entity::accessions_with_metadata::Entity as AccessionWithMetadata;
+use ::entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
+use ::entity::collection::ActiveModel as CollectionActiveModel;
+use ::entity::collection::Entity as Collection;
+use ::entity::collection::Model as CollectionModel;
+use ::entity::collection_accession::ActiveModel as CollectionAccessionActiveModel;
+use ::entity::collection_accession::Entity as CollectionAccession;
+use async_trait::async_trait;
+use entity::{accessions_with_metadata, collection_accession};
+use sea_orm::sea_query::{OnConflict, Query};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder,
+};
+
+/// Repository implementation for database operations on collections.
+#[derive(Debug, Clone, Default)]
+pub struct DBCollectionsRepo {
+    pub db_session: DatabaseConnection,
+    /// Tells the time collections are made at
+    pub clock: SharedClock,
+}
+
+/// Defines the interface for collection database operations.
+#[async_trait]
+pub trait CollectionsRepo: Send + Sync {
+    /// Creates an empty collection.
+    ///
+    /// # Arguments
+    /// * `organization_id` - The organization the collection belongs to
+    /// * `title` - What the collection is called
+    /// * `description` - What the collection is about
+    /// * `created_by` - Email of the curator making the collection
+    async fn write_one(
+        &self,
+        organization_id: i32,
+        title: String,
+        description: Option<String>,
+        created_by: String,
+    ) -> Result<CollectionModel, DbErr>;
+
+    /// Retrieves a collection by ID.
+    ///
+    /// # Arguments
+    /// * `collection_id` - The ID of the collection
+    async fn get_one(&self, collection_id: i32) -> Result<Option<CollectionModel>, DbErr>;
+
+    /// Adds an accession to a collection. Adding an accession already in it is a no-op.
+    ///
+    /// # Arguments
+    /// * `collection_id` - The ID of the collection
+    /// * `accession_id` - The ID of the accession to add
+    async fn add_accession(&self, collection_id: i32, accession_id: i32) -> Result<(), DbErr>;
+
+    /// Takes an accession out of a collection.
+    ///
+    /// # Arguments
+    /// * `collection_id` - The ID of the collection
+    /// * `accession_id` - The ID of the accession to take out
+    async fn remove_accession(
+        &self,
+        collection_id: i32,
+        accession_id: i32,
+    ) -> Result<Option<()>, DbErr>;
+
+    /// Lists the accessions in a collection, public or not, in ID order.
+    ///
+    /// # Arguments
+    /// * `collection_id` - The ID of the collection
+    async fn list_accessions(
+        &self,
+        collection_id: i32,
+    ) -> Result<Vec<AccessionWithMetadataModel>, DbErr>;
+}
+
+#[async_trait]
+impl CollectionsRepo for DBCollectionsRepo {
+    async fn write_one(
+        &self,
+        organization_id: i32,
+        title: String,
+        description: Option<String>,
+        created_by: String,
+    ) -> Result<CollectionModel, DbErr> {
+        let collection = CollectionActiveModel {
+            id: Default::default(),
+            title: ActiveValue::Set(title),
+            description: ActiveValue::Set(description),
+            created_by: ActiveValue::Set(created_by),
+            created_at: ActiveValue::Set(self.clock.naive_now()),
+            organization_id: ActiveValue::Set(organization_id),
+        };
+        collection.insert(&self.db_session).await
+    }
+
+    async fn get_one(&self, collection_id: i32) -> Result<Option<CollectionModel>, DbErr> {
+        Collection::find_by_id(collection_id)
+            .one(&self.db_session)
+            .await
+    }
+
+    async fn add_accession(&self, collection_id: i32, accession_id: i32) -> Result<(), DbErr> {
+        let link = CollectionAccessionActiveModel {
+            collection_id: ActiveValue::Set(collection_id),
+            accession_id: ActiveValue::Set(accession_id),
+        };
+        CollectionAccession::insert(link)
+            .on_conflict(
+                OnConflict::columns([
+                    collection_accession::Column::CollectionId,
+                    collection_accession::Column::AccessionId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .do_nothing()
+            .exec(&self.db_session)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_accession(
+        &self,
+        collection_id: i32,
+        accession_id: i32,
+    ) -> Result<Option<()>, DbErr> {
+        let deletion = CollectionAccession::delete_by_id((collection_id, accession_id))
+            .exec(&self.db_session)
+            .await?;
+        if deletion.rows_affected > 0 {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn list_accessions(
+        &self,
+        collection_id: i32,
+    ) -> Result<Vec<AccessionWithMetadataModel>, DbErr> {
+        // memberships live outside the view, so match against the link table
+        let members = Query::select()
+            .column(collection_accession::Column::AccessionId)
+            .from(collection_accession::Entity)
+            .and_where(collection_accession::Column::CollectionId.eq(collection_id))
+            .to_owned();
+        AccessionWithMetadata::find()
+            .filter(accessions_with_metadata::Column::Id.in_subquery(members))
+            .order_by_asc(accessions_with_metadata::Column::Id)
+            .all(&self.db_session)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::common::{MetadataLanguage, MetadataScrubbing};
+    use crate::models::request::{CreateAccessionRequestRaw, CreateSubjectRequest};
+    use crate::repos::accessions_repo::{AccessionsRepo, DBAccessionsRepo};
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::repos::subjects_repo::{DBSubjectsRepo, SubjectsRepo};
+    use crate::test_db::migrated_test_db;
+    use entity::sea_orm_active_enums::{DublinMetadataFormat, ScanStatus};
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    async fn write_accession(accessions_repo: &DBAccessionsRepo, is_private: bool) -> i32 {
+        let subjects_repo = DBSubjectsRepo {
+            db_session: accessions_repo.db_session.clone(),
+            ..Default::default()
+        };
+        let subject = subjects_repo
+            .write_one(
+                CreateSubjectRequest {
+                    metadata_subject: Uuid::new_v4().to_string(),
+                    lang: MetadataLanguage::English,
+                },
+                DEFAULT_ORGANIZATION_ID,
+                None,
+            )
+            .await
+            .unwrap();
+        accessions_repo
+            .write_one_raw(
+                CreateAccessionRequestRaw {
+                    metadata_language: MetadataLanguage::English,
+                    metadata_title: "Market fire".to_string(),
+                    metadata_description: None,
+                    metadata_time: Default::default(),
+                    metadata_subjects: vec![subject.id],
+                    is_private,
+                    embargo_until: None,
+                    content_warning: None,
+                    metadata_format: DublinMetadataFormat::Jpeg,
+                    original_url: "https://example.com".to_string(),
+                    s3_filename: "file.jpg".to_string(),
+                    metadata_scrubbing: MetadataScrubbing::Scrub,
+                },
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn adds_and_removes_accessions() {
+        let db = migrated_test_db().await;
+        let db_session = db.clone();
+        let accessions_repo = DBAccessionsRepo {
+            db_session: db_session.clone(),
+            ..Default::default()
+        };
+        let repo = DBCollectionsRepo {
+            db_session,
+            ..Default::default()
+        };
+        let public = write_accession(&accessions_repo, false).await;
+        let private = write_accession(&accessions_repo, true).await;
+        write_accession(&accessions_repo, false).await;
+        let collection = repo
+            .write_one(
+                DEFAULT_ORGANIZATION_ID,
+                "El Fasher siege".to_string(),
+                None,
+                "someuser@gmail.com".to_string(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_one(collection.id).await.unwrap(),
+            Some(collection.clone())
+        );
+
+        for accession_id in [private, public, private] {
+            repo.add_accession(collection.id, accession_id)
+                .await
+                .unwrap();
+        }
+        let members = repo.list_accessions(collection.id).await.unwrap();
+        assert_eq!(
+            members.iter().map(|row| row.id).collect::<Vec<_>>(),
+            vec![public, private]
+        );
+
+        assert_eq!(
+            repo.remove_accession(collection.id, public).await.unwrap(),
+            Some(())
+        );
+        assert_eq!(
+            repo.remove_accession(collection.id, public).await.unwrap(),
+            None
+        );
+        let members = repo.list_accessions(collection.id).await.unwrap();
+        assert_eq!(
+            members.iter().map(|row| row.id).collect::<Vec<_>>(),
+            vec![private]
+        );
+        assert!(repo
+            .add_accession(collection.id, private + 100)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,353 @@
+//! Repository module for curators' comments on accessions.
+//!
+//! Comments make up an internal discussion thread per accession, so curators can
+//! coordinate on a capture next to it rather than in chat apps. Only researchers and
+//! admins of the accession's organization see them.
+
+use crate::clock::SharedClock;
+use ::entity::archive_user::Entity as ArchiveUser;
+use ::entity::comment::ActiveModel as CommentActiveModel;
+use ::entity::comment::Entity as Comment;
+use ::entity::comment::Model as CommentModel;
+use ::entity::sea_orm_active_enums::Role;
+use async_trait::async_trait;
+use entity::{archive_user, comment};
+use sea_orm::prelude::Expr;
+use sea_orm::sea_query::{ExprTrait, Func};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect,
+};
+
+/// Repository implementation for database operations on comments.
+#[derive(Debug, Clone, Default)]
+pub struct DBCommentsRepo {
+    pub db_session: DatabaseConnection,
+    /// Tells the time comments are written and edited at
+    pub clock: SharedClock,
+}
+
+/// Defines the interface for comment database operations.
+#[async_trait]
+pub trait CommentsRepo: Send + Sync {
+    /// Adds a comment to an accession.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The accession being discussed
+    /// * `author_email` - Email of the user writing the comment
+    /// * `body` - The comment's text
+    /// * `markdown` - Whether the body is markdown
+    async fn write_one(
+        &self,
+        accession_id: i32,
+        author_email: String,
+        body: String,
+        markdown: bool,
+    ) -> Result<CommentModel, DbErr>;
+
+    /// Lists the comments on an accession, oldest first.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    async fn list_for_accession(&self, accession_id: i32) -> Result<Vec<CommentModel>, DbErr>;
+
+    /// Retrieves a comment if it is on the given accession.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    /// * `comment_id` - The ID of the comment
+    async fn find_one(
+        &self,
+        accession_id: i32,
+        comment_id: i32,
+    ) -> Result<Option<CommentModel>, DbErr>;
+
+    /// Replaces a comment's text, returning the updated comment or `None` when there is no
+    /// such comment on the accession.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    /// * `comment_id` - The ID of the comment
+    /// * `body` - The comment's new text
+    /// * `markdown` - Whether the new text is markdown
+    async fn update_one(
+        &self,
+        accession_id: i32,
+        comment_id: i32,
+        body: String,
+        markdown: bool,
+    ) -> Result<Option<CommentModel>, DbErr>;
+
+    /// Deletes a comment if it is on the given accession.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    /// * `comment_id` - The ID of the comment
+    async fn delete_one(&self, accession_id: i32, comment_id: i32) -> Result<Option<()>, DbErr>;
+
+    /// Narrows emails down to those of active researchers and admins of an organization,
+    /// who can be mentioned in its comments. Emails are matched ignoring case and the
+    /// stored spelling is returned.
+    ///
+    /// # Arguments
+    /// * `emails` - The emails mentioned
+    /// * `organization_id` - The organization of the accession being discussed
+    async fn find_mentionable(
+        &self,
+        emails: Vec<String>,
+        organization_id: i32,
+    ) -> Result<Vec<String>, DbErr>;
+}
+
+#[async_trait]
+impl CommentsRepo for DBCommentsRepo {
+    async fn write_one(
+        &self,
+        accession_id: i32,
+        author_email: String,
+        body: String,
+        markdown: bool,
+    ) -> Result<CommentModel, DbErr> {
+        let now = self.clock.naive_now();
+        let comment = CommentActiveModel {
+            id: Default::default(),
+            accession_id: ActiveValue::Set(accession_id),
+            author_email: ActiveValue::Set(author_email),
+            body: ActiveValue::Set(body),
+            markdown: ActiveValue::Set(markdown),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+        };
+        comment.insert(&self.db_session).await
+    }
+
+    async fn list_for_accession(&self, accession_id: i32) -> Result<Vec<CommentModel>, DbErr> {
+        Comment::find()
+            .filter(comment::Column::AccessionId.eq(accession_id))
+            .order_by_asc(comment::Column::Id)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn find_one(
+        &self,
+        accession_id: i32,
+        comment_id: i32,
+    ) -> Result<Option<CommentModel>, DbErr> {
+        Comment::find_by_id(comment_id)
+            .filter(comment::Column::AccessionId.eq(accession_id))
+            .one(&self.db_session)
+            .await
+    }
+
+    async fn update_one(
+        &self,
+        accession_id: i32,
+        comment_id: i32,
+        body: String,
+        markdown: bool,
+    ) -> Result<Option<CommentModel>, DbErr> {
+        let Some(comment) = self.find_one(accession_id, comment_id).await? else {
+            return Ok(None);
+        };
+        let mut comment: CommentActiveModel = comment.into();
+        comment.body = ActiveValue::Set(body);
+        comment.markdown = ActiveValue::Set(markdown);
+        comment.updated_at = ActiveValue::Set(self.clock.naive_now());
+        Ok(Some(comment.update(&self.db_session).await?))
+    }
+
+    async fn delete_one(&self, accession_id: i32, comment_id: i32) -> Result<Option<()>, DbErr> {
+        let deletion = Comment::delete_many()
+            .filter(comment::Column::Id.eq(comment_id))
+            .filter(comment::Column::AccessionId.eq(accession_id))
+            .exec(&self.db_session)
+            .await?;
+        if deletion.rows_affected > 0 {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn find_mentionable(
+        &self,
+        emails: Vec<String>,
+        organization_id: i32,
+    ) -> Result<Vec<String>, DbErr> {
+        if emails.is_empty() {
+            return Ok(vec![]);
+        }
+        let emails: Vec<String> = emails.iter().map(|email| email.to_lowercase()).collect();
+        ArchiveUser::find()
+            .select_only()
+            .column(archive_user::Column::Email)
+            .filter(Func::lower(Expr::col(archive_user::Column::Email)).is_in(emails))
+            .filter(archive_user::Column::OrganizationId.eq(organization_id))
+            .filter(archive_user::Column::IsActive.eq(true))
+            .filter(archive_user::Column::Role.is_in([Role::Researcher, Role::Admin]))
+            .order_by_asc(archive_user::Column::Email)
+            .into_tuple()
+            .all(&self.db_session)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::common::{MetadataLanguage, MetadataScrubbing};
+    use crate::models::request::CreateAccessionRequestRaw;
+    use crate::repos::accessions_repo::{AccessionsRepo, DBAccessionsRepo};
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::test_db::migrated_test_db;
+    use ::entity::archive_user::ActiveModel as ArchiveUserActiveModel;
+    use entity::sea_orm_active_enums::{DublinMetadataFormat, ScanStatus};
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    async fn write_accession(accessions_repo: &DBAccessionsRepo) -> i32 {
+        accessions_repo
+            .write_one_raw(
+                CreateAccessionRequestRaw {
+                    metadata_language: MetadataLanguage::English,
+                    metadata_title: "Testimony".to_string(),
+                    metadata_description: None,
+                    metadata_time: Default::default(),
+                    metadata_subjects: vec![],
+                    is_private: true,
+                    embargo_until: None,
+                    content_warning: None,
+                    metadata_format: DublinMetadataFormat::Jpeg,
+                    original_url: "https://example.com".to_string(),
+                    s3_filename: "file.jpg".to_string(),
+                    metadata_scrubbing: MetadataScrubbing::Scrub,
+                },
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn write_user(repo: &DBCommentsRepo, email: &str, role: Role, is_active: bool) {
+        ArchiveUserActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            email: ActiveValue::Set(email.to_string()),
+            is_active: ActiveValue::Set(is_active),
+            role: ActiveValue::Set(role),
+            email_status: ActiveValue::NotSet,
+            activity_digest: ActiveValue::NotSet,
+            activity_digest_sent_at: ActiveValue::NotSet,
+            organization_id: ActiveValue::NotSet,
+        }
+        .insert(&repo.db_session)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn writes_edits_and_deletes_comments() {
+        let db = migrated_test_db().await;
+        let db_session = db.clone();
+        let accessions_repo = DBAccessionsRepo {
+            db_session: db_session.clone(),
+            ..Default::default()
+        };
+        let repo = DBCommentsRepo {
+            db_session,
+            ..Default::default()
+        };
+        let accession = write_accession(&accessions_repo).await;
+        let other = write_accession(&accessions_repo).await;
+        let first = repo
+            .write_one(
+                accession,
+                "curator@sda.org".to_string(),
+                "Needs a better title".to_string(),
+                false,
+            )
+            .await
+            .unwrap();
+        let second = repo
+            .write_one(
+                accession,
+                "researcher@sda.org".to_string(),
+                "**Agreed**".to_string(),
+                true,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.list_for_accession(accession)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|comment| comment.id)
+                .collect::<Vec<_>>(),
+            vec![first.id, second.id]
+        );
+        assert_eq!(repo.find_one(other, first.id).await.unwrap(), None);
+
+        assert_eq!(
+            repo.update_one(other, first.id, "Moved".to_string(), false)
+                .await
+                .unwrap(),
+            None
+        );
+        let edited = repo
+            .update_one(accession, first.id, "Fixed the title".to_string(), true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(edited.body, "Fixed the title");
+        assert!(edited.markdown);
+        assert_eq!(edited.created_at, first.created_at);
+        assert!(edited.updated_at >= first.updated_at);
+
+        assert_eq!(repo.delete_one(other, first.id).await.unwrap(), None);
+        assert_eq!(
+            repo.delete_one(accession, first.id).await.unwrap(),
+            Some(())
+        );
+        assert_eq!(
+            repo.list_for_accession(accession).await.unwrap(),
+            vec![second]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn finds_mentionable_curators() {
+        let db = migrated_test_db().await;
+        let repo = DBCommentsRepo {
+            db_session: db.clone(),
+            ..Default::default()
+        };
+        write_user(&repo, "Curator@sda.org", Role::Admin, true).await;
+        write_user(&repo, "researcher@sda.org", Role::Researcher, true).await;
+        write_user(&repo, "contributor@sda.org", Role::Contributor, true).await;
+        write_user(&repo, "former@sda.org", Role::Researcher, false).await;
+
+        let emails = vec![
+            "curator@sda.org".to_string(),
+            "researcher@sda.org".to_string(),
+            "contributor@sda.org".to_string(),
+            "former@sda.org".to_string(),
+            "nobody@sda.org".to_string(),
+        ];
+        assert_eq!(
+            repo.find_mentionable(emails.clone(), DEFAULT_ORGANIZATION_ID)
+                .await
+                .unwrap(),
+            vec!["Curator@sda.org", "researcher@sda.org"]
+        );
+        assert!(repo
+            .find_mentionable(emails, DEFAULT_ORGANIZATION_ID + 1)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
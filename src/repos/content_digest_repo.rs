@@ -0,0 +1,195 @@
+//! Repository module for the content digests of captures.
+//!
+//! See [`crate::content_digest`] for what the digest covers.
+
+use crate::clock::SharedClock;
+use ::entity::accession_content_digest::ActiveModel as AccessionContentDigestActiveModel;
+use ::entity::accession_content_digest::Entity as AccessionContentDigest;
+use async_trait::async_trait;
+use entity::{accession, accession_content_digest};
+use sea_orm::sea_query::{OnConflict, Query};
+use sea_orm::{
+    ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
+
+/// Repository implementation for database operations on content digests.
+#[derive(Debug, Clone, Default)]
+pub struct DBContentDigestRepo {
+    pub db_session: DatabaseConnection,
+    /// Tells the time digests are computed at
+    pub clock: SharedClock,
+}
+
+/// Defines the interface for content digest database operations.
+#[async_trait]
+pub trait ContentDigestRepo: Send + Sync {
+    /// Stores the content digest of an accession's capture, replacing any stored before.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    /// * `digest` - The digest, see [`crate::content_digest::content_digest`]
+    async fn write_one(&self, accession_id: i32, digest: String) -> Result<(), DbErr>;
+
+    /// Gets the content digest of an accession's capture, if one was computed.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    async fn get_one(&self, accession_id: i32) -> Result<Option<String>, DbErr>;
+
+    /// Lists the accessions whose capture has a content digest.
+    ///
+    /// # Arguments
+    /// * `digest` - The digest to look for
+    /// * `organization_id` - Only list accessions of this organization, every one if `None`
+    ///
+    /// # Returns
+    /// The accession IDs, oldest first
+    async fn find_accession_ids(
+        &self,
+        digest: &str,
+        organization_id: Option<i32>,
+    ) -> Result<Vec<i32>, DbErr>;
+}
+
+#[async_trait]
+impl ContentDigestRepo for DBContentDigestRepo {
+    async fn write_one(&self, accession_id: i32, digest: String) -> Result<(), DbErr> {
+        let content_digest = AccessionContentDigestActiveModel {
+            accession_id: ActiveValue::Set(accession_id),
+            digest: ActiveValue::Set(digest),
+            computed_at: ActiveValue::Set(self.clock.naive_now()),
+        };
+        AccessionContentDigest::insert(content_digest)
+            .on_conflict(
+                OnConflict::column(accession_content_digest::Column::AccessionId)
+                    .update_columns([
+                        accession_content_digest::Column::Digest,
+                        accession_content_digest::Column::ComputedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&self.db_session)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_one(&self, accession_id: i32) -> Result<Option<String>, DbErr> {
+        Ok(AccessionContentDigest::find_by_id(accession_id)
+            .one(&self.db_session)
+            .await?
+            .map(|row| row.digest))
+    }
+
+    async fn find_accession_ids(
+        &self,
+        digest: &str,
+        organization_id: Option<i32>,
+    ) -> Result<Vec<i32>, DbErr> {
+        let mut query = AccessionContentDigest::find()
+            .select_only()
+            .column(accession_content_digest::Column::AccessionId)
+            .filter(accession_content_digest::Column::Digest.eq(digest));
+        if let Some(organization_id) = organization_id {
+            query = query.filter(
+                accession_content_digest::Column::AccessionId.in_subquery(
+                    Query::select()
+                        .column(accession::Column::Id)
+                        .from(accession::Entity)
+                        .and_where(accession::Column::OrganizationId.eq(organization_id))
+                        .to_owned(),
+                ),
+            );
+        }
+        query
+            .order_by_asc(accession_content_digest::Column::AccessionId)
+            .into_tuple()
+            .all(&self.db_session)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::common::{MetadataLanguage, MetadataScrubbing};
+    use crate::models::request::CreateAccessionRequestRaw;
+    use crate::repos::accessions_repo::{AccessionsRepo, DBAccessionsRepo};
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::test_db::migrated_test_db;
+    use entity::sea_orm_active_enums::{DublinMetadataFormat, ScanStatus};
+    use pretty_assertions::assert_eq;
+
+    async fn write_accession(accessions_repo: &DBAccessionsRepo) -> i32 {
+        accessions_repo
+            .write_one_raw(
+                CreateAccessionRequestRaw {
+                    metadata_language: MetadataLanguage::English,
+                    metadata_title: "Market fire".to_string(),
+                    metadata_description: None,
+                    metadata_time: Default::default(),
+                    metadata_subjects: vec![],
+                    is_private: false,
+                    embargo_until: None,
+                    content_warning: None,
+                    metadata_format: DublinMetadataFormat::Wacz,
+                    original_url: "https://example.com".to_string(),
+                    s3_filename: "file.wacz".to_string(),
+                    metadata_scrubbing: MetadataScrubbing::Scrub,
+                },
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn finds_accessions_sharing_a_digest() {
+        let db = migrated_test_db().await;
+        let db_session = db.clone();
+        let accessions_repo = DBAccessionsRepo {
+            db_session: db_session.clone(),
+            ..Default::default()
+        };
+        let repo = DBContentDigestRepo {
+            db_session,
+            ..Default::default()
+        };
+        let article = write_accession(&accessions_repo).await;
+        let mirror = write_accession(&accessions_repo).await;
+        let other = write_accession(&accessions_repo).await;
+        assert_eq!(repo.get_one(article).await.unwrap(), None);
+
+        repo.write_one(article, "sha256:abc".to_string())
+            .await
+            .unwrap();
+        repo.write_one(mirror, "sha256:old".to_string())
+            .await
+            .unwrap();
+        // recomputing a digest replaces the one stored
+        repo.write_one(mirror, "sha256:abc".to_string())
+            .await
+            .unwrap();
+        repo.write_one(other, "sha256:def".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            repo.get_one(mirror).await.unwrap().as_deref(),
+            Some("sha256:abc")
+        );
+        assert_eq!(
+            repo.find_accession_ids("sha256:abc", None).await.unwrap(),
+            vec![article, mirror]
+        );
+        assert_eq!(
+            repo.find_accession_ids("sha256:abc", Some(DEFAULT_ORGANIZATION_ID + 1))
+                .await
+                .unwrap(),
+            Vec::<i32>::new()
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Repository module for managing the global crawl blocklist.
+//!
+//! Entries are URL patterns admins never want crawled, for legal or safety reasons. How
+//! patterns match URLs is up to [`crate::crawl_blocklist`].
+
+use crate::clock::SharedClock;
+use ::entity::crawl_blocklist::ActiveModel as CrawlBlocklistActiveModel;
+use ::entity::crawl_blocklist::Entity as CrawlBlocklist;
+use ::entity::crawl_blocklist::Model as CrawlBlocklistModel;
+use async_trait::async_trait;
+use entity::crawl_blocklist;
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection, DbErr, EntityTrait, QueryOrder};
+
+/// Repository implementation for database operations on the crawl blocklist.
+#[derive(Debug, Clone, Default)]
+pub struct DBCrawlBlocklistRepo {
+    pub db_session: DatabaseConnection,
+    /// Tells the time patterns are blocked at
+    pub clock: SharedClock,
+}
+
+/// Defines the interface for crawl blocklist database operations.
+#[async_trait]
+pub trait CrawlBlocklistRepo: Send + Sync {
+    /// Adds a pattern to the blocklist.
+    ///
+    /// # Arguments
+    /// * `pattern` - The normalized pattern, see [`crate::crawl_blocklist::normalize_pattern`]
+    /// * `reason` - Why URLs matching it must not be crawled, shown to whoever tries
+    async fn write_one(
+        &self,
+        pattern: String,
+        reason: String,
+    ) -> Result<CrawlBlocklistModel, DbErr>;
+
+    /// Lists the whole blocklist ordered by pattern.
+    async fn list(&self) -> Result<Vec<CrawlBlocklistModel>, DbErr>;
+
+    /// Removes a pattern from the blocklist.
+    ///
+    /// # Arguments
+    /// * `entry_id` - The ID of the entry to remove
+    async fn delete_one(&self, entry_id: i32) -> Result<Option<()>, DbErr>;
+}
+
+#[async_trait]
+impl CrawlBlocklistRepo for DBCrawlBlocklistRepo {
+    async fn write_one(
+        &self,
+        pattern: String,
+        reason: String,
+    ) -> Result<CrawlBlocklistModel, DbErr> {
+        let entry = CrawlBlocklistActiveModel {
+            id: Default::default(),
+            pattern: ActiveValue::Set(pattern),
+            reason: ActiveValue::Set(reason),
+            created_at: ActiveValue::Set(self.clock.naive_now()),
+        };
+        entry.insert(&self.db_session).await
+    }
+
+    async fn list(&self) -> Result<Vec<CrawlBlocklistModel>, DbErr> {
+        CrawlBlocklist::find()
+            .order_by_asc(crawl_blocklist::Column::Pattern)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn delete_one(&self, entry_id: i32) -> Result<Option<()>, DbErr> {
+        let deletion = CrawlBlocklist::delete_by_id(entry_id)
+            .exec(&self.db_session)
+            .await?;
+        if deletion.rows_affected > 0 {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+}
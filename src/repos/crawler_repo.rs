@@ -0,0 +1,128 @@
+//! Repository for crawling URLs into web archives, whichever crawler does the crawling.
+//!
+//! Crawls used to go straight to the hosted Browsertrix API. [`CrawlerRepo`] is what the crawl
+//! workflow in [`crate::crawl_state_machine`] needs from a crawler instead, so Browsertrix,
+//! [`crate::repos::local_crawler_repo::LocalCrawlerRepo`] or anything else that can launch a
+//! crawl, say when it's done and hand over the archive, such as an ArchiveBox server, can do
+//! the crawling. [`Crawlers`] holds the ones configured; `CRAWLER` picks the default and a
+//! crawl request can ask for another with its `crawler` field.
+
+use crate::crawl_state_machine::LaunchedCrawl;
+use crate::models::common::CrawlerBackend;
+use crate::models::request::CreateCrawlRequest;
+use async_trait::async_trait;
+use bytes::Bytes;
+use entity::sea_orm_active_enums::DublinMetadataFormat;
+use futures::stream::BoxStream;
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+
+/// Errors crawlers report, which are only ever logged.
+pub type CrawlerError = Box<dyn Error + Send + Sync>;
+
+/// A finished crawl's archive, streamed from wherever the crawler left it.
+pub struct CrawlArchive {
+    /// Whether the archive is a WACZ, a bare WARC or a downloaded video
+    pub format: DublinMetadataFormat,
+    pub stream: BoxStream<'static, Result<Bytes, CrawlerError>>,
+    /// JSON the crawler wrote about the capture, stored next to it in S3 as
+    /// `<filename>.info.json`
+    pub metadata_json: Option<Bytes>,
+}
+
+#[async_trait]
+pub trait CrawlerRepo: Send + Sync {
+    /// Which crawler this is.
+    fn backend(&self) -> CrawlerBackend;
+
+    /// Starts crawling a URL.
+    ///
+    /// # Arguments
+    /// * `create_crawl_request` - The URL to crawl and the browser profile to crawl it with,
+    ///   which crawlers without profiles ignore
+    ///
+    /// # Errors
+    /// Returns Error if the crawler could not be reached or refused the crawl
+    async fn launch(
+        &self,
+        create_crawl_request: CreateCrawlRequest,
+    ) -> Result<LaunchedCrawl, CrawlerError>;
+
+    /// Checks whether a crawl has finished. A crawl that finished without producing an
+    /// archive fails to download.
+    ///
+    /// # Errors
+    /// Returns Error if the crawl's status could not be read
+    async fn is_finished(&self, crawl: &LaunchedCrawl) -> Result<bool, CrawlerError>;
+
+    /// Streams the archive of a finished crawl.
+    ///
+    /// # Errors
+    /// Returns Error if the archive could not be found or read
+    async fn download(&self, crawl: &LaunchedCrawl) -> Result<CrawlArchive, CrawlerError>;
+}
+
+/// The crawlers configured, and which one crawls when a request doesn't ask for one.
+#[derive(Clone)]
+pub struct Crawlers {
+    default: CrawlerBackend,
+    repos: HashMap<CrawlerBackend, Arc<dyn CrawlerRepo>>,
+}
+
+impl Crawlers {
+    pub fn new(default: CrawlerBackend) -> Self {
+        Self {
+            default,
+            repos: HashMap::new(),
+        }
+    }
+
+    /// Adds a crawler, replacing any other of the same backend.
+    pub fn with(mut self, repo: Arc<dyn CrawlerRepo>) -> Self {
+        self.repos.insert(repo.backend(), repo);
+        self
+    }
+
+    /// Which crawler a request would use: the one it asked for or else the default.
+    pub fn backend(&self, requested: Option<CrawlerBackend>) -> CrawlerBackend {
+        requested.unwrap_or(self.default)
+    }
+
+    /// The crawler to use for a request.
+    ///
+    /// # Arguments
+    /// * `requested` - The crawler the request asked for, if any
+    ///
+    /// # Returns
+    /// The crawler, see [`Crawlers::backend`], or `None` if it isn't configured
+    pub fn get(&self, requested: Option<CrawlerBackend>) -> Option<Arc<dyn CrawlerRepo>> {
+        self.repos.get(&self.backend(requested)).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::InMemoryBrowsertrixRepo;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_crawler_backend_from_str() {
+        assert_eq!("LOCAL".parse(), Ok(CrawlerBackend::Local));
+        assert_eq!("browsertrix".parse(), Ok(CrawlerBackend::Browsertrix));
+        assert!("archivebox".parse::<CrawlerBackend>().is_err());
+    }
+
+    #[test]
+    fn picks_the_requested_or_default_crawler() {
+        let crawlers =
+            Crawlers::new(CrawlerBackend::Browsertrix).with(Arc::new(InMemoryBrowsertrixRepo {}));
+        assert_eq!(
+            crawlers.get(None).map(|repo| repo.backend()),
+            Some(CrawlerBackend::Browsertrix)
+        );
+        assert!(crawlers.get(Some(CrawlerBackend::Local)).is_none());
+        assert!(Crawlers::new(CrawlerBackend::Local).get(None).is_none());
+    }
+}
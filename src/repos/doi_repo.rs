@@ -0,0 +1,174 @@
+//! Repository for minting DOIs.
+//!
+//! Registers DOIs with [DataCite](https://support.datacite.org/docs/api-create-dois) so
+//! researchers can cite accessions by an identifier that outlives the archive's URLs.
+//! DataCite generates the suffix under the archive's prefix, and the DOI resolves to the
+//! accession's public page.
+
+use async_trait::async_trait;
+use reqwest::{Client, Error};
+use serde::{Deserialize, Serialize};
+
+/// What DataCite records about a resource, which it shows wherever the DOI is looked up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoiMetadata {
+    /// Titles, each with the ISO 639-1 code of its language
+    pub titles: Vec<(String, &'static str)>,
+    /// Descriptions, each with the ISO 639-1 code of its language
+    pub descriptions: Vec<(String, &'static str)>,
+    pub subjects: Vec<String>,
+    /// Creator and publisher of the resource
+    pub publisher: &'static str,
+    pub publication_year: i32,
+    /// One of DataCite's `resourceTypeGeneral` values, e.g. `Image`
+    pub resource_type: &'static str,
+    /// Page the DOI resolves to
+    pub url: String,
+}
+
+#[async_trait]
+pub trait DoiRepo: Send + Sync {
+    /// Registers and publishes a new DOI.
+    ///
+    /// Published DOIs are permanent, so this should only be called for resources that
+    /// will stay public.
+    ///
+    /// # Arguments
+    /// * `metadata` - What the DOI identifies
+    ///
+    /// # Returns
+    /// The DOI, e.g. `10.1234/abcd-efgh`
+    async fn mint(&self, metadata: DoiMetadata) -> Result<String, Error>;
+}
+
+#[derive(Debug, Serialize)]
+struct DataCiteRequest<'a> {
+    data: DataCiteData<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct DataCiteData<'a> {
+    #[serde(rename = "type")]
+    data_type: &'a str,
+    attributes: DataCiteAttributes<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DataCiteAttributes<'a> {
+    prefix: &'a str,
+    event: &'a str,
+    creators: Vec<DataCiteName<'a>>,
+    titles: Vec<DataCiteTitle<'a>>,
+    descriptions: Vec<DataCiteDescription<'a>>,
+    subjects: Vec<DataCiteSubject<'a>>,
+    publisher: &'a str,
+    publication_year: i32,
+    types: DataCiteTypes<'a>,
+    url: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct DataCiteName<'a> {
+    name: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct DataCiteTitle<'a> {
+    title: &'a str,
+    lang: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DataCiteDescription<'a> {
+    description: &'a str,
+    description_type: &'a str,
+    lang: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct DataCiteSubject<'a> {
+    subject: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DataCiteTypes<'a> {
+    resource_type_general: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataCiteResponse {
+    data: DataCiteCreated,
+}
+
+#[derive(Debug, Deserialize)]
+struct DataCiteCreated {
+    id: String,
+}
+
+/// Mints DOIs by calling the DataCite REST API over HTTP.
+#[derive(Debug, Clone, Default)]
+pub struct DataCiteDoiRepo {
+    pub client: Client,
+    /// e.g. `https://api.datacite.org`, or `https://api.test.datacite.org` for test DOIs
+    pub base_url: String,
+    pub repository_id: String,
+    pub password: String,
+    /// The archive's DOI prefix, e.g. `10.1234`
+    pub prefix: String,
+}
+
+#[async_trait]
+impl DoiRepo for DataCiteDoiRepo {
+    async fn mint(&self, metadata: DoiMetadata) -> Result<String, Error> {
+        let request = DataCiteRequest {
+            data: DataCiteData {
+                data_type: "dois",
+                attributes: DataCiteAttributes {
+                    prefix: &self.prefix,
+                    event: "publish",
+                    creators: vec![DataCiteName {
+                        name: metadata.publisher,
+                    }],
+                    titles: metadata
+                        .titles
+                        .iter()
+                        .map(|(title, lang)| DataCiteTitle { title, lang })
+                        .collect(),
+                    descriptions: metadata
+                        .descriptions
+                        .iter()
+                        .map(|(description, lang)| DataCiteDescription {
+                            description,
+                            description_type: "Abstract",
+                            lang,
+                        })
+                        .collect(),
+                    subjects: metadata
+                        .subjects
+                        .iter()
+                        .map(|subject| DataCiteSubject { subject })
+                        .collect(),
+                    publisher: metadata.publisher,
+                    publication_year: metadata.publication_year,
+                    types: DataCiteTypes {
+                        resource_type_general: metadata.resource_type,
+                    },
+                    url: &metadata.url,
+                },
+            },
+        };
+        let resp = self
+            .client
+            .post(format!("{}/dois", self.base_url))
+            .basic_auth(&self.repository_id, Some(&self.password))
+            .header(reqwest::header::CONTENT_TYPE, "application/vnd.api+json")
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(resp.json::<DataCiteResponse>().await?.data.id)
+    }
+}
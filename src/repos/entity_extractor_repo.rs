@@ -0,0 +1,66 @@
+//! Repository for finding named entities, such as people and places, in metadata.
+//!
+//! Talks to a named entity recognition service over HTTP. The service is expected to
+//! accept `POST /entities` with `{"text": "...", "language": "en"}` and answer with
+//! `{"entities": [{"text": "Khartoum", "label": "GPE"}]}`, which is simple to put in front
+//! of spaCy or CAMeL Tools.
+
+use async_trait::async_trait;
+use reqwest::{Client, Error};
+use serde::{Deserialize, Serialize};
+
+/// An entity found in a text
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct NamedEntity {
+    /// The entity as it appears in the text
+    pub text: String,
+    /// Kind of entity, e.g. `PERSON` or `GPE`
+    pub label: String,
+}
+
+#[async_trait]
+pub trait EntityExtractorRepo: Send + Sync {
+    /// Finds the named entities in a text.
+    ///
+    /// # Arguments
+    /// * `text` - The text to search
+    /// * `language` - ISO 639-1 code of the language the text is in
+    async fn extract_entities(&self, text: &str, language: &str)
+        -> Result<Vec<NamedEntity>, Error>;
+}
+
+#[derive(Debug, Serialize)]
+struct EntitiesRequest<'a> {
+    text: &'a str,
+    language: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntitiesResponse {
+    entities: Vec<NamedEntity>,
+}
+
+/// Finds entities by calling a named entity recognition service over HTTP.
+#[derive(Debug, Clone, Default)]
+pub struct HTTPEntityExtractorRepo {
+    pub client: Client,
+    pub base_url: String,
+}
+
+#[async_trait]
+impl EntityExtractorRepo for HTTPEntityExtractorRepo {
+    async fn extract_entities(
+        &self,
+        text: &str,
+        language: &str,
+    ) -> Result<Vec<NamedEntity>, Error> {
+        let resp = self
+            .client
+            .post(format!("{}/entities", self.base_url))
+            .json(&EntitiesRequest { text, language })
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(resp.json::<EntitiesResponse>().await?.entities)
+    }
+}
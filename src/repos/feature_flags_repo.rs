@@ -0,0 +1,187 @@
+//! Repository module for managing feature flags.
+//!
+//! Flags gate risky features so they can be rolled out gradually, e.g. to staging or to
+//! admins first. Roles are stored by their database name, e.g. `researcher`.
+
+use crate::clock::SharedClock;
+use crate::models::request::{CreateFeatureFlagRequest, UpdateFeatureFlagRequest};
+use ::entity::feature_flag::ActiveModel as FeatureFlagActiveModel;
+use ::entity::feature_flag::Entity as FeatureFlag;
+use ::entity::feature_flag::Model as FeatureFlagModel;
+use ::entity::sea_orm_active_enums::Role;
+use async_trait::async_trait;
+use entity::feature_flag;
+use sea_orm::{
+    ActiveEnum, ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder,
+};
+
+/// Repository implementation for database operations on feature flags.
+#[derive(Debug, Clone, Default)]
+pub struct DBFeatureFlagsRepo {
+    pub db_session: DatabaseConnection,
+    /// Tells the time flags are changed at
+    pub clock: SharedClock,
+}
+
+/// Defines the interface for feature flag database operations.
+#[async_trait]
+pub trait FeatureFlagsRepo: Send + Sync {
+    /// Creates a new feature flag.
+    ///
+    /// # Arguments
+    /// * `create_feature_flag_request` - The flag's name and settings
+    async fn write_one(
+        &self,
+        create_feature_flag_request: CreateFeatureFlagRequest,
+    ) -> Result<FeatureFlagModel, DbErr>;
+
+    /// Lists all feature flags ordered by name.
+    async fn list(&self) -> Result<Vec<FeatureFlagModel>, DbErr>;
+
+    /// Replaces the settings of a feature flag.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the flag to update
+    /// * `update_feature_flag_request` - The flag's new settings
+    async fn update_one(
+        &self,
+        name: &str,
+        update_feature_flag_request: UpdateFeatureFlagRequest,
+    ) -> Result<Option<FeatureFlagModel>, DbErr>;
+
+    /// Deletes a feature flag.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the flag to delete
+    async fn delete_one(&self, name: &str) -> Result<Option<()>, DbErr>;
+}
+
+fn role_names(roles: Vec<Role>) -> Vec<String> {
+    roles.iter().map(|role| role.to_value()).collect()
+}
+
+#[async_trait]
+impl FeatureFlagsRepo for DBFeatureFlagsRepo {
+    async fn write_one(
+        &self,
+        create_feature_flag_request: CreateFeatureFlagRequest,
+    ) -> Result<FeatureFlagModel, DbErr> {
+        let feature_flag = FeatureFlagActiveModel {
+            id: Default::default(),
+            name: ActiveValue::Set(create_feature_flag_request.name),
+            description: ActiveValue::Set(create_feature_flag_request.description),
+            enabled: ActiveValue::Set(create_feature_flag_request.enabled),
+            environments: ActiveValue::Set(create_feature_flag_request.environments),
+            roles: ActiveValue::Set(role_names(create_feature_flag_request.roles)),
+            updated_at: ActiveValue::Set(self.clock.naive_now()),
+        };
+        feature_flag.insert(&self.db_session).await
+    }
+
+    async fn list(&self) -> Result<Vec<FeatureFlagModel>, DbErr> {
+        FeatureFlag::find()
+            .order_by_asc(feature_flag::Column::Name)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn update_one(
+        &self,
+        name: &str,
+        update_feature_flag_request: UpdateFeatureFlagRequest,
+    ) -> Result<Option<FeatureFlagModel>, DbErr> {
+        let existing = FeatureFlag::find()
+            .filter(feature_flag::Column::Name.eq(name))
+            .one(&self.db_session)
+            .await?;
+        let Some(existing) = existing else {
+            return Ok(None);
+        };
+        let mut feature_flag: FeatureFlagActiveModel = existing.into();
+        feature_flag.description = ActiveValue::Set(update_feature_flag_request.description);
+        feature_flag.enabled = ActiveValue::Set(update_feature_flag_request.enabled);
+        feature_flag.environments = ActiveValue::Set(update_feature_flag_request.environments);
+        feature_flag.roles = ActiveValue::Set(role_names(update_feature_flag_request.roles));
+        feature_flag.updated_at = ActiveValue::Set(self.clock.naive_now());
+        Ok(Some(feature_flag.update(&self.db_session).await?))
+    }
+
+    async fn delete_one(&self, name: &str) -> Result<Option<()>, DbErr> {
+        let deletion = FeatureFlag::delete_many()
+            .filter(feature_flag::Column::Name.eq(name))
+            .exec(&self.db_session)
+            .await?;
+        if deletion.rows_affected > 0 {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db::{migrated_test_db, TestDb};
+    use pretty_assertions::assert_eq;
+
+    async fn build_repo() -> (TestDb, DBFeatureFlagsRepo) {
+        let db = migrated_test_db().await;
+        let repo = DBFeatureFlagsRepo {
+            db_session: db.clone(),
+            ..Default::default()
+        };
+        (db, repo)
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn writes_updates_and_deletes_flags() {
+        let (_db, repo) = build_repo().await;
+        let created = repo
+            .write_one(CreateFeatureFlagRequest {
+                name: "wacz-full-text".to_string(),
+                description: None,
+                enabled: false,
+                environments: vec!["staging".to_string()],
+                roles: vec![Role::Admin],
+            })
+            .await
+            .unwrap();
+        assert_eq!(created.roles, vec!["admin".to_string()]);
+
+        let updated = repo
+            .update_one(
+                "wacz-full-text",
+                UpdateFeatureFlagRequest {
+                    description: Some("Search inside WACZ pages".to_string()),
+                    enabled: true,
+                    environments: vec![],
+                    roles: vec![Role::Admin, Role::Researcher],
+                },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(updated.enabled);
+        assert_eq!(updated.environments, Vec::<String>::new());
+        assert_eq!(repo.list().await.unwrap(), vec![updated]);
+
+        assert!(repo
+            .update_one(
+                "missing",
+                UpdateFeatureFlagRequest {
+                    description: None,
+                    enabled: true,
+                    environments: vec![],
+                    roles: vec![],
+                },
+            )
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(repo.delete_one("wacz-full-text").await.unwrap(), Some(()));
+        assert_eq!(repo.delete_one("wacz-full-text").await.unwrap(), None);
+    }
+}
@@ -4,11 +4,13 @@
 //! supporting multiple languages and search criteria. It's designed to be extensible for future
 //! enhancements like full-text search using ts_vector indices and additional metadata fields.
 
-use crate::models::common::MetadataLanguage;
+use crate::models::common::{MetadataLanguage, SubjectMatch};
+use crate::social_metadata::normalize_handle;
 use chrono::NaiveDateTime;
-use entity::accessions_with_metadata;
+use entity::sea_orm_active_enums::{PublicationState, SocialPlatform};
+use entity::{accession_workflow_label, accessions_with_metadata, social_metadata};
 use sea_orm::prelude::Expr;
-use sea_orm::sea_query::SimpleExpr;
+use sea_orm::sea_query::{Alias, BinOper, Func, IntoColumnRef, Query, SimpleExpr};
 use sea_orm::{sea_query, ColumnTrait};
 use sea_query::extension::postgres::PgBinOper;
 
@@ -21,7 +23,100 @@ pub struct FilterParams {
     pub url_filter: Option<String>,
     pub date_from: Option<NaiveDateTime>,
     pub date_to: Option<NaiveDateTime>,
-    pub is_private: bool,
+    pub has_content_warning: Option<bool>,
+    pub visibility: Visibility,
+    pub workflow_labels: Option<Vec<i32>>,
+    pub organization_id: Option<i32>,
+    pub social_platform: Option<SocialPlatform>,
+    pub author_handle: Option<String>,
+}
+
+/// Which accessions a search can return.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Visibility {
+    /// Published accessions that aren't under embargo
+    #[default]
+    Public,
+    /// Everything that isn't public, optionally only accessions in one publication state
+    Private(Option<PublicationState>),
+    /// Public and private accessions alike, for curators' bulk changes
+    Any,
+}
+
+impl Visibility {
+    /// Public accessions, or every accession that isn't public.
+    pub fn from_private(private: bool) -> Self {
+        if private {
+            Visibility::Private(None)
+        } else {
+            Visibility::Public
+        }
+    }
+
+    /// Builds the condition matching accessions with this visibility.
+    pub fn condition(&self) -> SimpleExpr {
+        let is_private = accessions_with_metadata::Column::IsPrivate;
+        match self {
+            Visibility::Public => is_private.eq(false),
+            Visibility::Private(None) => is_private.eq(true),
+            Visibility::Private(Some(state)) => is_private
+                .eq(true)
+                .and(accessions_with_metadata::Column::PublicationState.eq(state.clone())),
+            Visibility::Any => Expr::value(true),
+        }
+    }
+}
+
+/// Matches accessions crawled from a domain or any of its subdomains, so `bbc.co.uk`
+/// matches `https://www.bbc.co.uk/news` but not `https://notbbc.co.uk`.
+///
+/// `domain` must only hold letters, digits, dots and hyphens, which
+/// [`crate::models::request::ApplySubjectRequest`] checks.
+pub fn on_domain(domain: &str) -> SimpleExpr {
+    Expr::cust_with_values("seed_url ~* $1", [domain_pattern(domain)])
+}
+
+/// Regular expression matching URLs on a domain or its subdomains, whatever their scheme,
+/// port and path.
+fn domain_pattern(domain: &str) -> String {
+    format!(
+        r"^https?://([^/?#@]*\.)?{}(:[0-9]+)?([/?#]|$)",
+        domain.replace('.', r"\.")
+    )
+}
+
+/// Matches a text column containing `term`, ignoring case and accents, so "geneina" finds
+/// "Genēina". Uses the `f_unaccent` function from `m20261017_090000_add_unaccent`.
+pub fn contains_ignoring_accents(column: impl IntoColumnRef, term: &str) -> SimpleExpr {
+    let unaccent = || Func::cust(Alias::new("f_unaccent"));
+    let pattern = format!("%{}%", term.to_lowercase());
+    Expr::expr(unaccent().arg(Func::lower(Expr::col(column))))
+        .binary(BinOper::Like, unaccent().arg(pattern))
+}
+
+/// Matches a text column against `term` the way `mode` asks, ignoring case and accents like
+/// [`contains_ignoring_accents`]. Exact and prefix matches can use the `text_pattern_ops`
+/// indexes from `m20261017_160000_add_subject_pattern_indexes`, so `%` and `_` in the term
+/// are escaped rather than treated as wildcards.
+pub fn matches_ignoring_accents(
+    column: impl IntoColumnRef,
+    term: &str,
+    mode: SubjectMatch,
+) -> SimpleExpr {
+    let unaccent = || Func::cust(Alias::new("f_unaccent"));
+    let unaccented = |column| Expr::expr(unaccent().arg(Func::lower(Expr::col(column))));
+    let term = term.to_lowercase();
+    match mode {
+        SubjectMatch::Exact => unaccented(column).eq(unaccent().arg(term)),
+        SubjectMatch::Prefix => {
+            let escaped = term
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            unaccented(column).binary(BinOper::Like, unaccent().arg(format!("{escaped}%")))
+        }
+        SubjectMatch::Contains => contains_ignoring_accents(column, &term),
+    }
 }
 
 /// Defines the structure for metadata subjects filtering.
@@ -90,11 +185,13 @@ pub fn build_filter_expression(params: FilterParams) -> Option<SimpleExpr> {
             Expr::col(accessions_with_metadata::Column::SubjectsArIds),
         ),
     };
-    let (full_text_col_name, ts_lang) = match params.metadata_language {
-        MetadataLanguage::English => ("full_text_en", "english"),
-        MetadataLanguage::Arabic => ("full_text_ar", "arabic"),
+    // English text is indexed with its accents stripped, see `m20261017_090000_add_unaccent`
+    let (full_text_col_name, ts_query) = match params.metadata_language {
+        MetadataLanguage::English => ("full_text_en", "plainto_tsquery('english', f_unaccent($1))"),
+        MetadataLanguage::Arabic => ("full_text_ar", "plainto_tsquery('arabic', $1)"),
     };
 
+    let visibility = params.visibility.condition();
     let mut expression = match (
         params.query_term,
         params.date_from,
@@ -105,12 +202,12 @@ pub fn build_filter_expression(params: FilterParams) -> Option<SimpleExpr> {
             let mut expression = Expr::cust(full_text_col_name)
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values(format!("plainto_tsquery('{ts_lang}', $1)"), [&term]),
+                    Expr::cust_with_values(ts_query, [&term]),
                 )
                 .and(accessions_with_metadata::Column::DublinMetadataDate.gte(from))
                 .and(accessions_with_metadata::Column::DublinMetadataDate.lte(to))
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private));
+                .and(visibility.clone());
             expression =
                 add_array_operators_to_subjects(expression, subjects_column, metadata_subjects);
             Some(expression)
@@ -119,11 +216,11 @@ pub fn build_filter_expression(params: FilterParams) -> Option<SimpleExpr> {
             let mut expression = Expr::cust(full_text_col_name)
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values(format!("plainto_tsquery('{ts_lang}', $1)"), [&term]),
+                    Expr::cust_with_values(ts_query, [&term]),
                 )
                 .and(accessions_with_metadata::Column::DublinMetadataDate.gte(from))
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private));
+                .and(visibility.clone());
             expression =
                 add_array_operators_to_subjects(expression, subjects_column, metadata_subjects);
 
@@ -133,11 +230,11 @@ pub fn build_filter_expression(params: FilterParams) -> Option<SimpleExpr> {
             let mut expression = Expr::cust(full_text_col_name)
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values(format!("plainto_tsquery('{ts_lang}', $1)"), [&term]),
+                    Expr::cust_with_values(ts_query, [&term]),
                 )
                 .and(accessions_with_metadata::Column::DublinMetadataDate.lte(to))
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private));
+                .and(visibility.clone());
             expression =
                 add_array_operators_to_subjects(expression, subjects_column, metadata_subjects);
             Some(expression)
@@ -146,10 +243,10 @@ pub fn build_filter_expression(params: FilterParams) -> Option<SimpleExpr> {
             let mut expression = Expr::cust(full_text_col_name)
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values(format!("plainto_tsquery('{ts_lang}', $1)"), [&term]),
+                    Expr::cust_with_values(ts_query, [&term]),
                 )
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private));
+                .and(visibility.clone());
             expression =
                 add_array_operators_to_subjects(expression, subjects_column, metadata_subjects);
             Some(expression)
@@ -159,7 +256,7 @@ pub fn build_filter_expression(params: FilterParams) -> Option<SimpleExpr> {
                 .gte(from)
                 .and(accessions_with_metadata::Column::DublinMetadataDate.lte(to))
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private));
+                .and(visibility.clone());
             expression =
                 add_array_operators_to_subjects(expression, subjects_column, metadata_subjects);
             Some(expression)
@@ -168,7 +265,7 @@ pub fn build_filter_expression(params: FilterParams) -> Option<SimpleExpr> {
             let mut expression = accessions_with_metadata::Column::DublinMetadataDate
                 .gte(from)
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private));
+                .and(visibility.clone());
             expression =
                 add_array_operators_to_subjects(expression, subjects_column, metadata_subjects);
             Some(expression)
@@ -177,15 +274,13 @@ pub fn build_filter_expression(params: FilterParams) -> Option<SimpleExpr> {
             let mut expression = accessions_with_metadata::Column::DublinMetadataDate
                 .lte(to)
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private));
+                .and(visibility.clone());
             expression =
                 add_array_operators_to_subjects(expression, subjects_column, metadata_subjects);
             Some(expression)
         }
         (None, None, None, Some(metadata_subjects)) => {
-            let mut expression = lang_filter
-                .eq(true)
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private));
+            let mut expression = lang_filter.eq(true).and(visibility.clone());
             expression =
                 add_array_operators_to_subjects(expression, subjects_column, metadata_subjects);
             Some(expression)
@@ -194,66 +289,62 @@ pub fn build_filter_expression(params: FilterParams) -> Option<SimpleExpr> {
             Expr::cust(full_text_col_name)
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values(format!("plainto_tsquery('{ts_lang}', $1)"), [&term]),
+                    Expr::cust_with_values(ts_query, [&term]),
                 )
                 .and(accessions_with_metadata::Column::DublinMetadataDate.gte(from))
                 .and(accessions_with_metadata::Column::DublinMetadataDate.lte(to))
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private)),
+                .and(visibility.clone()),
         ),
         (Some(term), Some(from), None, None) => Some(
             Expr::cust(full_text_col_name)
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values(format!("plainto_tsquery('{ts_lang}', $1)"), [&term]),
+                    Expr::cust_with_values(ts_query, [&term]),
                 )
                 .and(accessions_with_metadata::Column::DublinMetadataDate.gte(from))
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private)),
+                .and(visibility.clone()),
         ),
         (Some(term), None, Some(to), None) => Some(
             Expr::cust(full_text_col_name)
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values(format!("plainto_tsquery('{ts_lang}', $1)"), [&term]),
+                    Expr::cust_with_values(ts_query, [&term]),
                 )
                 .and(accessions_with_metadata::Column::DublinMetadataDate.lte(to))
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private)),
+                .and(visibility.clone()),
         ),
         (Some(term), None, None, None) => Some(
             Expr::cust(full_text_col_name)
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values(format!("plainto_tsquery('{ts_lang}', $1)"), [&term]),
+                    Expr::cust_with_values(ts_query, [&term]),
                 )
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private)),
+                .and(visibility.clone()),
         ),
         (None, Some(from), Some(to), None) => Some(
             accessions_with_metadata::Column::DublinMetadataDate
                 .gte(from)
                 .and(accessions_with_metadata::Column::DublinMetadataDate.lte(to))
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private)),
+                .and(visibility.clone()),
         ),
         (None, Some(from), None, None) => Some(
             accessions_with_metadata::Column::DublinMetadataDate
                 .gte(from)
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private)),
+                .and(visibility.clone()),
         ),
         (None, None, Some(to), None) => Some(
             accessions_with_metadata::Column::DublinMetadataDate
                 .lte(to)
                 .and(lang_filter.eq(true))
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private)),
-        ),
-        (None, None, None, None) => Some(
-            lang_filter
-                .eq(true)
-                .and(accessions_with_metadata::Column::IsPrivate.eq(params.is_private)),
+                .and(visibility.clone()),
         ),
+        (None, None, None, None) => Some(lang_filter.eq(true).and(visibility.clone())),
     };
 
     if let Some(url) = params.url_filter {
@@ -262,6 +353,53 @@ pub fn build_filter_expression(params: FilterParams) -> Option<SimpleExpr> {
             expression.map(|e| e.and(accessions_with_metadata::Column::SeedUrl.like(url_like)));
     }
 
+    if let Some(has_content_warning) = params.has_content_warning {
+        let content_warning = accessions_with_metadata::Column::ContentWarning;
+        let condition = if has_content_warning {
+            content_warning.is_not_null()
+        } else {
+            content_warning.is_null()
+        };
+        expression = expression.map(|e| e.and(condition));
+    }
+
+    if let Some(organization_id) = params.organization_id {
+        expression = expression
+            .map(|e| e.and(accessions_with_metadata::Column::OrganizationId.eq(organization_id)));
+    }
+
+    // Workflow labels live outside the view, so match against the link table
+    if let Some(label_ids) = params.workflow_labels {
+        let labelled_accessions = Query::select()
+            .column(accession_workflow_label::Column::AccessionId)
+            .from(accession_workflow_label::Entity)
+            .and_where(accession_workflow_label::Column::LabelId.is_in(label_ids))
+            .to_owned();
+        expression = expression
+            .map(|e| e.and(accessions_with_metadata::Column::Id.in_subquery(labelled_accessions)));
+    }
+
+    // So does what's known about social media posts, see `crate::social_metadata`
+    if params.social_platform.is_some() || params.author_handle.is_some() {
+        let mut posts = Query::select()
+            .column(social_metadata::Column::AccessionId)
+            .from(social_metadata::Entity)
+            .to_owned();
+        if let Some(platform) = params.social_platform {
+            posts.and_where(social_metadata::Column::Platform.eq(platform));
+        }
+        if let Some(author_handle) = params.author_handle {
+            posts.and_where(
+                Expr::expr(Func::lower(Expr::col(
+                    social_metadata::Column::AuthorHandle,
+                )))
+                .eq(normalize_handle(&author_handle).to_lowercase()),
+            );
+        }
+        expression =
+            expression.map(|e| e.and(accessions_with_metadata::Column::Id.in_subquery(posts)));
+    }
+
     expression
 }
 
@@ -269,6 +407,8 @@ pub fn build_filter_expression(params: FilterParams) -> Option<SimpleExpr> {
 mod tests {
     use super::*;
     use chrono::NaiveDate;
+    use entity::dublin_metadata_subject_en;
+    use sea_orm::sea_query::PostgresQueryBuilder;
 
     #[test]
     fn test_build_filter_url_filter() {
@@ -279,7 +419,12 @@ mod tests {
             url_filter: Some("https://example.com".to_string()),
             date_from: None,
             date_to: None,
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
         let actual = build_filter_expression(params);
         let expected = Some(
@@ -291,6 +436,40 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn matches_domains_and_their_subdomains() {
+        assert_eq!(
+            domain_pattern("bbc.co.uk"),
+            r"^https?://([^/?#@]*\.)?bbc\.co\.uk(:[0-9]+)?([/?#]|$)"
+        );
+    }
+
+    #[test]
+    fn test_build_filter_content_warning() {
+        let params = FilterParams {
+            metadata_language: MetadataLanguage::English,
+            metadata_subjects: None,
+            query_term: None,
+            url_filter: None,
+            date_from: None,
+            date_to: None,
+            has_content_warning: Some(false),
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
+        };
+        let actual = build_filter_expression(params);
+        let expected = Some(
+            Expr::col(accessions_with_metadata::Column::HasEnglishMetadata)
+                .eq(true)
+                .and(accessions_with_metadata::Column::IsPrivate.eq(false))
+                .and(accessions_with_metadata::Column::ContentWarning.is_null()),
+        );
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn test_build_filter_none_params() {
         let params = FilterParams {
@@ -300,7 +479,12 @@ mod tests {
             url_filter: None,
             date_from: None,
             date_to: None,
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
         let actual = build_filter_expression(params);
         let expected = Some(
@@ -316,7 +500,12 @@ mod tests {
             url_filter: None,
             date_from: None,
             date_to: None,
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
         let actual = build_filter_expression(params);
         let expected = Some(
@@ -336,20 +525,21 @@ mod tests {
             url_filter: None,
             date_from: None,
             date_to: None,
-            is_private: false,
-        };
-        let actual = build_filter_expression(params.clone());
-        let (_full_text_col, ts_lang) = match params.metadata_language {
-            MetadataLanguage::English => ("full_text_en", "english"),
-            MetadataLanguage::Arabic => ("full_text_ar", "arabic"),
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
+        let actual = build_filter_expression(params);
         let term = "Test".to_string();
 
         let expected = Some(
             Expr::cust("full_text_en")
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values(format!("plainto_tsquery('{ts_lang}', $1)"), [&term]),
+                    Expr::cust_with_values("plainto_tsquery('english', f_unaccent($1))", [&term]),
                 )
                 .and(Expr::col(accessions_with_metadata::Column::HasEnglishMetadata).eq(true))
                 .and(accessions_with_metadata::Column::IsPrivate.eq(false)),
@@ -366,16 +556,20 @@ mod tests {
             url_filter: None,
             date_from: None,
             date_to: None,
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
-        let actual = build_filter_expression(params.clone());
-        let (_full_text_col, ts_lang) = ("full_text_ar", "arabic");
+        let actual = build_filter_expression(params);
         let term = "اختبار".to_string();
         let expected = Some(
             Expr::cust("full_text_ar")
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values(format!("plainto_tsquery('{ts_lang}', $1)"), [&term]),
+                    Expr::cust_with_values("plainto_tsquery('arabic', $1)", [&term]),
                 )
                 .and(Expr::col(accessions_with_metadata::Column::HasArabicMetadata).eq(true))
                 .and(accessions_with_metadata::Column::IsPrivate.eq(false)),
@@ -401,7 +595,12 @@ mod tests {
             url_filter: None,
             date_from: Some(from_date),
             date_to: Some(to_date),
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
 
         let actual = build_filter_expression(params);
@@ -429,7 +628,12 @@ mod tests {
             url_filter: None,
             date_from: Some(from_date),
             date_to: None,
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
 
         let actual = build_filter_expression(params);
@@ -456,7 +660,12 @@ mod tests {
             url_filter: None,
             date_from: None,
             date_to: Some(to_date),
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
 
         let actual = build_filter_expression(params);
@@ -487,7 +696,12 @@ mod tests {
             url_filter: None,
             date_from: Some(from_date),
             date_to: Some(to_date),
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
 
         let actual = build_filter_expression(params);
@@ -497,7 +711,7 @@ mod tests {
             Expr::cust("full_text_en")
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values("plainto_tsquery('english', $1)", [&term]),
+                    Expr::cust_with_values("plainto_tsquery('english', f_unaccent($1))", [&term]),
                 )
                 .and(accessions_with_metadata::Column::DublinMetadataDate.gte(from_date))
                 .and(accessions_with_metadata::Column::DublinMetadataDate.lte(to_date))
@@ -516,7 +730,12 @@ mod tests {
             url_filter: None,
             date_from: None,
             date_to: None,
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
         let actual_lower = build_filter_expression(params_lower);
         let params_upper = FilterParams {
@@ -526,7 +745,12 @@ mod tests {
             url_filter: None,
             date_from: None,
             date_to: None,
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
         let actual_upper = build_filter_expression(params_upper);
 
@@ -535,7 +759,10 @@ mod tests {
             Expr::cust("full_text_en")
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values("plainto_tsquery('english', $1)", [&term_lower]),
+                    Expr::cust_with_values(
+                        "plainto_tsquery('english', f_unaccent($1))",
+                        [&term_lower],
+                    ),
                 )
                 .and(Expr::col(accessions_with_metadata::Column::HasEnglishMetadata).eq(true))
                 .and(accessions_with_metadata::Column::IsPrivate.eq(false)),
@@ -546,7 +773,10 @@ mod tests {
             Expr::cust("full_text_en")
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values("plainto_tsquery('english', $1)", [&term_upper]),
+                    Expr::cust_with_values(
+                        "plainto_tsquery('english', f_unaccent($1))",
+                        [&term_upper],
+                    ),
                 )
                 .and(Expr::col(accessions_with_metadata::Column::HasEnglishMetadata).eq(true))
                 .and(accessions_with_metadata::Column::IsPrivate.eq(false)),
@@ -569,7 +799,12 @@ mod tests {
             url_filter: None,
             date_from: None,
             date_to: None,
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
         let actual = build_filter_expression(params);
 
@@ -597,7 +832,12 @@ mod tests {
             url_filter: None,
             date_from: None,
             date_to: None,
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
         let actual = build_filter_expression(params);
 
@@ -625,7 +865,12 @@ mod tests {
             url_filter: None,
             date_from: None,
             date_to: None,
-            is_private: false,
+            has_content_warning: None,
+            visibility: Visibility::Public,
+            workflow_labels: None,
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
         };
         let actual = build_filter_expression(params);
 
@@ -635,7 +880,7 @@ mod tests {
             Expr::cust("full_text_en")
                 .binary(
                     PgBinOper::Matches,
-                    Expr::cust_with_values("plainto_tsquery('english', $1)", [&term]),
+                    Expr::cust_with_values("plainto_tsquery('english', f_unaccent($1))", [&term]),
                 )
                 .and(Expr::col(accessions_with_metadata::Column::HasEnglishMetadata).eq(true))
                 .and(accessions_with_metadata::Column::IsPrivate.eq(false))
@@ -644,4 +889,135 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_build_filter_workflow_labels() {
+        let params = FilterParams {
+            metadata_language: MetadataLanguage::English,
+            metadata_subjects: None,
+            query_term: None,
+            url_filter: None,
+            date_from: None,
+            date_to: None,
+            has_content_warning: None,
+            visibility: Visibility::Private(None),
+            workflow_labels: Some(vec![4, 5]),
+            organization_id: None,
+            social_platform: None,
+            author_handle: None,
+        };
+        let actual = build_filter_expression(params);
+        let expected = Some(
+            Expr::col(accessions_with_metadata::Column::HasEnglishMetadata)
+                .eq(true)
+                .and(accessions_with_metadata::Column::IsPrivate.eq(true))
+                .and(
+                    accessions_with_metadata::Column::Id.in_subquery(
+                        Query::select()
+                            .column(accession_workflow_label::Column::AccessionId)
+                            .from(accession_workflow_label::Entity)
+                            .and_where(accession_workflow_label::Column::LabelId.is_in(vec![4, 5]))
+                            .to_owned(),
+                    ),
+                ),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_build_filter_organization() {
+        let params = FilterParams {
+            metadata_language: MetadataLanguage::English,
+            organization_id: Some(2),
+            ..Default::default()
+        };
+        let actual = build_filter_expression(params);
+        let expected = Some(
+            Expr::col(accessions_with_metadata::Column::HasEnglishMetadata)
+                .eq(true)
+                .and(accessions_with_metadata::Column::IsPrivate.eq(false))
+                .and(accessions_with_metadata::Column::OrganizationId.eq(2)),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_build_filter_social_media_author() {
+        let params = FilterParams {
+            metadata_language: MetadataLanguage::English,
+            social_platform: Some(SocialPlatform::X),
+            author_handle: Some("@RSFSudan".to_string()),
+            ..Default::default()
+        };
+        let sql = Query::select()
+            .column(accessions_with_metadata::Column::Id)
+            .from(accessions_with_metadata::Entity)
+            .and_where(build_filter_expression(params).unwrap())
+            .to_string(PostgresQueryBuilder);
+        assert!(sql.contains(
+            r#""accessions_with_metadata"."id" IN (SELECT "accession_id" FROM "social_metadata" WHERE"#
+        ));
+        assert!(sql.contains(r#""social_metadata"."platform" = (CAST('x' AS "social_platform"))"#));
+        assert!(sql.contains(r#"LOWER("author_handle") = 'rsfsudan'"#));
+    }
+
+    #[test]
+    fn test_build_filter_private_in_one_publication_state() {
+        let params = FilterParams {
+            metadata_language: MetadataLanguage::Arabic,
+            visibility: Visibility::Private(Some(PublicationState::InReview)),
+            ..Default::default()
+        };
+        let actual = build_filter_expression(params);
+        let expected = Some(
+            Expr::col(accessions_with_metadata::Column::HasArabicMetadata)
+                .eq(true)
+                .and(
+                    accessions_with_metadata::Column::IsPrivate.eq(true).and(
+                        accessions_with_metadata::Column::PublicationState
+                            .eq(PublicationState::InReview),
+                    ),
+                ),
+        );
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn matches_text_ignoring_case_and_accents() {
+        let sql = Query::select()
+            .column(dublin_metadata_subject_en::Column::Id)
+            .from(dublin_metadata_subject_en::Entity)
+            .and_where(contains_ignoring_accents(
+                dublin_metadata_subject_en::Column::Subject,
+                "Genēina",
+            ))
+            .to_string(PostgresQueryBuilder);
+        assert_eq!(
+            sql,
+            r#"SELECT "id" FROM "dublin_metadata_subject_en" WHERE f_unaccent(LOWER("subject")) LIKE f_unaccent('%genēina%')"#
+        );
+    }
+
+    #[test]
+    fn matches_text_exactly_or_by_prefix() {
+        let sql = |mode| {
+            Query::select()
+                .column(dublin_metadata_subject_en::Column::Id)
+                .from(dublin_metadata_subject_en::Entity)
+                .and_where(matches_ignoring_accents(
+                    dublin_metadata_subject_en::Column::Subject,
+                    "Dar_fur",
+                    mode,
+                ))
+                .to_string(PostgresQueryBuilder)
+        };
+        assert_eq!(
+            sql(SubjectMatch::Exact),
+            r#"SELECT "id" FROM "dublin_metadata_subject_en" WHERE f_unaccent(LOWER("subject")) = f_unaccent('dar_fur')"#
+        );
+        assert_eq!(
+            sql(SubjectMatch::Prefix),
+            r#"SELECT "id" FROM "dublin_metadata_subject_en" WHERE f_unaccent(LOWER("subject")) LIKE f_unaccent(E'dar\\_fur%')"#
+        );
+    }
 }
@@ -0,0 +1,151 @@
+//! Repository module for electing the API instance that runs the background tasks.
+//!
+//! Leadership is a session level Postgres advisory lock. Postgres releases it as soon as
+//! the session holding it ends, so when the leader dies or loses its database another
+//! instance takes the lock on its next attempt. Each instance names its session after
+//! itself, so anyone can look up which instance holds the lock in `pg_stat_activity`.
+
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, DbErr, Statement};
+
+/// Key of the advisory lock the leader holds, "SDA" in ASCII
+pub const LEADER_LOCK_KEY: i64 = 0x53_44_41;
+
+/// Repository implementation for leader election with Postgres advisory locks.
+///
+/// Advisory locks belong to the session that took them, so `db_session` must be a pool of
+/// exactly one connection that is kept open, not the pool the rest of the API shares.
+#[derive(Debug, Clone, Default)]
+pub struct DBLeaderElectionRepo {
+    pub db_session: DatabaseConnection,
+}
+
+/// Defines the interface for leader election.
+#[async_trait]
+pub trait LeaderElectionRepo: Send + Sync {
+    /// Takes leadership if no one holds it, or checks this instance still holds it.
+    ///
+    /// # Arguments
+    /// * `instance` - Name of this instance, which others see as the leader's
+    ///
+    /// # Returns
+    /// Whether this instance is the leader
+    async fn try_lead(&self, instance: &str) -> Result<bool, DbErr>;
+
+    /// Name of the instance that is the leader, `None` while no instance is.
+    async fn current_leader(&self) -> Result<Option<String>, DbErr>;
+}
+
+#[async_trait]
+impl LeaderElectionRepo for DBLeaderElectionRepo {
+    async fn try_lead(&self, instance: &str) -> Result<bool, DbErr> {
+        // a reconnected session has lost both the lock and its name
+        self.db_session
+            .execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                "SELECT set_config('application_name', $1, false)",
+                [instance.into()],
+            ))
+            .await?;
+        // the lock is reentrant, so taking it again while holding it would need as many
+        // unlocks to let go of it
+        let row = self
+            .db_session
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"SELECT CASE
+                    WHEN EXISTS (
+                        SELECT 1 FROM pg_locks
+                        WHERE locktype = 'advisory' AND granted AND pid = pg_backend_pid()
+                            AND classid = 0 AND objid::bigint = $1 AND objsubid = 1
+                    ) THEN true
+                    ELSE pg_try_advisory_lock($1)
+                END AS leading"#,
+                [LEADER_LOCK_KEY.into()],
+            ))
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound("Leader lock query returned no row".into()))?;
+        row.try_get("", "leading")
+    }
+
+    async fn current_leader(&self) -> Result<Option<String>, DbErr> {
+        let row = self
+            .db_session
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"SELECT activity.application_name AS leader
+                FROM pg_locks
+                JOIN pg_stat_activity activity ON activity.pid = pg_locks.pid
+                WHERE pg_locks.locktype = 'advisory' AND pg_locks.granted
+                    AND pg_locks.classid = 0 AND pg_locks.objid::bigint = $1
+                    AND pg_locks.objsubid = 1"#,
+                [LEADER_LOCK_KEY.into()],
+            ))
+            .await?;
+        row.map(|row| row.try_get("", "leader")).transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db::migrated_test_db;
+    use pretty_assertions::assert_eq;
+    use sea_orm::{ConnectOptions, Database};
+    use std::env;
+
+    /// A single connection to the same database, as another instance would have.
+    async fn instance_connection(db_session: &DatabaseConnection) -> DBLeaderElectionRepo {
+        let database: String = db_session
+            .query_one(Statement::from_string(
+                DbBackend::Postgres,
+                "SELECT current_database() AS name",
+            ))
+            .await
+            .unwrap()
+            .unwrap()
+            .try_get("", "name")
+            .unwrap();
+        let mut url = reqwest::Url::parse(&env::var("TEST_POSTGRES_URL").unwrap()).unwrap();
+        url.set_path(&database);
+        let mut options = ConnectOptions::new(url.to_string());
+        options.max_connections(1).min_connections(1);
+        DBLeaderElectionRepo {
+            db_session: Database::connect(options).await.unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn elects_one_leader_and_fails_over() {
+        let db = migrated_test_db().await;
+        let db_session = db.clone();
+        let first = instance_connection(&db_session).await;
+        let second = instance_connection(&db_session).await;
+        assert_eq!(first.current_leader().await.unwrap(), None);
+
+        assert!(first.try_lead("api-1").await.unwrap());
+        assert!(first.try_lead("api-1").await.unwrap());
+        assert!(!second.try_lead("api-2").await.unwrap());
+        assert_eq!(
+            second.current_leader().await.unwrap(),
+            Some("api-1".to_string())
+        );
+
+        first.db_session.close().await.unwrap();
+        // the lock goes once the server has ended the closed session, which can take a moment
+        let mut took_over = false;
+        for _ in 0..20 {
+            if second.try_lead("api-2").await.unwrap() {
+                took_over = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert!(took_over);
+        assert_eq!(
+            second.current_leader().await.unwrap(),
+            Some("api-2".to_string())
+        );
+    }
+}
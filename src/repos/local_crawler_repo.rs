@@ -0,0 +1,199 @@
+//! Repository for crawling with browsertrix-crawler run on the API's own host.
+//!
+//! [browsertrix-crawler](https://github.com/webrecorder/browsertrix-crawler) is the crawler the
+//! hosted Browsertrix runs, and running it directly, usually through `docker run`, archives
+//! URLs without a Browsertrix account. Each crawl gets its own collection named after the
+//! crawl's ID, so its WACZ ends up at `<collections dir>/<id>/<id>.wacz`. Which crawls are
+//! still running is only kept in memory, so crawls running when the server restarts are lost.
+
+use crate::clock::SharedIdGen;
+use crate::crawl_state_machine::LaunchedCrawl;
+use crate::models::common::CrawlerBackend;
+use crate::models::request::CreateCrawlRequest;
+use crate::repos::crawler_repo::{CrawlArchive, CrawlerError, CrawlerRepo};
+use async_trait::async_trait;
+use bytes::Bytes;
+use entity::sea_orm_active_enums::DublinMetadataFormat;
+use futures::StreamExt;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use tracing::{error, info};
+use uuid::Uuid;
+
+/// How much of a WACZ is read from disk at a time.
+const READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Crawls by running browsertrix-crawler.
+#[derive(Debug, Clone, Default)]
+pub struct LocalCrawlerRepo {
+    /// Command running the crawler's `crawl` entrypoint, split on whitespace, e.g.
+    /// `docker run --rm -v /srv/crawls:/crawls webrecorder/browsertrix-crawler crawl`
+    pub command: String,
+    /// Where the crawler's collections are on this host, e.g. `/srv/crawls/collections`
+    pub collections_dir: PathBuf,
+    /// Generates the IDs of crawls
+    pub ids: SharedIdGen,
+    /// IDs of crawls whose crawler hasn't exited yet
+    running: Arc<Mutex<HashSet<Uuid>>>,
+}
+
+impl LocalCrawlerRepo {
+    pub fn new(command: String, collections_dir: PathBuf, ids: SharedIdGen) -> Self {
+        Self {
+            command,
+            collections_dir,
+            ids,
+            running: Arc::default(),
+        }
+    }
+
+    /// Where a crawl's WACZ is written.
+    fn wacz_path(&self, crawl_id: Uuid) -> PathBuf {
+        self.collections_dir
+            .join(crawl_id.to_string())
+            .join(format!("{crawl_id}.wacz"))
+    }
+}
+
+/// Arguments crawling one page the way crawls in the hosted Browsertrix are configured, see
+/// [`crate::config::BrowsertrixCrawlConfig`].
+fn crawler_args(url: &str, crawl_id: Uuid) -> Vec<String> {
+    let collection = crawl_id.to_string();
+    [
+        "--url",
+        url,
+        "--collection",
+        collection.as_str(),
+        "--generateWACZ",
+        "--scopeType",
+        "page",
+        "--postLoadDelay",
+        "120",
+        "--behaviors",
+        "autoscroll,autoplay,autofetch,siteSpecific",
+    ]
+    .map(str::to_string)
+    .to_vec()
+}
+
+#[async_trait]
+impl CrawlerRepo for LocalCrawlerRepo {
+    fn backend(&self) -> CrawlerBackend {
+        CrawlerBackend::Local
+    }
+
+    async fn launch(
+        &self,
+        create_crawl_request: CreateCrawlRequest,
+    ) -> Result<LaunchedCrawl, CrawlerError> {
+        let crawl_id = self.ids.new_id();
+        let mut parts = self.command.split_whitespace();
+        let program = parts.next().ok_or("The local crawler command is empty")?;
+        let child = Command::new(program)
+            .args(parts)
+            .args(crawler_args(&create_crawl_request.url, crawl_id))
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        self.running.lock().unwrap().insert(crawl_id);
+        let running = self.running.clone();
+        // the crawler runs for as long as the crawl takes, so keep waiting on it off the async workers
+        tokio::task::spawn_blocking(move || {
+            match child.wait_with_output() {
+                Err(err) => error!(%err, %crawl_id, "Error occurred waiting on local crawler"),
+                Ok(output) if !output.status.success() => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let last_line = stderr.lines().last().unwrap_or_default();
+                    error!(%crawl_id, "Local crawler exited with {}: {last_line}", output.status);
+                }
+                Ok(_) => info!(%crawl_id, "Local crawler finished"),
+            }
+            running.lock().unwrap().remove(&crawl_id);
+        });
+        Ok(LaunchedCrawl {
+            id: crawl_id,
+            job_run_id: crawl_id.to_string(),
+            org_id: None,
+        })
+    }
+
+    async fn is_finished(&self, crawl: &LaunchedCrawl) -> Result<bool, CrawlerError> {
+        Ok(!self.running.lock().unwrap().contains(&crawl.id))
+    }
+
+    async fn download(&self, crawl: &LaunchedCrawl) -> Result<CrawlArchive, CrawlerError> {
+        let path = self.wacz_path(crawl.id);
+        let file = tokio::task::spawn_blocking(move || {
+            File::open(&path).map_err(|err| format!("Could not open {}: {err}", path.display()))
+        })
+        .await??;
+        let stream = futures::stream::try_unfold(file, |mut file| async move {
+            tokio::task::spawn_blocking(move || -> Result<_, CrawlerError> {
+                let mut chunk = vec![0; READ_CHUNK_SIZE];
+                let read = file.read(&mut chunk)?;
+                chunk.truncate(read);
+                Ok((read > 0).then(|| (Bytes::from(chunk), file)))
+            })
+            .await?
+        });
+        Ok(CrawlArchive {
+            format: DublinMetadataFormat::Wacz,
+            stream: stream.boxed(),
+            metadata_json: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+
+    #[test]
+    fn crawls_one_page_into_its_own_collection() {
+        let crawl_id = Uuid::nil();
+        let args = crawler_args("https://example.com", crawl_id);
+        assert_eq!(
+            args[..5],
+            [
+                "--url",
+                "https://example.com",
+                "--collection",
+                "00000000-0000-0000-0000-000000000000",
+                "--generateWACZ"
+            ]
+        );
+        assert!(args.windows(2).any(|pair| pair == ["--scopeType", "page"]));
+    }
+
+    #[tokio::test]
+    async fn streams_the_wacz_of_a_finished_crawl() {
+        let collections_dir = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let repo = LocalCrawlerRepo::new(
+            "true".to_string(),
+            collections_dir.clone(),
+            SharedIdGen::default(),
+        );
+        let crawl = LaunchedCrawl {
+            id: Uuid::new_v4(),
+            job_run_id: String::new(),
+            org_id: None,
+        };
+        assert!(repo.is_finished(&crawl).await.unwrap());
+        assert!(repo.download(&crawl).await.is_err());
+
+        let wacz_path = repo.wacz_path(crawl.id);
+        fs::create_dir_all(wacz_path.parent().unwrap()).unwrap();
+        fs::write(&wacz_path, b"PK wacz").unwrap();
+        let archive = repo.download(&crawl).await.unwrap();
+        let chunks: Vec<Bytes> = archive.stream.try_collect().await.unwrap();
+        assert_eq!(chunks.concat(), b"PK wacz");
+        fs::remove_dir_all(collections_dir).unwrap();
+    }
+}
@@ -0,0 +1,190 @@
+//! Repository for producing web friendly derivatives of uploaded images and videos.
+//!
+//! Shells out to [ffmpeg](https://ffmpeg.org), which reads the original straight from a
+//! presigned S3 URL. Output goes to a temporary file since MP4s need a seekable output
+//! to move their index to the front for streaming playback.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use entity::sea_orm_active_enums::DerivativeKind;
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::Command;
+use uuid::Uuid;
+
+/// Web videos are scaled down to at most this many pixels tall.
+const WEB_VIDEO_MAX_HEIGHT: u32 = 720;
+
+/// Thumbnails are scaled down to at most this many pixels wide.
+const THUMBNAIL_MAX_WIDTH: u32 = 480;
+
+/// Returns the file extension and content type of a derivative.
+pub fn derivative_file_type(kind: &DerivativeKind) -> (&'static str, &'static str) {
+    match kind {
+        DerivativeKind::WebVideo => ("mp4", "video/mp4"),
+        DerivativeKind::Thumbnail => ("webp", "image/webp"),
+    }
+}
+
+#[async_trait]
+pub trait MediaTranscoderRepo: Send + Sync {
+    /// Produces a derivative of an image or video.
+    ///
+    /// # Arguments
+    /// * `source_url` - URL the original file can be read from
+    /// * `kind` - The derivative to produce
+    ///
+    /// # Returns
+    /// The derivative file contents, see [`derivative_file_type`] for its format
+    ///
+    /// # Errors
+    /// Returns Error if the transcoder could not be run or failed
+    async fn transcode(
+        &self,
+        source_url: &str,
+        kind: DerivativeKind,
+    ) -> Result<Bytes, Box<dyn Error + Send + Sync>>;
+}
+
+/// Transcodes by running an ffmpeg binary.
+#[derive(Debug, Clone, Default)]
+pub struct FfmpegMediaTranscoderRepo {
+    /// Path to the ffmpeg binary, e.g. `/usr/bin/ffmpeg`
+    pub ffmpeg_path: String,
+}
+
+#[async_trait]
+impl MediaTranscoderRepo for FfmpegMediaTranscoderRepo {
+    async fn transcode(
+        &self,
+        source_url: &str,
+        kind: DerivativeKind,
+    ) -> Result<Bytes, Box<dyn Error + Send + Sync>> {
+        let (extension, _) = derivative_file_type(&kind);
+        let output_path: PathBuf =
+            std::env::temp_dir().join(format!("{}.{extension}", Uuid::new_v4()));
+        let mut command = Command::new(&self.ffmpeg_path);
+        command.args(ffmpeg_args(
+            source_url,
+            &kind,
+            &output_path.to_string_lossy(),
+        ));
+        // ffmpeg blocks for as long as the transcode takes, so keep it off the async workers
+        tokio::task::spawn_blocking(move || {
+            let output = command.output();
+            let result = match output {
+                Err(err) => Err(err.into()),
+                Ok(output) if !output.status.success() => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    let last_line = stderr.lines().last().unwrap_or_default();
+                    Err(format!("ffmpeg exited with {}: {last_line}", output.status).into())
+                }
+                Ok(_) => std::fs::read(&output_path)
+                    .map(Bytes::from)
+                    .map_err(Into::into),
+            };
+            // ffmpeg may leave a partial file behind when it fails
+            let _ = std::fs::remove_file(&output_path);
+            result
+        })
+        .await?
+    }
+}
+
+/// Builds the ffmpeg arguments for producing a derivative.
+///
+/// Metadata is never copied across, so derivatives can't leak anything that was kept
+/// on the original.
+fn ffmpeg_args(source_url: &str, kind: &DerivativeKind, output_path: &str) -> Vec<String> {
+    let mut args: Vec<String> = ["-y", "-nostdin", "-loglevel", "error", "-i", source_url]
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    args.extend(["-map_metadata".to_string(), "-1".to_string()]);
+    match kind {
+        DerivativeKind::WebVideo => args.extend(
+            [
+                "-vf",
+                &format!("scale=-2:'min({WEB_VIDEO_MAX_HEIGHT},ih)'"),
+                "-c:v",
+                "libx264",
+                "-preset",
+                "veryfast",
+                "-crf",
+                "23",
+                "-pix_fmt",
+                "yuv420p",
+                "-c:a",
+                "aac",
+                "-b:a",
+                "128k",
+                "-movflags",
+                "+faststart",
+                "-f",
+                "mp4",
+            ]
+            .map(str::to_string),
+        ),
+        DerivativeKind::Thumbnail => args.extend(
+            [
+                "-vf",
+                &format!("thumbnail,scale='min({THUMBNAIL_MAX_WIDTH},iw)':-2"),
+                "-frames:v",
+                "1",
+                "-c:v",
+                "libwebp",
+                "-quality",
+                "80",
+                "-f",
+                "webp",
+            ]
+            .map(str::to_string),
+        ),
+    }
+    args.push(output_path.to_string());
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn builds_web_video_args() {
+        let args = ffmpeg_args(
+            "https://s3/video.mp4",
+            &DerivativeKind::WebVideo,
+            "/tmp/out.mp4",
+        );
+        assert_eq!(
+            args[..6],
+            [
+                "-y",
+                "-nostdin",
+                "-loglevel",
+                "error",
+                "-i",
+                "https://s3/video.mp4"
+            ]
+        );
+        assert!(args.windows(2).any(|pair| pair == ["-map_metadata", "-1"]));
+        assert!(args
+            .windows(2)
+            .any(|pair| pair == ["-vf", "scale=-2:'min(720,ih)'"]));
+        assert!(args.windows(2).any(|pair| pair == ["-c:v", "libx264"]));
+        assert_eq!(args.last().unwrap(), "/tmp/out.mp4");
+    }
+
+    #[test]
+    fn builds_thumbnail_args() {
+        let args = ffmpeg_args(
+            "https://s3/photo.jpg",
+            &DerivativeKind::Thumbnail,
+            "/tmp/out.webp",
+        );
+        assert!(args.windows(2).any(|pair| pair == ["-frames:v", "1"]));
+        assert!(args.windows(2).any(|pair| pair == ["-c:v", "libwebp"]));
+        assert_eq!(args.last().unwrap(), "/tmp/out.webp");
+    }
+}
@@ -1,7 +1,36 @@
+pub mod accession_events_repo;
+pub mod accession_relations_repo;
 pub mod accessions_repo;
+pub mod activity_digest_repo;
+pub mod audit_log_repo;
 pub mod auth_repo;
 pub mod browsertrix_repo;
+pub mod collections_repo;
+pub mod comments_repo;
+pub mod content_digest_repo;
+pub mod crawl_blocklist_repo;
+pub mod crawler_repo;
+pub mod doi_repo;
 pub mod emails_repo;
+pub mod entity_extractor_repo;
+pub mod feature_flags_repo;
 mod filter_builder;
+pub mod leader_election_repo;
+pub mod local_crawler_repo;
+pub mod media_transcoder_repo;
+pub mod oembed_repo;
+pub mod organizations_repo;
+pub mod pagination;
+pub mod pdf_renderer_repo;
+pub mod provenance_repo;
+pub mod quick_capture_repo;
 pub mod s3_repo;
+pub mod social_metadata_repo;
 pub mod subjects_repo;
+pub mod timestamp_repo;
+pub mod translation_repo;
+pub mod uploads_repo;
+pub mod video_capture_repo;
+pub mod video_downloader_repo;
+pub mod virus_scanner_repo;
+pub mod workflow_labels_repo;
@@ -0,0 +1,107 @@
+//! Repository for asking social media platforms about a post, see [`crate::social_metadata`].
+//!
+//! X answers [oEmbed](https://oembed.com) requests without an account, giving the author's
+//! name and profile URL. Telegram has no oEmbed endpoint, but its embed widget at
+//! `https://t.me/<channel>/<id>?embed=1` carries the channel name and a machine readable
+//! publish time. Facebook's oEmbed needs a Meta app token, so Facebook posts only get what
+//! their URL says.
+
+use crate::social_metadata::{
+    handle_from_profile_url, telegram_author_name, telegram_posted_at, PostDetails, SocialPost,
+};
+use async_trait::async_trait;
+use entity::sea_orm_active_enums::SocialPlatform;
+use reqwest::{Client, Error};
+use serde::Deserialize;
+
+/// X's public oEmbed endpoint
+pub const X_OEMBED_URL: &str = "https://publish.twitter.com/oembed";
+/// Where Telegram serves embed widgets for public channel posts
+pub const TELEGRAM_EMBED_BASE_URL: &str = "https://t.me";
+
+#[async_trait]
+pub trait OembedRepo: Send + Sync {
+    /// Looks up what the platform says about a post.
+    ///
+    /// # Arguments
+    /// * `post` - The post, as identified from its URL
+    /// * `url` - The URL the post was crawled from
+    ///
+    /// # Returns
+    /// Whatever the platform gave away, which is nothing for platforms that can't be asked
+    async fn lookup(&self, post: &SocialPost, url: &str) -> Result<PostDetails, Error>;
+}
+
+#[derive(Debug, Deserialize)]
+struct OembedResponse {
+    author_name: Option<String>,
+    author_url: Option<String>,
+}
+
+/// Asks X's oEmbed endpoint and Telegram's embed widgets about posts over HTTP.
+#[derive(Debug, Clone)]
+pub struct HTTPOembedRepo {
+    pub client: Client,
+    pub x_oembed_url: String,
+    pub telegram_embed_base_url: String,
+}
+
+impl Default for HTTPOembedRepo {
+    fn default() -> Self {
+        Self {
+            client: Client::new(),
+            x_oembed_url: X_OEMBED_URL.to_string(),
+            telegram_embed_base_url: TELEGRAM_EMBED_BASE_URL.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl OembedRepo for HTTPOembedRepo {
+    async fn lookup(&self, post: &SocialPost, url: &str) -> Result<PostDetails, Error> {
+        match post.platform {
+            SocialPlatform::X => {
+                let oembed = self
+                    .client
+                    .get(&self.x_oembed_url)
+                    .query(&[("url", url), ("omit_script", "true")])
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json::<OembedResponse>()
+                    .await?;
+                Ok(PostDetails {
+                    author_handle: oembed
+                        .author_url
+                        .as_deref()
+                        .and_then(handle_from_profile_url),
+                    author_name: oembed.author_name,
+                    posted_at: None,
+                })
+            }
+            SocialPlatform::Telegram => {
+                let (Some(channel), Some(post_id)) = (&post.author_handle, &post.post_id) else {
+                    return Ok(PostDetails::default());
+                };
+                let html = self
+                    .client
+                    .get(format!(
+                        "{}/{channel}/{post_id}",
+                        self.telegram_embed_base_url
+                    ))
+                    .query(&[("embed", "1")])
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await?;
+                Ok(PostDetails {
+                    author_handle: None,
+                    author_name: telegram_author_name(&html),
+                    posted_at: telegram_posted_at(&html),
+                })
+            }
+            SocialPlatform::Facebook => Ok(PostDetails::default()),
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Repository module for organizations.
+//!
+//! Organizations give partner archives, such as diaspora groups, their own space in the
+//! archive. Users, accessions and subjects each belong to one, and everything that existed
+//! before organizations belongs to the archive itself, [`DEFAULT_ORGANIZATION_ID`].
+
+use crate::clock::SharedClock;
+use ::entity::archive_user::Entity as ArchiveUser;
+use ::entity::organization::ActiveModel as OrganizationActiveModel;
+use ::entity::organization::Entity as Organization;
+use ::entity::organization::Model as OrganizationModel;
+use async_trait::async_trait;
+use entity::{archive_user, organization};
+use sea_orm::prelude::Expr;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder,
+};
+use uuid::Uuid;
+
+/// The archive's own organization, which its admins run the platform from.
+pub const DEFAULT_ORGANIZATION_ID: i32 = 1;
+
+/// Repository implementation for database operations on organizations.
+#[derive(Debug, Clone, Default)]
+pub struct DBOrganizationsRepo {
+    pub db_session: DatabaseConnection,
+    /// Tells the time organizations are created at
+    pub clock: SharedClock,
+}
+
+/// Defines the interface for organization database operations.
+#[async_trait]
+pub trait OrganizationsRepo: Send + Sync {
+    /// Lists every organization ordered by name.
+    async fn list(&self) -> Result<Vec<OrganizationModel>, DbErr>;
+
+    /// Creates a new organization.
+    ///
+    /// # Arguments
+    /// * `slug` - Short, unique name for the organization, e.g. "sudanese-diaspora-archive"
+    /// * `name` - Name of the organization to show people
+    async fn write_one(&self, slug: String, name: String) -> Result<OrganizationModel, DbErr>;
+
+    /// Renames an organization, keeping its slug.
+    ///
+    /// # Returns
+    /// The renamed organization, or `None` if there is no such organization
+    async fn rename(
+        &self,
+        organization_id: i32,
+        name: String,
+    ) -> Result<Option<OrganizationModel>, DbErr>;
+
+    /// Moves a user into an organization.
+    ///
+    /// # Returns
+    /// `Some(())` if the user was moved, or `None` if there is no such user
+    async fn assign_user(&self, user_id: Uuid, organization_id: i32) -> Result<Option<()>, DbErr>;
+}
+
+#[async_trait]
+impl OrganizationsRepo for DBOrganizationsRepo {
+    async fn list(&self) -> Result<Vec<OrganizationModel>, DbErr> {
+        Organization::find()
+            .order_by_asc(organization::Column::Name)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn write_one(&self, slug: String, name: String) -> Result<OrganizationModel, DbErr> {
+        OrganizationActiveModel {
+            id: Default::default(),
+            slug: ActiveValue::Set(slug),
+            name: ActiveValue::Set(name),
+            created_at: ActiveValue::Set(self.clock.naive_now()),
+        }
+        .insert(&self.db_session)
+        .await
+    }
+
+    async fn rename(
+        &self,
+        organization_id: i32,
+        name: String,
+    ) -> Result<Option<OrganizationModel>, DbErr> {
+        let Some(organization) = Organization::find_by_id(organization_id)
+            .one(&self.db_session)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let mut organization: OrganizationActiveModel = organization.into();
+        organization.name = ActiveValue::Set(name);
+        Ok(Some(organization.update(&self.db_session).await?))
+    }
+
+    async fn assign_user(&self, user_id: Uuid, organization_id: i32) -> Result<Option<()>, DbErr> {
+        let result = ArchiveUser::update_many()
+            .col_expr(
+                archive_user::Column::OrganizationId,
+                Expr::value(organization_id),
+            )
+            .filter(archive_user::Column::Id.eq(user_id))
+            .exec(&self.db_session)
+            .await?;
+        if result.rows_affected > 0 {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db::migrated_test_db;
+    use ::entity::archive_user::ActiveModel as ArchiveUserActiveModel;
+    use ::entity::sea_orm_active_enums::Role;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn creates_renames_and_lists_organizations() {
+        let db = migrated_test_db().await;
+        let repo = DBOrganizationsRepo {
+            db_session: db.clone(),
+            ..Default::default()
+        };
+        let partner = repo
+            .write_one("diaspora-archive".to_string(), "Diaspora".to_string())
+            .await
+            .unwrap();
+        let renamed = repo
+            .rename(partner.id, "Diaspora Archive".to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(renamed.slug, "diaspora-archive");
+        assert!(repo
+            .rename(partner.id + 1, "Nobody".to_string())
+            .await
+            .unwrap()
+            .is_none());
+
+        let organizations = repo.list().await.unwrap();
+        assert_eq!(
+            organizations
+                .iter()
+                .map(|organization| (organization.id, organization.name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (partner.id, "Diaspora Archive"),
+                (DEFAULT_ORGANIZATION_ID, "Sudan Digital Archive")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn assigns_users_to_organizations() {
+        let db = migrated_test_db().await;
+        let repo = DBOrganizationsRepo {
+            db_session: db.clone(),
+            ..Default::default()
+        };
+        let partner = repo
+            .write_one(
+                "diaspora-archive".to_string(),
+                "Diaspora Archive".to_string(),
+            )
+            .await
+            .unwrap();
+        let user = ArchiveUserActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            email: ActiveValue::Set("researcher@example.com".to_string()),
+            is_active: ActiveValue::Set(true),
+            role: ActiveValue::Set(Role::Researcher),
+            email_status: ActiveValue::NotSet,
+            activity_digest: ActiveValue::NotSet,
+            activity_digest_sent_at: ActiveValue::NotSet,
+            organization_id: ActiveValue::NotSet,
+        }
+        .insert(&repo.db_session)
+        .await
+        .unwrap();
+        assert_eq!(user.organization_id, DEFAULT_ORGANIZATION_ID);
+
+        assert_eq!(
+            repo.assign_user(user.id, partner.id).await.unwrap(),
+            Some(())
+        );
+        assert_eq!(
+            repo.assign_user(Uuid::new_v4(), partner.id).await.unwrap(),
+            None
+        );
+        let user = ArchiveUser::find_by_id(user.id)
+            .one(&repo.db_session)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.organization_id, partner.id);
+    }
+}
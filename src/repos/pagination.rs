@@ -0,0 +1,69 @@
+//! Server-side limits on how much a listing query can ask the database for.
+//!
+//! Handlers reject out of range values with a 400 through the validator annotations on the
+//! pagination models, which reference the limits here. The repos clamp with [`PageWindow::new`]
+//! as well so no caller can get an unbounded page out of the view by skipping validation.
+
+/// Page size used when a caller doesn't give one.
+pub const DEFAULT_PER_PAGE: u64 = 20;
+/// Largest page size any listing will return.
+pub const MAX_PER_PAGE: u64 = 200;
+/// Deepest 0-indexed page that can be requested; offsets past this are slow to scan and no
+/// client pages that far.
+pub const MAX_PAGE: u64 = 10_000;
+
+/// A page and page size that are safe to hand to the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageWindow {
+    pub page: u64,
+    pub per_page: u64,
+}
+
+impl PageWindow {
+    /// Clamps a requested page and page size to the server-side limits.
+    ///
+    /// A page size of 0 falls back to [`DEFAULT_PER_PAGE`] since the paginator can't divide by it.
+    pub fn new(page: u64, per_page: u64) -> Self {
+        let per_page = match per_page {
+            0 => DEFAULT_PER_PAGE,
+            per_page => per_page.min(MAX_PER_PAGE),
+        };
+        Self {
+            page: page.min(MAX_PAGE),
+            per_page,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn keeps_values_within_limits() {
+        assert_eq!(
+            PageWindow::new(3, 50),
+            PageWindow {
+                page: 3,
+                per_page: 50
+            }
+        );
+    }
+
+    #[test]
+    fn clamps_values_past_limits() {
+        assert_eq!(
+            PageWindow::new(MAX_PAGE + 1, 10_000),
+            PageWindow {
+                page: MAX_PAGE,
+                per_page: MAX_PER_PAGE
+            }
+        );
+    }
+
+    #[test]
+    fn falls_back_to_default_page_size() {
+        assert_eq!(PageWindow::new(0, 0).per_page, DEFAULT_PER_PAGE);
+    }
+}
@@ -0,0 +1,53 @@
+//! Repository for rendering web pages to PDF via a headless browser service.
+//!
+//! Talks to a [Gotenberg](https://gotenberg.dev) compatible service which loads a URL in
+//! headless Chromium and returns the printed PDF.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use reqwest::{Client, Error};
+
+/// Boundary used for the multipart form sent to the renderer.
+const FORM_BOUNDARY: &str = "sda-pdf-renderer-boundary";
+
+#[async_trait]
+pub trait PdfRendererRepo: Send + Sync {
+    /// Renders the page at `url` to a PDF.
+    ///
+    /// # Arguments
+    /// * `url` - The page to render
+    ///
+    /// # Returns
+    /// The PDF file contents
+    async fn render_url_to_pdf(&self, url: &str) -> Result<Bytes, Error>;
+}
+
+/// Renders PDFs by calling a Gotenberg service over HTTP.
+#[derive(Debug, Clone, Default)]
+pub struct HTTPPdfRendererRepo {
+    pub client: Client,
+    pub base_url: String,
+}
+
+#[async_trait]
+impl PdfRendererRepo for HTTPPdfRendererRepo {
+    async fn render_url_to_pdf(&self, url: &str) -> Result<Bytes, Error> {
+        // Gotenberg only accepts form data; a single text field doesn't justify
+        // pulling in reqwest's multipart feature
+        let body = format!(
+            "--{FORM_BOUNDARY}\r\nContent-Disposition: form-data; name=\"url\"\r\n\r\n{url}\r\n--{FORM_BOUNDARY}--\r\n"
+        );
+        let resp = self
+            .client
+            .post(format!("{}/forms/chromium/convert/url", self.base_url))
+            .header(
+                http::header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={FORM_BOUNDARY}"),
+            )
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        resp.bytes().await
+    }
+}
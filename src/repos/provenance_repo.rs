@@ -0,0 +1,247 @@
+//! Repository module for the chain of custody of accession files.
+//!
+//! Each stored file's hash is recorded alongside its signature, when the archive has a
+//! signing key, so it can later be checked independently. See [`crate::provenance`].
+
+use ::entity::accession_provenance::ActiveModel as AccessionProvenanceActiveModel;
+use ::entity::accession_provenance::Entity as AccessionProvenance;
+use ::entity::accession_provenance::Model as AccessionProvenanceModel;
+use ::entity::sea_orm_active_enums::TimestampMethod;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use entity::accession_provenance;
+use sea_orm::sea_query::{Expr, OnConflict};
+use sea_orm::{
+    ActiveEnum, ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect,
+};
+
+/// Repository implementation for database operations on accession provenance.
+#[derive(Debug, Clone, Default)]
+pub struct DBProvenanceRepo {
+    pub db_session: DatabaseConnection,
+}
+
+/// Defines the interface for accession provenance database operations.
+#[async_trait]
+pub trait ProvenanceRepo: Send + Sync {
+    /// Records the hash and signature of an accession's file.
+    ///
+    /// # Arguments
+    /// * `provenance` - The record, which fails to write if the accession already has one
+    async fn write_one(&self, provenance: AccessionProvenanceModel) -> Result<(), DbErr>;
+
+    /// Records the hash and signature of a file that replaced an accession's earlier one,
+    /// overwriting the earlier record and its timestamp.
+    ///
+    /// # Arguments
+    /// * `provenance` - The record of the replacement
+    async fn replace_one(&self, provenance: AccessionProvenanceModel) -> Result<(), DbErr>;
+
+    /// Gets the provenance of an accession's file, if one was recorded.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    async fn get_one(&self, accession_id: i32) -> Result<Option<AccessionProvenanceModel>, DbErr>;
+
+    /// Lists records whose hash hasn't been timestamped yet, oldest accession first.
+    ///
+    /// # Arguments
+    /// * `limit` - How many records to return at most
+    async fn list_untimestamped(&self, limit: u64) -> Result<Vec<AccessionProvenanceModel>, DbErr>;
+
+    /// Stores the proof that an accession's hash was timestamped.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    /// * `method` - The kind of service the proof came from
+    /// * `proof` - The proof, in the format `method` uses
+    /// * `timestamped_at` - When the service was asked to timestamp the hash
+    async fn set_timestamp(
+        &self,
+        accession_id: i32,
+        method: TimestampMethod,
+        proof: Vec<u8>,
+        timestamped_at: NaiveDateTime,
+    ) -> Result<(), DbErr>;
+}
+
+#[async_trait]
+impl ProvenanceRepo for DBProvenanceRepo {
+    async fn write_one(&self, provenance: AccessionProvenanceModel) -> Result<(), DbErr> {
+        let provenance = AccessionProvenanceActiveModel {
+            accession_id: ActiveValue::Set(provenance.accession_id),
+            sha256: ActiveValue::Set(provenance.sha256),
+            signature: ActiveValue::Set(provenance.signature),
+            signer_public_key: ActiveValue::Set(provenance.signer_public_key),
+            recorded_at: ActiveValue::Set(provenance.recorded_at),
+            timestamp_method: ActiveValue::Set(provenance.timestamp_method),
+            timestamp_proof: ActiveValue::Set(provenance.timestamp_proof),
+            timestamped_at: ActiveValue::Set(provenance.timestamped_at),
+        };
+        provenance.insert(&self.db_session).await?;
+        Ok(())
+    }
+
+    async fn replace_one(&self, provenance: AccessionProvenanceModel) -> Result<(), DbErr> {
+        let provenance = AccessionProvenanceActiveModel {
+            accession_id: ActiveValue::Set(provenance.accession_id),
+            sha256: ActiveValue::Set(provenance.sha256),
+            signature: ActiveValue::Set(provenance.signature),
+            signer_public_key: ActiveValue::Set(provenance.signer_public_key),
+            recorded_at: ActiveValue::Set(provenance.recorded_at),
+            timestamp_method: ActiveValue::Set(provenance.timestamp_method),
+            timestamp_proof: ActiveValue::Set(provenance.timestamp_proof),
+            timestamped_at: ActiveValue::Set(provenance.timestamped_at),
+        };
+        AccessionProvenance::insert(provenance)
+            .on_conflict(
+                OnConflict::column(accession_provenance::Column::AccessionId)
+                    .update_columns([
+                        accession_provenance::Column::Sha256,
+                        accession_provenance::Column::Signature,
+                        accession_provenance::Column::SignerPublicKey,
+                        accession_provenance::Column::RecordedAt,
+                        accession_provenance::Column::TimestampMethod,
+                        accession_provenance::Column::TimestampProof,
+                        accession_provenance::Column::TimestampedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&self.db_session)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_one(&self, accession_id: i32) -> Result<Option<AccessionProvenanceModel>, DbErr> {
+        AccessionProvenance::find_by_id(accession_id)
+            .one(&self.db_session)
+            .await
+    }
+
+    async fn list_untimestamped(&self, limit: u64) -> Result<Vec<AccessionProvenanceModel>, DbErr> {
+        AccessionProvenance::find()
+            .filter(accession_provenance::Column::TimestampProof.is_null())
+            .order_by_asc(accession_provenance::Column::AccessionId)
+            .limit(limit)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn set_timestamp(
+        &self,
+        accession_id: i32,
+        method: TimestampMethod,
+        proof: Vec<u8>,
+        timestamped_at: NaiveDateTime,
+    ) -> Result<(), DbErr> {
+        AccessionProvenance::update_many()
+            .col_expr(
+                accession_provenance::Column::TimestampMethod,
+                method.as_enum(),
+            )
+            .col_expr(
+                accession_provenance::Column::TimestampProof,
+                Expr::value(proof),
+            )
+            .col_expr(
+                accession_provenance::Column::TimestampedAt,
+                Expr::value(timestamped_at),
+            )
+            .filter(accession_provenance::Column::AccessionId.eq(accession_id))
+            .exec(&self.db_session)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::common::{MetadataLanguage, MetadataScrubbing};
+    use crate::models::request::CreateAccessionRequestRaw;
+    use crate::repos::accessions_repo::{AccessionsRepo, DBAccessionsRepo};
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::test_db::migrated_test_db;
+    use chrono::Utc;
+    use entity::sea_orm_active_enums::{DublinMetadataFormat, ScanStatus};
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn records_one_provenance_per_accession() {
+        let db = migrated_test_db().await;
+        let db_session = db.clone();
+        let accession_id = DBAccessionsRepo {
+            db_session: db_session.clone(),
+            ..Default::default()
+        }
+        .write_one_raw(
+            CreateAccessionRequestRaw {
+                metadata_language: MetadataLanguage::English,
+                metadata_title: "Testimony".to_string(),
+                metadata_description: None,
+                metadata_time: Default::default(),
+                metadata_subjects: vec![],
+                is_private: false,
+                embargo_until: None,
+                content_warning: None,
+                metadata_format: DublinMetadataFormat::Jpeg,
+                original_url: "https://example.com".to_string(),
+                s3_filename: "file.jpg".to_string(),
+                metadata_scrubbing: MetadataScrubbing::Scrub,
+            },
+            ScanStatus::Clean,
+            true,
+            DEFAULT_ORGANIZATION_ID,
+        )
+        .await
+        .unwrap();
+        let repo = DBProvenanceRepo { db_session };
+        assert_eq!(repo.get_one(accession_id).await.unwrap(), None);
+
+        let provenance = AccessionProvenanceModel {
+            accession_id,
+            sha256: "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08".to_string(),
+            signature: None,
+            signer_public_key: None,
+            recorded_at: Utc::now().naive_utc(),
+            timestamp_method: None,
+            timestamp_proof: None,
+            timestamped_at: None,
+        };
+        repo.write_one(provenance.clone()).await.unwrap();
+        assert!(repo.write_one(provenance.clone()).await.is_err());
+        assert_eq!(
+            repo.get_one(accession_id)
+                .await
+                .unwrap()
+                .map(|row| row.sha256),
+            Some(provenance.sha256)
+        );
+
+        assert_eq!(repo.list_untimestamped(10).await.unwrap().len(), 1);
+        repo.set_timestamp(
+            accession_id,
+            TimestampMethod::Rfc3161,
+            vec![0x30, 0x00],
+            Utc::now().naive_utc(),
+        )
+        .await
+        .unwrap();
+        assert!(repo.list_untimestamped(10).await.unwrap().is_empty());
+        let timestamped = repo.get_one(accession_id).await.unwrap().unwrap();
+        assert_eq!(timestamped.timestamp_method, Some(TimestampMethod::Rfc3161));
+        assert_eq!(timestamped.timestamp_proof, Some(vec![0x30, 0x00]));
+
+        let replacement = AccessionProvenanceModel {
+            sha256: "60303ae22b998861bce3b28f33eec1be758a213c86c93c076dbe9f558c11c752".to_string(),
+            ..provenance
+        };
+        repo.replace_one(replacement.clone()).await.unwrap();
+        let replaced = repo.get_one(accession_id).await.unwrap().unwrap();
+        assert_eq!(replaced.sha256, replacement.sha256);
+        assert_eq!(replaced.timestamp_proof, None);
+        assert_eq!(repo.list_untimestamped(10).await.unwrap().len(), 1);
+    }
+}
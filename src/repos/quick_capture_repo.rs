@@ -0,0 +1,293 @@
+//! Repository for quick captures, see [`crate::quick_capture`].
+//!
+//! [`QuickCaptureRepo`] is a [`CrawlerRepo`] that fetches the page itself and only hands it to
+//! the crawler it wraps when the page needs a browser or can't be fetched. Each crawl requested
+//! with `capture_mode` set to `quick` gets its own, wrapped around the crawler it would
+//! otherwise have used.
+//!
+//! Pages are only fetched from public addresses, see [`is_public_address`], and the request
+//! goes to the address that was checked, so a host can't pass the check and then be resolved
+//! to somewhere inside our network. Other pages are left to the crawler.
+
+use crate::clock::{SharedClock, SharedIdGen};
+use crate::crawl_state_machine::LaunchedCrawl;
+use crate::models::common::CrawlerBackend;
+use crate::models::request::CreateCrawlRequest;
+use crate::quick_capture::{
+    is_public_address, needs_browser, write_warc, FetchedPage, MAX_QUICK_CAPTURE_SIZE,
+    QUICK_CAPTURE_TIMEOUT, QUICK_CAPTURE_USER_AGENT,
+};
+use crate::repos::crawler_repo::{CrawlArchive, CrawlerError, CrawlerRepo};
+use async_trait::async_trait;
+use bytes::Bytes;
+use entity::sea_orm_active_enums::DublinMetadataFormat;
+use futures::StreamExt;
+use http::header::USER_AGENT;
+use reqwest::redirect::Policy;
+use reqwest::{Client, Url};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::net::lookup_host;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Captures static pages directly, crawling the rest with `fallback`.
+pub struct QuickCaptureRepo {
+    /// Crawls pages that can't be captured quickly
+    pub fallback: Arc<dyn CrawlerRepo>,
+    /// Lets tests capture pages they serve on loopback addresses
+    allow_private_addresses: bool,
+    /// Tells the time pages are fetched at
+    clock: SharedClock,
+    /// Generates the IDs of quick captures
+    ids: SharedIdGen,
+    /// WARCs of quick captures waiting to be downloaded
+    captures: Mutex<HashMap<Uuid, Bytes>>,
+}
+
+impl QuickCaptureRepo {
+    pub fn new(fallback: Arc<dyn CrawlerRepo>, clock: SharedClock, ids: SharedIdGen) -> Self {
+        Self {
+            fallback,
+            allow_private_addresses: false,
+            clock,
+            ids,
+            captures: Mutex::default(),
+        }
+    }
+
+    /// Builds a client that connects to the addresses `url`'s host resolves to right now,
+    /// refusing hosts with any address that isn't public.
+    async fn pinned_client(&self, url: &Url) -> Result<Client, CrawlerError> {
+        // redirects are left to the crawler, see [`needs_browser`]
+        let client = Client::builder()
+            .redirect(Policy::none())
+            .timeout(QUICK_CAPTURE_TIMEOUT);
+        let port = url.port_or_known_default().ok_or("URL has no port")?;
+        let host = url.host_str().ok_or("URL has no host")?;
+        let addrs: Vec<SocketAddr> = match url.domain() {
+            Some(domain) => lookup_host((domain, port)).await?.collect(),
+            // an IP address, in brackets for IPv6
+            None => vec![SocketAddr::new(
+                host.trim_matches(['[', ']']).parse()?,
+                port,
+            )],
+        };
+        if addrs.is_empty() {
+            return Err("Host has no addresses".into());
+        }
+        if !self.allow_private_addresses && !addrs.iter().all(|addr| is_public_address(addr.ip())) {
+            return Err("Host resolves to an address that isn't public".into());
+        }
+        let client = match url.domain() {
+            Some(domain) => client.resolve_to_addrs(domain, &addrs),
+            None => client,
+        };
+        Ok(client.build()?)
+    }
+
+    /// Fetches a page, giving up on pages larger than [`MAX_QUICK_CAPTURE_SIZE`] and on hosts
+    /// that aren't public.
+    async fn fetch(&self, url: &str) -> Result<FetchedPage, CrawlerError> {
+        let url = Url::parse(url)?;
+        let client = self.pinned_client(&url).await?;
+        let fetched_at = self.clock.now();
+        let mut resp = client
+            .get(url.clone())
+            .header(USER_AGENT, QUICK_CAPTURE_USER_AGENT)
+            .send()
+            .await?;
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let mut body = Vec::new();
+        while let Some(chunk) = resp.chunk().await? {
+            if body.len() + chunk.len() > MAX_QUICK_CAPTURE_SIZE {
+                return Err("Page is too large for a quick capture".into());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(FetchedPage {
+            url,
+            status,
+            headers,
+            body: body.into(),
+            fetched_at,
+        })
+    }
+}
+
+#[async_trait]
+impl CrawlerRepo for QuickCaptureRepo {
+    /// The crawler pages fall back to, since that's the one the request asked for.
+    fn backend(&self) -> CrawlerBackend {
+        self.fallback.backend()
+    }
+
+    async fn launch(
+        &self,
+        create_crawl_request: CreateCrawlRequest,
+    ) -> Result<LaunchedCrawl, CrawlerError> {
+        let url = create_crawl_request.url.clone();
+        let page = match self.fetch(&url).await {
+            Ok(page) if !needs_browser(&page) => page,
+            Ok(page) => {
+                info!(%url, status = %page.status, "Page needs a browser, crawling it instead");
+                return self.fallback.launch(create_crawl_request).await;
+            }
+            Err(err) => {
+                warn!(%err, %url, "Quick capture failed, crawling the page instead");
+                return self.fallback.launch(create_crawl_request).await;
+            }
+        };
+        let crawl_id = self.ids.new_id();
+        self.captures
+            .lock()
+            .unwrap()
+            .insert(crawl_id, Bytes::from(write_warc(&page)));
+        info!(%url, %crawl_id, "Quick captured page");
+        Ok(LaunchedCrawl {
+            id: crawl_id,
+            job_run_id: crawl_id.to_string(),
+            org_id: None,
+        })
+    }
+
+    async fn is_finished(&self, crawl: &LaunchedCrawl) -> Result<bool, CrawlerError> {
+        if self.captures.lock().unwrap().contains_key(&crawl.id) {
+            return Ok(true);
+        }
+        self.fallback.is_finished(crawl).await
+    }
+
+    async fn download(&self, crawl: &LaunchedCrawl) -> Result<CrawlArchive, CrawlerError> {
+        let capture = self.captures.lock().unwrap().remove(&crawl.id);
+        match capture {
+            Some(warc) => Ok(CrawlArchive {
+                format: DublinMetadataFormat::Warc,
+                stream: futures::stream::once(async { Ok(warc) }).boxed(),
+                metadata_json: None,
+            }),
+            None => self.fallback.download(crawl).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_type::is_warc;
+    use crate::test_tools::InMemoryBrowsertrixRepo;
+    use axum::routing::get;
+    use axum::Router;
+    use futures::TryStreamExt;
+    use pretty_assertions::assert_eq;
+    use tokio::net::TcpListener;
+
+    /// Serves a static article and a JavaScript app shell on a local port.
+    async fn serve_pages() -> String {
+        let app = Router::new()
+            .route(
+                "/article",
+                get(|| async {
+                    (
+                        [(http::header::CONTENT_TYPE, "text/html")],
+                        format!("<p>{}</p>", "Market fire in Omdurman. ".repeat(20)),
+                    )
+                }),
+            )
+            .route(
+                "/app",
+                get(|| async {
+                    (
+                        [(http::header::CONTENT_TYPE, "text/html")],
+                        "<div id=\"root\"></div><script src=\"/app.js\"></script>",
+                    )
+                }),
+            );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    /// A repo that may capture the pages of [`serve_pages`].
+    fn local_repo() -> QuickCaptureRepo {
+        QuickCaptureRepo {
+            allow_private_addresses: true,
+            ..QuickCaptureRepo::new(
+                Arc::new(InMemoryBrowsertrixRepo {}),
+                SharedClock::default(),
+                SharedIdGen::default(),
+            )
+        }
+    }
+
+    fn crawl_request(url: String) -> CreateCrawlRequest {
+        CreateCrawlRequest {
+            url,
+            browser_profile: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn captures_static_pages_into_a_warc() {
+        let base_url = serve_pages().await;
+        let repo = local_repo();
+        let crawl = repo
+            .launch(crawl_request(format!("{base_url}/article")))
+            .await
+            .unwrap();
+        assert_eq!(crawl.org_id, None);
+        assert!(repo.is_finished(&crawl).await.unwrap());
+
+        let archive = repo.download(&crawl).await.unwrap();
+        assert_eq!(archive.format, DublinMetadataFormat::Warc);
+        let chunks: Vec<Bytes> = archive.stream.try_collect().await.unwrap();
+        let warc = chunks.concat();
+        assert!(is_warc(&warc));
+        assert!(String::from_utf8(warc)
+            .unwrap()
+            .contains("Market fire in Omdurman"));
+    }
+
+    #[tokio::test]
+    async fn crawls_pages_that_need_a_browser() {
+        let base_url = serve_pages().await;
+        let repo = local_repo();
+        for url in [
+            format!("{base_url}/app"),
+            format!("{base_url}/missing"),
+            "not a url".to_string(),
+        ] {
+            let crawl = repo.launch(crawl_request(url)).await.unwrap();
+            assert_eq!(crawl.job_run_id, "test_job_123");
+        }
+    }
+
+    #[tokio::test]
+    async fn crawls_pages_on_addresses_that_arent_public() {
+        let base_url = serve_pages().await;
+        let port = base_url.rsplit(':').next().unwrap();
+        let repo = QuickCaptureRepo::new(
+            Arc::new(InMemoryBrowsertrixRepo {}),
+            SharedClock::default(),
+            SharedIdGen::default(),
+        );
+        for url in [
+            format!("{base_url}/article"),
+            format!("http://localhost:{port}/article"),
+            "http://169.254.169.254/latest/meta-data/".to_string(),
+            "http://[::1]/".to_string(),
+        ] {
+            let err = repo.fetch(&url).await.unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Host resolves to an address that isn't public",
+                "{url}"
+            );
+            let crawl = repo.launch(crawl_request(url)).await.unwrap();
+            assert_eq!(crawl.job_run_id, "test_job_123");
+        }
+    }
+}
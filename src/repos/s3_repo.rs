@@ -1,5 +1,6 @@
 use async_trait::async_trait;
 use aws_config;
+use aws_sdk_s3::config::Credentials;
 use aws_sdk_s3::error::ProvideErrorMetadata;
 use aws_sdk_s3::operation::get_object::GetObjectError;
 use aws_sdk_s3::operation::put_object::PutObjectError;
@@ -67,6 +68,47 @@ pub trait S3Repo: Send + Sync {
         expires_in: u64,
     ) -> Result<String, Box<dyn Error>>;
 
+    /// Generates a presigned URL that lets a client PUT an object directly to S3
+    ///
+    /// # Arguments
+    /// * `key` - The object key (path) the client may write to
+    /// * `content_type` - MIME type the client must send in its `Content-Type` header
+    /// * `expires_in` - Duration in seconds until the presigned URL expires
+    ///
+    /// # Returns
+    /// A presigned URL that accepts a single PUT of the object
+    ///
+    /// # Errors
+    /// Returns Error if the presigning configuration or URL generation fails
+    async fn get_presigned_put_url(
+        &self,
+        key: &str,
+        content_type: &str,
+        expires_in: u64,
+    ) -> Result<String, Box<dyn Error>>;
+
+    /// Generates a presigned URL that lets a client upload one part of a multipart upload
+    /// directly to S3
+    ///
+    /// # Arguments
+    /// * `key` - The object key (path) in the S3 bucket
+    /// * `upload_id` - The ID of the multipart upload
+    /// * `part_number` - The part number (1-indexed)
+    /// * `expires_in` - Duration in seconds until the presigned URL expires
+    ///
+    /// # Returns
+    /// A presigned URL that accepts a PUT of the part
+    ///
+    /// # Errors
+    /// Returns Error if the presigning configuration or URL generation fails
+    async fn get_presigned_upload_part_url(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: i32,
+        expires_in: u64,
+    ) -> Result<String, Box<dyn Error>>;
+
     /// Initiates a multipart upload to S3.
     ///
     /// # Arguments
@@ -124,6 +166,37 @@ pub trait S3Repo: Send + Sync {
         parts: Vec<(String, i32)>,
     ) -> Result<String, Box<dyn Error>>;
 
+    /// Aborts a multipart upload, discarding any parts uploaded so far.
+    ///
+    /// # Arguments
+    /// * `key` - The object key (path) in the S3 bucket
+    /// * `upload_id` - The ID of the multipart upload
+    ///
+    /// # Errors
+    /// Returns Error if the abort fails
+    async fn abort_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+    ) -> Result<(), Box<dyn Error>>;
+
+    /// Lists the parts uploaded so far in a multipart upload.
+    ///
+    /// # Arguments
+    /// * `key` - The object key (path) in the S3 bucket
+    /// * `upload_id` - The ID of the multipart upload
+    ///
+    /// # Returns
+    /// Result containing (ETag, part_number, size) tuples ordered by part number
+    ///
+    /// # Errors
+    /// Returns Error if the upload doesn't exist or listing fails
+    async fn list_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+    ) -> Result<Vec<(String, i32, u64)>, Box<dyn Error>>;
+
     /// Deletes an object from the S3 bucket
     ///
     /// # Arguments
@@ -135,6 +208,75 @@ pub trait S3Repo: Send + Sync {
     /// # Errors
     /// Returns Error if the deletion fails
     async fn delete_object(&self, key: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Copies an object to another key in the same bucket, without downloading it
+    ///
+    /// # Arguments
+    /// * `from` - The key of the object to copy
+    /// * `to` - The key to copy it to, replacing any object already there
+    ///
+    /// # Errors
+    /// Returns Error if the object doesn't exist or the copy fails
+    async fn copy_object(&self, from: &str, to: &str) -> Result<(), Box<dyn Error>>;
+
+    /// Gets the size in bytes of an object in the S3 bucket
+    ///
+    /// # Arguments
+    /// * `key` - The object key (path) in the S3 bucket
+    ///
+    /// # Errors
+    /// Returns Error if the object doesn't exist or the size is missing
+    async fn get_object_size(&self, key: &str) -> Result<u64, Box<dyn Error>>;
+
+    /// Adds up the size in bytes of every object in the S3 bucket
+    ///
+    /// This lists the whole bucket a thousand objects at a time, so results should be cached
+    ///
+    /// # Errors
+    /// Returns Error if listing the bucket fails
+    async fn get_bucket_size(&self) -> Result<u64, Box<dyn Error>>;
+
+    /// Downloads a byte range of an object, so we can read parts of large files
+    /// such as a WACZ index without holding the whole file in memory
+    ///
+    /// # Arguments
+    /// * `key` - The object key (path) in the S3 bucket
+    /// * `start` - Offset of the first byte to fetch
+    /// * `end` - Offset of the last byte to fetch, inclusive like an HTTP `Range` header
+    ///
+    /// # Errors
+    /// Returns Error if the object doesn't exist or the range is invalid
+    async fn get_object_range(
+        &self,
+        key: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Bytes, Box<dyn Error>>;
+
+    /// Starts downloading an object, so it can be streamed on to a client without holding
+    /// the whole file in memory
+    ///
+    /// # Arguments
+    /// * `key` - The object key (path) in the S3 bucket
+    /// * `range` - The client's HTTP `Range` header, if it only wants part of the object
+    ///
+    /// # Errors
+    /// Returns Error if the object doesn't exist or the range is invalid
+    async fn get_object_stream(
+        &self,
+        key: &str,
+        range: Option<&str>,
+    ) -> Result<ObjectStream, Box<dyn Error>>;
+}
+
+/// An object being downloaded from the S3 bucket.
+#[derive(Debug)]
+pub struct ObjectStream {
+    pub body: ByteStream,
+    pub content_type: Option<String>,
+    pub content_length: Option<i64>,
+    /// Set when only part of the object was asked for
+    pub content_range: Option<String>,
 }
 
 /// Implementation for DigitalOcean Spaces (S3-compatible storage)
@@ -164,10 +306,12 @@ impl S3Repo for DigitalOceanSpacesRepo {
             return Err("DO Spaces credentials cannot be empty".into());
         }
 
-        std::env::set_var("AWS_ACCESS_KEY_ID", access_key);
-        std::env::set_var("AWS_SECRET_ACCESS_KEY", secret_key);
+        // passed straight to the client rather than through AWS_* env vars, which would
+        // undo reading them from secret files
+        let credentials = Credentials::new(access_key, secret_key, None, None, "do-spaces");
 
         let s3_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .credentials_provider(credentials)
             .endpoint_url(endpoint_url)
             .region("us-east-1")
             .timeout_config(timeout_config)
@@ -196,6 +340,28 @@ impl S3Repo for DigitalOceanSpacesRepo {
             })
     }
 
+    async fn copy_object(&self, from: &str, to: &str) -> Result<(), Box<dyn Error>> {
+        // the source is a URL path, which the keys we write are safe in without encoding,
+        // see crate::s3_keys
+        self.client
+            .copy_object()
+            .bucket(&self.bucket)
+            .copy_source(format!("{}/{}", self.bucket, from))
+            .key(to)
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|err| {
+                format!(
+                    "Failed to copy object {} to {}: {}",
+                    from,
+                    to,
+                    err.into_service_error()
+                )
+                .into()
+            })
+    }
+
     async fn upload_from_bytes(
         &self,
         key: &str,
@@ -278,6 +444,54 @@ impl S3Repo for DigitalOceanSpacesRepo {
         Ok(presigned_request.uri().to_string())
     }
 
+    async fn get_presigned_put_url(
+        &self,
+        key: &str,
+        content_type: &str,
+        expires_in: u64,
+    ) -> Result<String, Box<dyn Error>> {
+        let expires_in = std::time::Duration::from_secs(expires_in);
+        let presigned_request = self
+            .client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .presigned(
+                PresigningConfig::expires_in(expires_in)
+                    .map_err(|e| format!("Failed to create presigning config: {e}"))?,
+            )
+            .await
+            .map_err(|e| format!("Failed to generate presigned URL: {e}"))?;
+
+        Ok(presigned_request.uri().to_string())
+    }
+
+    async fn get_presigned_upload_part_url(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: i32,
+        expires_in: u64,
+    ) -> Result<String, Box<dyn Error>> {
+        let expires_in = std::time::Duration::from_secs(expires_in);
+        let presigned_request = self
+            .client
+            .upload_part()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .presigned(
+                PresigningConfig::expires_in(expires_in)
+                    .map_err(|e| format!("Failed to create presigning config: {e}"))?,
+            )
+            .await
+            .map_err(|e| format!("Failed to generate presigned URL: {e}"))?;
+
+        Ok(presigned_request.uri().to_string())
+    }
+
     async fn initiate_multipart_upload(
         &self,
         key: &str,
@@ -372,4 +586,169 @@ impl S3Repo for DigitalOceanSpacesRepo {
         let final_etag = result.e_tag().unwrap_or_default().to_string();
         Ok(final_etag)
     }
+
+    async fn get_object_size(&self, key: &str) -> Result<u64, Box<dyn Error>> {
+        let head = self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|err| {
+                format!(
+                    "Failed to get object metadata for {}: {}",
+                    key,
+                    err.into_service_error()
+                )
+            })?;
+        let size = head
+            .content_length()
+            .ok_or_else(|| format!("Missing content length for {key}"))?;
+        Ok(u64::try_from(size)?)
+    }
+
+    async fn get_bucket_size(&self) -> Result<u64, Box<dyn Error>> {
+        let mut total = 0;
+        let mut continuation_token = None;
+        loop {
+            let page = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(|err| {
+                    format!(
+                        "Failed to list objects in {}: {}",
+                        self.bucket,
+                        err.into_service_error()
+                    )
+                })?;
+            for object in page.contents() {
+                total += u64::try_from(object.size().unwrap_or_default())?;
+            }
+            continuation_token = page.next_continuation_token().map(str::to_string);
+            if continuation_token.is_none() {
+                return Ok(total);
+            }
+        }
+    }
+
+    async fn get_object_range(
+        &self,
+        key: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Bytes, Box<dyn Error>> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .range(format!("bytes={start}-{end}"))
+            .send()
+            .await
+            .map_err(|err| {
+                format!(
+                    "Failed to get bytes {}-{} of {}: {}",
+                    start,
+                    end,
+                    key,
+                    err.into_service_error()
+                )
+            })?;
+        let body = object
+            .body
+            .collect()
+            .await
+            .map_err(|err| format!("Failed to read bytes of {key}: {err}"))?;
+        Ok(body.into_bytes())
+    }
+
+    async fn get_object_stream(
+        &self,
+        key: &str,
+        range: Option<&str>,
+    ) -> Result<ObjectStream, Box<dyn Error>> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .set_range(range.map(str::to_string))
+            .send()
+            .await
+            .map_err(|err| format!("Failed to get {}: {}", key, err.into_service_error()))?;
+        Ok(ObjectStream {
+            content_type: object.content_type,
+            content_length: object.content_length,
+            content_range: object.content_range,
+            body: object.body,
+        })
+    }
+
+    async fn abort_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        self.client
+            .abort_multipart_upload()
+            .bucket(&self.bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|err| {
+                format!(
+                    "Failed to abort multipart upload for {}: {}",
+                    key,
+                    err.into_service_error()
+                )
+                .into()
+            })
+    }
+
+    async fn list_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+    ) -> Result<Vec<(String, i32, u64)>, Box<dyn Error>> {
+        let mut parts = Vec::new();
+        let mut part_number_marker: Option<String> = None;
+        // S3 returns at most 1000 parts per page
+        loop {
+            let page = self
+                .client
+                .list_parts()
+                .bucket(&self.bucket)
+                .key(key)
+                .upload_id(upload_id)
+                .set_part_number_marker(part_number_marker.clone())
+                .send()
+                .await
+                .map_err(|err| {
+                    format!(
+                        "Failed to list parts for {}: {}",
+                        key,
+                        err.into_service_error()
+                    )
+                })?;
+            for part in page.parts() {
+                parts.push((
+                    part.e_tag().unwrap_or_default().to_string(),
+                    part.part_number().unwrap_or_default(),
+                    u64::try_from(part.size().unwrap_or_default())?,
+                ));
+            }
+            if !page.is_truncated().unwrap_or(false) {
+                break;
+            }
+            part_number_marker = page.next_part_number_marker().map(str::to_string);
+        }
+        Ok(parts)
+    }
 }
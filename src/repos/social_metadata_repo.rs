@@ -0,0 +1,178 @@
+//! Repository module for who posted a crawled social media post and when.
+//!
+//! See [`crate::social_metadata`] for how it's extracted.
+
+use ::entity::social_metadata::ActiveModel as SocialMetadataActiveModel;
+use ::entity::social_metadata::Entity as SocialMetadata;
+use ::entity::social_metadata::Model as SocialMetadataModel;
+use async_trait::async_trait;
+use entity::social_metadata;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ActiveValue, DatabaseConnection, DbErr, EntityTrait};
+
+/// Repository implementation for database operations on social media metadata.
+#[derive(Debug, Clone, Default)]
+pub struct DBSocialMetadataRepo {
+    pub db_session: DatabaseConnection,
+}
+
+/// Defines the interface for social media metadata database operations.
+#[async_trait]
+pub trait SocialMetadataRepo: Send + Sync {
+    /// Stores what is known about the post an accession was crawled from, replacing what
+    /// was stored before.
+    ///
+    /// # Arguments
+    /// * `metadata` - The post's metadata
+    async fn write_one(&self, metadata: SocialMetadataModel) -> Result<(), DbErr>;
+
+    /// Gets the metadata of the post an accession was crawled from, if it was one.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    async fn get_one(&self, accession_id: i32) -> Result<Option<SocialMetadataModel>, DbErr>;
+}
+
+#[async_trait]
+impl SocialMetadataRepo for DBSocialMetadataRepo {
+    async fn write_one(&self, metadata: SocialMetadataModel) -> Result<(), DbErr> {
+        let metadata = SocialMetadataActiveModel {
+            accession_id: ActiveValue::Set(metadata.accession_id),
+            platform: ActiveValue::Set(metadata.platform),
+            author_handle: ActiveValue::Set(metadata.author_handle),
+            author_name: ActiveValue::Set(metadata.author_name),
+            post_id: ActiveValue::Set(metadata.post_id),
+            posted_at: ActiveValue::Set(metadata.posted_at),
+            extracted_at: ActiveValue::Set(metadata.extracted_at),
+        };
+        SocialMetadata::insert(metadata)
+            .on_conflict(
+                OnConflict::column(social_metadata::Column::AccessionId)
+                    .update_columns([
+                        social_metadata::Column::Platform,
+                        social_metadata::Column::AuthorHandle,
+                        social_metadata::Column::AuthorName,
+                        social_metadata::Column::PostId,
+                        social_metadata::Column::PostedAt,
+                        social_metadata::Column::ExtractedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&self.db_session)
+            .await?;
+        Ok(())
+    }
+
+    async fn get_one(&self, accession_id: i32) -> Result<Option<SocialMetadataModel>, DbErr> {
+        SocialMetadata::find_by_id(accession_id)
+            .one(&self.db_session)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::common::{MetadataLanguage, MetadataScrubbing};
+    use crate::models::request::{AccessionPaginationWithPrivate, CreateAccessionRequestRaw};
+    use crate::repos::accessions_repo::{AccessionsRepo, DBAccessionsRepo};
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::test_db::migrated_test_db;
+    use entity::sea_orm_active_enums::{DublinMetadataFormat, ScanStatus, SocialPlatform};
+    use pretty_assertions::assert_eq;
+
+    async fn write_accession(accessions_repo: &DBAccessionsRepo) -> i32 {
+        accessions_repo
+            .write_one_raw(
+                CreateAccessionRequestRaw {
+                    metadata_language: MetadataLanguage::English,
+                    metadata_title: "Testimony".to_string(),
+                    metadata_description: None,
+                    metadata_time: Default::default(),
+                    metadata_subjects: vec![],
+                    is_private: false,
+                    embargo_until: None,
+                    content_warning: None,
+                    metadata_format: DublinMetadataFormat::Jpeg,
+                    original_url: "https://example.com".to_string(),
+                    s3_filename: "file.jpg".to_string(),
+                    metadata_scrubbing: MetadataScrubbing::Scrub,
+                },
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn stores_and_filters_by_post_authors() {
+        let db = migrated_test_db().await;
+        let db_session = db.clone();
+        let accessions_repo = DBAccessionsRepo {
+            db_session: db_session.clone(),
+            ..Default::default()
+        };
+        let repo = DBSocialMetadataRepo { db_session };
+        let post = write_accession(&accessions_repo).await;
+        let other_post = write_accession(&accessions_repo).await;
+        let article = write_accession(&accessions_repo).await;
+        assert_eq!(repo.get_one(post).await.unwrap(), None);
+
+        let metadata = SocialMetadataModel {
+            accession_id: post,
+            platform: SocialPlatform::X,
+            author_handle: Some("RSFSudan".to_string()),
+            author_name: None,
+            post_id: Some("1785236745296498795".to_string()),
+            posted_at: None,
+            extracted_at: Default::default(),
+        };
+        repo.write_one(metadata.clone()).await.unwrap();
+        // looking the post up again replaces what was stored
+        let metadata = SocialMetadataModel {
+            author_name: Some("Rapid Support Forces".to_string()),
+            ..metadata
+        };
+        repo.write_one(metadata.clone()).await.unwrap();
+        assert_eq!(
+            repo.get_one(post).await.unwrap().map(|row| row.author_name),
+            Some(metadata.author_name.clone())
+        );
+        repo.write_one(SocialMetadataModel {
+            accession_id: other_post,
+            platform: SocialPlatform::Telegram,
+            author_handle: Some("sudanalyoum".to_string()),
+            ..metadata
+        })
+        .await
+        .unwrap();
+
+        let list_ids = |social_platform, author_handle: Option<&str>| {
+            let accessions_repo = accessions_repo.clone();
+            let author_handle = author_handle.map(str::to_string);
+            async move {
+                let (rows, _) = accessions_repo
+                    .list_paginated(AccessionPaginationWithPrivate {
+                        social_platform,
+                        author_handle,
+                        ..Default::default()
+                    })
+                    .await
+                    .unwrap();
+                let mut ids: Vec<i32> = rows.into_iter().map(|row| row.id).collect();
+                ids.sort();
+                ids
+            }
+        };
+        assert_eq!(list_ids(None, None).await, vec![post, other_post, article]);
+        assert_eq!(list_ids(Some(SocialPlatform::X), None).await, vec![post]);
+        assert_eq!(list_ids(None, Some("@rsfsudan")).await, vec![post]);
+        assert_eq!(
+            list_ids(Some(SocialPlatform::Telegram), Some("rsfsudan")).await,
+            Vec::<i32>::new()
+        );
+    }
+}
@@ -3,9 +3,20 @@
 //! This module provides functionality for creating and listing subject terms
 //! that can be used to categorize archived content in both Arabic and English.
 
-use crate::models::common::MetadataLanguage;
-use crate::models::request::CreateSubjectRequest;
+use crate::clock::SharedClock;
+use crate::models::common::{MetadataLanguage, SubjectMatch, SubjectSort};
+use crate::models::request::{ApplySubjectRequest, CreateSubjectRequest};
 use crate::models::response::SubjectResponse;
+use crate::repos::filter_builder::{
+    build_filter_expression, matches_ignoring_accents, on_domain, FilterParams, Visibility,
+};
+use crate::repos::pagination::PageWindow;
+use ::entity::accession::Entity as Accession;
+use ::entity::accessions_with_metadata::Entity as AccessionWithMetadata;
+use ::entity::dublin_metadata_ar_subjects::ActiveModel as DublinMetadataSubjectsArActiveModel;
+use ::entity::dublin_metadata_ar_subjects::Entity as DublinMetadataSubjectsAr;
+use ::entity::dublin_metadata_en_subjects::ActiveModel as DublinMetadataSubjectsEnActiveModel;
+use ::entity::dublin_metadata_en_subjects::Entity as DublinMetadataSubjectsEn;
 use ::entity::dublin_metadata_subject_ar::ActiveModel as DublinMetadataSubjectArActiveModel;
 use ::entity::dublin_metadata_subject_ar::Entity as DublinMetadataSubjectAr;
 use ::entity::dublin_metadata_subject_ar::Model as DublinMetadataSubjectArModel;
@@ -13,33 +24,78 @@ use ::entity::dublin_metadata_subject_en::ActiveModel as DublinMetadataSubjectEn
 use ::entity::dublin_metadata_subject_en::Entity as DublinMetadataSubjectEn;
 use ::entity::dublin_metadata_subject_en::Model as DublinMetadataSubjectEnModel;
 use async_trait::async_trait;
-use entity::{dublin_metadata_subject_ar, dublin_metadata_subject_en};
+use entity::{
+    accession, accessions_with_metadata, dublin_metadata_ar_subjects, dublin_metadata_en_subjects,
+    dublin_metadata_subject_ar, dublin_metadata_subject_en,
+};
 use sea_orm::prelude::Expr;
-use sea_orm::sea_query::{ExprTrait, Func};
+use sea_orm::sea_query::{ExprTrait, Func, OnConflict};
 use sea_orm::{
-    ActiveModelTrait, ActiveValue, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait,
+    ActiveModelTrait, ActiveValue, DatabaseConnection, DbErr, EntityTrait, Order, PaginatorTrait,
+    QueryOrder, QuerySelect, Select, TransactionTrait,
 };
 use sea_orm::{ColumnTrait, QueryFilter};
 
+/// Orders a subject query the way `sort` asks, breaking ties by ID so pages are stable.
+///
+/// # Arguments
+/// * `query` - Query on English or Arabic subjects
+/// * `sort` - What order to list subjects in
+/// * `subject` - The subject column of the subjects table
+/// * `id` - The ID column of the subjects table
+/// * `link_table` - Table linking the subjects to accession metadata, counted for usage
+/// * `subject_table` - Name of the subjects table
+fn order_subjects<E: EntityTrait>(
+    query: Select<E>,
+    sort: SubjectSort,
+    subject: E::Column,
+    id: E::Column,
+    link_table: &str,
+    subject_table: &str,
+) -> Select<E> {
+    match sort {
+        SubjectSort::Alpha => query.order_by_asc(subject).order_by_asc(id),
+        SubjectSort::Usage => {
+            let usage = Expr::cust(format!(
+                "(SELECT COUNT(*) FROM {link_table} WHERE {link_table}.subject_id = {subject_table}.id)"
+            ));
+            query
+                .order_by(usage, Order::Desc)
+                .order_by_asc(subject)
+                .order_by_asc(id)
+        }
+        SubjectSort::Recent => query.order_by_desc(id),
+    }
+}
+
 /// Repository implementation for database operations on subjects.
 #[derive(Debug, Clone, Default)]
 pub struct DBSubjectsRepo {
     pub db_session: DatabaseConnection,
+    /// Tells the time subjects are created at
+    pub clock: SharedClock,
 }
 
 /// Defines the interface for subject-related database operations.
 ///
 /// This trait provides methods for creating and retrieving subject terms
 /// that can be used to categorize archived content in both Arabic and English.
+/// Each organization keeps its own vocabulary, so every method but
+/// [`SubjectsRepo::count_all`] works within one.
 #[async_trait]
 pub trait SubjectsRepo: Send + Sync {
     /// Creates a new subject term in the specified language.
     ///
     /// # Arguments
     /// * `create_subject_request` - The request containing subject details and language
+    /// * `organization_id` - The organization the subject belongs to
+    /// * `created_by` - Email of the user adding the subject, `None` when no user is
+    ///   behind it such as when seeding
     async fn write_one(
         &self,
         create_subject_request: CreateSubjectRequest,
+        organization_id: i32,
+        created_by: Option<String>,
     ) -> Result<SubjectResponse, DbErr>;
 
     /// Lists Arabic subject terms with pagination and optional text search.
@@ -48,11 +104,17 @@ pub trait SubjectsRepo: Send + Sync {
     /// * `page` - The page number to retrieve
     /// * `per_page` - Number of records per page
     /// * `query_term` - Optional text search term
+    /// * `match_mode` - How the search term has to match subjects
+    /// * `sort` - What order to list subjects in
+    /// * `organization_id` - The organization whose subjects to list
     async fn list_paginated_ar(
         &self,
         page: u64,
         per_page: u64,
         query_term: Option<String>,
+        match_mode: SubjectMatch,
+        sort: SubjectSort,
+        organization_id: i32,
     ) -> Result<(Vec<DublinMetadataSubjectArModel>, u64), DbErr>;
 
     /// Lists English subject terms with pagination and optional text search.
@@ -61,34 +123,95 @@ pub trait SubjectsRepo: Send + Sync {
     /// * `page` - The page number to retrieve
     /// * `per_page` - Number of records per page
     /// * `query_term` - Optional text search term
+    /// * `match_mode` - How the search term has to match subjects
+    /// * `sort` - What order to list subjects in
+    /// * `organization_id` - The organization whose subjects to list
     async fn list_paginated_en(
         &self,
         page: u64,
         per_page: u64,
         query_term: Option<String>,
+        match_mode: SubjectMatch,
+        sort: SubjectSort,
+        organization_id: i32,
     ) -> Result<(Vec<DublinMetadataSubjectEnModel>, u64), DbErr>;
 
-    /// Verifies that all provided subject IDs exist in the database.
+    /// Verifies that all provided subject IDs exist in the organization.
     ///
     /// # Arguments
     /// * `subject_ids` - List of subject IDs to verify
     /// * `metadata_language` - Language of the subjects to check
+    /// * `organization_id` - The organization the subjects must belong to
     async fn verify_subjects_exist(
         &self,
         subject_ids: Vec<i32>,
         metadata_language: MetadataLanguage,
+        organization_id: i32,
     ) -> Result<bool, DbErr>;
 
+    /// Finds the subjects with any of the given names, ignoring case.
+    ///
+    /// # Arguments
+    /// * `names` - Subject names to look for
+    /// * `metadata_language` - Language of the subjects to look for
+    /// * `organization_id` - The organization whose subjects to look in
+    async fn find_by_names(
+        &self,
+        names: Vec<String>,
+        metadata_language: MetadataLanguage,
+        organization_id: i32,
+    ) -> Result<Vec<SubjectResponse>, DbErr>;
+
     /// Deletes a subject term by its ID.
     ///
     /// # Arguments
     /// * `subject_id` - The ID of the subject to delete.
     /// * `metadata_language` - Language of the subject to delete
+    /// * `organization_id` - The organization the subject must belong to
     async fn delete_one(
         &self,
         subject_id: i32,
         metadata_language: MetadataLanguage,
+        organization_id: i32,
     ) -> Result<Option<()>, DbErr>;
+
+    /// Finds an organization's accessions matching a filter, public or not, and picks out
+    /// those that don't have a subject yet.
+    ///
+    /// # Arguments
+    /// * `subject_id` - The subject to look for
+    /// * `filter` - Which accessions to match, and the language of the subject
+    /// * `organization_id` - The organization whose accessions to match
+    ///
+    /// # Returns
+    /// How many accessions match, and the IDs of the matching ones without the subject
+    async fn find_untagged(
+        &self,
+        subject_id: i32,
+        filter: ApplySubjectRequest,
+        organization_id: i32,
+    ) -> Result<(u64, Vec<i32>), DbErr>;
+
+    /// Attaches a subject to many accessions in a single transaction, bumping their
+    /// revision. Accessions without metadata in the subject's language are skipped.
+    ///
+    /// # Arguments
+    /// * `subject_id` - The subject to attach
+    /// * `metadata_language` - Language of the subject
+    /// * `accession_ids` - The accessions to attach it to
+    ///
+    /// # Returns
+    /// How many accessions the subject was attached to
+    async fn tag_accessions(
+        &self,
+        subject_id: i32,
+        metadata_language: MetadataLanguage,
+        accession_ids: Vec<i32>,
+    ) -> Result<u64, DbErr>;
+
+    /// Counts the subject terms of every organization in both languages, for the public
+    /// statistics widget.
+    async fn count_all(&self) -> Result<u64, DbErr>;
 }
 
 #[async_trait]
@@ -96,29 +219,32 @@ impl SubjectsRepo for DBSubjectsRepo {
     async fn write_one(
         &self,
         create_subject_request: CreateSubjectRequest,
+        organization_id: i32,
+        created_by: Option<String>,
     ) -> Result<SubjectResponse, DbErr> {
+        let now = self.clock.naive_now();
         let resp = match create_subject_request.lang {
             MetadataLanguage::English => {
                 let subject = DublinMetadataSubjectEnActiveModel {
                     id: Default::default(),
                     subject: ActiveValue::Set(create_subject_request.metadata_subject),
+                    organization_id: ActiveValue::Set(organization_id),
+                    created_at: ActiveValue::Set(now),
+                    updated_at: ActiveValue::Set(now),
+                    created_by: ActiveValue::Set(created_by),
                 };
-                let new_subject = subject.insert(&self.db_session).await?;
-                SubjectResponse {
-                    id: new_subject.id,
-                    subject: new_subject.subject,
-                }
+                subject.insert(&self.db_session).await?.into()
             }
             MetadataLanguage::Arabic => {
                 let subject = DublinMetadataSubjectArActiveModel {
                     id: Default::default(),
                     subject: ActiveValue::Set(create_subject_request.metadata_subject),
+                    organization_id: ActiveValue::Set(organization_id),
+                    created_at: ActiveValue::Set(now),
+                    updated_at: ActiveValue::Set(now),
+                    created_by: ActiveValue::Set(created_by),
                 };
-                let new_subject = subject.insert(&self.db_session).await?;
-                SubjectResponse {
-                    id: new_subject.id,
-                    subject: new_subject.subject,
-                }
+                subject.insert(&self.db_session).await?.into()
             }
         };
         Ok(resp)
@@ -129,20 +255,31 @@ impl SubjectsRepo for DBSubjectsRepo {
         page: u64,
         per_page: u64,
         query_term: Option<String>,
+        match_mode: SubjectMatch,
+        sort: SubjectSort,
+        organization_id: i32,
     ) -> Result<(Vec<DublinMetadataSubjectArModel>, u64), DbErr> {
-        let subject_pages;
+        let window = PageWindow::new(page, per_page);
+        let mut query = DublinMetadataSubjectAr::find()
+            .filter(dublin_metadata_subject_ar::Column::OrganizationId.eq(organization_id));
         if let Some(term) = query_term {
-            let query_string = format!("%{}%", term.to_lowercase());
-            let query_filter = Func::lower(Expr::col(dublin_metadata_subject_ar::Column::Subject))
-                .like(&query_string);
-            subject_pages = DublinMetadataSubjectAr::find()
-                .filter(query_filter)
-                .paginate(&self.db_session, per_page);
-        } else {
-            subject_pages = DublinMetadataSubjectAr::find().paginate(&self.db_session, per_page);
+            query = query.filter(matches_ignoring_accents(
+                dublin_metadata_subject_ar::Column::Subject,
+                &term,
+                match_mode,
+            ));
         }
+        let query = order_subjects(
+            query,
+            sort,
+            dublin_metadata_subject_ar::Column::Subject,
+            dublin_metadata_subject_ar::Column::Id,
+            "dublin_metadata_ar_subjects",
+            "dublin_metadata_subject_ar",
+        );
+        let subject_pages = query.paginate(&self.db_session, window.per_page);
         let num_pages = subject_pages.num_pages().await?;
-        Ok((subject_pages.fetch_page(page).await?, num_pages))
+        Ok((subject_pages.fetch_page(window.page).await?, num_pages))
     }
 
     async fn list_paginated_en(
@@ -150,31 +287,44 @@ impl SubjectsRepo for DBSubjectsRepo {
         page: u64,
         per_page: u64,
         query_term: Option<String>,
+        match_mode: SubjectMatch,
+        sort: SubjectSort,
+        organization_id: i32,
     ) -> Result<(Vec<DublinMetadataSubjectEnModel>, u64), DbErr> {
-        let subject_pages;
+        let window = PageWindow::new(page, per_page);
+        let mut query = DublinMetadataSubjectEn::find()
+            .filter(dublin_metadata_subject_en::Column::OrganizationId.eq(organization_id));
         if let Some(term) = query_term {
-            let query_string = format!("%{}%", term.to_lowercase());
-            let query_filter = Func::lower(Expr::col(dublin_metadata_subject_en::Column::Subject))
-                .like(&query_string);
-            subject_pages = DublinMetadataSubjectEn::find()
-                .filter(query_filter)
-                .paginate(&self.db_session, per_page);
-        } else {
-            subject_pages = DublinMetadataSubjectEn::find().paginate(&self.db_session, per_page);
+            query = query.filter(matches_ignoring_accents(
+                dublin_metadata_subject_en::Column::Subject,
+                &term,
+                match_mode,
+            ));
         }
+        let query = order_subjects(
+            query,
+            sort,
+            dublin_metadata_subject_en::Column::Subject,
+            dublin_metadata_subject_en::Column::Id,
+            "dublin_metadata_en_subjects",
+            "dublin_metadata_subject_en",
+        );
+        let subject_pages = query.paginate(&self.db_session, window.per_page);
         let num_pages = subject_pages.num_pages().await?;
-        Ok((subject_pages.fetch_page(page).await?, num_pages))
+        Ok((subject_pages.fetch_page(window.page).await?, num_pages))
     }
 
     async fn verify_subjects_exist(
         &self,
         subject_ids: Vec<i32>,
         metadata_language: MetadataLanguage,
+        organization_id: i32,
     ) -> Result<bool, DbErr> {
         let flag = match metadata_language {
             MetadataLanguage::English => {
                 let rows = DublinMetadataSubjectEn::find()
                     .filter(dublin_metadata_subject_en::Column::Id.is_in(subject_ids.clone()))
+                    .filter(dublin_metadata_subject_en::Column::OrganizationId.eq(organization_id))
                     .all(&self.db_session)
                     .await?;
                 rows.len() == subject_ids.len()
@@ -182,6 +332,7 @@ impl SubjectsRepo for DBSubjectsRepo {
             MetadataLanguage::Arabic => {
                 let rows = DublinMetadataSubjectAr::find()
                     .filter(dublin_metadata_subject_ar::Column::Id.is_in(subject_ids.clone()))
+                    .filter(dublin_metadata_subject_ar::Column::OrganizationId.eq(organization_id))
                     .all(&self.db_session)
                     .await?;
                 rows.len() == subject_ids.len()
@@ -190,19 +341,58 @@ impl SubjectsRepo for DBSubjectsRepo {
         Ok(flag)
     }
 
+    async fn find_by_names(
+        &self,
+        names: Vec<String>,
+        metadata_language: MetadataLanguage,
+        organization_id: i32,
+    ) -> Result<Vec<SubjectResponse>, DbErr> {
+        let names: Vec<String> = names.iter().map(|name| name.to_lowercase()).collect();
+        let subjects = match metadata_language {
+            MetadataLanguage::English => DublinMetadataSubjectEn::find()
+                .filter(
+                    Func::lower(Expr::col(dublin_metadata_subject_en::Column::Subject))
+                        .is_in(names),
+                )
+                .filter(dublin_metadata_subject_en::Column::OrganizationId.eq(organization_id))
+                .all(&self.db_session)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            MetadataLanguage::Arabic => DublinMetadataSubjectAr::find()
+                .filter(
+                    Func::lower(Expr::col(dublin_metadata_subject_ar::Column::Subject))
+                        .is_in(names),
+                )
+                .filter(dublin_metadata_subject_ar::Column::OrganizationId.eq(organization_id))
+                .all(&self.db_session)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        };
+        Ok(subjects)
+    }
+
     async fn delete_one(
         &self,
         subject_id: i32,
         metadata_language: MetadataLanguage,
+        organization_id: i32,
     ) -> Result<Option<()>, DbErr> {
         let deletion = match metadata_language {
             MetadataLanguage::English => {
-                DublinMetadataSubjectEn::delete_by_id(subject_id)
+                DublinMetadataSubjectEn::delete_many()
+                    .filter(dublin_metadata_subject_en::Column::Id.eq(subject_id))
+                    .filter(dublin_metadata_subject_en::Column::OrganizationId.eq(organization_id))
                     .exec(&self.db_session)
                     .await?
             }
             MetadataLanguage::Arabic => {
-                DublinMetadataSubjectAr::delete_by_id(subject_id)
+                DublinMetadataSubjectAr::delete_many()
+                    .filter(dublin_metadata_subject_ar::Column::Id.eq(subject_id))
+                    .filter(dublin_metadata_subject_ar::Column::OrganizationId.eq(organization_id))
                     .exec(&self.db_session)
                     .await?
             }
@@ -213,4 +403,586 @@ impl SubjectsRepo for DBSubjectsRepo {
             Ok(None)
         }
     }
+
+    async fn find_untagged(
+        &self,
+        subject_id: i32,
+        filter: ApplySubjectRequest,
+        organization_id: i32,
+    ) -> Result<(u64, Vec<i32>), DbErr> {
+        let subjects_column = match filter.lang {
+            MetadataLanguage::English => accessions_with_metadata::Column::SubjectsEnIds,
+            MetadataLanguage::Arabic => accessions_with_metadata::Column::SubjectsArIds,
+        };
+        let mut query = AccessionWithMetadata::find()
+            .select_only()
+            .column(accessions_with_metadata::Column::Id)
+            .column(subjects_column);
+        if let Some(expression) = build_filter_expression(FilterParams {
+            metadata_language: filter.lang,
+            query_term: filter.query_term,
+            date_from: filter.date_from,
+            date_to: filter.date_to,
+            visibility: Visibility::Any,
+            organization_id: Some(organization_id),
+            ..Default::default()
+        }) {
+            query = query.filter(expression);
+        }
+        if let Some(domain) = filter.domain {
+            query = query.filter(on_domain(&domain));
+        }
+        let matched: Vec<(i32, Option<Vec<i32>>)> = query
+            .order_by_asc(accessions_with_metadata::Column::Id)
+            .into_tuple()
+            .all(&self.db_session)
+            .await?;
+        let untagged = matched
+            .iter()
+            .filter(|(_, subject_ids)| {
+                !subject_ids
+                    .as_ref()
+                    .is_some_and(|subject_ids| subject_ids.contains(&subject_id))
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        Ok((matched.len() as u64, untagged))
+    }
+
+    async fn tag_accessions(
+        &self,
+        subject_id: i32,
+        metadata_language: MetadataLanguage,
+        accession_ids: Vec<i32>,
+    ) -> Result<u64, DbErr> {
+        if accession_ids.is_empty() {
+            return Ok(0);
+        }
+        let metadata_column = match metadata_language {
+            MetadataLanguage::English => accession::Column::DublinMetadataEn,
+            MetadataLanguage::Arabic => accession::Column::DublinMetadataAr,
+        };
+        let txn = self.db_session.begin().await?;
+        // lock the accessions so edits made meanwhile wait rather than miss the new revision
+        let tagged: Vec<(i32, i32)> = Accession::find()
+            .select_only()
+            .column(accession::Column::Id)
+            .column(metadata_column)
+            .filter(accession::Column::Id.is_in(accession_ids))
+            .filter(metadata_column.is_not_null())
+            .lock_exclusive()
+            .into_tuple()
+            .all(&txn)
+            .await?;
+        if tagged.is_empty() {
+            return Ok(0);
+        }
+        let inserted = match metadata_language {
+            MetadataLanguage::English => {
+                let links =
+                    tagged
+                        .iter()
+                        .map(|(_, metadata_id)| DublinMetadataSubjectsEnActiveModel {
+                            metadata_id: ActiveValue::Set(*metadata_id),
+                            subject_id: ActiveValue::Set(subject_id),
+                        });
+                DublinMetadataSubjectsEn::insert_many(links)
+                    .on_conflict(
+                        OnConflict::columns([
+                            dublin_metadata_en_subjects::Column::MetadataId,
+                            dublin_metadata_en_subjects::Column::SubjectId,
+                        ])
+                        .do_nothing()
+                        .to_owned(),
+                    )
+                    .exec_without_returning(&txn)
+                    .await?
+            }
+            MetadataLanguage::Arabic => {
+                let links =
+                    tagged
+                        .iter()
+                        .map(|(_, metadata_id)| DublinMetadataSubjectsArActiveModel {
+                            metadata_id: ActiveValue::Set(*metadata_id),
+                            subject_id: ActiveValue::Set(subject_id),
+                        });
+                DublinMetadataSubjectsAr::insert_many(links)
+                    .on_conflict(
+                        OnConflict::columns([
+                            dublin_metadata_ar_subjects::Column::MetadataId,
+                            dublin_metadata_ar_subjects::Column::SubjectId,
+                        ])
+                        .do_nothing()
+                        .to_owned(),
+                    )
+                    .exec_without_returning(&txn)
+                    .await?
+            }
+        };
+        Accession::update_many()
+            .col_expr(
+                accession::Column::Revision,
+                Expr::col(accession::Column::Revision).add(1),
+            )
+            .filter(accession::Column::Id.is_in(tagged.iter().map(|(id, _)| *id)))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(inserted)
+    }
+
+    async fn count_all(&self) -> Result<u64, DbErr> {
+        let english = DublinMetadataSubjectEn::find()
+            .count(&self.db_session)
+            .await?;
+        let arabic = DublinMetadataSubjectAr::find()
+            .count(&self.db_session)
+            .await?;
+        Ok(english + arabic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::common::MetadataScrubbing;
+    use crate::models::request::CreateAccessionRequestRaw;
+    use crate::repos::accessions_repo::{AccessionsRepo, DBAccessionsRepo};
+    use crate::repos::organizations_repo::{
+        DBOrganizationsRepo, OrganizationsRepo, DEFAULT_ORGANIZATION_ID,
+    };
+    use crate::test_db::{migrated_test_db, TestDb};
+    use ::entity::dublin_metadata_en::ActiveModel as DublinMetadataEnActiveModel;
+    use entity::sea_orm_active_enums::{DublinMetadataFormat, ScanStatus};
+    use pretty_assertions::assert_eq;
+
+    async fn build_repo() -> (TestDb, DBSubjectsRepo) {
+        let db = migrated_test_db().await;
+        let repo = DBSubjectsRepo {
+            db_session: db.clone(),
+            ..Default::default()
+        };
+        (db, repo)
+    }
+
+    async fn write_subject(repo: &DBSubjectsRepo, subject: &str, lang: MetadataLanguage) -> i32 {
+        repo.write_one(
+            CreateSubjectRequest {
+                metadata_subject: subject.to_string(),
+                lang,
+            },
+            DEFAULT_ORGANIZATION_ID,
+            Some("someuser@gmail.com".to_string()),
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn writes_and_searches_subjects() {
+        let (_db, repo) = build_repo().await;
+        write_subject(&repo, "Khartoum Protests", MetadataLanguage::English).await;
+        write_subject(&repo, "Darfur", MetadataLanguage::English).await;
+        write_subject(&repo, "Genēina", MetadataLanguage::English).await;
+        write_subject(&repo, "الخرطوم", MetadataLanguage::Arabic).await;
+
+        let (subjects, num_pages) = repo
+            .list_paginated_en(
+                0,
+                10,
+                Some("khartoum".to_string()),
+                SubjectMatch::Contains,
+                SubjectSort::Alpha,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        assert_eq!(num_pages, 1);
+        assert_eq!(
+            subjects.into_iter().map(|s| s.subject).collect::<Vec<_>>(),
+            vec!["Khartoum Protests"]
+        );
+        let (subjects, _) = repo
+            .list_paginated_en(
+                0,
+                10,
+                Some("Geneina".to_string()),
+                SubjectMatch::Contains,
+                SubjectSort::Alpha,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            subjects.into_iter().map(|s| s.subject).collect::<Vec<_>>(),
+            vec!["Genēina"]
+        );
+        let (subjects, _) = repo
+            .list_paginated_ar(
+                0,
+                10,
+                None,
+                SubjectMatch::Contains,
+                SubjectSort::Alpha,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        assert_eq!(subjects.len(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn records_who_added_subjects_and_when() {
+        let (_db, repo) = build_repo().await;
+        let written = repo
+            .write_one(
+                CreateSubjectRequest {
+                    metadata_subject: "الخرطوم".to_string(),
+                    lang: MetadataLanguage::Arabic,
+                },
+                DEFAULT_ORGANIZATION_ID,
+                Some("someuser@gmail.com".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(written.created_by, Some("someuser@gmail.com".to_string()));
+        assert_eq!(written.updated_at, written.created_at);
+
+        let (subjects, _) = repo
+            .list_paginated_ar(
+                0,
+                10,
+                None,
+                SubjectMatch::Contains,
+                SubjectSort::Alpha,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        assert_eq!(SubjectResponse::from(subjects[0].clone()), written);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn searches_subjects_exactly_or_by_prefix() {
+        let (_db, repo) = build_repo().await;
+        write_subject(&repo, "Sennar", MetadataLanguage::English).await;
+        write_subject(&repo, "Sennar Dam", MetadataLanguage::English).await;
+        write_subject(&repo, "Old Sennar", MetadataLanguage::English).await;
+
+        assert_eq!(
+            search_en(&repo, "sennar", SubjectMatch::Exact).await,
+            vec!["Sennar"]
+        );
+        assert_eq!(
+            search_en(&repo, "Sennar", SubjectMatch::Prefix).await,
+            vec!["Sennar", "Sennar Dam"]
+        );
+        assert_eq!(
+            search_en(&repo, "sennar", SubjectMatch::Contains).await,
+            vec!["Old Sennar", "Sennar", "Sennar Dam"]
+        );
+        assert!(search_en(&repo, "Senn_r", SubjectMatch::Prefix)
+            .await
+            .is_empty());
+    }
+
+    async fn search_en(repo: &DBSubjectsRepo, term: &str, match_mode: SubjectMatch) -> Vec<String> {
+        let (subjects, _) = repo
+            .list_paginated_en(
+                0,
+                10,
+                Some(term.to_string()),
+                match_mode,
+                SubjectSort::Alpha,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        let mut subjects: Vec<_> = subjects.into_iter().map(|s| s.subject).collect();
+        subjects.sort();
+        subjects
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn sorts_subjects() {
+        let (_db, repo) = build_repo().await;
+        let kassala = write_subject(&repo, "Kassala", MetadataLanguage::English).await;
+        write_subject(&repo, "Atbara", MetadataLanguage::English).await;
+        write_subject(&repo, "Nyala", MetadataLanguage::English).await;
+        for _ in 0..2 {
+            let metadata_id = DublinMetadataEnActiveModel {
+                id: Default::default(),
+                title: ActiveValue::Set("Flooding".to_string()),
+                description: ActiveValue::NotSet,
+                machine_translated: ActiveValue::Set(false),
+            }
+            .insert(&repo.db_session)
+            .await
+            .unwrap()
+            .id;
+            DublinMetadataSubjectsEnActiveModel {
+                metadata_id: ActiveValue::Set(metadata_id),
+                subject_id: ActiveValue::Set(kassala),
+            }
+            .insert(&repo.db_session)
+            .await
+            .unwrap();
+        }
+        let repo = &repo;
+        let sorted = |sort| async move {
+            let (subjects, _) = repo
+                .list_paginated_en(
+                    0,
+                    10,
+                    None,
+                    SubjectMatch::Contains,
+                    sort,
+                    DEFAULT_ORGANIZATION_ID,
+                )
+                .await
+                .unwrap();
+            subjects.into_iter().map(|s| s.subject).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            sorted(SubjectSort::Alpha).await,
+            vec!["Atbara", "Kassala", "Nyala"]
+        );
+        assert_eq!(
+            sorted(SubjectSort::Usage).await,
+            vec!["Kassala", "Atbara", "Nyala"]
+        );
+        assert_eq!(
+            sorted(SubjectSort::Recent).await,
+            vec!["Nyala", "Atbara", "Kassala"]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn verifies_subjects_exist_per_language() {
+        let (_db, repo) = build_repo().await;
+        let en_id = write_subject(&repo, "Darfur", MetadataLanguage::English).await;
+
+        assert!(repo
+            .verify_subjects_exist(
+                vec![en_id],
+                MetadataLanguage::English,
+                DEFAULT_ORGANIZATION_ID
+            )
+            .await
+            .unwrap());
+        assert!(!repo
+            .verify_subjects_exist(
+                vec![en_id, en_id + 1],
+                MetadataLanguage::English,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap());
+        assert!(!repo
+            .verify_subjects_exist(
+                vec![en_id],
+                MetadataLanguage::Arabic,
+                DEFAULT_ORGANIZATION_ID
+            )
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn finds_subjects_by_name_ignoring_case() {
+        let (_db, repo) = build_repo().await;
+        let darfur = write_subject(&repo, "Darfur", MetadataLanguage::English).await;
+        write_subject(&repo, "Khartoum Protests", MetadataLanguage::English).await;
+        write_subject(&repo, "الخرطوم", MetadataLanguage::Arabic).await;
+
+        let names = vec!["DARFUR".to_string(), "Khartoum".to_string()];
+        assert_eq!(
+            repo.find_by_names(
+                names.clone(),
+                MetadataLanguage::English,
+                DEFAULT_ORGANIZATION_ID
+            )
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|subject| (subject.id, subject.subject))
+            .collect::<Vec<_>>(),
+            vec![(darfur, "Darfur".to_string())]
+        );
+        assert_eq!(
+            repo.find_by_names(names, MetadataLanguage::Arabic, DEFAULT_ORGANIZATION_ID)
+                .await
+                .unwrap(),
+            vec![]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn deletes_subjects() {
+        let (_db, repo) = build_repo().await;
+        let id = write_subject(&repo, "Darfur", MetadataLanguage::English).await;
+
+        assert_eq!(
+            repo.delete_one(id, MetadataLanguage::English, DEFAULT_ORGANIZATION_ID)
+                .await
+                .unwrap(),
+            Some(())
+        );
+        assert_eq!(
+            repo.delete_one(id, MetadataLanguage::English, DEFAULT_ORGANIZATION_ID)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn keeps_each_organizations_subjects_apart() {
+        let (_db, repo) = build_repo().await;
+        let partner = DBOrganizationsRepo {
+            db_session: repo.db_session.clone(),
+            ..Default::default()
+        }
+        .write_one(
+            "diaspora-archive".to_string(),
+            "Diaspora Archive".to_string(),
+        )
+        .await
+        .unwrap();
+        let darfur = write_subject(&repo, "Darfur", MetadataLanguage::English).await;
+        // the same name can be used by another organization
+        let partner_darfur = repo
+            .write_one(
+                CreateSubjectRequest {
+                    metadata_subject: "Darfur".to_string(),
+                    lang: MetadataLanguage::English,
+                },
+                partner.id,
+                None,
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let (subjects, _) = repo
+            .list_paginated_en(
+                0,
+                10,
+                None,
+                SubjectMatch::Contains,
+                SubjectSort::Alpha,
+                partner.id,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            subjects.into_iter().map(|s| s.id).collect::<Vec<_>>(),
+            vec![partner_darfur]
+        );
+        assert!(!repo
+            .verify_subjects_exist(vec![darfur], MetadataLanguage::English, partner.id)
+            .await
+            .unwrap());
+        assert_eq!(
+            repo.delete_one(darfur, MetadataLanguage::English, partner.id)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    async fn write_accession(
+        accessions_repo: &DBAccessionsRepo,
+        url: &str,
+        subjects: Vec<i32>,
+    ) -> i32 {
+        accessions_repo
+            .write_one_raw(
+                CreateAccessionRequestRaw {
+                    metadata_language: MetadataLanguage::English,
+                    metadata_title: "Testimony".to_string(),
+                    metadata_description: None,
+                    metadata_time: Default::default(),
+                    metadata_subjects: subjects,
+                    is_private: true,
+                    embargo_until: None,
+                    content_warning: None,
+                    metadata_format: DublinMetadataFormat::Jpeg,
+                    original_url: url.to_string(),
+                    s3_filename: "file.jpg".to_string(),
+                    metadata_scrubbing: MetadataScrubbing::Scrub,
+                },
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn tags_accessions_matching_a_filter() {
+        let (_db, repo) = build_repo().await;
+        let accessions_repo = DBAccessionsRepo {
+            db_session: repo.db_session.clone(),
+            ..Default::default()
+        };
+        let darfur = write_subject(&repo, "Darfur", MetadataLanguage::English).await;
+        let tagged = write_accession(
+            &accessions_repo,
+            "https://www.bbc.co.uk/news/1",
+            vec![darfur],
+        )
+        .await;
+        let untagged = write_accession(&accessions_repo, "https://bbc.co.uk/news/2", vec![]).await;
+        write_accession(&accessions_repo, "https://notbbc.co.uk/news/3", vec![]).await;
+        let filter = ApplySubjectRequest {
+            lang: MetadataLanguage::English,
+            query_term: None,
+            date_from: None,
+            date_to: None,
+            domain: Some("bbc.co.uk".to_string()),
+            dry_run: false,
+        };
+
+        assert_eq!(
+            repo.find_untagged(darfur, filter.clone(), DEFAULT_ORGANIZATION_ID)
+                .await
+                .unwrap(),
+            (2, vec![untagged])
+        );
+        assert_eq!(
+            repo.tag_accessions(darfur, MetadataLanguage::English, vec![untagged])
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            repo.find_untagged(darfur, filter, DEFAULT_ORGANIZATION_ID)
+                .await
+                .unwrap(),
+            (2, vec![])
+        );
+        let revisions: Vec<i32> = Accession::find()
+            .select_only()
+            .column(accession::Column::Revision)
+            .filter(accession::Column::Id.is_in([tagged, untagged]))
+            .order_by_asc(accession::Column::Id)
+            .into_tuple()
+            .all(&repo.db_session)
+            .await
+            .unwrap();
+        assert_eq!(revisions, vec![0, 1]);
+    }
 }
@@ -0,0 +1,212 @@
+//! Repository for anchoring file hashes with an external timestamping service.
+//!
+//! A timestamp proves a hash, and so the file it was taken of, existed by a certain time
+//! without having to trust the archive. Two kinds of service are supported:
+//! - [OpenTimestamps](https://opentimestamps.org) calendars, which aggregate hashes into a
+//!   Bitcoin transaction. The proof is a detached `.ots` file that starts out pending and
+//!   can be completed and checked with `ots upgrade` and `ots verify` once the transaction
+//!   is confirmed, a few hours later.
+//! - [RFC 3161](https://datatracker.ietf.org/doc/html/rfc3161) timestamping authorities,
+//!   which sign the hash and time straight away. The proof is the authority's DER
+//!   `TimeStampResp`, which can be checked with `openssl ts -verify`.
+
+use async_trait::async_trait;
+use entity::sea_orm_active_enums::TimestampMethod;
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::Client;
+use std::error::Error;
+
+/// Magic bytes every `.ots` file starts with
+const OTS_HEADER_MAGIC: &[u8] =
+    b"\x00OpenTimestamps\x00\x00Proof\x00\xbf\x89\xe2\xe8\x84\xe8\x92\x94";
+/// Major version of the `.ots` format
+const OTS_VERSION: u8 = 1;
+/// Tag of the SHA-256 operation the file hash was made with
+const OTS_OP_SHA256: u8 = 0x08;
+
+/// DER encoding of a `TimeStampReq` for a SHA-256 hash up to the hash itself: version 1 and
+/// a `MessageImprint` with the SHA-256 OID. `certReq` follows the hash.
+const RFC3161_REQUEST_PREFIX: &[u8] = &[
+    0x30, 0x39, // TimeStampReq
+    0x02, 0x01, 0x01, // version
+    0x30, 0x31, // MessageImprint
+    0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, // AlgorithmIdentifier, sha256 with no parameters
+    0x04, 0x20, // hashedMessage
+];
+/// `certReq` set, so the authority includes its certificate for checking the signature
+const RFC3161_REQUEST_SUFFIX: &[u8] = &[0x01, 0x01, 0xff];
+
+#[async_trait]
+pub trait TimestampRepo: Send + Sync {
+    /// The kind of service timestamps come from
+    fn method(&self) -> TimestampMethod;
+
+    /// Timestamps a SHA-256 hash.
+    ///
+    /// # Arguments
+    /// * `sha256` - The raw hash to timestamp
+    ///
+    /// # Returns
+    /// The proof, in the format [`TimestampRepo::method`] says
+    ///
+    /// # Errors
+    /// Returns Error if the service could not be reached or refused to timestamp the hash
+    async fn timestamp(&self, sha256: &[u8; 32]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+}
+
+/// Timestamps hashes with an OpenTimestamps calendar.
+#[derive(Debug, Clone, Default)]
+pub struct OpenTimestampsRepo {
+    pub client: Client,
+    /// e.g. `https://alice.btc.calendar.opentimestamps.org`
+    pub calendar_url: String,
+}
+
+#[async_trait]
+impl TimestampRepo for OpenTimestampsRepo {
+    fn method(&self) -> TimestampMethod {
+        TimestampMethod::OpenTimestamps
+    }
+
+    async fn timestamp(&self, sha256: &[u8; 32]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let pending = self
+            .client
+            .post(format!(
+                "{}/digest",
+                self.calendar_url.trim_end_matches('/')
+            ))
+            .header(ACCEPT, "application/vnd.opentimestamps.v1")
+            .body(sha256.to_vec())
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        Ok(ots_file(sha256, &pending))
+    }
+}
+
+/// Wraps a calendar's timestamp of a hash into a detached `.ots` file for it.
+fn ots_file(sha256: &[u8; 32], timestamp: &[u8]) -> Vec<u8> {
+    [
+        OTS_HEADER_MAGIC,
+        &[OTS_VERSION, OTS_OP_SHA256],
+        sha256,
+        timestamp,
+    ]
+    .concat()
+}
+
+/// Timestamps hashes with an RFC 3161 timestamping authority.
+#[derive(Debug, Clone, Default)]
+pub struct Rfc3161TimestampRepo {
+    pub client: Client,
+    /// e.g. `https://freetsa.org/tsr`
+    pub tsa_url: String,
+}
+
+#[async_trait]
+impl TimestampRepo for Rfc3161TimestampRepo {
+    fn method(&self) -> TimestampMethod {
+        TimestampMethod::Rfc3161
+    }
+
+    async fn timestamp(&self, sha256: &[u8; 32]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let response = self
+            .client
+            .post(&self.tsa_url)
+            .header(CONTENT_TYPE, "application/timestamp-query")
+            .header(ACCEPT, "application/timestamp-reply")
+            .body(timestamp_request(sha256))
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        match response_status(&response) {
+            // granted, or granted with modifications
+            Some(0 | 1) => Ok(response.to_vec()),
+            Some(status) => {
+                Err(format!("Timestamp request was refused with status {status}").into())
+            }
+            None => Err("Timestamp response was malformed".into()),
+        }
+    }
+}
+
+/// DER encoding of a `TimeStampReq` for a SHA-256 hash.
+fn timestamp_request(sha256: &[u8; 32]) -> Vec<u8> {
+    [RFC3161_REQUEST_PREFIX, sha256, RFC3161_REQUEST_SUFFIX].concat()
+}
+
+/// Reads the status out of a DER `TimeStampResp`, which starts with a `PKIStatusInfo`
+/// sequence whose first item is the status.
+fn response_status(response: &[u8]) -> Option<u8> {
+    let status_info = der_sequence_contents(response)?;
+    let status = der_sequence_contents(status_info)?;
+    match status {
+        [0x02, 0x01, status, ..] => Some(*status),
+        _ => None,
+    }
+}
+
+/// The contents of the DER sequence at the start of `der`.
+fn der_sequence_contents(der: &[u8]) -> Option<&[u8]> {
+    let (&tag, rest) = der.split_first()?;
+    if tag != 0x30 {
+        return None;
+    }
+    let (&first, rest) = rest.split_first()?;
+    let (length, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        // long form, the low bits give how many bytes the length takes
+        let length_bytes = (first & 0x7f) as usize;
+        if length_bytes == 0 || length_bytes > 4 || rest.len() < length_bytes {
+            return None;
+        }
+        let (length, rest) = rest.split_at(length_bytes);
+        let length = length
+            .iter()
+            .fold(0usize, |length, byte| (length << 8) | *byte as usize);
+        (length, rest)
+    };
+    rest.get(..length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn builds_a_timestamp_request() {
+        let request = timestamp_request(&[0xab; 32]);
+        assert_eq!(request.len(), 59);
+        assert_eq!(der_sequence_contents(&request).map(<[u8]>::len), Some(57));
+        assert_eq!(&request[24..56], &[0xab; 32]);
+        assert_eq!(&request[56..], &[0x01, 0x01, 0xff]);
+    }
+
+    #[test]
+    fn reads_the_status_of_a_timestamp_response() {
+        let granted = [
+            0x30, 0x82, 0x00, 0x07, 0x30, 0x03, 0x02, 0x01, 0x00, 0x30, 0x00,
+        ];
+        assert_eq!(response_status(&granted), Some(0));
+        let rejected = [0x30, 0x05, 0x30, 0x03, 0x02, 0x01, 0x02];
+        assert_eq!(response_status(&rejected), Some(2));
+        assert_eq!(response_status(&[0x30, 0x05, 0x30]), None);
+        assert_eq!(response_status(b"<html>"), None);
+    }
+
+    #[test]
+    fn wraps_calendar_timestamps_in_an_ots_file() {
+        let file = ots_file(&[0xab; 32], &[0xf0, 0x10]);
+        assert!(file.starts_with(OTS_HEADER_MAGIC));
+        assert_eq!(&file[31..33], &[OTS_VERSION, OTS_OP_SHA256]);
+        assert_eq!(&file[33..65], &[0xab; 32]);
+        assert_eq!(&file[65..], &[0xf0, 0x10]);
+    }
+}
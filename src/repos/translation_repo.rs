@@ -0,0 +1,77 @@
+//! Repository for machine translating metadata.
+//!
+//! Talks to a [LibreTranslate](https://libretranslate.com) compatible service, which can
+//! be self hosted so accession metadata never leaves our infrastructure.
+
+use async_trait::async_trait;
+use reqwest::{Client, Error};
+use serde::{Deserialize, Serialize};
+
+#[async_trait]
+pub trait TranslationRepo: Send + Sync {
+    /// Translates each text from one language to another.
+    ///
+    /// # Arguments
+    /// * `texts` - The texts to translate
+    /// * `source` - ISO 639-1 code of the language the texts are in
+    /// * `target` - ISO 639-1 code of the language to translate into
+    ///
+    /// # Returns
+    /// The translations, in the same order as `texts`
+    async fn translate(
+        &self,
+        texts: Vec<String>,
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>, Error>;
+}
+
+#[derive(Debug, Serialize)]
+struct TranslateRequest<'a> {
+    q: Vec<String>,
+    source: &'a str,
+    target: &'a str,
+    format: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TranslateResponse {
+    translated_text: Vec<String>,
+}
+
+/// Translates by calling a LibreTranslate service over HTTP.
+#[derive(Debug, Clone, Default)]
+pub struct HTTPTranslationRepo {
+    pub client: Client,
+    pub base_url: String,
+    /// Only needed for services that require a key, e.g. the public LibreTranslate instance
+    pub api_key: Option<String>,
+}
+
+#[async_trait]
+impl TranslationRepo for HTTPTranslationRepo {
+    async fn translate(
+        &self,
+        texts: Vec<String>,
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>, Error> {
+        let resp = self
+            .client
+            .post(format!("{}/translate", self.base_url))
+            .json(&TranslateRequest {
+                q: texts,
+                source,
+                target,
+                format: "text",
+                api_key: self.api_key.as_deref(),
+            })
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(resp.json::<TranslateResponse>().await?.translated_text)
+    }
+}
@@ -0,0 +1,80 @@
+//! Repository module for tracking client-driven multipart uploads.
+//!
+//! S3 only knows about an upload by its object key and upload ID, so we record which
+//! key and user each upload belongs to. That lets clients resume an upload using just
+//! the upload ID, even after the server restarts.
+
+use crate::clock::SharedClock;
+use ::entity::upload_session::ActiveModel as UploadSessionActiveModel;
+use ::entity::upload_session::Entity as UploadSession;
+use ::entity::upload_session::Model as UploadSessionModel;
+use async_trait::async_trait;
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection, DbErr, EntityTrait};
+
+/// Repository implementation for database operations on upload sessions.
+#[derive(Debug, Clone, Default)]
+pub struct DBUploadsRepo {
+    pub db_session: DatabaseConnection,
+    /// Tells the time uploads start at
+    pub clock: SharedClock,
+}
+
+/// Defines the interface for upload session database operations.
+#[async_trait]
+pub trait UploadsRepo: Send + Sync {
+    /// Records a newly initiated multipart upload.
+    ///
+    /// # Arguments
+    /// * `upload_id` - The S3 multipart upload ID
+    /// * `s3_key` - The object key being uploaded to
+    /// * `created_by` - The user who started the upload
+    async fn write_one(
+        &self,
+        upload_id: String,
+        s3_key: String,
+        created_by: String,
+    ) -> Result<UploadSessionModel, DbErr>;
+
+    /// Gets an upload session by its S3 multipart upload ID.
+    ///
+    /// # Arguments
+    /// * `upload_id` - The S3 multipart upload ID
+    async fn get_one(&self, upload_id: &str) -> Result<Option<UploadSessionModel>, DbErr>;
+
+    /// Deletes an upload session once it has been completed or aborted.
+    ///
+    /// # Arguments
+    /// * `upload_id` - The S3 multipart upload ID
+    async fn delete_one(&self, upload_id: &str) -> Result<(), DbErr>;
+}
+
+#[async_trait]
+impl UploadsRepo for DBUploadsRepo {
+    async fn write_one(
+        &self,
+        upload_id: String,
+        s3_key: String,
+        created_by: String,
+    ) -> Result<UploadSessionModel, DbErr> {
+        let upload_session = UploadSessionActiveModel {
+            upload_id: ActiveValue::Set(upload_id),
+            s3_key: ActiveValue::Set(s3_key),
+            created_by: ActiveValue::Set(created_by),
+            created_at: ActiveValue::Set(self.clock.naive_now()),
+        };
+        upload_session.insert(&self.db_session).await
+    }
+
+    async fn get_one(&self, upload_id: &str) -> Result<Option<UploadSessionModel>, DbErr> {
+        UploadSession::find_by_id(upload_id)
+            .one(&self.db_session)
+            .await
+    }
+
+    async fn delete_one(&self, upload_id: &str) -> Result<(), DbErr> {
+        UploadSession::delete_by_id(upload_id)
+            .exec(&self.db_session)
+            .await?;
+        Ok(())
+    }
+}
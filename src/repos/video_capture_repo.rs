@@ -0,0 +1,200 @@
+//! Repository for video captures.
+//!
+//! Crawls of YouTube or Facebook videos replay poorly, since the player streams the video in
+//! segments the crawler rarely catches all of. [`VideoCaptureRepo`] is a [`CrawlerRepo`] that
+//! downloads the video itself with a [`VideoDownloaderRepo`] instead, keeping the platform's
+//! info JSON to be stored next to it. URLs the downloader finds no video at are handed to the
+//! crawler it wraps. Each crawl requested with `capture_mode` set to `video` gets its own,
+//! wrapped around the crawler it would otherwise have used.
+
+use crate::clock::SharedIdGen;
+use crate::crawl_state_machine::LaunchedCrawl;
+use crate::models::common::CrawlerBackend;
+use crate::models::request::CreateCrawlRequest;
+use crate::repos::crawler_repo::{CrawlArchive, CrawlerError, CrawlerRepo};
+use crate::repos::video_downloader_repo::VideoDownloaderRepo;
+use async_trait::async_trait;
+use bytes::Bytes;
+use entity::sea_orm_active_enums::DublinMetadataFormat;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// How much of a video is read from disk at a time.
+const READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// A video being downloaded, with what the platform said about it.
+struct VideoDownload {
+    info_json: Bytes,
+    task: JoinHandle<Result<PathBuf, CrawlerError>>,
+}
+
+/// Downloads videos with `downloader`, crawling URLs without one with `fallback`.
+pub struct VideoCaptureRepo {
+    downloader: Arc<dyn VideoDownloaderRepo>,
+    /// Crawls URLs the downloader finds no video at
+    pub fallback: Arc<dyn CrawlerRepo>,
+    /// Generates the IDs of downloads
+    ids: SharedIdGen,
+    downloads: Mutex<HashMap<Uuid, VideoDownload>>,
+}
+
+impl VideoCaptureRepo {
+    pub fn new(
+        downloader: Arc<dyn VideoDownloaderRepo>,
+        fallback: Arc<dyn CrawlerRepo>,
+        ids: SharedIdGen,
+    ) -> Self {
+        Self {
+            downloader,
+            fallback,
+            ids,
+            downloads: Mutex::default(),
+        }
+    }
+}
+
+/// Streams a downloaded video off disk, removing it once it has been read.
+fn stream_and_remove(path: PathBuf) -> Result<CrawlArchive, CrawlerError> {
+    let file =
+        File::open(&path).map_err(|err| format!("Could not open {}: {err}", path.display()))?;
+    let stream = futures::stream::try_unfold((file, path), |(mut file, path)| async move {
+        tokio::task::spawn_blocking(move || -> Result<_, CrawlerError> {
+            let mut chunk = vec![0; READ_CHUNK_SIZE];
+            let read = file.read(&mut chunk)?;
+            if read == 0 {
+                std::fs::remove_file(&path)?;
+                return Ok(None);
+            }
+            chunk.truncate(read);
+            Ok(Some((Bytes::from(chunk), (file, path))))
+        })
+        .await?
+    });
+    Ok(CrawlArchive {
+        format: DublinMetadataFormat::Mp4,
+        stream: stream.boxed(),
+        metadata_json: None,
+    })
+}
+
+#[async_trait]
+impl CrawlerRepo for VideoCaptureRepo {
+    /// The crawler URLs fall back to, since that's the one the request asked for.
+    fn backend(&self) -> CrawlerBackend {
+        self.fallback.backend()
+    }
+
+    async fn launch(
+        &self,
+        create_crawl_request: CreateCrawlRequest,
+    ) -> Result<LaunchedCrawl, CrawlerError> {
+        let url = create_crawl_request.url.clone();
+        let info_json = match self.downloader.probe(&url).await {
+            Ok(info_json) => info_json,
+            Err(err) => {
+                warn!(%err, %url, "No video found, crawling the page instead");
+                return self.fallback.launch(create_crawl_request).await;
+            }
+        };
+        let crawl_id = self.ids.new_id();
+        let downloader = self.downloader.clone();
+        let task = tokio::spawn({
+            let url = url.clone();
+            async move { downloader.download(&url).await }
+        });
+        self.downloads
+            .lock()
+            .unwrap()
+            .insert(crawl_id, VideoDownload { info_json, task });
+        info!(%url, %crawl_id, "Started video download");
+        Ok(LaunchedCrawl {
+            id: crawl_id,
+            job_run_id: crawl_id.to_string(),
+            org_id: None,
+        })
+    }
+
+    async fn is_finished(&self, crawl: &LaunchedCrawl) -> Result<bool, CrawlerError> {
+        if let Some(download) = self.downloads.lock().unwrap().get(&crawl.id) {
+            return Ok(download.task.is_finished());
+        }
+        self.fallback.is_finished(crawl).await
+    }
+
+    async fn download(&self, crawl: &LaunchedCrawl) -> Result<CrawlArchive, CrawlerError> {
+        let download = self.downloads.lock().unwrap().remove(&crawl.id);
+        let Some(VideoDownload { info_json, task }) = download else {
+            return self.fallback.download(crawl).await;
+        };
+        let path = task.await??;
+        let archive = tokio::task::spawn_blocking(move || stream_and_remove(path)).await??;
+        Ok(CrawlArchive {
+            metadata_json: Some(info_json),
+            ..archive
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::{InMemoryBrowsertrixRepo, InMemoryVideoDownloaderRepo};
+    use futures::TryStreamExt;
+    use pretty_assertions::assert_eq;
+
+    fn crawl_request(url: &str) -> CreateCrawlRequest {
+        CreateCrawlRequest {
+            url: url.to_string(),
+            browser_profile: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn downloads_videos_with_their_info_json() {
+        let repo = VideoCaptureRepo::new(
+            Arc::new(InMemoryVideoDownloaderRepo {}),
+            Arc::new(InMemoryBrowsertrixRepo {}),
+            SharedIdGen::default(),
+        );
+        let crawl = repo
+            .launch(crawl_request("https://www.youtube.com/watch?v=abc"))
+            .await
+            .unwrap();
+        assert_eq!(crawl.org_id, None);
+        while !repo.is_finished(&crawl).await.unwrap() {
+            tokio::task::yield_now().await;
+        }
+
+        let archive = repo.download(&crawl).await.unwrap();
+        assert_eq!(archive.format, DublinMetadataFormat::Mp4);
+        assert_eq!(
+            archive.metadata_json,
+            Some(Bytes::from_static(br#"{"title": "Fighting in Omdurman"}"#))
+        );
+        let chunks: Vec<Bytes> = archive.stream.try_collect().await.unwrap();
+        assert_eq!(chunks.concat(), b"mp4 bytes");
+    }
+
+    #[tokio::test]
+    async fn crawls_pages_without_videos() {
+        let repo = VideoCaptureRepo::new(
+            Arc::new(InMemoryVideoDownloaderRepo {}),
+            Arc::new(InMemoryBrowsertrixRepo {}),
+            SharedIdGen::default(),
+        );
+        let crawl = repo
+            .launch(crawl_request("https://sudantribune.com/article/1"))
+            .await
+            .unwrap();
+        assert_eq!(crawl.job_run_id, "test_job_123");
+        let archive = repo.download(&crawl).await.unwrap();
+        assert_eq!(archive.metadata_json, None);
+    }
+}
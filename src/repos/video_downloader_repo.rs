@@ -0,0 +1,163 @@
+//! Repository for downloading videos from the platforms they were posted on.
+//!
+//! Shells out to [yt-dlp](https://github.com/yt-dlp/yt-dlp), which knows how to get the media
+//! file behind a YouTube, Facebook, X or Telegram video and what the platform says about it:
+//! title, uploader, upload date, view counts and so on. Videos are remuxed into MP4 so they
+//! play in browsers and get the same derivatives as uploaded videos.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::Command;
+use uuid::Uuid;
+
+/// Videos larger than this are not downloaded.
+const MAX_VIDEO_FILESIZE: &str = "2G";
+
+/// Picks an MP4 when the platform has one, else the best video and audio to be remuxed.
+const VIDEO_FORMAT: &str = "bv*[ext=mp4]+ba[ext=m4a]/b[ext=mp4]/bv*+ba/b";
+
+#[async_trait]
+pub trait VideoDownloaderRepo: Send + Sync {
+    /// Asks the platform about the video at a URL without downloading it.
+    ///
+    /// # Arguments
+    /// * `url` - URL of the page the video is on
+    ///
+    /// # Returns
+    /// yt-dlp's info JSON about the video
+    ///
+    /// # Errors
+    /// Returns Error if the downloader could not be run or found no video at the URL
+    async fn probe(&self, url: &str) -> Result<Bytes, Box<dyn Error + Send + Sync>>;
+
+    /// Downloads the video at a URL.
+    ///
+    /// # Arguments
+    /// * `url` - URL of the page the video is on
+    ///
+    /// # Returns
+    /// Path of the downloaded MP4 in the temporary directory, which the caller removes
+    ///
+    /// # Errors
+    /// Returns Error if the downloader could not be run or failed
+    async fn download(&self, url: &str) -> Result<PathBuf, Box<dyn Error + Send + Sync>>;
+}
+
+/// Downloads by running a yt-dlp binary.
+#[derive(Debug, Clone, Default)]
+pub struct YtDlpVideoDownloaderRepo {
+    /// Path to the yt-dlp binary, e.g. `/usr/local/bin/yt-dlp`
+    pub ytdlp_path: String,
+}
+
+impl YtDlpVideoDownloaderRepo {
+    /// Runs yt-dlp with some arguments, off the async workers since it blocks for as long as
+    /// the download takes.
+    async fn run(&self, args: Vec<String>) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+        let mut command = Command::new(&self.ytdlp_path);
+        command.args(args);
+        tokio::task::spawn_blocking(move || match command.output() {
+            Err(err) => Err(err.into()),
+            Ok(output) if !output.status.success() => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let last_line = stderr.lines().last().unwrap_or_default();
+                Err(format!("yt-dlp exited with {}: {last_line}", output.status).into())
+            }
+            Ok(output) => Ok(output.stdout),
+        })
+        .await?
+    }
+}
+
+#[async_trait]
+impl VideoDownloaderRepo for YtDlpVideoDownloaderRepo {
+    async fn probe(&self, url: &str) -> Result<Bytes, Box<dyn Error + Send + Sync>> {
+        self.run(probe_args(url)).await.map(Bytes::from)
+    }
+
+    async fn download(&self, url: &str) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+        let output_path = std::env::temp_dir().join(format!("{}.mp4", Uuid::new_v4()));
+        let result = self
+            .run(download_args(url, &output_path.to_string_lossy()))
+            .await;
+        match result {
+            Ok(_) => Ok(output_path),
+            Err(err) => {
+                // yt-dlp may leave a partial file behind when it fails
+                let _ = std::fs::remove_file(&output_path);
+                Err(err)
+            }
+        }
+    }
+}
+
+/// Arguments shared by every yt-dlp run: one video, no config files, no progress output.
+fn common_args(url: &str) -> Vec<String> {
+    [
+        "--ignore-config",
+        "--no-playlist",
+        "--no-progress",
+        "--quiet",
+        "--no-warnings",
+        "--format",
+        VIDEO_FORMAT,
+        url,
+    ]
+    .map(str::to_string)
+    .to_vec()
+}
+
+/// Arguments printing the info JSON of the video yt-dlp would download.
+fn probe_args(url: &str) -> Vec<String> {
+    let mut args = vec!["--dump-single-json".to_string(), "--simulate".to_string()];
+    args.extend(common_args(url));
+    args
+}
+
+/// Arguments downloading the video into an MP4 at `output_path`.
+fn download_args(url: &str, output_path: &str) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "--max-filesize",
+        MAX_VIDEO_FILESIZE,
+        "--merge-output-format",
+        "mp4",
+        "--remux-video",
+        "mp4",
+        "--no-part",
+        "--output",
+        output_path,
+    ]
+    .map(str::to_string)
+    .to_vec();
+    args.extend(common_args(url));
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn probes_without_downloading() {
+        let args = probe_args("https://www.youtube.com/watch?v=abc");
+        assert_eq!(args[..2], ["--dump-single-json", "--simulate"]);
+        assert!(args.contains(&"--no-playlist".to_string()));
+        assert_eq!(args.last().unwrap(), "https://www.youtube.com/watch?v=abc");
+    }
+
+    #[test]
+    fn downloads_into_an_mp4() {
+        let args = download_args("https://www.youtube.com/watch?v=abc", "/tmp/out.mp4");
+        assert!(args
+            .windows(2)
+            .any(|pair| pair == ["--output", "/tmp/out.mp4"]));
+        assert!(args
+            .windows(2)
+            .any(|pair| pair == ["--merge-output-format", "mp4"]));
+        assert!(args.windows(2).any(|pair| pair == ["--max-filesize", "2G"]));
+        assert_eq!(args.last().unwrap(), "https://www.youtube.com/watch?v=abc");
+    }
+}
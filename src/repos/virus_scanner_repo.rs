@@ -0,0 +1,111 @@
+//! Repository for scanning uploaded files for malware.
+//!
+//! Talks to a [ClamAV](https://docs.clamav.net) `clamd` daemon over TCP using its
+//! `INSTREAM` command, so files are scanned as they stream past instead of being
+//! written to disk first.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use std::error::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// clamd rejects stream chunks over its `StreamMaxLength`, so larger chunks are split up.
+const MAX_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The outcome of a completed scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    /// The file matched a signature, e.g. `Win.Test.EICAR_HDB-1`
+    Infected(String),
+}
+
+#[async_trait]
+pub trait VirusScannerRepo: Send + Sync {
+    /// Scans a file for malware.
+    ///
+    /// # Arguments
+    /// * `chunks` - The file contents, in order. An error item aborts the scan.
+    ///
+    /// # Returns
+    /// Whether the file is clean or the signature it matched
+    ///
+    /// # Errors
+    /// Returns Error if the file could not be read or the scanner could not be reached
+    async fn scan(
+        &self,
+        chunks: BoxStream<'static, Result<Bytes, String>>,
+    ) -> Result<ScanVerdict, Box<dyn Error + Send + Sync>>;
+}
+
+/// Scans files with a clamd daemon listening on TCP.
+#[derive(Debug, Clone, Default)]
+pub struct ClamdVirusScannerRepo {
+    /// Host and port of clamd, e.g. `clamav:3310`
+    pub address: String,
+}
+
+#[async_trait]
+impl VirusScannerRepo for ClamdVirusScannerRepo {
+    async fn scan(
+        &self,
+        mut chunks: BoxStream<'static, Result<Bytes, String>>,
+    ) -> Result<ScanVerdict, Box<dyn Error + Send + Sync>> {
+        let mut connection = TcpStream::connect(&self.address).await?;
+        connection.write_all(b"zINSTREAM\0").await?;
+        while let Some(chunk) = chunks.next().await {
+            for piece in chunk?.chunks(MAX_CHUNK_SIZE) {
+                connection
+                    .write_all(&(piece.len() as u32).to_be_bytes())
+                    .await?;
+                connection.write_all(piece).await?;
+            }
+        }
+        // A zero length chunk marks the end of the stream
+        connection.write_all(&0u32.to_be_bytes()).await?;
+
+        let mut reply = Vec::new();
+        connection.read_to_end(&mut reply).await?;
+        parse_reply(&String::from_utf8_lossy(&reply))
+    }
+}
+
+/// Parses clamd's reply, e.g. `stream: OK` or `stream: Eicar-Signature FOUND`.
+fn parse_reply(reply: &str) -> Result<ScanVerdict, Box<dyn Error + Send + Sync>> {
+    let result = reply.trim_end_matches(['\0', '\n']);
+    let result = result.strip_prefix("stream: ").unwrap_or(result);
+    if result == "OK" {
+        Ok(ScanVerdict::Clean)
+    } else if let Some(signature) = result.strip_suffix(" FOUND") {
+        Ok(ScanVerdict::Infected(signature.to_string()))
+    } else {
+        Err(format!("Unexpected reply from clamd: {result}").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_clean_reply() {
+        assert_eq!(parse_reply("stream: OK\0").unwrap(), ScanVerdict::Clean);
+    }
+
+    #[test]
+    fn parses_infected_reply() {
+        assert_eq!(
+            parse_reply("stream: Win.Test.EICAR_HDB-1 FOUND\0").unwrap(),
+            ScanVerdict::Infected("Win.Test.EICAR_HDB-1".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_error_reply() {
+        assert!(parse_reply("INSTREAM size limit exceeded. ERROR\0").is_err());
+    }
+}
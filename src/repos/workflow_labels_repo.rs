@@ -0,0 +1,143 @@
+//! Repository module for managing internal workflow labels.
+//!
+//! Workflow labels such as "needs-translation" or "verify-source" help curators track
+//! work on accessions. Unlike Dublin Core subjects they are never exposed publicly.
+
+use ::entity::accession_workflow_label::ActiveModel as AccessionWorkflowLabelActiveModel;
+use ::entity::accession_workflow_label::Entity as AccessionWorkflowLabel;
+use ::entity::workflow_label::ActiveModel as WorkflowLabelActiveModel;
+use ::entity::workflow_label::Entity as WorkflowLabel;
+use ::entity::workflow_label::Model as WorkflowLabelModel;
+use async_trait::async_trait;
+use entity::{accession_workflow_label, workflow_label};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder,
+};
+
+/// Repository implementation for database operations on workflow labels.
+#[derive(Debug, Clone, Default)]
+pub struct DBWorkflowLabelsRepo {
+    pub db_session: DatabaseConnection,
+}
+
+/// Defines the interface for workflow label database operations.
+#[async_trait]
+pub trait WorkflowLabelsRepo: Send + Sync {
+    /// Creates a new workflow label.
+    ///
+    /// # Arguments
+    /// * `label` - The label text, e.g. "needs-translation"
+    async fn write_one(&self, label: String) -> Result<WorkflowLabelModel, DbErr>;
+
+    /// Lists all workflow labels ordered by label.
+    async fn list(&self) -> Result<Vec<WorkflowLabelModel>, DbErr>;
+
+    /// Deletes a workflow label and detaches it from all accessions.
+    ///
+    /// # Arguments
+    /// * `label_id` - The ID of the label to delete
+    async fn delete_one(&self, label_id: i32) -> Result<Option<()>, DbErr>;
+
+    /// Attaches a workflow label to an accession. Attaching an existing label is a no-op.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession to label
+    /// * `label_id` - The ID of the label to attach
+    async fn add_to_accession(&self, accession_id: i32, label_id: i32) -> Result<(), DbErr>;
+
+    /// Detaches a workflow label from an accession.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the labelled accession
+    /// * `label_id` - The ID of the label to detach
+    async fn remove_from_accession(
+        &self,
+        accession_id: i32,
+        label_id: i32,
+    ) -> Result<Option<()>, DbErr>;
+
+    /// Lists the workflow labels attached to an accession.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    async fn list_for_accession(&self, accession_id: i32)
+        -> Result<Vec<WorkflowLabelModel>, DbErr>;
+}
+
+#[async_trait]
+impl WorkflowLabelsRepo for DBWorkflowLabelsRepo {
+    async fn write_one(&self, label: String) -> Result<WorkflowLabelModel, DbErr> {
+        let workflow_label = WorkflowLabelActiveModel {
+            id: Default::default(),
+            label: ActiveValue::Set(label),
+        };
+        workflow_label.insert(&self.db_session).await
+    }
+
+    async fn list(&self) -> Result<Vec<WorkflowLabelModel>, DbErr> {
+        WorkflowLabel::find()
+            .order_by_asc(workflow_label::Column::Label)
+            .all(&self.db_session)
+            .await
+    }
+
+    async fn delete_one(&self, label_id: i32) -> Result<Option<()>, DbErr> {
+        let deletion = WorkflowLabel::delete_by_id(label_id)
+            .exec(&self.db_session)
+            .await?;
+        if deletion.rows_affected > 0 {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn add_to_accession(&self, accession_id: i32, label_id: i32) -> Result<(), DbErr> {
+        let link = AccessionWorkflowLabelActiveModel {
+            accession_id: ActiveValue::Set(accession_id),
+            label_id: ActiveValue::Set(label_id),
+        };
+        AccessionWorkflowLabel::insert(link)
+            .on_conflict(
+                OnConflict::columns([
+                    accession_workflow_label::Column::AccessionId,
+                    accession_workflow_label::Column::LabelId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .do_nothing()
+            .exec(&self.db_session)
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_from_accession(
+        &self,
+        accession_id: i32,
+        label_id: i32,
+    ) -> Result<Option<()>, DbErr> {
+        let deletion = AccessionWorkflowLabel::delete_by_id((accession_id, label_id))
+            .exec(&self.db_session)
+            .await?;
+        if deletion.rows_affected > 0 {
+            Ok(Some(()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn list_for_accession(
+        &self,
+        accession_id: i32,
+    ) -> Result<Vec<WorkflowLabelModel>, DbErr> {
+        WorkflowLabel::find()
+            .inner_join(AccessionWorkflowLabel)
+            .filter(accession_workflow_label::Column::AccessionId.eq(accession_id))
+            .order_by_asc(workflow_label::Column::Label)
+            .all(&self.db_session)
+            .await
+    }
+}
@@ -0,0 +1,267 @@
+//! Structured request logs for incident forensics.
+//!
+//! [`log_requests`] writes one JSON line per request with the method, route, status,
+//! latency, the client it came from as `IP_PRIVACY` allows, see [`ClientIp`], and, once
+//! [`AuthenticatedUser`](crate::models::auth::AuthenticatedUser) has run, who made it. Routes listed in [`RequestLogConfig::body_paths`] also get their
+//! request and response bodies logged, with emails and tokens redacted first.
+//!
+//! Paths are logged as the route they matched, e.g. `/api/v1/accessions/{id}`, so query
+//! strings such as magic link tokens never end up in the logs.
+
+use crate::client_ip::ClientIp;
+use crate::models::auth::AuthenticatedUser;
+use axum::body::{to_bytes, Body, Bytes, HttpBody};
+use axum::extract::{MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use http::header::{CONTENT_ENCODING, CONTENT_TYPE};
+use http::HeaderMap;
+use serde_json::{json, Value};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use tracing::info;
+
+/// Largest body that gets logged; bigger ones are left out of the log line
+const MAX_LOGGED_BODY: u64 = 16 * 1024;
+
+/// Stands in for anything redacted from a logged body or the config summary
+pub const REDACTED: &str = "[redacted]";
+
+/// JSON keys whose values are always redacted, matched case insensitively
+const SENSITIVE_KEYS: &[&str] = &[
+    "email",
+    "token",
+    "password",
+    "secret",
+    "api_key",
+    "api-key",
+    "apikey",
+    "jwt",
+    "cookie",
+    "authorization",
+];
+
+/// Which requests get logged and how much of them
+#[derive(Debug, Clone, Default)]
+pub struct RequestLogConfig {
+    pub enabled: bool,
+    /// Routes whose bodies are logged, as prefixes of the route path, e.g. `/api/v1/accessions`
+    pub body_paths: Vec<String>,
+}
+
+impl RequestLogConfig {
+    fn logs_bodies(&self, path: &str) -> bool {
+        self.body_paths
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+    }
+}
+
+/// Who made a request, filled in by the `AuthenticatedUser` extractor once it has
+/// authenticated them, along with how and the admin impersonating them if there is one
+#[derive(Debug, Clone, Default)]
+pub struct RequestUser(Arc<OnceLock<AuthenticatedUser>>);
+
+impl RequestUser {
+    pub fn set(&self, user: &AuthenticatedUser) {
+        let _ = self.0.set(user.clone());
+    }
+}
+
+/// Replaces anything that looks like an email address in `text`.
+pub fn redact_emails(text: &str) -> String {
+    let is_email_char =
+        |c: char| c.is_alphanumeric() || matches!(c, '.' | '_' | '%' | '+' | '-' | '@');
+    let mut redacted = String::with_capacity(text.len());
+    let mut word = String::new();
+    let flush = |word: &mut String, redacted: &mut String| {
+        let is_email = word
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'));
+        redacted.push_str(if is_email { REDACTED } else { word.as_str() });
+        word.clear();
+    };
+    for c in text.chars() {
+        if is_email_char(c) {
+            word.push(c);
+        } else {
+            flush(&mut word, &mut redacted);
+            redacted.push(c);
+        }
+    }
+    flush(&mut word, &mut redacted);
+    redacted
+}
+
+/// Redacts sensitive keys and emails anywhere in a JSON value.
+pub fn redact_json(value: &mut Value) {
+    match value {
+        Value::String(text) => *text = redact_emails(text),
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                let key = key.to_lowercase();
+                if SENSITIVE_KEYS
+                    .iter()
+                    .any(|sensitive| key.contains(sensitive))
+                {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(field);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Turns a body into something safe to log, or `None` if it isn't text.
+fn sanitize_body(headers: &HeaderMap, body: &[u8]) -> Option<Value> {
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if body.is_empty() {
+        return None;
+    }
+    if content_type.contains("json") {
+        let mut json: Value = serde_json::from_slice(body).ok()?;
+        redact_json(&mut json);
+        Some(json)
+    } else if content_type.starts_with("text/")
+        || content_type.starts_with("application/x-www-form-urlencoded")
+    {
+        let text = std::str::from_utf8(body).ok()?;
+        Some(Value::String(redact_emails(text)))
+    } else {
+        None
+    }
+}
+
+/// Buffers `body` if it is small and uncompressed enough to log.
+///
+/// Returns the body to pass on along with the bytes to log, if any.
+async fn capture_body(headers: &HeaderMap, body: Body) -> (Body, Option<Bytes>) {
+    let small = body
+        .size_hint()
+        .upper()
+        .is_some_and(|size| size <= MAX_LOGGED_BODY);
+    if !small || headers.contains_key(CONTENT_ENCODING) {
+        return (body, None);
+    }
+    match to_bytes(body, MAX_LOGGED_BODY as usize).await {
+        Ok(bytes) => (Body::from(bytes.clone()), Some(bytes)),
+        Err(_) => (Body::empty(), None),
+    }
+}
+
+/// Middleware writing a structured log line per request, see the module docs.
+pub async fn log_requests(
+    State(config): State<Arc<RequestLogConfig>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !config.enabled {
+        return next.run(request).await;
+    }
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let client = request
+        .extensions()
+        .get::<ClientIp>()
+        .map(|client_ip| client_ip.key.to_string());
+    let logs_bodies = config.logs_bodies(&path);
+    let user = RequestUser::default();
+
+    let (mut parts, body) = request.into_parts();
+    parts.extensions.insert(user.clone());
+    let (body, request_body) = if logs_bodies {
+        capture_body(&parts.headers, body).await
+    } else {
+        (body, None)
+    };
+    let request_body = request_body.and_then(|bytes| sanitize_body(&parts.headers, &bytes));
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    let (parts, body) = response.into_parts();
+    let (body, response_body) = if logs_bodies {
+        capture_body(&parts.headers, body).await
+    } else {
+        (body, None)
+    };
+    let response_body = response_body.and_then(|bytes| sanitize_body(&parts.headers, &bytes));
+    let (user_id, role, impersonator, auth_method) = match user.0.get() {
+        Some(user) => (
+            Some(user.user_id.clone()),
+            Some(format!("{:?}", user.role)),
+            user.impersonator.clone(),
+            Some(user.auth_method.as_str()),
+        ),
+        None => (None, None, None, None),
+    };
+    let entry = json!({
+        "method": method,
+        "path": path,
+        "status": parts.status.as_u16(),
+        "latency_ms": started.elapsed().as_millis() as u64,
+        "client": client,
+        "user_id": user_id,
+        "role": role,
+        "impersonator": impersonator,
+        "auth_method": auth_method,
+        "request_body": request_body,
+        "response_body": response_body,
+    });
+    info!(target: "request_log", "{entry}");
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn redacts_emails_in_text() {
+        assert_eq!(
+            redact_emails("Login link sent to someone@example.com, check your inbox"),
+            "Login link sent to [redacted], check your inbox"
+        );
+        assert_eq!(redact_emails("@handle and a.b"), "@handle and a.b");
+    }
+
+    #[test]
+    fn redacts_sensitive_keys_and_nested_emails() {
+        let mut body = json!({
+            "email": "someone@example.com",
+            "X-Api-Key": "abc",
+            "metadata": {"title": "Sent by someone@example.com", "session_token": "xyz"},
+            "subjects": [1, 2]
+        });
+        redact_json(&mut body);
+        assert_eq!(
+            body,
+            json!({
+                "email": "[redacted]",
+                "X-Api-Key": "[redacted]",
+                "metadata": {"title": "Sent by [redacted]", "session_token": "[redacted]"},
+                "subjects": [1, 2]
+            })
+        );
+    }
+
+    #[test]
+    fn logs_bodies_for_configured_prefixes() {
+        let config = RequestLogConfig {
+            enabled: true,
+            body_paths: vec!["/api/v1/accessions".to_string()],
+        };
+        assert!(config.logs_bodies("/api/v1/accessions/{id}"));
+        assert!(!config.logs_bodies("/api/v1/auth"));
+    }
+}
@@ -0,0 +1,244 @@
+//! Routes for relating accessions to each other.
+//! Relations link connected captures, such as the posts of a testimony thread or an article
+//! and its follow up, and are shown alongside an accession's details so researchers can
+//! move between them.
+//!
+//! This module provides HTTP endpoints for listing, adding and removing an accession's
+//! relations. It uses in-memory repositories for testing to avoid I/O operations.
+
+use crate::app_factory::AppState;
+use crate::auth::validate_at_least_researcher;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::request::CreateAccessionRelationRequest;
+use crate::models::response::{AccessionRelationResponse, ListAccessionRelationsResponse};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+
+/// Creates routes for accession relation endpoints under `/accessions/{accession_id}/relations`.
+pub fn get_accession_relations_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/accessions/{accession_id}/relations",
+            get(list_accession_relations),
+        )
+        .route(
+            "/accessions/{accession_id}/relations",
+            post(create_accession_relation),
+        )
+        .route(
+            "/accessions/{accession_id}/relations/{relation_id}",
+            delete(delete_accession_relation),
+        )
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/{accession_id}/relations",
+    tag = "Accession relations",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
+    responses(
+        (status = 200, description = "OK", body = ListAccessionRelationsResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_accession_relations(
+    State(state): State<AppState>,
+    Path(accession_id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .accessions_service
+        .list_relations(accession_id, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accessions/{accession_id}/relations",
+    tag = "Accession relations",
+    params(
+        ("accession_id" = i32, Path, description = "ID of the accession the relation starts from")
+    ),
+    request_body = CreateAccessionRelationRequest,
+    responses(
+        (status = 201, description = "Created", body = AccessionRelationResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn create_accession_relation(
+    State(state): State<AppState>,
+    Path(accession_id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<CreateAccessionRelationRequest>,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .accessions_service
+        .create_relation(accession_id, payload, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/accessions/{accession_id}/relations/{relation_id}",
+    tag = "Accession relations",
+    params(
+        ("accession_id" = i32, Path, description = "ID of either related accession"),
+        ("relation_id" = i32, Path, description = "Relation ID")
+    ),
+    responses(
+        (status = 200, description = "Relation removed"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn delete_accession_relation(
+    State(state): State<AppState>,
+    Path((accession_id, relation_id)): Path<(i32, i32)>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .accessions_service
+        .delete_relation(accession_id, relation_id, authenticated_user)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::common::RelationDirection;
+    use crate::models::response::{AccessionRelationResponse, ListAccessionRelationsResponse};
+    use crate::test_tools::{build_test_app, get_mock_jwt, mock_relations_response};
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use entity::sea_orm_active_enums::AccessionRelationKind;
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    fn create_relation_request(related_accession_id: i32) -> Request<Body> {
+        Request::builder()
+            .method(http::Method::POST)
+            .uri("/api/v1/accessions/1/relations")
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+            .body(Body::from(
+                serde_json::to_vec(&json!({
+                    "kind": "Supersedes",
+                    "accession_id": related_accession_id
+                }))
+                .unwrap(),
+            ))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn list_accession_relations_includes_private_ones() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1/relations")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListAccessionRelationsResponse = serde_json::from_slice(&body).unwrap();
+        let mut expected = mock_relations_response();
+        expected.push(AccessionRelationResponse {
+            id: 2,
+            kind: AccessionRelationKind::References,
+            direction: RelationDirection::Incoming,
+            accession_id: 3,
+            title_en: Some("English Title".to_string()),
+            title_ar: Some("Arabic Title".to_string()),
+        });
+        assert_eq!(actual.items, expected);
+    }
+
+    #[tokio::test]
+    async fn list_accession_relations_no_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1/relations")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn create_accession_relation() {
+        let app = build_test_app();
+        let response = app.oneshot(create_relation_request(2)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: AccessionRelationResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.id, 3);
+        assert_eq!(actual.kind, AccessionRelationKind::Supersedes);
+        assert_eq!(actual.direction, RelationDirection::Outgoing);
+        assert_eq!(actual.title_en, Some("English Title".to_string()));
+    }
+
+    #[tokio::test]
+    async fn create_accession_relation_to_itself() {
+        let app = build_test_app();
+        let response = app.oneshot(create_relation_request(1)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn delete_accession_relation() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri("/api/v1/accessions/1/relations/1")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
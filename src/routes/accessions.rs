@@ -5,15 +5,32 @@
 
 use crate::app_factory::AppState;
 use crate::auth::{validate_at_least_contributor, validate_at_least_researcher};
+use crate::citation_export::CslItem;
+use crate::client_country::ClientCountry;
+use crate::i18n::PreferredLanguage;
+use crate::iiif::Manifest;
 use crate::models::auth::AuthenticatedUser;
+use crate::models::common::{CaptureMode, ListingVisibility, MetadataLanguage};
+use crate::models::error::{ApiError, ErrorResponse};
 use crate::models::request::{
-    AccessionPagination, AccessionPaginationWithPrivate, CreateAccessionRawMultipartRequest,
-    CreateAccessionRequest, UpdateAccessionRequest,
+    AccessionPagination, AccessionStatsQuery, AccessionTimelineQuery, BadCapturesPagination,
+    BulkVisibilityRequest, CreateAccessionCrawlQuery, CreateAccessionRawMultipartRequest,
+    CreateAccessionRequest, CreateAccessionRequestRaw, ExportAccessionsQuery,
+    PrivateAccessionPagination, TopAccessionsQuery, UpdateAccessionRequest,
+    UpdatePublicationStateRequest,
+};
+use crate::models::response::{
+    AccessionCrawlStatusResponse, AccessionStatsResponse, AccessionTimelineResponse,
+    BulkVisibilityResponse, CreateAccessionCrawlResponse, DoiResponse, DryRunAccessionResponse,
+    DuplicateAccessionsResponse, GetOneAccessionResponse, GetOnePublicAccessionResponse,
+    ListAccessionPagesResponse, ListAccessionsResponse, ListPublicAccessionsResponse,
+    MessageResponse, ProvenanceResponse, PublicAccessionsWithMetadataResponse,
+    ReplaceAccessionFileResponse, SuggestedSubjectsResponse, TopAccessionsResponse,
 };
-use crate::models::response::{GetOneAccessionResponse, ListAccessionsResponse};
 use ::entity::sea_orm_active_enums::Role;
-use axum::extract::{DefaultBodyLimit, Multipart, Path, State};
-use axum::http::StatusCode;
+use axum::body::Body;
+use axum::extract::{Multipart, Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{delete, get, post, put};
 use axum::{Json, Router};
@@ -22,21 +39,75 @@ use tracing::{error, info};
 use validator::Validate;
 
 /// Creates routes for accession-related endpoints under `/accessions`.
-pub fn get_accessions_routes(max_file_upload_size: usize) -> Router<AppState> {
+///
+/// The endpoints streaming uploaded files are left out, see [`get_accession_upload_routes`].
+pub fn get_accessions_routes() -> Router<AppState> {
     Router::new().nest(
         "/accessions",
         Router::new()
             .route("/", get(list_accessions))
             .route("/private", get(list_accessions_private))
+            .route("/bad-captures", get(list_bad_captures))
+            .route("/stream", get(stream_published_accessions))
+            .route("/export", get(export_accessions))
+            .route("/timeline", get(get_accessions_timeline))
             .route("/crawl", post(create_accession_crawl))
-            .route("/raw", post(create_accession_raw))
-            // Increase limit; default is 2MB; this only applies to raw upload endpoint
-            // see https://docs.rs/axum/latest/axum/extract/struct.DefaultBodyLimit.html
-            .layer(DefaultBodyLimit::max(max_file_upload_size))
+            .route("/from-file", post(create_accession_from_file))
             .route("/{accession_id}", get(get_one_accession))
+            .route("/stats", get(list_top_accessions))
+            .route("/{accession_id}/pages", get(list_accession_pages))
+            .route(
+                "/{accession_id}/iiif/manifest.json",
+                get(get_accession_iiif_manifest),
+            )
+            .route("/{accession_id}/stats", get(get_accession_stats))
+            .route("/{accession_id}/status", get(get_accession_crawl_status))
+            .route("/{accession_id}/provenance", get(get_accession_provenance))
+            .route("/{accession_id}/duplicates", get(get_accession_duplicates))
             .route("/private/{accession_id}", get(get_one_private_accession))
+            .route(
+                "/private/{accession_id}/provenance",
+                get(get_private_accession_provenance),
+            )
+            .route("/private/files/{token}", get(stream_private_file))
             .route("/{accession_id}", delete(delete_accession))
-            .route("/{accession_id}", put(update_accession)),
+            .route("/{accession_id}", put(update_accession))
+            .route(
+                "/{accession_id}/publication-state",
+                put(update_accession_publication_state),
+            )
+            .route(
+                "/{accession_id}/translate-metadata",
+                post(translate_accession_metadata),
+            )
+            .route("/{accession_id}/doi", post(mint_accession_doi))
+            .route("/{accession_id}/bad-capture", delete(clear_bad_capture))
+            .route(
+                "/{accession_id}/suggested-subjects",
+                get(get_suggested_subjects),
+            ),
+    )
+}
+
+/// Creates the accession endpoints that stream uploaded files, under `/accessions`.
+///
+/// These get the upload route class's body limit and timeout rather than the JSON ones.
+pub fn get_accession_upload_routes() -> Router<AppState> {
+    Router::new().nest(
+        "/accessions",
+        Router::new()
+            .route("/raw", post(create_accession_raw))
+            .route("/{accession_id}/file", put(replace_accession_file)),
+    )
+}
+
+/// Creates the accession endpoints that act on many accessions at once, under `/accessions`.
+///
+/// These get the bulk route class's body limit and timeout, and accept compressed bodies.
+pub fn get_accession_bulk_routes() -> Router<AppState> {
+    Router::new().nest(
+        "/accessions",
+        Router::new().route("/bulk-visibility", post(bulk_update_visibility)),
     )
 }
 
@@ -52,7 +123,8 @@ pub fn get_accessions_routes(max_file_upload_size: usize) -> Router<AppState> {
     responses(
         (status = 201, description = "Accession created!"),
         (status = 400, description = "Bad request"),
-        (status = 403, description = "Forbidden")
+        (status = 403, description = "Forbidden"),
+        (status = 415, description = "File type does not match metadata format")
     ),
     security(
         ("jwt_cookie_auth" = []),
@@ -68,24 +140,42 @@ async fn create_accession_raw(
         return (StatusCode::FORBIDDEN, "Must have at least contributor role").into_response();
     }
     info!("Received raw accession creation request via multipart/form-data");
-    let create_accession_raw_request = match state
+    let (create_accession_raw_request, scan_status, metadata_scrubbed, sha256) = match state
         .accessions_service
         .clone()
-        .extract_accession_from_multipart_form(multipart, state.subjects_service)
+        .extract_accession_from_multipart_form(
+            multipart,
+            state.subjects_service,
+            authenticated_user.organization_id,
+        )
         .await
     {
         Ok(data) => data,
         Err(response) => return response,
     };
 
+    let s3_filename = create_accession_raw_request.s3_filename.clone();
+    let metadata_format = create_accession_raw_request.metadata_format.clone();
     match state
         .accessions_service
         .clone()
-        .write_one_raw(create_accession_raw_request)
+        .write_one_raw(
+            create_accession_raw_request,
+            scan_status,
+            metadata_scrubbed,
+            sha256,
+            authenticated_user.organization_id,
+        )
         .await
     {
         Ok(id) => {
             info!("Raw accession created with id: {}", id);
+            tokio::spawn(async move {
+                state
+                    .accessions_service
+                    .create_media_derivatives(id, &s3_filename, &metadata_format)
+                    .await;
+            });
             (
                 StatusCode::CREATED,
                 format!("Accession created with id: {id}"),
@@ -100,35 +190,78 @@ async fn create_accession_raw(
 }
 
 #[utoipa::path(
-    post,
-    path = "/api/v1/accessions/crawl",
+    put,
+    path = "/api/v1/accessions/{accession_id}/file",
     tag = "Accessions",
-    request_body = CreateAccessionRequest,
+    request_body(
+        content = Vec<u8>,
+        content_type = "application/octet-stream",
+        description = "The replacement file, in the accession's format"
+    ),
     responses(
-        (status = 201, description = "Started browsertrix crawl task!"),
+        (status = 200, description = "File replaced, the previous one was quarantined", body = ReplaceAccessionFileResponse),
         (status = 400, description = "Bad request"),
-        (status = 403, description = "Forbidden")
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "The accession's file changed while the replacement was uploaded"),
+        (status = 415, description = "File type does not match the accession's format")
     ),
     security(
         ("jwt_cookie_auth" = []),
         ("api_key_auth" = [])
     )
 )]
-async fn create_accession_crawl(
+async fn replace_accession_file(
     State(state): State<AppState>,
+    Path(id): Path<i32>,
     authenticated_user: AuthenticatedUser,
-    Json(payload): Json<CreateAccessionRequest>,
+    body: Body,
+) -> Response {
+    // the replaced file is what researchers cite, so only admins can swap it
+    if authenticated_user.role != Role::Admin {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state
+        .accessions_service
+        .replace_file(id, body, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accessions/from-file",
+    tag = "Accessions",
+    request_body = CreateAccessionRequestRaw,
+    responses(
+        (status = 201, description = "Accession created!"),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 415, description = "File type does not match metadata format")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn create_accession_from_file(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<CreateAccessionRequestRaw>,
 ) -> Response {
     if !validate_at_least_contributor(&authenticated_user.role) {
         return (StatusCode::FORBIDDEN, "Must have at least contributor role").into_response();
     }
     if let Err(err) = payload.validate() {
-        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+        return ApiError::validation(err).into_response();
     }
     let subjects_exist = state
         .subjects_service
         .clone()
-        .verify_subjects_exist(payload.metadata_subjects.clone(), payload.metadata_language)
+        .verify_subjects_exist(
+            payload.metadata_subjects.clone(),
+            payload.metadata_language,
+            authenticated_user.organization_id,
+        )
         .await;
     match subjects_exist {
         Err(err) => {
@@ -140,13 +273,124 @@ async fn create_accession_crawl(
             }
         }
     };
-    tokio::spawn(async move {
-        state
-            .accessions_service
-            .create_one(payload, authenticated_user.user_id)
+    state
+        .accessions_service
+        .create_from_file(payload, authenticated_user.organization_id)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accessions/crawl",
+    tag = "Accessions",
+    params(CreateAccessionCrawlQuery),
+    request_body = CreateAccessionRequest,
+    responses(
+        (status = 200, description = "Dry run of what the crawl would do", body = DryRunAccessionResponse),
+        (status = 201, description = "Accession created and crawl started", body = CreateAccessionCrawlResponse),
+        (status = 400, description = "Bad request, the URL is on the crawl blocklist, or the requested crawler or video capture is not configured", body = ErrorResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn create_accession_crawl(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Query(query): Query<CreateAccessionCrawlQuery>,
+    Json(payload): Json<CreateAccessionRequest>,
+) -> Response {
+    if !validate_at_least_contributor(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least contributor role").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return ApiError::validation(err).into_response();
+    }
+    for language in [MetadataLanguage::English, MetadataLanguage::Arabic] {
+        let Some(metadata) = payload.metadata(language) else {
+            continue;
+        };
+        let subjects_exist = state
+            .subjects_service
+            .clone()
+            .verify_subjects_exist(
+                metadata.subjects.clone(),
+                language,
+                authenticated_user.organization_id,
+            )
             .await;
-    });
-    (StatusCode::CREATED, "Started browsertrix crawl task!").into_response()
+        match subjects_exist {
+            Err(err) => {
+                return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+            }
+            Ok(flag) => {
+                if !flag {
+                    return (StatusCode::BAD_REQUEST, "Subjects do not exist").into_response();
+                }
+            }
+        };
+    }
+    if state
+        .accessions_service
+        .crawlers
+        .get(payload.crawler)
+        .is_none()
+    {
+        return (StatusCode::BAD_REQUEST, "Crawler is not configured").into_response();
+    }
+    if payload.capture_mode == CaptureMode::Video
+        && state.accessions_service.video_downloader_repo.is_none()
+    {
+        return (StatusCode::BAD_REQUEST, "Video capture is not configured").into_response();
+    }
+    if let Some(rejection) = state
+        .crawl_blocklist_service
+        .clone()
+        .reject_blocked_url(&payload.url)
+        .await
+    {
+        return rejection;
+    }
+    if query.dry_run {
+        return state.accessions_service.dry_run_create(payload).await;
+    }
+    state
+        .accessions_service
+        .start_crawl(
+            payload,
+            authenticated_user.user_id,
+            authenticated_user.organization_id,
+        )
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/{accession_id}/status",
+    tag = "Accessions",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
+    responses(
+        (status = 200, description = "OK", body = AccessionCrawlStatusResponse),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn get_accession_crawl_status(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    state
+        .accessions_service
+        .get_crawl_status(id, authenticated_user)
+        .await
 }
 
 #[utoipa::path(
@@ -157,12 +401,55 @@ async fn create_accession_crawl(
         ("accession_id" = i32, Path, description = "Accession ID")
     ),
     responses(
-        (status = 200, description = "OK", body = GetOneAccessionResponse),
+        (status = 200, description = "OK", body = GetOnePublicAccessionResponse),
+        (status = 404, description = "Not found")
+    )
+)]
+async fn get_one_accession(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    ClientCountry(country): ClientCountry,
+    PreferredLanguage(language): PreferredLanguage,
+) -> Response {
+    state
+        .accessions_service
+        .get_one_public(id, country, language)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/{accession_id}/pages",
+    tag = "Accessions",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
+    responses(
+        (status = 200, description = "OK", body = ListAccessionPagesResponse),
         (status = 404, description = "Not found")
     )
 )]
-async fn get_one_accession(State(state): State<AppState>, Path(id): Path<i32>) -> Response {
-    state.accessions_service.get_one(id, false).await
+async fn list_accession_pages(State(state): State<AppState>, Path(id): Path<i32>) -> Response {
+    state.accessions_service.list_pages(id).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/{accession_id}/iiif/manifest.json",
+    tag = "Accessions",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
+    responses(
+        (status = 200, description = "IIIF Presentation v3 manifest", body = Manifest, content_type = "application/ld+json"),
+        (status = 404, description = "Not found, or not an image")
+    )
+)]
+async fn get_accession_iiif_manifest(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Response {
+    state.accessions_service.get_iiif_manifest(id).await
 }
 
 #[utoipa::path(
@@ -182,382 +469,2247 @@ async fn get_one_accession(State(state): State<AppState>, Path(id): Path<i32>) -
 async fn get_one_private_accession(
     State(state): State<AppState>,
     Path(id): Path<i32>,
+    ClientCountry(country): ClientCountry,
+    PreferredLanguage(language): PreferredLanguage,
     authenticated_user: AuthenticatedUser,
 ) -> Response {
     if !validate_at_least_researcher(&authenticated_user.role) {
         return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
     }
-    state.accessions_service.get_one(id, true).await
+    state
+        .accessions_service
+        .get_one(id, authenticated_user, country, language)
+        .await
 }
 
 #[utoipa::path(
     get,
-    path = "/api/v1/accessions",
+    path = "/api/v1/accessions/{accession_id}/provenance",
     tag = "Accessions",
     params(
-        AccessionPagination
+        ("accession_id" = i32, Path, description = "Accession ID")
     ),
     responses(
-        (status = 200, description = "OK", body = ListAccessionsResponse),
-        (status = 400, description = "Bad request")
+        (status = 200, description = "OK", body = ProvenanceResponse),
+        (status = 404, description = "Not found, or no provenance recorded")
     )
 )]
-async fn list_accessions(
-    State(state): State<AppState>,
-    pagination: Query<AccessionPagination>,
-) -> Response {
-    if let Err(err) = pagination.0.validate() {
-        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
-    }
-    let list_params = AccessionPaginationWithPrivate {
-        page: pagination.0.page,
-        per_page: pagination.0.per_page,
-        lang: pagination.0.lang,
-        metadata_subjects: pagination.0.metadata_subjects,
-        metadata_subjects_inclusive_filter: pagination.0.metadata_subjects_inclusive_filter,
-        query_term: pagination.0.query_term,
-        url_filter: pagination.0.url_filter,
-        date_from: pagination.0.date_from,
-        date_to: pagination.0.date_to,
-        is_private: false,
-    };
-    state.accessions_service.list(list_params).await
+async fn get_accession_provenance(State(state): State<AppState>, Path(id): Path<i32>) -> Response {
+    state.accessions_service.get_provenance(id, None).await
 }
 
 #[utoipa::path(
     get,
-    path = "/api/v1/accessions/private",
+    path = "/api/v1/accessions/private/{accession_id}/provenance",
     tag = "Accessions",
     params(
-        AccessionPaginationWithPrivate
+        ("accession_id" = i32, Path, description = "Accession ID")
     ),
     responses(
-        (status = 200, description = "OK", body = ListAccessionsResponse),
-        (status = 400, description = "Bad request"),
-        (status = 403, description = "Forbidden")
+        (status = 200, description = "OK", body = ProvenanceResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found, or no provenance recorded")
     ),
     security(
         ("jwt_cookie_auth" = []),
         ("api_key_auth" = [])
     )
 )]
-async fn list_accessions_private(
+async fn get_private_accession_provenance(
     State(state): State<AppState>,
-    pagination: Query<AccessionPaginationWithPrivate>,
+    Path(id): Path<i32>,
     authenticated_user: AuthenticatedUser,
 ) -> Response {
     if !validate_at_least_researcher(&authenticated_user.role) {
         return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
     }
-    if let Err(err) = pagination.0.validate() {
-        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
-    }
-
-    state.accessions_service.list(pagination.0).await
+    state
+        .accessions_service
+        .get_provenance(id, Some(authenticated_user))
+        .await
 }
 
 #[utoipa::path(
-    delete,
-    path = "/api/v1/accessions/{accession_id}",
+    get,
+    path = "/api/v1/accessions/{accession_id}/duplicates",
     tag = "Accessions",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
     responses(
-        (status = 200, description = "Accession deleted"),
+        (status = 200, description = "OK", body = DuplicateAccessionsResponse),
         (status = 403, description = "Forbidden"),
-        (status = 404, description = "Not found")
+        (status = 404, description = "Not found, or the capture has no content digest")
     ),
     security(
         ("jwt_cookie_auth" = []),
         ("api_key_auth" = [])
     )
 )]
-async fn delete_accession(
+async fn get_accession_duplicates(
     State(state): State<AppState>,
     Path(id): Path<i32>,
     authenticated_user: AuthenticatedUser,
 ) -> Response {
-    if authenticated_user.role != Role::Admin {
-        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
     }
+    state
+        .accessions_service
+        .get_duplicates(id, authenticated_user)
+        .await
+}
 
-    state.accessions_service.delete_one(id).await
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/private/files/{token}",
+    tag = "Accessions",
+    params(
+        ("token" = String, Path, description = "Token from a private accession's file link"),
+        ("Range" = Option<String>, Header, description = "Byte range of the file to fetch")
+    ),
+    responses(
+        (status = 200, description = "The file"),
+        (status = 206, description = "Part of the file"),
+        (status = 400, description = "Invalid file link"),
+        (status = 403, description = "File link was issued to someone else"),
+        (status = 410, description = "File link expired")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn stream_private_file(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    // the link was checked against the user's role when it was issued
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|range| range.to_str().ok())
+        .map(str::to_string);
+    state
+        .accessions_service
+        .stream_private_file(token, range, authenticated_user)
+        .await
 }
 
 #[utoipa::path(
-    put,
-    path = "/api/v1/accessions/{accession_id}",
+    get,
+    path = "/api/v1/accessions/{accession_id}/stats",
     tag = "Accessions",
-    request_body = UpdateAccessionRequest,
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID"),
+        AccessionStatsQuery
+    ),
     responses(
-        (status = 200, description = "OK", body = GetOneAccessionResponse),
+        (status = 200, description = "OK", body = AccessionStatsResponse),
         (status = 400, description = "Bad request"),
-        (status = 403, description = "Forbidden"),
-        (status = 404, description = "Not found")
+        (status = 403, description = "Forbidden")
     ),
     security(
         ("jwt_cookie_auth" = []),
         ("api_key_auth" = [])
     )
 )]
-async fn update_accession(
+async fn get_accession_stats(
     State(state): State<AppState>,
     Path(id): Path<i32>,
+    query: Query<AccessionStatsQuery>,
     authenticated_user: AuthenticatedUser,
-    Json(payload): Json<UpdateAccessionRequest>,
 ) -> Response {
     if !validate_at_least_researcher(&authenticated_user.role) {
         return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
     }
-    let subjects_exist = state
-        .subjects_service
-        .clone()
-        .verify_subjects_exist(payload.metadata_subjects.clone(), payload.metadata_language)
-        .await;
-    match subjects_exist {
-        Err(err) => {
-            return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
-        }
-        Ok(flag) => {
-            if !flag {
-                return (StatusCode::BAD_REQUEST, "Subjects do not exist").into_response();
-            }
-        }
-    };
-    state.accessions_service.update_one(id, payload).await
+    if let Err(err) = query.0.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state.accessions_service.get_stats(id, query.0.days).await
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::models::common::MetadataLanguage;
-    use crate::models::request::CreateAccessionRequest;
-    use crate::models::response::{GetOneAccessionResponse, ListAccessionsResponse};
-    use crate::test_tools::{
-        build_test_accessions_service, build_test_app, get_mock_jwt,
-        mock_one_accession_with_metadata, mock_paginated_ar, mock_paginated_en,
-    };
-    use axum::{
-        body::Body,
-        http::{Request, StatusCode},
-    };
-    use bytes::Bytes;
-    use entity::sea_orm_active_enums::DublinMetadataFormat;
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/stats",
+    tag = "Accessions",
+    params(
+        TopAccessionsQuery
+    ),
+    responses(
+        (status = 200, description = "OK", body = TopAccessionsResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_top_accessions(
+    State(state): State<AppState>,
+    query: Query<TopAccessionsQuery>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    if let Err(err) = query.0.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state.accessions_service.list_top(query.0).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/stream",
+    tag = "Accessions",
+    responses(
+        (status = 200, description = "Stream of `accession` events, one per accession as it becomes public", body = PublicAccessionsWithMetadataResponse, content_type = "text/event-stream")
+    )
+)]
+async fn stream_published_accessions(State(state): State<AppState>) -> Response {
+    state.accessions_service.stream_published()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions",
+    tag = "Accessions",
+    params(
+        AccessionPagination
+    ),
+    responses(
+        (status = 200, description = "OK", body = ListPublicAccessionsResponse),
+        (status = 400, description = "Bad request")
+    )
+)]
+async fn list_accessions(
+    State(state): State<AppState>,
+    pagination: Query<AccessionPagination>,
+    PreferredLanguage(language): PreferredLanguage,
+) -> Response {
+    if let Err(err) = pagination.0.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state
+        .accessions_service
+        .list(pagination.0.into(), language, ListingVisibility::Public)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/export",
+    tag = "Accessions",
+    params(
+        ExportAccessionsQuery,
+        AccessionPagination
+    ),
+    responses(
+        (status = 200, description = "A page of public accessions as RIS references", body = String, content_type = "application/x-research-info-systems"),
+        (status = 200, description = "A page of public accessions as CSL-JSON references", body = [CslItem], content_type = "application/vnd.citationstyles.csl+json"),
+        (status = 400, description = "Bad request")
+    )
+)]
+async fn export_accessions(
+    State(state): State<AppState>,
+    export: Query<ExportAccessionsQuery>,
+    pagination: Query<AccessionPagination>,
+) -> Response {
+    if let Err(err) = pagination.0.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state
+        .accessions_service
+        .export_citations(pagination.0.into(), export.0.format)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/timeline",
+    tag = "Accessions",
+    params(
+        AccessionTimelineQuery,
+        AccessionPagination
+    ),
+    responses(
+        (status = 200, description = "Public accessions counted by date", body = AccessionTimelineResponse),
+        (status = 400, description = "Bad request")
+    )
+)]
+async fn get_accessions_timeline(
+    State(state): State<AppState>,
+    timeline: Query<AccessionTimelineQuery>,
+    pagination: Query<AccessionPagination>,
+) -> Response {
+    if let Err(err) = pagination.0.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state
+        .accessions_service
+        .timeline(pagination.0.into(), timeline.0.interval)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/private",
+    tag = "Accessions",
+    params(
+        PrivateAccessionPagination
+    ),
+    responses(
+        (status = 200, description = "OK", body = ListAccessionsResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_accessions_private(
+    State(state): State<AppState>,
+    pagination: Query<PrivateAccessionPagination>,
+    PreferredLanguage(language): PreferredLanguage,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    if let Err(err) = pagination.0.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    let params = pagination.0.into_list_params(&authenticated_user);
+    state
+        .accessions_service
+        .list(params, language, ListingVisibility::Authenticated)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/bad-captures",
+    tag = "Accessions",
+    params(
+        BadCapturesPagination
+    ),
+    responses(
+        (status = 200, description = "OK", body = ListAccessionsResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_bad_captures(
+    State(state): State<AppState>,
+    pagination: Query<BadCapturesPagination>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    if let Err(err) = pagination.0.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state
+        .accessions_service
+        .list_bad_captures(pagination.0, authenticated_user.organization_scope())
+        .await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/accessions/{accession_id}/bad-capture",
+    tag = "Accessions",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
+    responses(
+        (status = 200, description = "Capture accepted"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Accession is not flagged as a bad capture")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn clear_bad_capture(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .accessions_service
+        .clear_bad_capture(id, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/accessions/{accession_id}",
+    tag = "Accessions",
+    responses(
+        (status = 200, description = "Accession deleted", content((String = "text/plain"), (MessageResponse = "application/json"))),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn delete_accession(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if authenticated_user.role != Role::Admin {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+
+    state
+        .accessions_service
+        .delete_one(id, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/accessions/{accession_id}",
+    tag = "Accessions",
+    request_body = UpdateAccessionRequest,
+    responses(
+        (status = 200, description = "OK", body = GetOneAccessionResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Accession was edited since the given revision")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn update_accession(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<UpdateAccessionRequest>,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return ApiError::validation(err).into_response();
+    }
+    for language in [MetadataLanguage::English, MetadataLanguage::Arabic] {
+        let Some(subjects) = payload
+            .metadata(language)
+            .and_then(|metadata| metadata.subjects.clone())
+        else {
+            continue;
+        };
+        let subjects_exist = state
+            .subjects_service
+            .clone()
+            .verify_subjects_exist(subjects, language, authenticated_user.organization_id)
+            .await;
+        match subjects_exist {
+            Err(err) => {
+                return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response();
+            }
+            Ok(flag) => {
+                if !flag {
+                    return (StatusCode::BAD_REQUEST, "Subjects do not exist").into_response();
+                }
+            }
+        };
+    }
+    state
+        .accessions_service
+        .update_one(id, payload, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accessions/{accession_id}/translate-metadata",
+    tag = "Accessions",
+    responses(
+        (status = 200, description = "OK", body = GetOneAccessionResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Accession isn't missing a language"),
+        (status = 502, description = "Translation service error"),
+        (status = 503, description = "Machine translation is not configured")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn translate_accession_metadata(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .accessions_service
+        .translate_metadata(id, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accessions/{accession_id}/doi",
+    tag = "Accessions",
+    responses(
+        (status = 201, description = "Created", body = DoiResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Accession is private or already has a DOI"),
+        (status = 502, description = "DOI registration service error"),
+        (status = 503, description = "DOIs are not configured")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn mint_accession_doi(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    // DOIs can't be deleted once minted
+    if authenticated_user.role != Role::Admin {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state
+        .accessions_service
+        .mint_doi(id, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/{accession_id}/suggested-subjects",
+    tag = "Accessions",
+    responses(
+        (status = 200, description = "OK", body = SuggestedSubjectsResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+        (status = 502, description = "Entity recognition service error"),
+        (status = 503, description = "Subject suggestions are not configured")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn get_suggested_subjects(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    let accession = match state
+        .accessions_service
+        .find_one_managed(id, &authenticated_user)
+        .await
+    {
+        Err(err) => {
+            error!(%err, "Error occurred retrieving accession");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response();
+        }
+        Ok(None) => return (StatusCode::NOT_FOUND, "No such record").into_response(),
+        Ok(Some(accession)) => accession,
+    };
+    state
+        .subjects_service
+        .suggest_for_accession(accession)
+        .await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/accessions/{accession_id}/publication-state",
+    tag = "Accessions",
+    request_body = UpdatePublicationStateRequest,
+    responses(
+        (status = 200, description = "OK", body = GetOneAccessionResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found"),
+        (status = 409, description = "Accession cannot move to that state")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn update_accession_publication_state(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<UpdatePublicationStateRequest>,
+) -> Response {
+    if !validate_at_least_contributor(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least contributor role").into_response();
+    }
+    state
+        .accessions_service
+        .update_publication_state(id, payload.publication_state, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accessions/bulk-visibility",
+    tag = "Accessions",
+    request_body = BulkVisibilityRequest,
+    responses(
+        (status = 200, description = "OK", body = BulkVisibilityResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn bulk_update_visibility(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<BulkVisibilityRequest>,
+) -> Response {
+    if authenticated_user.role != Role::Admin {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state
+        .accessions_service
+        .bulk_update_visibility(
+            payload,
+            authenticated_user.user_id.clone(),
+            authenticated_user.organization_scope(),
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file_access::issue_file_token;
+    use crate::models::auth::{AuthMethod, AuthenticatedUser};
+    use crate::models::common::{
+        CrawlerBackend, MetadataLanguage, MetadataScrubbing, TextDirection, TimelineInterval,
+    };
+    use crate::models::error::ErrorResponse;
+    use crate::models::request::{
+        CreateAccessionRequest, CreateAccessionRequestRaw, CreateMetadataRequest,
+        PrivateAccessionPagination,
+    };
+    use crate::models::response::{
+        AccessionCrawlStatusResponse, AccessionStatsResponse, AccessionTimelineResponse,
+        AccessionsWithMetadataResponse, BulkVisibilityResponse, CountryUsageResponse,
+        CreateAccessionCrawlResponse, DryRunAccessionResponse, DuplicateAccessionsResponse,
+        GetOneAccessionResponse, GetOnePublicAccessionResponse, ListAccessionPagesResponse,
+        ListAccessionsResponse, ListPublicAccessionsResponse, ProvenanceResponse,
+        PublicAccessionsWithMetadataResponse, ReplaceAccessionFileResponse, SubjectResponse,
+        SubjectSuggestions, SuggestedSubjectsResponse, TimelineBucketResponse,
+        TopAccessionResponse, TopAccessionsResponse, WaczPageResponse,
+    };
+    use crate::provenance::{key_fingerprint, verify};
+    use crate::repos::accessions_repo::{AccessionsRepo, DBAccessionsRepo};
+    use crate::repos::organizations_repo::{
+        DBOrganizationsRepo, OrganizationsRepo, DEFAULT_ORGANIZATION_ID,
+    };
+    use crate::test_db::migrated_test_db;
+    use crate::test_tools::{
+        build_test_accessions_service, build_test_app, build_test_app_with_accessions_service,
+        build_test_wacz, get_mock_jwt, get_mock_jwt_for_organization, mock_bad_capture,
+        mock_derivatives_response, mock_one_accession_with_metadata,
+        mock_one_public_accession_with_metadata, mock_paginated_ar, mock_paginated_en,
+        mock_relations_response, mock_social_metadata_response, EICAR_SIGNATURE,
+        MOCK_CONTENT_DIGEST, MOCK_PRIVATE_ACCESSION_ID,
+    };
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use axum_extra::extract::Query;
+    use bytes::Bytes;
+    use entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
+    use entity::sea_orm_active_enums::{
+        AccessionEventKind, CrawlStatus, DublinMetadataFormat, PublicationState, Role, ScanStatus,
+    };
     use http_body_util::BodyExt;
     use pretty_assertions::assert_eq;
-    use serde_json::json;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
     use tower::ServiceExt;
 
-    async fn build_multipart_form_data(
-        metadata_json: serde_json::Value,
-        file_bytes: Vec<u8>,
-        file_name: &str,
-        file_content_type: &str,
-        metadata_first: bool,
-    ) -> Body {
-        let boundary = "------------------------abcdef1234567890";
-        let mut form_body_parts: Vec<Bytes> = Vec::new();
+    const ZIP_SIGNATURE: &[u8] = b"PK\x03\x04";
+
+    /// Builds a file of the given size that sniffs as a zip archive.
+    fn zip_file_bytes(size: usize) -> Vec<u8> {
+        let mut bytes = vec![0; size];
+        bytes[..ZIP_SIGNATURE.len()].copy_from_slice(ZIP_SIGNATURE);
+        bytes
+    }
+
+    async fn build_multipart_form_data(
+        metadata_json: serde_json::Value,
+        file_bytes: Vec<u8>,
+        file_name: &str,
+        file_content_type: &str,
+        metadata_first: bool,
+    ) -> Body {
+        let boundary = "------------------------abcdef1234567890";
+        let mut form_body_parts: Vec<Bytes> = Vec::new();
+
+        let metadata_part = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"metadata\"\r\nContent-Type: application/json\r\n\r\n{metadata_json}\r\n",
+            boundary = boundary,
+            metadata_json = metadata_json
+        );
+
+        let file_part_header = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{file_name}\"\r\nContent-Type: {file_content_type}\r\n\r\n",
+            boundary = boundary,
+            file_name = file_name,
+            file_content_type = file_content_type
+        );
+        let file_part_footer = "\r\n";
+
+        if metadata_first {
+            form_body_parts.push(Bytes::from(metadata_part.into_bytes()));
+            form_body_parts.push(Bytes::from(file_part_header.into_bytes()));
+            form_body_parts.push(Bytes::from(file_bytes));
+            form_body_parts.push(Bytes::from(file_part_footer.as_bytes()));
+        } else {
+            form_body_parts.push(Bytes::from(file_part_header.into_bytes()));
+            form_body_parts.push(Bytes::from(file_bytes));
+            form_body_parts.push(Bytes::from(file_part_footer.as_bytes()));
+            form_body_parts.push(Bytes::from(metadata_part.into_bytes()));
+        }
+
+        form_body_parts.push(Bytes::from(format!("--{}--\r\n", boundary).into_bytes()));
+
+        Body::from(form_body_parts.concat())
+    }
+
+    #[tokio::test]
+    async fn run_one_crawl() {
+        let accessions_service = build_test_accessions_service();
+        accessions_service
+            .create_one(
+                10,
+                CreateAccessionRequest {
+                    url: "".to_string(),
+                    metadata_en: Some(CreateMetadataRequest {
+                        title: "".to_string(),
+                        description: Some("".to_string()),
+                        subjects: vec![1, 2, 3],
+                    }),
+                    metadata_ar: None,
+                    metadata_time: Default::default(),
+                    browser_profile: None,
+                    crawler: None,
+                    capture_mode: Default::default(),
+                    is_private: false,
+                    embargo_until: None,
+                    content_warning: None,
+                    metadata_format: DublinMetadataFormat::Wacz,
+                    s3_filename: Some("test-file.wacz".to_string()),
+                },
+                "archiver@gmail.com".to_string(),
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await;
+    }
+
+    #[tokio::test]
+    async fn accessions_without_crawls_skip_browsertrix() {
+        let accessions_service = build_test_accessions_service();
+        let upload = AccessionWithMetadataModel {
+            crawl_id: None,
+            org_id: None,
+            job_run_id: None,
+            s3_filename: None,
+            ..mock_one_accession_with_metadata()
+        };
+        assert_eq!(
+            accessions_service.resolve_wacz_url(&upload, None).await,
+            Err("Accession has no file to replay")
+        );
+
+        let response = AccessionsWithMetadataResponse::from(upload);
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["crawl_id"], Value::Null);
+        assert_eq!(json["org_id"], Value::Null);
+        assert_eq!(json["job_run_id"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn run_one_crawl_without_description() {
+        let accessions_service = build_test_accessions_service();
+        accessions_service
+            .create_one(
+                10,
+                CreateAccessionRequest {
+                    url: "".to_string(),
+                    metadata_en: None,
+                    metadata_ar: Some(CreateMetadataRequest {
+                        title: "".to_string(),
+                        description: None,
+                        subjects: vec![1, 2, 3],
+                    }),
+                    metadata_time: Default::default(),
+                    browser_profile: None,
+                    crawler: None,
+                    capture_mode: Default::default(),
+                    is_private: true,
+                    embargo_until: None,
+                    content_warning: None,
+                    metadata_format: DublinMetadataFormat::Wacz,
+                    s3_filename: Some("test-file-2.wacz".to_string()),
+                },
+                "emailsare4eva@aol.com".to_string(),
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await;
+    }
+    #[tokio::test]
+    async fn create_one_accession_crawl() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/crawl")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+    "url": "https://www.theguardian.com/business/2025/jan/10/britain-energy-costs-labour-power-plants-uk-cold-weather?utm_source=firefox-newtab-en-gb",
+    "metadata_language": "english",
+    "metadata_title": "Guardian piece",
+    "metadata_subject": "UK energy costs",
+    "metadata_description": "Blah de blah",
+    "metadata_time": "2024-11-01T23:32:00",
+    "browser_profile": null,
+    "metadata_subjects": [1],
+    "is_private": false,
+    "metadata_format": "wacz",
+    "s3_filename": "guardian-article.wacz"
+})).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: CreateAccessionCrawlResponse = serde_json::from_slice(&body).unwrap();
+        let expected = CreateAccessionCrawlResponse {
+            accession_id: 10,
+            status_url: "/api/v1/accessions/10/status".to_string(),
+        };
+        assert_eq!(actual, expected)
+    }
+
+    #[tokio::test]
+    async fn create_one_accession_crawl_in_both_languages() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/crawl")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "url": "https://example.com/story",
+                            "metadata_en": {"title": "Market fire", "subjects": [1]},
+                            "metadata_ar": {
+                                "title": "حريق السوق",
+                                "description": "حريق في سوق نيالا",
+                                "subjects": [1]
+                            },
+                            "metadata_time": "2024-11-01T23:32:00",
+                            "browser_profile": null,
+                            "is_private": false,
+                            "metadata_format": "wacz",
+                            "s3_filename": null
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn create_one_accession_crawl_needs_metadata() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/crawl")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "url": "https://example.com/story",
+                            "metadata_time": "2024-11-01T23:32:00",
+                            "browser_profile": null,
+                            "is_private": false,
+                            "metadata_format": "wacz",
+                            "s3_filename": null
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        let details = actual.error.details.unwrap();
+        assert_eq!(details["__all__"][0]["code"], "missing_metadata");
+    }
+
+    #[tokio::test]
+    async fn create_one_accession_crawl_dry_run() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/crawl?dry_run=true")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "url": "https://example.com/?utm_source=newsletter",
+                            "metadata_language": "english",
+                            "metadata_title": "Example",
+                            "metadata_time": "2024-11-01T23:32:00",
+                            "browser_profile": "facebook",
+                            "metadata_subjects": [1],
+                            "is_private": false,
+                            "metadata_format": "wacz"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: DryRunAccessionResponse = serde_json::from_slice(&body).unwrap();
+        let expected = DryRunAccessionResponse {
+            url: "https://example.com/?utm_source=newsletter".to_string(),
+            canonical_url: "https://example.com/".to_string(),
+            crawler: CrawlerBackend::Browsertrix,
+            browser_profile_id: Some("b1cd3192-a554-41e1-9509-0cbff3b3df16".to_string()),
+            duplicate_accession_ids: vec![1],
+        };
+        assert_eq!(actual, expected);
+    }
+
+    fn from_file_request_body(s3_filename: &str) -> Body {
+        Body::from(
+            serde_json::to_vec(&json!({
+                "metadata_language": "english",
+                "metadata_title": "Example",
+                "metadata_time": "2024-11-01T23:32:00",
+                "metadata_subjects": [1],
+                "is_private": false,
+                "metadata_format": "wacz",
+                "original_url": "https://example.com/",
+                "s3_filename": s3_filename
+            }))
+            .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn create_accession_from_file() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/from-file")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(from_file_request_body(
+                        "0b7f4e0a-4cbb-4c3c-9d51-8e0f8cfb7d59.wacz",
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn create_accession_from_file_rejects_other_keys() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/from-file")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(from_file_request_body("some-accession.pdf"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn create_accession_from_file_refuses_unscrubbed_images() {
+        let app = build_test_app();
+        let body = serde_json::to_vec(&json!({
+            "metadata_language": "english",
+            "metadata_title": "Example",
+            "metadata_time": "2024-11-01T23:32:00",
+            "metadata_subjects": [1],
+            "is_private": false,
+            "metadata_format": "jpeg",
+            "original_url": "https://example.com/",
+            "s3_filename": "0b7f4e0a-4cbb-4c3c-9d51-8e0f8cfb7d59.jpg"
+        }))
+        .unwrap();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/from-file")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn create_one_accession_crawl_no_description() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/crawl")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "url": "https://facebook.com/some/story",
+                            "metadata_language": "english",
+                            "metadata_title": "Guardian piece",
+                            "browser_profile": "facebook",
+                            "metadata_description": null,
+                            "metadata_time": "2024-11-01T23:32:00",
+                            "browser_profile": "facebook",
+                            "metadata_subjects": [1],
+                            "is_private": true,
+                            "metadata_format": "wacz",
+                            "s3_filename": "facebook-story.wacz"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: CreateAccessionCrawlResponse = serde_json::from_slice(&body).unwrap();
+        let expected = CreateAccessionCrawlResponse {
+            accession_id: 10,
+            status_url: "/api/v1/accessions/10/status".to_string(),
+        };
+        assert_eq!(actual, expected)
+    }
+    #[tokio::test]
+    async fn create_one_accession_crawl_with_unconfigured_crawler() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/crawl")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "url": "https://example.com/story",
+                            "metadata_language": "english",
+                            "metadata_title": "Local crawl",
+                            "metadata_time": "2024-11-01T23:32:00",
+                            "metadata_subjects": [1],
+                            "crawler": "local",
+                            "is_private": false,
+                            "metadata_format": "wacz",
+                            "s3_filename": null
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "Crawler is not configured");
+    }
+
+    #[tokio::test]
+    async fn create_one_accession_crawl_blocklisted() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/crawl?dry_run=true")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "url": "https://news.blocked.example/story",
+                            "metadata_language": "english",
+                            "metadata_title": "Blocked",
+                            "metadata_time": "2024-11-01T23:32:00",
+                            "metadata_subjects": [1],
+                            "is_private": false,
+                            "metadata_format": "wacz",
+                            "s3_filename": null
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "URL is on the crawl blocklist: Court order");
+    }
+
+    #[tokio::test]
+    async fn create_one_accession_crawl_invalid_fields() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/crawl")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "url": "ftp://example.com/file",
+                            "metadata_language": "english",
+                            "metadata_title": "   ",
+                            "metadata_description": null,
+                            "metadata_time": "2999-01-01T00:00:00",
+                            "browser_profile": null,
+                            "metadata_subjects": [1, 1],
+                            "is_private": false,
+                            "metadata_format": "wacz",
+                            "s3_filename": null
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.error.code, "validation_error");
+        let details = actual.error.details.unwrap();
+        assert_eq!(details["url"][0]["code"], "http_scheme");
+        // the flat metadata fields are reported as the block they end up in
+        assert_eq!(details["metadata_en"]["title"][0]["code"], "blank");
+        assert_eq!(details["metadata_time"][0]["code"], "future_date");
+        assert_eq!(
+            details["metadata_en"]["subjects"][0]["code"],
+            "duplicate_subjects"
+        );
+    }
+
+    #[tokio::test]
+    async fn get_one_accession() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: GetOnePublicAccessionResponse = serde_json::from_slice(&body).unwrap();
+        let mocked_resp = mock_one_public_accession_with_metadata();
+        let expected = GetOnePublicAccessionResponse {
+            accession: mocked_resp.into(),
+            wacz_url: "my url".to_owned(),
+            pdf_url: Some("my url".to_owned()),
+            derivatives: mock_derivatives_response(),
+            relations: mock_relations_response(),
+            social_metadata: Some(mock_social_metadata_response()),
+        };
+        assert_eq!(actual, expected)
+    }
+
+    #[tokio::test]
+    async fn get_one_accession_hides_browsertrix_ids() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let accession = actual["accession"].as_object().unwrap();
+        assert!(!accession.contains_key("crawl_id"));
+        assert!(!accession.contains_key("org_id"));
+        assert!(!accession.contains_key("job_run_id"));
+    }
+
+    #[tokio::test]
+    async fn get_one_accession_lists_subjects_with_their_ids() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            actual["accession"]["subjects"],
+            json!([
+                {"id": 1, "subject": "archive", "lang": "english"},
+                {"id": 3, "subject": "mrhaba archive", "lang": "arabic"},
+            ])
+        );
+        assert!(actual["accession"].get("subjects_en_ids").is_none());
+    }
+
+    #[tokio::test]
+    async fn get_one_accession_displays_the_preferred_language() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1")
+                    .header(http::header::ACCEPT_LANGUAGE, "ar-SD,ar;q=0.9,en;q=0.8")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response
+            .headers()
+            .get_all(http::header::VARY)
+            .iter()
+            .any(|vary| vary == "accept-language"));
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: GetOnePublicAccessionResponse = serde_json::from_slice(&body).unwrap();
+        let display = actual.accession.display;
+        assert_eq!(display.display_title.as_deref(), Some("Arabic Title"));
+        assert_eq!(
+            display.display_description.as_deref(),
+            Some("Arabic Description")
+        );
+        assert_eq!(display.display_lang, MetadataLanguage::Arabic);
+        assert_eq!(display.display_dir, TextDirection::Rtl);
+    }
+
+    #[test]
+    fn displays_the_other_language_when_the_preferred_one_is_missing() {
+        let english_only = AccessionWithMetadataModel {
+            title_ar: None,
+            description_ar: None,
+            ..mock_one_accession_with_metadata()
+        };
+        let display = PublicAccessionsWithMetadataResponse::from(english_only)
+            .displayed_in(MetadataLanguage::Arabic)
+            .display;
+        assert_eq!(display.display_title.as_deref(), Some("English Title"));
+        assert_eq!(display.display_lang, MetadataLanguage::English);
+        assert_eq!(display.display_dir, TextDirection::Ltr);
+    }
+
+    #[tokio::test]
+    async fn get_one_private_accession_includes_browsertrix_ids() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/private/1")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let accession = actual["accession"].as_object().unwrap();
+        assert!(accession.contains_key("crawl_id"));
+        assert!(accession.contains_key("org_id"));
+        assert!(accession.contains_key("job_run_id"));
+    }
+
+    #[tokio::test]
+    async fn iiif_manifest_is_only_for_images() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1/iiif/manifest.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"Accession is not an image");
+    }
+
+    #[tokio::test]
+    async fn list_accession_pages() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1/pages")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListAccessionPagesResponse = serde_json::from_slice(&body).unwrap();
+        let expected = ListAccessionPagesResponse {
+            accession_id: 1,
+            pages: vec![
+                WaczPageResponse {
+                    url: "https://example.com/".to_string(),
+                    title: Some("Example".to_string()),
+                    ts: Some("2024-01-01T00:00:00Z".to_string()),
+                },
+                WaczPageResponse {
+                    url: "https://example.com/about".to_string(),
+                    title: None,
+                    ts: Some("2024-01-01T00:01:00Z".to_string()),
+                },
+            ],
+        };
+        assert_eq!(actual, expected)
+    }
+
+    #[tokio::test]
+    async fn get_one_private_accession_no_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/private/1")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: GetOneAccessionResponse = serde_json::from_slice(&body).unwrap();
+        assert_linked_through_proxy(&actual);
+    }
+
+    #[tokio::test]
+    async fn get_one_private_accession_of_another_organization() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/private/1")
+                    .header(
+                        http::header::COOKIE,
+                        format!("jwt={}", get_mock_jwt_for_organization(2)),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    /// Checks a private accession's files are linked through the file proxy, not presigned.
+    fn assert_linked_through_proxy(actual: &GetOneAccessionResponse) {
+        let expected: AccessionsWithMetadataResponse = mock_one_accession_with_metadata().into();
+        assert_eq!(actual.accession, expected);
+        let proxy_url = "https://api.example.org/api/v1/accessions/private/files/";
+        assert!(actual.wacz_url.starts_with(proxy_url));
+        assert!(actual.pdf_url.as_ref().unwrap().starts_with(proxy_url));
+        assert_eq!(actual.derivatives.len(), mock_derivatives_response().len());
+        assert!(actual
+            .derivatives
+            .iter()
+            .all(|derivative| derivative.url.starts_with(proxy_url)));
+    }
+
+    async fn get_private_wacz_url() -> String {
+        let response = build_test_app()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/private/1")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: GetOneAccessionResponse = serde_json::from_slice(&body).unwrap();
+        actual.wacz_url
+    }
+
+    #[tokio::test]
+    async fn stream_private_file_follows_a_file_link() {
+        let wacz_url = get_private_wacz_url().await;
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(wacz_url.trim_start_matches("https://api.example.org"))
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[http::header::CACHE_CONTROL],
+            "private, no-store"
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, build_test_wacz());
+    }
+
+    #[tokio::test]
+    async fn stream_private_file_refuses_links_issued_to_someone_else() {
+        let someone_else = AuthenticatedUser {
+            user_id: "someone@example.com".to_string(),
+            expiry: None,
+            role: Role::Researcher,
+            impersonator: None,
+            organization_id: DEFAULT_ORGANIZATION_ID,
+            auth_method: AuthMethod::Cookie,
+        };
+        let token = issue_file_token(&someone_else, 1, "some_file.wacz").unwrap();
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/v1/accessions/private/files/{token}"))
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn stream_private_file_needs_a_session() {
+        let wacz_url = get_private_wacz_url().await;
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(wacz_url.trim_start_matches("https://api.example.org"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn get_one_private_accession_with_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: GetOnePublicAccessionResponse = serde_json::from_slice(&body).unwrap();
+        let mocked_resp = mock_one_public_accession_with_metadata();
+        let expected = GetOnePublicAccessionResponse {
+            accession: mocked_resp.into(),
+            wacz_url: "my url".to_owned(),
+            pdf_url: Some("my url".to_owned()),
+            derivatives: mock_derivatives_response(),
+            relations: mock_relations_response(),
+            social_metadata: Some(mock_social_metadata_response()),
+        };
+        assert_eq!(actual, expected)
+    }
+
+    #[tokio::test]
+    async fn list_accessions_en() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions?page=0&per_page=1&lang=english")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListPublicAccessionsResponse = serde_json::from_slice(&body).unwrap();
+        let mocked_resp = mock_paginated_en();
+        let expected = mocked_resp;
+        assert_eq!(actual.num_pages, expected.1);
+        assert_eq!(actual.items.len(), expected.0.len());
+    }
+
+    #[tokio::test]
+    async fn list_accessions_rejects_oversized_pages() {
+        for query in ["page=0&per_page=10000", "page=10001&per_page=20"] {
+            let app = build_test_app();
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/api/v1/accessions?{query}&lang=english"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{query}");
+        }
+    }
+
+    #[tokio::test]
+    async fn list_accessions_ar() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions?page=0&per_page=1&lang=arabic")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListPublicAccessionsResponse = serde_json::from_slice(&body).unwrap();
+        let mocked_resp = mock_paginated_ar();
+        let expected = mocked_resp;
+        assert_eq!(actual.num_pages, expected.1);
+        assert_eq!(actual.items.len(), expected.0.len());
+    }
+
+    #[tokio::test]
+    async fn list_accessions_no_query_params() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListPublicAccessionsResponse = serde_json::from_slice(&body).unwrap();
+        let mocked_resp = mock_paginated_en();
+        let expected = mocked_resp;
+        assert_eq!(actual.num_pages, expected.1);
+        assert_eq!(actual.items.len(), expected.0.len());
+    }
+
+    #[tokio::test]
+    async fn list_accessions_private_no_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/private?page=0&per_page=1&lang=english&private=true")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn list_accessions_private_with_auth_en() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/private?page=0&per_page=1&lang=english")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListAccessionsResponse = serde_json::from_slice(&body).unwrap();
+        let mocked_resp = mock_paginated_en();
+        let expected = mocked_resp;
+        assert_eq!(actual.num_pages, expected.1);
+        assert_eq!(actual.items.len(), expected.0.len());
+    }
+
+    #[tokio::test]
+    async fn list_accessions_private_with_auth_no_query_params() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/private?page=0&per_page=1&lang=english")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListAccessionsResponse = serde_json::from_slice(&body).unwrap();
+        let mocked_resp = mock_paginated_en();
+        let expected = mocked_resp;
+        assert_eq!(actual.num_pages, expected.1);
+        assert_eq!(actual.items.len(), expected.0.len());
+    }
+
+    #[test]
+    fn private_listing_visibility_follows_the_viewer() {
+        let query = |uri: &str| {
+            Query::<PrivateAccessionPagination>::try_from_uri(&uri.parse().unwrap())
+                .unwrap()
+                .0
+        };
+        let viewer = |role, organization_id| AuthenticatedUser {
+            user_id: "someone@example.com".to_string(),
+            expiry: None,
+            role,
+            impersonator: None,
+            organization_id,
+            auth_method: AuthMethod::Cookie,
+        };
+        let escalation = "/api/v1/accessions/private?is_private=false&organization_id=1";
+
+        let partner = query(escalation).into_list_params(&viewer(Role::Admin, 2));
+        assert!(partner.is_private);
+        assert_eq!(partner.organization_id, Some(2));
+
+        let researcher = query(escalation).into_list_params(&viewer(Role::Researcher, 1));
+        assert!(researcher.is_private);
+        assert_eq!(researcher.organization_id, Some(1));
+
+        let platform_admin = viewer(Role::Admin, DEFAULT_ORGANIZATION_ID);
+        let filtered =
+            query("/api/v1/accessions/private?organization_id=2").into_list_params(&platform_admin);
+        assert!(filtered.is_private);
+        assert_eq!(filtered.organization_id, Some(2));
+        let everything = query("/api/v1/accessions/private").into_list_params(&platform_admin);
+        assert_eq!(everything.organization_id, None);
+    }
+
+    #[tokio::test]
+    async fn list_accessions_private_ignores_is_private() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/private?is_private=false&organization_id=1")
+                    .header(
+                        http::header::COOKIE,
+                        format!("jwt={}", get_mock_jwt_for_organization(2)),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
 
-        let metadata_part = format!(
-            "--{boundary}\r\nContent-Disposition: form-data; name=\"metadata\"\r\nContent-Type: application/json\r\n\r\n{metadata_json}\r\n",
-            boundary = boundary,
-            metadata_json = metadata_json.to_string()
-        );
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn list_accessions_private_only_returns_the_callers_private_accessions() {
+        let db = migrated_test_db().await;
+        let db_session = db.clone();
+        let accessions_repo = DBAccessionsRepo {
+            db_session: db_session.clone(),
+            ..Default::default()
+        };
+        let partner = DBOrganizationsRepo {
+            db_session,
+            ..Default::default()
+        }
+        .write_one("partner".to_string(), "Partner archive".to_string())
+        .await
+        .unwrap();
+        let write = |title: &str, is_private: bool, organization_id: i32| {
+            let request = CreateAccessionRequestRaw {
+                metadata_language: MetadataLanguage::English,
+                metadata_title: title.to_string(),
+                metadata_description: None,
+                metadata_time: Default::default(),
+                metadata_subjects: vec![],
+                is_private,
+                embargo_until: None,
+                content_warning: None,
+                metadata_format: DublinMetadataFormat::Wacz,
+                original_url: "https://example.com".to_string(),
+                s3_filename: "file.wacz".to_string(),
+                metadata_scrubbing: MetadataScrubbing::Scrub,
+            };
+            accessions_repo.write_one_raw(request, ScanStatus::Clean, true, organization_id)
+        };
+        write("Partner public", false, partner.id).await.unwrap();
+        let partner_private = write("Partner private", true, partner.id).await.unwrap();
+        write("Archive private", true, DEFAULT_ORGANIZATION_ID)
+            .await
+            .unwrap();
 
-        let file_part_header = format!(
-            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{file_name}\"\r\nContent-Type: {file_content_type}\r\n\r\n",
-            boundary = boundary,
-            file_name = file_name,
-            file_content_type = file_content_type
+        let mut accessions_service = build_test_accessions_service();
+        accessions_service.accessions_repo = Arc::new(accessions_repo);
+        let app = build_test_app_with_accessions_service(accessions_service);
+        let escalation = format!(
+            "/api/v1/accessions/private?is_private=false&organization_id={DEFAULT_ORGANIZATION_ID}"
         );
-        let file_part_footer = "\r\n";
-
-        if metadata_first {
-            form_body_parts.push(Bytes::from(metadata_part.into_bytes()));
-            form_body_parts.push(Bytes::from(file_part_header.into_bytes()));
-            form_body_parts.push(Bytes::from(file_bytes));
-            form_body_parts.push(Bytes::from(file_part_footer.as_bytes()));
-        } else {
-            form_body_parts.push(Bytes::from(file_part_header.into_bytes()));
-            form_body_parts.push(Bytes::from(file_bytes));
-            form_body_parts.push(Bytes::from(file_part_footer.as_bytes()));
-            form_body_parts.push(Bytes::from(metadata_part.into_bytes()));
+        let filtered = format!(
+            "/api/v1/accessions/private?is_private=false&organization_id={}",
+            partner.id
+        );
+        for (uri, jwt) in [
+            (escalation, get_mock_jwt_for_organization(partner.id)),
+            (filtered, get_mock_jwt()),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(uri)
+                        .header(http::header::COOKIE, format!("jwt={jwt}"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let actual: ListAccessionsResponse = serde_json::from_slice(&body).unwrap();
+            let ids: Vec<i32> = actual.items.iter().map(|item| item.id).collect();
+            assert_eq!(ids, vec![partner_private]);
         }
+    }
 
-        form_body_parts.push(Bytes::from(format!("--{}--\r\n", boundary).into_bytes()));
+    #[tokio::test]
+    async fn delete_one_accession_no_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri("/api/v1/accessions/1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
 
-        Body::from(form_body_parts.concat())
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
 
     #[tokio::test]
-    async fn run_one_crawl() {
-        let accessions_service = build_test_accessions_service();
-        accessions_service
-            .create_one(
-                CreateAccessionRequest {
-                    url: "".to_string(),
-                    metadata_language: MetadataLanguage::English,
-                    metadata_title: "".to_string(),
-                    metadata_description: Some("".to_string()),
-                    metadata_time: Default::default(),
-                    browser_profile: None,
-                    metadata_subjects: vec![1, 2, 3],
-                    is_private: false,
-                    metadata_format: DublinMetadataFormat::Wacz,
-                    s3_filename: Some("test-file.wacz".to_string()),
-                },
-                "archiver@gmail.com".to_string(),
+    async fn delete_one_accession_with_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri("/api/v1/accessions/1")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
             )
-            .await;
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual = String::from_utf8(body.to_vec()).unwrap();
+        let expected = "Accession deleted".to_string();
+        assert_eq!(actual, expected);
+    }
+    #[tokio::test]
+    async fn update_one_accession_no_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/accessions/1")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "metadata_en": {
+                                "title": "Guardian piece",
+                                "description": "Blah de blah",
+                                "subjects": [1]
+                            },
+                            "metadata_time": "2024-11-01T23:32:00",
+                            "revision": 0
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
 
     #[tokio::test]
-    async fn run_one_crawl_without_description() {
-        let accessions_service = build_test_accessions_service();
-        accessions_service
-            .create_one(
-                CreateAccessionRequest {
-                    url: "".to_string(),
-                    metadata_language: MetadataLanguage::English,
-                    metadata_title: "".to_string(),
-                    metadata_subjects: vec![1, 2, 3],
-                    metadata_description: None,
-                    metadata_time: Default::default(),
-                    browser_profile: None,
-                    is_private: true,
-                    metadata_format: DublinMetadataFormat::Wacz,
-                    s3_filename: Some("test-file-2.wacz".to_string()),
-                },
-                "emailsare4eva@aol.com".to_string(),
+    async fn update_one_accession_with_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/accessions/1")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "metadata_en": {
+                                "title": "Guardian piece",
+                                "description": "Blah de blah",
+                                "subjects": [1]
+                            },
+                            "metadata_time": "2024-11-01T23:32:00",
+                            "revision": 0
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: GetOneAccessionResponse = serde_json::from_slice(&body).unwrap();
+        assert_linked_through_proxy(&actual);
+    }
+
+    #[tokio::test]
+    async fn update_one_accession_partially() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/accessions/1")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "metadata_ar": {"description": null},
+                            "revision": 0
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn update_one_accession_in_both_languages() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/accessions/1")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "metadata_en": {"title": "Guardian piece", "subjects": [1]},
+                            "metadata_ar": {"title": "مقال الغارديان", "subjects": [1]},
+                            "revision": 0
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn update_one_accession_conflicts_on_stale_revision() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/accessions/1")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "metadata_en": {"title": "Guardian piece"},
+                            "revision": 7
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn update_one_accession_needs_a_revision() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/accessions/1")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "metadata_en": {"title": "Guardian piece"}
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        // without a revision there is nothing to tell a concurrent edit apart by
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn update_one_accession_rejects_url_and_duplicate_subjects() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/accessions/1")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "metadata_en": {"title": "  "},
+                            "metadata_ar": {"subjects": [1, 1]},
+                            "url": "https://example.com/elsewhere",
+                            "revision": 0
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        let details = actual.error.details.unwrap();
+        assert_eq!(details["url"][0]["code"], "immutable");
+        assert_eq!(details["metadata_en"]["title"][0]["code"], "blank");
+        assert_eq!(
+            details["metadata_ar"]["subjects"][0]["code"],
+            "duplicate_subjects"
+        );
+    }
+
+    #[tokio::test]
+    async fn publish_draft_accession() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/accessions/1/publication-state")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"publication_state": "Published"})).unwrap(),
+                    ))
+                    .unwrap(),
             )
-            .await;
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: GetOneAccessionResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            actual.accession.publication_state,
+            PublicationState::Published
+        );
+        assert!(!actual.accession.is_private);
     }
+
     #[tokio::test]
-    async fn create_one_accession_crawl() {
+    async fn withdraw_draft_accession_conflicts() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .method(http::Method::POST)
-                    .uri("/api/v1/accessions/crawl")
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/accessions/1/publication-state")
                     .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
                     .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
                     .body(Body::from(
-                        serde_json::to_vec(&json!({
-    "url": "https://www.theguardian.com/business/2025/jan/10/britain-energy-costs-labour-power-plants-uk-cold-weather?utm_source=firefox-newtab-en-gb",
-    "metadata_language": "english",
-    "metadata_title": "Guardian piece",
-    "metadata_subject": "UK energy costs",
-    "metadata_description": "Blah de blah",
-    "metadata_time": "2024-11-01T23:32:00",
-    "browser_profile": null,
-    "metadata_subjects": [1],
-    "is_private": false,
-    "metadata_format": "wacz",
-    "s3_filename": "guardian-article.wacz"
-})).unwrap(),
+                        serde_json::to_vec(&json!({"publication_state": "Withdrawn"})).unwrap(),
                     ))
                     .unwrap(),
             )
             .await
             .unwrap();
-        assert_eq!(response.status(), StatusCode::CREATED);
 
+        assert_eq!(response.status(), StatusCode::CONFLICT);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual = String::from_utf8((&body).to_vec()).unwrap();
-        let expected = "Started browsertrix crawl task!".to_string();
-        assert_eq!(actual, expected)
+        assert_eq!(&body[..], b"Accession cannot move from draft to withdrawn");
     }
 
     #[tokio::test]
-    async fn create_one_accession_crawl_no_description() {
+    async fn update_publication_state_of_missing_accession() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .method(http::Method::POST)
-                    .uri("/api/v1/accessions/crawl")
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/accessions/99/publication-state")
                     .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
                     .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
                     .body(Body::from(
-                        serde_json::to_vec(&json!({
-                            "url": "https://facebook.com/some/story",
-                            "metadata_language": "english",
-                            "metadata_title": "Guardian piece",
-                            "browser_profile": "facebook",
-                            "metadata_description": null,
-                            "metadata_time": "2024-11-01T23:32:00",
-                            "browser_profile": "facebook",
-                            "metadata_subjects": [1],
-                            "is_private": true,
-                            "metadata_format": "wacz",
-                            "s3_filename": "facebook-story.wacz"
-                        }))
-                        .unwrap(),
+                        serde_json::to_vec(&json!({"publication_state": "InReview"})).unwrap(),
                     ))
                     .unwrap(),
             )
             .await
             .unwrap();
-        assert_eq!(response.status(), StatusCode::CREATED);
 
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual = String::from_utf8((&body).to_vec()).unwrap();
-        let expected = "Started browsertrix crawl task!".to_string();
-        assert_eq!(actual, expected)
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
+
     #[tokio::test]
-    async fn get_one_accession() {
+    async fn translate_metadata_of_bilingual_accession_conflicts() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/api/v1/accessions/1")
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/1/translate-metadata")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::CONFLICT);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual: GetOneAccessionResponse = serde_json::from_slice(&body).unwrap();
-        let mocked_resp = mock_one_accession_with_metadata();
-        let expected = GetOneAccessionResponse {
-            accession: mocked_resp.into(),
-            wacz_url: "my url".to_owned(),
-        };
-        assert_eq!(actual, expected)
+        assert_eq!(
+            &body[..],
+            b"Accession already has metadata in both languages"
+        );
     }
 
     #[tokio::test]
-    async fn get_one_private_accession_no_auth() {
+    async fn mint_doi_for_private_accession_conflicts() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/api/v1/accessions/private/1")
+                    .method(http::Method::POST)
+                    .uri(format!(
+                        "/api/v1/accessions/{MOCK_PRIVATE_ACCESSION_ID}/doi"
+                    ))
                     .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"Only public accessions can be given a DOI");
+    }
+
+    fn replace_file_request(body: Vec<u8>) -> Request<Body> {
+        Request::builder()
+            .method(http::Method::PUT)
+            .uri("/api/v1/accessions/1/file")
+            .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+            .header(http::header::CONTENT_TYPE, "application/octet-stream")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn replace_accession_file() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(replace_file_request(build_test_wacz()))
+            .await
+            .unwrap();
+
         assert_eq!(response.status(), StatusCode::OK);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual: GetOneAccessionResponse = serde_json::from_slice(&body).unwrap();
-        let mocked_query = mock_one_accession_with_metadata();
-        let expected = GetOneAccessionResponse {
-            accession: mocked_query.into(),
-            wacz_url: "my url".to_owned(),
-        };
-        assert_eq!(actual, expected)
+        let replaced: ReplaceAccessionFileResponse = serde_json::from_slice(&body).unwrap();
+        assert!(replaced.s3_filename.ends_with(".wacz"));
+        assert_ne!(replaced.s3_filename, "some_file.wacz");
+        assert_eq!(
+            replaced.quarantined_s3_filename.as_deref(),
+            Some("quarantine/some_file.wacz")
+        );
     }
 
     #[tokio::test]
-    async fn get_one_private_accession_with_auth() {
+    async fn replace_accession_file_checks_the_format() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(replace_file_request(b"<html>not a capture</html>".to_vec()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn get_accession_provenance() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/api/v1/accessions/1")
+                    .uri("/api/v1/accessions/1/provenance")
                     .body(Body::empty())
                     .unwrap(),
             )
@@ -566,66 +2718,69 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::OK);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual: GetOneAccessionResponse = serde_json::from_slice(&body).unwrap();
-        let mocked_resp = mock_one_accession_with_metadata();
-        let expected = GetOneAccessionResponse {
-            accession: mocked_resp.into(),
-            wacz_url: "my url".to_owned(),
-        };
-        assert_eq!(actual, expected)
+        let actual: ProvenanceResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.hash_algorithm, "sha256");
+        assert_eq!(actual.signature_algorithm, Some("ed25519".to_string()));
+        assert_eq!(
+            actual.signer_public_key,
+            Some("11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=".to_string())
+        );
+        assert_eq!(
+            actual.signer_key_fingerprint,
+            key_fingerprint("11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=")
+        );
+        assert!(verify(
+            &actual.signer_public_key.unwrap(),
+            &actual.signed_message.unwrap(),
+            &actual.signature.unwrap()
+        ));
     }
 
     #[tokio::test]
-    async fn list_accessions_en() {
+    async fn get_accession_provenance_not_recorded() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/api/v1/accessions?page=0&per_page=1&lang=english")
+                    .uri("/api/v1/accessions/2/provenance")
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::OK);
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual: ListAccessionsResponse = serde_json::from_slice(&body).unwrap();
-        let mocked_resp = mock_paginated_en();
-        let expected = mocked_resp;
-        assert_eq!(actual.num_pages, expected.1);
-        assert_eq!(actual.items.len(), expected.0.len());
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 
     #[tokio::test]
-    async fn list_accessions_ar() {
+    async fn get_accession_crawl_status() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/api/v1/accessions?page=0&per_page=1&lang=arabic")
+                    .uri("/api/v1/accessions/1/status")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
-
         assert_eq!(response.status(), StatusCode::OK);
+
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual: ListAccessionsResponse = serde_json::from_slice(&body).unwrap();
-        let mocked_resp = mock_paginated_ar();
-        let expected = mocked_resp;
-        assert_eq!(actual.num_pages, expected.1);
-        assert_eq!(actual.items.len(), expected.0.len());
+        let actual: AccessionCrawlStatusResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.crawl_status, CrawlStatus::Complete);
+        assert_eq!(actual.crawl_quality_reason, None);
     }
 
     #[tokio::test]
-    async fn list_accessions_no_query_params() {
+    async fn get_accession_duplicates() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/api/v1/accessions")
+                    .uri("/api/v1/accessions/1/duplicates")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
                     .body(Body::empty())
                     .unwrap(),
             )
@@ -634,36 +2789,40 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::OK);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual: ListAccessionsResponse = serde_json::from_slice(&body).unwrap();
-        let mocked_resp = mock_paginated_en();
-        let expected = mocked_resp;
-        assert_eq!(actual.num_pages, expected.1);
-        assert_eq!(actual.items.len(), expected.0.len());
+        let actual: DuplicateAccessionsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            actual,
+            DuplicateAccessionsResponse {
+                accession_id: 1,
+                content_digest: MOCK_CONTENT_DIGEST.to_string(),
+                duplicate_accession_ids: vec![3],
+            }
+        );
     }
 
     #[tokio::test]
-    async fn list_accessions_private_no_auth() {
+    async fn get_private_accession_provenance_no_auth() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/api/v1/accessions/private?page=0&per_page=1&lang=english&private=true")
+                    .uri("/api/v1/accessions/private/1/provenance")
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
 
     #[tokio::test]
-    async fn list_accessions_private_with_auth_en() {
+    async fn list_bad_captures() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/api/v1/accessions/private?page=0&per_page=1&lang=english")
+                    .uri("/api/v1/accessions/bad-captures?page=0&per_page=20")
                     .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
                     .body(Body::empty())
                     .unwrap(),
@@ -674,19 +2833,22 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
         let body = response.into_body().collect().await.unwrap().to_bytes();
         let actual: ListAccessionsResponse = serde_json::from_slice(&body).unwrap();
-        let mocked_resp = mock_paginated_en();
-        let expected = mocked_resp;
-        assert_eq!(actual.num_pages, expected.1);
-        assert_eq!(actual.items.len(), expected.0.len());
+        let expected = ListAccessionsResponse {
+            items: vec![AccessionsWithMetadataResponse::from(mock_bad_capture())],
+            num_pages: Some(1),
+            page: 0,
+            per_page: 20,
+        };
+        assert_eq!(actual, expected);
     }
 
     #[tokio::test]
-    async fn list_accessions_private_with_auth_no_query_params() {
+    async fn list_bad_captures_rejects_oversized_pages() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/api/v1/accessions/private?page=0&per_page=1&lang=english")
+                    .uri("/api/v1/accessions/bad-captures?per_page=1000")
                     .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
                     .body(Body::empty())
                     .unwrap(),
@@ -694,40 +2856,34 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::OK);
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual: ListAccessionsResponse = serde_json::from_slice(&body).unwrap();
-        let mocked_resp = mock_paginated_en();
-        let expected = mocked_resp;
-        assert_eq!(actual.num_pages, expected.1);
-        assert_eq!(actual.items.len(), expected.0.len());
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
     #[tokio::test]
-    async fn delete_one_accession_no_auth() {
+    async fn clear_bad_capture() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
                     .method(http::Method::DELETE)
-                    .uri("/api/v1/accessions/1")
+                    .uri("/api/v1/accessions/1/bad-capture")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::OK);
     }
 
     #[tokio::test]
-    async fn delete_one_accession_with_auth() {
+    async fn suggests_existing_subjects_and_new_terms() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .method(http::Method::DELETE)
-                    .uri("/api/v1/accessions/1")
+                    .uri("/api/v1/accessions/1/suggested-subjects")
                     .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
                     .body(Body::empty())
                     .unwrap(),
@@ -737,29 +2893,36 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::OK);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual = String::from_utf8((&body).to_vec()).unwrap();
-        let expected = "Accession deleted".to_string();
-        assert_eq!(actual, expected);
+        let actual: SuggestedSubjectsResponse = serde_json::from_slice(&body).unwrap();
+        let expected = SubjectSuggestions {
+            existing: vec![SubjectResponse {
+                id: 2,
+                subject: "Khartoum".to_string(),
+                created_at: Default::default(),
+                updated_at: Default::default(),
+                created_by: None,
+            }],
+            new_terms: vec!["Omdurman".to_string()],
+        };
+        assert_eq!(actual.english, expected);
+        assert_eq!(actual.arabic, expected);
     }
+
     #[tokio::test]
-    async fn update_one_accession_no_auth() {
+    async fn bulk_update_visibility_by_ids() {
         let app = build_test_app();
         let response = app
             .oneshot(
                 Request::builder()
-                    .method(http::Method::PUT)
-                    .uri("/api/v1/accessions/1")
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/bulk-visibility")
                     .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
                     .body(Body::from(
                         serde_json::to_vec(&json!({
-                            "metadata_language": "english",
-                            "metadata_title": "Guardian piece",
-                            "metadata_subject": "UK energy costs",
-                            "metadata_description": "Blah de blah",
-                            "metadata_time": "2024-11-01T23:32:00",
-                            "browser_profile": null,
-                            "metadata_subjects": [1],
-                            "is_private": false
+                            "ids": [1, 2, 3],
+                            "visibility": "private",
+                            "reason": "Source asked for removal"
                         }))
                         .unwrap(),
                     ))
@@ -768,66 +2931,161 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: BulkVisibilityResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.updated_ids, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn bulk_update_visibility_needs_one_selection() {
+        for selection in [
+            json!({"visibility": "public"}),
+            json!({"ids": [1], "url_filter": "https://example.com", "visibility": "public"}),
+        ] {
+            let app = build_test_app();
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .method(http::Method::POST)
+                        .uri("/api/v1/accessions/bulk-visibility")
+                        .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                        .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                        .body(Body::from(serde_json::to_vec(&selection).unwrap()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn bulk_update_visibility_no_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/bulk-visibility")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"ids": [1], "visibility": "public"})).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn create_accession_raw_no_auth() {
+        let app = build_test_app();
+        let metadata = json!({
+            "metadata_language": "english",
+            "metadata_title": "Test Title",
+            "metadata_description": "Test Description",
+            "metadata_time": "2024-01-01T00:00:00",
+            "metadata_subjects": [1],
+            "is_private": false,
+            "metadata_format": "wacz",
+            "original_url": "https://coolurl.com",
+            "s3_filename": "test-no-auth.wacz"
+        });
+        let file_bytes = vec![0; 100]; // 100 bytes file
+        let body = build_multipart_form_data(
+            metadata,
+            file_bytes,
+            "test-file.wacz",
+            "application/wacz",
+            true,
+        )
+        .await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/raw")
+                    .header(
+                        http::header::CONTENT_TYPE,
+                        "multipart/form-data; boundary=------------------------abcdef1234567890",
+                    )
+                    .body(body)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
 
-    #[tokio::test]
-    async fn update_one_accession_with_auth() {
-        let app = build_test_app();
+    #[tokio::test]
+    async fn create_accession_raw_small_file() {
+        let app = build_test_app();
+        let metadata = json!({
+            "metadata_language": "english",
+            "metadata_title": "Test Small File",
+            "metadata_description": "Small file description",
+            "metadata_time": "2024-01-01T00:00:00",
+            "metadata_subjects": [1],
+            "is_private": false,
+            "metadata_format": "wacz",
+            "original_url": "https://coolurl.com",
+            "s3_filename": "test-small.wacz"
+        });
+        let file_bytes = zip_file_bytes(1024 * 1024); // 1MB file
+        let body = build_multipart_form_data(
+            metadata,
+            file_bytes,
+            "small-file.wacz",
+            "application/wacz",
+            true,
+        )
+        .await;
+
         let response = app
             .oneshot(
                 Request::builder()
-                    .method(http::Method::PUT)
-                    .uri("/api/v1/accessions/1")
-                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/raw")
+                    .header(
+                        http::header::CONTENT_TYPE,
+                        "multipart/form-data; boundary=------------------------abcdef1234567890",
+                    )
                     .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
-                    .body(Body::from(
-                        serde_json::to_vec(&json!({
-                            "metadata_language": "english",
-                            "metadata_title": "Guardian piece",
-                            "metadata_subject": "UK energy costs",
-                            "metadata_description": "Blah de blah",
-                            "metadata_time": "2024-11-01T23:32:00",
-                            "metadata_subjects": [1],
-                            "is_private": false
-                        }))
-                        .unwrap(),
-                    ))
+                    .body(body)
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::CREATED);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual: GetOneAccessionResponse = serde_json::from_slice(&body).unwrap();
-        let mocked_resp = mock_one_accession_with_metadata();
-        let expected = GetOneAccessionResponse {
-            accession: mocked_resp.into(),
-            wacz_url: "my url".to_owned(),
-        };
-        assert_eq!(actual, expected)
+        let actual = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(actual, "Accession created with id: 10");
     }
 
     #[tokio::test]
-    async fn create_accession_raw_no_auth() {
+    async fn create_accession_raw_wrong_file_type() {
         let app = build_test_app();
         let metadata = json!({
             "metadata_language": "english",
-            "metadata_title": "Test Title",
-            "metadata_description": "Test Description",
+            "metadata_title": "Test PDF File",
             "metadata_time": "2024-01-01T00:00:00",
             "metadata_subjects": [1],
             "is_private": false,
             "metadata_format": "wacz",
             "original_url": "https://coolurl.com",
-            "s3_filename": "test-no-auth.wacz"
+            "s3_filename": "test-pdf.wacz"
         });
-        let file_bytes = vec![0; 100]; // 100 bytes file
         let body = build_multipart_form_data(
             metadata,
-            file_bytes,
-            "test-file.wacz",
+            b"%PDF-1.7\n".to_vec(),
+            "actually-a-pdf.wacz",
             "application/wacz",
             true,
         )
@@ -842,34 +3100,95 @@ mod tests {
                         http::header::CONTENT_TYPE,
                         "multipart/form-data; boundary=------------------------abcdef1234567890",
                     )
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
                     .body(body)
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(
+            actual,
+            "File is a PDF document but metadata format wacz expects a zip archive"
+        );
+    }
+
+    /// Sends a JPEG through the raw upload route and returns the status and body.
+    async fn upload_raw_jpeg(file: Vec<u8>) -> (StatusCode, String) {
+        let app = build_test_app();
+        let metadata = json!({
+            "metadata_language": "english",
+            "metadata_title": "Test Photo",
+            "metadata_time": "2024-01-01T00:00:00",
+            "metadata_subjects": [1],
+            "is_private": false,
+            "metadata_format": "jpeg",
+            "original_url": "https://coolurl.com",
+            "s3_filename": "test-photo.jpg"
+        });
+        let body = build_multipart_form_data(metadata, file, "photo.jpg", "image/jpeg", true).await;
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/raw")
+                    .header(
+                        http::header::CONTENT_TYPE,
+                        "multipart/form-data; boundary=------------------------abcdef1234567890",
+                    )
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(body)
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
     }
 
     #[tokio::test]
-    async fn create_accession_raw_small_file() {
+    async fn create_accession_raw_image() {
+        let exif = b"\xff\xe1\x00\x08Exif\x00\x00";
+        let file = [&b"\xff\xd8"[..], exif, b"\xff\xda\x00\x02\x12\x34\xff\xd9"].concat();
+        let (status, body) = upload_raw_jpeg(file).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body, "Accession created with id: 10");
+    }
+
+    #[tokio::test]
+    async fn create_accession_raw_corrupt_image() {
+        // The EXIF segment claims to be longer than the file
+        let file = b"\xff\xd8\xff\xe1\x10\x00Exif".to_vec();
+        let (status, body) = upload_raw_jpeg(file).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            body,
+            "Could not scrub metadata from file, it may be corrupt"
+        );
+    }
+
+    #[tokio::test]
+    async fn create_accession_raw_infected_file() {
         let app = build_test_app();
         let metadata = json!({
             "metadata_language": "english",
-            "metadata_title": "Test Small File",
-            "metadata_description": "Small file description",
+            "metadata_title": "Test Infected File",
             "metadata_time": "2024-01-01T00:00:00",
             "metadata_subjects": [1],
             "is_private": false,
             "metadata_format": "wacz",
             "original_url": "https://coolurl.com",
-            "s3_filename": "test-small.wacz"
+            "s3_filename": "test-infected.wacz"
         });
-        let file_bytes = vec![0; 1024 * 1024]; // 1MB file
         let body = build_multipart_form_data(
             metadata,
-            file_bytes,
-            "small-file.wacz",
+            [ZIP_SIGNATURE, EICAR_SIGNATURE].concat(),
+            "infected-file.wacz",
             "application/wacz",
             true,
         )
@@ -891,10 +3210,10 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual = String::from_utf8((&body).to_vec()).unwrap();
-        assert_eq!(actual, "Accession created with id: 10");
+        let actual = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(actual, "File failed virus scan");
     }
 
     #[tokio::test]
@@ -911,7 +3230,7 @@ mod tests {
             "original_url": "https://coolurl.com",
             "s3_filename": "test-large.wacz"
         });
-        let file_bytes = vec![0; 6 * 1024 * 1024]; // 6MB file
+        let file_bytes = zip_file_bytes(6 * 1024 * 1024); // 6MB file
         let body = build_multipart_form_data(
             metadata,
             file_bytes,
@@ -939,7 +3258,7 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::CREATED);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual = String::from_utf8((&body).to_vec()).unwrap();
+        let actual = String::from_utf8(body.to_vec()).unwrap();
         assert_eq!(actual, "Accession created with id: 10");
     }
 
@@ -985,7 +3304,7 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual = String::from_utf8((&body).to_vec()).unwrap();
+        let actual = String::from_utf8(body.to_vec()).unwrap();
         assert_eq!(actual, "Metadata field should be the first form field");
     }
 
@@ -1031,8 +3350,239 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
         let body = response.into_body().collect().await.unwrap().to_bytes();
-        let actual = String::from_utf8((&body).to_vec()).unwrap();
+        let actual = String::from_utf8(body.to_vec()).unwrap();
         assert!(actual
             .contains("Failed to parse metadata JSON: Error(\"missing field `metadata_title`\""));
     }
+
+    #[tokio::test]
+    async fn get_accession_stats_with_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1/stats?days=30")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: AccessionStatsResponse = serde_json::from_slice(&body).unwrap();
+        let expected = AccessionStatsResponse {
+            accession_id: 1,
+            views: 4,
+            wacz_urls: 2,
+            countries: vec![
+                CountryUsageResponse {
+                    country: Some("SD".to_string()),
+                    views: 3,
+                    wacz_urls: 2,
+                },
+                CountryUsageResponse {
+                    country: None,
+                    views: 1,
+                    wacz_urls: 0,
+                },
+            ],
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn get_accession_stats_no_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1/stats")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn list_top_accessions_with_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/stats?kind=WaczUrl&limit=5")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: TopAccessionsResponse = serde_json::from_slice(&body).unwrap();
+        let expected = TopAccessionsResponse {
+            kind: AccessionEventKind::WaczUrl,
+            items: vec![TopAccessionResponse {
+                accession_id: 1,
+                count: 4,
+            }],
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn list_top_accessions_rejects_large_limits() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/stats?limit=1000")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn export_accessions_as_ris() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/export?format=ris&lang=english")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[http::header::CONTENT_TYPE],
+            "application/x-research-info-systems"
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.starts_with("TY  - ELEC\r\nID  - sda-1\r\nTI  - English Title\r\n"));
+    }
+
+    #[tokio::test]
+    async fn export_accessions_as_csl_json() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/export?format=csl-json&lang=arabic")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual[0]["title"], "Arabic Title");
+        assert_eq!(
+            actual[0]["URL"],
+            "https://sudandigitalarchive.com/archive/1"
+        );
+    }
+
+    #[tokio::test]
+    async fn get_accessions_timeline() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/timeline?interval=week&lang=english&query_term=market")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: AccessionTimelineResponse = serde_json::from_slice(&body).unwrap();
+        let expected = AccessionTimelineResponse {
+            interval: TimelineInterval::Week,
+            buckets: vec![TimelineBucketResponse {
+                starts_at: Default::default(),
+                count: 3,
+            }],
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn get_accessions_timeline_needs_a_known_interval() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/timeline?interval=decade")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn export_accessions_needs_a_known_format() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/export?format=bibtex")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn stream_published_accessions_is_public() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/stream")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[http::header::CONTENT_TYPE],
+            mime::TEXT_EVENT_STREAM.as_ref()
+        );
+    }
+
+    #[tokio::test]
+    async fn create_one_accession_crawl_body_over_json_limit() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/accessions/crawl")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(vec![b' '; 2 * 1024 * 1024]))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
 }
@@ -0,0 +1,1153 @@
+//! Routes for archive administration.
+//!
+//! This module provides admin-only HTTP endpoints for inspecting the state of the archive,
+//! such as the health of the crawl pipeline and background tasks. It uses in-memory repositories for testing to
+//! avoid I/O operations.
+
+use crate::app_factory::AppState;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::request::{AccessLogQuery, AuditLogPagination, ReviewQueuePagination};
+use crate::models::response::{
+    ImpersonationResponse, ListAuditLogResponse, ListUsersResponse, PipelineStatusResponse,
+    ReindexStatusResponse, ReviewQueueResponse, S3BackfillStatusResponse, SchedulerLeaderResponse,
+    SchedulerStatusResponse, StaticExportStatusResponse,
+};
+use ::entity::sea_orm_active_enums::Role;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use tracing::error;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Creates routes for admin endpoints under `/admin`.
+pub fn get_admin_routes() -> Router<AppState> {
+    Router::new().nest(
+        "/admin",
+        Router::new()
+            .route("/pipeline", get(get_pipeline_status))
+            .route("/scheduler", get(get_scheduler_status))
+            .route("/scheduler/leader", get(get_scheduler_leader))
+            .route("/review-queue", get(get_review_queue))
+            .route("/audit-log", get(list_audit_log))
+            .route("/audit-log/export", get(export_audit_log))
+            .route(
+                "/accessions/{accession_id}/access-log",
+                get(export_access_log),
+            )
+            .route("/users", get(list_users))
+            .route("/users/{user_id}/deactivate", post(deactivate_user))
+            .route("/impersonate/{user_id}", post(impersonate_user))
+            .route(
+                "/backfill-s3",
+                get(get_s3_backfill_status).post(start_s3_backfill),
+            )
+            .route(
+                "/static-export",
+                get(get_static_export_status).post(start_static_export),
+            )
+            .route("/reindex", get(get_reindex_status).post(start_reindex)),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/pipeline",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "OK", body = PipelineStatusResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn get_pipeline_status(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.accessions_service.pipeline_status().await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/review-queue",
+    tag = "Admin",
+    params(
+        ReviewQueuePagination
+    ),
+    responses(
+        (status = 200, description = "OK", body = ReviewQueueResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn get_review_queue(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    pagination: Query<ReviewQueuePagination>,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    if let Err(err) = pagination.0.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state.accessions_service.review_queue(pagination.0).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/audit-log",
+    tag = "Admin",
+    params(
+        AuditLogPagination
+    ),
+    responses(
+        (status = 200, description = "OK", body = ListAuditLogResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_audit_log(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    pagination: Query<AuditLogPagination>,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    if let Err(err) = pagination.0.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state.audit_log_service.list(pagination.0).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/audit-log/export",
+    tag = "Admin",
+    params(
+        AuditLogPagination
+    ),
+    responses(
+        (status = 200, description = "Up to 10,000 matching entries, most recent first, ignoring page and per_page", body = String, content_type = "text/csv"),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn export_audit_log(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    pagination: Query<AuditLogPagination>,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    if let Err(err) = pagination.0.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state.audit_log_service.export(pagination.0).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/accessions/{accession_id}/access-log",
+    tag = "Admin",
+    params(
+        ("accession_id" = i32, Path, description = "ID of the accession"),
+        AccessLogQuery
+    ),
+    responses(
+        (status = 200, description = "ZIP holding who accessed the accession and when, oldest first, and a signed manifest.json describing it", body = Vec<u8>, content_type = "application/zip"),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn export_access_log(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Path(id): Path<i32>,
+    query: Query<AccessLogQuery>,
+) -> Response {
+    if authenticated_user.role != Role::Admin {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state
+        .accessions_service
+        .export_access_log(id, query.0.format, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/scheduler",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "OK", body = SchedulerStatusResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn get_scheduler_status(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    Json(SchedulerStatusResponse {
+        tasks: state
+            .scheduler_metrics
+            .snapshot()
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+    })
+    .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/scheduler/leader",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "OK", body = SchedulerLeaderResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn get_scheduler_leader(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    let election = &state.leader_election;
+    match election.current_leader().await {
+        Ok(leader) => Json(SchedulerLeaderResponse {
+            instance: election.instance().to_string(),
+            is_leader: election.is_leader(),
+            leader,
+        })
+        .into_response(),
+        Err(err) => {
+            error!(%err, "Error occurred looking up the scheduler leader");
+            (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/users",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "OK", body = ListUsersResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_users(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if authenticated_user.role != Role::Admin {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.auth_service.list_users(authenticated_user).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/users/{user_id}/deactivate",
+    tag = "Admin",
+    params(
+        ("user_id" = Uuid, Path, description = "ID of the user to deactivate")
+    ),
+    responses(
+        (status = 200, description = "User deactivated, their sessions deleted and API keys revoked"),
+        (status = 403, description = "Forbidden, or the user is the admin themselves"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn deactivate_user(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Path(user_id): Path<Uuid>,
+) -> Response {
+    if authenticated_user.role != Role::Admin {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state
+        .auth_service
+        .deactivate_user(authenticated_user, user_id)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/impersonate/{user_id}",
+    tag = "Admin",
+    params(
+        ("user_id" = Uuid, Path, description = "ID of the user to act as")
+    ),
+    responses(
+        (status = 200, description = "Signed in as the user for up to 30 minutes, replacing the admin's session", body = ImpersonationResponse),
+        (status = 403, description = "Forbidden, or the user is an admin"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn impersonate_user(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Path(user_id): Path<Uuid>,
+) -> Response {
+    if authenticated_user.role != Role::Admin {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state
+        .auth_service
+        .impersonate(authenticated_user, user_id)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/backfill-s3",
+    tag = "Admin",
+    responses(
+        (status = 202, description = "Started copying WACZ files still served by Browsertrix into S3", body = S3BackfillStatusResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 409, description = "A backfill is already running")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn start_s3_backfill(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.accessions_service.start_s3_backfill().await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/backfill-s3",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Progress of the running or most recent backfill", body = S3BackfillStatusResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn get_s3_backfill_status(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.accessions_service.s3_backfill_status().await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/static-export",
+    tag = "Admin",
+    responses(
+        (status = 202, description = "Started exporting public metadata as a static bundle in S3", body = StaticExportStatusResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 409, description = "An export is already running")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn start_static_export(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.accessions_service.start_static_export().await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/static-export",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Progress of the running or most recent export", body = StaticExportStatusResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn get_static_export_status(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.accessions_service.static_export_status().await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/reindex",
+    tag = "Admin",
+    responses(
+        (status = 202, description = "Started recomputing full text search columns and rebuilding their indexes", body = ReindexStatusResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 409, description = "A reindex is already running")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn start_reindex(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.accessions_service.start_reindex().await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/reindex",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Progress of the running or most recent reindex", body = ReindexStatusResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn get_reindex_status(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.accessions_service.reindex_status().await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::access_log_export::{AccessLogManifest, MANIFEST_FILE_NAME};
+    use crate::auth::JWT_KEYS;
+    use crate::models::auth::JWTClaims;
+    use crate::models::common::ReviewQueueItemKind;
+    use crate::models::response::{
+        ImpersonationResponse, ListAuditLogResponse, ListUsersResponse, PipelineStatusResponse,
+        ReindexStatusResponse, ReviewQueueResponse, S3BackfillStatusResponse,
+        SchedulerLeaderResponse, SchedulerStatusResponse, StaticExportStatusResponse,
+    };
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::test_tools::{
+        build_test_app, get_mock_jwt, get_mock_jwt_for_organization, MOCK_DEACTIVATED_EMAIL,
+        MOCK_RESEARCHER_ID,
+    };
+    use ::entity::sea_orm_active_enums::{EmailStatus, Role};
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use chrono::Utc;
+    use http_body_util::BodyExt;
+    use jsonwebtoken::Validation;
+    use pretty_assertions::assert_eq;
+    use sha2::{Digest, Sha256};
+    use std::io::{Cursor, Read};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+    use zip::ZipArchive;
+
+    #[tokio::test]
+    async fn get_pipeline_status_no_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/pipeline")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn list_audit_log() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/audit-log?event=private_file_accessed&date_from=2026-10-01T00:00:00")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListAuditLogResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.num_pages, 1);
+        assert_eq!(actual.items.len(), 1);
+        assert_eq!(actual.items[0].event, "private_file_accessed");
+    }
+
+    #[tokio::test]
+    async fn list_audit_log_invalid_page_size() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/audit-log?per_page=0")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn export_audit_log() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/audit-log/export?entity_type=accession")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[http::header::CONTENT_TYPE],
+            "text/csv; charset=utf-8"
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let csv = String::from_utf8(body.to_vec()).unwrap();
+        assert!(csv.starts_with("id,created_at,actor_email,"));
+        assert_eq!(csv.lines().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn export_access_log() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/accessions/1/access-log?format=csv")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[http::header::CONTENT_TYPE],
+            "application/zip"
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let mut archive = ZipArchive::new(Cursor::new(body.to_vec())).unwrap();
+        let manifest: AccessLogManifest =
+            serde_json::from_reader(archive.by_name(MANIFEST_FILE_NAME).unwrap()).unwrap();
+        assert_eq!(manifest.accession_id, 1);
+        assert_eq!(manifest.entries, 2);
+        assert_eq!(manifest.generated_by, "someuser@gmail.com");
+        let mut csv = String::new();
+        archive
+            .by_name(&manifest.file_name)
+            .unwrap()
+            .read_to_string(&mut csv)
+            .unwrap();
+        assert_eq!(manifest.sha256, format!("{:x}", Sha256::digest(&csv)));
+        assert!(csv.starts_with("recorded_at,source,event,"));
+    }
+
+    #[tokio::test]
+    async fn export_access_log_of_another_organization() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/accessions/1/access-log")
+                    .header(
+                        http::header::COOKIE,
+                        format!("jwt={}", get_mock_jwt_for_organization(2)),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn export_audit_log_as_partner_admin() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/audit-log/export")
+                    .header(
+                        http::header::COOKIE,
+                        format!("jwt={}", get_mock_jwt_for_organization(2)),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn get_review_queue() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/review-queue?per_page=10")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ReviewQueueResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.per_page, 10);
+        assert_eq!(actual.items.len(), 1);
+        assert_eq!(actual.items[0].kind, ReviewQueueItemKind::BadCapture);
+        assert_eq!(
+            actual.items[0].reason,
+            Some("Seed page has no title".to_string())
+        );
+        let actions: Vec<&str> = actual.items[0]
+            .actions
+            .iter()
+            .map(|action| action.action.as_str())
+            .collect();
+        assert_eq!(actions, vec!["view", "accept_capture", "delete"]);
+        assert!(actual.failed_crawls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_review_queue_as_partner_admin() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/review-queue")
+                    .header(
+                        http::header::COOKIE,
+                        format!("jwt={}", get_mock_jwt_for_organization(2)),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn get_pipeline_status() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/pipeline")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: PipelineStatusResponse = serde_json::from_slice(&body).unwrap();
+        let expected = PipelineStatusResponse {
+            crawls_in_progress: vec![],
+            completed_crawls: 0,
+            average_crawl_duration_secs: None,
+            poll_retries: 0,
+            max_active_crawls: 2,
+            queue_depth: Some(0),
+            queued_crawls: vec![],
+            recent_failures: vec![],
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn get_scheduler_status() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/scheduler")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: SchedulerStatusResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual, SchedulerStatusResponse { tasks: vec![] });
+    }
+
+    #[tokio::test]
+    async fn get_scheduler_leader() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/scheduler/leader")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: SchedulerLeaderResponse = serde_json::from_slice(&body).unwrap();
+        // the test app never campaigns
+        let expected = SchedulerLeaderResponse {
+            instance: "api-1".to_string(),
+            is_leader: false,
+            leader: None,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn list_users_shows_email_status() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/users")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListUsersResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.items[0].email_status, EmailStatus::HardBounce);
+    }
+
+    #[tokio::test]
+    async fn start_s3_backfill_requires_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/admin/backfill-s3")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn start_s3_backfill() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/admin/backfill-s3")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: S3BackfillStatusResponse = serde_json::from_slice(&body).unwrap();
+        assert!(actual.running);
+        assert!(actual.started_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn s3_backfill_reports_progress() {
+        let app = build_test_app();
+        let request = |method| {
+            Request::builder()
+                .method(method)
+                .uri("/api/v1/admin/backfill-s3")
+                .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app
+            .clone()
+            .oneshot(request(http::Method::POST))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let mut status = None;
+        for _ in 0..50 {
+            let response = app
+                .clone()
+                .oneshot(request(http::Method::GET))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let actual: S3BackfillStatusResponse = serde_json::from_slice(&body).unwrap();
+            if !actual.running {
+                status = Some(actual);
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let status = status.expect("Backfill should finish");
+        assert_eq!((status.total, status.transferred), (1, 1));
+        assert!(status.failures.is_empty());
+        assert!(status.finished_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn start_static_export_requires_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/admin/static-export")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn static_export_reports_progress() {
+        let app = build_test_app();
+        let request = |method| {
+            Request::builder()
+                .method(method)
+                .uri("/api/v1/admin/static-export")
+                .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app
+            .clone()
+            .oneshot(request(http::Method::POST))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let mut status = None;
+        for _ in 0..50 {
+            let response = app
+                .clone()
+                .oneshot(request(http::Method::GET))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let actual: StaticExportStatusResponse = serde_json::from_slice(&body).unwrap();
+            if !actual.running {
+                status = Some(actual);
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let status = status.expect("Export should finish");
+        assert_eq!((status.exported, status.pages), (1, 1));
+        assert_eq!(status.error, None);
+        assert!(status.finished_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn start_reindex_requires_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/admin/reindex")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn reindex_reports_progress() {
+        let app = build_test_app();
+        let request = |method| {
+            Request::builder()
+                .method(method)
+                .uri("/api/v1/admin/reindex")
+                .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app
+            .clone()
+            .oneshot(request(http::Method::POST))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let mut status = None;
+        for _ in 0..50 {
+            let response = app
+                .clone()
+                .oneshot(request(http::Method::GET))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let actual: ReindexStatusResponse = serde_json::from_slice(&body).unwrap();
+            if !actual.running {
+                status = Some(actual);
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        let status = status.expect("Reindex should finish");
+        assert_eq!((status.total, status.refreshed), (1, 1));
+        assert!(status.indexes_rebuilt);
+        assert_eq!(status.error, None);
+    }
+
+    fn impersonate_request(user_id: Uuid, jwt: &str) -> Request<Body> {
+        Request::builder()
+            .method(http::Method::POST)
+            .uri(format!("/api/v1/admin/impersonate/{user_id}"))
+            .header(http::header::COOKIE, format!("jwt={jwt}"))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn impersonate_user() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(impersonate_request(MOCK_RESEARCHER_ID, &get_mock_jwt()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let jwt = response
+            .headers()
+            .get_all(http::header::SET_COOKIE)
+            .iter()
+            .find_map(|cookie| cookie.to_str().unwrap().strip_prefix("jwt="))
+            .and_then(|cookie| cookie.split(';').next())
+            .expect("Should set a session cookie")
+            .to_string();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ImpersonationResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.user_id, MOCK_RESEARCHER_ID);
+        assert_eq!(actual.email, "researcher@example.com");
+        assert_eq!(actual.role, Role::Researcher);
+        let support_window = actual.expires_at - Utc::now().naive_utc();
+        assert!(support_window <= chrono::Duration::minutes(30));
+        assert!(support_window > chrono::Duration::minutes(29));
+
+        let claims = JWT_KEYS
+            .decode::<JWTClaims>(&jwt, &Validation::default())
+            .unwrap()
+            .claims;
+        assert_eq!(claims.sub, "researcher@example.com");
+        assert_eq!(claims.role, Role::Researcher);
+        assert_eq!(claims.impersonator.as_deref(), Some("someuser@gmail.com"));
+    }
+
+    #[tokio::test]
+    async fn impersonate_user_refuses_admins() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(impersonate_request(Uuid::new_v4(), &get_mock_jwt()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn impersonate_user_without_admin_role() {
+        let app = build_test_app();
+        let claims = JWTClaims {
+            sub: "researcher@example.com".to_string(),
+            exp: (Utc::now() + chrono::Duration::hours(1)).timestamp() as usize,
+            role: Role::Researcher,
+            impersonator: None,
+            organization_id: DEFAULT_ORGANIZATION_ID,
+        };
+        let jwt = JWT_KEYS.encode(&claims).unwrap();
+        let response = app
+            .oneshot(impersonate_request(MOCK_RESEARCHER_ID, &jwt))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    fn admin_jwt(email: &str, valid_for: chrono::Duration) -> String {
+        let claims = JWTClaims {
+            sub: email.to_string(),
+            exp: (Utc::now() + valid_for).timestamp() as usize,
+            role: Role::Admin,
+            impersonator: None,
+            organization_id: DEFAULT_ORGANIZATION_ID,
+        };
+        JWT_KEYS.encode(&claims).unwrap()
+    }
+
+    fn deactivate_request(user_id: Uuid, jwt: &str) -> Request<Body> {
+        Request::builder()
+            .method(http::Method::POST)
+            .uri(format!("/api/v1/admin/users/{user_id}/deactivate"))
+            .header(http::header::COOKIE, format!("jwt={jwt}"))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn deactivate_user() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(deactivate_request(MOCK_RESEARCHER_ID, &get_mock_jwt()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn deactivate_user_refuses_the_admin_themselves() {
+        let app = build_test_app();
+        // the mock repo names every user but the researcher test@example.com
+        let jwt = admin_jwt("test@example.com", chrono::Duration::hours(1));
+        let response = app
+            .oneshot(deactivate_request(Uuid::new_v4(), &jwt))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn deactivated_users_are_signed_out() {
+        let app = build_test_app();
+        let list_users = |jwt: String| {
+            Request::builder()
+                .uri("/api/v1/admin/users")
+                .header(http::header::COOKIE, format!("jwt={jwt}"))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app
+            .clone()
+            .oneshot(list_users(admin_jwt(
+                MOCK_DEACTIVATED_EMAIL,
+                chrono::Duration::hours(1),
+            )))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // sessions about to expire aren't worth the lookup
+        let response = app
+            .oneshot(list_users(admin_jwt(
+                MOCK_DEACTIVATED_EMAIL,
+                chrono::Duration::minutes(5),
+            )))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
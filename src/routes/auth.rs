@@ -5,15 +5,16 @@
 //! The module uses an authentication service to handle the authentication logic.
 
 use crate::app_factory::AppState;
+use crate::client_ip::ClientIp;
 use crate::models::auth::AuthenticatedUser;
-use crate::models::request::{AuthorizeRequest, LoginRequest};
-use crate::models::response::CreateApiKeyResponse;
+use crate::models::request::{AuthorizeRequest, LoginRequest, UpdateActivityDigestRequest};
+use crate::models::response::{CreateApiKeyResponse, MessageResponse};
 use ::entity::sea_orm_active_enums::Role;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use axum::routing::{get, post};
-use axum::{Json, Router};
+use axum::routing::{get, post, put};
+use axum::{Extension, Json, Router};
 use tracing::{error, info};
 use uuid::Uuid;
 use validator::Validate;
@@ -25,7 +26,8 @@ pub fn get_auth_routes() -> Router<AppState> {
             .route("/", post(login))
             .route("/authorize", post(authorize))
             .route("/", get(verify))
-            .route("/{:user_id}/api-key", post(create_api_key)),
+            .route("/activity-digest", put(set_activity_digest))
+            .route("/{user_id}/api-key", post(create_api_key)),
     )
 }
 
@@ -35,17 +37,22 @@ pub fn get_auth_routes() -> Router<AppState> {
     tag = "Auth",
     request_body = LoginRequest,
     responses(
-        (status = 200, description = "OK"),
+        (status = 200, description = "OK", content((String = "text/plain"), (MessageResponse = "application/json"))),
         (status = 400, description = "Bad request"),
         (status = 500, description = "Internal server error")
     )
 )]
-async fn login(State(state): State<AppState>, Json(payload): Json<LoginRequest>) -> Response {
+async fn login(
+    State(state): State<AppState>,
+    client: Option<Extension<ClientIp>>,
+    Json(payload): Json<LoginRequest>,
+) -> Response {
     if let Err(err) = payload.validate() {
         return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
     }
 
-    let login_result = state.auth_service.clone().login(payload).await;
+    let client = client.map(|Extension(client)| client.key.to_string());
+    let login_result = state.auth_service.clone().login(payload, client).await;
 
     match login_result {
         Ok(response) => response,
@@ -89,8 +96,9 @@ async fn authorize(
     path = "/api/v1/auth",
     tag = "Auth",
     responses(
-        (status = 200, description = "OK", body = String),
-        (status = 401, description = "Unauthorized")
+        (status = 200, description = "OK", content((String = "text/plain"), (MessageResponse = "application/json"))),
+        (status = 400, description = "Invalid API key or session cookie"),
+        (status = 401, description = "No credentials, or the session expired")
     ),
     security(
         ("jwt_cookie_auth" = []),
@@ -102,6 +110,32 @@ async fn verify(State(_state): State<AppState>, authenticated_user: Authenticate
     (StatusCode::OK, user_data).into_response()
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/auth/activity-digest",
+    tag = "Auth",
+    request_body = UpdateActivityDigestRequest,
+    responses(
+        (status = 200, description = "OK"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "User not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn set_activity_digest(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<UpdateActivityDigestRequest>,
+) -> Response {
+    state
+        .auth_service
+        .set_activity_digest(&authenticated_user, payload)
+        .await
+}
+
 #[utoipa::path(
     post,
     path = "/api/v1/auth/{user_id}/api-key",
@@ -109,6 +143,7 @@ async fn verify(State(_state): State<AppState>, authenticated_user: Authenticate
     responses(
         (status = 201, description = "API key created", body = CreateApiKeyResponse),
         (status = 403, description = "Forbidden"),
+        (status = 404, description = "User not found"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -125,18 +160,18 @@ async fn create_api_key(
         return (StatusCode::FORBIDDEN, "Only admins can create API keys").into_response();
     }
 
-    let api_key_result = state.auth_service.create_api_key(user_id).await;
+    let api_key_result = state
+        .auth_service
+        .create_api_key(&authenticated_user, user_id)
+        .await;
 
     match api_key_result {
-        Ok(api_key_secret) => {
+        Ok(None) => (StatusCode::NOT_FOUND, "User not found").into_response(),
+        Ok(Some(api_key_secret)) => {
             info!(
                 "API key created by admin {} for user {}",
                 authenticated_user.user_id, user_id
             );
-            let auth_service = state.auth_service.clone();
-            tokio::spawn(async move {
-                auth_service.delete_expired_api_keys().await;
-            });
             let response = CreateApiKeyResponse { api_key_secret };
             (StatusCode::CREATED, Json(response)).into_response()
         }
@@ -159,6 +194,7 @@ mod tests {
         body::Body,
         http::{Request, StatusCode},
     };
+    use http::HeaderValue;
     use http_body_util::BodyExt;
     use serde_json::json;
     use tower::ServiceExt;
@@ -167,10 +203,10 @@ mod tests {
     // Import JWT creation utilities
     use crate::auth::JWT_KEYS;
     use crate::models::auth::JWTClaims;
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
     use crate::test_tools::get_mock_jwt;
     use ::entity::sea_orm_active_enums::Role;
     use chrono::Utc;
-    use jsonwebtoken::{encode, Header};
 
     #[tokio::test]
     async fn login_with_valid_email() {
@@ -271,6 +307,7 @@ mod tests {
         let actual = String::from_utf8((&body).to_vec()).unwrap();
         assert!(actual.contains("Verifying your account"));
         assert!(actual.contains("someuser@gmail.com"));
+        assert!(actual.contains("Auth method: cookie"));
     }
 
     #[tokio::test]
@@ -286,7 +323,7 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
 
     #[tokio::test]
@@ -322,9 +359,10 @@ mod tests {
             sub: "researcher@gmail.com".to_string(),
             exp: expiry_time.timestamp() as usize,
             role: Role::Researcher,
+            impersonator: None,
+            organization_id: DEFAULT_ORGANIZATION_ID,
         };
-        let jwt =
-            encode(&Header::default(), &claims, &JWT_KEYS.encoding).expect("Failed to encode JWT");
+        let jwt = JWT_KEYS.encode(&claims).expect("Failed to encode JWT");
 
         let response = app
             .oneshot(
@@ -360,7 +398,7 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
 
     #[tokio::test]
@@ -429,5 +467,69 @@ mod tests {
         assert!(actual.contains("Verifying your account"));
         // With API key auth, the user_id is the email from the API key info
         assert!(actual.contains("test@example.com"));
+        assert!(actual.contains("Auth method: api_key"));
+    }
+
+    #[tokio::test]
+    async fn verify_prefers_api_key_over_cookie() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/auth")
+                    .header("X-Api-Key", "mock_api_key_secret")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual = String::from_utf8(body.to_vec()).unwrap();
+        assert!(actual.contains("UserId: test@example.com"));
+        assert!(actual.contains("Auth method: api_key"));
+    }
+
+    #[tokio::test]
+    async fn verify_does_not_fall_back_to_cookie_for_bad_api_key() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/auth")
+                    .header("X-Api-Key", HeaderValue::from_bytes(b"\xffkey").unwrap())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn subscribe_to_activity_digest() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/auth/activity-digest")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"enabled": true})).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "Subscribed to the activity digest");
     }
 }
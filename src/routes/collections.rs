@@ -0,0 +1,384 @@
+//! Routes for curated collections of accessions.
+//! A collection groups accessions, e.g. every capture about one event, so they can be
+//! exported together as a ZIP, e.g. to hand captures out offline, see
+//! [`crate::collection_export`].
+//!
+//! This module provides HTTP endpoints for making collections, adding and removing
+//! accessions and starting exports.
+//! It uses in-memory repositories for testing to avoid I/O operations.
+
+use crate::app_factory::AppState;
+use crate::auth::validate_at_least_researcher;
+use crate::collection_export::COLLECTION_EXPORTS_FLAG;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::error::ApiError;
+use crate::models::request::CreateCollectionRequest;
+use crate::models::response::{CollectionExportResponse, CollectionResponse};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post, put};
+use axum::{Json, Router};
+use validator::Validate;
+
+/// Creates routes for collection endpoints under `/collections`.
+pub fn get_collections_routes() -> Router<AppState> {
+    Router::new()
+        .route("/collections", post(create_collection))
+        .route("/collections/{collection_id}", get(get_collection))
+        .route(
+            "/collections/{collection_id}/accessions/{accession_id}",
+            put(add_collection_accession),
+        )
+        .route(
+            "/collections/{collection_id}/accessions/{accession_id}",
+            delete(remove_collection_accession),
+        )
+        .route(
+            "/collections/{collection_id}/export",
+            post(export_collection),
+        )
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/collections",
+    tag = "Collections",
+    request_body = CreateCollectionRequest,
+    responses(
+        (status = 201, description = "Created", body = CollectionResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn create_collection(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<CreateCollectionRequest>,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return ApiError::validation(err).into_response();
+    }
+    state
+        .collections_service
+        .create_one(payload, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/collections/{collection_id}",
+    tag = "Collections",
+    params(
+        ("collection_id" = i32, Path, description = "Collection ID")
+    ),
+    responses(
+        (status = 200, description = "OK", body = CollectionResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn get_collection(
+    State(state): State<AppState>,
+    Path(collection_id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .collections_service
+        .get_one(collection_id, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/collections/{collection_id}/accessions/{accession_id}",
+    tag = "Collections",
+    params(
+        ("collection_id" = i32, Path, description = "Collection ID"),
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
+    responses(
+        (status = 200, description = "Accession added to collection"),
+        (status = 400, description = "Accession belongs to another organization"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn add_collection_accession(
+    State(state): State<AppState>,
+    Path((collection_id, accession_id)): Path<(i32, i32)>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .collections_service
+        .add_accession(collection_id, accession_id, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/collections/{collection_id}/accessions/{accession_id}",
+    tag = "Collections",
+    params(
+        ("collection_id" = i32, Path, description = "Collection ID"),
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
+    responses(
+        (status = 200, description = "Accession removed from collection"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn remove_collection_accession(
+    State(state): State<AppState>,
+    Path((collection_id, accession_id)): Path<(i32, i32)>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .collections_service
+        .remove_accession(collection_id, accession_id, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/collections/{collection_id}/export",
+    tag = "Collections",
+    params(
+        ("collection_id" = i32, Path, description = "Collection ID")
+    ),
+    responses(
+        (status = 202, description = "Export started, a link to it will be emailed", body = CollectionExportResponse),
+        (status = 400, description = "Collection has no public accessions to export"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found, or exports aren't turned on for the caller"),
+        (status = 413, description = "Collection is too large to export")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn export_collection(
+    State(state): State<AppState>,
+    Path(collection_id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    if let Err(response) = state
+        .flags_service
+        .require_enabled(COLLECTION_EXPORTS_FLAG, Some(&authenticated_user.role))
+        .await
+    {
+        return response;
+    }
+    state
+        .collections_service
+        .start_export(collection_id, authenticated_user)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::response::{CollectionExportResponse, CollectionResponse};
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::services::flags_service::FlagsService;
+    use crate::test_tools::{
+        build_test_app, build_test_app_with_flags_service, build_test_flags_service, get_mock_jwt,
+        get_mock_jwt_for_organization,
+    };
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    fn collection_request(method: http::Method, uri: &str, jwt: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header(http::header::COOKIE, format!("jwt={jwt}"))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn create_collection() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/collections")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"title": "Market fires"})).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: CollectionResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.title, "Market fires");
+        assert_eq!(actual.organization_id, DEFAULT_ORGANIZATION_ID);
+        assert_eq!(actual.accession_ids, Vec::<i32>::new());
+    }
+
+    #[tokio::test]
+    async fn get_collection_only_in_its_organization() {
+        let app = build_test_app();
+        let response = app
+            .clone()
+            .oneshot(collection_request(
+                http::Method::GET,
+                "/api/v1/collections/1",
+                &get_mock_jwt(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: CollectionResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.accession_ids, vec![1, 2]);
+
+        let response = app
+            .oneshot(collection_request(
+                http::Method::GET,
+                "/api/v1/collections/1",
+                &get_mock_jwt_for_organization(DEFAULT_ORGANIZATION_ID + 1),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn add_and_remove_collection_accession() {
+        let app = build_test_app();
+        let response = app
+            .clone()
+            .oneshot(collection_request(
+                http::Method::PUT,
+                "/api/v1/collections/1/accessions/1",
+                &get_mock_jwt(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(collection_request(
+                http::Method::DELETE,
+                "/api/v1/collections/1/accessions/1",
+                &get_mock_jwt(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(collection_request(
+                http::Method::DELETE,
+                "/api/v1/collections/1/accessions/3",
+                &get_mock_jwt(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn export_collection_leaves_out_private_accessions() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(collection_request(
+                http::Method::POST,
+                "/api/v1/collections/1/export",
+                &get_mock_jwt(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: CollectionExportResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            actual,
+            CollectionExportResponse {
+                collection_id: 1,
+                accessions: 1,
+                left_out: 1,
+                email: "someuser@gmail.com".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn export_collection_only_where_exports_are_turned_on() {
+        // the mock flag only turns exports on in the test environment
+        let app = build_test_app_with_flags_service(FlagsService {
+            environment: "production".to_string(),
+            ..build_test_flags_service()
+        });
+        let response = app
+            .oneshot(collection_request(
+                http::Method::POST,
+                "/api/v1/collections/1/export",
+                &get_mock_jwt(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn export_missing_collection() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(collection_request(
+                http::Method::POST,
+                "/api/v1/collections/2/export",
+                &get_mock_jwt(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,362 @@
+//! Routes for curators' comments on accessions.
+//! Each accession has an internal discussion thread for its organization's researchers and
+//! admins, where writing `@` and a curator's email emails them.
+//!
+//! This module provides HTTP endpoints for listing, writing, editing and removing comments.
+//! It uses in-memory repositories for testing to avoid I/O operations.
+
+use crate::app_factory::AppState;
+use crate::auth::validate_at_least_researcher;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::error::ApiError;
+use crate::models::request::CommentRequest;
+use crate::models::response::{CommentResponse, ListCommentsResponse};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post, put};
+use axum::{Json, Router};
+use validator::Validate;
+
+/// Creates routes for comment endpoints under `/accessions/{accession_id}/comments`.
+pub fn get_comments_routes() -> Router<AppState> {
+    Router::new()
+        .route("/accessions/{accession_id}/comments", get(list_comments))
+        .route("/accessions/{accession_id}/comments", post(create_comment))
+        .route(
+            "/accessions/{accession_id}/comments/{comment_id}",
+            put(update_comment),
+        )
+        .route(
+            "/accessions/{accession_id}/comments/{comment_id}",
+            delete(delete_comment),
+        )
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/{accession_id}/comments",
+    tag = "Comments",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
+    responses(
+        (status = 200, description = "OK", body = ListCommentsResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_comments(
+    State(state): State<AppState>,
+    Path(accession_id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .accessions_service
+        .list_comments(accession_id, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/accessions/{accession_id}/comments",
+    tag = "Comments",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
+    request_body = CommentRequest,
+    responses(
+        (status = 201, description = "Created", body = CommentResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn create_comment(
+    State(state): State<AppState>,
+    Path(accession_id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<CommentRequest>,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return ApiError::validation(err).into_response();
+    }
+    state
+        .accessions_service
+        .create_comment(accession_id, payload, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/accessions/{accession_id}/comments/{comment_id}",
+    tag = "Comments",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID"),
+        ("comment_id" = i32, Path, description = "Comment ID")
+    ),
+    request_body = CommentRequest,
+    responses(
+        (status = 200, description = "OK", body = CommentResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden, including for anyone but the author"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn update_comment(
+    State(state): State<AppState>,
+    Path((accession_id, comment_id)): Path<(i32, i32)>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<CommentRequest>,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return ApiError::validation(err).into_response();
+    }
+    state
+        .accessions_service
+        .update_comment(accession_id, comment_id, payload, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/accessions/{accession_id}/comments/{comment_id}",
+    tag = "Comments",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID"),
+        ("comment_id" = i32, Path, description = "Comment ID")
+    ),
+    responses(
+        (status = 200, description = "Comment removed"),
+        (status = 403, description = "Forbidden, including for anyone but the author or an admin"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn delete_comment(
+    State(state): State<AppState>,
+    Path((accession_id, comment_id)): Path<(i32, i32)>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .accessions_service
+        .delete_comment(accession_id, comment_id, authenticated_user)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::auth::JWT_KEYS;
+    use crate::models::auth::JWTClaims;
+    use crate::models::response::{CommentResponse, ListCommentsResponse};
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::test_tools::{build_test_app, get_mock_jwt, mock_comments};
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use chrono::Utc;
+    use entity::sea_orm_active_enums::Role;
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    fn jwt(role: Role) -> String {
+        let claims = JWTClaims {
+            sub: "someuser@gmail.com".to_string(),
+            exp: (Utc::now() + chrono::Duration::hours(1)).timestamp() as usize,
+            role,
+            impersonator: None,
+            organization_id: DEFAULT_ORGANIZATION_ID,
+        };
+        JWT_KEYS.encode(&claims).unwrap()
+    }
+
+    fn comment_request(method: http::Method, uri: &str, jwt: &str, body: Value) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header(http::header::COOKIE, format!("jwt={jwt}"))
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    }
+
+    fn delete_request(uri: &str, jwt: &str) -> Request<Body> {
+        Request::builder()
+            .method(http::Method::DELETE)
+            .uri(uri)
+            .header(http::header::COOKIE, format!("jwt={jwt}"))
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn list_comments() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1/comments")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListCommentsResponse = serde_json::from_slice(&body).unwrap();
+        let expected: Vec<CommentResponse> = mock_comments(1).into_iter().map(Into::into).collect();
+        assert_eq!(actual.items, expected);
+    }
+
+    #[tokio::test]
+    async fn list_comments_needs_researcher_role() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1/comments")
+                    .header(
+                        http::header::COOKIE,
+                        format!("jwt={}", jwt(Role::Contributor)),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn create_comment() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(comment_request(
+                http::Method::POST,
+                "/api/v1/accessions/1/comments",
+                &get_mock_jwt(),
+                json!({"body": "@otheruser@gmail.com can you check the date?"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: CommentResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.accession_id, 1);
+        assert_eq!(actual.author_email, "someuser@gmail.com");
+        assert!(!actual.markdown);
+    }
+
+    #[tokio::test]
+    async fn create_comment_rejects_html_in_markdown() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(comment_request(
+                http::Method::POST,
+                "/api/v1/accessions/1/comments",
+                &get_mock_jwt(),
+                json!({"body": "<script>alert(1)</script>", "markdown": true}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn update_comment_by_its_author_only() {
+        let app = build_test_app();
+        let body = json!({"body": "Fixed the title", "markdown": true});
+        let response = app
+            .clone()
+            .oneshot(comment_request(
+                http::Method::PUT,
+                "/api/v1/accessions/1/comments/1",
+                &jwt(Role::Researcher),
+                body.clone(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response_body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: CommentResponse = serde_json::from_slice(&response_body).unwrap();
+        assert_eq!(actual.body, "Fixed the title");
+        assert!(actual.markdown);
+
+        let response = app
+            .oneshot(comment_request(
+                http::Method::PUT,
+                "/api/v1/accessions/1/comments/2",
+                &get_mock_jwt(),
+                body,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn delete_comment_by_its_author_or_an_admin() {
+        let app = build_test_app();
+        let response = app
+            .clone()
+            .oneshot(delete_request(
+                "/api/v1/accessions/1/comments/2",
+                &jwt(Role::Researcher),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        for (comment_id, role) in [(1, Role::Researcher), (2, Role::Admin)] {
+            let response = app
+                .clone()
+                .oneshot(delete_request(
+                    &format!("/api/v1/accessions/1/comments/{comment_id}"),
+                    &jwt(role),
+                ))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = app
+            .oneshot(delete_request(
+                "/api/v1/accessions/1/comments/3",
+                &get_mock_jwt(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,252 @@
+//! Routes for managing the global crawl blocklist.
+//! The blocklist holds URL patterns that must never be crawled, for legal or safety reasons.
+//! It applies to every organization, so only platform admins can change or see it.
+//!
+//! This module provides HTTP endpoints for adding, listing and removing patterns. Crawl
+//! requests are checked against it in [`crate::routes::accessions`]. It uses in-memory
+//! repositories for testing to avoid I/O operations.
+
+use crate::app_factory::AppState;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::request::CreateCrawlBlocklistEntryRequest;
+use crate::models::response::{CrawlBlocklistEntryResponse, ListCrawlBlocklistResponse};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use validator::Validate;
+
+/// Creates routes for crawl blocklist endpoints under `/admin/crawl-blocklist`.
+pub fn get_crawl_blocklist_routes() -> Router<AppState> {
+    Router::new().nest(
+        "/admin/crawl-blocklist",
+        Router::new()
+            .route("/", get(list_crawl_blocklist))
+            .route("/", post(create_crawl_blocklist_entry))
+            .route("/{entry_id}", delete(delete_crawl_blocklist_entry)),
+    )
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/crawl-blocklist",
+    tag = "Crawl blocklist",
+    request_body = CreateCrawlBlocklistEntryRequest,
+    responses(
+        (status = 201, description = "Created", body = CrawlBlocklistEntryResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn create_crawl_blocklist_entry(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<CreateCrawlBlocklistEntryRequest>,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state.crawl_blocklist_service.create_one(payload).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/crawl-blocklist",
+    tag = "Crawl blocklist",
+    responses(
+        (status = 200, description = "OK", body = ListCrawlBlocklistResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_crawl_blocklist(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.crawl_blocklist_service.list().await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/crawl-blocklist/{entry_id}",
+    tag = "Crawl blocklist",
+    responses(
+        (status = 200, description = "Crawl blocklist entry deleted"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn delete_crawl_blocklist_entry(
+    State(state): State<AppState>,
+    Path(entry_id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.crawl_blocklist_service.delete_one(entry_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::error::ErrorResponse;
+    use crate::models::response::{CrawlBlocklistEntryResponse, ListCrawlBlocklistResponse};
+    use crate::test_tools::{build_test_app, get_mock_jwt, get_mock_jwt_for_organization};
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn list_crawl_blocklist() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/crawl-blocklist")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListCrawlBlocklistResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.items.len(), 1);
+        assert_eq!(actual.items[0].pattern, "*.blocked.example");
+    }
+
+    #[tokio::test]
+    async fn partner_admins_cannot_see_crawl_blocklist() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/crawl-blocklist")
+                    .header(
+                        http::header::COOKIE,
+                        format!("jwt={}", get_mock_jwt_for_organization(2)),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn create_crawl_blocklist_entry() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/admin/crawl-blocklist")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "pattern": "https://www.Example.com/private/",
+                            "reason": "Court order"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: CrawlBlocklistEntryResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.pattern, "example.com/private/");
+        assert_eq!(actual.reason, "Court order");
+    }
+
+    #[tokio::test]
+    async fn create_crawl_blocklist_entry_invalid_pattern() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/admin/crawl-blocklist")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"pattern": "/private", "reason": "Unsafe"}))
+                            .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn create_crawl_blocklist_entry_invalid_pattern_as_json() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/admin/crawl-blocklist")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::ACCEPT, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"pattern": "/private", "reason": "Unsafe"}))
+                            .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.error.code, "bad_request");
+        assert!(actual.error.messages.is_some());
+    }
+
+    #[tokio::test]
+    async fn delete_crawl_blocklist_entry() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri("/api/v1/admin/crawl-blocklist/1")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,335 @@
+//! Routes for managing feature flags.
+//! Feature flags let admins roll risky features out gradually, per environment or per role,
+//! so they are only available to admins.
+//!
+//! This module provides HTTP endpoints for creating, listing, updating and deleting flags.
+//! It uses in-memory repositories for testing to avoid I/O operations.
+
+use crate::app_factory::AppState;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::request::{CreateFeatureFlagRequest, UpdateFeatureFlagRequest};
+use crate::models::response::{
+    EnabledFeatureFlagsResponse, FeatureFlagResponse, ListFeatureFlagsResponse,
+};
+use ::entity::sea_orm_active_enums::Role;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post, put};
+use axum::{Json, Router};
+use validator::Validate;
+
+/// Creates routes for feature flag endpoints under `/admin/feature-flags`, plus
+/// `/feature-flags` for users to see which flags are on for them.
+pub fn get_feature_flags_routes() -> Router<AppState> {
+    Router::new()
+        .nest(
+            "/admin/feature-flags",
+            Router::new()
+                .route("/", get(list_feature_flags))
+                .route("/", post(create_feature_flag))
+                .route("/{name}", put(update_feature_flag))
+                .route("/{name}", delete(delete_feature_flag)),
+        )
+        .route("/feature-flags", get(list_enabled_feature_flags))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/feature-flags",
+    tag = "Feature flags",
+    responses(
+        (status = 200, description = "OK", body = EnabledFeatureFlagsResponse)
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_enabled_feature_flags(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    state
+        .flags_service
+        .list_enabled(authenticated_user.role)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/feature-flags",
+    tag = "Feature flags",
+    request_body = CreateFeatureFlagRequest,
+    responses(
+        (status = 201, description = "Created", body = FeatureFlagResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn create_feature_flag(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<CreateFeatureFlagRequest>,
+) -> Response {
+    if authenticated_user.role != Role::Admin {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state.flags_service.create_one(payload).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/feature-flags",
+    tag = "Feature flags",
+    responses(
+        (status = 200, description = "OK", body = ListFeatureFlagsResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_feature_flags(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if authenticated_user.role != Role::Admin {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.flags_service.list().await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/feature-flags/{name}",
+    tag = "Feature flags",
+    request_body = UpdateFeatureFlagRequest,
+    responses(
+        (status = 200, description = "OK", body = FeatureFlagResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn update_feature_flag(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<UpdateFeatureFlagRequest>,
+) -> Response {
+    if authenticated_user.role != Role::Admin {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state.flags_service.update_one(name, payload).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/feature-flags/{name}",
+    tag = "Feature flags",
+    responses(
+        (status = 200, description = "Feature flag deleted"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn delete_feature_flag(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if authenticated_user.role != Role::Admin {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.flags_service.delete_one(name).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::response::{
+        EnabledFeatureFlagsResponse, FeatureFlagResponse, ListFeatureFlagsResponse,
+    };
+    use crate::test_tools::{build_test_app, get_mock_jwt};
+    use ::entity::sea_orm_active_enums::Role;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn list_feature_flags_no_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/feature-flags")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn list_enabled_feature_flags() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/feature-flags")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: EnabledFeatureFlagsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            actual,
+            EnabledFeatureFlagsResponse {
+                names: vec![
+                    "collection-exports".to_string(),
+                    "wacz-full-text".to_string()
+                ]
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn list_feature_flags() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/feature-flags")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListFeatureFlagsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.items.len(), 2);
+        assert_eq!(actual.items[0].name, "wacz-full-text".to_string());
+    }
+
+    #[tokio::test]
+    async fn create_feature_flag() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/admin/feature-flags")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "name": "new-search",
+                            "enabled": true,
+                            "environments": ["staging"],
+                            "roles": ["Admin"]
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: FeatureFlagResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.name, "new-search".to_string());
+        assert_eq!(actual.environments, vec!["staging".to_string()]);
+        assert_eq!(actual.roles, vec![Role::Admin]);
+    }
+
+    #[tokio::test]
+    async fn create_feature_flag_bad_name() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/admin/feature-flags")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"name": "New Search"})).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn update_feature_flag() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/admin/feature-flags/wacz-full-text")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"enabled": false})).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: FeatureFlagResponse = serde_json::from_slice(&body).unwrap();
+        assert!(!actual.enabled);
+    }
+
+    #[tokio::test]
+    async fn delete_feature_flag() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri("/api/v1/admin/feature-flags/wacz-full-text")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
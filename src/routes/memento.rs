@@ -0,0 +1,170 @@
+//! Routes implementing the Memento protocol, see [`crate::memento`].
+//!
+//! The original URL is the rest of the path, e.g. `/timegate/https://example.com/page`,
+//! as Memento clients expect.
+
+use crate::app_factory::AppState;
+use crate::memento::original_url;
+use axum::extract::{Path, RawQuery, State};
+use axum::http::HeaderMap;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+
+/// Creates the Memento TimeGate and TimeMap routes.
+pub fn get_memento_routes() -> Router<AppState> {
+    Router::new()
+        .route("/timegate/{*url}", get(memento_timegate))
+        .route("/timemap/link/{*url}", get(memento_timemap))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/timegate/{url}",
+    tag = "Memento",
+    params(
+        ("url" = String, Path, description = "Original URL, including any query string"),
+        ("Accept-Datetime" = Option<String>, Header, description = "HTTP date to find the closest capture to, defaults to the latest")
+    ),
+    responses(
+        (status = 302, description = "Redirect to the closest capture"),
+        (status = 400, description = "Accept-Datetime is not an HTTP date"),
+        (status = 404, description = "No public captures of the URL")
+    )
+)]
+async fn memento_timegate(
+    State(state): State<AppState>,
+    Path(url): Path<String>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+) -> Response {
+    let accept_datetime = headers
+        .get("accept-datetime")
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+    state
+        .accessions_service
+        .memento_timegate(original_url(&url, query.as_deref()), accept_datetime)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/timemap/link/{url}",
+    tag = "Memento",
+    params(
+        ("url" = String, Path, description = "Original URL, including any query string")
+    ),
+    responses(
+        (status = 200, description = "TimeMap of every public capture", body = String, content_type = "application/link-format"),
+        (status = 404, description = "No public captures of the URL")
+    )
+)]
+async fn memento_timemap(
+    State(state): State<AppState>,
+    Path(url): Path<String>,
+    RawQuery(query): RawQuery,
+) -> Response {
+    state
+        .accessions_service
+        .memento_timemap(original_url(&url, query.as_deref()))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_tools::build_test_app;
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn timegate_redirects_to_the_closest_capture() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/timegate/https://example.com/?utm_source=feed")
+                    .header("Accept-Datetime", "Thu, 02 May 2024 12:00:00 GMT")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FOUND);
+        let headers = response.headers();
+        assert_eq!(
+            headers[header::LOCATION],
+            "https://sudandigitalarchive.com/archive/1"
+        );
+        assert_eq!(headers[header::VARY], "accept-datetime");
+        assert_eq!(
+            headers[header::LINK],
+            "<https://example.com/?utm_source=feed>; rel=\"original\", \
+             <https://api.example.org/api/v1/timemap/link/https://example.com/?utm_source=feed>; \
+             rel=\"timemap\"; type=\"application/link-format\""
+        );
+    }
+
+    #[tokio::test]
+    async fn timegate_rejects_invalid_datetimes() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/timegate/https://example.com/")
+                    .header("Accept-Datetime", "yesterday")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn timemap_lists_captures() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/timemap/link/https://example.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/link-format"
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.starts_with("<https://example.com>; rel=\"original\",\n"));
+        assert!(body.ends_with(
+            "<https://sudandigitalarchive.com/archive/1>; rel=\"first last memento\"; \
+             datetime=\"Thu, 01 Jan 1970 00:00:00 GMT\"\n"
+        ));
+    }
+
+    #[tokio::test]
+    async fn timemap_is_not_found_for_unknown_urls() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/timemap/link/https://unknown.example.com/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
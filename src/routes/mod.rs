@@ -1,4 +1,18 @@
+pub mod accession_relations;
 pub mod accessions;
+pub mod admin;
 pub mod auth;
+pub mod collections;
+pub mod comments;
+pub mod crawl_blocklist;
+pub mod feature_flags;
 pub mod health;
+pub mod memento;
+pub mod organizations;
+pub mod public;
+pub mod stats;
 pub mod subjects;
+pub mod uploads;
+pub mod v2;
+pub mod webhooks;
+pub mod workflow_labels;
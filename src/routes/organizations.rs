@@ -0,0 +1,281 @@
+//! Routes for managing organizations.
+//!
+//! Organizations give partner archives their own users, accessions and subjects. Only
+//! platform admins, the admins of the archive's own organization, can manage them.
+//!
+//! This module provides HTTP endpoints for creating, listing and renaming organizations,
+//! and for moving users between them. It uses in-memory repositories for testing to avoid
+//! I/O operations.
+
+use crate::app_factory::AppState;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::request::{CreateOrganizationRequest, RenameOrganizationRequest};
+use crate::models::response::{ListOrganizationsResponse, OrganizationResponse};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Creates routes for organization endpoints under `/admin/organizations`.
+pub fn get_organizations_routes() -> Router<AppState> {
+    Router::new().nest(
+        "/admin/organizations",
+        Router::new()
+            .route("/", get(list_organizations))
+            .route("/", post(create_organization))
+            .route("/{organization_id}", put(rename_organization))
+            .route(
+                "/{organization_id}/users/{user_id}",
+                put(assign_user_to_organization),
+            ),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/organizations",
+    tag = "Organizations",
+    responses(
+        (status = 200, description = "OK", body = ListOrganizationsResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_organizations(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state.organizations_service.list().await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/organizations",
+    tag = "Organizations",
+    request_body = CreateOrganizationRequest,
+    responses(
+        (status = 201, description = "Created", body = OrganizationResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn create_organization(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<CreateOrganizationRequest>,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state.organizations_service.create_one(payload).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/organizations/{organization_id}",
+    tag = "Organizations",
+    params(
+        ("organization_id" = i32, Path, description = "Organization ID")
+    ),
+    request_body = RenameOrganizationRequest,
+    responses(
+        (status = 200, description = "OK", body = OrganizationResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn rename_organization(
+    State(state): State<AppState>,
+    Path(organization_id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<RenameOrganizationRequest>,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state
+        .organizations_service
+        .rename(organization_id, payload)
+        .await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/organizations/{organization_id}/users/{user_id}",
+    tag = "Organizations",
+    params(
+        ("organization_id" = i32, Path, description = "Organization ID"),
+        ("user_id" = Uuid, Path, description = "ID of the user to move")
+    ),
+    responses(
+        (status = 200, description = "User moved"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "User or organization not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn assign_user_to_organization(
+    State(state): State<AppState>,
+    Path((organization_id, user_id)): Path<(i32, Uuid)>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !authenticated_user.is_platform_admin() {
+        return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+    }
+    state
+        .organizations_service
+        .assign_user(organization_id, user_id)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::response::{ListOrganizationsResponse, OrganizationResponse};
+    use crate::test_tools::{build_test_app, get_mock_jwt, get_mock_jwt_for_organization};
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn list_organizations() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/organizations")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListOrganizationsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.items.len(), 1);
+        assert_eq!(actual.items[0].slug, "sudan-digital-archive");
+    }
+
+    #[tokio::test]
+    async fn partner_admins_cannot_manage_organizations() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/admin/organizations")
+                    .header(
+                        http::header::COOKIE,
+                        format!("jwt={}", get_mock_jwt_for_organization(2)),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn create_organization() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/admin/organizations")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "slug": "diaspora-archive",
+                            "name": "Diaspora Archive"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: OrganizationResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.slug, "diaspora-archive");
+        assert_eq!(actual.name, "Diaspora Archive");
+    }
+
+    #[tokio::test]
+    async fn create_organization_needs_a_slug() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/admin/organizations")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({
+                            "slug": "Diaspora Archive",
+                            "name": "Diaspora Archive"
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn assign_user_to_organization() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri(format!(
+                        "/api/v1/admin/organizations/2/users/{}",
+                        uuid::Uuid::new_v4()
+                    ))
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,183 @@
+//! Read-only routes for partners embedding archive search on their own sites.
+//!
+//! Mounted at `/public/v1`. These never look at the auth cookie or API keys, so browsers
+//! can call them from any origin without credentials, and they only ever return public
+//! accessions. Responses may be cached by browsers and CDNs, and the routes are rate
+//! limited harder than the rest of the API, see [`crate::app_factory`].
+
+use crate::app_factory::AppState;
+use crate::client_country::ClientCountry;
+use crate::i18n::PreferredLanguage;
+use crate::models::common::ListingVisibility;
+use crate::models::request::{AccessionPagination, SubjectPagination};
+use crate::models::response::{
+    GetOnePublicAccessionResponse, ListPublicAccessionsResponse, ListSubjectsArResponse,
+    ListSubjectsEnResponse,
+};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use axum_extra::extract::Query;
+use validator::Validate;
+
+/// Creates the public read-only routes.
+pub fn get_public_routes() -> Router<AppState> {
+    Router::new()
+        .route("/accessions", get(list_accessions))
+        .route("/accessions/{accession_id}", get(get_one_accession))
+        .route("/metadata-subjects", get(list_subjects))
+}
+
+#[utoipa::path(
+    get,
+    path = "/public/v1/accessions",
+    tag = "Public",
+    params(
+        AccessionPagination
+    ),
+    responses(
+        (status = 200, description = "OK", body = ListPublicAccessionsResponse),
+        (status = 400, description = "Bad request"),
+        (status = 429, description = "Too many requests")
+    )
+)]
+async fn list_accessions(
+    State(state): State<AppState>,
+    pagination: Query<AccessionPagination>,
+    PreferredLanguage(language): PreferredLanguage,
+) -> Response {
+    if let Err(err) = pagination.0.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state
+        .accessions_service
+        .list(pagination.0.into(), language, ListingVisibility::Public)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/public/v1/accessions/{accession_id}",
+    tag = "Public",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
+    responses(
+        (status = 200, description = "OK", body = GetOnePublicAccessionResponse),
+        (status = 404, description = "Not found"),
+        (status = 429, description = "Too many requests")
+    )
+)]
+async fn get_one_accession(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    ClientCountry(country): ClientCountry,
+    PreferredLanguage(language): PreferredLanguage,
+) -> Response {
+    state
+        .accessions_service
+        .get_one_public(id, country, language)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/public/v1/metadata-subjects",
+    tag = "Public",
+    params(
+        SubjectPagination
+    ),
+    responses(
+        (status = 200, description = "OK", body = ListSubjectsEnResponse, content_type = "application/json"),
+        (status = 200, description = "OK", body = ListSubjectsArResponse, content_type = "application/json"),
+        (status = 400, description = "Bad request"),
+        (status = 429, description = "Too many requests")
+    )
+)]
+async fn list_subjects(
+    State(state): State<AppState>,
+    pagination: Query<SubjectPagination>,
+) -> Response {
+    if let Err(err) = pagination.0.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state.subjects_service.list(pagination.0).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::response::{GetOnePublicAccessionResponse, ListPublicAccessionsResponse};
+    use crate::test_tools::{build_test_app, get_mock_jwt};
+    use axum::{
+        body::Body,
+        http::{self, Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn lists_accessions_cacheably_for_any_origin() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/public/v1/accessions?page=0&per_page=1")
+                    .header(http::header::ORIGIN, "https://partner.example.org")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(headers[http::header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert!(headers
+            .get(http::header::ACCESS_CONTROL_ALLOW_CREDENTIALS)
+            .is_none());
+        assert_eq!(headers[http::header::CACHE_CONTROL], "public, max-age=300");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListPublicAccessionsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.items.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn gets_one_accession() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/public/v1/accessions/1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: GetOnePublicAccessionResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.accession.id, 1);
+    }
+
+    #[tokio::test]
+    async fn is_read_only() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/public/v1/metadata-subjects")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+}
@@ -0,0 +1,72 @@
+//! Route for the public statistics widget.
+//!
+//! Mounted at `/api/v1/public/stats` for the counter on the public site. Like the routes in
+//! [`crate::routes::public`] it never looks at the auth cookie or API keys and may be
+//! called from any origin, but it has a rate limit of its own rather than sharing the rest
+//! of the API's, see [`crate::app_factory`].
+
+use crate::app_factory::AppState;
+use crate::models::response::PublicStatsResponse;
+use axum::extract::State;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+
+/// Creates the public statistics route under `/public/stats`.
+pub fn get_stats_routes() -> Router<AppState> {
+    Router::new().route("/public/stats", get(get_public_stats))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/public/stats",
+    tag = "Public",
+    responses(
+        (status = 200, description = "OK, cached for up to an hour", body = PublicStatsResponse),
+        (status = 429, description = "Too many requests")
+    )
+)]
+async fn get_public_stats(State(state): State<AppState>) -> Response {
+    state.stats_service.public_stats().await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::response::PublicStatsResponse;
+    use crate::test_tools::build_test_app;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn get_public_stats_anonymously() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/public/stats")
+                    .header(http::header::ORIGIN, "https://partner.example")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[http::header::CACHE_CONTROL],
+            "public, max-age=3600"
+        );
+        assert_eq!(
+            response.headers()[http::header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "*"
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: PublicStatsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.public_accessions, 1);
+        assert_eq!(actual.subjects, 2);
+    }
+}
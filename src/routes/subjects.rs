@@ -6,10 +6,15 @@
 //! It uses in-memory repositories for testing to avoid I/O operations.
 
 use crate::app_factory::AppState;
-use crate::auth::validate_at_least_contributor;
+use crate::auth::{validate_at_least_contributor, validate_at_least_researcher};
 use crate::models::auth::AuthenticatedUser;
-use crate::models::request::{CreateSubjectRequest, DeleteSubjectRequest, SubjectPagination};
-use crate::models::response::{ListSubjectsArResponse, ListSubjectsEnResponse, SubjectResponse};
+use crate::models::request::{
+    ApplySubjectRequest, CreateSubjectRequest, DeleteSubjectRequest, SubjectPagination,
+};
+use crate::models::response::{
+    ApplySubjectResponse, ListSubjectsArResponse, ListSubjectsEnResponse, MessageResponse,
+    SubjectResponse,
+};
 use ::entity::sea_orm_active_enums::Role;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
@@ -25,7 +30,8 @@ pub fn get_subjects_routes() -> Router<AppState> {
         Router::new()
             .route("/", get(list_subjects))
             .route("/", post(create_subject))
-            .route("/{subject_id}", delete(delete_subject)),
+            .route("/{subject_id}", delete(delete_subject))
+            .route("/{subject_id}/apply", post(apply_subject)),
     )
 }
 
@@ -55,7 +61,14 @@ async fn create_subject(
     if let Err(err) = payload.validate() {
         return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
     }
-    state.subjects_service.create_one(payload).await
+    state
+        .subjects_service
+        .create_one(
+            payload,
+            authenticated_user.organization_id,
+            authenticated_user.user_id,
+        )
+        .await
 }
 
 #[utoipa::path(
@@ -78,15 +91,7 @@ async fn list_subjects(
     if let Err(err) = pagination.0.validate() {
         return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
     }
-    state
-        .subjects_service
-        .list(
-            pagination.0.page,
-            pagination.0.per_page,
-            pagination.0.lang,
-            pagination.0.query_term,
-        )
-        .await
+    state.subjects_service.list(pagination.0).await
 }
 
 #[utoipa::path(
@@ -95,7 +100,7 @@ async fn list_subjects(
     tag = "Subjects",
     request_body = DeleteSubjectRequest,
     responses(
-        (status = 200, description = "Subject deleted"),
+        (status = 200, description = "Subject deleted", content((String = "text/plain"), (MessageResponse = "application/json"))),
         (status = 400, description = "Bad request"),
         (status = 403, description = "Forbidden"),
         (status = 404, description = "Not found")
@@ -117,13 +122,55 @@ async fn delete_subject(
     if let Err(err) = payload.validate() {
         return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
     }
-    state.subjects_service.delete_one(id, payload.lang).await
+    state
+        .subjects_service
+        .delete_one(id, payload.lang, authenticated_user.organization_id)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/metadata-subjects/{subject_id}/apply",
+    tag = "Subjects",
+    params(
+        ("subject_id" = i32, Path, description = "Subject ID")
+    ),
+    request_body = ApplySubjectRequest,
+    responses(
+        (status = 200, description = "OK", body = ApplySubjectResponse),
+        (status = 400, description = "Bad request, including when too many accessions match"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn apply_subject(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<ApplySubjectRequest>,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state
+        .subjects_service
+        .apply_to_accessions(id, payload, authenticated_user.organization_id)
+        .await
 }
 #[cfg(test)]
 mod tests {
 
+    use crate::models::error::LocalizedMessages;
     use crate::models::response::{
-        ListSubjectsArResponse, ListSubjectsEnResponse, SubjectResponse,
+        ApplySubjectResponse, ListSubjectsArResponse, ListSubjectsEnResponse, MessageResponse,
+        SubjectResponse,
     };
     use crate::test_tools::{
         build_test_app, get_mock_jwt, mock_paginated_subjects_ar, mock_paginated_subjects_en,
@@ -158,7 +205,7 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
     #[tokio::test]
     async fn create_one_subject_en() {
@@ -186,6 +233,7 @@ mod tests {
         let body = response.into_body().collect().await.unwrap().to_bytes();
         let actual: SubjectResponse = serde_json::from_slice(&body).unwrap();
         assert_eq!(actual.subject, "some cool archive".to_string());
+        assert_eq!(actual.created_by, Some("someuser@gmail.com".to_string()));
     }
 
     #[tokio::test]
@@ -237,6 +285,75 @@ mod tests {
         assert_eq!(actual.items.len(), mocked_resp.0.len());
     }
 
+    #[tokio::test]
+    async fn list_subjects_by_match_mode() {
+        let app = build_test_app();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/metadata-subjects?lang=english&query_term=sud&match=prefix")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/metadata-subjects?lang=english&query_term=sud&match=fuzzy")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn list_subjects_by_sort() {
+        let app = build_test_app();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/metadata-subjects?lang=english&sort=usage")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/metadata-subjects?lang=english&sort=popular")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn list_subjects_rejects_oversized_pages() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/metadata-subjects?page=0&per_page=10000&lang=english")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[tokio::test]
     async fn list_subjects_ar() {
         let app = build_test_app();
@@ -300,7 +417,7 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
     }
     #[tokio::test]
     async fn delete_one_subject_with_auth() {
@@ -325,4 +442,98 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[tokio::test]
+    async fn delete_one_subject_as_json() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri("/api/v1/metadata-subjects/1")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::ACCEPT, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::ACCEPT_LANGUAGE, "ar")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"lang": "english"})).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: MessageResponse = serde_json::from_slice(&body).unwrap();
+        let expected = MessageResponse {
+            code: "ok".to_string(),
+            message: "تم حذف الموضوع".to_string(),
+            details: None,
+            messages: Some(LocalizedMessages {
+                en: "Subject deleted".to_string(),
+                ar: "تم حذف الموضوع".to_string(),
+            }),
+        };
+        assert_eq!(actual, expected);
+    }
+
+    fn apply_subject_request(body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method(http::Method::POST)
+            .uri("/api/v1/metadata-subjects/1/apply")
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn apply_subject_counts_on_a_dry_run_then_tags() {
+        let app = build_test_app();
+        for dry_run in [true, false] {
+            let response = app
+                .clone()
+                .oneshot(apply_subject_request(json!({
+                    "lang": "english",
+                    "domain": "bbc.co.uk",
+                    "date_from": "2023-04-15T00:00:00",
+                    "dry_run": dry_run
+                })))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let actual: ApplySubjectResponse = serde_json::from_slice(&body).unwrap();
+            assert_eq!(
+                actual,
+                ApplySubjectResponse {
+                    dry_run,
+                    matched: 3,
+                    tagged: 2,
+                }
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn apply_subject_rejects_bad_filters() {
+        let app = build_test_app();
+        for body in [
+            json!({"lang": "english"}),
+            json!({"lang": "english", "domain": "https://bbc.co.uk/news"}),
+            json!({
+                "lang": "english",
+                "date_from": "2024-01-01T00:00:00",
+                "date_to": "2023-01-01T00:00:00"
+            }),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(apply_subject_request(body))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
 }
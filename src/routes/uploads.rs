@@ -0,0 +1,519 @@
+//! Routes for uploading files.
+//!
+//! Streaming a large file in one request means a dropped connection restarts the whole
+//! upload, which is common on flaky connections. These endpoints let clients upload files
+//! in parts and resume by re-sending only the parts that failed, or upload straight to
+//! storage with presigned URLs so large files skip the API entirely. Progress of uploads
+//! streamed through the server is also exposed as server-sent events. It uses in-memory
+//! repositories for testing to avoid I/O operations.
+
+use crate::app_factory::AppState;
+use crate::auth::validate_at_least_contributor;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::error::{ApiError, ErrorResponse};
+use crate::models::request::{InitiateUploadRequest, PresignUploadRequest};
+use crate::models::response::{
+    CompleteUploadResponse, InitiateUploadResponse, ListUploadPartsResponse, PresignUploadResponse,
+    UploadPartResponse, UploadProgressResponse,
+};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post, put};
+use axum::{Json, Router};
+use bytes::Bytes;
+use validator::Validate;
+
+/// S3 allows at most this many parts in a multipart upload.
+const MAX_PART_NUMBER: i32 = 10_000;
+
+/// Creates routes for upload endpoints under `/uploads`.
+///
+/// Uploading a part is left out, see [`get_upload_part_routes`].
+pub fn get_uploads_routes() -> Router<AppState> {
+    Router::new().nest(
+        "/uploads",
+        Router::new()
+            .route("/", post(initiate_upload))
+            .route("/presign", post(presign_upload))
+            .route("/{upload_id}", get(list_upload_parts))
+            .route("/{upload_id}", delete(abort_upload))
+            .route("/{upload_id}/complete", post(complete_upload))
+            .route("/{upload_id}/progress", get(get_upload_progress)),
+    )
+}
+
+/// Creates the endpoint for uploading a part, under `/uploads`.
+///
+/// Parts are at least 5MB, so this gets the upload route class's body limit and timeout
+/// rather than the JSON ones.
+pub fn get_upload_part_routes() -> Router<AppState> {
+    Router::new().nest(
+        "/uploads",
+        Router::new().route("/{upload_id}/parts/{part_number}", put(upload_part)),
+    )
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/uploads",
+    tag = "Uploads",
+    request_body = InitiateUploadRequest,
+    responses(
+        (status = 201, description = "Upload started", body = InitiateUploadResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn initiate_upload(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<InitiateUploadRequest>,
+) -> Response {
+    if !validate_at_least_contributor(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least contributor role").into_response();
+    }
+    state
+        .uploads_service
+        .initiate(payload, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/uploads/presign",
+    tag = "Uploads",
+    request_body = PresignUploadRequest,
+    responses(
+        (status = 201, description = "Presigned upload URLs", body = PresignUploadResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn presign_upload(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<PresignUploadRequest>,
+) -> Response {
+    if !validate_at_least_contributor(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least contributor role").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return ApiError::validation(err).into_response();
+    }
+    state
+        .uploads_service
+        .presign(payload, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/uploads/{upload_id}/parts/{part_number}",
+    tag = "Uploads",
+    params(
+        ("upload_id" = String, Path, description = "S3 multipart upload ID"),
+        ("part_number" = i32, Path, description = "Part number, from 1 to 10000")
+    ),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "Part uploaded", body = UploadPartResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No such upload")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn upload_part(
+    State(state): State<AppState>,
+    Path((upload_id, part_number)): Path<(String, i32)>,
+    authenticated_user: AuthenticatedUser,
+    body: Bytes,
+) -> Response {
+    if !validate_at_least_contributor(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least contributor role").into_response();
+    }
+    if !(1..=MAX_PART_NUMBER).contains(&part_number) {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("Part number must be between 1 and {MAX_PART_NUMBER}"),
+        )
+            .into_response();
+    }
+    if body.is_empty() {
+        return (StatusCode::BAD_REQUEST, "Part must not be empty").into_response();
+    }
+    state
+        .uploads_service
+        .upload_part(upload_id, part_number, body, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/uploads/{upload_id}",
+    tag = "Uploads",
+    params(
+        ("upload_id" = String, Path, description = "S3 multipart upload ID")
+    ),
+    responses(
+        (status = 200, description = "Parts uploaded so far", body = ListUploadPartsResponse),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No such upload")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_upload_parts(
+    State(state): State<AppState>,
+    Path(upload_id): Path<String>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_contributor(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least contributor role").into_response();
+    }
+    state
+        .uploads_service
+        .list_parts(upload_id, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/uploads/{upload_id}/complete",
+    tag = "Uploads",
+    params(
+        ("upload_id" = String, Path, description = "S3 multipart upload ID")
+    ),
+    responses(
+        (status = 200, description = "Upload completed", body = CompleteUploadResponse),
+        (status = 400, description = "Upload has no parts"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No such upload")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn complete_upload(
+    State(state): State<AppState>,
+    Path(upload_id): Path<String>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_contributor(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least contributor role").into_response();
+    }
+    state
+        .uploads_service
+        .complete(upload_id, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/uploads/{upload_id}",
+    tag = "Uploads",
+    params(
+        ("upload_id" = String, Path, description = "S3 multipart upload ID")
+    ),
+    responses(
+        (status = 200, description = "Upload aborted"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No such upload")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn abort_upload(
+    State(state): State<AppState>,
+    Path(upload_id): Path<String>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_contributor(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least contributor role").into_response();
+    }
+    state
+        .uploads_service
+        .abort(upload_id, authenticated_user)
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/uploads/{upload_id}/progress",
+    tag = "Uploads",
+    params(
+        ("upload_id" = String, Path, description = "S3 multipart upload ID")
+    ),
+    responses(
+        (status = 200, description = "Stream of progress events", body = UploadProgressResponse, content_type = "text/event-stream"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "No such upload in progress")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn get_upload_progress(
+    State(state): State<AppState>,
+    Path(upload_id): Path<String>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_contributor(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least contributor role").into_response();
+    }
+    state.accessions_service.stream_upload_progress(&upload_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::response::{
+        CompleteUploadResponse, InitiateUploadResponse, ListUploadPartsResponse,
+        PresignUploadResponse, UploadPartResponse,
+    };
+    use crate::test_tools::{build_test_app, get_mock_jwt};
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn initiate_upload() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/uploads")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"metadata_format": "wacz"})).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: InitiateUploadResponse = serde_json::from_slice(&body).unwrap();
+        assert!(actual.upload_id.starts_with("mock-upload-id-"));
+        assert!(actual.key.ends_with(".wacz"));
+    }
+
+    #[tokio::test]
+    async fn presign_single_upload() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/uploads/presign")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"metadata_format": "wacz"})).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: PresignUploadResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            actual.url,
+            Some(format!("mock-presigned-put-{}", actual.key))
+        );
+        assert_eq!(actual.upload_id, None);
+        assert!(actual.part_urls.is_empty());
+    }
+
+    #[tokio::test]
+    async fn presign_multipart_upload() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/uploads/presign")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"metadata_format": "wacz", "part_count": 3}))
+                            .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: PresignUploadResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.url, None);
+        assert_eq!(
+            actual.upload_id,
+            Some(format!("mock-upload-id-{}", actual.key))
+        );
+        assert_eq!(actual.part_urls.len(), 3);
+        assert_eq!(actual.part_urls[2].url, "mock-presigned-part-3".to_string());
+    }
+
+    #[tokio::test]
+    async fn presign_upload_too_many_parts() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/uploads/presign")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(
+                            &json!({"metadata_format": "wacz", "part_count": 10001}),
+                        )
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn upload_part() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/uploads/mock-upload-id/parts/2")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(vec![0u8; 1024]))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: UploadPartResponse = serde_json::from_slice(&body).unwrap();
+        let expected = UploadPartResponse {
+            part_number: 2,
+            etag: "mock-etag-part-2".to_string(),
+            size: 1024,
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn upload_part_invalid_part_number() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/uploads/mock-upload-id/parts/0")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(vec![0u8; 1024]))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn list_upload_parts() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/uploads/mock-upload-id")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListUploadPartsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.parts.len(), 1);
+        assert_eq!(actual.key, "mock-upload.wacz".to_string());
+    }
+
+    #[tokio::test]
+    async fn complete_upload() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/uploads/mock-upload-id/complete")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: CompleteUploadResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.key, "mock-upload.wacz".to_string());
+    }
+
+    #[tokio::test]
+    async fn abort_unknown_upload() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri("/api/v1/uploads/some-other-upload-id")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn get_upload_progress_unknown_upload() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/uploads/some-upload-id/progress")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,330 @@
+//! Version 2 routes for reading archival records (accessions).
+//!
+//! Listing uses 1-indexed pages with a nested pagination block, and all failures
+//! are returned as structured [`ErrorResponse`] bodies. Accessions come with their display
+//! fields filled in for the reader's `Accept-Language`, so every response varies with it.
+
+use crate::app_factory::AppState;
+use crate::auth::validate_at_least_researcher;
+use crate::client_country::ClientCountry;
+use crate::i18n::PreferredLanguage;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::error::{ApiError, ErrorResponse};
+use crate::models::response::{
+    AccessionsWithMetadataResponse, PublicAccessionsWithMetadataResponse,
+};
+use crate::models::v2::{
+    AccessionPaginationV2, GetOneAccessionV2Response, GetOnePublicAccessionV2Response,
+    ListAccessionsV2Response, ListPublicAccessionsV2Response, PaginationMeta,
+};
+use crate::services::accessions_service::AccessionDetail;
+use axum::extract::{Path, State};
+use axum::http::header::VARY;
+use axum::http::HeaderValue;
+use axum::routing::get;
+use axum::{Json, Router};
+use axum_extra::extract::Query;
+use entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
+use tower_http::set_header::SetResponseHeaderLayer;
+use tracing::error;
+use validator::Validate;
+
+/// Creates v2 routes for accession-related endpoints under `/accessions`.
+pub fn get_accessions_routes() -> Router<AppState> {
+    Router::new().nest(
+        "/accessions",
+        Router::new()
+            .route("/", get(list_accessions))
+            .route("/private", get(list_accessions_private))
+            .route("/{accession_id}", get(get_one_accession))
+            .route("/private/{accession_id}", get(get_one_private_accession))
+            .layer(SetResponseHeaderLayer::appending(
+                VARY,
+                HeaderValue::from_static("accept-language"),
+            )),
+    )
+}
+
+async fn list(
+    state: AppState,
+    pagination: AccessionPaginationV2,
+    is_private: bool,
+) -> Result<(Vec<AccessionWithMetadataModel>, PaginationMeta), ApiError> {
+    pagination.validate().map_err(ApiError::validation)?;
+    let page = pagination.page;
+    let per_page = pagination.per_page;
+    let (rows, total_pages) = state
+        .accessions_service
+        .list_rows(pagination.into_list_params(is_private))
+        .await
+        .map_err(|err| {
+            error!(%err, "Error occurred paginating accessions");
+            ApiError::internal("Internal database error")
+        })?;
+    let meta = PaginationMeta {
+        page,
+        per_page,
+        total_pages,
+    };
+    Ok((rows, meta))
+}
+
+/// Looks up an accession the same way v1 does, see [`AccessionDetail`].
+async fn get_one(
+    state: AppState,
+    id: i32,
+    viewer: Option<&AuthenticatedUser>,
+    country: Option<String>,
+) -> Result<AccessionDetail, ApiError> {
+    state
+        .accessions_service
+        .find_one_detail(id, viewer, country)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(ApiError::not_found)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/accessions",
+    tag = "Accessions v2",
+    params(
+        AccessionPaginationV2
+    ),
+    responses(
+        (status = 200, description = "OK", body = ListPublicAccessionsV2Response),
+        (status = 400, description = "Bad request", body = ErrorResponse)
+    )
+)]
+async fn list_accessions(
+    State(state): State<AppState>,
+    pagination: Query<AccessionPaginationV2>,
+    PreferredLanguage(language): PreferredLanguage,
+) -> Result<Json<ListPublicAccessionsV2Response>, ApiError> {
+    let (rows, pagination) = list(state, pagination.0, false).await?;
+    Ok(Json(ListPublicAccessionsV2Response {
+        items: rows
+            .into_iter()
+            .map(|row| PublicAccessionsWithMetadataResponse::from(row).displayed_in(language))
+            .collect(),
+        pagination,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/accessions/private",
+    tag = "Accessions v2",
+    params(
+        AccessionPaginationV2
+    ),
+    responses(
+        (status = 200, description = "OK", body = ListAccessionsV2Response),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_accessions_private(
+    State(state): State<AppState>,
+    pagination: Query<AccessionPaginationV2>,
+    PreferredLanguage(language): PreferredLanguage,
+    authenticated_user: AuthenticatedUser,
+) -> Result<Json<ListAccessionsV2Response>, ApiError> {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return Err(ApiError::forbidden("Must have at least researcher role"));
+    }
+    let mut pagination = pagination.0;
+    if let Some(organization_id) = authenticated_user.organization_scope() {
+        pagination.organization_id = Some(organization_id);
+    }
+    let (rows, pagination) = list(state, pagination, true).await?;
+    Ok(Json(ListAccessionsV2Response {
+        items: rows
+            .into_iter()
+            .map(|row| AccessionsWithMetadataResponse::from(row).displayed_in(language))
+            .collect(),
+        pagination,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/accessions/{accession_id}",
+    tag = "Accessions v2",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
+    responses(
+        (status = 200, description = "OK", body = GetOnePublicAccessionV2Response),
+        (status = 404, description = "Not found", body = ErrorResponse)
+    )
+)]
+async fn get_one_accession(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    ClientCountry(country): ClientCountry,
+    PreferredLanguage(language): PreferredLanguage,
+) -> Result<Json<GetOnePublicAccessionV2Response>, ApiError> {
+    let found = get_one(state, id, None, country).await?;
+    Ok(Json(GetOnePublicAccessionV2Response {
+        accession: PublicAccessionsWithMetadataResponse::from(found.accession)
+            .displayed_in(language),
+        wacz_url: found.wacz_url,
+        pdf_url: found.pdf_url,
+        derivatives: found.derivatives,
+        relations: found.relations,
+        social_metadata: found.social_metadata,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/accessions/private/{accession_id}",
+    tag = "Accessions v2",
+    params(
+        ("accession_id" = i32, Path, description = "Accession ID")
+    ),
+    responses(
+        (status = 200, description = "OK", body = GetOneAccessionV2Response),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse)
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn get_one_private_accession(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    ClientCountry(country): ClientCountry,
+    PreferredLanguage(language): PreferredLanguage,
+    authenticated_user: AuthenticatedUser,
+) -> Result<Json<GetOneAccessionV2Response>, ApiError> {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return Err(ApiError::forbidden("Must have at least researcher role"));
+    }
+    let found = get_one(state, id, Some(&authenticated_user), country).await?;
+    Ok(Json(GetOneAccessionV2Response {
+        accession: AccessionsWithMetadataResponse::from(found.accession).displayed_in(language),
+        wacz_url: found.wacz_url,
+        pdf_url: found.pdf_url,
+        derivatives: found.derivatives,
+        relations: found.relations,
+        social_metadata: found.social_metadata,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::error::ErrorResponse;
+    use crate::models::v2::{
+        GetOnePublicAccessionV2Response, ListAccessionsV2Response, ListPublicAccessionsV2Response,
+    };
+    use crate::test_tools::{
+        build_test_app, get_mock_jwt, mock_derivatives_response,
+        mock_one_public_accession_with_metadata, mock_relations_response,
+        mock_social_metadata_response,
+    };
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn list_accessions_defaults_to_first_page() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v2/accessions")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListPublicAccessionsV2Response = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.pagination.page, 1);
+        assert_eq!(actual.pagination.per_page, 20);
+        assert_eq!(actual.pagination.total_pages, Some(10));
+        assert_eq!(actual.items.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn list_accessions_page_zero_is_structured_error() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v2/accessions?page=0")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.error.code, "validation_error");
+        assert!(actual.error.details.unwrap().get("page").is_some());
+    }
+
+    #[tokio::test]
+    async fn get_one_accession() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v2/accessions/1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: GetOnePublicAccessionV2Response = serde_json::from_slice(&body).unwrap();
+        let expected = GetOnePublicAccessionV2Response {
+            accession: mock_one_public_accession_with_metadata().into(),
+            wacz_url: "my url".to_owned(),
+            pdf_url: Some("my url".to_owned()),
+            derivatives: mock_derivatives_response(),
+            relations: mock_relations_response(),
+            social_metadata: Some(mock_social_metadata_response()),
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn list_accessions_private_with_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v2/accessions/private?page=2&per_page=5")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListAccessionsV2Response = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.pagination.page, 2);
+        assert_eq!(actual.pagination.per_page, 5);
+    }
+}
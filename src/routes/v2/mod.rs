@@ -0,0 +1,7 @@
+//! Routes for version 2 of the API, mounted under `/api/v2`.
+//!
+//! These handlers share services with v1 but use the request and response models
+//! from [`crate::models::v2`], so pagination and error formats can evolve without
+//! breaking existing v1 clients.
+
+pub mod accessions;
@@ -0,0 +1,116 @@
+//! Routes for webhooks sent by third party services.
+//!
+//! These are called by the services themselves rather than by users, so they
+//! authenticate with credentials configured on the service instead of a JWT or API key.
+
+use crate::app_factory::AppState;
+use crate::models::request::PostmarkWebhookRequest;
+use axum::extract::State;
+use axum::response::Response;
+use axum::routing::post;
+use axum::{Json, Router};
+use axum_extra::headers::authorization::Basic;
+use axum_extra::headers::Authorization;
+use axum_extra::TypedHeader;
+
+/// Creates routes for webhooks under `/webhooks`.
+pub fn get_webhooks_routes() -> Router<AppState> {
+    Router::new().nest(
+        "/webhooks",
+        Router::new().route("/postmark", post(handle_postmark_webhook)),
+    )
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks/postmark",
+    tag = "Webhooks",
+    request_body = PostmarkWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook processed or ignored"),
+        (status = 401, description = "Missing or wrong basic auth credentials"),
+        (status = 404, description = "Webhook is turned off")
+    ),
+    security(
+        ("postmark_basic_auth" = [])
+    )
+)]
+async fn handle_postmark_webhook(
+    State(state): State<AppState>,
+    credentials: Option<TypedHeader<Authorization<Basic>>>,
+    Json(payload): Json<PostmarkWebhookRequest>,
+) -> Response {
+    let password = credentials.map(|TypedHeader(auth)| auth.password().to_string());
+    state
+        .auth_service
+        .handle_postmark_webhook(password, payload)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_tools::build_test_app;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    fn postmark_webhook(password: &str, body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/api/v1/webhooks/postmark")
+            .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .header(
+                http::header::AUTHORIZATION,
+                format!("Basic {}", STANDARD.encode(format!("postmark:{password}"))),
+            )
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn postmark_webhook_records_hard_bounces() {
+        let response = build_test_app()
+            .oneshot(postmark_webhook(
+                "webhook-secret",
+                json!({
+                    "RecordType": "Bounce",
+                    "Type": "HardBounce",
+                    "TypeCode": 1,
+                    "MessageID": "883953f4-6105-42a2-a16a-77a8eac79483",
+                    "Email": "test@example.com"
+                }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn postmark_webhook_ignores_deliveries() {
+        let response = build_test_app()
+            .oneshot(postmark_webhook(
+                "webhook-secret",
+                json!({"RecordType": "Delivery", "Recipient": "test@example.com"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn postmark_webhook_wrong_password() {
+        let response = build_test_app()
+            .oneshot(postmark_webhook(
+                "guess",
+                json!({"RecordType": "SpamComplaint", "Email": "test@example.com"}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,334 @@
+//! Routes for managing internal workflow labels on accessions.
+//! Workflow labels like "needs-translation" or "priority" are operational notes for curators,
+//! kept separate from the public Dublin Core subjects and only available to researchers.
+//!
+//! This module provides HTTP endpoints for creating, listing and deleting labels, and for
+//! attaching them to accessions. It uses in-memory repositories for testing to avoid I/O operations.
+
+use crate::app_factory::AppState;
+use crate::auth::validate_at_least_researcher;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::request::CreateWorkflowLabelRequest;
+use crate::models::response::{ListWorkflowLabelsResponse, WorkflowLabelResponse};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post, put};
+use axum::{Json, Router};
+use validator::Validate;
+
+/// Creates routes for workflow label endpoints under `/workflow-labels` and
+/// `/accessions/{accession_id}/workflow-labels`.
+pub fn get_workflow_labels_routes() -> Router<AppState> {
+    Router::new()
+        .nest(
+            "/workflow-labels",
+            Router::new()
+                .route("/", get(list_workflow_labels))
+                .route("/", post(create_workflow_label))
+                .route("/{label_id}", delete(delete_workflow_label)),
+        )
+        .route(
+            "/accessions/{accession_id}/workflow-labels",
+            get(list_accession_workflow_labels),
+        )
+        .route(
+            "/accessions/{accession_id}/workflow-labels/{label_id}",
+            put(add_accession_workflow_label),
+        )
+        .route(
+            "/accessions/{accession_id}/workflow-labels/{label_id}",
+            delete(remove_accession_workflow_label),
+        )
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/workflow-labels",
+    tag = "Workflow labels",
+    request_body = CreateWorkflowLabelRequest,
+    responses(
+        (status = 201, description = "Created", body = WorkflowLabelResponse),
+        (status = 400, description = "Bad request"),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn create_workflow_label(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+    Json(payload): Json<CreateWorkflowLabelRequest>,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    if let Err(err) = payload.validate() {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+    state.workflow_labels_service.create_one(payload).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/workflow-labels",
+    tag = "Workflow labels",
+    responses(
+        (status = 200, description = "OK", body = ListWorkflowLabelsResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_workflow_labels(
+    State(state): State<AppState>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state.workflow_labels_service.list().await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/workflow-labels/{label_id}",
+    tag = "Workflow labels",
+    responses(
+        (status = 200, description = "Workflow label deleted"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn delete_workflow_label(
+    State(state): State<AppState>,
+    Path(label_id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state.workflow_labels_service.delete_one(label_id).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/accessions/{accession_id}/workflow-labels",
+    tag = "Workflow labels",
+    responses(
+        (status = 200, description = "OK", body = ListWorkflowLabelsResponse),
+        (status = 403, description = "Forbidden")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn list_accession_workflow_labels(
+    State(state): State<AppState>,
+    Path(accession_id): Path<i32>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .workflow_labels_service
+        .list_for_accession(accession_id)
+        .await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/accessions/{accession_id}/workflow-labels/{label_id}",
+    tag = "Workflow labels",
+    responses(
+        (status = 200, description = "Workflow label added"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn add_accession_workflow_label(
+    State(state): State<AppState>,
+    Path((accession_id, label_id)): Path<(i32, i32)>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .workflow_labels_service
+        .add_to_accession(accession_id, label_id)
+        .await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/accessions/{accession_id}/workflow-labels/{label_id}",
+    tag = "Workflow labels",
+    responses(
+        (status = 200, description = "Workflow label removed"),
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not found")
+    ),
+    security(
+        ("jwt_cookie_auth" = []),
+        ("api_key_auth" = [])
+    )
+)]
+async fn remove_accession_workflow_label(
+    State(state): State<AppState>,
+    Path((accession_id, label_id)): Path<(i32, i32)>,
+    authenticated_user: AuthenticatedUser,
+) -> Response {
+    if !validate_at_least_researcher(&authenticated_user.role) {
+        return (StatusCode::FORBIDDEN, "Must have at least researcher role").into_response();
+    }
+    state
+        .workflow_labels_service
+        .remove_from_accession(accession_id, label_id)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::response::{ListWorkflowLabelsResponse, WorkflowLabelResponse};
+    use crate::test_tools::{build_test_app, get_mock_jwt};
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn list_workflow_labels_no_auth() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/workflow-labels")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn create_workflow_label() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/workflow-labels")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"label": "needs-translation"})).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: WorkflowLabelResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(actual.label, "needs-translation".to_string());
+    }
+
+    #[tokio::test]
+    async fn create_workflow_label_too_long() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::POST)
+                    .uri("/api/v1/workflow-labels")
+                    .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::from(
+                        serde_json::to_vec(&json!({"label": "a".repeat(51)})).unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn list_accession_workflow_labels() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/accessions/1/workflow-labels")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let actual: ListWorkflowLabelsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            actual.items,
+            vec![WorkflowLabelResponse {
+                id: 1,
+                label: "needs-translation".to_string()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn add_accession_workflow_label() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::PUT)
+                    .uri("/api/v1/accessions/1/workflow-labels/1")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn remove_accession_workflow_label() {
+        let app = build_test_app();
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(http::Method::DELETE)
+                    .uri("/api/v1/accessions/1/workflow-labels/1")
+                    .header(http::header::COOKIE, format!("jwt={}", get_mock_jwt()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
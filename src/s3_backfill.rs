@@ -0,0 +1,128 @@
+//! Progress of moving legacy WACZ files from Browsertrix into our own S3 storage.
+//!
+//! Accessions crawled before we stored files in S3 still have their WACZ served by
+//! Browsertrix. An admin can start a backfill that copies them over one at a time on a
+//! background task; this tracks how far it has got so admins can check on it. Only one
+//! backfill runs at a time and progress resets whenever the server restarts.
+
+use chrono::{NaiveDateTime, Utc};
+use std::sync::{Arc, Mutex};
+
+/// An accession whose WACZ could not be copied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillFailure {
+    /// `None` when the backfill couldn't list the accessions to copy at all
+    pub accession_id: Option<i32>,
+    pub reason: String,
+}
+
+/// Point in time view of the latest backfill.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackfillSnapshot {
+    pub running: bool,
+    /// Accessions the backfill set out to copy
+    pub total: u64,
+    pub transferred: u64,
+    pub failures: Vec<BackfillFailure>,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+/// Thread safe tracker for the S3 backfill, shared between requests.
+#[derive(Debug, Clone, Default)]
+pub struct S3BackfillProgress {
+    inner: Arc<Mutex<BackfillSnapshot>>,
+}
+
+impl S3BackfillProgress {
+    /// Marks a backfill as started, unless one is already running.
+    ///
+    /// # Returns
+    /// Whether the caller should go ahead with the backfill
+    pub fn try_start(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.running {
+            return false;
+        }
+        *inner = BackfillSnapshot {
+            running: true,
+            started_at: Some(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+        true
+    }
+
+    /// Records how many accessions the running backfill found to copy.
+    pub fn set_total(&self, total: usize) {
+        self.inner.lock().unwrap().total = total as u64;
+    }
+
+    /// Records an accession whose WACZ is now in S3.
+    pub fn transferred(&self) {
+        self.inner.lock().unwrap().transferred += 1;
+    }
+
+    /// Records an accession that could not be copied.
+    pub fn failed(&self, accession_id: Option<i32>, reason: &str) {
+        self.inner.lock().unwrap().failures.push(BackfillFailure {
+            accession_id,
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Marks the running backfill as finished.
+    pub fn finish(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.running = false;
+        inner.finished_at = Some(Utc::now().naive_utc());
+    }
+
+    /// Returns the progress of the running or most recent backfill.
+    pub fn snapshot(&self) -> BackfillSnapshot {
+        self.inner.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn only_one_backfill_runs_at_a_time() {
+        let progress = S3BackfillProgress::default();
+        assert!(progress.try_start());
+        assert!(!progress.try_start());
+        progress.finish();
+        assert!(progress.try_start());
+    }
+
+    #[test]
+    fn restarting_resets_progress() {
+        let progress = S3BackfillProgress::default();
+        progress.try_start();
+        progress.set_total(2);
+        progress.transferred();
+        progress.failed(Some(7), "Browsertrix returned 404");
+        progress.finish();
+
+        let snapshot = progress.snapshot();
+        assert!(!snapshot.running);
+        assert_eq!((snapshot.total, snapshot.transferred), (2, 1));
+        assert_eq!(
+            snapshot.failures,
+            vec![BackfillFailure {
+                accession_id: Some(7),
+                reason: "Browsertrix returned 404".to_string()
+            }]
+        );
+        assert!(snapshot.finished_at.is_some());
+
+        progress.try_start();
+        let snapshot = progress.snapshot();
+        assert!(snapshot.running);
+        assert_eq!((snapshot.total, snapshot.transferred), (0, 0));
+        assert!(snapshot.failures.is_empty());
+        assert_eq!(snapshot.finished_at, None);
+    }
+}
@@ -0,0 +1,287 @@
+//! Naming of the files the API stores in S3.
+//!
+//! Bare UUID keys make the bucket and exports hard to browse, so by default files are
+//! stored as `{year}/{month}/{uuid}-{slug}.{extension}`, with the slug made from the
+//! accession's title. Arabic titles are transliterated to Latin letters so keys stay
+//! plain ASCII. Keys are only ever looked up through the `s3_filename` stored with the
+//! accession, so changing the scheme leaves existing files where they are.
+//!
+//! Files of partner organizations go under `organizations/{id}/`, so each organization's
+//! files can be exported or handed back on their own. The archive's own files keep
+//! unprefixed keys.
+//!
+//! Files uploaded straight to S3 by clients are named before there is any metadata, so
+//! those keep bare UUID keys, see [`crate::services::uploads_service`].
+
+use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+use chrono::{Datelike, NaiveDateTime};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Where files replaced by corrected ones are kept, see [`quarantine_key`].
+pub const QUARANTINE_PREFIX: &str = "quarantine";
+
+/// Longest slug put in a key, so keys stay well under S3's 1024 byte limit.
+const MAX_SLUG_LENGTH: usize = 60;
+
+/// How keys of files the API writes to S3 are built
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum S3KeyScheme {
+    /// `{uuid}.{extension}`, as all files were named before
+    Uuid,
+    /// `{year}/{month}/{uuid}-{slug}.{extension}`
+    #[default]
+    Dated,
+}
+
+impl FromStr for S3KeyScheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "uuid" => Ok(S3KeyScheme::Uuid),
+            "dated" => Ok(S3KeyScheme::Dated),
+            other => Err(format!("Unknown S3 key scheme: {other}")),
+        }
+    }
+}
+
+impl S3KeyScheme {
+    /// Builds a new, unique key for a file.
+    ///
+    /// # Arguments
+    /// * `title` - Title of the accession the file belongs to, in either language
+    /// * `extension` - File extension without the dot, e.g. `wacz`
+    /// * `id` - New ID the key starts with, which keeps it unique
+    /// * `stored_at` - When the file is being stored, which picks its folder
+    /// * `organization_id` - The organization the accession belongs to
+    pub fn object_key(
+        &self,
+        title: &str,
+        extension: &str,
+        id: Uuid,
+        stored_at: NaiveDateTime,
+        organization_id: i32,
+    ) -> String {
+        let key = self.unprefixed_key(title, extension, id, stored_at);
+        if organization_id == DEFAULT_ORGANIZATION_ID {
+            key
+        } else {
+            format!("organizations/{organization_id}/{key}")
+        }
+    }
+
+    fn unprefixed_key(
+        &self,
+        title: &str,
+        extension: &str,
+        id: Uuid,
+        stored_at: NaiveDateTime,
+    ) -> String {
+        match self {
+            S3KeyScheme::Uuid => format!("{id}.{extension}"),
+            S3KeyScheme::Dated => {
+                let slug = slugify(title);
+                let name = if slug.is_empty() {
+                    id.to_string()
+                } else {
+                    format!("{id}-{slug}")
+                };
+                format!(
+                    "{}/{:02}/{name}.{extension}",
+                    stored_at.year(),
+                    stored_at.month()
+                )
+            }
+        }
+    }
+}
+
+/// Key a replaced file is moved to, so it can be restored if the replacement was a mistake.
+///
+/// The original key is kept after the prefix, so quarantined files can be traced back to
+/// where they were.
+pub fn quarantine_key(key: &str) -> String {
+    format!("{QUARANTINE_PREFIX}/{key}")
+}
+
+/// Latin spelling of an Arabic letter or digit.
+///
+/// Loosely follows ALA-LC without diacritics; hamza and ʿayn are dropped since they have
+/// no plain ASCII equivalent. Returns `None` for anything that isn't Arabic.
+fn transliterate_arabic(c: char) -> Option<&'static str> {
+    let latin = match c {
+        'ا' | 'أ' | 'آ' | 'ى' | 'ة' => "a",
+        'إ' | 'ئ' => "i",
+        'ؤ' => "u",
+        'ء' | 'ع' | 'ـ' => "",
+        'ب' => "b",
+        'ت' => "t",
+        'ث' => "th",
+        'ج' => "j",
+        'ح' | 'ه' => "h",
+        'خ' => "kh",
+        'د' | 'ض' => "d",
+        'ذ' => "dh",
+        'ر' => "r",
+        'ز' | 'ظ' => "z",
+        'س' | 'ص' => "s",
+        'ش' => "sh",
+        'ط' => "t",
+        'غ' => "gh",
+        'ف' => "f",
+        'ق' => "q",
+        'ك' => "k",
+        'ل' => "l",
+        'م' => "m",
+        'ن' => "n",
+        'و' => "w",
+        'ي' => "y",
+        // harakat, shadda and sukun
+        '\u{064B}'..='\u{0652}' | '\u{0670}' => "",
+        '٠' | '۰' => "0",
+        '١' | '۱' => "1",
+        '٢' | '۲' => "2",
+        '٣' | '۳' => "3",
+        '٤' | '۴' => "4",
+        '٥' | '۵' => "5",
+        '٦' | '۶' => "6",
+        '٧' | '۷' => "7",
+        '٨' | '۸' => "8",
+        '٩' | '۹' => "9",
+        _ => return None,
+    };
+    Some(latin)
+}
+
+/// Turns a title into a lowercase, hyphen separated slug of ASCII letters and digits.
+///
+/// Arabic is transliterated and anything else that isn't ASCII is dropped. Slugs are
+/// cut at a word boundary once they reach [`MAX_SLUG_LENGTH`].
+pub fn slugify(title: &str) -> String {
+    let mut words: Vec<String> = vec![];
+    let mut word = String::new();
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            word.push(c.to_ascii_lowercase());
+        } else if let Some(latin) = transliterate_arabic(c) {
+            word.push_str(latin);
+        } else if !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    let mut slug = String::new();
+    for word in words {
+        let separator = usize::from(!slug.is_empty());
+        if slug.len() + separator + word.len() > MAX_SLUG_LENGTH {
+            if slug.is_empty() {
+                slug.push_str(&word[..MAX_SLUG_LENGTH]);
+            }
+            break;
+        }
+        if separator == 1 {
+            slug.push('-');
+        }
+        slug.push_str(&word);
+    }
+    slug
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn slugifies_english_titles() {
+        assert_eq!(
+            slugify("Eyewitness report from El Fasher (2024)!"),
+            "eyewitness-report-from-el-fasher-2024"
+        );
+        assert_eq!(slugify("  --  "), "");
+    }
+
+    #[test]
+    fn transliterates_arabic_titles() {
+        assert_eq!(slugify("شهادة عيان من الخرطوم"), "shhada-yan-mn-alkhrtwm");
+        assert_eq!(slugify("بيان ١٩ ديسمبر"), "byan-19-dysmbr");
+        assert_eq!(slugify("مُقَابَلَة"), "mqabla");
+    }
+
+    #[test]
+    fn cuts_long_slugs_at_word_boundaries() {
+        let slug = slugify(&"archive ".repeat(20));
+        assert!(slug.len() <= MAX_SLUG_LENGTH);
+        assert!(slug.ends_with("archive"));
+        assert_eq!(slugify(&"a".repeat(100)).len(), MAX_SLUG_LENGTH);
+    }
+
+    #[test]
+    fn builds_keys_per_scheme() {
+        let stored_at = NaiveDate::from_ymd_opt(2025, 3, 9)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let id = Uuid::from_u128(1);
+        let key = S3KeyScheme::Dated.object_key(
+            "Statement on Darfur",
+            "wacz",
+            id,
+            stored_at,
+            DEFAULT_ORGANIZATION_ID,
+        );
+        assert!(key.starts_with("2025/03/"));
+        assert!(key.ends_with("-statement-on-darfur.wacz"));
+        assert!(Uuid::try_parse(&key["2025/03/".len()..][..36]).is_ok());
+
+        let key =
+            S3KeyScheme::Dated.object_key("؟؟", "png", id, stored_at, DEFAULT_ORGANIZATION_ID);
+        assert!(Uuid::try_parse(&key["2025/03/".len()..key.len() - ".png".len()]).is_ok());
+
+        let key = S3KeyScheme::Uuid.object_key(
+            "Statement on Darfur",
+            "wacz",
+            id,
+            stored_at,
+            DEFAULT_ORGANIZATION_ID,
+        );
+        assert!(Uuid::try_parse(key.strip_suffix(".wacz").unwrap()).is_ok());
+    }
+
+    #[test]
+    fn prefixes_keys_of_partner_organizations() {
+        let stored_at = NaiveDate::from_ymd_opt(2025, 3, 9)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let id = Uuid::from_u128(1);
+        let key = S3KeyScheme::Dated.object_key("Statement on Darfur", "wacz", id, stored_at, 4);
+        assert!(key.starts_with("organizations/4/2025/03/"));
+        let key = S3KeyScheme::Uuid.object_key("Statement on Darfur", "wacz", id, stored_at, 4);
+        assert!(Uuid::try_parse(
+            key.strip_prefix("organizations/4/")
+                .and_then(|key| key.strip_suffix(".wacz"))
+                .unwrap()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn quarantines_under_the_original_key() {
+        assert_eq!(
+            quarantine_key("organizations/4/2025/03/abc-statement.wacz"),
+            "quarantine/organizations/4/2025/03/abc-statement.wacz"
+        );
+    }
+
+    #[test]
+    fn parses_schemes() {
+        assert_eq!("dated".parse(), Ok(S3KeyScheme::Dated));
+        assert_eq!("UUID".parse(), Ok(S3KeyScheme::Uuid));
+        assert!("random".parse::<S3KeyScheme>().is_err());
+    }
+}
@@ -0,0 +1,587 @@
+//! The background tasks registered with the [`Scheduler`](crate::scheduler::Scheduler)
+//! at startup.
+//!
+//! Tasks that check the whole archive work through it a batch at a time in ID order,
+//! remembering where they got to in memory and starting over once they reach the end.
+
+use crate::activity_digest::ActivityDigest;
+use crate::auth::validate_at_least_researcher;
+use crate::clock::SharedClock;
+use crate::email_outbox::EmailOutbox;
+use crate::provenance::sha256_bytes;
+use crate::publication_feed::PublicationFeed;
+use crate::repos::accessions_repo::AccessionsRepo;
+use crate::repos::activity_digest_repo::ActivityDigestRepo;
+use crate::repos::auth_repo::AuthRepo;
+use crate::repos::emails_repo::EmailsRepo;
+use crate::repos::provenance_repo::ProvenanceRepo;
+use crate::repos::s3_repo::S3Repo;
+use crate::repos::timestamp_repo::TimestampRepo;
+use crate::scheduler::ScheduledTask;
+use async_trait::async_trait;
+use entity::accession::Model as AccessionModel;
+use reqwest::{Client, StatusCode};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const SESSION_CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const FIXITY_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const FIXITY_CHECK_BATCH_SIZE: u64 = 50;
+const LINK_ROT_CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const LINK_ROT_CHECK_BATCH_SIZE: u64 = 20;
+const LINK_ROT_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+const EMAIL_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+const EMBARGO_LIFT_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const TIMESTAMP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const TIMESTAMP_BATCH_SIZE: u64 = 50;
+const ACTIVITY_DIGEST_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Days between the activity digests a user gets
+const ACTIVITY_DIGEST_PERIOD_DAYS: i64 = 7;
+
+/// Where a task that works through the archive in batches got to.
+#[derive(Debug, Default)]
+struct BatchCursor {
+    after_id: AtomicI32,
+}
+
+impl BatchCursor {
+    /// Fetches the next batch, wrapping back to the start of the archive after the end.
+    async fn next_batch(
+        &self,
+        accessions_repo: &dyn AccessionsRepo,
+        limit: u64,
+    ) -> Result<Vec<AccessionModel>, String> {
+        let after_id = self.after_id.load(Ordering::SeqCst);
+        let batch = accessions_repo
+            .list_batch_after_id(after_id, limit)
+            .await
+            .map_err(|err| err.to_string())?;
+        let next_after_id = batch.last().map_or(0, |accession| accession.id);
+        self.after_id.store(next_after_id, Ordering::SeqCst);
+        Ok(batch)
+    }
+}
+
+/// Deletes expired login sessions and API keys.
+pub struct SessionCleanupTask {
+    pub auth_repo: Arc<dyn AuthRepo>,
+}
+
+#[async_trait]
+impl ScheduledTask for SessionCleanupTask {
+    fn name(&self) -> &'static str {
+        "session_cleanup"
+    }
+
+    fn interval(&self) -> Duration {
+        SESSION_CLEANUP_INTERVAL
+    }
+
+    async fn run(&self) -> Result<String, String> {
+        self.auth_repo.delete_expired_sessions().await;
+        self.auth_repo.delete_expired_api_keys().await;
+        Ok("Deleted expired sessions and API keys".to_string())
+    }
+}
+
+/// Checks that the files stored for accessions are still in S3.
+///
+/// We don't record checksums yet, so this can only catch files that have gone missing
+/// or been truncated to nothing rather than silently corrupted ones.
+pub struct FixityCheckTask {
+    pub accessions_repo: Arc<dyn AccessionsRepo>,
+    pub s3_repo: Arc<dyn S3Repo>,
+    cursor: BatchCursor,
+}
+
+impl FixityCheckTask {
+    pub fn new(accessions_repo: Arc<dyn AccessionsRepo>, s3_repo: Arc<dyn S3Repo>) -> Self {
+        Self {
+            accessions_repo,
+            s3_repo,
+            cursor: BatchCursor::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl ScheduledTask for FixityCheckTask {
+    fn name(&self) -> &'static str {
+        "fixity_check"
+    }
+
+    fn interval(&self) -> Duration {
+        FIXITY_CHECK_INTERVAL
+    }
+
+    async fn run(&self) -> Result<String, String> {
+        let batch = self
+            .cursor
+            .next_batch(self.accessions_repo.as_ref(), FIXITY_CHECK_BATCH_SIZE)
+            .await?;
+        if batch.is_empty() {
+            return Ok("Reached the end of the archive, starting over".to_string());
+        }
+        let mut checked = 0;
+        let mut bad_keys = vec![];
+        for accession in &batch {
+            let keys = [&accession.s3_filename, &accession.pdf_s3_filename];
+            for key in keys.into_iter().flatten() {
+                checked += 1;
+                let size = self
+                    .s3_repo
+                    .get_object_size(key)
+                    .await
+                    .map_err(|err| err.to_string());
+                match size {
+                    Ok(size) if size > 0 => {}
+                    Ok(_) => {
+                        warn!("File {key} for accession {} is empty", accession.id);
+                        bad_keys.push(key.clone());
+                    }
+                    Err(err) => {
+                        warn!(%err, "File {key} for accession {} is missing", accession.id);
+                        bad_keys.push(key.clone());
+                    }
+                }
+            }
+        }
+        if bad_keys.is_empty() {
+            Ok(format!(
+                "Checked {checked} files for {} accessions",
+                batch.len()
+            ))
+        } else {
+            Err(format!(
+                "{} of {checked} files are missing or empty: {}",
+                bad_keys.len(),
+                bad_keys.join(", ")
+            ))
+        }
+    }
+}
+
+/// Checks whether the original URLs of accessions are still up, so curators can see
+/// which captures are now the only copy.
+///
+/// Unreachable URLs are logged rather than treated as failures, since link rot is what
+/// the archive exists for.
+pub struct LinkRotCheckTask {
+    pub accessions_repo: Arc<dyn AccessionsRepo>,
+    pub client: Client,
+    cursor: BatchCursor,
+}
+
+impl LinkRotCheckTask {
+    pub fn new(accessions_repo: Arc<dyn AccessionsRepo>, client: Client) -> Self {
+        Self {
+            accessions_repo,
+            client,
+            cursor: BatchCursor::default(),
+        }
+    }
+
+    async fn is_reachable(&self, url: &str) -> bool {
+        let head = self
+            .client
+            .head(url)
+            .timeout(LINK_ROT_REQUEST_TIMEOUT)
+            .send()
+            .await;
+        match head {
+            // plenty of servers don't implement HEAD, fall back to asking for the page
+            Ok(resp) if resp.status() == StatusCode::METHOD_NOT_ALLOWED => self
+                .client
+                .get(url)
+                .timeout(LINK_ROT_REQUEST_TIMEOUT)
+                .send()
+                .await
+                .is_ok_and(|resp| !is_dead_status(resp.status())),
+            Ok(resp) => !is_dead_status(resp.status()),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Client errors other than rate limiting and auth walls mean the page is gone; server
+/// errors mean the site is down.
+fn is_dead_status(status: StatusCode) -> bool {
+    let blocked = [
+        StatusCode::UNAUTHORIZED,
+        StatusCode::FORBIDDEN,
+        StatusCode::TOO_MANY_REQUESTS,
+    ];
+    (status.is_client_error() && !blocked.contains(&status)) || status.is_server_error()
+}
+
+#[async_trait]
+impl ScheduledTask for LinkRotCheckTask {
+    fn name(&self) -> &'static str {
+        "link_rot_check"
+    }
+
+    fn interval(&self) -> Duration {
+        LINK_ROT_CHECK_INTERVAL
+    }
+
+    async fn run(&self) -> Result<String, String> {
+        let batch = self
+            .cursor
+            .next_batch(self.accessions_repo.as_ref(), LINK_ROT_CHECK_BATCH_SIZE)
+            .await?;
+        if batch.is_empty() {
+            return Ok("Reached the end of the archive, starting over".to_string());
+        }
+        let mut unreachable = 0;
+        for accession in &batch {
+            if !self.is_reachable(&accession.seed_url).await {
+                warn!(
+                    "Original URL {} of accession {} is unreachable",
+                    accession.seed_url, accession.id
+                );
+                unreachable += 1;
+            }
+        }
+        Ok(format!(
+            "{unreachable} of {} original URLs are unreachable",
+            batch.len()
+        ))
+    }
+}
+
+/// Retries emails that failed to send.
+pub struct EmailRetryTask {
+    /// The underlying emails repo, not the one wrapped with the outbox
+    pub emails_repo: Arc<dyn EmailsRepo>,
+    pub outbox: EmailOutbox,
+}
+
+#[async_trait]
+impl ScheduledTask for EmailRetryTask {
+    fn name(&self) -> &'static str {
+        "email_retry"
+    }
+
+    fn interval(&self) -> Duration {
+        EMAIL_RETRY_INTERVAL
+    }
+
+    async fn run(&self) -> Result<String, String> {
+        if self.outbox.pending_count() == 0 {
+            return Ok("No emails to retry".to_string());
+        }
+        let summary = self.outbox.retry(self.emails_repo.as_ref()).await;
+        Ok(format!(
+            "Sent {}, will retry {}, gave up on {}",
+            summary.sent, summary.requeued, summary.dropped
+        ))
+    }
+}
+
+/// Clears embargoes once they lapse.
+///
+/// Lapsed embargoes already read as public through the accessions view, this tidies up
+/// the embargo date and announces published accessions on the publication feed. Accessions
+/// that aren't published stay private.
+pub struct EmbargoLiftTask {
+    pub accessions_repo: Arc<dyn AccessionsRepo>,
+    pub publication_feed: PublicationFeed,
+    pub clock: SharedClock,
+}
+
+#[async_trait]
+impl ScheduledTask for EmbargoLiftTask {
+    fn name(&self) -> &'static str {
+        "embargo_lift"
+    }
+
+    fn interval(&self) -> Duration {
+        EMBARGO_LIFT_INTERVAL
+    }
+
+    async fn run(&self) -> Result<String, String> {
+        let lifted = self
+            .accessions_repo
+            .lift_lapsed_embargoes(self.clock.naive_now())
+            .await
+            .map_err(|err| err.to_string())?;
+        if lifted.is_empty() {
+            return Ok("No embargoes have lapsed".to_string());
+        }
+        info!("Lifted embargoes on accessions {lifted:?}");
+        for id in &lifted {
+            match self.accessions_repo.get_one(*id, false).await {
+                Ok(Some(accession)) => self.publication_feed.publish(accession),
+                Ok(None) => {}
+                Err(err) => warn!(%err, "Could not look up accession {id} to publish"),
+            }
+        }
+        Ok(format!("Lifted {} embargoes", lifted.len()))
+    }
+}
+
+/// Sends the hashes of newly stored files to a timestamping service and stores the proofs,
+/// see [`crate::repos::timestamp_repo`].
+///
+/// Hashes that fail to timestamp are left for the next run to retry.
+pub struct TimestampTask {
+    pub provenance_repo: Arc<dyn ProvenanceRepo>,
+    pub timestamp_repo: Arc<dyn TimestampRepo>,
+    pub clock: SharedClock,
+}
+
+#[async_trait]
+impl ScheduledTask for TimestampTask {
+    fn name(&self) -> &'static str {
+        "timestamp"
+    }
+
+    fn interval(&self) -> Duration {
+        TIMESTAMP_INTERVAL
+    }
+
+    async fn run(&self) -> Result<String, String> {
+        let batch = self
+            .provenance_repo
+            .list_untimestamped(TIMESTAMP_BATCH_SIZE)
+            .await
+            .map_err(|err| err.to_string())?;
+        if batch.is_empty() {
+            return Ok("No hashes to timestamp".to_string());
+        }
+        let mut failed = vec![];
+        for provenance in &batch {
+            let accession_id = provenance.accession_id;
+            let Some(sha256) = sha256_bytes(&provenance.sha256) else {
+                warn!("Recorded hash of accession {accession_id} isn't a SHA-256");
+                failed.push(accession_id);
+                continue;
+            };
+            let timestamped_at = self.clock.naive_now();
+            let proof = match self.timestamp_repo.timestamp(&sha256).await {
+                Ok(proof) => proof,
+                Err(err) => {
+                    warn!(%err, "Could not timestamp hash of accession {accession_id}");
+                    failed.push(accession_id);
+                    continue;
+                }
+            };
+            self.provenance_repo
+                .set_timestamp(
+                    accession_id,
+                    self.timestamp_repo.method(),
+                    proof,
+                    timestamped_at,
+                )
+                .await
+                .map_err(|err| err.to_string())?;
+        }
+        if failed.is_empty() {
+            Ok(format!("Timestamped {} hashes", batch.len()))
+        } else {
+            Err(format!(
+                "Could not timestamp {} of {} hashes, for accessions {failed:?}",
+                failed.len(),
+                batch.len()
+            ))
+        }
+    }
+}
+
+/// Emails users who opted in a digest of their week, see [`crate::activity_digest`].
+///
+/// Runs hourly and sends to whoever hasn't had one for a week, so each user's digests stay
+/// a week apart however often the server restarts. Weeks with nothing to report are
+/// skipped without an email.
+pub struct ActivityDigestTask {
+    pub activity_digest_repo: Arc<dyn ActivityDigestRepo>,
+    /// Emails repo wrapped with the outbox, so digests that fail to send are retried
+    pub emails_repo: Arc<dyn EmailsRepo>,
+    pub clock: SharedClock,
+}
+
+#[async_trait]
+impl ScheduledTask for ActivityDigestTask {
+    fn name(&self) -> &'static str {
+        "activity_digest"
+    }
+
+    fn interval(&self) -> Duration {
+        ACTIVITY_DIGEST_INTERVAL
+    }
+
+    async fn run(&self) -> Result<String, String> {
+        let now = self.clock.naive_now();
+        let period_start = now - chrono::Duration::days(ACTIVITY_DIGEST_PERIOD_DAYS);
+        let users = self
+            .activity_digest_repo
+            .list_due_users(period_start)
+            .await
+            .map_err(|err| err.to_string())?;
+        if users.is_empty() {
+            return Ok("No activity digests due".to_string());
+        }
+        let mut sent = 0;
+        for user in &users {
+            let since = user.activity_digest_sent_at.unwrap_or(period_start);
+            let activity = self
+                .activity_digest_repo
+                .list_crawl_activity(&user.email, since)
+                .await
+                .map_err(|err| err.to_string())?;
+            let awaiting_review = if validate_at_least_researcher(&user.role) {
+                self.activity_digest_repo
+                    .list_awaiting_review(user.organization_id)
+                    .await
+                    .map_err(|err| err.to_string())?
+            } else {
+                vec![]
+            };
+            let digest = ActivityDigest::new(&activity, awaiting_review);
+            if !digest.is_empty() {
+                let result = self
+                    .emails_repo
+                    .send_email(user.email.clone(), digest.subject(), digest.html_body())
+                    .await;
+                match result {
+                    Ok(()) => sent += 1,
+                    // queued in the outbox, so still counts as this week's digest
+                    Err(err) => warn!(%err, "Could not send activity digest to {}", user.email),
+                }
+            }
+            self.activity_digest_repo
+                .mark_sent(user.id, now)
+                .await
+                .map_err(|err| err.to_string())?;
+        }
+        Ok(format!(
+            "Sent {sent} activity digests to {} due users",
+            users.len()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+    use crate::models::common::{MetadataLanguage, MetadataScrubbing};
+    use crate::models::request::CreateAccessionRequestRaw;
+    use crate::repos::accessions_repo::DBAccessionsRepo;
+    use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+    use crate::test_db::migrated_test_db;
+    use crate::test_tools::{
+        FixedClock, InMemoryAccessionsRepo, InMemoryActivityDigestRepo, InMemoryEmailsRepo,
+        InMemoryProvenanceRepo, InMemoryS3Repo, InMemoryTimestampRepo,
+    };
+    use entity::sea_orm_active_enums::{DublinMetadataFormat, ScanStatus};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn treats_missing_pages_and_outages_as_dead() {
+        assert!(is_dead_status(StatusCode::NOT_FOUND));
+        assert!(is_dead_status(StatusCode::GONE));
+        assert!(is_dead_status(StatusCode::BAD_GATEWAY));
+        assert!(!is_dead_status(StatusCode::OK));
+        assert!(!is_dead_status(StatusCode::FORBIDDEN));
+        assert!(!is_dead_status(StatusCode::TOO_MANY_REQUESTS));
+    }
+
+    #[tokio::test]
+    async fn fixity_check_works_through_the_archive() {
+        let task = FixityCheckTask::new(
+            Arc::new(InMemoryAccessionsRepo::default()),
+            Arc::new(InMemoryS3Repo {
+                bucket: "test-bucket".to_string(),
+            }),
+        );
+        assert_eq!(
+            task.run().await,
+            Ok("Checked 2 files for 1 accessions".to_string())
+        );
+        assert_eq!(
+            task.run().await,
+            Ok("Reached the end of the archive, starting over".to_string())
+        );
+        assert_eq!(
+            task.run().await,
+            Ok("Checked 2 files for 1 accessions".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn embargo_lift_reports_when_nothing_lapsed() {
+        let task = EmbargoLiftTask {
+            accessions_repo: Arc::new(InMemoryAccessionsRepo::default()),
+            publication_feed: PublicationFeed::default(),
+            clock: SharedClock::default(),
+        };
+        assert_eq!(task.run().await, Ok("No embargoes have lapsed".to_string()));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn embargo_lift_waits_for_the_embargo_to_lapse() {
+        let clock = FixedClock::default();
+        let db = migrated_test_db().await;
+        let accessions_repo = DBAccessionsRepo {
+            db_session: db.clone(),
+            ..Default::default()
+        };
+        accessions_repo
+            .write_one_raw(
+                CreateAccessionRequestRaw {
+                    metadata_language: MetadataLanguage::English,
+                    metadata_title: "Market fire".to_string(),
+                    metadata_description: None,
+                    metadata_time: Default::default(),
+                    metadata_subjects: vec![],
+                    is_private: false,
+                    embargo_until: Some(clock.naive_now() + chrono::Duration::days(1)),
+                    content_warning: None,
+                    metadata_format: DublinMetadataFormat::Wacz,
+                    original_url: "https://example.com".to_string(),
+                    s3_filename: "file.wacz".to_string(),
+                    metadata_scrubbing: MetadataScrubbing::Scrub,
+                },
+                ScanStatus::Clean,
+                true,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await
+            .unwrap();
+        let task = EmbargoLiftTask {
+            accessions_repo: Arc::new(accessions_repo),
+            publication_feed: PublicationFeed::default(),
+            clock: SharedClock::new(clock.clone()),
+        };
+        assert_eq!(task.run().await, Ok("No embargoes have lapsed".to_string()));
+        clock.advance(chrono::Duration::days(2));
+        assert_eq!(task.run().await, Ok("Lifted 1 embargoes".to_string()));
+    }
+
+    #[tokio::test]
+    async fn timestamps_untimestamped_hashes() {
+        let task = TimestampTask {
+            provenance_repo: Arc::new(InMemoryProvenanceRepo::default()),
+            timestamp_repo: Arc::new(InMemoryTimestampRepo::default()),
+            clock: SharedClock::default(),
+        };
+        assert_eq!(task.run().await, Ok("Timestamped 1 hashes".to_string()));
+    }
+
+    #[tokio::test]
+    async fn activity_digest_skips_users_with_nothing_to_report() {
+        let task = ActivityDigestTask {
+            activity_digest_repo: Arc::new(InMemoryActivityDigestRepo::default()),
+            emails_repo: Arc::new(InMemoryEmailsRepo::default()),
+            clock: SharedClock::default(),
+        };
+        assert_eq!(
+            task.run().await,
+            Ok("Sent 1 activity digests to 2 due users".to_string())
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Periodic background tasks.
+//!
+//! Tasks implement [`ScheduledTask`] and are registered with a [`Scheduler`] at startup,
+//! which runs each one on its own tokio task every [`ScheduledTask::interval`]. Every run
+//! is delayed by a random jitter of up to a tenth of the interval, so tasks on several
+//! instances, or several tasks with the same interval, don't all hit the database and S3
+//! at once. Runs are recorded in [`SchedulerMetrics`] for the admin dashboard; like the
+//! pipeline metrics they reset whenever the server restarts. Their start times come from
+//! the shared [`Clock`](crate::clock::Clock).
+//!
+//! Only the instance elected leader runs tasks, see [`crate::leader_election`]. Every
+//! instance keeps its loops going and skips the runs that come round while it isn't the
+//! leader, so a new leader picks up where the old one left off.
+
+use crate::clock::SharedClock;
+use crate::leader_election::SharedLeaderElection;
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use tracing::{debug, error, info};
+
+/// A job that runs periodically in the background.
+#[async_trait]
+pub trait ScheduledTask: Send + Sync {
+    /// Short unique name of the task, used in logs and metrics
+    fn name(&self) -> &'static str;
+
+    /// How long to wait between runs, before jitter
+    fn interval(&self) -> Duration;
+
+    /// Runs the task once.
+    ///
+    /// # Returns
+    /// A short summary of what the run did, e.g. "Checked 50 files"
+    ///
+    /// # Errors
+    /// Returns a description of what went wrong if the run failed
+    async fn run(&self) -> Result<String, String>;
+}
+
+/// Point in time view of one task's runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskStats {
+    pub runs: u64,
+    pub failures: u64,
+    pub last_started_at: Option<NaiveDateTime>,
+    pub last_duration_ms: Option<u64>,
+    pub last_result: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// Thread safe recorder for scheduled task runs, keyed by task name.
+#[derive(Debug, Default)]
+pub struct SchedulerMetrics {
+    tasks: Mutex<BTreeMap<&'static str, TaskStats>>,
+}
+
+pub type SharedSchedulerMetrics = Arc<SchedulerMetrics>;
+
+/// Creates empty scheduler metrics to share between the scheduler and routes.
+pub fn new_scheduler_metrics() -> SharedSchedulerMetrics {
+    Arc::new(SchedulerMetrics::default())
+}
+
+impl SchedulerMetrics {
+    /// Records a task that has been registered, so it shows up before its first run.
+    pub fn task_registered(&self, name: &'static str) {
+        self.tasks.lock().unwrap().entry(name).or_default();
+    }
+
+    /// Records a finished run of a task.
+    pub fn run_finished(
+        &self,
+        name: &'static str,
+        started_at: NaiveDateTime,
+        duration: Duration,
+        result: &Result<String, String>,
+    ) {
+        let mut tasks = self.tasks.lock().unwrap();
+        let stats = tasks.entry(name).or_default();
+        stats.runs += 1;
+        stats.last_started_at = Some(started_at);
+        stats.last_duration_ms = Some(duration.as_millis() as u64);
+        match result {
+            Ok(summary) => stats.last_result = Some(summary.clone()),
+            Err(err) => {
+                stats.failures += 1;
+                stats.last_error = Some(err.clone());
+            }
+        }
+    }
+
+    /// Returns the stats of every registered task, ordered by name.
+    pub fn snapshot(&self) -> Vec<(&'static str, TaskStats)> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, stats)| (*name, stats.clone()))
+            .collect()
+    }
+}
+
+/// Adds a random delay of up to a tenth of `interval`.
+fn jittered(interval: Duration) -> Duration {
+    let max_jitter_ms = (interval.as_millis() / 10) as u64;
+    if max_jitter_ms == 0 {
+        return interval;
+    }
+    interval + Duration::from_millis(rand::thread_rng().gen_range(0..=max_jitter_ms))
+}
+
+/// Runs a task once and records it in the metrics.
+async fn run_once(task: &dyn ScheduledTask, metrics: &SchedulerMetrics, clock: &SharedClock) {
+    let name = task.name();
+    info!("Running scheduled task {name}...");
+    let started_at = clock.naive_now();
+    let timer = Instant::now();
+    let result = task.run().await;
+    match &result {
+        Ok(summary) => info!("Scheduled task {name} finished: {summary}"),
+        Err(err) => error!(%err, "Scheduled task {name} failed"),
+    }
+    metrics.run_finished(name, started_at, timer.elapsed(), &result);
+}
+
+/// Holds the tasks to run in the background until [`Scheduler::start`] is called.
+pub struct Scheduler {
+    tasks: Vec<Arc<dyn ScheduledTask>>,
+    metrics: SharedSchedulerMetrics,
+    clock: SharedClock,
+    leader_election: SharedLeaderElection,
+}
+
+impl Scheduler {
+    pub fn new(
+        metrics: SharedSchedulerMetrics,
+        clock: SharedClock,
+        leader_election: SharedLeaderElection,
+    ) -> Self {
+        Self {
+            tasks: vec![],
+            metrics,
+            clock,
+            leader_election,
+        }
+    }
+
+    /// Adds a task to run once the scheduler starts.
+    pub fn register(mut self, task: Arc<dyn ScheduledTask>) -> Self {
+        self.metrics.task_registered(task.name());
+        self.tasks.push(task);
+        self
+    }
+
+    /// Spawns a loop per task. The first run of each happens after one interval, so a
+    /// restart loop can't hammer anything. Runs are skipped while this instance isn't the
+    /// leader.
+    ///
+    /// # Returns
+    /// Handles of the spawned loops, which run until the server shuts down
+    pub fn start(self) -> Vec<JoinHandle<()>> {
+        self.tasks
+            .into_iter()
+            .map(|task| {
+                let metrics = self.metrics.clone();
+                let clock = self.clock.clone();
+                let leader_election = self.leader_election.clone();
+                tokio::spawn(async move {
+                    loop {
+                        sleep(jittered(task.interval())).await;
+                        if !leader_election.is_leader() {
+                            debug!("Skipping scheduled task {}, not the leader", task.name());
+                            continue;
+                        }
+                        run_once(task.as_ref(), &metrics, &clock).await;
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::FixedClock;
+    use pretty_assertions::assert_eq;
+
+    struct FlakyTask {
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl ScheduledTask for FlakyTask {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn interval(&self) -> Duration {
+            Duration::from_secs(60)
+        }
+
+        async fn run(&self) -> Result<String, String> {
+            if self.fail {
+                Err("boom".to_string())
+            } else {
+                Ok("did things".to_string())
+            }
+        }
+    }
+
+    #[test]
+    fn jitters_up_to_a_tenth_of_the_interval() {
+        let interval = Duration::from_secs(100);
+        for _ in 0..100 {
+            let delay = jittered(interval);
+            assert!(delay >= interval && delay <= Duration::from_secs(110));
+        }
+        assert_eq!(jittered(Duration::ZERO), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn records_runs_and_failures() {
+        let metrics = new_scheduler_metrics();
+        let clock = FixedClock::default();
+        let shared_clock = SharedClock::new(clock.clone());
+        metrics.task_registered("flaky");
+        assert_eq!(metrics.snapshot(), vec![("flaky", TaskStats::default())]);
+
+        run_once(&FlakyTask { fail: false }, &metrics, &shared_clock).await;
+        clock.advance(chrono::Duration::minutes(1));
+        run_once(&FlakyTask { fail: true }, &metrics, &shared_clock).await;
+
+        let (_, stats) = metrics.snapshot().remove(0);
+        assert_eq!(stats.runs, 2);
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.last_result, Some("did things".to_string()));
+        assert_eq!(stats.last_error, Some("boom".to_string()));
+        assert_eq!(stats.last_started_at, Some(shared_clock.naive_now()));
+    }
+}
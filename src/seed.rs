@@ -0,0 +1,372 @@
+//! Fake data for local development, run with `cargo run -- seed`.
+//!
+//! Populates an empty database with bilingual subjects, a user of each role, workflow
+//! labels and a few hundred accessions spread across dates, languages, crawl statuses and
+//! upload formats, so the frontend can be worked on without a copy of production data.
+//! Data is generated from a fixed seed, so every run produces the same archive.
+
+use crate::config::secret_var;
+use crate::models::common::{MetadataLanguage, MetadataScrubbing};
+use crate::models::request::{
+    CreateAccessionRequest, CreateAccessionRequestRaw, CreateMetadataRequest, CreateSubjectRequest,
+};
+use crate::repos::accessions_repo::{AccessionsRepo, DBAccessionsRepo};
+use crate::repos::organizations_repo::DEFAULT_ORGANIZATION_ID;
+use crate::repos::subjects_repo::{DBSubjectsRepo, SubjectsRepo};
+use crate::repos::workflow_labels_repo::{DBWorkflowLabelsRepo, WorkflowLabelsRepo};
+use ::entity::accession::Entity as Accession;
+use ::entity::archive_user::ActiveModel as ArchiveUserActiveModel;
+use ::entity::archive_user::Entity as ArchiveUser;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use entity::archive_user;
+use entity::sea_orm_active_enums::{
+    ContentWarning, CrawlStatus, DublinMetadataFormat, Role, ScanStatus,
+};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ActiveValue, Database, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait};
+use uuid::Uuid;
+
+/// How many accessions a seeded archive has.
+const NUM_ACCESSIONS: usize = 300;
+
+/// Subjects as (English, Arabic) pairs.
+const SUBJECTS: [(&str, &str); 12] = [
+    ("Khartoum", "الخرطوم"),
+    ("Darfur", "دارفور"),
+    ("El Fasher", "الفاشر"),
+    ("Port Sudan", "بورتسودان"),
+    ("Displacement", "النزوح"),
+    ("Humanitarian aid", "المساعدات الإنسانية"),
+    ("Protests", "الاحتجاجات"),
+    ("Resistance committees", "لجان المقاومة"),
+    ("Cultural heritage", "التراث الثقافي"),
+    ("Journalism", "الصحافة"),
+    ("Health", "الصحة"),
+    ("Education", "التعليم"),
+];
+
+/// Title templates as (English, Arabic) pairs, completed with a subject.
+const TITLES: [(&str, &str); 6] = [
+    ("Eyewitness report from", "شهادة عيان من"),
+    ("Statement on", "بيان حول"),
+    ("Photographs of", "صور من"),
+    ("Timeline of events in", "تسلسل الأحداث في"),
+    ("Interview about", "مقابلة حول"),
+    ("Open letter regarding", "رسالة مفتوحة بشأن"),
+];
+
+const SITES: [&str; 5] = [
+    "https://news.example.com",
+    "https://blog.example.org",
+    "https://ngo.example.net",
+    "https://social.example.com",
+    "https://radio.example.sd",
+];
+
+const WORKFLOW_LABELS: [&str; 3] = ["needs-translation", "verify-source", "sensitive"];
+
+/// Counts of what [`seed_database`] wrote.
+#[derive(Debug, Default, PartialEq)]
+pub struct SeedSummary {
+    pub subjects: usize,
+    pub users: usize,
+    pub workflow_labels: usize,
+    pub accessions: usize,
+}
+
+/// Entry point for the `seed` subcommand.
+///
+/// Only needs `POSTGRES_URL` or `POSTGRES_URL_FILE`, the rest of the app config is not read.
+///
+/// # Panics
+/// Panics if the database can't be connected to or seeding fails, there is nothing to
+/// recover to in a one off command
+pub async fn run_seed() {
+    let postgres_url =
+        secret_var("POSTGRES_URL").expect("Missing POSTGRES_URL or POSTGRES_URL_FILE env var");
+    let db_session = Database::connect(postgres_url)
+        .await
+        .expect("Could not connect to db");
+    let summary = seed_database(&db_session)
+        .await
+        .expect("Could not seed database");
+    println!("Seeded database: {summary:?}");
+}
+
+/// Writes fake data into an empty, migrated database.
+///
+/// # Errors
+/// Returns `DbErr::Custom` if the database already has accessions, since seeding is
+/// only meant for throwaway local databases, or any error from writing the data
+pub async fn seed_database(db_session: &DatabaseConnection) -> Result<SeedSummary, DbErr> {
+    if Accession::find().count(db_session).await? > 0 {
+        return Err(DbErr::Custom(
+            "Database already has accessions, refusing to seed it".to_string(),
+        ));
+    }
+    let mut rng = StdRng::seed_from_u64(1956);
+    let mut summary = SeedSummary::default();
+
+    let subjects_repo = DBSubjectsRepo {
+        db_session: db_session.clone(),
+        ..Default::default()
+    };
+    let mut subject_ids: Vec<(i32, i32)> = vec![];
+    for (subject_en, subject_ar) in SUBJECTS {
+        let en = subjects_repo
+            .write_one(
+                CreateSubjectRequest {
+                    metadata_subject: subject_en.to_string(),
+                    lang: MetadataLanguage::English,
+                },
+                DEFAULT_ORGANIZATION_ID,
+                None,
+            )
+            .await?;
+        let ar = subjects_repo
+            .write_one(
+                CreateSubjectRequest {
+                    metadata_subject: subject_ar.to_string(),
+                    lang: MetadataLanguage::Arabic,
+                },
+                DEFAULT_ORGANIZATION_ID,
+                None,
+            )
+            .await?;
+        subject_ids.push((en.id, ar.id));
+    }
+    summary.subjects = subject_ids.len() * 2;
+
+    for role in [Role::Admin, Role::Researcher, Role::Contributor] {
+        let user = ArchiveUserActiveModel {
+            id: ActiveValue::Set(Uuid::from_u128(rng.gen())),
+            email: ActiveValue::Set(format!("{}@example.com", seed_user_name(&role))),
+            is_active: ActiveValue::Set(true),
+            role: ActiveValue::Set(role),
+            email_status: ActiveValue::NotSet,
+            activity_digest: ActiveValue::NotSet,
+            activity_digest_sent_at: ActiveValue::NotSet,
+            organization_id: ActiveValue::Set(DEFAULT_ORGANIZATION_ID),
+        };
+        ArchiveUser::insert(user)
+            .on_conflict(
+                OnConflict::column(archive_user::Column::Email)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .do_nothing()
+            .exec(db_session)
+            .await?;
+        summary.users += 1;
+    }
+
+    let workflow_labels_repo = DBWorkflowLabelsRepo {
+        db_session: db_session.clone(),
+    };
+    let mut label_ids = vec![];
+    for label in WORKFLOW_LABELS {
+        label_ids.push(workflow_labels_repo.write_one(label.to_string()).await?.id);
+    }
+    summary.workflow_labels = label_ids.len();
+
+    let accessions_repo = DBAccessionsRepo {
+        db_session: db_session.clone(),
+        ..Default::default()
+    };
+    let first_day = NaiveDate::from_ymd_opt(2019, 4, 6)
+        .and_then(|date| date.and_hms_opt(12, 0, 0))
+        .expect("Seed start date should be valid");
+    for n in 0..NUM_ACCESSIONS {
+        let id =
+            write_fake_accession(&accessions_repo, &mut rng, &subject_ids, first_day, n).await?;
+        if rng.gen_bool(0.15) {
+            let label_id = *label_ids.choose(&mut rng).expect("Labels are not empty");
+            workflow_labels_repo.add_to_accession(id, label_id).await?;
+        }
+    }
+    summary.accessions = NUM_ACCESSIONS;
+    Ok(summary)
+}
+
+fn seed_user_name(role: &Role) -> &'static str {
+    match role {
+        Role::Admin => "admin",
+        Role::Researcher => "researcher",
+        Role::Contributor => "contributor",
+    }
+}
+
+async fn write_fake_accession(
+    accessions_repo: &DBAccessionsRepo,
+    rng: &mut StdRng,
+    subject_ids: &[(i32, i32)],
+    first_day: NaiveDateTime,
+    n: usize,
+) -> Result<i32, DbErr> {
+    let metadata_language = if rng.gen_bool(0.6) {
+        MetadataLanguage::English
+    } else {
+        MetadataLanguage::Arabic
+    };
+    let num_subjects = rng.gen_range(1..=3);
+    let subject_indexes = rand::seq::index::sample(rng, SUBJECTS.len(), num_subjects).into_vec();
+    let (title_en, title_ar) = TITLES.choose(rng).expect("Titles are not empty");
+    let (subject_en, subject_ar) = SUBJECTS[subject_indexes[0]];
+    let (metadata_title, metadata_description, metadata_subjects) = match metadata_language {
+        MetadataLanguage::English => (
+            format!("{title_en} {subject_en}"),
+            format!("Archived material about {subject_en}, item {n}."),
+            subject_indexes.iter().map(|i| subject_ids[*i].0).collect(),
+        ),
+        MetadataLanguage::Arabic => (
+            format!("{title_ar} {subject_ar}"),
+            format!("مواد مؤرشفة حول {subject_ar}، العنصر {n}."),
+            subject_indexes.iter().map(|i| subject_ids[*i].1).collect(),
+        ),
+    };
+    let metadata_description = rng.gen_bool(0.8).then_some(metadata_description);
+    let metadata_time = first_day + Duration::days(rng.gen_range(0..2400));
+    let is_private = rng.gen_bool(0.1);
+    // a few embargoes, some already lapsed so the lift task has something to do
+    let embargo_until = rng
+        .gen_bool(0.05)
+        .then(|| Utc::now().naive_utc() + Duration::days(rng.gen_range(-30..60)));
+    let content_warning = rng.gen_bool(0.1).then(|| {
+        [
+            ContentWarning::GraphicViolence,
+            ContentWarning::SexualViolence,
+            ContentWarning::HumanRemains,
+            ContentWarning::Distressing,
+        ]
+        .choose(rng)
+        .cloned()
+        .expect("Content warnings are not empty")
+    });
+    let site = SITES.choose(rng).expect("Sites are not empty");
+    let url = format!("{site}/posts/{n}?utm_source=seed");
+
+    if rng.gen_bool(0.2) {
+        let (metadata_format, extension) = [
+            (DublinMetadataFormat::Jpeg, "jpg"),
+            (DublinMetadataFormat::Png, "png"),
+            (DublinMetadataFormat::Mp4, "mp4"),
+            (DublinMetadataFormat::Wacz, "wacz"),
+            (DublinMetadataFormat::Warc, "warc"),
+        ]
+        .choose(rng)
+        .cloned()
+        .expect("Formats are not empty");
+        let scan_status = [
+            ScanStatus::Clean,
+            ScanStatus::Clean,
+            ScanStatus::NotScanned,
+            ScanStatus::Failed,
+        ]
+        .choose(rng)
+        .cloned()
+        .expect("Scan statuses are not empty");
+        let metadata_scrubbed = !matches!(
+            metadata_format,
+            DublinMetadataFormat::Wacz | DublinMetadataFormat::Warc
+        );
+        return accessions_repo
+            .write_one_raw(
+                CreateAccessionRequestRaw {
+                    metadata_language,
+                    metadata_title,
+                    metadata_description,
+                    metadata_time,
+                    metadata_subjects,
+                    is_private,
+                    embargo_until,
+                    content_warning,
+                    metadata_format,
+                    original_url: url,
+                    s3_filename: format!("seed/{n}.{extension}"),
+                    metadata_scrubbing: MetadataScrubbing::Scrub,
+                },
+                scan_status,
+                metadata_scrubbed,
+                DEFAULT_ORGANIZATION_ID,
+            )
+            .await;
+    }
+
+    let crawl_status = [
+        CrawlStatus::Complete,
+        CrawlStatus::Complete,
+        CrawlStatus::Complete,
+        CrawlStatus::Pending,
+        CrawlStatus::Error,
+        CrawlStatus::BadCrawl,
+    ]
+    .choose(rng)
+    .cloned()
+    .expect("Crawl statuses are not empty");
+    let s3_filename = (crawl_status == CrawlStatus::Complete).then(|| format!("seed/{n}.wacz"));
+    let metadata = CreateMetadataRequest {
+        title: metadata_title,
+        description: metadata_description,
+        subjects: metadata_subjects,
+    };
+    let (metadata_en, metadata_ar) = match metadata_language {
+        MetadataLanguage::English => (Some(metadata), None),
+        MetadataLanguage::Arabic => (None, Some(metadata)),
+    };
+    accessions_repo
+        .write_one(
+            CreateAccessionRequest {
+                url,
+                metadata_en,
+                metadata_ar,
+                metadata_time,
+                browser_profile: None,
+                crawler: None,
+                capture_mode: Default::default(),
+                is_private,
+                embargo_until,
+                content_warning,
+                metadata_format: DublinMetadataFormat::Wacz,
+                s3_filename,
+            },
+            Some(Uuid::from_u128(rng.gen())),
+            Uuid::from_u128(rng.gen()),
+            format!("seed-job-{n}"),
+            crawl_status,
+            DEFAULT_ORGANIZATION_ID,
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db::migrated_test_db;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    #[ignore = "needs TEST_POSTGRES_URL"]
+    async fn seeds_an_empty_database_once() {
+        let db = migrated_test_db().await;
+        let db_session = db.clone();
+        let summary = seed_database(&db_session).await.unwrap();
+
+        assert_eq!(
+            summary,
+            SeedSummary {
+                subjects: 24,
+                users: 3,
+                workflow_labels: 3,
+                accessions: NUM_ACCESSIONS,
+            }
+        );
+        assert_eq!(
+            Accession::find().count(&db_session).await.unwrap(),
+            NUM_ACCESSIONS as u64
+        );
+        assert!(seed_database(&db_session).await.is_err());
+    }
+}
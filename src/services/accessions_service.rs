@@ -3,27 +3,125 @@
 //! This module handles the business logic for creating, retrieving, and listing
 //! archival records, including their associated web crawls and metadata in both
 //! Arabic and English.
-use crate::models::request::AccessionPaginationWithPrivate;
+use crate::access_log_export::{
+    bundle, merge, AccessLogFormat, AccessLogManifest, ACCESS_LOG_CONTENT_TYPE,
+};
+use crate::activity_digest::{CRAWL_COMPLETED, CRAWL_FAILED};
+use crate::capture_quality::assess_capture;
+use crate::citation_export::{doi_metadata, to_csl_json, to_ris, CitationFormat};
+use crate::clock::{SharedClock, SharedIdGen};
+use crate::comments::{find_mentions, MentionEmail};
+use crate::config::ScanEnforcement;
+use crate::content_digest::read_wacz_content_digest;
+use crate::crawl_queue::SharedCrawlQueue;
+use crate::crawl_state_machine::{
+    CrawlError, CrawlStateMachine, CrawlSteps, LaunchedCrawl, PollOutcome, StoredCapture,
+    POLL_INTERVAL,
+};
+use crate::file_access::{
+    file_proxy_url, issue_file_token, verify_file_token, FileTokenError, PRIVATE_FILE_ACCESSED,
+};
+use crate::file_type::{check_file_type, is_warc, SNIFF_LENGTH, WARC_SNIFF_LENGTH};
+use crate::iiif::{
+    build_manifest, image_dimensions, image_format, ImageResource, DIMENSIONS_READ_LENGTH,
+    MANIFEST_CONTENT_TYPE,
+};
+use crate::machine_translation::{draft_update, translation_source};
+use crate::memento::{
+    closest_capture, memento_url, parse_accept_datetime, Capture, MementoUrls,
+    LINK_FORMAT_CONTENT_TYPE,
+};
+use crate::metadata_scrubber::{scrub_stream, MetadataScrubber, ScrubError};
+use crate::models::auth::AuthenticatedUser;
+use crate::models::common::{
+    CaptureMode, ListingVisibility, MetadataLanguage, MetadataScrubbing, RelationDirection,
+    TimelineInterval,
+};
+use crate::models::request::{
+    AccessionPaginationWithPrivate, BadCapturesPagination, ReviewQueuePagination,
+    TopAccessionsQuery,
+};
 use crate::models::request::{
-    CreateAccessionRequest, CreateAccessionRequestRaw, CreateCrawlRequest, UpdateAccessionRequest,
+    BulkVisibilityRequest, CommentRequest, CreateAccessionRelationRequest, CreateAccessionRequest,
+    CreateAccessionRequestRaw, CreateCrawlRequest, UpdateAccessionRequest,
 };
-use crate::models::response::{GetOneAccessionResponse, ListAccessionsResponse};
-use crate::repos::accessions_repo::AccessionsRepo;
+use crate::models::response::{
+    AccessionCrawlStatusResponse, AccessionRelationResponse, AccessionStatsResponse,
+    AccessionTimelineResponse, AccessionsWithMetadataResponse, BulkVisibilityResponse,
+    CommentResponse, CreateAccessionCrawlResponse, DerivativeResponse, DoiResponse,
+    DryRunAccessionResponse, DuplicateAccessionsResponse, GetOneAccessionResponse,
+    GetOnePublicAccessionResponse, ListAccessionPagesResponse, ListAccessionRelationsResponse,
+    ListAccessionsResponse, ListCommentsResponse, ListPublicAccessionsResponse,
+    PipelineStatusResponse, ProvenanceResponse, PublicAccessionsWithMetadataResponse,
+    ReindexStatusResponse, ReplaceAccessionFileResponse, ReviewQueueResponse,
+    S3BackfillStatusResponse, SocialMetadataResponse, StaticExportStatusResponse,
+    TopAccessionsResponse, UploadProgressResponse,
+};
+use crate::pipeline_metrics::SharedPipelineMetrics;
+use crate::provenance::{signed_message, ProvenanceSigner};
+use crate::publication_feed::PublicationFeed;
+use crate::publication_workflow::{bulk_visibility_transition, check_transition, TransitionError};
+use crate::reindex::{ReindexProgress, REINDEX_BATCH_SIZE};
+use crate::repos::accession_events_repo::AccessionEventsRepo;
+use crate::repos::accession_relations_repo::{AccessionRelationsRepo, RelatedAccession};
+use crate::repos::accessions_repo::{AccessionSelection, AccessionsRepo};
+use crate::repos::audit_log_repo::{AuditEntry, AuditLogFilter, AuditLogRepo};
 use crate::repos::browsertrix_repo::BrowsertrixRepo;
+use crate::repos::comments_repo::CommentsRepo;
+use crate::repos::content_digest_repo::ContentDigestRepo;
+use crate::repos::crawler_repo::{CrawlArchive, CrawlerRepo, Crawlers};
+use crate::repos::doi_repo::DoiRepo;
 use crate::repos::emails_repo::EmailsRepo;
+use crate::repos::media_transcoder_repo::{derivative_file_type, MediaTranscoderRepo};
+use crate::repos::oembed_repo::OembedRepo;
+use crate::repos::pagination::PageWindow;
+use crate::repos::pdf_renderer_repo::PdfRendererRepo;
+use crate::repos::provenance_repo::ProvenanceRepo;
+use crate::repos::quick_capture_repo::QuickCaptureRepo;
 use crate::repos::s3_repo::S3Repo;
+use crate::repos::social_metadata_repo::SocialMetadataRepo;
+use crate::repos::translation_repo::TranslationRepo;
+use crate::repos::video_capture_repo::VideoCaptureRepo;
+use crate::repos::video_downloader_repo::VideoDownloaderRepo;
+use crate::repos::virus_scanner_repo::{ScanVerdict, VirusScannerRepo};
+use crate::s3_backfill::S3BackfillProgress;
+use crate::s3_keys::{quarantine_key, S3KeyScheme};
 use crate::services::subjects_service::SubjectsService;
+use crate::services::uploads_service::{file_type, is_upload_key};
+use crate::social_metadata::identify_post;
+use crate::static_export::{
+    manifest_key, page_key, search_index_key, SearchIndex, StaticExportManifest,
+    StaticExportProgress, STATIC_EXPORT_PAGE_SIZE,
+};
+use crate::upload_progress::UploadProgressRegistry;
+use crate::url_canonicalizer::canonicalize_url;
+use crate::wacz::{read_wacz_pages, WaczPagesCache};
+use ::entity::accession_provenance::Model as AccessionProvenanceModel;
 use ::entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
+use ::entity::comment::Model as CommentModel;
+use ::entity::social_metadata::Model as SocialMetadataModel;
+use async_trait::async_trait;
+use axum::body::Body;
 use axum::extract::multipart::Field;
 use axum::extract::Multipart;
-use axum::http::StatusCode;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use bytes::Bytes;
-use entity::sea_orm_active_enums::{CrawlStatus, DublinMetadataFormat};
+use chrono::Datelike;
+use entity::sea_orm_active_enums::{
+    AccessionEventKind, DerivativeKind, DublinMetadataFormat, PublicationState, Role, ScanStatus,
+};
 use futures::StreamExt;
+use sea_orm::{ActiveEnum, DbErr};
+use serde::Serialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
@@ -35,6 +133,59 @@ use validator::Validate;
 // to the archive
 static FIVE_MB: usize = 5 * 1024 * 1024;
 
+/// How many upload chunks can queue up for the virus scanner before the upload waits on it.
+const VIRUS_SCAN_BUFFERED_CHUNKS: usize = 8;
+
+/// Audit log event for an admin replacing an accession's stored file
+pub const ACCESSION_FILE_REPLACED: &str = "accession_file_replaced";
+/// Audit log event for an admin exporting who accessed an accession
+pub const ACCESS_LOG_EXPORTED: &str = "access_log_exported";
+
+/// Most entries an access log export holds from each of its sources, newest first.
+pub const ACCESS_LOG_EXPORT_LIMIT: u64 = 100_000;
+
+/// S3 key for a derivative. Derivatives live under their own prefix, apart from the
+/// uploaded originals, and regenerating one overwrites it.
+fn derivative_key(id: i32, kind: &DerivativeKind, extension: &str) -> String {
+    let name = match kind {
+        DerivativeKind::WebVideo => "web",
+        DerivativeKind::Thumbnail => "thumbnail",
+    };
+    format!("derivatives/{id}/{name}.{extension}")
+}
+
+/// Reads chunks from the start of a multipart field until there are enough bytes to
+/// sniff its file type, or the field ends.
+async fn read_field_head(field: &mut Field<'_>) -> Result<Vec<Bytes>, Response> {
+    let mut head = Vec::new();
+    let mut head_size = 0;
+    while head_size < SNIFF_LENGTH {
+        match field.chunk().await {
+            Ok(Some(chunk)) => {
+                head_size += chunk.len();
+                head.push(chunk);
+            }
+            Ok(None) => break,
+            Err(err) => {
+                error!("Failed to read chunk from stream: {}", err);
+                return Err((StatusCode::BAD_REQUEST, "Failed to read file stream").into_response());
+            }
+        }
+    }
+    Ok(head)
+}
+
+/// An accession with everything its detail responses show alongside it, see
+/// [`AccessionsService::find_one_detail`].
+pub struct AccessionDetail {
+    pub accession: AccessionWithMetadataModel,
+    pub wacz_url: String,
+    pub pdf_url: Option<String>,
+    pub derivatives: Vec<DerivativeResponse>,
+    pub relations: Vec<AccessionRelationResponse>,
+    pub social_metadata: Option<SocialMetadataResponse>,
+}
+
 #[derive(PartialEq, Eq)]
 enum MultiPartExtractionStep {
     ExpectMetadata,
@@ -46,9 +197,50 @@ enum MultiPartExtractionStep {
 #[derive(Clone)]
 pub struct AccessionsService {
     pub accessions_repo: Arc<dyn AccessionsRepo>,
+    /// The hosted Browsertrix, which older captures are replayed and backfilled from
     pub browsertrix_repo: Arc<dyn BrowsertrixRepo>,
+    /// Crawlers new accessions can be crawled with
+    pub crawlers: Crawlers,
     pub emails_repo: Arc<dyn EmailsRepo>,
     pub s3_repo: Arc<dyn S3Repo>,
+    /// Renders PDF derivatives of captures, `None` when no renderer is configured
+    pub pdf_renderer_repo: Option<Arc<dyn PdfRendererRepo>>,
+    /// Transcodes image and video derivatives, `None` when no transcoder is configured
+    pub media_transcoder_repo: Option<Arc<dyn MediaTranscoderRepo>>,
+    /// Downloads videos for video captures, `None` when no downloader is configured
+    pub video_downloader_repo: Option<Arc<dyn VideoDownloaderRepo>>,
+    /// Scans uploaded files for malware, `None` when no scanner is configured
+    pub virus_scanner_repo: Option<Arc<dyn VirusScannerRepo>>,
+    /// Drafts missing metadata translations, `None` when no translator is configured
+    pub translation_repo: Option<Arc<dyn TranslationRepo>>,
+    /// Mints DOIs for public accessions, `None` when no DOI registration agency is configured
+    pub doi_repo: Option<Arc<dyn DoiRepo>>,
+    pub scan_enforcement: ScanEnforcement,
+    pub s3_key_scheme: S3KeyScheme,
+    /// Base of absolute links to the API, see [`crate::config::AppConfig::public_api_url`]
+    pub public_api_url: String,
+    pub wacz_pages_cache: WaczPagesCache,
+    pub pipeline_metrics: SharedPipelineMetrics,
+    /// Limits how many crawls run at once
+    pub crawl_queue: SharedCrawlQueue,
+    pub upload_progress: UploadProgressRegistry,
+    pub accession_events_repo: Arc<dyn AccessionEventsRepo>,
+    pub accession_relations_repo: Arc<dyn AccessionRelationsRepo>,
+    pub comments_repo: Arc<dyn CommentsRepo>,
+    pub provenance_repo: Arc<dyn ProvenanceRepo>,
+    /// Signs the hashes of stored files, `None` when no signing key is configured
+    pub provenance_signer: Option<Arc<ProvenanceSigner>>,
+    pub social_metadata_repo: Arc<dyn SocialMetadataRepo>,
+    pub content_digest_repo: Arc<dyn ContentDigestRepo>,
+    /// Asks platforms about crawled social media posts
+    pub oembed_repo: Arc<dyn OembedRepo>,
+    pub audit_log_repo: Arc<dyn AuditLogRepo>,
+    pub publication_feed: PublicationFeed,
+    pub s3_backfill: S3BackfillProgress,
+    pub static_export: StaticExportProgress,
+    pub reindex: ReindexProgress,
+    pub clock: SharedClock,
+    pub ids: SharedIdGen,
 }
 
 impl AccessionsService {
@@ -56,319 +248,2753 @@ impl AccessionsService {
     ///
     /// # Arguments
     /// * `params` - Struct containing all pagination and filtering parameters
+    /// * `language` - Language the reader prefers, for the display fields
+    /// * `visibility` - Who the listing is for. Items include internal Browsertrix
+    ///   identifiers unless it is [`ListingVisibility::Public`], so only endpoints that
+    ///   require an authenticated user should list anything else
     ///
     /// # Returns
     /// JSON response containing paginated accessions or an error response
-    pub async fn list(self, params: AccessionPaginationWithPrivate) -> Response {
+    pub async fn list(
+        self,
+        params: AccessionPaginationWithPrivate,
+        language: MetadataLanguage,
+        visibility: ListingVisibility,
+    ) -> Response {
+        let page = params.page;
+        let per_page = params.per_page;
+        let (rows, num_pages) = match self.list_rows(params).await {
+            Ok(rows) => rows,
+            Err(err) => {
+                error!(%err, "Error occurred paginating accessions");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        };
+        let body = match visibility {
+            ListingVisibility::Public => Json(ListPublicAccessionsResponse {
+                items: rows
+                    .into_iter()
+                    .map(|row| {
+                        PublicAccessionsWithMetadataResponse::from(row).displayed_in(language)
+                    })
+                    .collect(),
+                num_pages,
+                page,
+                per_page,
+            })
+            .into_response(),
+            ListingVisibility::Authenticated => Json(ListAccessionsResponse {
+                items: rows
+                    .into_iter()
+                    .map(|row| AccessionsWithMetadataResponse::from(row).displayed_in(language))
+                    .collect(),
+                num_pages,
+                page,
+                per_page,
+            })
+            .into_response(),
+        };
+        ([(header::VARY, "accept-language")], body).into_response()
+    }
+
+    /// Counts the accessions matching a listing's filters by their Dublin Core date.
+    ///
+    /// # Arguments
+    /// * `params` - Struct containing all filtering parameters; paging is ignored
+    /// * `interval` - How wide each bucket is
+    ///
+    /// # Returns
+    /// JSON response containing the buckets or an error response
+    pub async fn timeline(
+        self,
+        params: AccessionPaginationWithPrivate,
+        interval: TimelineInterval,
+    ) -> Response {
+        match self.accessions_repo.timeline(params, interval).await {
+            Err(err) => {
+                error!(%err, "Error occurred building accession timeline");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+            Ok(buckets) => Json(AccessionTimelineResponse {
+                interval,
+                buckets: buckets.into_iter().map(Into::into).collect(),
+            })
+            .into_response(),
+        }
+    }
+
+    /// Exports a page of accessions as references for reference managers.
+    ///
+    /// # Arguments
+    /// * `params` - Struct containing all pagination and filtering parameters
+    /// * `format` - File format to export the references in
+    ///
+    /// # Returns
+    /// The references as a file download, or an error response
+    pub async fn export_citations(
+        self,
+        params: AccessionPaginationWithPrivate,
+        format: CitationFormat,
+    ) -> Response {
+        let lang = params.lang;
+        let params = AccessionPaginationWithPrivate {
+            count: false,
+            ..params
+        };
+        let rows = match self.list_rows(params).await {
+            Ok((rows, _)) => rows,
+            Err(err) => {
+                error!(%err, "Error occurred exporting accessions");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        };
+        let headers = [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", format.file_name()),
+            ),
+        ];
+        match format {
+            CitationFormat::Ris => (headers, to_ris(&rows, lang)).into_response(),
+            CitationFormat::CslJson => (headers, Json(to_csl_json(&rows, lang))).into_response(),
+        }
+    }
+
+    /// Fetches a page of accessions as raw view rows alongside the total page count.
+    ///
+    /// Unlike [`AccessionsService::list`] this does not shape the response, so that
+    /// different API versions can serialize the rows however they need to. The page
+    /// count is empty if `params.count` is false.
+    pub async fn list_rows(
+        &self,
+        params: AccessionPaginationWithPrivate,
+    ) -> Result<(Vec<AccessionWithMetadataModel>, Option<u64>), DbErr> {
         info!(
-            "Getting page {} of {} accessions with per page {}...",
+            "Getting page {} of {} accession rows with per page {}...",
             params.page, params.lang, params.per_page
         );
+        self.accessions_repo.list_paginated(params).await
+    }
 
-        let rows = self.accessions_repo.list_paginated(params.clone()).await;
+    /// Fetches a single accession view row by ID without shaping the response.
+    pub async fn find_one(
+        &self,
+        id: i32,
+        private: bool,
+    ) -> Result<Option<AccessionWithMetadataModel>, DbErr> {
+        self.accessions_repo.get_one(id, private).await
+    }
+
+    /// Fetches a single accession view row by ID whether it is public or private.
+    pub async fn find_one_any_visibility(
+        &self,
+        id: i32,
+    ) -> Result<Option<AccessionWithMetadataModel>, DbErr> {
+        match self.find_one(id, false).await? {
+            Some(accession) => Ok(Some(accession)),
+            None => self.find_one(id, true).await,
+        }
+    }
 
-        match rows {
+    /// Fetches an accession a user can work on, whether it is public or private.
+    ///
+    /// Accessions of other organizations are treated as missing, so users can't tell
+    /// which IDs they hold, see [`AuthenticatedUser::can_access_organization`].
+    pub async fn find_one_managed(
+        &self,
+        id: i32,
+        user: &AuthenticatedUser,
+    ) -> Result<Option<AccessionWithMetadataModel>, DbErr> {
+        Ok(self
+            .find_one_any_visibility(id)
+            .await?
+            .filter(|accession| user.can_access_organization(accession.organization_id)))
+    }
+
+    /// Like [`AccessionsService::find_one_managed`], mapping failures to error responses.
+    async fn find_one_managed_or_respond(
+        &self,
+        id: i32,
+        user: &AuthenticatedUser,
+    ) -> Result<AccessionWithMetadataModel, Response> {
+        match self.find_one_managed(id, user).await {
             Err(err) => {
-                error!(%err, "Error occurred paginating accessions");
-                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+                error!(%err, "Error occurred retrieving accession");
+                Err((StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response())
+            }
+            Ok(None) => Err((StatusCode::NOT_FOUND, "No such record").into_response()),
+            Ok(Some(accession)) => Ok(accession),
+        }
+    }
+
+    /// Retrieves a single accession by ID with its associated metadata and WACZ URL.
+    ///
+    /// The accession may be private and includes internal Browsertrix identifiers, see
+    /// [`AccessionsService::get_one_public`] for the anonymous equivalent.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `viewer` - The signed in user, who private file links are bound to
+    /// * `language` - Language the reader prefers, for the display fields
+    ///
+    /// # Returns
+    /// JSON response containing the accession details or an error response
+    pub async fn get_one(
+        self,
+        id: i32,
+        viewer: AuthenticatedUser,
+        country: Option<String>,
+        language: MetadataLanguage,
+    ) -> Response {
+        info!("Getting private accession with id {id}");
+        match self.find_one_detail(id, Some(&viewer), country).await {
+            Ok(Some(found)) => {
+                let response = GetOneAccessionResponse {
+                    accession: AccessionsWithMetadataResponse::from(found.accession)
+                        .displayed_in(language),
+                    wacz_url: found.wacz_url,
+                    pdf_url: found.pdf_url,
+                    derivatives: found.derivatives,
+                    relations: found.relations,
+                    social_metadata: found.social_metadata,
+                };
+                ([(header::VARY, "accept-language")], Json(response)).into_response()
             }
-            Ok(rows) => {
-                let resp = ListAccessionsResponse {
-                    items: rows.0.into_iter().map(Into::into).collect(),
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(message) => (StatusCode::INTERNAL_SERVER_ERROR, message).into_response(),
+        }
+    }
 
-                    num_pages: rows.1,
-                    page: params.page,
-                    per_page: params.per_page,
+    /// Retrieves a single public accession by ID, trimmed for anonymous users.
+    pub async fn get_one_public(
+        self,
+        id: i32,
+        country: Option<String>,
+        language: MetadataLanguage,
+    ) -> Response {
+        info!("Getting public accession with id {id}");
+        match self.find_one_detail(id, None, country).await {
+            Ok(Some(found)) => {
+                let response = GetOnePublicAccessionResponse {
+                    accession: PublicAccessionsWithMetadataResponse::from(found.accession)
+                        .displayed_in(language),
+                    wacz_url: found.wacz_url,
+                    pdf_url: found.pdf_url,
+                    derivatives: found.derivatives,
+                    relations: found.relations,
+                    social_metadata: found.social_metadata,
                 };
-                Json(resp).into_response()
+                ([(header::VARY, "accept-language")], Json(response)).into_response()
             }
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(message) => (StatusCode::INTERNAL_SERVER_ERROR, message).into_response(),
         }
     }
-    /// Retrieves a single accession by ID with its associated metadata and WACZ URL.
+
+    /// Builds a IIIF manifest for a public image accession, see [`crate::iiif`].
+    ///
+    /// The image is linked with a presigned URL, so viewers need to refetch the manifest
+    /// once it expires after an hour.
     ///
     /// # Arguments
     /// * `id` - The unique identifier of the accession
     ///
     /// # Returns
-    /// JSON response containing the accession details or an error response
-    pub async fn get_one(self, id: i32, private: bool) -> Response {
-        info!("Getting {private} accession with id {id}");
-        let query_result = self.accessions_repo.get_one(id, private).await;
-        match query_result {
-            Err(query_result) => {
-                error!(%query_result, "Error occurred retrieving accession");
+    /// The manifest as JSON-LD, 404 if the accession isn't public or isn't an image
+    pub async fn get_iiif_manifest(self, id: i32) -> Response {
+        info!("Getting IIIF manifest for accession with id {id}");
+        let accession = match self.find_one(id, false).await {
+            Ok(Some(accession)) => accession,
+            Ok(None) => return (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred retrieving accession");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        };
+        let (Some(format), Some(s3_filename)) = (
+            image_format(&accession.dublin_metadata_format),
+            accession.s3_filename.as_deref(),
+        ) else {
+            return (StatusCode::NOT_FOUND, "Accession is not an image").into_response();
+        };
+        let head = match self
+            .s3_repo
+            .get_object_range(s3_filename, 0, DIMENSIONS_READ_LENGTH - 1)
+            .await
+        {
+            Ok(head) => head,
+            Err(err) => {
+                error!(%err, "Failed to read start of image {s3_filename}");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read image").into_response();
+            }
+        };
+        let Some((width, height)) = image_dimensions(&head) else {
+            error!("Could not find dimensions of image {s3_filename}");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Could not read image dimensions",
+            )
+                .into_response();
+        };
+        let url = match self.s3_repo.get_presigned_url(s3_filename, 3600).await {
+            Ok(url) => url,
+            Err(err) => {
+                error!(%err, "Error occurred generating presigned image url");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Could not generate image url",
+                )
+                    .into_response();
+            }
+        };
+        let manifest_url = format!(
+            "{}/api/v1/accessions/{id}/iiif/manifest.json",
+            self.public_api_url.trim_end_matches('/')
+        );
+        let manifest = build_manifest(
+            &accession,
+            &manifest_url,
+            ImageResource {
+                url,
+                format,
+                width,
+                height,
+            },
+        );
+        (
+            [
+                (header::CONTENT_TYPE, MANIFEST_CONTENT_TYPE),
+                // viewers on other sites fetch manifests straight from the browser
+                (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*"),
+            ],
+            Json(manifest),
+        )
+            .into_response()
+    }
+
+    /// Looks up the public captures of a URL for Memento, see [`crate::memento`].
+    async fn find_captures(&self, url: &str) -> Result<Vec<Capture>, Response> {
+        match self
+            .accessions_repo
+            .list_public_captures(&canonicalize_url(url))
+            .await
+        {
+            Ok(captures) if captures.is_empty() => {
+                Err((StatusCode::NOT_FOUND, "No captures of this URL").into_response())
+            }
+            Ok(captures) => Ok(captures),
+            Err(err) => {
+                error!(%err, "Error occurred listing captures");
+                Err((StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response())
+            }
+        }
+    }
+
+    fn memento_urls(&self, url: &str) -> MementoUrls {
+        let api_url = format!("{}/api/v1", self.public_api_url.trim_end_matches('/'));
+        MementoUrls::new(&api_url, url)
+    }
+
+    /// Redirects to the public capture of a URL closest to a datetime, as a Memento TimeGate.
+    ///
+    /// # Arguments
+    /// * `url` - The original URL
+    /// * `accept_datetime` - The client's `Accept-Datetime` header, if it sent one
+    ///
+    /// # Returns
+    /// A redirect to the capture's memento, 404 if there are no captures or 400 if the
+    /// datetime isn't a valid HTTP date
+    pub async fn memento_timegate(self, url: String, accept_datetime: Option<String>) -> Response {
+        info!("Memento TimeGate request for {url}");
+        let datetime = match accept_datetime.as_deref().map(parse_accept_datetime) {
+            None => None,
+            Some(Some(datetime)) => Some(datetime),
+            Some(None) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    "Accept-Datetime must be an HTTP date",
+                )
+                    .into_response()
+            }
+        };
+        let captures = match self.find_captures(&url).await {
+            Ok(captures) => captures,
+            Err(resp) => return resp,
+        };
+        let Some(capture) = closest_capture(&captures, datetime) else {
+            return (StatusCode::NOT_FOUND, "No captures of this URL").into_response();
+        };
+        (
+            StatusCode::FOUND,
+            [
+                (header::LOCATION, memento_url(capture.accession_id)),
+                (header::VARY, "accept-datetime".to_string()),
+                (header::LINK, self.memento_urls(&url).timegate_link_header()),
+            ],
+        )
+            .into_response()
+    }
+
+    /// Lists every public capture of a URL as a Memento TimeMap in link format.
+    ///
+    /// # Arguments
+    /// * `url` - The original URL
+    pub async fn memento_timemap(self, url: String) -> Response {
+        info!("Memento TimeMap request for {url}");
+        let captures = match self.find_captures(&url).await {
+            Ok(captures) => captures,
+            Err(resp) => return resp,
+        };
+        (
+            [(header::CONTENT_TYPE, LINK_FORMAT_CONTENT_TYPE)],
+            self.memento_urls(&url).timemap(&captures),
+        )
+            .into_response()
+    }
+
+    /// Records that an accession was viewed and had a WACZ URL issued for it.
+    ///
+    /// Events are written in the background and failures only logged, so analytics
+    /// can never slow down or break reading the archive.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `country` - Country code of the client, see [`crate::client_country`]
+    fn record_usage(&self, id: i32, country: Option<String>) {
+        let accession_events_repo = self.accession_events_repo.clone();
+        tokio::spawn(async move {
+            let kinds = vec![AccessionEventKind::View, AccessionEventKind::WaczUrl];
+            if let Err(err) = accession_events_repo.record(id, kinds, country).await {
+                warn!(%err, "Could not record usage of accession {id}");
+            }
+        });
+    }
+
+    /// Summarises how much an accession has been used, broken down by country.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `days` - Only count usage from the last this many days, or all time if None
+    ///
+    /// # Returns
+    /// JSON response containing the usage stats or an error response
+    pub async fn get_stats(self, id: i32, days: Option<i64>) -> Response {
+        info!("Getting usage stats for accession with id {id}");
+        let since = days.map(|days| self.clock.naive_now() - chrono::Duration::days(days));
+        match self
+            .accession_events_repo
+            .count_for_accession(id, since)
+            .await
+        {
+            Ok(counts) => Json(AccessionStatsResponse::from_counts(id, counts)).into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred counting accession events");
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
             }
-            Ok(query_result) => {
-                if let Some(accession) = query_result {
-                    self.enrich_accession_with_wacz_url(accession).await
-                } else {
-                    (StatusCode::NOT_FOUND, "No such record").into_response()
-                }
+        }
+    }
+
+    /// Lists the most used accessions across the archive.
+    ///
+    /// # Arguments
+    /// * `query` - What usage to rank by, over how long and how many to return
+    ///
+    /// # Returns
+    /// JSON response containing the top accessions or an error response
+    pub async fn list_top(self, query: TopAccessionsQuery) -> Response {
+        info!("Getting top {} accessions by {:?}", query.limit, query.kind);
+        let since = query
+            .days
+            .map(|days| self.clock.naive_now() - chrono::Duration::days(days));
+        match self
+            .accession_events_repo
+            .top_accessions(query.kind.clone(), since, query.limit)
+            .await
+        {
+            Ok(totals) => Json(TopAccessionsResponse {
+                kind: query.kind,
+                items: totals.into_iter().map(Into::into).collect(),
+            })
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred listing top accessions");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
             }
         }
     }
 
+    /// Lists the pages captured in a public accession's WACZ file.
+    ///
+    /// Page lists are read from the WACZ's `pages/pages.jsonl` in S3 and cached in
+    /// memory since WACZ files never change once uploaded.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    ///
+    /// # Returns
+    /// JSON response containing the captured pages or an error response
+    pub async fn list_pages(self, id: i32) -> Response {
+        info!("Listing pages for accession with id {id}");
+        let accession = match self.find_one(id, false).await {
+            Err(err) => {
+                error!(%err, "Error occurred retrieving accession");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+            Ok(None) => return (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Ok(Some(accession)) => accession,
+        };
+        let s3_filename = match (accession.s3_filename, accession.dublin_metadata_format) {
+            (Some(s3_filename), DublinMetadataFormat::Wacz) => s3_filename,
+            _ => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    "No archived WACZ file for this accession",
+                )
+                    .into_response()
+            }
+        };
+
+        let cached_pages = self
+            .wacz_pages_cache
+            .lock()
+            .expect("WACZ pages cache lock poisoned")
+            .get(&s3_filename)
+            .cloned();
+        let pages = match cached_pages {
+            Some(pages) => pages,
+            None => match read_wacz_pages(self.s3_repo.as_ref(), &s3_filename).await {
+                Ok(pages) => {
+                    let pages = Arc::new(pages);
+                    self.wacz_pages_cache
+                        .lock()
+                        .expect("WACZ pages cache lock poisoned")
+                        .put(s3_filename, pages.clone());
+                    pages
+                }
+                Err(err) => {
+                    error!(%err, "Error occurred reading pages from WACZ file");
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Could not read pages from wacz file",
+                    )
+                        .into_response();
+                }
+            },
+        };
+        Json(ListAccessionPagesResponse {
+            accession_id: id,
+            pages: pages.iter().cloned().map(Into::into).collect(),
+        })
+        .into_response()
+    }
+
+    /// Looks up an accession with everything its detail responses show, for both API
+    /// versions, and records that it was viewed.
+    ///
+    /// Private accessions are included when a `viewer` is signed in, whose private file
+    /// links are bound to them, except those of organizations they can't access.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `viewer` - The signed in user, or `None` for anonymous readers
+    /// * `country` - Country code of the client, see [`crate::client_country`]
+    ///
+    /// # Returns
+    /// The accession, `None` if there is no such record, or a message on failure
+    pub async fn find_one_detail(
+        &self,
+        id: i32,
+        viewer: Option<&AuthenticatedUser>,
+        country: Option<String>,
+    ) -> Result<Option<AccessionDetail>, &'static str> {
+        let accession = match self.find_one(id, viewer.is_some()).await {
+            Err(err) => {
+                error!(%err, "Error occurred retrieving accession");
+                return Err("Internal database error");
+            }
+            Ok(accession) => accession,
+        };
+        // private accessions of other organizations are hidden from signed in users
+        let Some(accession) = accession.filter(|accession| {
+            viewer.is_none_or(|viewer| viewer.can_access_organization(accession.organization_id))
+        }) else {
+            return Ok(None);
+        };
+        let wacz_url = self.resolve_wacz_url(&accession, viewer).await?;
+        self.record_usage(id, country);
+        let pdf_url = self.resolve_pdf_url(&accession, viewer).await;
+        let derivatives = self.resolve_derivatives(&accession, viewer).await;
+        let relations = self.resolve_relations(&accession, viewer).await;
+        let social_metadata = self.resolve_social_metadata(&accession).await;
+        Ok(Some(AccessionDetail {
+            accession,
+            wacz_url,
+            pdf_url,
+            derivatives,
+            relations,
+            social_metadata,
+        }))
+    }
+
     /// Enriches an accession with a WACZ URL.
     ///
-    /// This method determines the source of the WACZ file:
-    /// 1. If an `s3_filename` is present and the format is WACZ, the file is stored in our own
-    ///    DigitalOcean Spaces storage. We generate a presigned URL for direct access.
-    /// 2. If no `s3_filename` is present but a `job_run_id` exists, the file is still in Browsertrix.
-    ///    We retrieve the replay URL from the Browsertrix service.
-    /// 3. If neither is present return an error; this shouldn't happen
+    /// See [`AccessionsService::resolve_wacz_url`] for how the URL is determined.
     async fn enrich_accession_with_wacz_url(
         self,
         accession: AccessionWithMetadataModel,
+        viewer: &AuthenticatedUser,
     ) -> Response {
-        let accession_for_response = accession.clone();
+        match self.resolve_wacz_url(&accession, Some(viewer)).await {
+            Ok(wacz_url) => {
+                let pdf_url = self.resolve_pdf_url(&accession, Some(viewer)).await;
+                let derivatives = self.resolve_derivatives(&accession, Some(viewer)).await;
+                let relations = self.resolve_relations(&accession, Some(viewer)).await;
+                let social_metadata = self.resolve_social_metadata(&accession).await;
+                let resp = GetOneAccessionResponse {
+                    accession: accession.into(),
+                    wacz_url,
+                    pdf_url,
+                    derivatives,
+                    relations,
+                    social_metadata,
+                };
+                Json(resp).into_response()
+            }
+            Err(message) => (StatusCode::INTERNAL_SERVER_ERROR, message).into_response(),
+        }
+    }
+
+    /// Resolves the URL a client can use to fetch an accession's WACZ file.
+    ///
+    /// This method determines the source of the WACZ file:
+    /// 1. If an `s3_filename` is present, the file is stored in our own DigitalOcean Spaces
+    ///    storage. We generate a URL for direct access, see [`AccessionsService::file_url`].
+    ///    For WARC, image and video accessions this is the uploaded file itself, and replay
+    ///    tools tell WARCs from WACZs by the `.warc` extension of its key.
+    /// 2. If no `s3_filename` is present but a `job_run_id` exists, the file is still in Browsertrix.
+    ///    We retrieve the replay URL from the Browsertrix service.
+    /// 3. If neither is present return an error. Uploads have no crawl fields, so they are
+    ///    never looked up in Browsertrix, but they always have an `s3_filename`
+    ///
+    /// # Returns
+    /// The WACZ URL, or a client safe error message if it could not be generated
+    pub async fn resolve_wacz_url(
+        &self,
+        accession: &AccessionWithMetadataModel,
+        viewer: Option<&AuthenticatedUser>,
+    ) -> Result<String, &'static str> {
         match (
             accession.s3_filename.as_deref(),
-            &accession.dublin_metadata_format,
+            accession.job_run_id.as_deref(),
         ) {
             // If it has an s3 filename, then we know its in our own digital ocean spaces storage
-            (Some(s3_filename), DublinMetadataFormat::Wacz) => {
-                match self.s3_repo.get_presigned_url(s3_filename, 3600).await {
-                    Ok(presigned_url) => {
-                        let resp = GetOneAccessionResponse {
-                            accession: accession_for_response.into(),
-                            wacz_url: presigned_url,
-                        };
-                        Json(resp).into_response()
-                    }
-                    Err(err) => {
+            (Some(s3_filename), _) => {
+                self.file_url(accession, s3_filename, viewer)
+                    .await
+                    .map_err(|err| {
                         error!(%err, "Error occurred generating presigned url");
-                        (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            "Could not retrieving wacz url from s3 storage",
-                        )
-                            .into_response()
-                    }
-                }
+                        "Could not retrieving wacz url from s3 storage"
+                    })
             }
-            _ => {
-                if let Some(ref job_run_id) = accession.job_run_id {
-                    match self.browsertrix_repo.get_wacz_url(job_run_id).await {
-                        Ok(wacz_url) => {
-                            let resp = GetOneAccessionResponse {
-                                accession: accession_for_response.into(),
-                                wacz_url,
-                            };
-                            Json(resp).into_response()
-                        }
-                        Err(err) => {
-                            error!(%err, "Error occurred retrieving wacz url");
-                            (
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                "Error retrieving wacz url",
-                            )
-                                .into_response()
-                        }
-                    }
-                } else {
-                    error!(
-                        "Error occurred generating wacz URL, no s3 filename or job run id present"
-                    );
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "Could not retrieving wacz url from s3 storage",
-                    )
-                        .into_response()
-                }
+            (None, Some(job_run_id)) => self
+                .browsertrix_repo
+                .get_wacz_url(job_run_id)
+                .await
+                .map_err(|err| {
+                    error!(%err, "Error occurred retrieving wacz url");
+                    "Error retrieving wacz url"
+                }),
+            (None, None) => {
+                error!(
+                    "Error occurred generating wacz URL for accession {}, no s3 filename or job run id present",
+                    accession.id
+                );
+                Err("Accession has no file to replay")
             }
         }
     }
-    /// Creates a new accession by initiating a web crawl and storing the metadata.
-    ///
-    /// This method performs the following steps:
-    /// 1. Launches a web crawl for the specified URL
-    /// 2. Polls the crawl status for up to 30 minutes
-    /// 3. Creates an accession record once the crawl is complete
+
+    /// Resolves a URL for an accession's PDF derivative, if it has one.
     ///
-    /// You should validate that `metadata_subjects` exist in the
-    /// payload before calling this method - it will error out
-    /// if they don't.
+    /// Failures are logged rather than returned since the PDF is supplementary to the WACZ.
+    async fn resolve_pdf_url(
+        &self,
+        accession: &AccessionWithMetadataModel,
+        viewer: Option<&AuthenticatedUser>,
+    ) -> Option<String> {
+        let pdf_s3_filename = accession.pdf_s3_filename.as_deref()?;
+        match self.file_url(accession, pdf_s3_filename, viewer).await {
+            Ok(url) => Some(url),
+            Err(err) => {
+                error!(%err, "Error occurred generating presigned pdf url");
+                None
+            }
+        }
+    }
+
+    /// Resolves URLs for an accession's derivatives.
     ///
-    /// # Arguments
-    /// * `payload` - The creation request containing URL and metadata
-    /// * `user_email` - Email address to send user to upon successful crawl
-    pub async fn create_one(self, payload: CreateAccessionRequest, user_email: String) {
-        let create_crawl_request = CreateCrawlRequest {
-            url: payload.url.clone(),
-            browser_profile: payload.browser_profile.clone(),
+    /// Like the PDF, derivatives are supplementary so failures are logged and the affected
+    /// derivatives left out.
+    async fn resolve_derivatives(
+        &self,
+        accession: &AccessionWithMetadataModel,
+        viewer: Option<&AuthenticatedUser>,
+    ) -> Vec<DerivativeResponse> {
+        let derivatives = match self.accessions_repo.list_derivatives(accession.id).await {
+            Ok(derivatives) => derivatives,
+            Err(err) => {
+                error!(%err, "Error occurred listing derivatives");
+                return vec![];
+            }
+        };
+        let mut responses = Vec::with_capacity(derivatives.len());
+        for derivative in derivatives {
+            match self
+                .file_url(accession, &derivative.s3_filename, viewer)
+                .await
+            {
+                Ok(url) => responses.push(DerivativeResponse {
+                    kind: derivative.kind,
+                    content_type: derivative.content_type,
+                    url,
+                }),
+                Err(err) => error!(%err, "Error occurred generating presigned derivative url"),
+            }
+        }
+        responses
+    }
+
+    /// Lists the accessions related to an accession that the viewer can see.
+    ///
+    /// Anonymous viewers only see public accessions, signed in users also see the private
+    /// accessions of their organization. Like derivatives, relations are supplementary so
+    /// failures are logged and no relations returned.
+    async fn resolve_relations(
+        &self,
+        accession: &AccessionWithMetadataModel,
+        viewer: Option<&AuthenticatedUser>,
+    ) -> Vec<AccessionRelationResponse> {
+        let related = match self
+            .accession_relations_repo
+            .list_for_accession(accession.id, viewer.is_some())
+            .await
+        {
+            Ok(related) => related,
+            Err(err) => {
+                error!(%err, "Error occurred listing accession relations");
+                return vec![];
+            }
+        };
+        related
+            .into_iter()
+            .filter(|related| match viewer {
+                Some(viewer) => {
+                    !related.accession.is_private
+                        || viewer.can_access_organization(related.accession.organization_id)
+                }
+                None => !related.accession.is_private,
+            })
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Gets who posted the social media post an accession was crawled from, if it was one.
+    ///
+    /// Failing to read it is only logged, leaving it out of the response.
+    async fn resolve_social_metadata(
+        &self,
+        accession: &AccessionWithMetadataModel,
+    ) -> Option<SocialMetadataResponse> {
+        self.social_metadata_repo
+            .get_one(accession.id)
+            .await
+            .inspect_err(|err| error!(%err, "Error occurred retrieving social media metadata"))
+            .ok()
+            .flatten()
+            .map(Into::into)
+    }
+
+    /// Resolves the URL a client fetches one of an accession's files in S3 from.
+    ///
+    /// Private accessions link to the file proxy with a token bound to the viewer, see
+    /// [`crate::file_access`], since a presigned URL would work for anyone it's shared
+    /// with. Other accessions get a presigned URL.
+    ///
+    /// # Errors
+    /// Returns an error for private accessions when there's no viewer to bind the link to
+    async fn file_url(
+        &self,
+        accession: &AccessionWithMetadataModel,
+        key: &str,
+        viewer: Option<&AuthenticatedUser>,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        if !accession.is_private {
+            return self.s3_repo.get_presigned_url(key, 3600).await;
+        }
+        let viewer = viewer.ok_or("Private files can only be linked for a signed in user")?;
+        let token = issue_file_token(viewer, accession.id, key)?;
+        Ok(file_proxy_url(&self.public_api_url, &token))
+    }
+
+    /// Streams a private accession's file to the user a file link was issued to.
+    ///
+    /// Each access is written to the audit log before anything is streamed, and the
+    /// client's `Range` header is passed on so replay tools can seek through a WACZ.
+    ///
+    /// # Arguments
+    /// * `token` - The token from a link made by [`AccessionsService::file_url`]
+    /// * `range` - The client's HTTP `Range` header, if any
+    /// * `viewer` - The signed in user following the link
+    pub async fn stream_private_file(
+        self,
+        token: String,
+        range: Option<String>,
+        viewer: AuthenticatedUser,
+    ) -> Response {
+        let claims = match verify_file_token(&token, &viewer) {
+            Ok(claims) => claims,
+            Err(FileTokenError::Invalid) => {
+                return (StatusCode::BAD_REQUEST, "Invalid file link").into_response();
+            }
+            Err(FileTokenError::Expired) => {
+                return (StatusCode::GONE, "File link expired, reload the accession")
+                    .into_response();
+            }
+            Err(FileTokenError::WrongUser) => {
+                warn!(
+                    "User {} tried to use a file link issued to someone else",
+                    viewer.user_id
+                );
+                return (
+                    StatusCode::FORBIDDEN,
+                    "File link was issued to someone else",
+                )
+                    .into_response();
+            }
+        };
+        info!(
+            "Streaming {} of accession {} to {}",
+            claims.key, claims.accession_id, viewer.user_id
+        );
+        if let Err(err) = self
+            .audit_log_repo
+            .record(AuditEntry {
+                actor_email: viewer.user_id,
+                impersonator_email: viewer.impersonator,
+                event: PRIVATE_FILE_ACCESSED,
+                entity_type: "accession",
+                entity_id: claims.accession_id.to_string(),
+                details: Some(json!({"key": claims.key, "range": range})),
+            })
+            .await
+        {
+            error!(%err, "Error occurred auditing private file access");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response();
+        }
+        let object = match self
+            .s3_repo
+            .get_object_stream(&claims.key, range.as_deref())
+            .await
+        {
+            Ok(object) => object,
+            Err(err) => {
+                error!(%err, "Error occurred streaming private file from s3");
+                return (
+                    StatusCode::BAD_GATEWAY,
+                    "Could not retrieve file from s3 storage",
+                )
+                    .into_response();
+            }
+        };
+        let status = match object.content_range {
+            Some(_) => StatusCode::PARTIAL_CONTENT,
+            None => StatusCode::OK,
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+        // the proxy response is as private as the file, so nothing on the way may keep it
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("private, no-store"),
+        );
+        for (name, value) in [
+            (header::CONTENT_TYPE, object.content_type),
+            (
+                header::CONTENT_LENGTH,
+                object.content_length.map(|len| len.to_string()),
+            ),
+            (header::CONTENT_RANGE, object.content_range),
+        ] {
+            if let Some(value) = value.and_then(|value| HeaderValue::from_str(&value).ok()) {
+                headers.insert(name, value);
+            }
+        }
+        let body = futures::stream::unfold(object.body, |mut body| async move {
+            body.next().await.map(|chunk| (chunk, body))
+        });
+        (status, headers, Body::from_stream(body)).into_response()
+    }
+
+    /// Generates web friendly derivatives of an uploaded image or video: a thumbnail for
+    /// both, plus a downscaled H.264 rendition for videos.
+    ///
+    /// This is a no-op when no transcoder is configured or the accession isn't an image
+    /// or video. It's slow so is meant to run in the background; errors are logged since
+    /// the original upload is already safely stored.
+    ///
+    /// # Arguments
+    /// * `id` - The accession the derivatives belong to
+    /// * `s3_filename` - The S3 key of the original file
+    /// * `metadata_format` - The format of the original file
+    pub async fn create_media_derivatives(
+        &self,
+        id: i32,
+        s3_filename: &str,
+        metadata_format: &DublinMetadataFormat,
+    ) {
+        let Some(media_transcoder_repo) = &self.media_transcoder_repo else {
+            debug!("No media transcoder configured, skipping derivatives for accession {id}");
+            return;
+        };
+        let kinds: &[DerivativeKind] = match metadata_format {
+            DublinMetadataFormat::Mp4 => &[DerivativeKind::Thumbnail, DerivativeKind::WebVideo],
+            DublinMetadataFormat::Jpeg | DublinMetadataFormat::Png => &[DerivativeKind::Thumbnail],
+            DublinMetadataFormat::Wacz | DublinMetadataFormat::Warc => return,
+        };
+        let source_url = self
+            .s3_repo
+            .get_presigned_url(s3_filename, 3600)
+            .await
+            .map_err(|err| err.to_string());
+        let source_url = match source_url {
+            Ok(source_url) => source_url,
+            Err(err) => {
+                error!(%err, "Error occurred generating presigned url for transcoding");
+                return;
+            }
+        };
+        for kind in kinds {
+            info!("Generating {kind:?} derivative for accession {id}");
+            let derivative = match media_transcoder_repo
+                .transcode(&source_url, kind.clone())
+                .await
+            {
+                Ok(derivative) => derivative,
+                Err(err) => {
+                    error!(%err, "Error occurred generating {kind:?} derivative");
+                    continue;
+                }
+            };
+            let (extension, content_type) = derivative_file_type(kind);
+            let key = derivative_key(id, kind, extension);
+            if let Err(err) = self
+                .s3_repo
+                .upload_from_bytes(&key, derivative, content_type)
+                .await
+            {
+                error!(%err, "Error occurred uploading {kind:?} derivative to S3");
+                continue;
+            }
+            if let Err(err) = self
+                .accessions_repo
+                .write_derivative(id, kind.clone(), key, content_type.to_string())
+                .await
+            {
+                error!(%err, "Error occurred saving {kind:?} derivative");
+            }
+        }
+    }
+
+    /// Renders the captured page to PDF, uploads it to S3 and links it to the accession.
+    ///
+    /// This is a no-op when no PDF renderer is configured. Errors are logged since
+    /// this runs after the accession has already been created.
+    ///
+    /// # Arguments
+    /// * `id` - The accession the PDF belongs to
+    /// * `url` - The page to render
+    pub async fn create_pdf_derivative(&self, id: i32, url: &str) {
+        let Some(pdf_renderer_repo) = &self.pdf_renderer_repo else {
+            debug!("No PDF renderer configured, skipping PDF derivative for accession {id}");
+            return;
+        };
+        info!("Rendering PDF derivative for accession {id}");
+        let pdf = match pdf_renderer_repo.render_url_to_pdf(url).await {
+            Ok(pdf) => pdf,
+            Err(err) => {
+                error!(%err, "Error occurred rendering PDF derivative");
+                return;
+            }
+        };
+        let pdf_s3_filename = format!("{}.pdf", self.ids.new_id());
+        if let Err(err) = self
+            .s3_repo
+            .upload_from_bytes(&pdf_s3_filename, pdf, "application/pdf")
+            .await
+        {
+            error!(%err, "Error occurred uploading PDF derivative to S3");
+            return;
+        }
+        if let Err(err) = self
+            .accessions_repo
+            .set_pdf_s3_filename(id, pdf_s3_filename)
+            .await
+        {
+            error!(%err, "Error occurred saving PDF derivative filename");
+        }
+    }
+
+    /// Creates a pending accession for a crawl and archives it in the background with
+    /// [`AccessionsService::create_one`].
+    ///
+    /// Validate the payload as for [`AccessionsService::create_one`] before calling this.
+    ///
+    /// # Arguments
+    /// * `payload` - The creation request containing URL and metadata
+    /// * `user_email` - Email address to send user to upon successful crawl
+    /// * `organization_id` - The organization the accession belongs to
+    ///
+    /// # Returns
+    /// 201 with the accession's ID and where to check on its crawl, or an error response
+    pub async fn start_crawl(
+        self,
+        payload: CreateAccessionRequest,
+        user_email: String,
+        organization_id: i32,
+    ) -> Response {
+        let payload = payload.trimmed();
+        let accession_id = match self
+            .accessions_repo
+            .write_pending(payload.clone(), organization_id)
+            .await
+        {
+            Ok(accession_id) => accession_id,
+            Err(err) => {
+                error!(%err, "Error occurred writing pending accession for {}", payload.url);
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        };
+        info!(
+            "Pending accession {accession_id} created for url {}",
+            payload.url
+        );
+        tokio::spawn(async move {
+            self.create_one(accession_id, payload, user_email, organization_id)
+                .await;
+        });
+        let response = CreateAccessionCrawlResponse {
+            accession_id,
+            status_url: format!("/api/v1/accessions/{accession_id}/status"),
+        };
+        (StatusCode::CREATED, Json(response)).into_response()
+    }
+
+    /// Reports how the crawl of an accession the viewer can work on is going.
+    ///
+    /// # Arguments
+    /// * `id` - The ID of the accession
+    /// * `viewer` - The signed in user asking
+    pub async fn get_crawl_status(self, id: i32, viewer: AuthenticatedUser) -> Response {
+        match self.find_one_managed_or_respond(id, &viewer).await {
+            Ok(accession) => Json(AccessionCrawlStatusResponse {
+                accession_id: accession.id,
+                crawl_status: accession.crawl_status,
+                crawl_quality_reason: accession.crawl_quality_reason,
+            })
+            .into_response(),
+            Err(response) => response,
+        }
+    }
+
+    /// Archives a crawl into a pending accession, see [`AccessionsService::start_crawl`].
+    ///
+    /// This method performs the following steps:
+    /// 1. Waits in the crawl queue until the crawlers have capacity
+    /// 2. Launches a web crawl for the specified URL with the crawler the payload asks for,
+    ///    or captures the page directly if it asks for a quick capture, or downloads its
+    ///    video if it asks for a video capture
+    /// 3. Polls the crawl status for up to 30 minutes
+    /// 4. Records the stored crawl on the pending accession once the crawl is complete
+    /// 5. Renders a PDF derivative of the page if a renderer is configured
+    ///
+    /// Steps 2 to 5 are taken by a [`CrawlStateMachine`], see [`crate::crawl_state_machine`].
+    ///
+    /// You should validate that the subjects in each language's
+    /// metadata exist and that the crawler it asks for, and the video downloader for
+    /// video captures, are configured before calling this method - it will error out if
+    /// they don't.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The pending accession, see [`AccessionsRepo::write_pending`]
+    /// * `payload` - The creation request containing URL and metadata
+    /// * `user_email` - Email address to send user to upon successful crawl
+    /// * `organization_id` - The organization the accession belongs to
+    pub async fn create_one(
+        self,
+        accession_id: i32,
+        payload: CreateAccessionRequest,
+        user_email: String,
+        organization_id: i32,
+    ) {
+        let Some(crawler) = self.crawlers.get(payload.crawler) else {
+            error!(
+                "Crawler for url {} is not configured, aborting accession creation",
+                payload.url
+            );
+            self.fail_pending_crawl(accession_id).await;
+            return;
+        };
+        let crawler = match payload.capture_mode {
+            CaptureMode::Full => crawler,
+            CaptureMode::Quick => Arc::new(QuickCaptureRepo::new(
+                crawler,
+                self.clock.clone(),
+                self.ids.clone(),
+            )) as Arc<dyn CrawlerRepo>,
+            CaptureMode::Video => {
+                let Some(video_downloader_repo) = self.video_downloader_repo.clone() else {
+                    error!(
+                        "Video downloader for url {} is not configured, aborting accession creation",
+                        payload.url
+                    );
+                    self.fail_pending_crawl(accession_id).await;
+                    return;
+                };
+                Arc::new(VideoCaptureRepo::new(
+                    video_downloader_repo,
+                    crawler,
+                    self.ids.clone(),
+                ))
+            }
+        };
+        // Held until this crawl is done with so the next one in the queue can launch
+        let _turn = self.crawl_queue.wait_for_turn(&payload.url).await;
+        CrawlStateMachine::new(CrawlJob {
+            service: self,
+            crawler,
+            accession_id,
+            payload: payload.trimmed(),
+            user_email,
+            organization_id,
+        })
+        .run()
+        .await;
+    }
+
+    /// Marks a pending accession's crawl as errored, logging rather than returning failures.
+    async fn fail_pending_crawl(&self, accession_id: i32) {
+        if let Err(err) = self.accessions_repo.fail_crawl(accession_id).await {
+            error!(%err, "Error occurred marking crawl of accession {accession_id} as failed");
+        }
+    }
+
+    /// Reports what creating an accession from a crawl would do without launching the crawl.
+    ///
+    /// Callers should validate the payload and check its subjects exist first, the same
+    /// as before calling [`AccessionsService::create_one`].
+    ///
+    /// # Arguments
+    /// * `payload` - The creation request containing URL and metadata
+    ///
+    /// # Returns
+    /// JSON response describing the crawl that would be launched or an error response
+    pub async fn dry_run_create(self, payload: CreateAccessionRequest) -> Response {
+        let canonical_url = canonicalize_url(&payload.url);
+        info!("Dry run of crawl request for url {}", payload.url);
+        match self
+            .accessions_repo
+            .find_ids_by_canonical_url(&canonical_url)
+            .await
+        {
+            Ok(duplicate_accession_ids) => Json(DryRunAccessionResponse {
+                url: payload.url,
+                canonical_url,
+                crawler: self.crawlers.backend(payload.crawler),
+                browser_profile_id: payload
+                    .browser_profile
+                    .map(|profile| profile.profile_id().to_string()),
+                duplicate_accession_ids,
+            })
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred looking up duplicate accessions");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Streams progress of a running multipart upload as server-sent events.
+    ///
+    /// Each event carries a JSON [`UploadProgressResponse`]. The stream ends once the
+    /// upload completes or fails.
+    ///
+    /// # Arguments
+    /// * `upload_id` - The S3 multipart upload ID
+    ///
+    /// # Returns
+    /// An SSE response, or 404 if no upload with that ID is running
+    pub fn stream_upload_progress(&self, upload_id: &str) -> Response {
+        let Some(updates) = self.upload_progress.subscribe(upload_id) else {
+            return (StatusCode::NOT_FOUND, "No such upload in progress").into_response();
+        };
+        let events = updates.map(|progress| {
+            Event::default()
+                .event("progress")
+                .json_data(UploadProgressResponse::from(progress))
+        });
+        Sse::new(events)
+            .keep_alive(KeepAlive::default())
+            .into_response()
+    }
+
+    /// Streams accessions as they become public as server-sent events.
+    ///
+    /// Each event carries a JSON [`PublicAccessionsWithMetadataResponse`]. The stream
+    /// stays open until the client disconnects.
+    ///
+    /// # Returns
+    /// An SSE response
+    pub fn stream_published(&self) -> Response {
+        let events = self.publication_feed.subscribe().map(|accession| {
+            Event::default()
+                .event("accession")
+                .id(accession.id.to_string())
+                .json_data(PublicAccessionsWithMetadataResponse::from(accession))
+        });
+        Sse::new(events)
+            .keep_alive(KeepAlive::default())
+            .into_response()
+    }
+
+    /// Checks a crawl's stored WACZ for signs of a bad capture, see [`assess_capture`].
+    ///
+    /// # Returns
+    /// Why the capture looks bad, or `None` if it looks fine or its size couldn't be read
+    async fn assess_stored_capture(&self, key: &str, seed_url: &str) -> Option<String> {
+        let size = self
+            .s3_repo
+            .get_object_size(key)
+            .await
+            .map_err(|err| err.to_string());
+        let size = match size {
+            Ok(size) => size,
+            Err(err) => {
+                error!(%err, "Failed to get size of WACZ {key}, skipping quality checks");
+                return None;
+            }
+        };
+        let pages = read_wacz_pages(self.s3_repo.as_ref(), key)
+            .await
+            .map_err(|err| err.to_string())
+            .inspect_err(|err| warn!(%err, "Failed to read pages of WACZ {key}"))
+            .ok();
+        assess_capture(size, pages.as_deref(), seed_url)
+    }
+
+    /// Flags an accession as a bad capture for curators to review.
+    ///
+    /// Failing to flag it is only logged, since the accession itself was written fine.
+    async fn flag_bad_capture(&self, id: i32, reason: String) {
+        warn!("Flagging accession {id} as a bad capture: {reason}");
+        if let Err(err) = self.accessions_repo.flag_bad_crawl(id, reason).await {
+            error!(%err, "Error occurred flagging accession {id} as a bad capture");
+        }
+    }
+
+    /// Lists captures flagged as bad for curators to review, most recent first.
+    ///
+    /// # Arguments
+    /// * `pagination` - The page to list
+    /// * `organization_id` - Only list captures of this organization, every one if `None`
+    ///
+    /// # Returns
+    /// JSON response containing the flagged accessions or an error response
+    pub async fn list_bad_captures(
+        self,
+        pagination: BadCapturesPagination,
+        organization_id: Option<i32>,
+    ) -> Response {
+        let window = PageWindow::new(pagination.page, pagination.per_page);
+        match self
+            .accessions_repo
+            .list_bad_crawls(window, organization_id)
+            .await
+        {
+            Err(err) => {
+                error!(%err, "Error occurred listing bad captures");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+            Ok((rows, num_pages)) => Json(ListAccessionsResponse {
+                items: rows.into_iter().map(Into::into).collect(),
+                num_pages: Some(num_pages),
+                page: window.page,
+                per_page: window.per_page,
+            })
+            .into_response(),
+        }
+    }
+
+    /// Lists everything across organizations that needs a person to look at it: bad
+    /// captures and accessions in review, a page at a time, alongside crawls that failed
+    /// since the server started.
+    ///
+    /// # Arguments
+    /// * `pagination` - The page of accessions to list
+    ///
+    /// # Returns
+    /// JSON response containing the queue or an error response
+    pub async fn review_queue(self, pagination: ReviewQueuePagination) -> Response {
+        let window = PageWindow::new(pagination.page, pagination.per_page);
+        match self.accessions_repo.list_review_queue(window).await {
+            Err(err) => {
+                error!(%err, "Error occurred listing the review queue");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+            Ok((rows, num_pages)) => Json(ReviewQueueResponse {
+                items: rows.into_iter().map(Into::into).collect(),
+                failed_crawls: self
+                    .pipeline_metrics
+                    .snapshot()
+                    .recent_failures
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+                num_pages,
+                page: window.page,
+                per_page: window.per_page,
+            })
+            .into_response(),
+        }
+    }
+
+    /// Accepts a capture flagged as bad after a curator has checked it, marking its crawl
+    /// complete again and publishing it on the feed if it is public.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `viewer` - The curator accepting the capture
+    ///
+    /// # Returns
+    /// A success status, 409 if the accession isn't flagged, or an error response
+    pub async fn clear_bad_capture(self, id: i32, viewer: AuthenticatedUser) -> Response {
+        info!("Accepting bad capture of accession with id {id}");
+        if let Err(response) = self.find_one_managed_or_respond(id, &viewer).await {
+            return response;
+        }
+        match self.accessions_repo.clear_bad_crawl(id).await {
+            Ok(Some(())) => {
+                self.announce_if_public(id).await;
+                (StatusCode::OK, "Capture accepted").into_response()
+            }
+            Ok(None) => (
+                StatusCode::CONFLICT,
+                "Accession is not flagged as a bad capture",
+            )
+                .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred accepting bad capture of accession {id}");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Publishes an accession on the publication feed if it can be seen publicly.
+    ///
+    /// Failing to look the accession up is only logged, since the accession itself was
+    /// written fine.
+    async fn announce_if_public(&self, id: i32) {
+        match self.accessions_repo.get_one(id, false).await {
+            Ok(Some(accession)) => self.publication_feed.publish(accession),
+            Ok(None) => {}
+            Err(err) => error!(%err, "Error occurred looking up accession {id} to publish"),
+        }
+    }
+
+    /// Summarizes the state of the crawl pipeline for admins.
+    ///
+    /// # Returns
+    /// JSON response with crawls in progress and queued, crawl durations, poll retries and
+    /// recent failures
+    pub async fn pipeline_status(self) -> Response {
+        info!("Getting crawl pipeline status...");
+        Json(PipelineStatusResponse::from((
+            self.pipeline_metrics.snapshot(),
+            self.crawl_queue.snapshot(),
+        )))
+        .into_response()
+    }
+
+    /// Starts copying WACZ files still served by Browsertrix into S3 on a background task.
+    ///
+    /// # Returns
+    /// 202 with the backfill's progress so far, or 409 if a backfill is already running
+    pub async fn start_s3_backfill(self) -> Response {
+        if !self.s3_backfill.try_start() {
+            return (StatusCode::CONFLICT, "A backfill is already running").into_response();
+        }
+        info!("Starting S3 backfill...");
+        let s3_backfill = self.s3_backfill.clone();
+        tokio::spawn(self.run_s3_backfill());
+        (
+            StatusCode::ACCEPTED,
+            Json(S3BackfillStatusResponse::from(s3_backfill.snapshot())),
+        )
+            .into_response()
+    }
+
+    /// Reports how far the running or most recent S3 backfill got.
+    pub async fn s3_backfill_status(self) -> Response {
+        Json(S3BackfillStatusResponse::from(self.s3_backfill.snapshot())).into_response()
+    }
+
+    /// Copies every WACZ file still served by Browsertrix into S3, one at a time.
+    ///
+    /// The backfill must already have been marked as started, see
+    /// [`AccessionsService::start_s3_backfill`]. Failures are recorded and skipped, so
+    /// running the backfill again retries them.
+    async fn run_s3_backfill(self) {
+        match self.accessions_repo.list_missing_s3_files().await {
+            Err(err) => {
+                error!(%err, "Error occurred listing accessions to backfill");
+                self.s3_backfill
+                    .failed(None, "Error occurred listing accessions to backfill");
+            }
+            Ok(accessions) => {
+                self.s3_backfill.set_total(accessions.len());
+                for accession in accessions {
+                    match self.clone().backfill_one(&accession).await {
+                        Ok(()) => self.s3_backfill.transferred(),
+                        Err(reason) => self.s3_backfill.failed(Some(accession.id), reason),
+                    }
+                }
+            }
+        }
+        let snapshot = self.s3_backfill.snapshot();
+        info!(
+            "S3 backfill finished, copied {} of {} WACZ files",
+            snapshot.transferred, snapshot.total
+        );
+        self.s3_backfill.finish();
+    }
+
+    /// Streams one accession's WACZ from Browsertrix into S3 and records its new key.
+    async fn backfill_one(
+        self,
+        accession: &AccessionWithMetadataModel,
+    ) -> Result<(), &'static str> {
+        let Some(job_run_id) = accession.job_run_id.as_deref() else {
+            return Err("Accession has no Browsertrix job run");
+        };
+        let wacz_response = self
+            .browsertrix_repo
+            .download_wacz_stream(job_run_id)
+            .await
+            .map_err(|err| {
+                error!(%err, "Error occurred downloading WACZ file for accession {}", accession.id);
+                "Error occurred downloading WACZ file"
+            })?;
+        let title = accession
+            .title_en
+            .as_deref()
+            .or(accession.title_ar.as_deref())
+            .unwrap_or_default();
+        let key = self.s3_key_scheme.object_key(
+            title,
+            "wacz",
+            self.ids.new_id(),
+            self.clock.naive_now(),
+            accession.organization_id,
+        );
+        let sha256 = self
+            .clone()
+            .upload_from_stream(
+                key.clone(),
+                wacz_response.bytes_stream(),
+                "application/wacz".to_string(),
+            )
+            .await
+            .map_err(|_| "Error occurred uploading WACZ file to S3")?;
+        if let Err(err) = self
+            .accessions_repo
+            .set_s3_filename(accession.id, key.clone())
+            .await
+        {
+            error!(%err, "Error occurred saving S3 filename for accession {}", accession.id);
+            if let Err(err) = self.s3_repo.delete_object(&key).await {
+                warn!(%err, "Could not delete backfilled file {key}");
+            }
+            return Err("Error occurred saving S3 filename");
+        }
+        self.record_provenance(accession.id, sha256).await;
+        info!(
+            "Backfilled WACZ file for accession {} to {key}",
+            accession.id
+        );
+        Ok(())
+    }
+
+    /// Starts exporting the public archive as a static bundle in S3 on a background task,
+    /// see [`crate::static_export`].
+    ///
+    /// # Returns
+    /// Accepted with the export's progress, or a conflict if one is already running
+    pub async fn start_static_export(self) -> Response {
+        if !self.static_export.try_start() {
+            return (StatusCode::CONFLICT, "An export is already running").into_response();
+        }
+        info!("Starting static export...");
+        let static_export = self.static_export.clone();
+        tokio::spawn(self.run_static_export());
+        (
+            StatusCode::ACCEPTED,
+            Json(StaticExportStatusResponse::from(static_export.snapshot())),
+        )
+            .into_response()
+    }
+
+    /// Reports how far the running or most recent static export got.
+    pub async fn static_export_status(self) -> Response {
+        Json(StaticExportStatusResponse::from(
+            self.static_export.snapshot(),
+        ))
+        .into_response()
+    }
+
+    /// Writes the static bundle and records whether it finished.
+    ///
+    /// The export must already have been marked as started, see
+    /// [`AccessionsService::start_static_export`].
+    async fn run_static_export(self) {
+        if let Err(reason) = self.clone().export_static_bundle().await {
+            self.static_export.failed(reason);
+        }
+        let snapshot = self.static_export.snapshot();
+        info!(
+            "Static export finished, wrote {} accessions in {} pages",
+            snapshot.exported, snapshot.pages
+        );
+        self.static_export.finish();
+    }
+
+    /// Writes every public accession to page files, then the search index and manifest.
+    async fn export_static_bundle(self) -> Result<(), &'static str> {
+        let mut search_index = SearchIndex::default();
+        let mut after_id = 0;
+        let mut page = 0;
+        let mut accessions = 0;
+        loop {
+            let batch = self
+                .accessions_repo
+                .list_public_batch_after_id(after_id, STATIC_EXPORT_PAGE_SIZE)
+                .await
+                .map_err(|err| {
+                    error!(%err, "Error occurred listing accessions to export");
+                    "Error occurred listing accessions to export"
+                })?;
+            let Some(last) = batch.last() else {
+                break;
+            };
+            after_id = last.id;
+            let items: Vec<PublicAccessionsWithMetadataResponse> =
+                batch.into_iter().map(Into::into).collect();
+            for item in &items {
+                search_index.add(item, page);
+            }
+            let exported = items.len();
+            self.upload_static_json(
+                &page_key(page),
+                &ListPublicAccessionsResponse {
+                    items,
+                    num_pages: None,
+                    page,
+                    per_page: STATIC_EXPORT_PAGE_SIZE,
+                },
+            )
+            .await?;
+            self.static_export.page_written(exported);
+            accessions += exported as u64;
+            page += 1;
+        }
+        self.upload_static_json(&search_index_key(), &search_index)
+            .await?;
+        self.upload_static_json(
+            &manifest_key(),
+            &StaticExportManifest {
+                generated_at: self.clock.naive_now(),
+                pages: page,
+                per_page: STATIC_EXPORT_PAGE_SIZE,
+                accessions,
+            },
+        )
+        .await
+    }
+
+    /// Uploads one file of the static bundle.
+    async fn upload_static_json(
+        &self,
+        key: &str,
+        value: &impl Serialize,
+    ) -> Result<(), &'static str> {
+        let body = serde_json::to_vec(value).map_err(|err| {
+            error!(%err, "Error occurred serializing {key}");
+            "Error occurred serializing static export"
+        })?;
+        self.s3_repo
+            .upload_from_bytes(key, Bytes::from(body), "application/json")
+            .await
+            .map_err(|err| {
+                error!(%err, "Error occurred uploading {key}");
+                "Error occurred uploading static export"
+            })?;
+        Ok(())
+    }
+
+    /// Starts recomputing the full text search columns of every accession and rebuilding
+    /// their indexes on a background task, see [`crate::reindex`].
+    ///
+    /// # Returns
+    /// Accepted with the reindex's progress, or a conflict if one is already running
+    pub async fn start_reindex(self) -> Response {
+        if !self.reindex.try_start() {
+            return (StatusCode::CONFLICT, "A reindex is already running").into_response();
+        }
+        info!("Starting full text reindex...");
+        let reindex = self.reindex.clone();
+        tokio::spawn(self.run_reindex());
+        (
+            StatusCode::ACCEPTED,
+            Json(ReindexStatusResponse::from(reindex.snapshot())),
+        )
+            .into_response()
+    }
+
+    /// Reports how far the running or most recent reindex got.
+    pub async fn reindex_status(self) -> Response {
+        Json(ReindexStatusResponse::from(self.reindex.snapshot())).into_response()
+    }
+
+    /// Reindexes and records whether it finished.
+    ///
+    /// The reindex must already have been marked as started, see
+    /// [`AccessionsService::start_reindex`].
+    async fn run_reindex(self) {
+        if let Err(reason) = self.reindex_full_text().await {
+            self.reindex.failed(reason);
+        }
+        let snapshot = self.reindex.snapshot();
+        info!(
+            "Full text reindex finished, refreshed {} of {} accessions",
+            snapshot.refreshed, snapshot.total
+        );
+        self.reindex.finish();
+    }
+
+    /// Refreshes every accession's full text columns a batch at a time, then rebuilds the
+    /// indexes over them.
+    async fn reindex_full_text(&self) -> Result<(), &'static str> {
+        let total = self.accessions_repo.count_all().await.map_err(|err| {
+            error!(%err, "Error occurred counting accessions to reindex");
+            "Error occurred counting accessions to reindex"
+        })?;
+        self.reindex.set_total(total);
+        let mut after_id = 0;
+        loop {
+            let refreshed = self
+                .accessions_repo
+                .refresh_full_text_batch(after_id, REINDEX_BATCH_SIZE)
+                .await
+                .map_err(|err| {
+                    error!(%err, "Error occurred refreshing full text columns after accession {after_id}");
+                    "Error occurred refreshing full text columns"
+                })?;
+            let Some(last) = refreshed.last() else {
+                break;
+            };
+            after_id = *last;
+            self.reindex.refreshed(refreshed.len());
+        }
+        self.accessions_repo
+            .rebuild_full_text_indexes()
+            .await
+            .map_err(|err| {
+                error!(%err, "Error occurred rebuilding full text indexes");
+                "Error occurred rebuilding full text indexes"
+            })?;
+        self.reindex.indexes_rebuilt();
+        Ok(())
+    }
+
+    /// Deletes a single accession by ID.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `viewer` - The user deleting it
+    ///
+    /// # Returns
+    /// Response indicating success or failure of the deletion
+    pub async fn delete_one(self, id: i32, viewer: AuthenticatedUser) -> Response {
+        info!("Deleting accession with id {id}");
+        if let Err(response) = self.find_one_managed_or_respond(id, &viewer).await {
+            return response;
+        }
+        let delete_result = self.accessions_repo.delete_one(id).await;
+        match delete_result {
+            Err(err) => {
+                error!(%err, "Error occurred deleting accession");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+            Ok(delete_result) => {
+                if let Some(accession) = delete_result {
+                    if let Some(s3_filename) = accession.s3_filename {
+                        if let Err(err) = self.s3_repo.delete_object(&s3_filename).await {
+                            error!(%err, "Error deleting s3 object {s3_filename}");
+                            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                                .into_response();
+                        } else {
+                            info!("Deleted s3 object {s3_filename}");
+                        }
+                    }
+                    (StatusCode::OK, "Accession deleted").into_response()
+                } else {
+                    (StatusCode::NOT_FOUND, "No such record").into_response()
+                }
+            }
+        }
+    }
+
+    /// Lists the accessions related to an accession.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `viewer` - The user asking, who only sees private accessions of their organization
+    ///
+    /// # Returns
+    /// JSON response containing the related accessions or an error response
+    pub async fn list_relations(self, id: i32, viewer: AuthenticatedUser) -> Response {
+        info!("Getting relations of accession with id {id}");
+        let accession = match self.find_one_managed_or_respond(id, &viewer).await {
+            Ok(accession) => accession,
+            Err(response) => return response,
+        };
+        Json(ListAccessionRelationsResponse {
+            items: self.resolve_relations(&accession, Some(&viewer)).await,
+        })
+        .into_response()
+    }
+
+    /// Relates an accession to another.
+    ///
+    /// The related accession may belong to another organization as long as it is public,
+    /// so partners can point to the archive's own captures.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession the relation starts from
+    /// * `payload` - How it relates to which accession
+    /// * `viewer` - The user relating them
+    ///
+    /// # Returns
+    /// JSON response with the new relation or an error response
+    pub async fn create_relation(
+        self,
+        id: i32,
+        payload: CreateAccessionRelationRequest,
+        viewer: AuthenticatedUser,
+    ) -> Response {
+        info!(
+            "Relating accession with id {id} to accession with id {}",
+            payload.accession_id
+        );
+        if payload.accession_id == id {
+            return (
+                StatusCode::BAD_REQUEST,
+                "An accession can't be related to itself",
+            )
+                .into_response();
+        }
+        if let Err(response) = self.find_one_managed_or_respond(id, &viewer).await {
+            return response;
+        }
+        let related = match self.find_one_any_visibility(payload.accession_id).await {
+            Err(err) => {
+                error!(%err, "Error occurred retrieving related accession");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+            Ok(Some(related))
+                if !related.is_private
+                    || viewer.can_access_organization(related.organization_id) =>
+            {
+                related
+            }
+            Ok(_) => {
+                return (StatusCode::NOT_FOUND, "No such related accession").into_response();
+            }
+        };
+        match self
+            .accession_relations_repo
+            .write_one(id, payload.accession_id, payload.kind)
+            .await
+        {
+            Ok(relation) => (
+                StatusCode::CREATED,
+                Json(AccessionRelationResponse::from(RelatedAccession {
+                    relation_id: relation.id,
+                    kind: relation.kind,
+                    direction: RelationDirection::Outgoing,
+                    accession: related,
+                })),
+            )
+                .into_response(),
+            Err(err) => {
+                if err
+                    .to_string()
+                    .contains("duplicate key value violates unique constraint")
+                {
+                    warn!(%err, "Accession {id} is already related to {}", payload.accession_id);
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        "Accessions are already related that way",
+                    )
+                        .into_response();
+                }
+                error!(%err, "Error occurred writing accession relation");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Removes a relation from an accession, whichever end of it the accession is on.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `relation_id` - The relation to remove
+    /// * `viewer` - The user removing it
+    ///
+    /// # Returns
+    /// Response indicating success or failure of the removal
+    pub async fn delete_relation(
+        self,
+        id: i32,
+        relation_id: i32,
+        viewer: AuthenticatedUser,
+    ) -> Response {
+        info!("Removing relation {relation_id} from accession with id {id}");
+        if let Err(response) = self.find_one_managed_or_respond(id, &viewer).await {
+            return response;
+        }
+        match self
+            .accession_relations_repo
+            .delete_one(id, relation_id)
+            .await
+        {
+            Ok(Some(())) => (StatusCode::OK, "Relation removed").into_response(),
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred deleting accession relation");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Lists the curators' comments on an accession, oldest first.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `viewer` - The user reading the comments
+    ///
+    /// # Returns
+    /// JSON response containing the comments or an error response
+    pub async fn list_comments(self, id: i32, viewer: AuthenticatedUser) -> Response {
+        info!("Getting comments on accession with id {id}");
+        if let Err(response) = self.find_one_managed_or_respond(id, &viewer).await {
+            return response;
+        }
+        match self.comments_repo.list_for_accession(id).await {
+            Ok(comments) => Json(ListCommentsResponse {
+                items: comments.into_iter().map(Into::into).collect(),
+            })
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred listing comments");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Comments on an accession and emails the curators mentioned in the comment.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `payload` - The comment, which should be validated before calling this method
+    /// * `viewer` - The user commenting
+    ///
+    /// # Returns
+    /// JSON response with the new comment or an error response
+    pub async fn create_comment(
+        self,
+        id: i32,
+        payload: CommentRequest,
+        viewer: AuthenticatedUser,
+    ) -> Response {
+        info!("Commenting on accession with id {id}");
+        let accession = match self.find_one_managed_or_respond(id, &viewer).await {
+            Ok(accession) => accession,
+            Err(response) => return response,
+        };
+        match self
+            .comments_repo
+            .write_one(id, viewer.user_id.clone(), payload.body, payload.markdown)
+            .await
+        {
+            Ok(comment) => {
+                self.notify_mentions(&comment, accession.organization_id, vec![])
+                    .await;
+                (StatusCode::CREATED, Json(CommentResponse::from(comment))).into_response()
+            }
+            Err(err) => {
+                error!(%err, "Error occurred writing comment");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Edits a comment, which only its author can do. Curators newly mentioned in the
+    /// edit are emailed, those mentioned before aren't emailed again.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `comment_id` - The comment to edit
+    /// * `payload` - The comment's new text, which should be validated before calling this
+    ///   method
+    /// * `viewer` - The user editing the comment
+    ///
+    /// # Returns
+    /// JSON response with the edited comment or an error response
+    pub async fn update_comment(
+        self,
+        id: i32,
+        comment_id: i32,
+        payload: CommentRequest,
+        viewer: AuthenticatedUser,
+    ) -> Response {
+        info!("Editing comment {comment_id} on accession with id {id}");
+        let accession = match self.find_one_managed_or_respond(id, &viewer).await {
+            Ok(accession) => accession,
+            Err(response) => return response,
+        };
+        let previous = match self.find_comment_or_respond(id, comment_id).await {
+            Ok(comment) => comment,
+            Err(response) => return response,
+        };
+        if previous.author_email != viewer.user_id {
+            return (StatusCode::FORBIDDEN, "Only the author can edit a comment").into_response();
+        }
+        match self
+            .comments_repo
+            .update_one(id, comment_id, payload.body, payload.markdown)
+            .await
+        {
+            Ok(Some(comment)) => {
+                self.notify_mentions(
+                    &comment,
+                    accession.organization_id,
+                    find_mentions(&previous.body),
+                )
+                .await;
+                Json(CommentResponse::from(comment)).into_response()
+            }
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred editing comment");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Removes a comment, which its author or an admin can do.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `comment_id` - The comment to remove
+    /// * `viewer` - The user removing the comment
+    ///
+    /// # Returns
+    /// Response indicating success or failure of the removal
+    pub async fn delete_comment(
+        self,
+        id: i32,
+        comment_id: i32,
+        viewer: AuthenticatedUser,
+    ) -> Response {
+        info!("Removing comment {comment_id} from accession with id {id}");
+        if let Err(response) = self.find_one_managed_or_respond(id, &viewer).await {
+            return response;
+        }
+        let comment = match self.find_comment_or_respond(id, comment_id).await {
+            Ok(comment) => comment,
+            Err(response) => return response,
+        };
+        if comment.author_email != viewer.user_id && viewer.role != Role::Admin {
+            return (
+                StatusCode::FORBIDDEN,
+                "Only the author or an admin can remove a comment",
+            )
+                .into_response();
+        }
+        match self.comments_repo.delete_one(id, comment_id).await {
+            Ok(Some(())) => (StatusCode::OK, "Comment removed").into_response(),
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred deleting comment");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Looks up a comment on an accession, mapping failures to error responses.
+    async fn find_comment_or_respond(
+        &self,
+        id: i32,
+        comment_id: i32,
+    ) -> Result<CommentModel, Response> {
+        match self.comments_repo.find_one(id, comment_id).await {
+            Err(err) => {
+                error!(%err, "Error occurred retrieving comment");
+                Err((StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response())
+            }
+            Ok(None) => Err((StatusCode::NOT_FOUND, "No such record").into_response()),
+            Ok(Some(comment)) => Ok(comment),
+        }
+    }
+
+    /// Emails the curators mentioned in a comment, other than its author and those in
+    /// `already_notified`. Only researchers and admins of the accession's organization can
+    /// be mentioned, other emails are ignored. Failures are logged, the comment stands
+    /// either way.
+    ///
+    /// # Arguments
+    /// * `comment` - The comment just written or edited
+    /// * `organization_id` - The organization of the accession commented on
+    /// * `already_notified` - Emails mentioned in an earlier version of the comment
+    async fn notify_mentions(
+        &self,
+        comment: &CommentModel,
+        organization_id: i32,
+        already_notified: Vec<String>,
+    ) {
+        let author = comment.author_email.to_lowercase();
+        let mentions: Vec<String> = find_mentions(&comment.body)
+            .into_iter()
+            .filter(|email| *email != author && !already_notified.contains(email))
+            .collect();
+        if mentions.is_empty() {
+            return;
+        }
+        let recipients = match self
+            .comments_repo
+            .find_mentionable(mentions, organization_id)
+            .await
+        {
+            Ok(recipients) => recipients,
+            Err(err) => {
+                error!(%err, "Could not look up users mentioned in comment {}", comment.id);
+                return;
+            }
+        };
+        let email = MentionEmail {
+            accession_id: comment.accession_id,
+            author_email: comment.author_email.clone(),
+            body: comment.body.clone(),
+        };
+        for recipient in recipients {
+            if let Err(err) = self
+                .emails_repo
+                .send_email(recipient.clone(), email.subject(), email.html_body())
+                .await
+            {
+                error!(
+                    %err,
+                    "Could not email {recipient} about a mention in comment {}",
+                    comment.id
+                );
+            }
+        }
+    }
+
+    /// Updates a single accession by ID, changing only the fields given.
+    ///
+    /// You should validate the payload and check any subjects in it exist before
+    /// calling this method.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `payload` - The update request containing new metadata
+    /// * `viewer` - The user making the update, who private file links are bound to
+    ///
+    /// # Returns
+    /// Response indicating success or failure of the update
+    pub async fn update_one(
+        self,
+        id: i32,
+        payload: UpdateAccessionRequest,
+        viewer: AuthenticatedUser,
+    ) -> Response {
+        info!("Updating accession with id {id}");
+        let accession = match self.find_one_managed_or_respond(id, &viewer).await {
+            Ok(accession) => accession,
+            Err(response) => return response,
+        };
+        let payload = payload.trimmed();
+        let untitled_languages: Vec<MetadataLanguage> =
+            [MetadataLanguage::English, MetadataLanguage::Arabic]
+                .into_iter()
+                .filter(|language| {
+                    payload
+                        .metadata(*language)
+                        .is_some_and(|metadata| metadata.title.is_none())
+                })
+                .collect();
+        if !untitled_languages.is_empty() {
+            // the title is optional when editing metadata but there's no metadata without one
+            let adds_untitled_metadata = untitled_languages.iter().any(|language| match language {
+                MetadataLanguage::English => !accession.has_english_metadata,
+                MetadataLanguage::Arabic => !accession.has_arabic_metadata,
+            });
+            if adds_untitled_metadata {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    "A title is needed to add metadata in a new language",
+                )
+                    .into_response();
+            }
+        }
+        let update_result = self.accessions_repo.update_one(id, payload).await;
+        match update_result {
+            Err(DbErr::RecordNotUpdated) => (
+                StatusCode::CONFLICT,
+                "Accession was changed by someone else, try again",
+            )
+                .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred updating accession");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+            Ok(Some(accession)) => {
+                self.enrich_accession_with_wacz_url(accession, &viewer)
+                    .await
+            }
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+        }
+    }
+
+    /// Drafts the metadata an accession is missing in English or Arabic by machine translation.
+    ///
+    /// The draft is saved like any other metadata update, flagged as machine translated
+    /// until someone edits it. See [`crate::machine_translation`].
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `viewer` - The user asking for the translation, who private file links are bound to
+    ///
+    /// # Returns
+    /// The updated accession, or an error response if there is nothing to translate
+    pub async fn translate_metadata(self, id: i32, viewer: AuthenticatedUser) -> Response {
+        info!("Drafting machine translated metadata for accession with id {id}");
+        let Some(translation_repo) = self.translation_repo.clone() else {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Machine translation is not configured",
+            )
+                .into_response();
+        };
+        let accession = match self.find_one_managed_or_respond(id, &viewer).await {
+            Ok(accession) => accession,
+            Err(response) => return response,
+        };
+        let source = match translation_source(&accession) {
+            Ok(source) => source,
+            Err(message) => return (StatusCode::CONFLICT, message).into_response(),
+        };
+        let translations = match translation_repo
+            .translate(source.texts, source.source, source.target)
+            .await
+        {
+            Ok(translations) => translations,
+            Err(err) => {
+                error!(%err, "Error occurred translating accession metadata");
+                return (StatusCode::BAD_GATEWAY, "Translation service error").into_response();
+            }
+        };
+        // refused if someone edited the accession while it was being translated
+        let draft = UpdateAccessionRequest {
+            revision: accession.revision,
+            ..draft_update(source.target_language, &translations)
+        };
+        self.update_one(id, draft, viewer).await
+    }
+
+    /// Mints a DOI for a public accession, so it can be cited by an identifier that
+    /// outlives the archive's URLs.
+    ///
+    /// DOIs are published as soon as they're minted and can't be taken back, so private
+    /// accessions can't get one and each accession only ever gets one.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `viewer` - The user asking for the DOI
+    ///
+    /// # Returns
+    /// The minted DOI, or an error response if the accession can't be given one
+    pub async fn mint_doi(self, id: i32, viewer: AuthenticatedUser) -> Response {
+        info!("Minting DOI for accession with id {id}");
+        let Some(doi_repo) = self.doi_repo.clone() else {
+            return (StatusCode::SERVICE_UNAVAILABLE, "DOIs are not configured").into_response();
+        };
+        let accession = match self.find_one_managed_or_respond(id, &viewer).await {
+            Ok(accession) => accession,
+            Err(response) => return response,
+        };
+        if let Some(doi) = accession.doi {
+            return (
+                StatusCode::CONFLICT,
+                format!("Accession already has DOI {doi}"),
+            )
+                .into_response();
+        }
+        if accession.is_private {
+            return (
+                StatusCode::CONFLICT,
+                "Only public accessions can be given a DOI",
+            )
+                .into_response();
+        }
+        let metadata = doi_metadata(&accession, self.clock.now().year());
+        let doi = match doi_repo.mint(metadata).await {
+            Ok(doi) => doi,
+            Err(err) => {
+                error!(%err, "Error occurred minting DOI");
+                return (StatusCode::BAD_GATEWAY, "DOI registration service error").into_response();
+            }
+        };
+        match self.accessions_repo.set_doi(id, doi.clone()).await {
+            Ok(Some(())) => (StatusCode::CREATED, Json(DoiResponse::new(doi))).into_response(),
+            Ok(None) => {
+                // the DOI exists now, so it's logged to be recorded by hand
+                error!("Accession {id} got another DOI while {doi} was minted for it");
+                (
+                    StatusCode::CONFLICT,
+                    "Accession got another DOI in the meantime",
+                )
+                    .into_response()
+            }
+            Err(err) => {
+                error!(%err, "Error occurred recording DOI {doi} for accession {id}");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Exports who accessed an accession and when, with a signed manifest, see
+    /// [`crate::access_log_export`].
+    ///
+    /// The export is itself written to the audit log with the log's hash, so later
+    /// exports show who was handed which log. It isn't handed out if that fails.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `format` - The format of the log in the ZIP
+    /// * `viewer` - The admin exporting it
+    ///
+    /// # Returns
+    /// ZIP attachment holding the log and its manifest, or an error response
+    pub async fn export_access_log(
+        self,
+        id: i32,
+        format: AccessLogFormat,
+        viewer: AuthenticatedUser,
+    ) -> Response {
+        info!("Exporting access log of accession with id {id}");
+        if let Err(response) = self.find_one_managed_or_respond(id, &viewer).await {
+            return response;
+        }
+        let filter = AuditLogFilter {
+            entity_type: Some("accession".to_string()),
+            entity_id: Some(id.to_string()),
+            ..Default::default()
+        };
+        let audit_log = self
+            .audit_log_repo
+            .export(filter, ACCESS_LOG_EXPORT_LIMIT)
+            .await;
+        let usage = self
+            .accession_events_repo
+            .list_for_accession(id, ACCESS_LOG_EXPORT_LIMIT)
+            .await;
+        let (audit_log, usage) = match (audit_log, usage) {
+            (Ok(audit_log), Ok(usage)) => (audit_log, usage),
+            (Err(err), _) | (_, Err(err)) => {
+                error!(%err, "Error occurred exporting access log of accession {id}");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        };
+        let truncated = [audit_log.len(), usage.len()]
+            .iter()
+            .any(|count| *count as u64 >= ACCESS_LOG_EXPORT_LIMIT);
+        let entries = merge(audit_log, usage);
+        let log = format.render(&entries);
+        let manifest = AccessLogManifest::new(
+            id,
+            format,
+            &log,
+            entries.len(),
+            truncated,
+            viewer.user_id.clone(),
+            self.clock.naive_now(),
+            self.provenance_signer.as_deref(),
+        );
+        let zip = match bundle(&log, &manifest) {
+            Ok(zip) => zip,
+            Err(err) => {
+                error!(%err, "Error occurred bundling access log of accession {id}");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+                    .into_response();
+            }
+        };
+        if let Err(err) = self
+            .audit_log_repo
+            .record(AuditEntry {
+                actor_email: viewer.user_id,
+                impersonator_email: viewer.impersonator,
+                event: ACCESS_LOG_EXPORTED,
+                entity_type: "accession",
+                entity_id: id.to_string(),
+                details: Some(json!({
+                    "file_name": manifest.file_name,
+                    "sha256": manifest.sha256,
+                    "entries": manifest.entries,
+                    "truncated": manifest.truncated,
+                    "signed": manifest.signature.is_some(),
+                })),
+            })
+            .await
+        {
+            error!(%err, "Error occurred auditing access log export of accession {id}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response();
+        }
+        (
+            [
+                (header::CONTENT_TYPE, ACCESS_LOG_CONTENT_TYPE.to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"access-log-{id}.zip\""),
+                ),
+            ],
+            zip,
+        )
+            .into_response()
+    }
+
+    /// Swaps an accession's stored file for a corrected one, e.g. a capture made by hand.
+    ///
+    /// The replacement is streamed to S3 under a new key, and the accession is only pointed
+    /// at it once it is stored, so readers get either the old file or the new one. The old
+    /// file is moved under [`crate::s3_keys::QUARANTINE_PREFIX`] rather than deleted, in case
+    /// the replacement was a mistake.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `body` - The replacement file
+    /// * `viewer` - The admin replacing it
+    ///
+    /// # Returns
+    /// JSON response with the replacement's key and hash, or an error response
+    pub async fn replace_file(self, id: i32, body: Body, viewer: AuthenticatedUser) -> Response {
+        info!("Replacing file of accession with id {id}");
+        let accession = match self.find_one_managed_or_respond(id, &viewer).await {
+            Ok(accession) => accession,
+            Err(response) => return response,
+        };
+        let mut stream = body.into_data_stream();
+        let mut head = Vec::new();
+        let mut head_size = 0;
+        while head_size < SNIFF_LENGTH {
+            match stream.next().await {
+                Some(Ok(chunk)) => {
+                    head_size += chunk.len();
+                    head.push(chunk);
+                }
+                None => break,
+                Some(Err(err)) => {
+                    error!("Failed to read chunk from stream: {}", err);
+                    return (StatusCode::BAD_REQUEST, "Failed to read file stream").into_response();
+                }
+            }
+        }
+        if head_size == 0 {
+            return (StatusCode::BAD_REQUEST, "File must not be empty").into_response();
+        }
+        let head_bytes: Vec<u8> = head
+            .iter()
+            .flat_map(|chunk| chunk.iter().copied())
+            .take(SNIFF_LENGTH)
+            .collect();
+        if let Err(message) = check_file_type(&accession.dublin_metadata_format, &head_bytes) {
+            warn!("Rejected replacement file for accession {id}: {message}");
+            return (StatusCode::UNSUPPORTED_MEDIA_TYPE, message).into_response();
+        }
+
+        let (extension, content_type) = file_type(&accession.dublin_metadata_format);
+        let title = accession
+            .title_en
+            .as_deref()
+            .or(accession.title_ar.as_deref())
+            .unwrap_or_default();
+        let key = self.s3_key_scheme.object_key(
+            title,
+            extension,
+            self.ids.new_id(),
+            self.clock.naive_now(),
+            accession.organization_id,
+        );
+        let stream = futures::stream::iter(head.into_iter().map(Ok)).chain(stream);
+        let sha256 = match self
+            .clone()
+            .upload_from_stream(key.clone(), Box::pin(stream), content_type.to_string())
+            .await
+        {
+            Ok(sha256) => sha256,
+            Err(response) => return response,
+        };
+
+        let previous = accession.s3_filename;
+        let replaced = self
+            .accessions_repo
+            .replace_s3_filename(id, previous.clone(), key.clone())
+            .await;
+        if !matches!(replaced, Ok(Some(()))) {
+            if let Err(err) = self.s3_repo.delete_object(&key).await {
+                warn!(%err, "Could not delete replacement file {key}");
+            }
+        }
+        match replaced {
+            Ok(Some(())) => {}
+            Ok(None) => {
+                return (
+                    StatusCode::CONFLICT,
+                    "Accession's file changed in the meantime",
+                )
+                    .into_response();
+            }
+            Err(err) => {
+                error!(%err, "Error occurred saving S3 filename for accession {id}");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        }
+
+        let quarantined = match &previous {
+            Some(previous) => self.quarantine_file(previous).await,
+            None => None,
+        };
+        let previous_sha256 = match self.provenance_repo.get_one(id).await {
+            Ok(provenance) => provenance.map(|provenance| provenance.sha256),
+            Err(err) => {
+                error!(%err, "Error occurred retrieving provenance of accession {id}");
+                None
+            }
+        };
+        let provenance = self.sign_provenance(id, sha256.clone());
+        if let Err(err) = self.provenance_repo.replace_one(provenance).await {
+            error!(%err, "Error occurred recording provenance of accession {id}");
+        }
+        if accession.dublin_metadata_format == DublinMetadataFormat::Wacz {
+            self.record_content_digest(id, &key).await;
+        }
+        // the file was replaced either way, so failing to audit it is only logged
+        if let Err(err) = self
+            .audit_log_repo
+            .record(AuditEntry {
+                actor_email: viewer.user_id,
+                impersonator_email: viewer.impersonator,
+                event: ACCESSION_FILE_REPLACED,
+                entity_type: "accession",
+                entity_id: id.to_string(),
+                details: Some(json!({
+                    "previous": previous,
+                    "previous_sha256": previous_sha256,
+                    "quarantined": quarantined,
+                    "replacement": key,
+                    "sha256": sha256,
+                    "auth_method": viewer.auth_method.as_str(),
+                })),
+            })
+            .await
+        {
+            error!(%err, "Error occurred auditing replacement of accession {id}'s file");
+        }
+        info!("Replaced file of accession {id} with {key}");
+        Json(ReplaceAccessionFileResponse {
+            s3_filename: key,
+            sha256,
+            quarantined_s3_filename: quarantined,
+        })
+        .into_response()
+    }
+
+    /// Moves a replaced file under the quarantine prefix, see [`quarantine_key`].
+    ///
+    /// Failing to is only logged, since the accession already points at its replacement.
+    ///
+    /// # Returns
+    /// The file's new key, `None` if it couldn't be moved
+    async fn quarantine_file(&self, key: &str) -> Option<String> {
+        let quarantined = quarantine_key(key);
+        if let Err(err) = self.s3_repo.copy_object(key, &quarantined).await {
+            error!(%err, "Error occurred quarantining replaced file {key}");
+            return None;
+        }
+        if let Err(err) = self.s3_repo.delete_object(key).await {
+            warn!(%err, "Could not delete replaced file {key} after quarantining it");
+        }
+        Some(quarantined)
+    }
+
+    /// Gets the recorded hash and signature of an accession's file.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `viewer` - The signed in user, `None` to only allow public accessions
+    ///
+    /// # Returns
+    /// JSON response with the accession's provenance, 404 if none was recorded for it
+    pub async fn get_provenance(self, id: i32, viewer: Option<AuthenticatedUser>) -> Response {
+        info!("Getting provenance of accession with id {id}");
+        let accession = match &viewer {
+            Some(viewer) => self.find_one_managed(id, viewer).await,
+            None => self.find_one(id, false).await,
+        };
+        match accession {
+            Err(err) => {
+                error!(%err, "Error occurred retrieving accession");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+            Ok(None) => return (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Ok(Some(_)) => {}
+        }
+        match self.provenance_repo.get_one(id).await {
+            Ok(Some(provenance)) => Json(ProvenanceResponse::from(provenance)).into_response(),
+            // files uploaded before hashes were recorded, and accessions without a file
+            Ok(None) => (
+                StatusCode::NOT_FOUND,
+                "No provenance recorded for this accession",
+            )
+                .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred retrieving provenance of accession {id}");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Lists other accessions whose capture has the same content, so curators can
+    /// consolidate them. See [`crate::content_digest`].
+    ///
+    /// Captures crawled before content digests were computed get theirs computed and stored
+    /// on the first request.
+    ///
+    /// # Arguments
+    /// * `id` - The unique identifier of the accession
+    /// * `viewer` - The signed in user, whose organization the duplicates are limited to
+    ///
+    /// # Returns
+    /// JSON response with the capture's digest and its duplicates, or an error response
+    pub async fn get_duplicates(self, id: i32, viewer: AuthenticatedUser) -> Response {
+        info!("Getting duplicates of accession with id {id}");
+        let accession = match self.find_one_managed_or_respond(id, &viewer).await {
+            Ok(accession) => accession,
+            Err(response) => return response,
         };
-        let resp = self
-            .browsertrix_repo
-            .create_crawl(create_crawl_request)
-            .await;
-        match resp {
-            Err(err) => {
-                error!(%err, "Error occurred launching browsertrix crawl");
-            }
-            Ok(resp) => {
-                info!("Launched crawl request for url {}", payload.url.clone());
-                let time_to_sleep = Duration::from_secs(60);
-                let time_to_sleep_as_secs = time_to_sleep.as_secs();
-                let mut count = 0;
-                while count <= 30 {
-                    count += 1;
-                    info!("Polled {count} time(s) for url {}", payload.url.clone());
-                    let get_crawl_resp = self.browsertrix_repo.get_crawl_status(resp.id).await;
-                    match get_crawl_resp {
-                        Ok(valid_crawl_resp) => {
-                            if valid_crawl_resp == "complete" {
-                                let crawl_time_secs = (time_to_sleep * count).as_secs();
-                                info!(%valid_crawl_resp, %count, "Crawl complete after {crawl_time_secs}s");
-                                let trimmed_title = payload.metadata_title.trim().to_string();
-                                let trimmed_description = payload
-                                    .metadata_description
-                                    .map(|description| description.trim().to_string());
-
-                                let wacz_response = match self
-                                    .browsertrix_repo
-                                    .download_wacz_stream(&resp.run_now_job)
-                                    .await
-                                {
-                                    Ok(response) => response,
-                                    Err(err) => {
-                                        error!(%err, "Error occurred downloading WACZ file, aborting accession creation");
-                                        return;
-                                    }
-                                };
-
-                                let unique_filename = format!("{}.wacz", Uuid::new_v4());
-                                if let Err(err) = self
-                                    .clone()
-                                    .upload_from_stream(
-                                        unique_filename.clone(),
-                                        wacz_response.bytes_stream(),
-                                        "application/wacz".to_string(),
-                                    )
-                                    .await
-                                {
-                                    error!("Error occurred uploading WACZ file to S3: {:?}, aborting accession creation", err);
-                                    return;
-                                };
-                                info!("WACZ file uploaded to S3 with filename {}", unique_filename);
-                                let create_accessions_request = CreateAccessionRequest {
-                                    url: payload.url.clone(),
-                                    browser_profile: payload.browser_profile,
-                                    metadata_language: payload.metadata_language,
-                                    metadata_title: trimmed_title,
-                                    metadata_description: trimmed_description,
-                                    metadata_time: payload.metadata_time,
-                                    metadata_subjects: payload.metadata_subjects,
-                                    is_private: payload.is_private,
-                                    metadata_format: DublinMetadataFormat::Wacz,
-                                    s3_filename: Some(unique_filename.clone()),
-                                };
-                                let write_result = self
-                                    .accessions_repo
-                                    .write_one(
-                                        create_accessions_request,
-                                        self.browsertrix_repo.get_org_id(),
-                                        resp.id,
-                                        resp.run_now_job,
-                                        CrawlStatus::Complete,
-                                    )
-                                    .await;
-                                match write_result {
-                                    Err(err) => {
-                                        error!(%err, "Error occurred writing crawl result to db!");
-                                    }
-                                    Ok(id) => {
-                                        info!("Crawl result written to db successfully");
-                                        let email_subject =
-                                            format!("Your URL {} has been archived!", payload.url);
-                                        let email_body = format!(
-                                            "We have archived your <a href='https://sudandigitalarchive.com/archive/{}?isPrivate={}&lang={}'>url</a>.",
-                                            id, payload.is_private, payload.metadata_language
-                                        );
-                                        let email_result = self
-                                            .emails_repo
-                                            .send_email(user_email, email_subject, email_body)
-                                            .await;
-                                        info!(
-                                            "Email sent to user with id {id} for url {}",
-                                            payload.url
-                                        );
-                                        if let Err(err) = email_result {
-                                            error!(%err, "Error occurred sending email to user");
-                                        }
-                                    }
-                                }
-                                break;
-                            } else {
-                                sleep(time_to_sleep).await;
-                            }
-                        }
-                        Err(invalid_crawl_resp) => {
-                            error!(%invalid_crawl_resp, "Invalid crawl response, trying again in {time_to_sleep_as_secs}s");
-                            sleep(time_to_sleep).await;
-                        }
-                    }
+        let content_digest = match self.content_digest_repo.get_one(id).await {
+            Ok(Some(content_digest)) => Some(content_digest),
+            Ok(None) => match (&accession.s3_filename, &accession.dublin_metadata_format) {
+                (Some(s3_filename), DublinMetadataFormat::Wacz) => {
+                    self.record_content_digest(id, s3_filename).await
                 }
+                _ => None,
+            },
+            Err(err) => {
+                error!(%err, "Error occurred retrieving content digest of accession {id}");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        };
+        // uploads, and WACZs without a pages index
+        let Some(content_digest) = content_digest else {
+            return (
+                StatusCode::NOT_FOUND,
+                "No content digest for this accession",
+            )
+                .into_response();
+        };
+        match self
+            .content_digest_repo
+            .find_accession_ids(&content_digest, viewer.organization_scope())
+            .await
+        {
+            Ok(accession_ids) => Json(DuplicateAccessionsResponse {
+                accession_id: id,
+                content_digest,
+                duplicate_accession_ids: accession_ids
+                    .into_iter()
+                    .filter(|&accession_id| accession_id != id)
+                    .collect(),
+            })
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred finding duplicates of accession {id}");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
             }
         }
     }
 
-    /// Deletes a single accession by ID.
+    /// Moves an accession to another publication state.
+    ///
+    /// See [`crate::publication_workflow`] for which moves each role can make. Accessions
+    /// that get published go out on the publication feed.
     ///
     /// # Arguments
     /// * `id` - The unique identifier of the accession
+    /// * `publication_state` - The state to move the accession to
+    /// * `viewer` - The user making the move, who private file links are bound to
     ///
     /// # Returns
-    /// Response indicating success or failure of the deletion
-    pub async fn delete_one(self, id: i32) -> Response {
-        info!("Deleting accession with id {id}");
-        let delete_result = self.accessions_repo.delete_one(id).await;
-        match delete_result {
+    /// The updated accession, or an error response if the move isn't allowed
+    pub async fn update_publication_state(
+        self,
+        id: i32,
+        publication_state: PublicationState,
+        viewer: AuthenticatedUser,
+    ) -> Response {
+        info!("Moving accession with id {id} to {publication_state:?}");
+        if let Err(response) = self.find_one_managed_or_respond(id, &viewer).await {
+            return response;
+        }
+        let current_state = match self.accessions_repo.get_publication_state(id).await {
             Err(err) => {
-                error!(%err, "Error occurred deleting accession");
+                error!(%err, "Error occurred getting publication state of accession");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+            Ok(None) => return (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Ok(Some(current_state)) => current_state,
+        };
+        match check_transition(&current_state, &publication_state, &viewer.role) {
+            Ok(()) => {}
+            Err(TransitionError::InsufficientRole) => {
+                return (StatusCode::FORBIDDEN, "Insufficient permissions").into_response();
+            }
+            Err(TransitionError::NotAllowed) => {
+                return (
+                    StatusCode::CONFLICT,
+                    format!(
+                        "Accession cannot move from {} to {}",
+                        current_state.to_value(),
+                        publication_state.to_value()
+                    ),
+                )
+                    .into_response();
+            }
+        }
+        let update_result = self
+            .accessions_repo
+            .set_publication_state(id, current_state, publication_state.clone())
+            .await;
+        match update_result {
+            Err(err) => {
+                error!(%err, "Error occurred updating publication state of accession");
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
             }
-            Ok(delete_result) => {
-                if let Some(accession) = delete_result {
-                    if let Some(s3_filename) = accession.s3_filename {
-                        if let Err(err) = self.s3_repo.delete_object(&s3_filename).await {
-                            error!(%err, "Error deleting s3 object {s3_filename}");
-                            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
-                                .into_response();
-                        } else {
-                            info!("Deleted s3 object {s3_filename}");
-                        }
-                    }
-                    (StatusCode::OK, "Accession deleted").into_response()
-                } else {
-                    (StatusCode::NOT_FOUND, "No such record").into_response()
+            Ok(None) => (
+                StatusCode::CONFLICT,
+                "Accession was changed by someone else, try again",
+            )
+                .into_response(),
+            Ok(Some(accession)) => {
+                if publication_state == PublicationState::Published {
+                    self.publication_feed.publish(accession.clone());
                 }
+                self.enrich_accession_with_wacz_url(accession, &viewer)
+                    .await
             }
         }
     }
 
-    /// Updates a single accession by ID.
+    /// Makes many accessions public or private at once, e.g. to take down every capture of a
+    /// source that has become unsafe to show.
+    ///
+    /// Accessions are moved between publication states as described in
+    /// [`bulk_visibility_transition`], all in one transaction with an audit log entry each.
     ///
     /// # Arguments
-    /// * `id` - The unique identifier of the accession
-    /// * `payload` - The update request containing new metadata
+    /// * `payload` - The accessions to change and the visibility to give them
+    /// * `actor_email` - Email of the admin making the change
+    /// * `organization_id` - Only accessions of this organization are changed, or those of
+    ///   every organization when `None`
     ///
     /// # Returns
-    /// Response indicating success or failure of the update
-    pub async fn update_one(self, id: i32, payload: UpdateAccessionRequest) -> Response {
-        info!("Updating accession with id {id}");
-        let update_result = self.accessions_repo.update_one(id, payload).await;
+    /// JSON response listing the accessions that changed, or an error response
+    pub async fn bulk_update_visibility(
+        self,
+        payload: BulkVisibilityRequest,
+        actor_email: String,
+        organization_id: Option<i32>,
+    ) -> Response {
+        let selection = match (payload.ids, payload.url_filter) {
+            (Some(ids), None) => AccessionSelection::Ids(ids),
+            (None, Some(url_filter)) => AccessionSelection::UrlPrefix(url_filter),
+            _ => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    "Select accessions with either ids or url_filter",
+                )
+                    .into_response();
+            }
+        };
+        let (from, to) = bulk_visibility_transition(payload.visibility);
+        info!("Moving accessions matching {selection:?} to {to:?} for {actor_email}");
+        let update_result = self
+            .accessions_repo
+            .bulk_set_publication_state(
+                selection,
+                from,
+                to.clone(),
+                actor_email,
+                payload.reason,
+                organization_id,
+            )
+            .await;
         match update_result {
             Err(err) => {
-                error!(%err, "Error occurred updating accession");
+                error!(%err, "Error occurred changing visibility of accessions");
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
             }
-            Ok(update_result) => {
-                if let Some(accession) = update_result {
-                    self.enrich_accession_with_wacz_url(accession).await
-                } else {
-                    error!("Error occurred finding accession in view after update");
-                    (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+            Ok(updated_ids) => {
+                info!("Moved accessions {updated_ids:?} to {to:?}");
+                if to == PublicationState::Published {
+                    for id in &updated_ids {
+                        self.announce_if_public(*id).await;
+                    }
                 }
+                Json(BulkVisibilityResponse { updated_ids }).into_response()
             }
         }
     }
@@ -377,15 +3003,29 @@ impl AccessionsService {
     ///
     /// # Arguments
     /// * `payload` - The raw accession request with metadata and S3 filename
+    /// * `scan_status` - The result of virus scanning the file
+    /// * `metadata_scrubbed` - Whether embedded metadata was stripped from the file
+    /// * `sha256` - Hex SHA-256 of the stored file, whose provenance is then recorded
+    /// * `organization_id` - The organization the accession belongs to
     ///
     /// # Returns
     /// Result containing the accession ID or an error response
-    pub async fn write_one_raw(self, payload: CreateAccessionRequestRaw) -> Result<i32, Response> {
+    pub async fn write_one_raw(
+        self,
+        payload: CreateAccessionRequestRaw,
+        scan_status: ScanStatus,
+        metadata_scrubbed: bool,
+        sha256: Option<String>,
+        organization_id: i32,
+    ) -> Result<i32, Response> {
         info!(
             "Writing raw accession with title: {}",
             payload.metadata_title
         );
-        let write_result = self.accessions_repo.write_one_raw(payload).await;
+        let write_result = self
+            .accessions_repo
+            .write_one_raw(payload, scan_status, metadata_scrubbed, organization_id)
+            .await;
         match write_result {
             Err(err) => {
                 error!(%err, "Error occurred writing raw accession to db");
@@ -393,11 +3033,367 @@ impl AccessionsService {
             }
             Ok(id) => {
                 info!("Raw accession written to db successfully with id {id}");
+                if let Some(sha256) = sha256 {
+                    self.record_provenance(id, sha256).await;
+                }
+                self.announce_if_public(id).await;
                 Ok(id)
             }
         }
     }
 
+    /// Creates an accession from a file the client uploaded straight to S3 with presigned URLs.
+    ///
+    /// Since the bytes never passed through the API, the object is checked here before it
+    /// is referenced: the key must be one handed out for uploads, the object must be a
+    /// readable WACZ and it must pass virus scanning. Objects that fail validation are
+    /// deleted so they don't linger in the bucket.
+    ///
+    /// The API never sees the bytes so can't scrub embedded metadata from them; images and
+    /// videos are refused unless the request opts out of scrubbing.
+    ///
+    /// # Arguments
+    /// * `payload` - The accession metadata, with `s3_filename` set to the uploaded key
+    /// * `organization_id` - The organization the accession belongs to
+    ///
+    /// # Returns
+    /// Created response with the new accession ID, or an error response
+    pub async fn create_from_file(
+        self,
+        mut payload: CreateAccessionRequestRaw,
+        organization_id: i32,
+    ) -> Response {
+        let key = payload.s3_filename.clone();
+        if !is_upload_key(&key, payload.metadata_format.clone()) {
+            return (StatusCode::BAD_REQUEST, "Not an uploaded file key").into_response();
+        }
+        if payload.metadata_scrubbing == MetadataScrubbing::Scrub
+            && MetadataScrubber::for_format(&payload.metadata_format).is_some()
+        {
+            return (
+                StatusCode::BAD_REQUEST,
+                "Files uploaded directly to storage can't have their metadata scrubbed; upload through /api/v1/accessions/raw or set metadata_scrubbing to keep",
+            )
+                .into_response();
+        }
+        let object_size = self
+            .s3_repo
+            .get_object_size(&key)
+            .await
+            .map_err(|err| err.to_string());
+        let object_size = match object_size {
+            Ok(0) => {
+                self.delete_invalid_upload(&key).await;
+                return (StatusCode::BAD_REQUEST, "Uploaded file is empty").into_response();
+            }
+            Ok(size) => size,
+            Err(err) => {
+                warn!(%err, "Could not find uploaded file {key}");
+                return (StatusCode::BAD_REQUEST, "Uploaded file not found").into_response();
+            }
+        };
+        let head = self
+            .s3_repo
+            .get_object_range(&key, 0, object_size.min(SNIFF_LENGTH as u64) - 1)
+            .await
+            .map_err(|err| err.to_string());
+        let head = match head {
+            Ok(head) => head,
+            Err(err) => {
+                error!(%err, "Failed to read start of uploaded file {key}");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to read uploaded file",
+                )
+                    .into_response();
+            }
+        };
+        if let Err(message) = check_file_type(&payload.metadata_format, &head) {
+            self.delete_invalid_upload(&key).await;
+            return (StatusCode::UNSUPPORTED_MEDIA_TYPE, message).into_response();
+        }
+        let is_valid_file = match &payload.metadata_format {
+            DublinMetadataFormat::Wacz => read_wacz_pages(self.s3_repo.as_ref(), &key)
+                .await
+                .inspect_err(|err| warn!(%err, "Uploaded file {key} is not a valid WACZ"))
+                .is_ok(),
+            DublinMetadataFormat::Warc => self
+                .s3_repo
+                .get_object_range(&key, 0, object_size.min(WARC_SNIFF_LENGTH as u64) - 1)
+                .await
+                .map_err(|err| err.to_string())
+                .inspect_err(|err| warn!(%err, "Failed to read start of uploaded file {key}"))
+                .is_ok_and(|head| is_warc(&head)),
+            // Images and videos have nothing to check beyond their sniffed file type
+            DublinMetadataFormat::Jpeg | DublinMetadataFormat::Png | DublinMetadataFormat::Mp4 => {
+                true
+            }
+        };
+        if !is_valid_file {
+            self.delete_invalid_upload(&key).await;
+            return (
+                StatusCode::BAD_REQUEST,
+                "Uploaded file does not match metadata format",
+            )
+                .into_response();
+        }
+        let scan_result = self.scan_stored_file(&key, object_size).await;
+        let scan_status = match self.enforce_scan_result(&mut payload, scan_result).await {
+            Ok(scan_status) => scan_status,
+            Err(err) => return err,
+        };
+        // a file that can't be read back is still kept, it just has no provenance
+        let sha256 = self
+            .hash_stored_file(&key, object_size)
+            .await
+            .inspect_err(|err| error!(%err, "Failed to hash uploaded file {key}"))
+            .ok();
+        match self
+            .write_one_raw(payload, scan_status, false, sha256, organization_id)
+            .await
+        {
+            Ok(id) => (
+                StatusCode::CREATED,
+                format!("Accession created with id: {id}"),
+            )
+                .into_response(),
+            Err(err) => err,
+        }
+    }
+
+    async fn delete_invalid_upload(&self, key: &str) {
+        if let Err(err) = self.s3_repo.delete_object(key).await {
+            error!(%err, "Failed to delete invalid upload {key}");
+        }
+    }
+
+    /// Starts scanning a file in the background, fed by sending its chunks to the
+    /// returned sender. Returns `None` when no virus scanner is configured.
+    #[allow(clippy::type_complexity)]
+    fn start_virus_scan(
+        &self,
+    ) -> Option<(mpsc::Sender<Bytes>, JoinHandle<Result<ScanVerdict, String>>)> {
+        let virus_scanner_repo = self.virus_scanner_repo.clone()?;
+        let (sender, receiver) = mpsc::channel::<Bytes>(VIRUS_SCAN_BUFFERED_CHUNKS);
+        let chunks = futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|chunk| (Ok(chunk), receiver))
+        })
+        .boxed();
+        let task = tokio::spawn(async move {
+            virus_scanner_repo
+                .scan(chunks)
+                .await
+                .map_err(|err| err.to_string())
+        });
+        Some((sender, task))
+    }
+
+    /// Scans a file that is already in S3, reading it in five MB ranges.
+    /// Returns `None` when no virus scanner is configured.
+    async fn scan_stored_file(&self, key: &str, size: u64) -> Option<Result<ScanVerdict, String>> {
+        let virus_scanner_repo = self.virus_scanner_repo.clone()?;
+        let s3_repo = self.s3_repo.clone();
+        let key = key.to_string();
+        let chunks = futures::stream::unfold(0u64, move |start| {
+            let s3_repo = s3_repo.clone();
+            let key = key.clone();
+            async move {
+                if start >= size {
+                    return None;
+                }
+                let end = (start + FIVE_MB as u64).min(size) - 1;
+                let chunk = s3_repo
+                    .get_object_range(&key, start, end)
+                    .await
+                    .map_err(|err| err.to_string());
+                Some((chunk, end + 1))
+            }
+        })
+        .boxed();
+        Some(
+            virus_scanner_repo
+                .scan(chunks)
+                .await
+                .map_err(|err| err.to_string()),
+        )
+    }
+
+    /// Hashes a file that is already in S3, reading it in five MB ranges.
+    ///
+    /// # Returns
+    /// The hex SHA-256 of the file or the error that stopped it being read
+    async fn hash_stored_file(&self, key: &str, size: u64) -> Result<String, String> {
+        let mut hasher = Sha256::new();
+        let mut start = 0u64;
+        while start < size {
+            let end = (start + FIVE_MB as u64).min(size) - 1;
+            let chunk = self
+                .s3_repo
+                .get_object_range(key, start, end)
+                .await
+                .map_err(|err| err.to_string())?;
+            hasher.update(&chunk);
+            start = end + 1;
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+
+    /// Records the hash of an accession's stored file, signing it when a signing key is
+    /// configured.
+    ///
+    /// Failing to record it is only logged, since the accession itself was written fine.
+    async fn record_provenance(&self, accession_id: i32, sha256: String) {
+        let provenance = self.sign_provenance(accession_id, sha256);
+        if let Err(err) = self.provenance_repo.write_one(provenance).await {
+            error!(%err, "Error occurred recording provenance of accession {accession_id}");
+        }
+    }
+
+    /// Builds the provenance record of an accession's file, signed when a signing key is
+    /// configured.
+    fn sign_provenance(&self, accession_id: i32, sha256: String) -> AccessionProvenanceModel {
+        let recorded_at = self.clock.naive_now();
+        let (signature, signer_public_key) = match &self.provenance_signer {
+            Some(signer) => (
+                Some(signer.sign(&signed_message(accession_id, &sha256, recorded_at))),
+                Some(signer.public_key()),
+            ),
+            None => (None, None),
+        };
+        AccessionProvenanceModel {
+            accession_id,
+            sha256,
+            signature,
+            signer_public_key,
+            recorded_at,
+            timestamp_method: None,
+            timestamp_proof: None,
+            timestamped_at: None,
+        }
+    }
+
+    /// Computes and stores the content digest of an accession's WACZ, see
+    /// [`crate::content_digest`].
+    ///
+    /// Failing to is only logged, since the accession itself was written fine.
+    ///
+    /// # Returns
+    /// The digest, `None` if it couldn't be computed
+    async fn record_content_digest(&self, accession_id: i32, s3_filename: &str) -> Option<String> {
+        let content_digest = read_wacz_content_digest(self.s3_repo.as_ref(), s3_filename)
+            .await
+            .map_err(|err| err.to_string())
+            .inspect_err(|err| warn!(%err, "Failed to compute content digest of {s3_filename}"))
+            .ok()
+            .flatten()?;
+        if let Err(err) = self
+            .content_digest_repo
+            .write_one(accession_id, content_digest.clone())
+            .await
+        {
+            error!(%err, "Error occurred recording content digest of accession {accession_id}");
+        }
+        Some(content_digest)
+    }
+
+    /// Finds the accessions of an organization whose capture has the same content as a new
+    /// one, recording the new capture's content digest on the way.
+    ///
+    /// # Returns
+    /// Why the capture should be reviewed if it has duplicates
+    async fn duplicate_capture_reason(
+        &self,
+        accession_id: i32,
+        capture: &StoredCapture,
+        organization_id: i32,
+    ) -> Option<String> {
+        if capture.format != DublinMetadataFormat::Wacz {
+            return None;
+        }
+        let content_digest = self
+            .record_content_digest(accession_id, &capture.s3_filename)
+            .await?;
+        let duplicate_ids: Vec<String> = self
+            .content_digest_repo
+            .find_accession_ids(&content_digest, Some(organization_id))
+            .await
+            .inspect_err(
+                |err| error!(%err, "Error occurred finding duplicates of accession {accession_id}"),
+            )
+            .ok()?
+            .into_iter()
+            .filter(|&id| id != accession_id)
+            .map(|id| id.to_string())
+            .collect();
+        (!duplicate_ids.is_empty())
+            .then(|| format!("Same content as accessions {}", duplicate_ids.join(", ")))
+    }
+
+    /// Stores who posted the social media post an accession was crawled from, see
+    /// [`crate::social_metadata`]. Does nothing for URLs that aren't posts.
+    ///
+    /// What the platform couldn't be asked about is left unset, and failing to store it is
+    /// only logged, since the accession itself was written fine.
+    async fn record_social_metadata(&self, accession_id: i32, url: &str) {
+        let Some(post) = identify_post(url) else {
+            return;
+        };
+        let post = match self.oembed_repo.lookup(&post, url).await {
+            Ok(details) => post.with_details(details),
+            Err(err) => {
+                warn!(%err, "Error occurred looking up social media post {url}");
+                post
+            }
+        };
+        let metadata = SocialMetadataModel {
+            accession_id,
+            platform: post.platform,
+            author_handle: post.author_handle,
+            author_name: post.author_name,
+            post_id: post.post_id,
+            posted_at: post.posted_at,
+            extracted_at: self.clock.naive_now(),
+        };
+        if let Err(err) = self.social_metadata_repo.write_one(metadata).await {
+            error!(%err, "Error occurred recording social media metadata of accession {accession_id}");
+        }
+    }
+
+    /// Works out an uploaded file's scan status, applying the configured enforcement.
+    ///
+    /// When blocking, files that are infected or could not be scanned are deleted and an
+    /// error response is returned. When flagging, they are kept but the accession is made
+    /// private so staff can review it before it is published.
+    async fn enforce_scan_result(
+        &self,
+        payload: &mut CreateAccessionRequestRaw,
+        scan_result: Option<Result<ScanVerdict, String>>,
+    ) -> Result<ScanStatus, Response> {
+        let key = payload.s3_filename.clone();
+        let scan_status = match scan_result {
+            None => return Ok(ScanStatus::NotScanned),
+            Some(Ok(ScanVerdict::Clean)) => return Ok(ScanStatus::Clean),
+            Some(Ok(ScanVerdict::Infected(signature))) => {
+                warn!("Uploaded file {key} matched virus signature {signature}");
+                ScanStatus::Infected
+            }
+            Some(Err(err)) => {
+                error!(%err, "Failed to virus scan uploaded file {key}");
+                ScanStatus::Failed
+            }
+        };
+        if self.scan_enforcement == ScanEnforcement::Flag {
+            payload.is_private = true;
+            return Ok(scan_status);
+        }
+        self.delete_invalid_upload(&key).await;
+        if scan_status == ScanStatus::Infected {
+            Err((StatusCode::BAD_REQUEST, "File failed virus scan").into_response())
+        } else {
+            Err((StatusCode::SERVICE_UNAVAILABLE, "Unable to virus scan file").into_response())
+        }
+    }
+
     /// Uploads from a generic stream to S3 with smart chunk handling.
     ///
     /// This method streams the bytes and decides on upload strategy as it reads:
@@ -410,7 +3406,7 @@ impl AccessionsService {
     /// * `content_type` - The MIME type of the file
     ///
     /// # Returns
-    /// Result containing the upload ID or an error response
+    /// Result containing the hex SHA-256 of the uploaded bytes or an error response
     async fn upload_from_stream<S, E>(
         self,
         key: String,
@@ -431,10 +3427,14 @@ impl AccessionsService {
         let mut upload_id: Option<String> = None;
         let mut upload_parts: Vec<(String, i32)> = Vec::new();
         let mut part_number = 1i32;
+        let mut hasher = Sha256::new();
 
         while let Some(chunk_result) = stream.next().await {
             let chunk = chunk_result.map_err(|err| {
                 error!("Failed to read chunk from stream: {}", err);
+                if let Some(ref id) = upload_id {
+                    self.upload_progress.finish(id, true);
+                }
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     "Failed to read file stream",
@@ -443,6 +3443,7 @@ impl AccessionsService {
             })?;
 
             total_size += chunk.len();
+            hasher.update(&chunk);
             buffer.extend_from_slice(&chunk);
             debug!(
                 "Received chunk of {} bytes, total so far: {:.1} MB",
@@ -468,6 +3469,7 @@ impl AccessionsService {
                 {
                     Ok(id) => {
                         upload_id = Some(id.clone());
+                        self.upload_progress.start(&id);
                         info!("Initiated multipart upload with id: {}", id);
                     }
                     Err(err) => {
@@ -487,10 +3489,11 @@ impl AccessionsService {
                     continue;
                 }
                 let part_bytes = Bytes::from(buffer.split_off(0));
+                let part_size = part_bytes.len();
                 debug!(
                     "Uploading part {} with {:.1} MB.",
                     part_number,
-                    part_bytes.len() as f64 / 1024.0 / 1024.0
+                    part_size as f64 / 1024.0 / 1024.0
                 );
                 match self
                     .s3_repo
@@ -499,11 +3502,13 @@ impl AccessionsService {
                 {
                     Ok((etag, _)) => {
                         upload_parts.push((etag, part_number));
+                        self.upload_progress.record_part(id, part_size);
                         debug!("Successfully uploaded part {}", part_number);
                         part_number += 1;
                     }
                     Err(err) => {
                         error!(%err, "Failed to upload part {} for key: {}", part_number, key);
+                        self.upload_progress.finish(id, true);
                         return Err((
                             StatusCode::INTERNAL_SERVER_ERROR,
                             "Failed to upload file part",
@@ -521,6 +3526,7 @@ impl AccessionsService {
             }
         }
         debug!("Exited loop for reading stream for key: {}", key);
+        let sha256 = format!("{:x}", hasher.finalize());
         // Handle stream end; we now either need to bundle up all the multipart upload parts into the final
         // object or if we didn't do a multipart upload because it was under 5MB, we need to do a single upload
         if let Some(id) = upload_id {
@@ -531,6 +3537,7 @@ impl AccessionsService {
                     buffer.len() as f64 / 1024.0 / 1024.0
                 );
                 let part_bytes = Bytes::from(buffer.split_off(0));
+                let part_size = part_bytes.len();
                 match self
                     .s3_repo
                     .upload_part(&key, &id, part_number, part_bytes)
@@ -538,10 +3545,12 @@ impl AccessionsService {
                 {
                     Ok((etag, _)) => {
                         upload_parts.push((etag, part_number));
+                        self.upload_progress.record_part(&id, part_size);
                         debug!("Successfully uploaded final part {}", part_number);
                     }
                     Err(err) => {
                         error!(%err, "Failed to upload final part for key: {}", key);
+                        self.upload_progress.finish(&id, true);
                         return Err((
                             StatusCode::INTERNAL_SERVER_ERROR,
                             "Failed to upload final part",
@@ -567,10 +3576,12 @@ impl AccessionsService {
                         key,
                         total_size as f64 / 1024.0 / 1024.0
                     );
-                    Ok(id)
+                    self.upload_progress.finish(&id, false);
+                    Ok(sha256)
                 }
                 Err(err) => {
                     error!(%err, "Failed to complete multipart upload for key: {}", key);
+                    self.upload_progress.finish(&id, true);
                     Err((
                         StatusCode::INTERNAL_SERVER_ERROR,
                         "Failed to complete upload",
@@ -593,7 +3604,7 @@ impl AccessionsService {
                         "Successfully uploaded file with key: {} and content type: {}",
                         key, content_type
                     );
-                    Ok(key)
+                    Ok(sha256)
                 }
                 Err(err) => {
                     error!(%err, "Failed to upload file to S3. Key: {}, Content-Type: {}", key, content_type);
@@ -612,18 +3623,67 @@ impl AccessionsService {
     ///
     /// # Arguments
     /// * `key` - The S3 object key where the file will be uploaded
-    /// * `field` - The multipart field containing the file data
+    /// * `head` - Chunks already read from the start of the field
+    /// * `field` - The multipart field containing the rest of the file data
     /// * `content_type` - The MIME type of the file
+    /// * `scrubber` - Strips embedded metadata from the file before it is scanned and stored
+    /// * `virus_scan` - Sender for a running virus scan, which gets a copy of each chunk
     ///
     /// # Returns
-    /// Result containing the upload ID or an error response
+    /// Result containing the hex SHA-256 of the stored, scrubbed bytes or an error response
     async fn upload_from_multipart_field(
         self,
         key: String,
+        head: Vec<Bytes>,
         field: Field<'_>,
         content_type: String,
+        scrubber: Option<MetadataScrubber>,
+        virus_scan: Option<mpsc::Sender<Bytes>>,
     ) -> Result<String, Response> {
-        self.upload_from_stream(key, field, content_type).await
+        let stream = futures::stream::iter(head.into_iter().map(Ok)).chain(field);
+        let scrub_failed = Arc::new(AtomicBool::new(false));
+        let stream = match scrubber {
+            Some(scrubber) => {
+                let scrub_failed = scrub_failed.clone();
+                scrub_stream(stream, scrubber)
+                    .map(move |chunk| {
+                        chunk.map_err(|err| {
+                            if matches!(err, ScrubError::Malformed(_)) {
+                                scrub_failed.store(true, Ordering::Relaxed);
+                            }
+                            err.to_string()
+                        })
+                    })
+                    .boxed()
+            }
+            None => stream
+                .map(|chunk| chunk.map_err(|err| err.to_string()))
+                .boxed(),
+        };
+        let stream = stream.then(move |chunk| {
+            let virus_scan = virus_scan.clone();
+            async move {
+                if let (Ok(bytes), Some(virus_scan)) = (&chunk, virus_scan) {
+                    // A scanner that stopped early reports it in its verdict, so a failed
+                    // send can be ignored here
+                    let _ = virus_scan.send(bytes.clone()).await;
+                }
+                chunk
+            }
+        });
+        self.upload_from_stream(key, Box::pin(stream), content_type)
+            .await
+            .map_err(|err| {
+                if scrub_failed.load(Ordering::Relaxed) {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        "Could not scrub metadata from file, it may be corrupt",
+                    )
+                        .into_response()
+                } else {
+                    err
+                }
+            })
     }
 
     /// Extracts and validates accession data from a multipart form submission.
@@ -639,15 +3699,22 @@ impl AccessionsService {
     /// # Arguments
     /// * `multipart` - The multipart form data from the HTTP request
     /// * `subjects_service` - Service for validating metadata subjects exist
+    /// * `organization_id` - The organization the accession will belong to
     ///
     /// # Returns
-    /// Result containing the parsed accession request or an HTTP error response
+    /// Result containing the parsed accession request, the file's virus scan status, whether
+    /// its embedded metadata was scrubbed and the hex SHA-256 of the stored file, or an HTTP
+    /// error response
     pub async fn extract_accession_from_multipart_form(
         self,
         mut multipart: Multipart,
         subjects_service: SubjectsService,
-    ) -> Result<CreateAccessionRequestRaw, Response> {
+        organization_id: i32,
+    ) -> Result<(CreateAccessionRequestRaw, ScanStatus, bool, Option<String>), Response> {
         let mut metadata_payload: Option<CreateAccessionRequestRaw> = None;
+        let mut scan_status = ScanStatus::NotScanned;
+        let mut metadata_scrubbed = false;
+        let mut sha256 = None;
         let mut step = MultiPartExtractionStep::ExpectMetadata; // first field must be the metadata JSON
 
         while let Some(field) = multipart.next_field().await.map_err(|e| {
@@ -698,6 +3765,7 @@ impl AccessionsService {
                     .verify_subjects_exist(
                         parsed.metadata_subjects.clone(),
                         parsed.metadata_language,
+                        organization_id,
                     )
                     .await;
 
@@ -727,21 +3795,61 @@ impl AccessionsService {
                     (StatusCode::BAD_REQUEST, "File part arrived before metadata").into_response()
                 })?;
 
-                let file_ext = match create_request.metadata_format {
-                    DublinMetadataFormat::Wacz => "wacz",
-                };
+                let (file_ext, upload_content_type) = file_type(&create_request.metadata_format);
+                let mut field = field;
+                let head = read_field_head(&mut field).await?;
+                let head_bytes: Vec<u8> = head
+                    .iter()
+                    .flat_map(|chunk| chunk.iter().copied())
+                    .take(SNIFF_LENGTH)
+                    .collect();
+                if let Err(message) = check_file_type(&create_request.metadata_format, &head_bytes)
+                {
+                    warn!("Rejected upload with content type {content_type}: {message}");
+                    return Err((StatusCode::UNSUPPORTED_MEDIA_TYPE, message).into_response());
+                }
 
                 // Discard the original filename since we have all that from the metadata
-                // Use this to make sure there are no filename collisions between objects in s3
-                let unique_name = format!("{}.{}", Uuid::new_v4(), file_ext);
+                // The key starts with a UUID so there are no collisions between objects in s3
+                let unique_name = self.s3_key_scheme.object_key(
+                    &create_request.metadata_title,
+                    file_ext,
+                    self.ids.new_id(),
+                    self.clock.naive_now(),
+                    organization_id,
+                );
                 create_request.s3_filename = unique_name.clone();
-                self.clone()
-                    .upload_from_multipart_field(unique_name.clone(), field, content_type.clone())
+                let scrubber = match create_request.metadata_scrubbing {
+                    MetadataScrubbing::Scrub => {
+                        MetadataScrubber::for_format(&create_request.metadata_format)
+                    }
+                    MetadataScrubbing::Keep => None,
+                };
+                metadata_scrubbed = scrubber.is_some();
+                let (virus_scan, virus_scan_task) = self.start_virus_scan().unzip();
+                let file_sha256 = self
+                    .clone()
+                    .upload_from_multipart_field(
+                        unique_name.clone(),
+                        head,
+                        field,
+                        upload_content_type.to_string(),
+                        scrubber,
+                        virus_scan,
+                    )
                     .await
                     .map_err(|e| {
                         error!("Failed to upload file {unique_name}: {e:?}");
                         e
                     })?;
+                let scan_result = match virus_scan_task {
+                    Some(task) => Some(task.await.unwrap_or_else(|err| Err(err.to_string()))),
+                    None => None,
+                };
+                scan_status = self
+                    .enforce_scan_result(create_request, scan_result)
+                    .await?;
+                sha256 = Some(file_sha256);
 
                 info!("Successfully uploaded file: {unique_name}");
                 if let Some(ref mut req) = metadata_payload {
@@ -753,12 +3861,275 @@ impl AccessionsService {
             error!("Skipping unexpected field without filename: name={field_name}");
         }
 
-        metadata_payload.ok_or_else(|| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Could not extract metadata",
+        metadata_payload
+            .map(|payload| (payload, scan_status, metadata_scrubbed, sha256))
+            .ok_or_else(|| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Could not extract metadata",
+                )
+                    .into_response()
+            })
+    }
+}
+
+/// Archives one crawl requested through [`AccessionsService::create_one`].
+struct CrawlJob {
+    service: AccessionsService,
+    crawler: Arc<dyn CrawlerRepo>,
+    /// The pending accession the crawl is stored as
+    accession_id: i32,
+    payload: CreateAccessionRequest,
+    user_email: String,
+    organization_id: i32,
+}
+
+impl CrawlJob {
+    /// Records how the crawl went against the user who asked for it, for their activity
+    /// digest, see [`crate::activity_digest`].
+    async fn record_outcome(
+        &self,
+        event: &'static str,
+        entity_type: &'static str,
+        entity_id: String,
+        reason: Option<&str>,
+    ) {
+        let entry = AuditEntry {
+            actor_email: self.user_email.clone(),
+            impersonator_email: None,
+            event,
+            entity_type,
+            entity_id,
+            details: Some(json!({"url": self.payload.url, "reason": reason})),
+        };
+        if let Err(err) = self.service.audit_log_repo.record(entry).await {
+            error!(%err, "Error occurred recording outcome of crawl for {}", self.payload.url);
+        }
+    }
+}
+
+#[async_trait]
+impl CrawlSteps for CrawlJob {
+    type Wacz = CrawlArchive;
+
+    async fn launch(&self) -> Option<LaunchedCrawl> {
+        // Crawl the canonical url so tracking parameters don't produce duplicate captures;
+        // the original url is still stored as the seed url
+        let create_crawl_request = CreateCrawlRequest {
+            url: canonicalize_url(&self.payload.url),
+            browser_profile: self.payload.browser_profile.clone(),
+        };
+        let crawler = self.crawler.backend();
+        match self.crawler.launch(create_crawl_request).await {
+            Err(err) => {
+                error!(%err, %crawler, "Error occurred launching crawl");
+                None
+            }
+            Ok(crawl) => {
+                info!(%crawler, "Launched crawl request for url {}", self.payload.url);
+                self.service
+                    .pipeline_metrics
+                    .crawl_started(crawl.id, &self.payload.url);
+                Some(crawl)
+            }
+        }
+    }
+
+    async fn poll(&self, crawl: &LaunchedCrawl, poll: u32) -> PollOutcome {
+        info!("Polled {poll} time(s) for url {}", self.payload.url);
+        self.service.pipeline_metrics.crawl_polled(crawl.id);
+        match self.crawler.is_finished(crawl).await {
+            Ok(true) => {
+                let crawl_time_secs = (POLL_INTERVAL * poll).as_secs();
+                info!(%poll, "Crawl complete after {crawl_time_secs}s");
+                PollOutcome::Complete
+            }
+            Ok(false) => PollOutcome::Running,
+            Err(err) => {
+                error!(
+                    %err,
+                    "Invalid crawl response, trying again in {}s",
+                    POLL_INTERVAL.as_secs()
+                );
+                self.service.pipeline_metrics.poll_retried();
+                PollOutcome::Errored
+            }
+        }
+    }
+
+    async fn wait(&self) {
+        sleep(POLL_INTERVAL).await;
+    }
+
+    async fn download(&self, crawl: &LaunchedCrawl) -> Option<CrawlArchive> {
+        self.crawler
+            .download(crawl)
+            .await
+            .inspect_err(|err| {
+                error!(%err, "Error occurred downloading crawl archive, aborting accession creation")
+            })
+            .ok()
+    }
+
+    async fn upload(&self, archive: CrawlArchive) -> Option<StoredCapture> {
+        let primary_title = self
+            .payload
+            .metadata(self.payload.primary_language())
+            .map(|metadata| metadata.title.clone())
+            .unwrap_or_default();
+        let (file_ext, content_type) = file_type(&archive.format);
+        let s3_filename = self.service.s3_key_scheme.object_key(
+            &primary_title,
+            file_ext,
+            self.service.ids.new_id(),
+            self.service.clock.naive_now(),
+            self.organization_id,
+        );
+        let sha256 = match self
+            .service
+            .clone()
+            .upload_from_stream(
+                s3_filename.clone(),
+                archive.stream,
+                content_type.to_string(),
             )
-                .into_response()
+            .await
+        {
+            Ok(sha256) => sha256,
+            Err(err) => {
+                error!(
+                    "Error occurred uploading crawl archive to S3: {:?}, aborting accession creation",
+                    err
+                );
+                return None;
+            }
+        };
+        info!("Crawl archive uploaded to S3 with filename {}", s3_filename);
+        if let Some(metadata_json) = archive.metadata_json {
+            // the capture is still usable without it, so a failure here isn't fatal
+            if let Err(err) = self
+                .service
+                .s3_repo
+                .upload_from_bytes(
+                    &format!("{s3_filename}.info.json"),
+                    metadata_json,
+                    "application/json",
+                )
+                .await
+            {
+                error!(%err, "Error occurred uploading capture metadata JSON to S3");
+            }
+        }
+        // the quality checks read the pages index, which only WACZs have
+        let quality_reason = match archive.format {
+            DublinMetadataFormat::Wacz => {
+                self.service
+                    .assess_stored_capture(&s3_filename, &self.payload.url)
+                    .await
+            }
+            _ => None,
+        };
+        Some(StoredCapture {
+            s3_filename,
+            format: archive.format,
+            sha256,
+            quality_reason,
         })
     }
+
+    async fn write(&self, crawl: &LaunchedCrawl, capture: &StoredCapture) -> Option<i32> {
+        let create_accessions_request = CreateAccessionRequest {
+            metadata_format: capture.format.clone(),
+            s3_filename: Some(capture.s3_filename.clone()),
+            ..self.payload.clone()
+        };
+        self.service
+            .accessions_repo
+            .complete_crawl(
+                self.accession_id,
+                create_accessions_request,
+                crawl.org_id,
+                crawl.id,
+                crawl.job_run_id.clone(),
+            )
+            .await
+            .inspect_err(|err| error!(%err, "Error occurred writing crawl result to db!"))
+            .ok()
+            .map(|()| self.accession_id)
+    }
+
+    async fn notify(&self, crawl: &LaunchedCrawl, id: i32, capture: StoredCapture) {
+        info!("Crawl result written to db successfully");
+        self.service.pipeline_metrics.crawl_completed(crawl.id);
+        self.service
+            .record_provenance(id, capture.sha256.clone())
+            .await;
+        self.service
+            .record_social_metadata(id, &self.payload.url)
+            .await;
+        let duplicate_reason = self
+            .service
+            .duplicate_capture_reason(id, &capture, self.organization_id)
+            .await;
+        let bad_capture_reason = capture.quality_reason.or(duplicate_reason);
+        self.record_outcome(
+            CRAWL_COMPLETED,
+            "accession",
+            id.to_string(),
+            bad_capture_reason.as_deref(),
+        )
+        .await;
+        match bad_capture_reason {
+            // held back from the feed until a curator accepts it
+            Some(reason) => self.service.flag_bad_capture(id, reason).await,
+            None => self.service.announce_if_public(id).await,
+        }
+        let email_subject = format!("Your URL {} has been archived!", self.payload.url);
+        let email_body = format!(
+            "We have archived your <a href='https://sudandigitalarchive.com/archive/{}?isPrivate={}&lang={}'>url</a>.",
+            id,
+            self.payload.is_private || self.payload.embargo_until.is_some(),
+            self.payload.primary_language()
+        );
+        let email_result = self
+            .service
+            .emails_repo
+            .send_email(self.user_email.clone(), email_subject, email_body)
+            .await;
+        info!(
+            "Email sent to user with id {id} for url {}",
+            self.payload.url
+        );
+        if let Err(err) = email_result {
+            error!(%err, "Error occurred sending email to user");
+        }
+        self.service
+            .create_pdf_derivative(id, &canonicalize_url(&self.payload.url))
+            .await;
+        // downloaded videos get the same thumbnail and web rendition as uploaded ones
+        if capture.format == DublinMetadataFormat::Mp4 {
+            self.service
+                .create_media_derivatives(id, &capture.s3_filename, &capture.format)
+                .await;
+        }
+    }
+
+    async fn failed(&self, crawl_id: Option<Uuid>, error: CrawlError) {
+        self.record_outcome(
+            CRAWL_FAILED,
+            "crawl",
+            crawl_id.map_or_else(|| self.payload.url.clone(), |crawl_id| crawl_id.to_string()),
+            Some(error.reason()),
+        )
+        .await;
+        self.service.fail_pending_crawl(self.accession_id).await;
+        let metrics = &self.service.pipeline_metrics;
+        match (crawl_id, error) {
+            // The crawler may still finish the crawl, we've only stopped waiting on it
+            (Some(crawl_id), CrawlError::TimedOut) => {
+                metrics.crawl_abandoned(crawl_id, error.reason())
+            }
+            _ => metrics.crawl_failed(crawl_id, &self.payload.url, error.reason()),
+        }
+    }
 }
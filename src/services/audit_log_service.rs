@@ -0,0 +1,102 @@
+//! Service layer for searching the audit log.
+//!
+//! Admins page through entries as JSON or download them as CSV, see
+//! [`crate::audit_log_export`]. Entries are written by the services doing the audited
+//! actions through [`crate::repos::audit_log_repo::AuditLogRepo::record`].
+
+use crate::audit_log_export::{to_csv, CSV_CONTENT_TYPE};
+use crate::models::request::AuditLogPagination;
+use crate::models::response::ListAuditLogResponse;
+use crate::repos::audit_log_repo::{AuditLogFilter, AuditLogRepo};
+use crate::repos::pagination::PageWindow;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use http::{header, StatusCode};
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Most entries a single CSV export holds, newest first.
+pub const AUDIT_LOG_EXPORT_LIMIT: u64 = 10_000;
+
+/// Service for searching the audit log.
+/// Uses dynamic traits for dependency injection
+#[derive(Clone)]
+pub struct AuditLogService {
+    pub audit_log_repo: Arc<dyn AuditLogRepo>,
+}
+
+impl From<&AuditLogPagination> for AuditLogFilter {
+    fn from(pagination: &AuditLogPagination) -> Self {
+        Self {
+            entity_type: pagination.entity_type.clone(),
+            entity_id: None,
+            actor: pagination.actor.clone(),
+            event: pagination.event.clone(),
+            date_from: pagination.date_from,
+            date_to: pagination.date_to,
+        }
+    }
+}
+
+impl AuditLogService {
+    /// Lists a page of audit log entries matching the filters.
+    ///
+    /// # Arguments
+    /// * `pagination` - The page to list and the filters to apply
+    ///
+    /// # Returns
+    /// JSON response containing the entries or an error response
+    pub async fn list(self, pagination: AuditLogPagination) -> Response {
+        let window = PageWindow::new(pagination.page, pagination.per_page);
+        match self
+            .audit_log_repo
+            .list(AuditLogFilter::from(&pagination), window)
+            .await
+        {
+            Err(err) => {
+                error!(%err, "Error occurred listing the audit log");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+            Ok((rows, num_pages)) => Json(ListAuditLogResponse {
+                items: rows.into_iter().map(Into::into).collect(),
+                num_pages,
+                page: window.page,
+                per_page: window.per_page,
+            })
+            .into_response(),
+        }
+    }
+
+    /// Exports the audit log entries matching the filters as CSV, up to
+    /// [`AUDIT_LOG_EXPORT_LIMIT`] of them.
+    ///
+    /// # Arguments
+    /// * `pagination` - The filters to apply, its page and page size are ignored
+    ///
+    /// # Returns
+    /// CSV attachment containing the entries or an error response
+    pub async fn export(self, pagination: AuditLogPagination) -> Response {
+        info!("Exporting the audit log...");
+        match self
+            .audit_log_repo
+            .export(AuditLogFilter::from(&pagination), AUDIT_LOG_EXPORT_LIMIT)
+            .await
+        {
+            Err(err) => {
+                error!(%err, "Error occurred exporting the audit log");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+            Ok(rows) => (
+                [
+                    (header::CONTENT_TYPE, CSV_CONTENT_TYPE),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        "attachment; filename=\"audit-log.csv\"",
+                    ),
+                ],
+                to_csv(&rows),
+            )
+                .into_response(),
+        }
+    }
+}
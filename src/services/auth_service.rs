@@ -1,27 +1,43 @@
 use crate::auth::JWT_KEYS;
-use crate::models::auth::JWTClaims;
-use crate::models::request::{AuthorizeRequest, LoginRequest};
+use crate::client_ip::KnownApiKeys;
+use crate::clock::SharedClock;
+use crate::models::auth::{AuthenticatedUser, JWTClaims};
+use crate::models::request::{
+    AuthorizeRequest, LoginRequest, PostmarkWebhookRequest, UpdateActivityDigestRequest,
+};
+use crate::models::response::{ImpersonationResponse, ListUsersResponse, UserResponse};
 use crate::repos::{
+    audit_log_repo::{AuditEntry, AuditLogRepo},
     auth_repo::{ApiKeyUserInfo, AuthRepo},
     emails_repo::EmailsRepo,
 };
 use ::entity::archive_user::Model as ArchiveUserModel;
-use ::entity::sea_orm_active_enums::Role;
+use ::entity::sea_orm_active_enums::{EmailStatus, Role};
 use axum::http::{
     header::{HeaderMap, HeaderValue, SET_COOKIE},
     StatusCode,
 };
 use axum::response::{IntoResponse, Response};
-use chrono::{NaiveDateTime, Utc};
+use axum::Json;
+use chrono::{DateTime, Duration, NaiveDateTime};
 use jsonwebtoken::errors::Error;
-use jsonwebtoken::{encode, Header};
 use sea_orm::DbErr;
+use serde_json::json;
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
-fn calculate_max_age(expiry_time: NaiveDateTime) -> i64 {
-    let now = Utc::now().naive_utc();
+/// How long an admin can act as another user before signing in again
+pub const IMPERSONATION_TTL_MINUTES: i64 = 30;
+/// Audit log event for an admin starting to act as another user
+pub const IMPERSONATION_STARTED: &str = "impersonation_started";
+/// Audit log event for a mutating request made while an admin acts as another user
+pub const IMPERSONATED_REQUEST: &str = "impersonated_request";
+/// Audit log event for an admin deactivating a user
+pub const USER_DEACTIVATED: &str = "user_deactivated";
+
+fn calculate_max_age(expiry_time: NaiveDateTime, now: NaiveDateTime) -> i64 {
     let duration = expiry_time.signed_duration_since(now);
     if duration.num_seconds() > 0 {
         duration.num_seconds()
@@ -30,17 +46,44 @@ fn calculate_max_age(expiry_time: NaiveDateTime) -> i64 {
     }
 }
 
+/// What a Postmark webhook says about the address it reports on, if anything we track.
+fn email_status_for(payload: &PostmarkWebhookRequest) -> Option<EmailStatus> {
+    match (payload.record_type.as_str(), payload.bounce_type.as_deref()) {
+        ("SpamComplaint", _) => Some(EmailStatus::SpamComplaint),
+        ("Bounce", Some("HardBounce" | "BadEmailAddress")) => Some(EmailStatus::HardBounce),
+        ("Bounce", Some("SoftBounce" | "Transient" | "DnsError")) => Some(EmailStatus::SoftBounce),
+        _ => None,
+    }
+}
+
+/// Compares secrets by their hashes so the time taken doesn't leak how much matched.
+fn secrets_match(expected: &str, actual: &str) -> bool {
+    Sha256::digest(expected.as_bytes()) == Sha256::digest(actual.as_bytes())
+}
+
 #[derive(Clone)]
 pub struct AuthService {
     pub auth_repo: Arc<dyn AuthRepo>,
     pub emails_repo: Arc<dyn EmailsRepo>,
+    pub audit_log_repo: Arc<dyn AuditLogRepo>,
     pub jwt_cookie_domain: String,
+    /// Password Postmark sends with its webhooks, `None` turns the webhook off
+    pub postmark_webhook_secret: Option<String>,
+    /// API keys verified so far, which the rate limiter keys by their owner
+    pub known_api_keys: KnownApiKeys,
+    pub clock: SharedClock,
 }
 
 impl AuthService {
+    /// Starts a session for the user with the email in `login_request`, if there is one.
+    ///
+    /// # Arguments
+    /// * `login_request` - Email of the user logging in
+    /// * `client` - What the client asking for the login link is known by
     pub async fn log_user_in(
         self,
         login_request: LoginRequest,
+        client: Option<String>,
     ) -> Result<Option<(Uuid, Uuid)>, DbErr> {
         let user_id = self
             .auth_repo
@@ -48,17 +91,13 @@ impl AuthService {
             .await?;
         match user_id {
             Some(user_id) => {
-                let session_id = self.auth_repo.create_session(user_id).await?;
+                let session_id = self.auth_repo.create_session(user_id, client).await?;
                 Ok(Some((session_id, user_id)))
             }
             None => Ok(None),
         }
     }
 
-    pub async fn delete_expired_sessions(self) {
-        self.auth_repo.delete_expired_sessions().await
-    }
-
     pub async fn send_login_email(self, session_id: Uuid, user_id: Uuid, user_email: String) {
         let email_body = format!(
             "<a href='https://sudandigitalarchive.com/jwt-auth?sessionId={session_id}&userId={user_id}'>Click to login!</a>"        );
@@ -87,15 +126,19 @@ impl AuthService {
         self,
         user_email: String,
         role: Role,
+        organization_id: i32,
         expiry_time: NaiveDateTime,
+        impersonator: Option<String>,
     ) -> Result<[String; 2], Error> {
         let claims = JWTClaims {
             sub: user_email,
             exp: expiry_time.and_utc().timestamp() as usize,
             role,
+            impersonator,
+            organization_id,
         };
-        let jwt = encode(&Header::default(), &claims, &JWT_KEYS.encoding)?;
-        let max_age = calculate_max_age(expiry_time);
+        let jwt = JWT_KEYS.encode(&claims)?;
+        let max_age = calculate_max_age(expiry_time, self.clock.naive_now());
         // need this cookie that is not http only to just read the jwt on the client side
         let cookie_string = if self.jwt_cookie_domain == "localhost" {
             let logged_in_cookie =
@@ -139,7 +182,13 @@ impl AuthService {
                     Some(user) => {
                         let cookie_strings_results = self
                             .clone()
-                            .build_auth_cookie_strings(user.email, user.role, sesh_exists)
+                            .build_auth_cookie_strings(
+                                user.email,
+                                user.role,
+                                user.organization_id,
+                                sesh_exists,
+                                None,
+                            )
                             .map_err(|err| format!("Failed to build cookie string: {err}"))?;
                         let mut headers = HeaderMap::new();
                         for cookie_string in cookie_strings_results.iter() {
@@ -165,22 +214,22 @@ impl AuthService {
         }
     }
 
-    pub async fn login(self, payload: LoginRequest) -> Result<Response, String> {
+    pub async fn login(
+        self,
+        payload: LoginRequest,
+        client: Option<String>,
+    ) -> Result<Response, String> {
         let login_result = self
             .clone()
-            .log_user_in(payload.clone())
+            .log_user_in(payload.clone(), client)
             .await
             .map_err(|err| format!("Database error: {err}"))?;
 
         match login_result {
             Some((session_id, user_id)) => {
-                info!(
-                    "Sending login email to user with email {} and deleting expired sessions",
-                    payload.email
-                );
+                info!("Sending login email to user with email {}", payload.email);
                 let email = payload.email.clone();
                 tokio::spawn(async move {
-                    self.clone().delete_expired_sessions().await;
                     self.clone()
                         .send_login_email(session_id, user_id, email)
                         .await;
@@ -195,44 +244,412 @@ impl AuthService {
         }
     }
 
-    pub async fn create_api_key(&self, user_id: Uuid) -> Result<String, DbErr> {
-        self.auth_repo.create_api_key_for_user(user_id).await
+    /// Finds a user an admin may manage, which for platform admins is anyone and for other
+    /// admins is only users in their own organization.
+    ///
+    /// # Returns
+    /// The user, or `None` if there is no such active user the admin may manage
+    async fn get_managed_user(
+        &self,
+        admin: &AuthenticatedUser,
+        user_id: Uuid,
+    ) -> Result<Option<ArchiveUserModel>, DbErr> {
+        let user = self.auth_repo.get_one(user_id).await?;
+        Ok(user.filter(|user| {
+            admin.is_platform_admin() || user.organization_id == admin.organization_id
+        }))
     }
 
+    /// Creates an API key for a user the admin may manage.
+    ///
+    /// # Returns
+    /// The key's secret, or `None` if the admin may not manage the user
+    pub async fn create_api_key(
+        &self,
+        admin: &AuthenticatedUser,
+        user_id: Uuid,
+    ) -> Result<Option<String>, DbErr> {
+        if self.get_managed_user(admin, user_id).await?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(self.auth_repo.create_api_key_for_user(user_id).await?))
+    }
+
+    /// Looks up whose an API key is, remembering valid keys for the rate limiter.
     pub async fn verify_api_key(&self, api_key: String) -> Result<Option<ApiKeyUserInfo>, DbErr> {
-        self.auth_repo.verify_api_key(api_key).await
+        let user_info = self.auth_repo.verify_api_key(api_key.clone()).await?;
+        if let Some(user_info) = &user_info {
+            self.known_api_keys.remember(&api_key, &user_info.email);
+        }
+        Ok(user_info)
+    }
+
+    /// Lists users along with whether emails to them are getting through.
+    ///
+    /// Platform admins see every user, other admins only see their own organization's.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin asking
+    ///
+    /// # Returns
+    /// JSON response containing the users or an error response
+    pub async fn list_users(self, admin: AuthenticatedUser) -> Response {
+        info!("Getting users...");
+        let organization_id = (!admin.is_platform_admin()).then_some(admin.organization_id);
+        match self.auth_repo.list_users(organization_id).await {
+            Ok(users) => Json(ListUsersResponse {
+                items: users.into_iter().map(UserResponse::from).collect(),
+            })
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred listing users");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Signs an admin in as another user for support, until the support session or the
+    /// admin's own session expires, whichever comes first.
+    ///
+    /// The session replaces the admin's and names them as the impersonator, so every mutating
+    /// request made in it is audited against both of them. Admins can't be impersonated,
+    /// which stops support mode being used to act as another admin, and only platform
+    /// admins can impersonate users outside their own organization.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin starting support mode
+    /// * `user_id` - ID of the user to act as
+    ///
+    /// # Returns
+    /// The impersonated user along with the session cookies, 404 if there's no such active
+    /// user the admin may manage or 403 if they're an admin
+    pub async fn impersonate(self, admin: AuthenticatedUser, user_id: Uuid) -> Response {
+        let user = match self.get_managed_user(&admin, user_id).await {
+            Ok(Some(user)) => user,
+            Ok(None) => return (StatusCode::NOT_FOUND, "User not found").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred getting user to impersonate");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        };
+        if user.role == Role::Admin {
+            return (StatusCode::FORBIDDEN, "Admins can't be impersonated").into_response();
+        }
+        let ttl_expiry = self.clock.now() + Duration::minutes(IMPERSONATION_TTL_MINUTES);
+        // API keys don't expire, so their support sessions only last the TTL
+        let expires_at = admin
+            .expiry
+            .and_then(|expiry| DateTime::from_timestamp(expiry as i64, 0))
+            .map_or(ttl_expiry, |expiry| expiry.min(ttl_expiry))
+            .naive_utc();
+        let entry = AuditEntry {
+            actor_email: admin.user_id.clone(),
+            impersonator_email: None,
+            event: IMPERSONATION_STARTED,
+            entity_type: "archive_user",
+            entity_id: user.id.to_string(),
+            details: Some(json!({
+                "email": user.email,
+                "expires_at": expires_at,
+                "auth_method": admin.auth_method.as_str(),
+            })),
+        };
+        if let Err(err) = self.audit_log_repo.record(entry).await {
+            error!(%err, "Error occurred auditing impersonation");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response();
+        }
+        info!("Admin {} is impersonating {}", admin.user_id, user.email);
+        let cookie_strings = match self.clone().build_auth_cookie_strings(
+            user.email.clone(),
+            user.role.clone(),
+            user.organization_id,
+            expires_at,
+            Some(admin.user_id),
+        ) {
+            Ok(cookie_strings) => cookie_strings,
+            Err(err) => {
+                error!(%err, "Error occurred building impersonation cookies");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+                    .into_response();
+            }
+        };
+        let mut headers = HeaderMap::new();
+        for cookie_string in cookie_strings.iter() {
+            match HeaderValue::from_str(cookie_string) {
+                Ok(header_value) => headers.append(SET_COOKIE, header_value),
+                Err(err) => {
+                    error!(%err, "Error occurred creating impersonation cookie header");
+                    return (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+                        .into_response();
+                }
+            };
+        }
+        let response = ImpersonationResponse {
+            user_id: user.id,
+            email: user.email,
+            role: user.role,
+            expires_at,
+        };
+        (StatusCode::OK, headers, Json(response)).into_response()
+    }
+
+    /// Deactivates a user the admin may manage, signing them out everywhere.
+    ///
+    /// Their sessions are deleted and API keys revoked straight away. Session cookies they
+    /// already hold stop working once [`crate::models::auth::AuthenticatedUser`] next
+    /// checks that they are active. Admins can't deactivate themselves, so there is always
+    /// someone left to undo a mistake.
+    ///
+    /// # Arguments
+    /// * `admin` - The admin deactivating the user
+    /// * `user_id` - ID of the user to deactivate
+    ///
+    /// # Returns
+    /// 200 once the user is deactivated, 404 if there's no such active user the admin may
+    /// manage or 403 if it's the admin themselves
+    pub async fn deactivate_user(self, admin: AuthenticatedUser, user_id: Uuid) -> Response {
+        let user = match self.get_managed_user(&admin, user_id).await {
+            Ok(Some(user)) => user,
+            Ok(None) => return (StatusCode::NOT_FOUND, "User not found").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred getting user to deactivate");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        };
+        if user.email == admin.user_id {
+            return (StatusCode::FORBIDDEN, "Admins can't deactivate themselves").into_response();
+        }
+        match self.auth_repo.deactivate_user(user_id).await {
+            Ok(true) => {}
+            Ok(false) => return (StatusCode::NOT_FOUND, "User not found").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred deactivating user");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        }
+        let entry = AuditEntry {
+            actor_email: admin.user_id.clone(),
+            impersonator_email: admin.impersonator.clone(),
+            event: USER_DEACTIVATED,
+            entity_type: "archive_user",
+            entity_id: user.id.to_string(),
+            details: Some(json!({
+                "email": user.email,
+                "auth_method": admin.auth_method.as_str(),
+            })),
+        };
+        if let Err(err) = self.audit_log_repo.record(entry).await {
+            // the user is already locked out, so this isn't worth failing the request for
+            error!(%err, "Error occurred auditing user deactivation");
+        }
+        info!("Admin {} deactivated {}", admin.user_id, user.email);
+        (StatusCode::OK, "User deactivated").into_response()
+    }
+
+    /// Checks whether the user with an address is still active.
+    pub async fn is_user_active(&self, email: String) -> Result<bool, DbErr> {
+        self.auth_repo.is_active(email).await
+    }
+
+    /// Writes a mutating request made in support mode to the audit log, against both the
+    /// impersonated user and the admin acting as them.
+    ///
+    /// # Arguments
+    /// * `user` - The impersonated user, naming the admin as their impersonator
+    /// * `method` - HTTP method of the request
+    /// * `path` - Path the request was made to
+    /// * `route` - The route template the path matched, if any
+    pub async fn audit_impersonated_request(
+        &self,
+        user: &AuthenticatedUser,
+        method: &str,
+        path: &str,
+        route: Option<String>,
+    ) -> Result<(), DbErr> {
+        self.audit_log_repo
+            .record(AuditEntry {
+                actor_email: user.user_id.clone(),
+                impersonator_email: user.impersonator.clone(),
+                event: IMPERSONATED_REQUEST,
+                entity_type: "request",
+                entity_id: path.to_string(),
+                details: Some(json!({
+                    "method": method,
+                    "route": route,
+                    "auth_method": user.auth_method.as_str(),
+                })),
+            })
+            .await
     }
 
-    pub async fn delete_expired_api_keys(self) {
-        self.auth_repo.delete_expired_api_keys().await
+    /// Records a bounce or spam complaint reported by Postmark against the user it was for.
+    ///
+    /// Postmark doesn't sign its webhooks, instead it sends the basic auth credentials set
+    /// in the webhook URL, so the password is checked against the configured secret.
+    /// Reports about addresses that aren't users, or record types we don't track, are
+    /// acknowledged and ignored so Postmark doesn't retry them.
+    ///
+    /// # Arguments
+    /// * `password` - The basic auth password sent with the webhook, if any
+    /// * `payload` - The webhook body
+    ///
+    /// # Returns
+    /// 200 once handled, 401 for bad credentials or 404 if the webhook is turned off
+    pub async fn handle_postmark_webhook(
+        self,
+        password: Option<String>,
+        payload: PostmarkWebhookRequest,
+    ) -> Response {
+        let Some(secret) = self.postmark_webhook_secret.as_deref() else {
+            return (StatusCode::NOT_FOUND, "Not found").into_response();
+        };
+        if !password.is_some_and(|password| secrets_match(secret, &password)) {
+            warn!("Rejected Postmark webhook with bad credentials");
+            return (StatusCode::UNAUTHORIZED, "Invalid webhook credentials").into_response();
+        }
+        let (Some(email_status), Some(email)) = (email_status_for(&payload), payload.email) else {
+            return (StatusCode::OK, "Webhook ignored").into_response();
+        };
+        // the report is logged by message ID, the address stays out of the logs
+        info!(
+            "Postmark reported {email_status:?} for message {}",
+            payload.message_id.as_deref().unwrap_or("without an ID")
+        );
+        match self.auth_repo.set_email_status(email, email_status).await {
+            Ok(_) => (StatusCode::OK, "Webhook processed").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred recording email status");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Opts the signed in user in to or out of the weekly activity digest email.
+    ///
+    /// # Arguments
+    /// * `user` - The signed in user
+    /// * `payload` - Whether they want the digest
+    ///
+    /// # Returns
+    /// 200 once saved, or 404 if the user no longer exists
+    pub async fn set_activity_digest(
+        self,
+        user: &AuthenticatedUser,
+        payload: UpdateActivityDigestRequest,
+    ) -> Response {
+        info!(
+            "Setting activity digest of {} to {}",
+            user.user_id, payload.enabled
+        );
+        match self
+            .auth_repo
+            .set_activity_digest(user.user_id.clone(), payload.enabled)
+            .await
+        {
+            Ok(true) if payload.enabled => {
+                (StatusCode::OK, "Subscribed to the activity digest").into_response()
+            }
+            Ok(true) => (StatusCode::OK, "Unsubscribed from the activity digest").into_response(),
+            Ok(false) => (StatusCode::NOT_FOUND, "User not found").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred setting activity digest");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Duration;
+    use crate::clock::Clock;
+    use crate::test_tools::{build_test_auth_service, FixedClock};
+    use pretty_assertions::assert_eq;
 
     #[test]
     fn test_calculate_max_age_future() {
-        let now = Utc::now().naive_utc();
+        let now = FixedClock::default().naive_now();
         let expiry = now + Duration::seconds(3600);
-        let max_age = calculate_max_age(expiry);
-        assert!(max_age > 3598 && max_age <= 3600);
+        let max_age = calculate_max_age(expiry, now);
+        assert_eq!(max_age, 3600);
     }
 
     #[test]
     fn test_calculate_max_age_past() {
-        let now = Utc::now().naive_utc();
+        let now = FixedClock::default().naive_now();
         let expiry = now - Duration::seconds(3600);
-        let max_age = calculate_max_age(expiry);
+        let max_age = calculate_max_age(expiry, now);
         assert_eq!(max_age, 0);
     }
 
+    #[test]
+    fn cookies_last_until_the_session_expires() {
+        let clock = FixedClock::default();
+        let service = AuthService {
+            clock: SharedClock::new(clock.clone()),
+            ..build_test_auth_service()
+        };
+        let expiry = clock.now().naive_utc() + Duration::hours(2);
+        let [auth_cookie, logged_in_cookie] = service
+            .clone()
+            .build_auth_cookie_strings(
+                "someuser@gmail.com".to_string(),
+                Role::Admin,
+                1,
+                expiry,
+                None,
+            )
+            .unwrap();
+        assert!(auth_cookie.contains("Max-Age=7200"));
+        assert!(logged_in_cookie.contains("Max-Age=7200"));
+
+        clock.advance(Duration::hours(3));
+        let [auth_cookie, _] = service
+            .build_auth_cookie_strings(
+                "someuser@gmail.com".to_string(),
+                Role::Admin,
+                1,
+                expiry,
+                None,
+            )
+            .unwrap();
+        assert!(auth_cookie.contains("Max-Age=0"));
+    }
+
+    #[test]
+    fn maps_postmark_records_to_email_statuses() {
+        let webhook = |record_type: &str, bounce_type: Option<&str>| PostmarkWebhookRequest {
+            record_type: record_type.to_string(),
+            bounce_type: bounce_type.map(str::to_string),
+            email: Some("someone@example.com".to_string()),
+            message_id: None,
+        };
+        assert_eq!(
+            email_status_for(&webhook("Bounce", Some("HardBounce"))),
+            Some(EmailStatus::HardBounce)
+        );
+        assert_eq!(
+            email_status_for(&webhook("Bounce", Some("Transient"))),
+            Some(EmailStatus::SoftBounce)
+        );
+        assert_eq!(
+            email_status_for(&webhook("SpamComplaint", Some("SpamComplaint"))),
+            Some(EmailStatus::SpamComplaint)
+        );
+        assert_eq!(
+            email_status_for(&webhook("Bounce", Some("AutoResponder"))),
+            None
+        );
+        assert_eq!(email_status_for(&webhook("Delivery", None)), None);
+    }
+
     #[test]
     fn test_calculate_max_age_now() {
-        let now = Utc::now().naive_utc();
-        let max_age = calculate_max_age(now);
+        let now = FixedClock::default().naive_now();
+        let max_age = calculate_max_age(now, now);
         assert_eq!(max_age, 0);
     }
 }
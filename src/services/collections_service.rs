@@ -0,0 +1,497 @@
+//! Service layer for curated collections of accessions.
+//!
+//! This module handles the business logic for making collections and adding and removing
+//! their accessions.
+//! Collections can be exported as one ZIP of their public accessions, see
+//! [`crate::collection_export`].
+
+use crate::clock::{SharedClock, SharedIdGen};
+use crate::collection_export::{
+    bundle, capture_file_name, export_key, CollectionExportManifest, ExportFailedEmail, ExportFile,
+    ExportPermits, ExportReadyEmail, ExportedAccession, COLLECTION_EXPORT_CONTENT_TYPE,
+    EXPORT_LINK_EXPIRY_SECONDS, EXPORT_PART_SIZE, MAX_EXPORT_BYTES,
+};
+use crate::models::auth::AuthenticatedUser;
+use crate::models::request::CreateCollectionRequest;
+use crate::models::response::{CollectionExportResponse, CollectionResponse};
+use crate::repos::accessions_repo::AccessionsRepo;
+use crate::repos::collections_repo::CollectionsRepo;
+use crate::repos::emails_repo::EmailsRepo;
+use crate::repos::s3_repo::S3Repo;
+use ::entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
+use ::entity::collection::Model as CollectionModel;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use bytes::Bytes;
+use http::StatusCode;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{error, info};
+
+/// Service for managing collections.
+/// Uses dynamic traits for dependency injection
+#[derive(Clone)]
+pub struct CollectionsService {
+    pub collections_repo: Arc<dyn CollectionsRepo>,
+    pub accessions_repo: Arc<dyn AccessionsRepo>,
+    pub s3_repo: Arc<dyn S3Repo>,
+    pub emails_repo: Arc<dyn EmailsRepo>,
+    pub clock: SharedClock,
+    pub ids: SharedIdGen,
+    pub export_permits: ExportPermits,
+}
+
+impl CollectionsService {
+    /// Makes an empty collection.
+    ///
+    /// # Arguments
+    /// * `payload` - The collection's title and description, which should be validated
+    ///   before calling this method
+    /// * `viewer` - The curator making the collection
+    ///
+    /// # Returns
+    /// JSON response with the new collection or an error response
+    pub async fn create_one(
+        self,
+        payload: CreateCollectionRequest,
+        viewer: AuthenticatedUser,
+    ) -> Response {
+        info!("Creating collection {}", payload.title);
+        match self
+            .collections_repo
+            .write_one(
+                viewer.organization_id,
+                payload.title,
+                payload.description,
+                viewer.user_id,
+            )
+            .await
+        {
+            Ok(collection) => (
+                StatusCode::CREATED,
+                Json(CollectionResponse::new(collection, vec![])),
+            )
+                .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred writing collection");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Retrieves a collection with the IDs of the accessions in it.
+    ///
+    /// # Arguments
+    /// * `collection_id` - The ID of the collection
+    /// * `viewer` - The curator asking, who must be able to work on the collection's
+    ///   organization
+    ///
+    /// # Returns
+    /// JSON response containing the collection or an error response
+    pub async fn get_one(self, collection_id: i32, viewer: AuthenticatedUser) -> Response {
+        info!("Getting collection with id {collection_id}");
+        let collection = match self.find_one_or_respond(collection_id, &viewer).await {
+            Ok(collection) => collection,
+            Err(response) => return response,
+        };
+        match self.collections_repo.list_accessions(collection_id).await {
+            Ok(accessions) => Json(CollectionResponse::new(
+                collection,
+                accessions.iter().map(|accession| accession.id).collect(),
+            ))
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred listing accessions in collection");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Adds an accession to a collection of the same organization.
+    ///
+    /// # Arguments
+    /// * `collection_id` - The ID of the collection
+    /// * `accession_id` - The ID of the accession to add
+    /// * `viewer` - The curator adding it
+    ///
+    /// # Returns
+    /// Returns a success status or an error response.
+    pub async fn add_accession(
+        self,
+        collection_id: i32,
+        accession_id: i32,
+        viewer: AuthenticatedUser,
+    ) -> Response {
+        info!("Adding accession {accession_id} to collection {collection_id}");
+        let collection = match self.find_one_or_respond(collection_id, &viewer).await {
+            Ok(collection) => collection,
+            Err(response) => return response,
+        };
+        let accession = match self.accessions_repo.get_one(accession_id, true).await {
+            Ok(Some(accession)) => accession,
+            Ok(None) => return (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred retrieving accession");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        };
+        if accession.organization_id != collection.organization_id {
+            return (
+                StatusCode::BAD_REQUEST,
+                "Accession belongs to another organization",
+            )
+                .into_response();
+        }
+        match self
+            .collections_repo
+            .add_accession(collection_id, accession.id)
+            .await
+        {
+            Ok(()) => (StatusCode::OK, "Accession added to collection").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred adding accession to collection");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Takes an accession out of a collection.
+    ///
+    /// # Arguments
+    /// * `collection_id` - The ID of the collection
+    /// * `accession_id` - The ID of the accession to take out
+    /// * `viewer` - The curator taking it out
+    ///
+    /// # Returns
+    /// Returns a success status or an error response.
+    pub async fn remove_accession(
+        self,
+        collection_id: i32,
+        accession_id: i32,
+        viewer: AuthenticatedUser,
+    ) -> Response {
+        info!("Removing accession {accession_id} from collection {collection_id}");
+        if let Err(response) = self.find_one_or_respond(collection_id, &viewer).await {
+            return response;
+        }
+        match self
+            .collections_repo
+            .remove_accession(collection_id, accession_id)
+            .await
+        {
+            Ok(Some(())) => (StatusCode::OK, "Accession removed from collection").into_response(),
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred removing accession from collection");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Starts exporting the public accessions of a collection as one ZIP on a background
+    /// task, see [`crate::collection_export`]. The viewer is emailed a link to the ZIP once
+    /// it is ready, or told the export failed.
+    ///
+    /// # Arguments
+    /// * `collection_id` - The ID of the collection
+    /// * `viewer` - The curator asking for the export
+    ///
+    /// # Returns
+    /// Accepted with what will be exported, or an error response if the collection has no
+    /// public accessions or its files are too large to export together
+    pub async fn start_export(self, collection_id: i32, viewer: AuthenticatedUser) -> Response {
+        info!("Exporting collection with id {collection_id}");
+        let collection = match self.find_one_or_respond(collection_id, &viewer).await {
+            Ok(collection) => collection,
+            Err(response) => return response,
+        };
+        let accessions = match self.collections_repo.list_accessions(collection_id).await {
+            Ok(accessions) => accessions,
+            Err(err) => {
+                error!(%err, "Error occurred listing accessions in collection");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        };
+        let (public, private): (Vec<_>, Vec<_>) = accessions
+            .into_iter()
+            .partition(|accession| !accession.is_private);
+        if public.is_empty() {
+            return (
+                StatusCode::BAD_REQUEST,
+                "Collection has no public accessions to export",
+            )
+                .into_response();
+        }
+        let mut total_size = 0;
+        for key in public
+            .iter()
+            .filter_map(|accession| accession.s3_filename.as_deref())
+        {
+            match self.s3_repo.get_object_size(key).await {
+                Ok(size) => total_size += size,
+                Err(err) => {
+                    error!(%err, "Error occurred getting size of {key} to export");
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Error occurred reading collection files",
+                    )
+                        .into_response();
+                }
+            }
+        }
+        if total_size > MAX_EXPORT_BYTES {
+            return (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "Collection is too large to export",
+            )
+                .into_response();
+        }
+        let response = CollectionExportResponse {
+            collection_id,
+            accessions: public.len(),
+            left_out: private.len(),
+            email: viewer.user_id.clone(),
+        };
+        tokio::spawn(self.run_export(collection, public, private.len(), viewer.user_id));
+        (StatusCode::ACCEPTED, Json(response)).into_response()
+    }
+
+    /// Builds and stores a collection's export, then emails the curator who asked for it a
+    /// link to it, or that it failed. Waits its turn if other exports are running, and the
+    /// email goes out through the outbox like every other notification.
+    ///
+    /// # Arguments
+    /// * `collection` - The collection being exported
+    /// * `accessions` - Its public accessions
+    /// * `left_out` - How many private accessions it has
+    /// * `requested_by` - Email of the curator who asked for the export
+    async fn run_export(
+        self,
+        collection: CollectionModel,
+        accessions: Vec<AccessionWithMetadataModel>,
+        left_out: usize,
+        requested_by: String,
+    ) {
+        let permits = self.export_permits.clone();
+        let Ok(_permit) = permits.acquire().await else {
+            error!("Export permits were closed");
+            return;
+        };
+        let (subject, body) = match self.export(&collection, &accessions, &requested_by).await {
+            Ok(url) => {
+                info!(
+                    "Exported {} accessions of collection {}",
+                    accessions.len(),
+                    collection.id
+                );
+                let email = ExportReadyEmail {
+                    title: collection.title.clone(),
+                    url,
+                    exported: accessions.len(),
+                    left_out,
+                };
+                (email.subject(), email.html_body())
+            }
+            Err(reason) => {
+                error!("Export of collection {} failed: {reason}", collection.id);
+                let email = ExportFailedEmail {
+                    title: collection.title.clone(),
+                };
+                (email.subject(), email.html_body())
+            }
+        };
+        if let Err(err) = self
+            .emails_repo
+            .send_email(requested_by.clone(), subject, body)
+            .await
+        {
+            error!(
+                %err,
+                "Could not email {requested_by} about the export of collection {}",
+                collection.id
+            );
+        }
+    }
+
+    /// Puts a collection's ZIP together in a temporary directory and stores it in S3.
+    ///
+    /// # Returns
+    /// A presigned link to the ZIP
+    pub(crate) async fn export(
+        &self,
+        collection: &CollectionModel,
+        accessions: &[AccessionWithMetadataModel],
+        generated_by: &str,
+    ) -> Result<String, &'static str> {
+        let dir = tempfile::tempdir().map_err(|err| {
+            error!(%err, "Error occurred making a directory to export into");
+            "Error occurred reading collection files"
+        })?;
+        let mut files = vec![];
+        let mut exported = vec![];
+        for accession in accessions {
+            let file = match &accession.s3_filename {
+                Some(key) => Some(
+                    self.download_export_file(
+                        key,
+                        capture_file_name(accession.id, key),
+                        dir.path().join(accession.id.to_string()),
+                    )
+                    .await?,
+                ),
+                None => None,
+            };
+            exported.push(ExportedAccession::new(accession, file.as_ref()));
+            files.extend(file);
+        }
+        let manifest = CollectionExportManifest::new(
+            collection,
+            exported,
+            generated_by.to_string(),
+            self.clock.naive_now(),
+        );
+        let zip_path = dir.path().join("export.zip");
+        let zip_file = std::fs::File::create(&zip_path).map_err(|err| {
+            error!(%err, "Error occurred creating {}", zip_path.display());
+            "Error occurred bundling collection export"
+        })?;
+        let collection_id = collection.id;
+        tokio::task::spawn_blocking(move || bundle(&files, &manifest, zip_file))
+            .await
+            .map_err(|err| err.to_string())
+            .and_then(|bundled| bundled)
+            .map_err(|err| {
+                error!(%err, "Error occurred bundling collection {collection_id}");
+                "Error occurred bundling collection export"
+            })?;
+        let key = export_key(collection.id, self.ids.new_id());
+        self.upload_export(&key, &zip_path).await?;
+        self.s3_repo
+            .get_presigned_url(&key, EXPORT_LINK_EXPIRY_SECONDS)
+            .await
+            .map_err(|err| {
+                error!(%err, "Error occurred presigning {key}");
+                "Error occurred presigning collection export"
+            })
+    }
+
+    /// Copies a file to put in a collection export from S3 onto disk, hashing it on the way.
+    async fn download_export_file(
+        &self,
+        key: &str,
+        name: String,
+        path: PathBuf,
+    ) -> Result<ExportFile, &'static str> {
+        let read_error = |err: &dyn std::fmt::Display| {
+            error!(%err, "Error occurred reading {key} to export");
+            "Error occurred reading collection files"
+        };
+        let mut object = self
+            .s3_repo
+            .get_object_stream(key, None)
+            .await
+            .map_err(|err| read_error(&err))?;
+        let mut file = tokio::fs::File::create(&path)
+            .await
+            .map_err(|err| read_error(&err))?;
+        let mut hasher = Sha256::new();
+        while let Some(chunk) = object.body.next().await {
+            let chunk = chunk.map_err(|err| read_error(&err))?;
+            hasher.update(&chunk);
+            file.write_all(&chunk)
+                .await
+                .map_err(|err| read_error(&err))?;
+        }
+        file.flush().await.map_err(|err| read_error(&err))?;
+        Ok(ExportFile {
+            name,
+            path,
+            sha256: format!("{:x}", hasher.finalize()),
+        })
+    }
+
+    /// Uploads an export's ZIP in parts of [`EXPORT_PART_SIZE`], aborting the upload if any
+    /// part fails.
+    async fn upload_export(&self, key: &str, zip_path: &Path) -> Result<(), &'static str> {
+        let upload_id = self
+            .s3_repo
+            .initiate_multipart_upload(key, COLLECTION_EXPORT_CONTENT_TYPE)
+            .await
+            .map_err(|err| {
+                error!(%err, "Error occurred starting upload of {key}");
+                "Error occurred uploading collection export"
+            })?;
+        let uploaded = self.upload_export_parts(key, &upload_id, zip_path).await;
+        if uploaded.is_err() {
+            if let Err(err) = self.s3_repo.abort_multipart_upload(key, &upload_id).await {
+                error!(%err, "Error occurred aborting upload of {key}");
+            }
+        }
+        uploaded
+    }
+
+    async fn upload_export_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        zip_path: &Path,
+    ) -> Result<(), &'static str> {
+        let upload_error = |err: &dyn std::fmt::Display| {
+            error!(%err, "Error occurred uploading {key}");
+            "Error occurred uploading collection export"
+        };
+        let mut zip = tokio::fs::File::open(zip_path)
+            .await
+            .map_err(|err| upload_error(&err))?;
+        let mut parts = vec![];
+        for part_number in 1.. {
+            let mut part = Vec::with_capacity(EXPORT_PART_SIZE);
+            (&mut zip)
+                .take(EXPORT_PART_SIZE as u64)
+                .read_to_end(&mut part)
+                .await
+                .map_err(|err| upload_error(&err))?;
+            if part.is_empty() && part_number > 1 {
+                break;
+            }
+            let last = part.len() < EXPORT_PART_SIZE;
+            let uploaded = self
+                .s3_repo
+                .upload_part(key, upload_id, part_number, Bytes::from(part))
+                .await
+                .map_err(|err| upload_error(&err))?;
+            parts.push(uploaded);
+            if last {
+                break;
+            }
+        }
+        self.s3_repo
+            .complete_multipart_upload(key, upload_id, parts)
+            .await
+            .map_err(|err| upload_error(&err))?;
+        Ok(())
+    }
+
+    /// Looks up a collection the user can work on, mapping failures to error responses.
+    /// Collections of other organizations are reported as missing.
+    async fn find_one_or_respond(
+        &self,
+        collection_id: i32,
+        user: &AuthenticatedUser,
+    ) -> Result<CollectionModel, Response> {
+        match self.collections_repo.get_one(collection_id).await {
+            Err(err) => {
+                error!(%err, "Error occurred retrieving collection");
+                Err((StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response())
+            }
+            Ok(Some(collection)) if user.can_access_organization(collection.organization_id) => {
+                Ok(collection)
+            }
+            Ok(_) => Err((StatusCode::NOT_FOUND, "No such record").into_response()),
+        }
+    }
+}
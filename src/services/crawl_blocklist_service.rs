@@ -0,0 +1,133 @@
+//! Service layer for managing the global crawl blocklist.
+//!
+//! This module handles the business logic for adding and removing blocked URL patterns and
+//! for turning away crawl requests for URLs they match, see [`crate::crawl_blocklist`].
+
+use crate::crawl_blocklist::{find_blocking_entry, normalize_pattern};
+use crate::models::request::CreateCrawlBlocklistEntryRequest;
+use crate::models::response::{CrawlBlocklistEntryResponse, ListCrawlBlocklistResponse};
+use crate::repos::crawl_blocklist_repo::CrawlBlocklistRepo;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use http::StatusCode;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// Service for managing the global crawl blocklist.
+/// Uses dynamic traits for dependency injection
+#[derive(Clone)]
+pub struct CrawlBlocklistService {
+    pub crawl_blocklist_repo: Arc<dyn CrawlBlocklistRepo>,
+}
+
+impl CrawlBlocklistService {
+    /// Adds a URL pattern to the blocklist.
+    ///
+    /// # Arguments
+    /// * `payload` - The creation request containing the pattern and why it is blocked
+    ///
+    /// # Returns
+    /// Returns a JSON response with the created entry or an error response
+    pub async fn create_one(self, payload: CreateCrawlBlocklistEntryRequest) -> Response {
+        let Some(pattern) = normalize_pattern(&payload.pattern) else {
+            return (StatusCode::BAD_REQUEST, "Invalid blocklist pattern").into_response();
+        };
+        info!("Adding {pattern} to the crawl blocklist...");
+        match self
+            .crawl_blocklist_repo
+            .write_one(pattern.clone(), payload.reason)
+            .await
+        {
+            Err(write_error) => {
+                if write_error
+                    .to_string()
+                    .contains("duplicate key value violates unique constraint")
+                {
+                    warn!(%write_error, "Can't add {pattern} to the crawl blocklist since it is already on it");
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        format!("Pattern {pattern} is already blocked"),
+                    )
+                        .into_response();
+                }
+                error!(%write_error, "Error occurred writing crawl blocklist entry");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+            Ok(new_entry) => (
+                StatusCode::CREATED,
+                Json(CrawlBlocklistEntryResponse::from(new_entry)),
+            )
+                .into_response(),
+        }
+    }
+
+    /// Lists the whole blocklist.
+    ///
+    /// # Returns
+    /// Returns a JSON response containing every entry or an error response
+    pub async fn list(self) -> Response {
+        info!("Getting crawl blocklist...");
+        match self.crawl_blocklist_repo.list().await {
+            Ok(rows) => Json(ListCrawlBlocklistResponse {
+                items: rows.into_iter().map(Into::into).collect(),
+            })
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred listing crawl blocklist");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Removes a URL pattern from the blocklist.
+    ///
+    /// # Arguments
+    /// * `entry_id` - The ID of the entry to remove
+    ///
+    /// # Returns
+    /// Returns a success status or an error response.
+    pub async fn delete_one(self, entry_id: i32) -> Response {
+        info!("Deleting crawl blocklist entry with id {entry_id}...");
+        match self.crawl_blocklist_repo.delete_one(entry_id).await {
+            Ok(Some(())) => (StatusCode::OK, "Crawl blocklist entry deleted").into_response(),
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred deleting crawl blocklist entry");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Checks a URL against the blocklist before it is crawled.
+    ///
+    /// Fails closed: if the blocklist can't be read, the crawl is turned away too.
+    ///
+    /// # Arguments
+    /// * `url` - The URL about to be crawled
+    ///
+    /// # Returns
+    /// A response rejecting the crawl if the URL is blocked, `None` if it may be crawled
+    pub async fn reject_blocked_url(self, url: &str) -> Option<Response> {
+        let entries = match self.crawl_blocklist_repo.list().await {
+            Ok(entries) => entries,
+            Err(err) => {
+                error!(%err, "Error occurred listing crawl blocklist");
+                return Some(
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response(),
+                );
+            }
+        };
+        let entry = find_blocking_entry(&entries, url)?;
+        warn!(
+            "Refusing to crawl {url} since it matches blocked pattern {}",
+            entry.pattern
+        );
+        Some(
+            (
+                StatusCode::BAD_REQUEST,
+                format!("URL is on the crawl blocklist: {}", entry.reason),
+            )
+                .into_response(),
+        )
+    }
+}
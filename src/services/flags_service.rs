@@ -0,0 +1,305 @@
+//! Service layer for feature flags.
+//!
+//! Besides the admin CRUD operations this provides [`FlagsService::is_enabled`] and
+//! [`FlagsService::require_enabled`] for gating handlers and services behind a flag.
+//! Flags are checked on hot paths so they are cached in memory for
+//! [`FLAGS_CACHE_TTL`]; writes through this service clear the cache straight away,
+//! other instances pick changes up once their cache expires.
+
+use crate::models::request::{CreateFeatureFlagRequest, UpdateFeatureFlagRequest};
+use crate::models::response::{
+    EnabledFeatureFlagsResponse, FeatureFlagResponse, ListFeatureFlagsResponse,
+};
+use crate::repos::feature_flags_repo::FeatureFlagsRepo;
+use ::entity::feature_flag::Model as FeatureFlagModel;
+use ::entity::sea_orm_active_enums::Role;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use http::StatusCode;
+use sea_orm::ActiveEnum;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+/// How long flags are cached before being reloaded from the database.
+pub const FLAGS_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Feature flags keyed by name, along with when they were loaded.
+#[derive(Debug, Clone)]
+pub struct CachedFlags {
+    loaded_at: Instant,
+    flags: Arc<HashMap<String, FeatureFlagModel>>,
+}
+
+/// In-memory cache of every feature flag, empty until first used.
+pub type FeatureFlagsCache = Arc<Mutex<Option<CachedFlags>>>;
+
+/// Creates an empty [`FeatureFlagsCache`].
+pub fn new_feature_flags_cache() -> FeatureFlagsCache {
+    Arc::new(Mutex::new(None))
+}
+
+/// Service for managing and checking feature flags.
+/// Uses dynamic traits for dependency injection
+#[derive(Clone)]
+pub struct FlagsService {
+    pub feature_flags_repo: Arc<dyn FeatureFlagsRepo>,
+    /// Name of the environment this instance runs in, e.g. `production`
+    pub environment: String,
+    pub flags_cache: FeatureFlagsCache,
+}
+
+/// Whether a flag is on for a caller in an environment.
+///
+/// Empty environment or role lists match everything. Anonymous callers only match flags
+/// with no roles.
+fn flag_applies(flag: &FeatureFlagModel, environment: &str, role: Option<&Role>) -> bool {
+    let environment_matches =
+        flag.environments.is_empty() || flag.environments.iter().any(|e| e == environment);
+    let role_matches =
+        flag.roles.is_empty() || role.is_some_and(|role| flag.roles.contains(&role.to_value()));
+    flag.enabled && environment_matches && role_matches
+}
+
+impl FlagsService {
+    /// Checks whether a feature flag is on for a caller.
+    ///
+    /// Unknown flags are off, as are all flags if they can't be loaded, so gated features
+    /// fail closed.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the flag
+    /// * `role` - The caller's role, or None for anonymous callers
+    pub async fn is_enabled(&self, name: &str, role: Option<&Role>) -> bool {
+        match self.load_flags().await {
+            Ok(flags) => flags
+                .get(name)
+                .is_some_and(|flag| flag_applies(flag, &self.environment, role)),
+            Err(err) => {
+                error!(%err, "Error occurred loading feature flags, treating {name} as off");
+                false
+            }
+        }
+    }
+
+    /// Gates a handler behind a feature flag.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the flag
+    /// * `role` - The caller's role, or None for anonymous callers
+    ///
+    /// # Returns
+    /// Returns a 404 response to send back when the flag is off, so gated features look
+    /// like they don't exist yet
+    pub async fn require_enabled(&self, name: &str, role: Option<&Role>) -> Result<(), Response> {
+        if self.is_enabled(name, role).await {
+            Ok(())
+        } else {
+            Err((StatusCode::NOT_FOUND, "Not found").into_response())
+        }
+    }
+
+    async fn load_flags(&self) -> Result<Arc<HashMap<String, FeatureFlagModel>>, String> {
+        if let Some(cached) = self.flags_cache.lock().map_err(|e| e.to_string())?.as_ref() {
+            if cached.loaded_at.elapsed() < FLAGS_CACHE_TTL {
+                return Ok(cached.flags.clone());
+            }
+        }
+        let flags: HashMap<String, FeatureFlagModel> = self
+            .feature_flags_repo
+            .list()
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|flag| (flag.name.clone(), flag))
+            .collect();
+        let flags = Arc::new(flags);
+        *self.flags_cache.lock().map_err(|e| e.to_string())? = Some(CachedFlags {
+            loaded_at: Instant::now(),
+            flags: flags.clone(),
+        });
+        Ok(flags)
+    }
+
+    /// Lists the names of the flags that are on for a caller, so the frontend can toggle UI.
+    ///
+    /// # Arguments
+    /// * `role` - The caller's role
+    ///
+    /// # Returns
+    /// Returns a JSON response containing the enabled flag names or an error response
+    pub async fn list_enabled(self, role: Role) -> Response {
+        match self.load_flags().await {
+            Ok(flags) => {
+                let mut names: Vec<String> = flags
+                    .values()
+                    .filter(|flag| flag_applies(flag, &self.environment, Some(&role)))
+                    .map(|flag| flag.name.clone())
+                    .collect();
+                names.sort();
+                Json(EnabledFeatureFlagsResponse { names }).into_response()
+            }
+            Err(err) => {
+                error!(%err, "Error occurred loading feature flags");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    fn clear_cache(&self) {
+        match self.flags_cache.lock() {
+            Ok(mut cache) => *cache = None,
+            Err(err) => error!(%err, "Could not clear feature flags cache"),
+        }
+    }
+
+    /// Creates a new feature flag.
+    ///
+    /// # Arguments
+    /// * `payload` - The flag's name and settings
+    ///
+    /// # Returns
+    /// Returns a JSON response with the created flag or an error response
+    pub async fn create_one(self, payload: CreateFeatureFlagRequest) -> Response {
+        info!("Creating new feature flag {}...", payload.name);
+        let name = payload.name.clone();
+        match self.feature_flags_repo.write_one(payload).await {
+            Err(write_error) => {
+                if write_error
+                    .to_string()
+                    .contains("duplicate key value violates unique constraint")
+                {
+                    warn!(%write_error, "Can't write feature flag {name} since it already exists");
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        format!("Feature flag {name} already exists"),
+                    )
+                        .into_response();
+                }
+                error!(%write_error, "Error occurred writing feature flag");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+            Ok(new_flag) => {
+                self.clear_cache();
+                (
+                    StatusCode::CREATED,
+                    Json(FeatureFlagResponse::from(new_flag)),
+                )
+                    .into_response()
+            }
+        }
+    }
+
+    /// Lists all feature flags.
+    ///
+    /// # Returns
+    /// Returns a JSON response containing every flag or an error response
+    pub async fn list(self) -> Response {
+        info!("Getting feature flags...");
+        match self.feature_flags_repo.list().await {
+            Ok(rows) => Json(ListFeatureFlagsResponse {
+                items: rows.into_iter().map(Into::into).collect(),
+            })
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred listing feature flags");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Replaces the settings of a feature flag.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the flag to update
+    /// * `payload` - The flag's new settings
+    ///
+    /// # Returns
+    /// Returns a JSON response with the updated flag or an error response
+    pub async fn update_one(self, name: String, payload: UpdateFeatureFlagRequest) -> Response {
+        info!("Updating feature flag {name}...");
+        match self.feature_flags_repo.update_one(&name, payload).await {
+            Ok(Some(flag)) => {
+                self.clear_cache();
+                Json(FeatureFlagResponse::from(flag)).into_response()
+            }
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred updating feature flag");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Deletes a feature flag, turning off anything gated behind it.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the flag to delete
+    ///
+    /// # Returns
+    /// Returns a success status or an error response.
+    pub async fn delete_one(self, name: String) -> Response {
+        info!("Deleting feature flag {name}...");
+        match self.feature_flags_repo.delete_one(&name).await {
+            Ok(Some(())) => {
+                self.clear_cache();
+                (StatusCode::OK, "Feature flag deleted").into_response()
+            }
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred deleting feature flag");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::{build_test_flags_service, mock_one_feature_flag};
+
+    #[test]
+    fn applies_flags_per_environment_and_role() {
+        let flag = FeatureFlagModel {
+            environments: vec!["staging".to_string()],
+            roles: vec!["admin".to_string()],
+            ..mock_one_feature_flag()
+        };
+        assert!(flag_applies(&flag, "staging", Some(&Role::Admin)));
+        assert!(!flag_applies(&flag, "production", Some(&Role::Admin)));
+        assert!(!flag_applies(&flag, "staging", Some(&Role::Researcher)));
+        assert!(!flag_applies(&flag, "staging", None));
+    }
+
+    #[test]
+    fn unrestricted_flags_apply_to_everyone() {
+        let flag = mock_one_feature_flag();
+        assert!(flag_applies(&flag, "production", None));
+        assert!(!flag_applies(
+            &FeatureFlagModel {
+                enabled: false,
+                ..flag
+            },
+            "production",
+            Some(&Role::Admin)
+        ));
+    }
+
+    #[tokio::test]
+    async fn checks_cached_flags() {
+        let flags_service = build_test_flags_service();
+        assert!(flags_service.is_enabled("wacz-full-text", None).await);
+        assert!(flags_service.flags_cache.lock().unwrap().is_some());
+        assert!(
+            !flags_service
+                .is_enabled("unknown", Some(&Role::Admin))
+                .await
+        );
+        assert!(flags_service
+            .require_enabled("unknown", None)
+            .await
+            .is_err());
+    }
+}
@@ -1,3 +1,11 @@
 pub mod accessions_service;
+pub mod audit_log_service;
 pub mod auth_service;
+pub mod collections_service;
+pub mod crawl_blocklist_service;
+pub mod flags_service;
+pub mod organizations_service;
+pub mod stats_service;
 pub mod subjects_service;
+pub mod uploads_service;
+pub mod workflow_labels_service;
@@ -0,0 +1,138 @@
+//! Service layer for managing organizations.
+//!
+//! This module handles the business logic for creating and renaming the organizations
+//! partner archives work in, and for moving users between them.
+
+use crate::models::request::{CreateOrganizationRequest, RenameOrganizationRequest};
+use crate::models::response::{ListOrganizationsResponse, OrganizationResponse};
+use crate::repos::organizations_repo::OrganizationsRepo;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use http::StatusCode;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Service for managing organizations.
+/// Uses dynamic traits for dependency injection
+#[derive(Clone)]
+pub struct OrganizationsService {
+    pub organizations_repo: Arc<dyn OrganizationsRepo>,
+}
+
+impl OrganizationsService {
+    /// Lists every organization.
+    ///
+    /// # Returns
+    /// Returns a JSON response containing every organization or an error response
+    pub async fn list(self) -> Response {
+        info!("Getting organizations...");
+        match self.organizations_repo.list().await {
+            Ok(rows) => Json(ListOrganizationsResponse {
+                items: rows.into_iter().map(Into::into).collect(),
+            })
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred listing organizations");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Creates a new organization.
+    ///
+    /// # Arguments
+    /// * `payload` - The creation request containing the slug and name
+    ///
+    /// # Returns
+    /// Returns a JSON response with the created organization or an error response
+    pub async fn create_one(self, payload: CreateOrganizationRequest) -> Response {
+        info!("Creating new organization {}...", payload.slug);
+        match self
+            .organizations_repo
+            .write_one(payload.slug.clone(), payload.name)
+            .await
+        {
+            Err(write_error) => {
+                if write_error
+                    .to_string()
+                    .contains("duplicate key value violates unique constraint")
+                {
+                    warn!(%write_error, "Can't write organization {} since it already exists", payload.slug);
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        format!("Organization {} already exists", payload.slug),
+                    )
+                        .into_response();
+                }
+                error!(%write_error, "Error occurred writing organization");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+            Ok(organization) => (
+                StatusCode::CREATED,
+                Json(OrganizationResponse::from(organization)),
+            )
+                .into_response(),
+        }
+    }
+
+    /// Renames an organization.
+    ///
+    /// # Arguments
+    /// * `organization_id` - The organization to rename
+    /// * `payload` - The request containing its new name
+    ///
+    /// # Returns
+    /// Returns a JSON response with the renamed organization or an error response
+    pub async fn rename(
+        self,
+        organization_id: i32,
+        payload: RenameOrganizationRequest,
+    ) -> Response {
+        info!("Renaming organization with id {organization_id}...");
+        match self
+            .organizations_repo
+            .rename(organization_id, payload.name)
+            .await
+        {
+            Ok(Some(organization)) => {
+                Json(OrganizationResponse::from(organization)).into_response()
+            }
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred renaming organization");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Moves a user into an organization.
+    ///
+    /// Users keep their role. Sessions carry the organization, so the move takes effect the
+    /// next time the user signs in; API keys pick it up straight away.
+    ///
+    /// # Arguments
+    /// * `organization_id` - The organization to move the user to
+    /// * `user_id` - The user to move
+    ///
+    /// # Returns
+    /// Returns a success status or an error response
+    pub async fn assign_user(self, organization_id: i32, user_id: Uuid) -> Response {
+        info!("Moving user {user_id} to organization with id {organization_id}...");
+        match self
+            .organizations_repo
+            .assign_user(user_id, organization_id)
+            .await
+        {
+            Ok(Some(())) => (StatusCode::OK, "User moved").into_response(),
+            Ok(None) => (StatusCode::NOT_FOUND, "User not found").into_response(),
+            Err(err) => {
+                if err.to_string().contains("violates foreign key constraint") {
+                    return (StatusCode::NOT_FOUND, "Organization not found").into_response();
+                }
+                error!(%err, "Error occurred moving user to organization");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+}
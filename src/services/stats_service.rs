@@ -0,0 +1,112 @@
+//! Service layer for the public statistics widget.
+//!
+//! The public site shows a counter of what the archive holds. Summing up the storage used
+//! means listing every object in the bucket, so the totals are cached in memory for
+//! [`PUBLIC_STATS_CACHE_TTL`] rather than worked out on every page view.
+
+use crate::models::response::PublicStatsResponse;
+use crate::repos::accessions_repo::AccessionsRepo;
+use crate::repos::s3_repo::S3Repo;
+use crate::repos::subjects_repo::SubjectsRepo;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use http::header::CACHE_CONTROL;
+use http::StatusCode;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+/// How long the totals are cached before being counted again.
+pub const PUBLIC_STATS_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// How long browsers and CDNs may cache the totals, matching the server side cache.
+const PUBLIC_STATS_CACHE_CONTROL: &str = "public, max-age=3600";
+
+/// The archive's totals, along with when they were counted.
+#[derive(Debug, Clone)]
+pub struct CachedStats {
+    loaded_at: Instant,
+    stats: PublicStatsResponse,
+}
+
+/// In-memory cache of the archive's totals, empty until first asked for.
+pub type PublicStatsCache = Arc<Mutex<Option<CachedStats>>>;
+
+/// Creates an empty [`PublicStatsCache`].
+pub fn new_public_stats_cache() -> PublicStatsCache {
+    Arc::new(Mutex::new(None))
+}
+
+/// Service for the public statistics widget.
+/// Uses dynamic traits for dependency injection
+#[derive(Clone)]
+pub struct StatsService {
+    pub accessions_repo: Arc<dyn AccessionsRepo>,
+    pub subjects_repo: Arc<dyn SubjectsRepo>,
+    pub s3_repo: Arc<dyn S3Repo>,
+    pub stats_cache: PublicStatsCache,
+}
+
+impl StatsService {
+    /// Gets the archive's totals for the public site's counter.
+    ///
+    /// # Returns
+    /// JSON response containing the totals, cacheable for an hour, or an error response
+    pub async fn public_stats(self) -> Response {
+        match self.load_stats().await {
+            Ok(stats) => {
+                ([(CACHE_CONTROL, PUBLIC_STATS_CACHE_CONTROL)], Json(stats)).into_response()
+            }
+            Err(err) => {
+                error!(%err, "Error occurred counting public stats");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+            }
+        }
+    }
+
+    async fn load_stats(&self) -> Result<PublicStatsResponse, String> {
+        if let Some(cached) = self.stats_cache.lock().map_err(|e| e.to_string())?.as_ref() {
+            if cached.loaded_at.elapsed() < PUBLIC_STATS_CACHE_TTL {
+                return Ok(cached.stats.clone());
+            }
+        }
+        info!("Counting public stats...");
+        let stats = PublicStatsResponse {
+            public_accessions: self
+                .accessions_repo
+                .count_public()
+                .await
+                .map_err(|e| e.to_string())?,
+            subjects: self
+                .subjects_repo
+                .count_all()
+                .await
+                .map_err(|e| e.to_string())?,
+            storage_bytes: self
+                .s3_repo
+                .get_bucket_size()
+                .await
+                .map_err(|e| e.to_string())?,
+        };
+        *self.stats_cache.lock().map_err(|e| e.to_string())? = Some(CachedStats {
+            loaded_at: Instant::now(),
+            stats: stats.clone(),
+        });
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_tools::build_test_stats_service;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn caches_stats() {
+        let stats_service = build_test_stats_service();
+        let stats = stats_service.load_stats().await.unwrap();
+        assert_eq!(stats.public_accessions, 1);
+        let cached = stats_service.stats_cache.lock().unwrap().clone().unwrap();
+        assert_eq!(cached.stats, stats);
+    }
+}
@@ -4,9 +4,15 @@
 //! that are used to categorize archival records in both Arabic and English.
 
 use crate::models::common::MetadataLanguage;
-use crate::models::request::CreateSubjectRequest;
-use crate::models::response::{ListSubjectsArResponse, ListSubjectsEnResponse};
+use crate::models::request::{ApplySubjectRequest, CreateSubjectRequest, SubjectPagination};
+use crate::models::response::{
+    ApplySubjectResponse, ListSubjectsArResponse, ListSubjectsEnResponse, SubjectSuggestions,
+    SuggestedSubjectsResponse,
+};
+use crate::repos::entity_extractor_repo::EntityExtractorRepo;
 use crate::repos::subjects_repo::SubjectsRepo;
+use crate::subject_suggestions::{candidate_terms, split_suggestions};
+use ::entity::accessions_with_metadata::Model as AccessionWithMetadataModel;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use http::StatusCode;
@@ -14,11 +20,17 @@ use sea_orm::DbErr;
 use std::sync::Arc;
 use tracing::{error, info, warn};
 
+/// Most accessions a subject can be attached to at once, so a loose filter can't rewrite a
+/// big part of the archive in one go.
+const MAX_BULK_TAGGED: usize = 5_000;
+
 /// Service for managing metadata subjects in multiple languages.
 /// Uses dynamic traits for dependency injection
 #[derive(Clone)]
 pub struct SubjectsService {
     pub subjects_repo: Arc<dyn SubjectsRepo>,
+    /// Finds entities to suggest as subjects, `None` when no NER service is configured
+    pub entity_extractor_repo: Option<Arc<dyn EntityExtractorRepo>>,
 }
 
 impl SubjectsService {
@@ -26,15 +38,25 @@ impl SubjectsService {
     ///
     /// # Arguments
     /// * `payload` - The creation request containing subject text and language
+    /// * `organization_id` - The organization the subject belongs to
+    /// * `created_by` - Email of the user adding the subject
     ///
     /// # Returns
     /// Returns a JSON response with the created subject or an error response
-    pub async fn create_one(self, payload: CreateSubjectRequest) -> Response {
+    pub async fn create_one(
+        self,
+        payload: CreateSubjectRequest,
+        organization_id: i32,
+        created_by: String,
+    ) -> Response {
         info!(
             "Creating new {} subject {}...",
             payload.lang, payload.metadata_subject
         );
-        let write_result = self.subjects_repo.write_one(payload.clone()).await;
+        let write_result = self
+            .subjects_repo
+            .write_one(payload.clone(), organization_id, Some(created_by))
+            .await;
         match write_result {
             Err(write_error) => {
                 if write_error
@@ -57,29 +79,36 @@ impl SubjectsService {
         }
     }
 
-    /// Lists paginated subjects with optional search filtering.
+    /// Lists paginated subjects with optional search filtering and sorting.
     ///
     /// # Arguments
-    /// * `page` - The page number to retrieve
-    /// * `per_page` - Number of items per page
-    /// * `metadata_language` - Language of subjects to retrieve (Arabic or English)
-    /// * `query_term` - Optional search term to filter subjects
+    /// * `pagination` - Page, language, search and sort of the subjects to list
     ///
     /// # Returns
     /// Returns a JSON response containing paginated subjects or an error response
-    pub async fn list(
-        self,
-        page: u64,
-        per_page: u64,
-        metadata_language: MetadataLanguage,
-        query_term: Option<String>,
-    ) -> Response {
+    pub async fn list(self, pagination: SubjectPagination) -> Response {
+        let SubjectPagination {
+            page,
+            per_page,
+            lang: metadata_language,
+            query_term,
+            match_mode,
+            sort,
+            organization_id,
+        } = pagination;
         info!("Getting page {page} of {metadata_language} subjects with per page {per_page}...");
         match metadata_language {
             MetadataLanguage::Arabic => {
                 match self
                     .subjects_repo
-                    .list_paginated_ar(page, per_page, query_term)
+                    .list_paginated_ar(
+                        page,
+                        per_page,
+                        query_term,
+                        match_mode,
+                        sort,
+                        organization_id,
+                    )
                     .await
                 {
                     Ok(rows) => {
@@ -101,7 +130,14 @@ impl SubjectsService {
             MetadataLanguage::English => {
                 match self
                     .subjects_repo
-                    .list_paginated_en(page, per_page, query_term)
+                    .list_paginated_en(
+                        page,
+                        per_page,
+                        query_term,
+                        match_mode,
+                        sort,
+                        organization_id,
+                    )
                     .await
                 {
                     Ok(rows) => {
@@ -128,6 +164,7 @@ impl SubjectsService {
     /// # Arguments
     /// * `metadata_subjects` - List of subject IDs to verify
     /// * `metadata_language` - Language of the subjects to check
+    /// * `organization_id` - The organization the subjects must belong to
     ///
     /// # Returns
     /// Returns true if all subjects exist, false otherwise, or a database error
@@ -135,9 +172,10 @@ impl SubjectsService {
         self,
         metadata_subjects: Vec<i32>,
         metadata_language: MetadataLanguage,
+        organization_id: i32,
     ) -> Result<bool, DbErr> {
         self.subjects_repo
-            .verify_subjects_exist(metadata_subjects, metadata_language)
+            .verify_subjects_exist(metadata_subjects, metadata_language, organization_id)
             .await
     }
 
@@ -146,6 +184,7 @@ impl SubjectsService {
     /// # Arguments
     /// * `subject_id` - The ID of the subject to delete.
     /// * `metadata_language` - Language of the subject to delete
+    /// * `organization_id` - The organization of the user deleting it
     ///
     /// # Returns
     /// Returns a success status or an error response.
@@ -153,11 +192,12 @@ impl SubjectsService {
         self,
         subject_id: i32,
         metadata_language: MetadataLanguage,
+        organization_id: i32,
     ) -> Response {
         info!("Deleting {metadata_language} subject with id {subject_id}...");
         let deletion_result = self
             .subjects_repo
-            .delete_one(subject_id, metadata_language)
+            .delete_one(subject_id, metadata_language, organization_id)
             .await;
 
         match deletion_result {
@@ -190,4 +230,178 @@ impl SubjectsService {
             }
         }
     }
+
+    /// Attaches a subject to every accession of an organization matching a filter, or only
+    /// counts them on a dry run.
+    ///
+    /// # Arguments
+    /// * `subject_id` - The subject to attach
+    /// * `payload` - Which accessions to attach it to and whether this is a dry run
+    /// * `organization_id` - The organization of the user, whose accessions are matched
+    ///
+    /// # Returns
+    /// Returns a JSON response with how many accessions matched and were tagged, or an
+    /// error response
+    pub async fn apply_to_accessions(
+        self,
+        subject_id: i32,
+        payload: ApplySubjectRequest,
+        organization_id: i32,
+    ) -> Response {
+        info!(
+            "Applying {} subject with id {subject_id} to matching accessions...",
+            payload.lang
+        );
+        let metadata_language = payload.lang;
+        let dry_run = payload.dry_run;
+        match self
+            .subjects_repo
+            .verify_subjects_exist(vec![subject_id], metadata_language, organization_id)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred verifying {metadata_language} subject");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        }
+        let (matched, untagged) = match self
+            .subjects_repo
+            .find_untagged(subject_id, payload, organization_id)
+            .await
+        {
+            Ok(found) => found,
+            Err(err) => {
+                error!(%err, "Error occurred finding accessions to tag");
+                return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error")
+                    .into_response();
+            }
+        };
+        if dry_run {
+            return Json(ApplySubjectResponse {
+                dry_run,
+                matched,
+                tagged: untagged.len() as u64,
+            })
+            .into_response();
+        }
+        if untagged.len() > MAX_BULK_TAGGED {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Can't tag more than {MAX_BULK_TAGGED} accessions at once, narrow the filter"
+                ),
+            )
+                .into_response();
+        }
+        match self
+            .subjects_repo
+            .tag_accessions(subject_id, metadata_language, untagged)
+            .await
+        {
+            Ok(tagged) => {
+                info!("Tagged {tagged} accessions with {metadata_language} subject {subject_id}");
+                Json(ApplySubjectResponse {
+                    dry_run,
+                    matched,
+                    tagged,
+                })
+                .into_response()
+            }
+            Err(err) => {
+                error!(%err, "Error occurred tagging accessions with {metadata_language} subject");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Suggests subjects for an accession from the entities named in its metadata.
+    ///
+    /// See [`crate::subject_suggestions`]. Each language is only looked at if the
+    /// accession has metadata in it.
+    ///
+    /// # Arguments
+    /// * `accession` - The accession to suggest subjects for
+    ///
+    /// # Returns
+    /// Returns a JSON response with suggestions per language or an error response
+    pub async fn suggest_for_accession(self, accession: AccessionWithMetadataModel) -> Response {
+        info!("Suggesting subjects for accession with id {}", accession.id);
+        let Some(entity_extractor_repo) = self.entity_extractor_repo.clone() else {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Subject suggestions are not configured",
+            )
+                .into_response();
+        };
+        let english = if accession.has_english_metadata {
+            self.suggest_in_language(
+                entity_extractor_repo.as_ref(),
+                MetadataLanguage::English,
+                [&accession.title_en, &accession.description_en],
+                accession.subjects_en_ids.as_deref().unwrap_or_default(),
+                accession.organization_id,
+            )
+            .await
+        } else {
+            Ok(SubjectSuggestions::default())
+        };
+        let arabic = if accession.has_arabic_metadata {
+            self.suggest_in_language(
+                entity_extractor_repo.as_ref(),
+                MetadataLanguage::Arabic,
+                [&accession.title_ar, &accession.description_ar],
+                accession.subjects_ar_ids.as_deref().unwrap_or_default(),
+                accession.organization_id,
+            )
+            .await
+        } else {
+            Ok(SubjectSuggestions::default())
+        };
+        match (english, arabic) {
+            (Ok(english), Ok(arabic)) => {
+                Json(SuggestedSubjectsResponse { english, arabic }).into_response()
+            }
+            (Err(response), _) | (_, Err(response)) => response,
+        }
+    }
+
+    /// Suggests subjects from the metadata in one language.
+    ///
+    /// # Arguments
+    /// * `texts` - The title and description
+    /// * `tagged` - IDs of subjects the accession already has
+    /// * `organization_id` - The accession's organization, whose subjects are suggested
+    async fn suggest_in_language(
+        &self,
+        entity_extractor_repo: &dyn EntityExtractorRepo,
+        metadata_language: MetadataLanguage,
+        texts: [&Option<String>; 2],
+        tagged: &[i32],
+        organization_id: i32,
+    ) -> Result<SubjectSuggestions, Response> {
+        let text = texts.into_iter().flatten().cloned().collect::<Vec<_>>();
+        let entities = entity_extractor_repo
+            .extract_entities(&text.join("\n\n"), &metadata_language.to_string())
+            .await
+            .map_err(|err| {
+                error!(%err, "Error occurred finding entities in accession metadata");
+                (StatusCode::BAD_GATEWAY, "Entity recognition service error").into_response()
+            })?;
+        let candidates = candidate_terms(&entities);
+        if candidates.is_empty() {
+            return Ok(SubjectSuggestions::default());
+        }
+        let matches = self
+            .subjects_repo
+            .find_by_names(candidates.clone(), metadata_language, organization_id)
+            .await
+            .map_err(|err| {
+                error!(%err, "Error occurred finding subjects by name");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            })?;
+        Ok(split_suggestions(candidates, matches, tagged))
+    }
 }
@@ -0,0 +1,452 @@
+//! Service layer for client-driven multipart uploads.
+//!
+//! Streaming a large file through a single request means a dropped connection restarts
+//! the whole upload. Here clients upload a file in parts instead, so only the part that
+//! failed needs to be sent again.
+
+use crate::clock::SharedIdGen;
+use crate::models::auth::AuthenticatedUser;
+use crate::models::request::{InitiateUploadRequest, PresignUploadRequest};
+use crate::models::response::{
+    CompleteUploadResponse, InitiateUploadResponse, ListUploadPartsResponse, PresignUploadResponse,
+    PresignedPartUrlResponse, UploadPartResponse,
+};
+use crate::repos::s3_repo::S3Repo;
+use crate::repos::uploads_repo::UploadsRepo;
+use ::entity::sea_orm_active_enums::{DublinMetadataFormat, Role};
+use ::entity::upload_session::Model as UploadSessionModel;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use bytes::Bytes;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// S3 rejects parts under 5MB, except for the last part of an upload.
+pub const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+
+/// How long presigned upload URLs stay valid, in seconds.
+const PRESIGNED_UPLOAD_EXPIRY_SECS: u64 = 3600;
+
+/// Returns the file extension and content type for files in the given format.
+pub fn file_type(metadata_format: &DublinMetadataFormat) -> (&'static str, &'static str) {
+    match metadata_format {
+        DublinMetadataFormat::Wacz => ("wacz", "application/wacz"),
+        DublinMetadataFormat::Jpeg => ("jpg", "image/jpeg"),
+        DublinMetadataFormat::Png => ("png", "image/png"),
+        DublinMetadataFormat::Mp4 => ("mp4", "video/mp4"),
+        // ReplayWeb.page picks its loader by extension and reads gzipped WARCs either way
+        DublinMetadataFormat::Warc => ("warc", "application/warc"),
+    }
+}
+
+/// Returns a new object key and content type for a file in the given format.
+fn new_object_key(id: Uuid, metadata_format: DublinMetadataFormat) -> (String, &'static str) {
+    let (file_ext, content_type) = file_type(&metadata_format);
+    // Use a new ID as the key so there are no filename collisions between objects in s3
+    (format!("{id}.{file_ext}"), content_type)
+}
+
+/// Checks a key has the shape of one handed out for uploads, so clients can't claim
+/// objects they didn't upload, e.g. another accession's PDF derivative.
+pub fn is_upload_key(key: &str, metadata_format: DublinMetadataFormat) -> bool {
+    let (file_ext, _) = file_type(&metadata_format);
+    key.strip_suffix(&format!(".{file_ext}"))
+        .is_some_and(|stem| Uuid::try_parse(stem).is_ok())
+}
+
+/// Service for managing client-driven multipart uploads.
+/// Uses dynamic traits for dependency injection
+#[derive(Clone)]
+pub struct UploadsService {
+    pub uploads_repo: Arc<dyn UploadsRepo>,
+    pub s3_repo: Arc<dyn S3Repo>,
+    /// Generates the keys uploaded files are stored under
+    pub ids: SharedIdGen,
+}
+
+impl UploadsService {
+    /// Starts a multipart upload for a new file.
+    ///
+    /// # Arguments
+    /// * `payload` - The request describing the file to upload
+    /// * `user` - The user starting the upload
+    ///
+    /// # Returns
+    /// JSON response with the upload ID and object key, or an error response
+    pub async fn initiate(
+        self,
+        payload: InitiateUploadRequest,
+        user: AuthenticatedUser,
+    ) -> Response {
+        let (key, content_type) = new_object_key(self.ids.new_id(), payload.metadata_format);
+        let upload_id = match self
+            .s3_repo
+            .initiate_multipart_upload(&key, content_type)
+            .await
+        {
+            Ok(upload_id) => upload_id,
+            Err(err) => {
+                error!(%err, "Failed to initiate multipart upload for key: {}", key);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to initiate upload",
+                )
+                    .into_response();
+            }
+        };
+        if let Err(err) = self
+            .uploads_repo
+            .write_one(upload_id.clone(), key.clone(), user.user_id)
+            .await
+        {
+            error!(%err, "Error occurred writing upload session to db");
+            if let Err(err) = self.s3_repo.abort_multipart_upload(&key, &upload_id).await {
+                error!(%err, "Failed to abort orphaned multipart upload {upload_id}");
+            }
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response();
+        }
+        info!("Initiated client multipart upload {upload_id} for key {key}");
+        (
+            StatusCode::CREATED,
+            Json(InitiateUploadResponse {
+                upload_id,
+                key,
+                min_part_size: MIN_PART_SIZE,
+            }),
+        )
+            .into_response()
+    }
+
+    /// Generates presigned URLs so clients can upload a file straight to S3 without
+    /// the bytes passing through the API. Files uploaded in parts get an upload session
+    /// like [`UploadsService::initiate`], so they are completed the same way.
+    ///
+    /// # Arguments
+    /// * `payload` - The request describing the file to upload
+    /// * `user` - The user starting the upload
+    ///
+    /// # Returns
+    /// JSON response with the object key and presigned URLs, or an error response
+    pub async fn presign(self, payload: PresignUploadRequest, user: AuthenticatedUser) -> Response {
+        let (key, content_type) = new_object_key(self.ids.new_id(), payload.metadata_format);
+        let Some(part_count) = payload.part_count else {
+            return match self
+                .s3_repo
+                .get_presigned_put_url(&key, content_type, PRESIGNED_UPLOAD_EXPIRY_SECS)
+                .await
+            {
+                Ok(url) => (
+                    StatusCode::CREATED,
+                    Json(PresignUploadResponse {
+                        key,
+                        upload_id: None,
+                        content_type: content_type.to_string(),
+                        url: Some(url),
+                        part_urls: vec![],
+                        expires_in: PRESIGNED_UPLOAD_EXPIRY_SECS,
+                    }),
+                )
+                    .into_response(),
+                Err(err) => {
+                    error!(%err, "Failed to presign upload for key: {}", key);
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Failed to presign upload",
+                    )
+                        .into_response()
+                }
+            };
+        };
+
+        let upload_id = match self
+            .s3_repo
+            .initiate_multipart_upload(&key, content_type)
+            .await
+        {
+            Ok(upload_id) => upload_id,
+            Err(err) => {
+                error!(%err, "Failed to initiate multipart upload for key: {}", key);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to initiate upload",
+                )
+                    .into_response();
+            }
+        };
+        let mut part_urls = Vec::with_capacity(part_count as usize);
+        for part_number in 1..=part_count {
+            let part_url = self
+                .s3_repo
+                .get_presigned_upload_part_url(
+                    &key,
+                    &upload_id,
+                    part_number,
+                    PRESIGNED_UPLOAD_EXPIRY_SECS,
+                )
+                .await
+                .map_err(|err| err.to_string());
+            match part_url {
+                Ok(url) => part_urls.push(PresignedPartUrlResponse { part_number, url }),
+                Err(err) => {
+                    error!(%err, "Failed to presign part {part_number} of upload {upload_id}");
+                    if let Err(err) = self.s3_repo.abort_multipart_upload(&key, &upload_id).await {
+                        error!(%err, "Failed to abort orphaned multipart upload {upload_id}");
+                    }
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Failed to presign upload",
+                    )
+                        .into_response();
+                }
+            }
+        }
+        if let Err(err) = self
+            .uploads_repo
+            .write_one(upload_id.clone(), key.clone(), user.user_id)
+            .await
+        {
+            error!(%err, "Error occurred writing upload session to db");
+            if let Err(err) = self.s3_repo.abort_multipart_upload(&key, &upload_id).await {
+                error!(%err, "Failed to abort orphaned multipart upload {upload_id}");
+            }
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response();
+        }
+        info!("Presigned {part_count} part multipart upload {upload_id} for key {key}");
+        (
+            StatusCode::CREATED,
+            Json(PresignUploadResponse {
+                key,
+                upload_id: Some(upload_id),
+                content_type: content_type.to_string(),
+                url: None,
+                part_urls,
+                expires_in: PRESIGNED_UPLOAD_EXPIRY_SECS,
+            }),
+        )
+            .into_response()
+    }
+
+    /// Uploads one part of a multipart upload. Uploading the same part number again
+    /// replaces the earlier part, which is how clients retry failed parts.
+    ///
+    /// # Arguments
+    /// * `upload_id` - The S3 multipart upload ID
+    /// * `part_number` - The 1-indexed part number
+    /// * `bytes` - The part contents
+    /// * `user` - The user uploading the part
+    ///
+    /// # Returns
+    /// JSON response with the part's ETag, or an error response
+    pub async fn upload_part(
+        self,
+        upload_id: String,
+        part_number: i32,
+        bytes: Bytes,
+        user: AuthenticatedUser,
+    ) -> Response {
+        let upload_session = match self.find_owned_upload(&upload_id, &user).await {
+            Ok(upload_session) => upload_session,
+            Err(err) => return err,
+        };
+        let size = bytes.len() as u64;
+        match self
+            .s3_repo
+            .upload_part(&upload_session.s3_key, &upload_id, part_number, bytes)
+            .await
+        {
+            Ok((etag, part_number)) => Json(UploadPartResponse {
+                part_number,
+                etag,
+                size,
+            })
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Failed to upload part {part_number} of upload {upload_id}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to upload file part",
+                )
+                    .into_response()
+            }
+        }
+    }
+
+    /// Lists the parts uploaded so far, so clients can work out where to resume.
+    ///
+    /// # Arguments
+    /// * `upload_id` - The S3 multipart upload ID
+    /// * `user` - The user who started the upload
+    ///
+    /// # Returns
+    /// JSON response with the uploaded parts, or an error response
+    pub async fn list_parts(self, upload_id: String, user: AuthenticatedUser) -> Response {
+        let upload_session = match self.find_owned_upload(&upload_id, &user).await {
+            Ok(upload_session) => upload_session,
+            Err(err) => return err,
+        };
+        match self
+            .s3_repo
+            .list_parts(&upload_session.s3_key, &upload_id)
+            .await
+        {
+            Ok(parts) => Json(ListUploadPartsResponse {
+                upload_id,
+                key: upload_session.s3_key,
+                parts: parts
+                    .into_iter()
+                    .map(|(etag, part_number, size)| UploadPartResponse {
+                        part_number,
+                        etag,
+                        size,
+                    })
+                    .collect(),
+            })
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Failed to list parts of upload {upload_id}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to list upload parts",
+                )
+                    .into_response()
+            }
+        }
+    }
+
+    /// Assembles the uploaded parts into the final object.
+    ///
+    /// # Arguments
+    /// * `upload_id` - The S3 multipart upload ID
+    /// * `user` - The user who started the upload
+    ///
+    /// # Returns
+    /// JSON response with the key of the uploaded object, or an error response
+    pub async fn complete(self, upload_id: String, user: AuthenticatedUser) -> Response {
+        let upload_session = match self.find_owned_upload(&upload_id, &user).await {
+            Ok(upload_session) => upload_session,
+            Err(err) => return err,
+        };
+        let key = upload_session.s3_key;
+        // S3 is the source of truth for which parts made it, so clients don't have to
+        // keep track of ETags across retries
+        let parts = match self.s3_repo.list_parts(&key, &upload_id).await {
+            Ok(parts) => parts,
+            Err(err) => {
+                error!(%err, "Failed to list parts of upload {upload_id}");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to list upload parts",
+                )
+                    .into_response();
+            }
+        };
+        if parts.is_empty() {
+            return (StatusCode::BAD_REQUEST, "Upload has no parts").into_response();
+        }
+        let parts = parts
+            .into_iter()
+            .map(|(etag, part_number, _)| (etag, part_number))
+            .collect();
+        if let Err(err) = self
+            .s3_repo
+            .complete_multipart_upload(&key, &upload_id, parts)
+            .await
+        {
+            error!(%err, "Failed to complete multipart upload {upload_id} for key: {}", key);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to complete upload",
+            )
+                .into_response();
+        }
+        if let Err(err) = self.uploads_repo.delete_one(&upload_id).await {
+            // The object is already in S3 so don't fail the request over bookkeeping
+            warn!(%err, "Failed to delete completed upload session {upload_id}");
+        }
+        info!("Completed client multipart upload {upload_id} for key {key}");
+        Json(CompleteUploadResponse { key }).into_response()
+    }
+
+    /// Aborts a multipart upload and discards its parts.
+    ///
+    /// # Arguments
+    /// * `upload_id` - The S3 multipart upload ID
+    /// * `user` - The user who started the upload
+    ///
+    /// # Returns
+    /// A success status or an error response
+    pub async fn abort(self, upload_id: String, user: AuthenticatedUser) -> Response {
+        let upload_session = match self.find_owned_upload(&upload_id, &user).await {
+            Ok(upload_session) => upload_session,
+            Err(err) => return err,
+        };
+        if let Err(err) = self
+            .s3_repo
+            .abort_multipart_upload(&upload_session.s3_key, &upload_id)
+            .await
+        {
+            error!(%err, "Failed to abort multipart upload {upload_id}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to abort upload").into_response();
+        }
+        if let Err(err) = self.uploads_repo.delete_one(&upload_id).await {
+            error!(%err, "Error occurred deleting upload session {upload_id}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response();
+        }
+        info!("Aborted client multipart upload {upload_id}");
+        (StatusCode::OK, "Upload aborted").into_response()
+    }
+
+    /// Looks up an upload session, making sure the user is allowed to touch it.
+    /// Admins can manage any upload; everyone else only their own.
+    async fn find_owned_upload(
+        &self,
+        upload_id: &str,
+        user: &AuthenticatedUser,
+    ) -> Result<UploadSessionModel, Response> {
+        match self.uploads_repo.get_one(upload_id).await {
+            Ok(Some(upload_session)) => {
+                if upload_session.created_by != user.user_id && user.role != Role::Admin {
+                    return Err(
+                        (StatusCode::FORBIDDEN, "Upload belongs to another user").into_response()
+                    );
+                }
+                Ok(upload_session)
+            }
+            Ok(None) => Err((StatusCode::NOT_FOUND, "No such upload").into_response()),
+            Err(err) => {
+                error!(%err, "Error occurred getting upload session {upload_id}");
+                Err((StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_object_keys_are_upload_keys() {
+        let (key, content_type) = new_object_key(Uuid::from_u128(1), DublinMetadataFormat::Wacz);
+        assert!(is_upload_key(&key, DublinMetadataFormat::Wacz));
+        assert_eq!(content_type, "application/wacz");
+    }
+
+    #[test]
+    fn rejects_keys_not_handed_out_for_uploads() {
+        assert!(!is_upload_key(
+            "mock-upload.wacz",
+            DublinMetadataFormat::Wacz
+        ));
+        assert!(!is_upload_key(
+            "0b7f4e0a-4cbb-4c3c-9d51-8e0f8cfb7d59.pdf",
+            DublinMetadataFormat::Wacz
+        ));
+        assert!(!is_upload_key(
+            "../0b7f4e0a-4cbb-4c3c-9d51-8e0f8cfb7d59.wacz",
+            DublinMetadataFormat::Wacz
+        ));
+    }
+}
@@ -0,0 +1,176 @@
+//! Service layer for managing internal workflow labels.
+//!
+//! This module handles the business logic for creating labels and attaching them to
+//! accessions so curators can track operational work without touching public metadata.
+
+use crate::models::request::CreateWorkflowLabelRequest;
+use crate::models::response::{ListWorkflowLabelsResponse, WorkflowLabelResponse};
+use crate::repos::workflow_labels_repo::WorkflowLabelsRepo;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use http::StatusCode;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// Service for managing internal workflow labels.
+/// Uses dynamic traits for dependency injection
+#[derive(Clone)]
+pub struct WorkflowLabelsService {
+    pub workflow_labels_repo: Arc<dyn WorkflowLabelsRepo>,
+}
+
+impl WorkflowLabelsService {
+    /// Creates a new workflow label.
+    ///
+    /// # Arguments
+    /// * `payload` - The creation request containing the label text
+    ///
+    /// # Returns
+    /// Returns a JSON response with the created label or an error response
+    pub async fn create_one(self, payload: CreateWorkflowLabelRequest) -> Response {
+        info!("Creating new workflow label {}...", payload.label);
+        match self
+            .workflow_labels_repo
+            .write_one(payload.label.clone())
+            .await
+        {
+            Err(write_error) => {
+                if write_error
+                    .to_string()
+                    .contains("duplicate key value violates unique constraint")
+                {
+                    warn!(%write_error, "Can't write workflow label {} since it already exists", payload.label);
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        format!("Workflow label {} already exists", payload.label),
+                    )
+                        .into_response();
+                }
+                error!(%write_error, "Error occurred writing workflow label");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+            Ok(new_label) => (
+                StatusCode::CREATED,
+                Json(WorkflowLabelResponse::from(new_label)),
+            )
+                .into_response(),
+        }
+    }
+
+    /// Lists all workflow labels.
+    ///
+    /// # Returns
+    /// Returns a JSON response containing every label or an error response
+    pub async fn list(self) -> Response {
+        info!("Getting workflow labels...");
+        match self.workflow_labels_repo.list().await {
+            Ok(rows) => Json(ListWorkflowLabelsResponse {
+                items: rows.into_iter().map(Into::into).collect(),
+            })
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred listing workflow labels");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Deletes a workflow label, detaching it from any accessions.
+    ///
+    /// # Arguments
+    /// * `label_id` - The ID of the label to delete
+    ///
+    /// # Returns
+    /// Returns a success status or an error response.
+    pub async fn delete_one(self, label_id: i32) -> Response {
+        info!("Deleting workflow label with id {label_id}...");
+        match self.workflow_labels_repo.delete_one(label_id).await {
+            Ok(Some(())) => (StatusCode::OK, "Workflow label deleted").into_response(),
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred deleting workflow label");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Lists the workflow labels attached to an accession.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession
+    ///
+    /// # Returns
+    /// Returns a JSON response containing the accession's labels or an error response
+    pub async fn list_for_accession(self, accession_id: i32) -> Response {
+        info!("Getting workflow labels for accession with id {accession_id}...");
+        match self
+            .workflow_labels_repo
+            .list_for_accession(accession_id)
+            .await
+        {
+            Ok(rows) => Json(ListWorkflowLabelsResponse {
+                items: rows.into_iter().map(Into::into).collect(),
+            })
+            .into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred listing workflow labels for accession");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Attaches a workflow label to an accession.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the accession to label
+    /// * `label_id` - The ID of the label to attach
+    ///
+    /// # Returns
+    /// Returns a success status or an error response.
+    pub async fn add_to_accession(self, accession_id: i32, label_id: i32) -> Response {
+        info!("Adding workflow label {label_id} to accession with id {accession_id}...");
+        match self
+            .workflow_labels_repo
+            .add_to_accession(accession_id, label_id)
+            .await
+        {
+            Ok(()) => (StatusCode::OK, "Workflow label added").into_response(),
+            Err(db_err) => {
+                if db_err
+                    .to_string()
+                    .contains("violates foreign key constraint")
+                {
+                    warn!(%db_err, "Can't label accession {accession_id} with {label_id} since one of them does not exist");
+                    return (StatusCode::NOT_FOUND, "No such accession or workflow label")
+                        .into_response();
+                }
+                error!(%db_err, "Error occurred adding workflow label to accession");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+
+    /// Detaches a workflow label from an accession.
+    ///
+    /// # Arguments
+    /// * `accession_id` - The ID of the labelled accession
+    /// * `label_id` - The ID of the label to detach
+    ///
+    /// # Returns
+    /// Returns a success status or an error response.
+    pub async fn remove_from_accession(self, accession_id: i32, label_id: i32) -> Response {
+        info!("Removing workflow label {label_id} from accession with id {accession_id}...");
+        match self
+            .workflow_labels_repo
+            .remove_from_accession(accession_id, label_id)
+            .await
+        {
+            Ok(Some(())) => (StatusCode::OK, "Workflow label removed").into_response(),
+            Ok(None) => (StatusCode::NOT_FOUND, "No such record").into_response(),
+            Err(err) => {
+                error!(%err, "Error occurred removing workflow label from accession");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal database error").into_response()
+            }
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Who posted a crawled social media post and when.
+//!
+//! A capture of a Facebook, X or Telegram post is only useful as evidence if it's known whose
+//! account posted it and when, which is tedious to read out of a replay. When an accession is
+//! crawled from a post's URL, [`identify_post`] picks the platform, handle and post ID out of
+//! the URL, and [`crate::repos::oembed_repo`] asks the platform for the rest. The result is
+//! stored in the `social_metadata` table and accessions can be filtered by platform and handle.
+//!
+//! X post IDs are [snowflakes](https://github.com/twitter-archive/snowflake) with the time
+//! they were made in them, so X posts get a timestamp even when oEmbed is unreachable.
+
+use chrono::{DateTime, NaiveDateTime};
+use entity::sea_orm_active_enums::SocialPlatform;
+use reqwest::Url;
+
+/// Milliseconds since the Unix epoch at which X's snowflake timestamps start.
+const X_SNOWFLAKE_EPOCH_MS: i64 = 1_288_834_974_657;
+
+/// First path segments of Facebook URLs that aren't a page or profile name.
+const FACEBOOK_NON_HANDLES: [&str; 6] = [
+    "permalink.php",
+    "story.php",
+    "photo.php",
+    "photo",
+    "watch",
+    "reel",
+];
+
+/// What is known about a post, from its URL and then the platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocialPost {
+    pub platform: SocialPlatform,
+    /// Handle of the account that posted, without a leading `@`
+    pub author_handle: Option<String>,
+    pub author_name: Option<String>,
+    pub post_id: Option<String>,
+    pub posted_at: Option<NaiveDateTime>,
+}
+
+/// What a platform says about a post, see [`crate::repos::oembed_repo::OembedRepo`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PostDetails {
+    pub author_handle: Option<String>,
+    pub author_name: Option<String>,
+    pub posted_at: Option<NaiveDateTime>,
+}
+
+impl SocialPost {
+    /// Fills in what the URL didn't give away from what the platform said. The URL is
+    /// trusted over the platform where both know something, since it's what was crawled.
+    pub fn with_details(self, details: PostDetails) -> Self {
+        Self {
+            author_handle: self.author_handle.or(details.author_handle),
+            author_name: self.author_name.or(details.author_name),
+            posted_at: self.posted_at.or(details.posted_at),
+            ..self
+        }
+    }
+}
+
+/// Recognises the URL of a social media post.
+///
+/// # Returns
+/// The post's platform and whatever its URL says about it, or `None` if the URL isn't on a
+/// supported platform
+pub fn identify_post(url: &str) -> Option<SocialPost> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?.to_ascii_lowercase();
+    let host = host
+        .strip_prefix("www.")
+        .or_else(|| host.strip_prefix("mobile."))
+        .or_else(|| host.strip_prefix("m."))
+        .unwrap_or(&host);
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|segment| !segment.is_empty()).collect())
+        .unwrap_or_default();
+    match host {
+        "x.com" | "twitter.com" => Some(x_post(&segments)),
+        "t.me" | "telegram.me" => Some(telegram_post(&segments)),
+        "facebook.com" | "fb.com" => Some(facebook_post(&url, &segments)),
+        _ => None,
+    }
+}
+
+/// `/<handle>/status/<id>`, possibly followed by `/photo/1` and the like.
+fn x_post(segments: &[&str]) -> SocialPost {
+    let (author_handle, post_id) = match segments {
+        [handle, "status" | "statuses", id, ..] => {
+            (Some(normalize_handle(handle)), Some(id.to_string()))
+        }
+        [handle, ..] => (Some(normalize_handle(handle)), None),
+        [] => (None, None),
+    };
+    SocialPost {
+        platform: SocialPlatform::X,
+        posted_at: post_id.as_deref().and_then(x_snowflake_time),
+        author_handle,
+        author_name: None,
+        post_id,
+    }
+}
+
+/// `/<channel>/<id>`, or `/s/<channel>/<id>` for the web preview.
+fn telegram_post(segments: &[&str]) -> SocialPost {
+    let segments = match segments {
+        ["s", rest @ ..] => rest,
+        _ => segments,
+    };
+    SocialPost {
+        platform: SocialPlatform::Telegram,
+        author_handle: segments.first().map(|channel| normalize_handle(channel)),
+        author_name: None,
+        post_id: segments.get(1).map(|id| id.to_string()),
+        posted_at: None,
+    }
+}
+
+/// `/<page>/posts/<id>`, `/groups/<group>/posts/<id>` or `/permalink.php?story_fbid=<id>`,
+/// among others.
+fn facebook_post(url: &Url, segments: &[&str]) -> SocialPost {
+    let query = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    let (author_handle, post_id) = match segments {
+        ["groups", group, kind, id, ..] if *kind == "posts" || *kind == "permalink" => {
+            (Some(normalize_handle(group)), Some(id.to_string()))
+        }
+        [first, ..] if FACEBOOK_NON_HANDLES.contains(first) => (
+            None,
+            query("story_fbid")
+                .or_else(|| query("fbid"))
+                .or_else(|| query("v")),
+        ),
+        [page, _, id, ..] => (Some(normalize_handle(page)), Some(id.to_string())),
+        [page, ..] => (Some(normalize_handle(page)), None),
+        [] => (None, None),
+    };
+    SocialPost {
+        platform: SocialPlatform::Facebook,
+        author_handle,
+        author_name: None,
+        post_id,
+        posted_at: None,
+    }
+}
+
+/// Strips the `@` people tend to type in front of handles.
+pub fn normalize_handle(handle: &str) -> String {
+    handle.trim().trim_start_matches('@').to_string()
+}
+
+/// When an X post was made, read out of its snowflake ID.
+fn x_snowflake_time(post_id: &str) -> Option<NaiveDateTime> {
+    let id: i64 = post_id.parse().ok()?;
+    DateTime::from_timestamp_millis((id >> 22) + X_SNOWFLAKE_EPOCH_MS).map(|time| time.naive_utc())
+}
+
+/// The handle in an X profile URL, as oEmbed gives in `author_url`.
+pub fn handle_from_profile_url(profile_url: &str) -> Option<String> {
+    let url = Url::parse(profile_url).ok()?;
+    url.path_segments()?
+        .find(|segment| !segment.is_empty())
+        .map(normalize_handle)
+}
+
+/// When a Telegram post was published, from the `<time datetime="...">` in its embed widget.
+pub fn telegram_posted_at(embed_html: &str) -> Option<NaiveDateTime> {
+    let (_, rest) = embed_html.split_once("<time datetime=\"")?;
+    let (datetime, _) = rest.split_once('"')?;
+    DateTime::parse_from_rfc3339(datetime)
+        .ok()
+        .map(|time| time.naive_utc())
+}
+
+/// The channel's name in a Telegram post's embed widget.
+pub fn telegram_author_name(embed_html: &str) -> Option<String> {
+    let (_, rest) = embed_html.split_once("tgme_widget_message_owner_name")?;
+    let (_, rest) = rest.split_once("<span dir=\"auto\">")?;
+    let (name, _) = rest.split_once("</span>")?;
+    Some(name.trim().to_string()).filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn identifies_x_posts_and_when_they_were_made() {
+        let post =
+            identify_post("https://twitter.com/@RSFSudan/status/1785236745296498795?s=20").unwrap();
+        assert_eq!(post.platform, SocialPlatform::X);
+        assert_eq!(post.author_handle.as_deref(), Some("RSFSudan"));
+        assert_eq!(post.post_id.as_deref(), Some("1785236745296498795"));
+        assert_eq!(
+            post.posted_at,
+            NaiveDate::from_ymd_opt(2024, 4, 30)
+                .unwrap()
+                .and_hms_milli_opt(9, 16, 22, 830)
+        );
+        let post = identify_post("https://x.com/SudanTribune").unwrap();
+        assert_eq!(post.post_id, None);
+        assert_eq!(post.posted_at, None);
+    }
+
+    #[test]
+    fn identifies_telegram_and_facebook_posts() {
+        let post = identify_post("https://t.me/s/sudanalyoum/4512").unwrap();
+        assert_eq!(post.platform, SocialPlatform::Telegram);
+        assert_eq!(post.author_handle.as_deref(), Some("sudanalyoum"));
+        assert_eq!(post.post_id.as_deref(), Some("4512"));
+
+        let post = identify_post("https://m.facebook.com/AlJazeera/posts/10161234567890").unwrap();
+        assert_eq!(post.platform, SocialPlatform::Facebook);
+        assert_eq!(post.author_handle.as_deref(), Some("AlJazeera"));
+        assert_eq!(post.post_id.as_deref(), Some("10161234567890"));
+
+        let post =
+            identify_post("https://www.facebook.com/permalink.php?story_fbid=123&id=456").unwrap();
+        assert_eq!(post.author_handle, None);
+        assert_eq!(post.post_id.as_deref(), Some("123"));
+
+        let post = identify_post("https://facebook.com/groups/sudanmissing/posts/789").unwrap();
+        assert_eq!(post.author_handle.as_deref(), Some("sudanmissing"));
+        assert_eq!(post.post_id.as_deref(), Some("789"));
+
+        assert_eq!(identify_post("https://sudantribune.com/article/1"), None);
+    }
+
+    #[test]
+    fn trusts_the_url_over_the_platform() {
+        let post = identify_post("https://t.me/sudanalyoum/4512")
+            .unwrap()
+            .with_details(PostDetails {
+                author_handle: Some("someone_else".to_string()),
+                author_name: Some("السودان اليوم".to_string()),
+                posted_at: NaiveDate::from_ymd_opt(2024, 5, 1)
+                    .unwrap()
+                    .and_hms_opt(9, 0, 0),
+            });
+        assert_eq!(post.author_handle.as_deref(), Some("sudanalyoum"));
+        assert_eq!(post.author_name.as_deref(), Some("السودان اليوم"));
+        assert!(post.posted_at.is_some());
+    }
+
+    #[test]
+    fn reads_telegram_embed_widgets() {
+        let html = r#"<div class="tgme_widget_message_author"><a class="tgme_widget_message_owner_name" href="https://t.me/sudanalyoum"><span dir="auto">Sudan Alyoum</span></a></div>
+            <a class="tgme_widget_message_date" href="https://t.me/sudanalyoum/4512"><time datetime="2024-05-01T09:00:00+03:00" class="time">09:00</time></a>"#;
+        assert_eq!(telegram_author_name(html).as_deref(), Some("Sudan Alyoum"));
+        assert_eq!(
+            telegram_posted_at(html),
+            NaiveDate::from_ymd_opt(2024, 5, 1)
+                .unwrap()
+                .and_hms_opt(6, 0, 0)
+        );
+        assert_eq!(
+            handle_from_profile_url("https://twitter.com/RSFSudan").as_deref(),
+            Some("RSFSudan")
+        );
+    }
+}
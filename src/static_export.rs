@@ -0,0 +1,231 @@
+//! Export of the public archive as a static bundle of JSON files in S3.
+//!
+//! The bundle lets a read-only mirror of the archive be served straight from a bucket or
+//! CDN if the API goes down or is blocked. It is laid out under [`STATIC_EXPORT_PREFIX`] as:
+//! - `accessions/page-{n}.json`, pages of public accessions in the same shape the public
+//!   listing endpoint returns, in ID order
+//! - `search-index.json`, a [`SearchIndex`] mapping the words of each accession's titles,
+//!   descriptions and subjects to the accessions they appear in, so a mirror can search
+//!   without a server
+//! - `manifest.json`, written last, saying when the bundle was made and how many pages it has.
+//!   Pages past that count are left over from an earlier, larger export and should be ignored.
+//!
+//! An admin starts an export, which runs on a background task; this tracks how far it has got.
+//! Only one export runs at a time and progress resets whenever the server restarts.
+
+use crate::models::response::PublicAccessionsWithMetadataResponse;
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+/// Where in S3 the bundle lives
+pub const STATIC_EXPORT_PREFIX: &str = "static-site";
+/// Accessions per page file
+pub const STATIC_EXPORT_PAGE_SIZE: u64 = 100;
+/// Words shorter than this aren't worth searching for
+const MIN_TERM_LENGTH: usize = 2;
+
+/// S3 key of a page of accessions.
+pub fn page_key(page: u64) -> String {
+    format!("{STATIC_EXPORT_PREFIX}/accessions/page-{page}.json")
+}
+
+/// S3 key of the search index.
+pub fn search_index_key() -> String {
+    format!("{STATIC_EXPORT_PREFIX}/search-index.json")
+}
+
+/// S3 key of the manifest.
+pub fn manifest_key() -> String {
+    format!("{STATIC_EXPORT_PREFIX}/manifest.json")
+}
+
+/// Describes a finished bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StaticExportManifest {
+    pub generated_at: NaiveDateTime,
+    pub pages: u64,
+    pub per_page: u64,
+    pub accessions: u64,
+}
+
+/// Enough of an accession to show a search result without loading its page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchDocument {
+    pub title_en: Option<String>,
+    pub title_ar: Option<String>,
+    /// The page file the full accession is in
+    pub page: u64,
+}
+
+/// Inverted index over the public accessions, in the spirit of Lunr or Pagefind but simple
+/// enough to load and query with a few lines of JavaScript.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SearchIndex {
+    /// Lowercased word to the IDs of the accessions it appears in
+    pub terms: BTreeMap<String, BTreeSet<i32>>,
+    /// Accession ID to its search result
+    pub documents: BTreeMap<i32, SearchDocument>,
+}
+
+impl SearchIndex {
+    /// Indexes an accession's English and Arabic metadata.
+    ///
+    /// # Arguments
+    /// * `accession` - The accession to index
+    /// * `page` - The page file it was written to
+    pub fn add(&mut self, accession: &PublicAccessionsWithMetadataResponse, page: u64) {
+        let subjects = accession.subjects.iter().map(|subject| &subject.subject);
+        let texts = [
+            &accession.title_en,
+            &accession.description_en,
+            &accession.title_ar,
+            &accession.description_ar,
+        ]
+        .into_iter()
+        .flatten()
+        .chain(subjects);
+        for text in texts {
+            for term in search_terms(text) {
+                self.terms.entry(term).or_default().insert(accession.id);
+            }
+        }
+        self.documents.insert(
+            accession.id,
+            SearchDocument {
+                title_en: accession.title_en.clone(),
+                title_ar: accession.title_ar.clone(),
+                page,
+            },
+        );
+    }
+}
+
+/// Splits text into lowercased words. Anything that isn't a letter or digit in any script
+/// separates words, so this works for Arabic as well as English.
+fn search_terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_TERM_LENGTH)
+        .map(str::to_lowercase)
+}
+
+/// Point in time view of the latest export.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StaticExportSnapshot {
+    pub running: bool,
+    /// Accessions written to page files so far
+    pub exported: u64,
+    pub pages: u64,
+    /// Why the export stopped early, if it did
+    pub error: Option<String>,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+/// Thread safe tracker for the static export, shared between requests.
+#[derive(Debug, Clone, Default)]
+pub struct StaticExportProgress {
+    inner: Arc<Mutex<StaticExportSnapshot>>,
+}
+
+impl StaticExportProgress {
+    /// Marks an export as started, unless one is already running.
+    ///
+    /// # Returns
+    /// Whether the caller should go ahead with the export
+    pub fn try_start(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.running {
+            return false;
+        }
+        *inner = StaticExportSnapshot {
+            running: true,
+            started_at: Some(Utc::now().naive_utc()),
+            ..Default::default()
+        };
+        true
+    }
+
+    /// Records a page file written to S3.
+    pub fn page_written(&self, accessions: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.pages += 1;
+        inner.exported += accessions as u64;
+    }
+
+    /// Records why the running export stopped early.
+    pub fn failed(&self, reason: &str) {
+        self.inner.lock().unwrap().error = Some(reason.to_string());
+    }
+
+    /// Marks the running export as finished.
+    pub fn finish(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.running = false;
+        inner.finished_at = Some(Utc::now().naive_utc());
+    }
+
+    /// Returns the progress of the running or most recent export.
+    pub fn snapshot(&self) -> StaticExportSnapshot {
+        self.inner.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::mock_one_accession_with_metadata;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn only_one_export_runs_at_a_time() {
+        let progress = StaticExportProgress::default();
+        assert!(progress.try_start());
+        assert!(!progress.try_start());
+        progress.page_written(3);
+        progress.failed("Error occurred uploading static export");
+        progress.finish();
+
+        let snapshot = progress.snapshot();
+        assert_eq!((snapshot.pages, snapshot.exported), (1, 3));
+        assert!(snapshot.error.is_some());
+
+        assert!(progress.try_start());
+        let snapshot = progress.snapshot();
+        assert_eq!((snapshot.pages, snapshot.exported), (0, 0));
+        assert_eq!(snapshot.error, None);
+    }
+
+    #[test]
+    fn splits_english_and_arabic_into_terms() {
+        assert_eq!(
+            search_terms("Burnt-out market, El Fasher (2024) a").collect::<Vec<_>>(),
+            vec!["burnt", "out", "market", "el", "fasher", "2024"]
+        );
+        assert_eq!(
+            search_terms("سوق الفاشر، ٢٠٢٤").collect::<Vec<_>>(),
+            vec!["سوق", "الفاشر", "٢٠٢٤"]
+        );
+    }
+
+    #[test]
+    fn indexes_titles_descriptions_and_subjects() {
+        let accession =
+            PublicAccessionsWithMetadataResponse::from(mock_one_accession_with_metadata());
+        let mut index = SearchIndex::default();
+        index.add(&accession, 4);
+
+        assert_eq!(index.terms["english"], BTreeSet::from([accession.id]));
+        assert_eq!(index.terms["archive"], BTreeSet::from([accession.id]));
+        assert_eq!(index.terms["mrhaba"], BTreeSet::from([accession.id]));
+        assert_eq!(
+            index.documents[&accession.id],
+            SearchDocument {
+                title_en: accession.title_en.clone(),
+                title_ar: accession.title_ar.clone(),
+                page: 4,
+            }
+        );
+    }
+}
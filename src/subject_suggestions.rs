@@ -0,0 +1,116 @@
+//! Suggesting subjects for an accession from the entities named in its metadata.
+//!
+//! A named entity recognition service picks out people, places, organisations and events
+//! from an accession's title and description, see
+//! [`crate::repos::entity_extractor_repo`]. Entities matching a subject we already have
+//! are suggested as that subject, and the rest as candidates for new subjects, which saves
+//! cataloguers from tagging everything by hand. Nothing is tagged until someone accepts a
+//! suggestion through the normal update path.
+
+use crate::models::response::{SubjectResponse, SubjectSuggestions};
+use crate::repos::entity_extractor_repo::NamedEntity;
+use std::collections::HashSet;
+
+/// Entity labels worth suggesting as subjects, from the spaCy and CoNLL label sets.
+///
+/// Dates, numbers and the like are left out since subjects aren't used for them.
+const SUBJECT_LABELS: [&str; 8] = ["PERSON", "PER", "ORG", "GPE", "LOC", "FAC", "EVENT", "NORP"];
+
+/// Longest subject accepted by [`crate::models::request::CreateSubjectRequest`]
+const SUBJECT_MAX_CHARS: usize = 100;
+
+/// Turns the entities found in a text into distinct subject terms, in the order found.
+pub fn candidate_terms(entities: &[NamedEntity]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    entities
+        .iter()
+        .filter(|entity| SUBJECT_LABELS.contains(&entity.label.to_uppercase().as_str()))
+        .map(|entity| {
+            entity
+                .text
+                .trim_matches(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+                .to_string()
+        })
+        .filter(|term| !term.is_empty() && term.chars().count() <= SUBJECT_MAX_CHARS)
+        .filter(|term| seen.insert(term.to_lowercase()))
+        .collect()
+}
+
+/// Splits candidate terms into existing subjects and new ones.
+///
+/// # Arguments
+/// * `candidates` - Terms from [`candidate_terms`]
+/// * `matches` - Existing subjects whose name matches a candidate, ignoring case
+/// * `tagged` - IDs of subjects the accession already has, which aren't suggested again
+pub fn split_suggestions(
+    candidates: Vec<String>,
+    matches: Vec<SubjectResponse>,
+    tagged: &[i32],
+) -> SubjectSuggestions {
+    let mut suggestions = SubjectSuggestions::default();
+    for candidate in candidates {
+        let key = candidate.to_lowercase();
+        match matches
+            .iter()
+            .find(|subject| subject.subject.to_lowercase() == key)
+        {
+            Some(subject) if tagged.contains(&subject.id) => {}
+            Some(subject) => suggestions.existing.push(subject.clone()),
+            None => suggestions.new_terms.push(candidate),
+        }
+    }
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn entity(text: &str, label: &str) -> NamedEntity {
+        NamedEntity {
+            text: text.to_string(),
+            label: label.to_string(),
+        }
+    }
+
+    #[test]
+    fn keeps_distinct_subject_like_entities() {
+        let entities = [
+            entity("Khartoum,", "GPE"),
+            entity("2023", "DATE"),
+            entity("RSF", "ORG"),
+            entity("khartoum", "gpe"),
+            entity(" ", "PERSON"),
+            entity(&"x".repeat(101), "EVENT"),
+        ];
+        assert_eq!(candidate_terms(&entities), vec!["Khartoum", "RSF"]);
+    }
+
+    #[test]
+    fn splits_existing_subjects_from_new_terms() {
+        let subject = |id, name: &str| SubjectResponse {
+            id,
+            subject: name.to_string(),
+            created_at: Default::default(),
+            updated_at: Default::default(),
+            created_by: None,
+        };
+        let suggestions = split_suggestions(
+            vec![
+                "khartoum".to_string(),
+                "Omdurman".to_string(),
+                "Darfur".to_string(),
+            ],
+            vec![subject(1, "Darfur"), subject(2, "Khartoum")],
+            &[1],
+        );
+        assert_eq!(
+            suggestions,
+            SubjectSuggestions {
+                existing: vec![subject(2, "Khartoum")],
+                new_terms: vec!["Omdurman".to_string()],
+            }
+        );
+    }
+}
@@ -2,41 +2,119 @@
 //! This module provides in-memory implementations of repositories
 //! to facilitate testing without requiring actual database or external API connections.
 
+use crate::activity_digest::CRAWL_COMPLETED;
 use crate::app_factory::{create_app, AppState};
 use crate::auth::JWT_KEYS;
-use crate::config::AppConfig;
+use crate::clock::{Clock, IdGen, SharedClock, SharedIdGen};
+use crate::collection_export::{new_export_permits, COLLECTION_EXPORTS_FLAG};
+use crate::config::{AppConfig, RouteLimits, ScanEnforcement};
+use crate::crawl_queue::new_crawl_queue;
+use crate::crawl_state_machine::LaunchedCrawl;
+use crate::leader_election::LeaderElection;
+use crate::memento::Capture;
 use crate::models::auth::JWTClaims;
-use crate::models::common::MetadataLanguage;
+use crate::models::common::{
+    CrawlerBackend, MetadataLanguage, RelationDirection, SubjectMatch, SubjectSort,
+    TimelineInterval,
+};
 use crate::models::request::{
     AccessionPaginationWithPrivate, CreateAccessionRequest, CreateAccessionRequestRaw,
-    CreateCrawlRequest,
+    CreateCrawlRequest, CreateFeatureFlagRequest, UpdateFeatureFlagRequest,
+};
+use crate::models::response::{
+    AccessionRelationResponse, CreateCrawlResponse, DerivativeResponse, SocialMetadataResponse,
 };
-use crate::models::response::CreateCrawlResponse;
-use crate::repos::accessions_repo::AccessionsRepo;
+use crate::pipeline_metrics::new_pipeline_metrics;
+use crate::provenance::{signed_message, ProvenanceSigner};
+use crate::publication_feed::PublicationFeed;
+use crate::reindex::ReindexProgress;
+use crate::repos::accession_events_repo::{AccessionEventTotal, AccessionEventsRepo, EventCount};
+use crate::repos::accession_relations_repo::{AccessionRelationsRepo, RelatedAccession};
+use crate::repos::accessions_repo::{AccessionSelection, AccessionsRepo, TimelineBucket};
+use crate::repos::activity_digest_repo::ActivityDigestRepo;
+use crate::repos::audit_log_repo::{AuditEntry, AuditLogFilter, AuditLogRepo};
 use crate::repos::auth_repo::{ApiKeyUserInfo, AuthRepo};
 use crate::repos::browsertrix_repo::BrowsertrixRepo;
+use crate::repos::collections_repo::CollectionsRepo;
+use crate::repos::comments_repo::CommentsRepo;
+use crate::repos::content_digest_repo::ContentDigestRepo;
+use crate::repos::crawl_blocklist_repo::CrawlBlocklistRepo;
+use crate::repos::crawler_repo::{CrawlArchive, CrawlerError, CrawlerRepo, Crawlers};
+use crate::repos::doi_repo::{DoiMetadata, DoiRepo};
 use crate::repos::emails_repo::EmailsRepo;
-use crate::repos::s3_repo::S3Repo;
+use crate::repos::entity_extractor_repo::{EntityExtractorRepo, NamedEntity};
+use crate::repos::feature_flags_repo::FeatureFlagsRepo;
+use crate::repos::leader_election_repo::LeaderElectionRepo;
+use crate::repos::media_transcoder_repo::MediaTranscoderRepo;
+use crate::repos::oembed_repo::OembedRepo;
+use crate::repos::organizations_repo::{OrganizationsRepo, DEFAULT_ORGANIZATION_ID};
+use crate::repos::pagination::PageWindow;
+use crate::repos::pdf_renderer_repo::PdfRendererRepo;
+use crate::repos::provenance_repo::ProvenanceRepo;
+use crate::repos::s3_repo::{ObjectStream, S3Repo};
+use crate::repos::social_metadata_repo::SocialMetadataRepo;
 use crate::repos::subjects_repo::SubjectsRepo;
+use crate::repos::timestamp_repo::TimestampRepo;
+use crate::repos::translation_repo::TranslationRepo;
+use crate::repos::uploads_repo::UploadsRepo;
+use crate::repos::video_downloader_repo::VideoDownloaderRepo;
+use crate::repos::virus_scanner_repo::{ScanVerdict, VirusScannerRepo};
+use crate::repos::workflow_labels_repo::WorkflowLabelsRepo;
+use crate::s3_backfill::S3BackfillProgress;
+use crate::s3_keys::S3KeyScheme;
+use crate::scheduler::new_scheduler_metrics;
 use crate::services::accessions_service::AccessionsService;
+use crate::services::audit_log_service::AuditLogService;
 use crate::services::auth_service::AuthService;
+use crate::services::collections_service::CollectionsService;
+use crate::services::crawl_blocklist_service::CrawlBlocklistService;
+use crate::services::flags_service::{new_feature_flags_cache, FlagsService};
+use crate::services::organizations_service::OrganizationsService;
+use crate::services::stats_service::{new_public_stats_cache, StatsService};
 use crate::services::subjects_service::SubjectsService;
-use ::entity::sea_orm_active_enums::{DublinMetadataFormat, Role};
+use crate::services::uploads_service::UploadsService;
+use crate::services::workflow_labels_service::WorkflowLabelsService;
+use crate::social_metadata::{PostDetails, SocialPost};
+use crate::static_export::StaticExportProgress;
+use crate::upload_progress::UploadProgressRegistry;
+use crate::wacz::new_wacz_pages_cache;
+use ::entity::sea_orm_active_enums::{
+    AccessionEventKind, AccessionRelationKind, DerivativeKind, DublinMetadataFormat, EmailStatus,
+    PublicationState, Role, ScanStatus, SocialPlatform, TimestampMethod,
+};
 use async_trait::async_trait;
+use aws_smithy_types::byte_stream::ByteStream;
 use axum::Router;
 use bytes::Bytes;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use entity::accession::Model as AccessionModel;
+use entity::accession_derivative::Model as AccessionDerivativeModel;
+use entity::accession_provenance::Model as AccessionProvenanceModel;
+use entity::accession_relation::Model as AccessionRelationModel;
 use entity::accessions_with_metadata::Model as AccessionsWithMetadataModel;
+use entity::collection::Model as CollectionModel;
+use entity::comment::Model as CommentModel;
+use entity::crawl_blocklist::Model as CrawlBlocklistModel;
 use entity::dublin_metadata_subject_ar::Model as DublinMetadataSubjectArModel;
 use entity::dublin_metadata_subject_en::Model as DublinMetadataSubjectEnModel;
+use entity::feature_flag::Model as FeatureFlagModel;
+use entity::organization::Model as OrganizationModel;
 use entity::sea_orm_active_enums::CrawlStatus;
-use jsonwebtoken::{encode, Header};
+use entity::social_metadata::Model as SocialMetadataModel;
+use entity::upload_session::Model as UploadSessionModel;
+use entity::workflow_label::Model as WorkflowLabelModel;
+use futures::stream::BoxStream;
+use futures::StreamExt;
 use reqwest::{Error, RequestBuilder, Response};
-use sea_orm::DbErr;
+use sea_orm::{ActiveEnum, DbErr};
 use std::error::Error as StdError;
-use std::sync::Arc;
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
 /// In-memory implementation of AccessionsRepo for testing.
 /// Returns predefined mock data instead of interacting with a database.
 #[derive(Clone, Debug, Default)]
@@ -48,103 +126,1259 @@ impl AccessionsRepo for InMemoryAccessionsRepo {
     async fn write_one(
         &self,
         _create_accession_request: CreateAccessionRequest,
-        _org_id: Uuid,
+        _org_id: Option<Uuid>,
         _crawl_id: Uuid,
         _job_run_id: String,
         _crawl_status: CrawlStatus,
+        _organization_id: i32,
+    ) -> Result<i32, DbErr> {
+        Ok(10)
+    }
+
+    /// Mock implementation that always succeeds without storing data.
+    async fn write_pending(
+        &self,
+        _create_accession_request: CreateAccessionRequest,
+        _organization_id: i32,
     ) -> Result<i32, DbErr> {
         Ok(10)
     }
 
+    async fn complete_crawl(
+        &self,
+        _id: i32,
+        _create_accession_request: CreateAccessionRequest,
+        _org_id: Option<Uuid>,
+        _crawl_id: Uuid,
+        _job_run_id: String,
+    ) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    async fn fail_crawl(&self, _id: i32) -> Result<(), DbErr> {
+        Ok(())
+    }
+
     /// Mock implementation for raw accession creation.
     async fn write_one_raw(
         &self,
         _create_accession_request: CreateAccessionRequestRaw,
+        _scan_status: ScanStatus,
+        _metadata_scrubbed: bool,
+        _organization_id: i32,
     ) -> Result<i32, DbErr> {
         Ok(10)
     }
 
-    /// Returns a predefined mock accession.
+    /// Returns the mock accession with the visibility asked for, as the view filters on it.
+    /// [`MOCK_PRIVATE_ACCESSION_ID`] is only found as private.
     async fn get_one(
         &self,
-        _id: i32,
-        _private: bool,
+        id: i32,
+        private: bool,
     ) -> Result<Option<AccessionsWithMetadataModel>, DbErr> {
-        Ok(Some(mock_one_accession_with_metadata()))
+        if id == MOCK_PRIVATE_ACCESSION_ID && !private {
+            Ok(None)
+        } else if private {
+            Ok(Some(mock_one_accession_with_metadata()))
+        } else {
+            Ok(Some(mock_one_public_accession_with_metadata()))
+        }
     }
 
     /// Returns predefined mock paginated accessions.
     async fn list_paginated(
         &self,
         _params: AccessionPaginationWithPrivate,
-    ) -> Result<(Vec<AccessionsWithMetadataModel>, u64), DbErr> {
+    ) -> Result<(Vec<AccessionsWithMetadataModel>, Option<u64>), DbErr> {
         Ok(mock_paginated_en())
     }
 
+    async fn timeline(
+        &self,
+        _params: AccessionPaginationWithPrivate,
+        _interval: TimelineInterval,
+    ) -> Result<Vec<TimelineBucket>, DbErr> {
+        Ok(vec![TimelineBucket {
+            starts_at: Default::default(),
+            count: 3,
+        }])
+    }
+
     async fn delete_one(&self, _id: i32) -> Result<Option<AccessionModel>, DbErr> {
         Ok(Some(mock_one_accession()))
     }
 
-    async fn update_one(
-        &self,
-        _id: i32,
-        _update_accession_request: crate::models::request::UpdateAccessionRequest,
-    ) -> Result<Option<AccessionsWithMetadataModel>, DbErr> {
-        Ok(Some(mock_one_accession_with_metadata()))
+    /// Refuses updates based on any revision other than the mock accession's.
+    async fn update_one(
+        &self,
+        _id: i32,
+        update_accession_request: crate::models::request::UpdateAccessionRequest,
+    ) -> Result<Option<AccessionsWithMetadataModel>, DbErr> {
+        let mock = mock_one_accession_with_metadata();
+        if update_accession_request.revision != mock.revision {
+            return Err(DbErr::RecordNotUpdated);
+        }
+        Ok(Some(mock))
+    }
+
+    async fn set_pdf_s3_filename(&self, _id: i32, _pdf_s3_filename: String) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    async fn set_doi(&self, _id: i32, _doi: String) -> Result<Option<()>, DbErr> {
+        Ok(Some(()))
+    }
+
+    async fn flag_bad_crawl(&self, _id: i32, _reason: String) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    /// Only the mock accession is flagged as a bad capture.
+    async fn clear_bad_crawl(&self, id: i32) -> Result<Option<()>, DbErr> {
+        Ok((id == mock_one_accession_with_metadata().id).then_some(()))
+    }
+
+    async fn list_bad_crawls(
+        &self,
+        _window: PageWindow,
+        _organization_id: Option<i32>,
+    ) -> Result<(Vec<AccessionsWithMetadataModel>, u64), DbErr> {
+        Ok((vec![mock_bad_capture()], 1))
+    }
+
+    async fn list_review_queue(
+        &self,
+        _window: PageWindow,
+    ) -> Result<(Vec<AccessionsWithMetadataModel>, u64), DbErr> {
+        Ok((vec![mock_bad_capture()], 1))
+    }
+
+    /// Reports the mock accession as a duplicate of its own canonical url.
+    async fn find_ids_by_canonical_url(&self, canonical_url: &str) -> Result<Vec<i32>, DbErr> {
+        let mock = mock_one_accession_with_metadata();
+        if mock.canonical_url.as_deref() == Some(canonical_url) {
+            Ok(vec![mock.id])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    async fn write_derivative(
+        &self,
+        _accession_id: i32,
+        _kind: DerivativeKind,
+        _s3_filename: String,
+        _content_type: String,
+    ) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    /// Returns a single mock thumbnail.
+    async fn list_derivatives(
+        &self,
+        _accession_id: i32,
+    ) -> Result<Vec<AccessionDerivativeModel>, DbErr> {
+        Ok(vec![mock_one_derivative()])
+    }
+
+    /// Returns the mock accession on the first batch and nothing after it.
+    async fn list_batch_after_id(
+        &self,
+        after_id: i32,
+        _limit: u64,
+    ) -> Result<Vec<AccessionModel>, DbErr> {
+        let mock = mock_one_accession();
+        if after_id < mock.id {
+            Ok(vec![mock])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    async fn list_public_batch_after_id(
+        &self,
+        after_id: i32,
+        _limit: u64,
+    ) -> Result<Vec<AccessionsWithMetadataModel>, DbErr> {
+        let mock = mock_one_accession_with_metadata();
+        if after_id < mock.id {
+            Ok(vec![mock])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    /// Reports the mock accession as only being in Browsertrix.
+    async fn list_missing_s3_files(&self) -> Result<Vec<AccessionsWithMetadataModel>, DbErr> {
+        Ok(vec![AccessionsWithMetadataModel {
+            s3_filename: None,
+            ..mock_one_accession_with_metadata()
+        }])
+    }
+
+    async fn set_s3_filename(&self, _id: i32, _s3_filename: String) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    async fn replace_s3_filename(
+        &self,
+        _id: i32,
+        _previous: Option<String>,
+        _s3_filename: String,
+    ) -> Result<Option<()>, DbErr> {
+        Ok(Some(()))
+    }
+
+    /// Reports the mock accession as the only capture of its canonical URL.
+    async fn list_public_captures(&self, canonical_url: &str) -> Result<Vec<Capture>, DbErr> {
+        let mock = mock_one_accession_with_metadata();
+        if mock.canonical_url.as_deref() == Some(canonical_url) {
+            Ok(vec![Capture {
+                accession_id: mock.id,
+                captured_at: mock.crawl_timestamp,
+            }])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    async fn count_all(&self) -> Result<u64, DbErr> {
+        Ok(1)
+    }
+
+    async fn count_public(&self) -> Result<u64, DbErr> {
+        Ok(1)
+    }
+
+    async fn refresh_full_text_batch(&self, after_id: i32, _limit: u64) -> Result<Vec<i32>, DbErr> {
+        let mock = mock_one_accession();
+        if after_id < mock.id {
+            Ok(vec![mock.id])
+        } else {
+            Ok(vec![])
+        }
+    }
+
+    async fn rebuild_full_text_indexes(&self) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    async fn lift_lapsed_embargoes(&self, _now: NaiveDateTime) -> Result<Vec<i32>, DbErr> {
+        Ok(vec![])
+    }
+
+    /// Reports the mock accession as a draft, other IDs don't exist.
+    async fn get_publication_state(&self, id: i32) -> Result<Option<PublicationState>, DbErr> {
+        let mock = mock_one_accession();
+        Ok((id == mock.id).then_some(mock.publication_state))
+    }
+
+    /// Reports every selected ID as moved.
+    async fn bulk_set_publication_state(
+        &self,
+        selection: AccessionSelection,
+        _from: Vec<PublicationState>,
+        _to: PublicationState,
+        _actor_email: String,
+        _reason: Option<String>,
+        _organization_id: Option<i32>,
+    ) -> Result<Vec<i32>, DbErr> {
+        match selection {
+            AccessionSelection::Ids(ids) => Ok(ids),
+            AccessionSelection::UrlPrefix(_) => Ok(vec![mock_one_accession().id]),
+        }
+    }
+
+    /// Returns the mock accession in its new state.
+    async fn set_publication_state(
+        &self,
+        _id: i32,
+        _from: PublicationState,
+        to: PublicationState,
+    ) -> Result<Option<AccessionsWithMetadataModel>, DbErr> {
+        Ok(Some(AccessionsWithMetadataModel {
+            is_private: to != PublicationState::Published,
+            publication_state: to,
+            ..mock_one_accession_with_metadata()
+        }))
+    }
+}
+
+/// In-memory implementation of PdfRendererRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryPdfRendererRepo {}
+
+#[async_trait]
+impl PdfRendererRepo for InMemoryPdfRendererRepo {
+    /// Returns a minimal PDF header rather than rendering anything.
+    async fn render_url_to_pdf(&self, _url: &str) -> Result<Bytes, Error> {
+        Ok(Bytes::from_static(b"%PDF-1.7"))
+    }
+}
+
+/// In-memory implementation of TranslationRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryTranslationRepo {}
+
+#[async_trait]
+impl TranslationRepo for InMemoryTranslationRepo {
+    /// Tags each text with the target language rather than translating it.
+    async fn translate(
+        &self,
+        texts: Vec<String>,
+        _source: &str,
+        target: &str,
+    ) -> Result<Vec<String>, Error> {
+        Ok(texts
+            .into_iter()
+            .map(|text| format!("[{target}] {text}"))
+            .collect())
+    }
+}
+
+/// In-memory implementation of DoiRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryDoiRepo {}
+
+#[async_trait]
+impl DoiRepo for InMemoryDoiRepo {
+    /// Returns a made up DOI without registering anything.
+    async fn mint(&self, _metadata: DoiMetadata) -> Result<String, Error> {
+        Ok("10.1234/abcd-efgh".to_string())
+    }
+}
+
+/// In-memory implementation of VirusScannerRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryVirusScannerRepo {}
+
+#[async_trait]
+impl VirusScannerRepo for InMemoryVirusScannerRepo {
+    /// Flags files containing the EICAR test signature and passes everything else.
+    async fn scan(
+        &self,
+        mut chunks: BoxStream<'static, Result<Bytes, String>>,
+    ) -> Result<ScanVerdict, Box<dyn StdError + Send + Sync>> {
+        let mut contents = Vec::new();
+        while let Some(chunk) = chunks.next().await {
+            contents.extend_from_slice(&chunk?);
+        }
+        if contents
+            .windows(EICAR_SIGNATURE.len())
+            .any(|window| window == EICAR_SIGNATURE)
+        {
+            return Ok(ScanVerdict::Infected("Eicar-Signature".to_string()));
+        }
+        Ok(ScanVerdict::Clean)
+    }
+}
+
+/// In-memory implementation of MediaTranscoderRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryMediaTranscoderRepo {}
+
+#[async_trait]
+impl MediaTranscoderRepo for InMemoryMediaTranscoderRepo {
+    /// Returns a placeholder rather than transcoding anything.
+    async fn transcode(
+        &self,
+        _source_url: &str,
+        kind: DerivativeKind,
+    ) -> Result<Bytes, Box<dyn StdError + Send + Sync>> {
+        Ok(Bytes::from(format!("mock {kind:?}")))
+    }
+}
+
+/// In-memory implementation of VideoDownloaderRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryVideoDownloaderRepo {}
+
+#[async_trait]
+impl VideoDownloaderRepo for InMemoryVideoDownloaderRepo {
+    /// Finds a video at YouTube URLs only.
+    async fn probe(&self, url: &str) -> Result<Bytes, Box<dyn StdError + Send + Sync>> {
+        if !url.contains("youtube.com") {
+            return Err("ERROR: Unsupported URL".into());
+        }
+        Ok(Bytes::from_static(br#"{"title": "Fighting in Omdurman"}"#))
+    }
+
+    /// Writes a placeholder MP4 to the temporary directory rather than downloading anything.
+    async fn download(&self, _url: &str) -> Result<PathBuf, Box<dyn StdError + Send + Sync>> {
+        let path = std::env::temp_dir().join(format!("{}.mp4", Uuid::new_v4()));
+        std::fs::write(&path, b"mp4 bytes")?;
+        Ok(path)
+    }
+}
+
+/// An accession ID the mock accessions repo only has a private accession for.
+pub const MOCK_PRIVATE_ACCESSION_ID: i32 = 3;
+
+/// Prefix of the EICAR antivirus test file, which scanners treat as malware.
+pub const EICAR_SIGNATURE: &[u8] = b"X5O!P%@AP[4\\PZX54(P^)7CC)7}$EICAR";
+
+/// In-memory implementation of SubjectsRepo for testing.
+/// Provides mock data for subject-related operations.
+#[derive(Clone, Debug, Default)]
+pub struct InMemorySubjectsRepo {}
+
+#[async_trait]
+impl SubjectsRepo for InMemorySubjectsRepo {
+    /// Returns a predefined subject response, credited to the creator, without storing data.
+    async fn write_one(
+        &self,
+        _create_subject_request: crate::models::request::CreateSubjectRequest,
+        _organization_id: i32,
+        created_by: Option<String>,
+    ) -> Result<crate::models::response::SubjectResponse, DbErr> {
+        Ok(crate::models::response::SubjectResponse {
+            id: 1,
+            subject: "some cool archive".to_string(),
+            created_at: Default::default(),
+            updated_at: Default::default(),
+            created_by,
+        })
+    }
+    async fn delete_one(
+        &self,
+        _subject_id: i32,
+        _metadata_language: MetadataLanguage,
+        _organization_id: i32,
+    ) -> Result<Option<()>, DbErr> {
+        Ok(Some(()))
+    }
+    async fn count_all(&self) -> Result<u64, DbErr> {
+        Ok(2)
+    }
+    /// Returns predefined mock Arabic subjects.
+    async fn list_paginated_ar(
+        &self,
+        _page: u64,
+        _per_page: u64,
+        _query_term: Option<String>,
+        _match_mode: SubjectMatch,
+        _sort: SubjectSort,
+        _organization_id: i32,
+    ) -> Result<(Vec<DublinMetadataSubjectArModel>, u64), DbErr> {
+        Ok(mock_paginated_subjects_ar())
+    }
+
+    /// Returns predefined mock English subjects.
+    async fn list_paginated_en(
+        &self,
+        _page: u64,
+        _per_page: u64,
+        _query_term: Option<String>,
+        _match_mode: SubjectMatch,
+        _sort: SubjectSort,
+        _organization_id: i32,
+    ) -> Result<(Vec<DublinMetadataSubjectEnModel>, u64), DbErr> {
+        Ok(mock_paginated_subjects_en())
+    }
+
+    /// Always returns true for subject verification in tests.
+    async fn verify_subjects_exist(
+        &self,
+        _subject_ids: Vec<i32>,
+        _metadata_language: MetadataLanguage,
+        _organization_id: i32,
+    ) -> Result<bool, DbErr> {
+        Ok(true)
+    }
+
+    /// Only knows the subject Khartoum.
+    async fn find_by_names(
+        &self,
+        names: Vec<String>,
+        _metadata_language: MetadataLanguage,
+        _organization_id: i32,
+    ) -> Result<Vec<crate::models::response::SubjectResponse>, DbErr> {
+        Ok(names
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case("khartoum"))
+            .then(|| crate::models::response::SubjectResponse {
+                id: 2,
+                subject: "Khartoum".to_string(),
+                created_at: Default::default(),
+                updated_at: Default::default(),
+                created_by: None,
+            })
+            .into_iter()
+            .collect())
+    }
+
+    /// Three accessions match any filter, two of them without the subject.
+    async fn find_untagged(
+        &self,
+        _subject_id: i32,
+        _filter: crate::models::request::ApplySubjectRequest,
+        _organization_id: i32,
+    ) -> Result<(u64, Vec<i32>), DbErr> {
+        Ok((3, vec![1, 2]))
+    }
+
+    /// Pretends every accession was tagged.
+    async fn tag_accessions(
+        &self,
+        _subject_id: i32,
+        _metadata_language: MetadataLanguage,
+        accession_ids: Vec<i32>,
+    ) -> Result<u64, DbErr> {
+        Ok(accession_ids.len() as u64)
+    }
+}
+
+/// In-memory implementation of EntityExtractorRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryEntityExtractorRepo {}
+
+#[async_trait]
+impl EntityExtractorRepo for InMemoryEntityExtractorRepo {
+    /// Finds the same two places and a date in every text.
+    async fn extract_entities(
+        &self,
+        _text: &str,
+        _language: &str,
+    ) -> Result<Vec<NamedEntity>, Error> {
+        Ok(["Khartoum", "Omdurman"]
+            .into_iter()
+            .map(|place| NamedEntity {
+                text: place.to_string(),
+                label: "GPE".to_string(),
+            })
+            .chain([NamedEntity {
+                text: "2023".to_string(),
+                label: "DATE".to_string(),
+            }])
+            .collect())
+    }
+}
+
+/// In-memory implementation of OrganizationsRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryOrganizationsRepo {}
+
+#[async_trait]
+impl OrganizationsRepo for InMemoryOrganizationsRepo {
+    async fn list(&self) -> Result<Vec<OrganizationModel>, DbErr> {
+        Ok(vec![mock_one_organization()])
+    }
+
+    /// Echoes the organization back with a fixed id without storing data.
+    async fn write_one(&self, slug: String, name: String) -> Result<OrganizationModel, DbErr> {
+        Ok(OrganizationModel {
+            id: 2,
+            slug,
+            name,
+            created_at: Default::default(),
+        })
+    }
+
+    /// Only knows the archive's own organization.
+    async fn rename(
+        &self,
+        organization_id: i32,
+        name: String,
+    ) -> Result<Option<OrganizationModel>, DbErr> {
+        Ok(
+            (organization_id == DEFAULT_ORGANIZATION_ID).then(|| OrganizationModel {
+                name,
+                ..mock_one_organization()
+            }),
+        )
+    }
+
+    async fn assign_user(
+        &self,
+        _user_id: Uuid,
+        _organization_id: i32,
+    ) -> Result<Option<()>, DbErr> {
+        Ok(Some(()))
+    }
+}
+
+/// In-memory implementation of WorkflowLabelsRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryWorkflowLabelsRepo {}
+
+#[async_trait]
+impl WorkflowLabelsRepo for InMemoryWorkflowLabelsRepo {
+    /// Echoes the label back with a fixed id without storing data.
+    async fn write_one(&self, label: String) -> Result<WorkflowLabelModel, DbErr> {
+        Ok(WorkflowLabelModel { id: 1, label })
+    }
+
+    async fn list(&self) -> Result<Vec<WorkflowLabelModel>, DbErr> {
+        Ok(vec![mock_one_workflow_label()])
+    }
+
+    async fn delete_one(&self, _label_id: i32) -> Result<Option<()>, DbErr> {
+        Ok(Some(()))
+    }
+
+    async fn add_to_accession(&self, _accession_id: i32, _label_id: i32) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    async fn remove_from_accession(
+        &self,
+        _accession_id: i32,
+        _label_id: i32,
+    ) -> Result<Option<()>, DbErr> {
+        Ok(Some(()))
+    }
+
+    async fn list_for_accession(
+        &self,
+        _accession_id: i32,
+    ) -> Result<Vec<WorkflowLabelModel>, DbErr> {
+        Ok(vec![mock_one_workflow_label()])
+    }
+}
+
+/// In-memory implementation of AuditLogRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryAuditLogRepo {}
+
+#[async_trait]
+impl AuditLogRepo for InMemoryAuditLogRepo {
+    async fn record(&self, _entry: AuditEntry) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        _filter: AuditLogFilter,
+        _window: PageWindow,
+    ) -> Result<(Vec<entity::audit_log::Model>, u64), DbErr> {
+        Ok((vec![mock_one_audit_log_entry()], 1))
+    }
+
+    async fn export(
+        &self,
+        _filter: AuditLogFilter,
+        _limit: u64,
+    ) -> Result<Vec<entity::audit_log::Model>, DbErr> {
+        Ok(vec![mock_one_audit_log_entry()])
+    }
+}
+
+/// In-memory implementation of CollectionsRepo for testing.
+///
+/// Only [`mock_one_collection`] exists and it holds a public and a private accession.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryCollectionsRepo {}
+
+#[async_trait]
+impl CollectionsRepo for InMemoryCollectionsRepo {
+    async fn write_one(
+        &self,
+        organization_id: i32,
+        title: String,
+        description: Option<String>,
+        created_by: String,
+    ) -> Result<CollectionModel, DbErr> {
+        Ok(CollectionModel {
+            organization_id,
+            title,
+            description,
+            created_by,
+            ..mock_one_collection()
+        })
+    }
+
+    async fn get_one(&self, collection_id: i32) -> Result<Option<CollectionModel>, DbErr> {
+        Ok(Some(mock_one_collection()).filter(|collection| collection.id == collection_id))
+    }
+
+    async fn add_accession(&self, _collection_id: i32, _accession_id: i32) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    async fn remove_accession(
+        &self,
+        _collection_id: i32,
+        accession_id: i32,
+    ) -> Result<Option<()>, DbErr> {
+        Ok((accession_id == 1).then_some(()))
+    }
+
+    async fn list_accessions(
+        &self,
+        _collection_id: i32,
+    ) -> Result<Vec<AccessionsWithMetadataModel>, DbErr> {
+        Ok(vec![
+            AccessionsWithMetadataModel {
+                is_private: false,
+                ..mock_one_accession_with_metadata()
+            },
+            AccessionsWithMetadataModel {
+                id: 2,
+                ..mock_one_accession_with_metadata()
+            },
+        ])
+    }
+}
+
+/// In-memory implementation of AccessionEventsRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryAccessionEventsRepo {}
+
+#[async_trait]
+impl AccessionEventsRepo for InMemoryAccessionEventsRepo {
+    async fn record(
+        &self,
+        _accession_id: i32,
+        _kinds: Vec<AccessionEventKind>,
+        _country: Option<String>,
+    ) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    async fn list_for_accession(
+        &self,
+        accession_id: i32,
+        _limit: u64,
+    ) -> Result<Vec<entity::accession_event::Model>, DbErr> {
+        Ok(vec![entity::accession_event::Model {
+            id: 1,
+            accession_id,
+            kind: AccessionEventKind::View,
+            country: Some("SD".to_string()),
+            created_at: Default::default(),
+        }])
+    }
+
+    async fn count_for_accession(
+        &self,
+        _accession_id: i32,
+        _since: Option<chrono::NaiveDateTime>,
+    ) -> Result<Vec<EventCount>, DbErr> {
+        Ok(vec![
+            EventCount {
+                kind: AccessionEventKind::View,
+                country: Some("SD".to_string()),
+                count: 3,
+            },
+            EventCount {
+                kind: AccessionEventKind::WaczUrl,
+                country: Some("SD".to_string()),
+                count: 2,
+            },
+            EventCount {
+                kind: AccessionEventKind::View,
+                country: None,
+                count: 1,
+            },
+        ])
+    }
+
+    async fn top_accessions(
+        &self,
+        _kind: AccessionEventKind,
+        _since: Option<chrono::NaiveDateTime>,
+        _limit: u64,
+    ) -> Result<Vec<AccessionEventTotal>, DbErr> {
+        Ok(vec![AccessionEventTotal {
+            accession_id: 1,
+            count: 4,
+        }])
+    }
+}
+
+/// In-memory implementation of AccessionRelationsRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryAccessionRelationsRepo {}
+
+#[async_trait]
+impl AccessionRelationsRepo for InMemoryAccessionRelationsRepo {
+    async fn write_one(
+        &self,
+        from_accession_id: i32,
+        to_accession_id: i32,
+        kind: AccessionRelationKind,
+    ) -> Result<AccessionRelationModel, DbErr> {
+        Ok(AccessionRelationModel {
+            id: 3,
+            from_accession_id,
+            to_accession_id,
+            kind,
+            created_at: Default::default(),
+        })
+    }
+
+    async fn delete_one(&self, _accession_id: i32, relation_id: i32) -> Result<Option<()>, DbErr> {
+        Ok((relation_id == 1).then_some(()))
+    }
+
+    /// Relates every accession to a public one, and to a private one when asked for.
+    async fn list_for_accession(
+        &self,
+        _accession_id: i32,
+        include_private: bool,
+    ) -> Result<Vec<RelatedAccession>, DbErr> {
+        let mut related = vec![RelatedAccession {
+            relation_id: 1,
+            kind: AccessionRelationKind::PartOf,
+            direction: RelationDirection::Outgoing,
+            accession: AccessionsWithMetadataModel {
+                id: 2,
+                is_private: false,
+                ..mock_one_accession_with_metadata()
+            },
+        }];
+        if include_private {
+            related.push(RelatedAccession {
+                relation_id: 2,
+                kind: AccessionRelationKind::References,
+                direction: RelationDirection::Incoming,
+                accession: AccessionsWithMetadataModel {
+                    id: 3,
+                    is_private: true,
+                    ..mock_one_accession_with_metadata()
+                },
+            });
+        }
+        Ok(related)
+    }
+}
+
+/// In-memory implementation of CommentsRepo for testing.
+///
+/// Every accession has the comments of [`mock_comments`].
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryCommentsRepo {}
+
+#[async_trait]
+impl CommentsRepo for InMemoryCommentsRepo {
+    async fn write_one(
+        &self,
+        accession_id: i32,
+        author_email: String,
+        body: String,
+        markdown: bool,
+    ) -> Result<CommentModel, DbErr> {
+        Ok(CommentModel {
+            id: 3,
+            accession_id,
+            author_email,
+            body,
+            markdown,
+            created_at: Default::default(),
+            updated_at: Default::default(),
+        })
+    }
+
+    async fn list_for_accession(&self, accession_id: i32) -> Result<Vec<CommentModel>, DbErr> {
+        Ok(mock_comments(accession_id))
+    }
+
+    async fn find_one(
+        &self,
+        accession_id: i32,
+        comment_id: i32,
+    ) -> Result<Option<CommentModel>, DbErr> {
+        Ok(mock_comments(accession_id)
+            .into_iter()
+            .find(|comment| comment.id == comment_id))
+    }
+
+    async fn update_one(
+        &self,
+        accession_id: i32,
+        comment_id: i32,
+        body: String,
+        markdown: bool,
+    ) -> Result<Option<CommentModel>, DbErr> {
+        Ok(self
+            .find_one(accession_id, comment_id)
+            .await?
+            .map(|comment| CommentModel {
+                body,
+                markdown,
+                ..comment
+            }))
+    }
+
+    async fn delete_one(&self, accession_id: i32, comment_id: i32) -> Result<Option<()>, DbErr> {
+        Ok(self.find_one(accession_id, comment_id).await?.map(|_| ()))
+    }
+
+    /// Everyone can be mentioned.
+    async fn find_mentionable(
+        &self,
+        emails: Vec<String>,
+        _organization_id: i32,
+    ) -> Result<Vec<String>, DbErr> {
+        Ok(emails)
+    }
+}
+
+/// Comments on an accession, the first by the user of [`get_mock_jwt`] and the second by
+/// someone else.
+pub fn mock_comments(accession_id: i32) -> Vec<CommentModel> {
+    vec![
+        CommentModel {
+            id: 1,
+            accession_id,
+            author_email: "someuser@gmail.com".to_string(),
+            body: "Needs a better title".to_string(),
+            markdown: false,
+            created_at: Default::default(),
+            updated_at: Default::default(),
+        },
+        CommentModel {
+            id: 2,
+            accession_id,
+            author_email: "otheruser@gmail.com".to_string(),
+            body: "@someuser@gmail.com **agreed**".to_string(),
+            markdown: true,
+            created_at: Default::default(),
+            updated_at: Default::default(),
+        },
+    ]
+}
+
+/// Seed of the RFC 8032 Ed25519 test key, which signs mock provenance records.
+pub const MOCK_PROVENANCE_SIGNING_KEY: &str = "nWGxne/9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A=";
+
+/// In-memory implementation of ProvenanceRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryProvenanceRepo {}
+
+#[async_trait]
+impl ProvenanceRepo for InMemoryProvenanceRepo {
+    async fn write_one(&self, _provenance: AccessionProvenanceModel) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    async fn replace_one(&self, _provenance: AccessionProvenanceModel) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    /// Only accession 1 has a recorded provenance, signed with [`MOCK_PROVENANCE_SIGNING_KEY`].
+    async fn get_one(&self, accession_id: i32) -> Result<Option<AccessionProvenanceModel>, DbErr> {
+        if accession_id != 1 {
+            return Ok(None);
+        }
+        let signer = ProvenanceSigner::from_base64_seed(MOCK_PROVENANCE_SIGNING_KEY).unwrap();
+        let sha256 = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08".to_string();
+        let recorded_at = Default::default();
+        Ok(Some(AccessionProvenanceModel {
+            accession_id,
+            signature: Some(signer.sign(&signed_message(accession_id, &sha256, recorded_at))),
+            signer_public_key: Some(signer.public_key()),
+            sha256,
+            recorded_at,
+            timestamp_method: Some(TimestampMethod::OpenTimestamps),
+            timestamp_proof: Some(b"mock ots proof".to_vec()),
+            timestamped_at: Some(recorded_at),
+        }))
+    }
+
+    /// Every record is already timestamped apart from accession 2's.
+    async fn list_untimestamped(
+        &self,
+        _limit: u64,
+    ) -> Result<Vec<AccessionProvenanceModel>, DbErr> {
+        Ok(vec![AccessionProvenanceModel {
+            accession_id: 2,
+            sha256: "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08".to_string(),
+            signature: None,
+            signer_public_key: None,
+            recorded_at: Default::default(),
+            timestamp_method: None,
+            timestamp_proof: None,
+            timestamped_at: None,
+        }])
+    }
+
+    async fn set_timestamp(
+        &self,
+        _accession_id: i32,
+        _method: TimestampMethod,
+        _proof: Vec<u8>,
+        _timestamped_at: NaiveDateTime,
+    ) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
+
+/// In-memory implementation of SocialMetadataRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemorySocialMetadataRepo {}
+
+#[async_trait]
+impl SocialMetadataRepo for InMemorySocialMetadataRepo {
+    async fn write_one(&self, _metadata: SocialMetadataModel) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    /// Only accession 1 was crawled from a social media post.
+    async fn get_one(&self, accession_id: i32) -> Result<Option<SocialMetadataModel>, DbErr> {
+        Ok((accession_id == 1).then(|| SocialMetadataModel {
+            accession_id,
+            platform: SocialPlatform::X,
+            author_handle: Some("RSFSudan".to_string()),
+            author_name: Some("Rapid Support Forces".to_string()),
+            post_id: Some("1785236745296498795".to_string()),
+            posted_at: Some(Default::default()),
+            extracted_at: Default::default(),
+        }))
+    }
+}
+
+/// Content digest shared by accessions 1 and 3, see [`InMemoryContentDigestRepo`].
+pub const MOCK_CONTENT_DIGEST: &str =
+    "sha256:9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+/// In-memory implementation of ContentDigestRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryContentDigestRepo {}
+
+#[async_trait]
+impl ContentDigestRepo for InMemoryContentDigestRepo {
+    async fn write_one(&self, _accession_id: i32, _digest: String) -> Result<(), DbErr> {
+        Ok(())
+    }
+
+    /// Only accession 1 has a content digest.
+    async fn get_one(&self, accession_id: i32) -> Result<Option<String>, DbErr> {
+        Ok((accession_id == 1).then(|| MOCK_CONTENT_DIGEST.to_string()))
+    }
+
+    /// Accessions 1 and 3 captured the same content.
+    async fn find_accession_ids(
+        &self,
+        digest: &str,
+        _organization_id: Option<i32>,
+    ) -> Result<Vec<i32>, DbErr> {
+        Ok(if digest == MOCK_CONTENT_DIGEST {
+            vec![1, 3]
+        } else {
+            vec![]
+        })
+    }
+}
+
+/// In-memory implementation of OembedRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryOembedRepo {}
+
+#[async_trait]
+impl OembedRepo for InMemoryOembedRepo {
+    /// Knows nothing about any post, without contacting the platform.
+    async fn lookup(&self, _post: &SocialPost, _url: &str) -> Result<PostDetails, reqwest::Error> {
+        Ok(PostDetails::default())
+    }
+}
+
+/// In-memory implementation of TimestampRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryTimestampRepo {}
+
+#[async_trait]
+impl TimestampRepo for InMemoryTimestampRepo {
+    fn method(&self) -> TimestampMethod {
+        TimestampMethod::OpenTimestamps
+    }
+
+    /// Returns a made up proof without contacting a calendar.
+    async fn timestamp(
+        &self,
+        _sha256: &[u8; 32],
+    ) -> Result<Vec<u8>, Box<dyn StdError + Send + Sync>> {
+        Ok(b"mock ots proof".to_vec())
+    }
+}
+
+/// In-memory implementation of FeatureFlagsRepo for testing.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryFeatureFlagsRepo {}
+
+#[async_trait]
+impl FeatureFlagsRepo for InMemoryFeatureFlagsRepo {
+    /// Echoes the flag back with a fixed id without storing data.
+    async fn write_one(
+        &self,
+        create_feature_flag_request: CreateFeatureFlagRequest,
+    ) -> Result<FeatureFlagModel, DbErr> {
+        Ok(FeatureFlagModel {
+            name: create_feature_flag_request.name,
+            description: create_feature_flag_request.description,
+            enabled: create_feature_flag_request.enabled,
+            environments: create_feature_flag_request.environments,
+            roles: create_feature_flag_request
+                .roles
+                .iter()
+                .map(|role| role.to_value())
+                .collect(),
+            ..mock_one_feature_flag()
+        })
+    }
+
+    async fn list(&self) -> Result<Vec<FeatureFlagModel>, DbErr> {
+        Ok(vec![
+            mock_one_feature_flag(),
+            FeatureFlagModel {
+                id: 2,
+                name: COLLECTION_EXPORTS_FLAG.to_string(),
+                description: Some("Export collections as ZIPs".to_string()),
+                environments: vec!["test".to_string()],
+                ..mock_one_feature_flag()
+            },
+        ])
+    }
+
+    async fn update_one(
+        &self,
+        _name: &str,
+        update_feature_flag_request: UpdateFeatureFlagRequest,
+    ) -> Result<Option<FeatureFlagModel>, DbErr> {
+        Ok(Some(FeatureFlagModel {
+            description: update_feature_flag_request.description,
+            enabled: update_feature_flag_request.enabled,
+            environments: update_feature_flag_request.environments,
+            roles: update_feature_flag_request
+                .roles
+                .iter()
+                .map(|role| role.to_value())
+                .collect(),
+            ..mock_one_feature_flag()
+        }))
+    }
+
+    async fn delete_one(&self, _name: &str) -> Result<Option<()>, DbErr> {
+        Ok(Some(()))
+    }
+}
+
+/// In-memory implementation of ActivityDigestRepo for testing.
+///
+/// `researcher@example.com` and `contributor@example.com` are due a digest, and only the
+/// researcher has had a crawl complete.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryActivityDigestRepo {}
+
+#[async_trait]
+impl ActivityDigestRepo for InMemoryActivityDigestRepo {
+    async fn list_due_users(
+        &self,
+        _sent_before: NaiveDateTime,
+    ) -> Result<Vec<entity::archive_user::Model>, DbErr> {
+        let user = |id, email: &str, role| entity::archive_user::Model {
+            id,
+            email: email.to_string(),
+            role,
+            is_active: true,
+            email_status: EmailStatus::Deliverable,
+            organization_id: DEFAULT_ORGANIZATION_ID,
+            activity_digest: true,
+            activity_digest_sent_at: None,
+        };
+        Ok(vec![
+            user(
+                MOCK_RESEARCHER_ID,
+                "researcher@example.com",
+                Role::Researcher,
+            ),
+            user(Uuid::nil(), "contributor@example.com", Role::Contributor),
+        ])
+    }
+
+    async fn list_crawl_activity(
+        &self,
+        email: &str,
+        _since: NaiveDateTime,
+    ) -> Result<Vec<entity::audit_log::Model>, DbErr> {
+        if email != "researcher@example.com" {
+            return Ok(vec![]);
+        }
+        Ok(vec![entity::audit_log::Model {
+            id: 1,
+            actor_email: email.to_string(),
+            impersonator_email: None,
+            event: CRAWL_COMPLETED.to_string(),
+            entity_type: "accession".to_string(),
+            entity_id: "1".to_string(),
+            details: Some(serde_json::json!({"url": "https://example.com"})),
+            created_at: Default::default(),
+        }])
+    }
+
+    async fn list_awaiting_review(&self, _organization_id: i32) -> Result<Vec<i32>, DbErr> {
+        Ok(vec![3])
+    }
+
+    async fn mark_sent(&self, _user_id: Uuid, _sent_at: NaiveDateTime) -> Result<(), DbErr> {
+        Ok(())
     }
 }
 
-/// In-memory implementation of SubjectsRepo for testing.
-/// Provides mock data for subject-related operations.
+/// In-memory implementation of CrawlBlocklistRepo for testing.
 #[derive(Clone, Debug, Default)]
-pub struct InMemorySubjectsRepo {}
+pub struct InMemoryCrawlBlocklistRepo {}
 
 #[async_trait]
-impl SubjectsRepo for InMemorySubjectsRepo {
-    /// Returns a predefined subject response without storing data.
+impl CrawlBlocklistRepo for InMemoryCrawlBlocklistRepo {
+    /// Echoes the entry back with a fixed id without storing data.
     async fn write_one(
         &self,
-        _create_subject_request: crate::models::request::CreateSubjectRequest,
-    ) -> Result<crate::models::response::SubjectResponse, DbErr> {
-        Ok(crate::models::response::SubjectResponse {
-            id: 1,
-            subject: "some cool archive".to_string(),
+        pattern: String,
+        reason: String,
+    ) -> Result<CrawlBlocklistModel, DbErr> {
+        Ok(CrawlBlocklistModel {
+            pattern,
+            reason,
+            ..mock_one_crawl_blocklist_entry()
         })
     }
-    async fn delete_one(
-        &self,
-        _subject_id: i32,
-        _metadata_language: MetadataLanguage,
-    ) -> Result<Option<()>, DbErr> {
+
+    async fn list(&self) -> Result<Vec<CrawlBlocklistModel>, DbErr> {
+        Ok(vec![mock_one_crawl_blocklist_entry()])
+    }
+
+    async fn delete_one(&self, _entry_id: i32) -> Result<Option<()>, DbErr> {
         Ok(Some(()))
     }
-    /// Returns predefined mock Arabic subjects.
-    async fn list_paginated_ar(
+}
+
+/// In-memory implementation of UploadsRepo for testing.
+/// Only knows about a single upload with ID `mock-upload-id`.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryUploadsRepo {}
+
+#[async_trait]
+impl UploadsRepo for InMemoryUploadsRepo {
+    async fn write_one(
         &self,
-        _page: u64,
-        _per_page: u64,
-        _query_term: Option<String>,
-    ) -> Result<(Vec<DublinMetadataSubjectArModel>, u64), DbErr> {
-        Ok(mock_paginated_subjects_ar())
+        upload_id: String,
+        s3_key: String,
+        created_by: String,
+    ) -> Result<UploadSessionModel, DbErr> {
+        Ok(UploadSessionModel {
+            upload_id,
+            s3_key,
+            created_by,
+            created_at: Default::default(),
+        })
     }
 
-    /// Returns predefined mock English subjects.
-    async fn list_paginated_en(
-        &self,
-        _page: u64,
-        _per_page: u64,
-        _query_term: Option<String>,
-    ) -> Result<(Vec<DublinMetadataSubjectEnModel>, u64), DbErr> {
-        Ok(mock_paginated_subjects_en())
+    async fn get_one(&self, upload_id: &str) -> Result<Option<UploadSessionModel>, DbErr> {
+        if upload_id != "mock-upload-id" {
+            return Ok(None);
+        }
+        Ok(Some(UploadSessionModel {
+            upload_id: upload_id.to_string(),
+            s3_key: "mock-upload.wacz".to_string(),
+            created_by: "someuser@gmail.com".to_string(),
+            created_at: Default::default(),
+        }))
     }
 
-    /// Always returns true for subject verification in tests.
-    async fn verify_subjects_exist(
-        &self,
-        _subject_ids: Vec<i32>,
-        _metadata_language: MetadataLanguage,
-    ) -> Result<bool, DbErr> {
-        Ok(true)
+    async fn delete_one(&self, _upload_id: &str) -> Result<(), DbErr> {
+        Ok(())
     }
 }
 
@@ -159,6 +1393,52 @@ impl EmailsRepo for InMemoryEmailsRepo {
     }
 }
 
+/// ID of the one researcher [`InMemoryAuthRepo`] knows about
+pub const MOCK_RESEARCHER_ID: Uuid = Uuid::from_u128(1);
+/// Address of the one user [`InMemoryAuthRepo`] reports as deactivated
+pub const MOCK_DEACTIVATED_EMAIL: &str = "deactivated@example.com";
+
+/// In-memory leader election, where the lock goes to whoever asks first until released.
+#[derive(Debug, Default)]
+pub struct InMemoryLeaderElectionRepo {
+    leader: Mutex<Option<String>>,
+    unreachable: AtomicBool,
+}
+
+impl InMemoryLeaderElectionRepo {
+    /// Lets go of the lock, as Postgres does when the leader's session ends.
+    pub fn release(&self) {
+        *self.leader.lock().unwrap() = None;
+    }
+
+    /// Makes every call fail, as when the database can't be reached.
+    pub fn set_unreachable(&self, unreachable: bool) {
+        self.unreachable.store(unreachable, Ordering::SeqCst);
+    }
+
+    fn check_reachable(&self) -> Result<(), DbErr> {
+        if self.unreachable.load(Ordering::SeqCst) {
+            return Err(DbErr::Custom("Database unreachable".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LeaderElectionRepo for InMemoryLeaderElectionRepo {
+    async fn try_lead(&self, instance: &str) -> Result<bool, DbErr> {
+        self.check_reachable()?;
+        let mut leader = self.leader.lock().unwrap();
+        let leader = leader.get_or_insert_with(|| instance.to_string());
+        Ok(leader == instance)
+    }
+
+    async fn current_leader(&self) -> Result<Option<String>, DbErr> {
+        self.check_reachable()?;
+        Ok(self.leader.lock().unwrap().clone())
+    }
+}
+
 /// In-memory implementation of AuthRepo for testing.
 #[derive(Clone, Debug, Default)]
 pub struct InMemoryAuthRepo {}
@@ -169,7 +1449,7 @@ impl AuthRepo for InMemoryAuthRepo {
         Ok(Some(Uuid::new_v4()))
     }
 
-    async fn create_session(&self, _user_id: Uuid) -> Result<Uuid, DbErr> {
+    async fn create_session(&self, _user_id: Uuid, _client: Option<String>) -> Result<Uuid, DbErr> {
         Ok(Uuid::new_v4())
     }
 
@@ -184,12 +1464,22 @@ impl AuthRepo for InMemoryAuthRepo {
         Ok(Some(chrono::NaiveDateTime::default()))
     }
 
-    async fn get_one(&self, _user_id: Uuid) -> Result<Option<entity::archive_user::Model>, DbErr> {
+    /// Finds an admin for any ID but [`MOCK_RESEARCHER_ID`], who is a researcher.
+    async fn get_one(&self, user_id: Uuid) -> Result<Option<entity::archive_user::Model>, DbErr> {
+        let (email, role) = if user_id == MOCK_RESEARCHER_ID {
+            ("researcher@example.com", Role::Researcher)
+        } else {
+            ("test@example.com", Role::Admin)
+        };
         Ok(Some(entity::archive_user::Model {
-            id: Uuid::new_v4(),
-            email: "test@example.com".to_string(),
-            role: entity::sea_orm_active_enums::Role::Admin,
+            id: user_id,
+            email: email.to_string(),
+            role,
             is_active: true,
+            email_status: EmailStatus::Deliverable,
+            organization_id: DEFAULT_ORGANIZATION_ID,
+            activity_digest: false,
+            activity_digest_sent_at: None,
         }))
     }
 
@@ -201,12 +1491,60 @@ impl AuthRepo for InMemoryAuthRepo {
         Ok(Some(ApiKeyUserInfo {
             email: "test@example.com".to_string(),
             role: Role::Admin,
+            organization_id: DEFAULT_ORGANIZATION_ID,
         }))
     }
 
     async fn delete_expired_api_keys(&self) {
         // No-op for tests
     }
+
+    async fn list_users(
+        &self,
+        _organization_id: Option<i32>,
+    ) -> Result<Vec<entity::archive_user::Model>, DbErr> {
+        Ok(vec![entity::archive_user::Model {
+            id: Uuid::nil(),
+            email: "test@example.com".to_string(),
+            role: Role::Admin,
+            is_active: true,
+            email_status: EmailStatus::HardBounce,
+            organization_id: DEFAULT_ORGANIZATION_ID,
+            activity_digest: false,
+            activity_digest_sent_at: None,
+        }])
+    }
+
+    /// Pretends only `test@example.com` is a user.
+    async fn set_email_status(
+        &self,
+        email: String,
+        _email_status: EmailStatus,
+    ) -> Result<bool, DbErr> {
+        Ok(email == "test@example.com")
+    }
+
+    /// Reports `bounced@example.com` as hard bounced and everyone else as deliverable.
+    async fn get_email_status(&self, email: String) -> Result<Option<EmailStatus>, DbErr> {
+        if email == "bounced@example.com" {
+            Ok(Some(EmailStatus::HardBounce))
+        } else {
+            Ok(Some(EmailStatus::Deliverable))
+        }
+    }
+
+    async fn set_activity_digest(&self, _email: String, _enabled: bool) -> Result<bool, DbErr> {
+        Ok(true)
+    }
+
+    async fn deactivate_user(&self, _user_id: Uuid) -> Result<bool, DbErr> {
+        Ok(true)
+    }
+
+    /// Pretends only [`MOCK_DEACTIVATED_EMAIL`] has been deactivated.
+    async fn is_active(&self, email: String) -> Result<bool, DbErr> {
+        Ok(email != MOCK_DEACTIVATED_EMAIL)
+    }
 }
 
 /// In-memory implementation of BrowsertrixRepo for testing.
@@ -215,11 +1553,6 @@ pub struct InMemoryBrowsertrixRepo {}
 
 #[async_trait]
 impl BrowsertrixRepo for InMemoryBrowsertrixRepo {
-    /// Returns a random UUID as organization ID.
-    fn get_org_id(&self) -> Uuid {
-        Uuid::new_v4()
-    }
-
     /// Mock refresh authentication that does nothing.
     async fn refresh_auth(&self) {
         // No-op for tests
@@ -268,6 +1601,39 @@ impl BrowsertrixRepo for InMemoryBrowsertrixRepo {
         Ok("complete".to_owned())
     }
 }
+
+#[async_trait]
+impl CrawlerRepo for InMemoryBrowsertrixRepo {
+    fn backend(&self) -> CrawlerBackend {
+        CrawlerBackend::Browsertrix
+    }
+
+    /// Returns a launched crawl with random UUIDs and fixed job ID.
+    async fn launch(
+        &self,
+        _create_crawl_request: CreateCrawlRequest,
+    ) -> Result<LaunchedCrawl, CrawlerError> {
+        Ok(LaunchedCrawl {
+            id: Uuid::new_v4(),
+            job_run_id: "test_job_123".to_string(),
+            org_id: Some(Uuid::new_v4()),
+        })
+    }
+
+    /// Every crawl is finished.
+    async fn is_finished(&self, _crawl: &LaunchedCrawl) -> Result<bool, CrawlerError> {
+        Ok(true)
+    }
+
+    /// Returns a mock stream for WACZ file content.
+    async fn download(&self, _crawl: &LaunchedCrawl) -> Result<CrawlArchive, CrawlerError> {
+        Ok(CrawlArchive {
+            format: DublinMetadataFormat::Wacz,
+            stream: futures::stream::once(async { Ok(Bytes::from_static(b"{}")) }).boxed(),
+            metadata_json: None,
+        })
+    }
+}
 /// Mock implementation for testing
 #[derive(Debug, Clone, Default)]
 pub struct InMemoryS3Repo {
@@ -307,6 +1673,25 @@ impl S3Repo for InMemoryS3Repo {
         Ok("my url".to_string())
     }
 
+    async fn get_presigned_put_url(
+        &self,
+        key: &str,
+        _content_type: &str,
+        _expires_in: u64,
+    ) -> Result<String, Box<dyn StdError>> {
+        Ok(format!("mock-presigned-put-{}", key))
+    }
+
+    async fn get_presigned_upload_part_url(
+        &self,
+        _key: &str,
+        _upload_id: &str,
+        part_number: i32,
+        _expires_in: u64,
+    ) -> Result<String, Box<dyn StdError>> {
+        Ok(format!("mock-presigned-part-{}", part_number))
+    }
+
     async fn initiate_multipart_upload(
         &self,
         key: &str,
@@ -337,12 +1722,113 @@ impl S3Repo for InMemoryS3Repo {
     async fn delete_object(&self, _key: &str) -> Result<(), Box<dyn StdError>> {
         Ok(())
     }
+
+    async fn copy_object(&self, _from: &str, _to: &str) -> Result<(), Box<dyn StdError>> {
+        Ok(())
+    }
+
+    async fn abort_multipart_upload(
+        &self,
+        _key: &str,
+        _upload_id: &str,
+    ) -> Result<(), Box<dyn StdError>> {
+        Ok(())
+    }
+
+    async fn list_parts(
+        &self,
+        _key: &str,
+        _upload_id: &str,
+    ) -> Result<Vec<(String, i32, u64)>, Box<dyn StdError>> {
+        Ok(vec![("mock-etag-part-1".to_string(), 1, 5 * 1024 * 1024)])
+    }
+
+    async fn get_object_size(&self, _key: &str) -> Result<u64, Box<dyn StdError>> {
+        Ok(build_test_wacz().len() as u64)
+    }
+
+    async fn get_bucket_size(&self) -> Result<u64, Box<dyn StdError>> {
+        Ok(build_test_wacz().len() as u64)
+    }
+
+    async fn get_object_range(
+        &self,
+        _key: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Bytes, Box<dyn StdError>> {
+        let wacz = build_test_wacz();
+        let end = (end as usize).min(wacz.len() - 1);
+        Ok(Bytes::copy_from_slice(&wacz[start as usize..=end]))
+    }
+
+    /// Streams the test WACZ whatever the key, ignoring the range.
+    async fn get_object_stream(
+        &self,
+        _key: &str,
+        _range: Option<&str>,
+    ) -> Result<ObjectStream, Box<dyn StdError>> {
+        let wacz = build_test_wacz();
+        Ok(ObjectStream {
+            content_type: Some("application/wacz".to_string()),
+            content_length: Some(wacz.len() as i64),
+            content_range: None,
+            body: ByteStream::from(wacz),
+        })
+    }
+}
+
+/// Clock that stays at the time it was set to until a test moves it on. Clones share the
+/// time, so a test can keep one to move the clock a service was given.
+#[derive(Debug, Clone)]
+pub struct FixedClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    /// Moves the clock on by `duration`.
+    pub fn advance(&self, duration: chrono::Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+/// Starts at midnight on 1 January 2025.
+impl Default for FixedClock {
+    fn default() -> Self {
+        Self::new(DateTime::from_timestamp(1_735_689_600, 0).unwrap())
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Hands out UUIDs 1, 2, 3 and so on.
+#[derive(Debug, Default)]
+pub struct SequentialIds {
+    last: AtomicU64,
+}
+
+impl IdGen for SequentialIds {
+    fn new_id(&self) -> Uuid {
+        Uuid::from_u128(u128::from(self.last.fetch_add(1, Ordering::SeqCst) + 1))
+    }
 }
+
 /// Builds a test accessions service with in-memory repositories.
 /// Useful for unit testing service functionality without database connections.
 pub fn build_test_accessions_service() -> AccessionsService {
     let accessions_repo = Arc::new(InMemoryAccessionsRepo::default());
     let browsertrix_repo = Arc::new(InMemoryBrowsertrixRepo {});
+    let crawlers = Crawlers::new(CrawlerBackend::Browsertrix).with(browsertrix_repo.clone());
     let emails_repo = Arc::new(InMemoryEmailsRepo::default());
     let s3_repo = Arc::new(InMemoryS3Repo {
         bucket: "test-bucket".to_string(),
@@ -350,11 +1836,61 @@ pub fn build_test_accessions_service() -> AccessionsService {
     AccessionsService {
         accessions_repo,
         browsertrix_repo,
+        crawlers,
         emails_repo,
         s3_repo,
+        pdf_renderer_repo: Some(Arc::new(InMemoryPdfRendererRepo::default())),
+        translation_repo: Some(Arc::new(InMemoryTranslationRepo::default())),
+        doi_repo: Some(Arc::new(InMemoryDoiRepo::default())),
+        virus_scanner_repo: Some(Arc::new(InMemoryVirusScannerRepo::default())),
+        media_transcoder_repo: Some(Arc::new(InMemoryMediaTranscoderRepo::default())),
+        video_downloader_repo: Some(Arc::new(InMemoryVideoDownloaderRepo::default())),
+        scan_enforcement: ScanEnforcement::Block,
+        s3_key_scheme: S3KeyScheme::Dated,
+        public_api_url: "https://api.example.org".to_string(),
+        wacz_pages_cache: new_wacz_pages_cache(),
+        pipeline_metrics: new_pipeline_metrics(SharedClock::default()),
+        crawl_queue: new_crawl_queue(2),
+        upload_progress: UploadProgressRegistry::default(),
+        accession_events_repo: Arc::new(InMemoryAccessionEventsRepo::default()),
+        accession_relations_repo: Arc::new(InMemoryAccessionRelationsRepo::default()),
+        comments_repo: Arc::new(InMemoryCommentsRepo::default()),
+        provenance_repo: Arc::new(InMemoryProvenanceRepo::default()),
+        provenance_signer: Some(Arc::new(
+            ProvenanceSigner::from_base64_seed(MOCK_PROVENANCE_SIGNING_KEY).unwrap(),
+        )),
+        social_metadata_repo: Arc::new(InMemorySocialMetadataRepo::default()),
+        content_digest_repo: Arc::new(InMemoryContentDigestRepo::default()),
+        oembed_repo: Arc::new(InMemoryOembedRepo::default()),
+        audit_log_repo: Arc::new(InMemoryAuditLogRepo::default()),
+        publication_feed: PublicationFeed::default(),
+        s3_backfill: S3BackfillProgress::default(),
+        static_export: StaticExportProgress::default(),
+        reindex: ReindexProgress::default(),
+        clock: SharedClock::default(),
+        ids: SharedIdGen::default(),
     }
 }
 
+/// Builds a small WACZ with a deflated pages index for testing page listing.
+pub fn build_test_wacz() -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    writer.start_file("datapackage.json", options).unwrap();
+    writer
+        .write_all(b"{\"profile\": \"data-package\"}")
+        .unwrap();
+    writer.start_file("pages/pages.jsonl", options).unwrap();
+    writer
+        .write_all(
+            b"{\"format\": \"json-pages-1.0\", \"id\": \"pages\", \"title\": \"All Pages\"}\n\
+              {\"id\": \"1\", \"url\": \"https://example.com/\", \"title\": \"Example\", \"ts\": \"2024-01-01T00:00:00Z\"}\n\
+              {\"id\": \"2\", \"url\": \"https://example.com/about\", \"ts\": \"2024-01-01T00:01:00Z\"}\n",
+        )
+        .unwrap();
+    writer.finish().unwrap().into_inner()
+}
+
 pub fn build_test_auth_service() -> AuthService {
     let auth_repo = Arc::new(InMemoryAuthRepo::default());
     let emails_repo = Arc::new(InMemoryEmailsRepo::default());
@@ -362,40 +1898,169 @@ pub fn build_test_auth_service() -> AuthService {
     AuthService {
         auth_repo,
         emails_repo,
+        audit_log_repo: Arc::new(InMemoryAuditLogRepo::default()),
         jwt_cookie_domain: "test".to_string(),
+        postmark_webhook_secret: Some("webhook-secret".to_string()),
+        known_api_keys: Default::default(),
+        clock: SharedClock::default(),
     }
 }
 
 /// Builds a test subjects service with in-memory repository.
 pub fn build_test_subjects_service() -> SubjectsService {
     let subjects_repo = Arc::new(InMemorySubjectsRepo::default());
-    SubjectsService { subjects_repo }
+    SubjectsService {
+        subjects_repo,
+        entity_extractor_repo: Some(Arc::new(InMemoryEntityExtractorRepo::default())),
+    }
+}
+
+/// Builds a test workflow labels service with in-memory repository.
+pub fn build_test_workflow_labels_service() -> WorkflowLabelsService {
+    let workflow_labels_repo = Arc::new(InMemoryWorkflowLabelsRepo::default());
+    WorkflowLabelsService {
+        workflow_labels_repo,
+    }
+}
+
+/// Builds a test collections service with in-memory repositories.
+pub fn build_test_collections_service() -> CollectionsService {
+    CollectionsService {
+        collections_repo: Arc::new(InMemoryCollectionsRepo::default()),
+        accessions_repo: Arc::new(InMemoryAccessionsRepo::default()),
+        s3_repo: Arc::new(InMemoryS3Repo {
+            bucket: "test-bucket".to_string(),
+        }),
+        emails_repo: Arc::new(InMemoryEmailsRepo::default()),
+        clock: SharedClock::default(),
+        ids: SharedIdGen::default(),
+        export_permits: new_export_permits(),
+    }
+}
+
+/// Builds a test organizations service with in-memory repository.
+pub fn build_test_organizations_service() -> OrganizationsService {
+    OrganizationsService {
+        organizations_repo: Arc::new(InMemoryOrganizationsRepo::default()),
+    }
+}
+
+/// Builds a test feature flags service with in-memory repository.
+pub fn build_test_flags_service() -> FlagsService {
+    FlagsService {
+        feature_flags_repo: Arc::new(InMemoryFeatureFlagsRepo::default()),
+        environment: "test".to_string(),
+        flags_cache: new_feature_flags_cache(),
+    }
+}
+
+/// Builds a test crawl blocklist service with in-memory repository.
+pub fn build_test_crawl_blocklist_service() -> CrawlBlocklistService {
+    CrawlBlocklistService {
+        crawl_blocklist_repo: Arc::new(InMemoryCrawlBlocklistRepo::default()),
+    }
+}
+
+/// Builds a test audit log service with an in-memory repository.
+pub fn build_test_audit_log_service() -> AuditLogService {
+    AuditLogService {
+        audit_log_repo: Arc::new(InMemoryAuditLogRepo::default()),
+    }
+}
+
+/// Builds a test public stats service with in-memory repositories.
+pub fn build_test_stats_service() -> StatsService {
+    StatsService {
+        accessions_repo: Arc::new(InMemoryAccessionsRepo::default()),
+        subjects_repo: Arc::new(InMemorySubjectsRepo::default()),
+        s3_repo: Arc::new(InMemoryS3Repo {
+            bucket: "test-bucket".to_string(),
+        }),
+        stats_cache: new_public_stats_cache(),
+    }
+}
+
+/// Builds a test uploads service with in-memory repositories.
+pub fn build_test_uploads_service() -> UploadsService {
+    UploadsService {
+        uploads_repo: Arc::new(InMemoryUploadsRepo::default()),
+        s3_repo: Arc::new(InMemoryS3Repo {
+            bucket: "test-bucket".to_string(),
+        }),
+        ids: SharedIdGen::default(),
+    }
 }
 
 /// Creates a test application instance with in-memory services.
 /// The returned Router can be used with axum test utilities.
 pub fn build_test_app() -> Router {
-    let accessions_service = build_test_accessions_service();
+    build_test_app_with_accessions_service(build_test_accessions_service())
+}
+
+/// Creates a test application instance like [`build_test_app`], but with the given
+/// accessions service, e.g. one backed by a real database.
+pub fn build_test_app_with_accessions_service(accessions_service: AccessionsService) -> Router {
+    build_test_app_with(accessions_service, build_test_flags_service())
+}
+
+/// Creates a test application instance like [`build_test_app`], but with the given feature
+/// flags service, e.g. one for another environment.
+pub fn build_test_app_with_flags_service(flags_service: FlagsService) -> Router {
+    build_test_app_with(build_test_accessions_service(), flags_service)
+}
+
+fn build_test_app_with(
+    accessions_service: AccessionsService,
+    flags_service: FlagsService,
+) -> Router {
     let subjects_service = build_test_subjects_service();
     let auth_service = build_test_auth_service();
+    let workflow_labels_service = build_test_workflow_labels_service();
+    let collections_service = build_test_collections_service();
+    let uploads_service = build_test_uploads_service();
+    let crawl_blocklist_service = build_test_crawl_blocklist_service();
+    let stats_service = build_test_stats_service();
+    let audit_log_service = build_test_audit_log_service();
+    let organizations_service = build_test_organizations_service();
     let app_state = AppState {
         accessions_service,
         subjects_service,
         auth_service,
+        workflow_labels_service,
+        collections_service,
+        uploads_service,
+        flags_service,
+        crawl_blocklist_service,
+        stats_service,
+        audit_log_service,
+        organizations_service,
+        scheduler_metrics: new_scheduler_metrics(),
+        leader_election: Arc::new(LeaderElection::new(
+            "api-1".to_string(),
+            Arc::new(InMemoryLeaderElectionRepo::default()),
+        )),
+        clock: SharedClock::default(),
+    };
+    let app_config = AppConfig {
+        json_route_limits: RouteLimits::JSON,
+        bulk_route_limits: RouteLimits::BULK,
+        upload_route_limits: RouteLimits {
+            body_limit: 100 * 1024 * 1024,
+            ..RouteLimits::UPLOAD
+        },
+        ..Default::default()
     };
-    let mut app_config = AppConfig::default();
-    app_config.max_file_upload_size = 100 * 1024 * 1024;
     create_app(app_state, app_config, true)
 }
 
 /// Creates a mock paginated collection of English accessions.
-pub fn mock_paginated_en() -> (Vec<AccessionsWithMetadataModel>, u64) {
-    (vec![mock_one_accession_with_metadata()], 10)
+pub fn mock_paginated_en() -> (Vec<AccessionsWithMetadataModel>, Option<u64>) {
+    (vec![mock_one_accession_with_metadata()], Some(10))
 }
 
 /// Creates a mock paginated collection of Arabic accessions.
-pub fn mock_paginated_ar() -> (Vec<AccessionsWithMetadataModel>, u64) {
-    (vec![mock_one_accession_with_metadata()], 10)
+pub fn mock_paginated_ar() -> (Vec<AccessionsWithMetadataModel>, Option<u64>) {
+    (vec![mock_one_accession_with_metadata()], Some(10))
 }
 
 /// Creates a single mock accession with metadata for testing.
@@ -409,6 +2074,8 @@ pub fn mock_one_accession_with_metadata() -> AccessionsWithMetadataModel {
         job_run_id: Some("some_job_id".to_string()),
         dublin_metadata_date: Default::default(),
         has_arabic_metadata: true,
+        machine_translated_en: false,
+        machine_translated_ar: false,
         has_english_metadata: true,
         title_en: Some("English Title".to_string()),
         description_en: Some("English Description".to_string()),
@@ -417,11 +2084,39 @@ pub fn mock_one_accession_with_metadata() -> AccessionsWithMetadataModel {
         subjects_en: Some(vec!["archive".to_string()]),
         subjects_ar: Some(vec!["mrhaba archive".to_string()]),
         seed_url: "https://example.com".to_string(),
+        canonical_url: Some("https://example.com/".to_string()),
         subjects_en_ids: Some(vec![1]),
         subjects_ar_ids: Some(vec![3]),
         is_private: true,
+        publication_state: PublicationState::Draft,
+        embargo_until: None,
+        content_warning: None,
         dublin_metadata_format: DublinMetadataFormat::Wacz,
         s3_filename: Some("some_file.wacz".to_string()),
+        pdf_s3_filename: Some("some_file.pdf".to_string()),
+        scan_status: ScanStatus::NotScanned,
+        metadata_scrubbed: false,
+        revision: 0,
+        organization_id: DEFAULT_ORGANIZATION_ID,
+        doi: None,
+        crawl_quality_reason: None,
+    }
+}
+
+/// Creates the mock accession as a capture flagged as bad.
+pub fn mock_bad_capture() -> AccessionsWithMetadataModel {
+    AccessionsWithMetadataModel {
+        crawl_status: CrawlStatus::BadCrawl,
+        crawl_quality_reason: Some("Seed page has no title".to_string()),
+        ..mock_one_accession_with_metadata()
+    }
+}
+
+/// Creates the mock accession as public lookups see it.
+pub fn mock_one_public_accession_with_metadata() -> AccessionsWithMetadataModel {
+    AccessionsWithMetadataModel {
+        is_private: false,
+        ..mock_one_accession_with_metadata()
     }
 }
 
@@ -437,9 +2132,130 @@ pub fn mock_one_accession() -> AccessionModel {
         org_id: Some(Default::default()),
         job_run_id: Some("some_job_id".to_string()),
         seed_url: "https://example.com".to_string(),
-        is_private: true,
+        canonical_url: Some("https://example.com/".to_string()),
+        publication_state: PublicationState::Draft,
+        embargo_until: None,
+        content_warning: None,
         dublin_metadata_format: DublinMetadataFormat::Wacz,
         s3_filename: Some("some_file.wacz".to_string()),
+        pdf_s3_filename: Some("some_file.pdf".to_string()),
+        scan_status: ScanStatus::NotScanned,
+        metadata_scrubbed: false,
+        revision: 0,
+        organization_id: DEFAULT_ORGANIZATION_ID,
+        doi: None,
+        crawl_quality_reason: None,
+    }
+}
+
+/// Creates a single mock thumbnail derivative for testing.
+pub fn mock_one_derivative() -> AccessionDerivativeModel {
+    AccessionDerivativeModel {
+        id: 1,
+        accession_id: 1,
+        kind: DerivativeKind::Thumbnail,
+        s3_filename: "derivatives/1/thumbnail.webp".to_string(),
+        content_type: "image/webp".to_string(),
+        created_at: Default::default(),
+    }
+}
+
+/// The derivatives a detail response contains for [`mock_one_derivative`].
+pub fn mock_derivatives_response() -> Vec<DerivativeResponse> {
+    vec![DerivativeResponse {
+        kind: DerivativeKind::Thumbnail,
+        content_type: "image/webp".to_string(),
+        url: "my url".to_string(),
+    }]
+}
+
+/// The relations a public detail response contains, see [`InMemoryAccessionRelationsRepo`].
+pub fn mock_relations_response() -> Vec<AccessionRelationResponse> {
+    vec![AccessionRelationResponse {
+        id: 1,
+        kind: AccessionRelationKind::PartOf,
+        direction: RelationDirection::Outgoing,
+        accession_id: 2,
+        title_en: Some("English Title".to_string()),
+        title_ar: Some("Arabic Title".to_string()),
+    }]
+}
+
+/// The social media metadata of accession 1, see [`InMemorySocialMetadataRepo`].
+pub fn mock_social_metadata_response() -> SocialMetadataResponse {
+    SocialMetadataResponse {
+        platform: SocialPlatform::X,
+        author_handle: Some("RSFSudan".to_string()),
+        author_name: Some("Rapid Support Forces".to_string()),
+        post_id: Some("1785236745296498795".to_string()),
+        posted_at: Some(Default::default()),
+    }
+}
+
+/// Creates a single mock workflow label for testing.
+pub fn mock_one_workflow_label() -> WorkflowLabelModel {
+    WorkflowLabelModel {
+        id: 1,
+        label: "needs-translation".to_string(),
+    }
+}
+
+/// The collection of [`InMemoryCollectionsRepo`].
+pub fn mock_one_collection() -> CollectionModel {
+    CollectionModel {
+        id: 1,
+        organization_id: DEFAULT_ORGANIZATION_ID,
+        title: "El Fasher siege".to_string(),
+        description: None,
+        created_by: "someuser@gmail.com".to_string(),
+        created_at: Default::default(),
+    }
+}
+
+/// Creates the archive's own organization for testing.
+pub fn mock_one_organization() -> OrganizationModel {
+    OrganizationModel {
+        id: DEFAULT_ORGANIZATION_ID,
+        slug: "sudan-digital-archive".to_string(),
+        name: "Sudan Digital Archive".to_string(),
+        created_at: Default::default(),
+    }
+}
+
+/// Creates a single mock feature flag, on everywhere for everyone, for testing.
+pub fn mock_one_feature_flag() -> FeatureFlagModel {
+    FeatureFlagModel {
+        id: 1,
+        name: "wacz-full-text".to_string(),
+        description: Some("Search inside archived pages".to_string()),
+        enabled: true,
+        environments: vec![],
+        roles: vec![],
+        updated_at: Default::default(),
+    }
+}
+
+/// Creates a single mock crawl blocklist entry for testing, blocking `blocked.example`
+/// and its subdomains.
+pub fn mock_one_audit_log_entry() -> entity::audit_log::Model {
+    entity::audit_log::Model {
+        id: 1,
+        actor_email: "researcher@example.com".to_string(),
+        impersonator_email: None,
+        event: "private_file_accessed".to_string(),
+        entity_type: "accession".to_string(),
+        entity_id: "1".to_string(),
+        details: Some(serde_json::json!({"key": "some_file.wacz"})),
+        created_at: Default::default(),
+    }
+}
+
+pub fn mock_one_crawl_blocklist_entry() -> CrawlBlocklistModel {
+    CrawlBlocklistModel {
+        id: 1,
+        pattern: "*.blocked.example".to_string(),
+        reason: "Court order".to_string(),
+        created_at: Default::default(),
     }
 }
 
@@ -449,6 +2265,10 @@ pub fn mock_paginated_subjects_en() -> (Vec<DublinMetadataSubjectEnModel>, u64)
         vec![DublinMetadataSubjectEnModel {
             id: 1,
             subject: "English Subject".to_string(),
+            organization_id: DEFAULT_ORGANIZATION_ID,
+            created_at: Default::default(),
+            updated_at: Default::default(),
+            created_by: Some("someuser@gmail.com".to_string()),
         }],
         10,
     )
@@ -460,19 +2280,29 @@ pub fn mock_paginated_subjects_ar() -> (Vec<DublinMetadataSubjectArModel>, u64)
         vec![DublinMetadataSubjectArModel {
             id: 1,
             subject: "Arabic Subject".to_string(),
+            organization_id: DEFAULT_ORGANIZATION_ID,
+            created_at: Default::default(),
+            updated_at: Default::default(),
+            created_by: Some("someuser@gmail.com".to_string()),
         }],
         10,
     )
 }
 
 pub fn get_mock_jwt() -> String {
+    get_mock_jwt_for_organization(DEFAULT_ORGANIZATION_ID)
+}
+
+/// Signs an admin session for an organization, see [`get_mock_jwt`] for the archive's own.
+pub fn get_mock_jwt_for_organization(organization_id: i32) -> String {
     let expiry_time: DateTime<Utc> = Utc::now() + chrono::Duration::hours(24);
     let claims = JWTClaims {
         sub: "someuser@gmail.com".to_string(),
         exp: expiry_time.timestamp() as usize,
         role: Role::Admin,
+        impersonator: None,
+        organization_id,
     };
-    let jwt =
-        encode(&Header::default(), &claims, &JWT_KEYS.encoding).expect("Failed to encode JWT");
-    jwt
+
+    JWT_KEYS.encode(&claims).expect("Failed to encode JWT")
 }
@@ -0,0 +1,133 @@
+//! Progress tracking for multipart uploads to S3.
+//!
+//! Large uploads can take minutes over slow connections, so the upload loop publishes
+//! how many bytes and parts have reached S3 after every part. Progress is keyed by the
+//! S3 multipart upload ID and only lives in memory while the upload is running.
+
+use futures::stream::{self, Stream};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+/// Progress of a single multipart upload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UploadProgress {
+    pub bytes_transferred: u64,
+    pub parts_completed: u32,
+    pub complete: bool,
+    pub failed: bool,
+}
+
+impl UploadProgress {
+    /// Whether the upload has stopped, successfully or not.
+    pub fn is_finished(&self) -> bool {
+        self.complete || self.failed
+    }
+}
+
+/// Shared registry of running multipart uploads.
+#[derive(Debug, Clone, Default)]
+pub struct UploadProgressRegistry {
+    uploads: Arc<Mutex<HashMap<String, watch::Sender<UploadProgress>>>>,
+}
+
+impl UploadProgressRegistry {
+    /// Starts tracking a newly initiated multipart upload.
+    pub fn start(&self, upload_id: &str) {
+        let (sender, _) = watch::channel(UploadProgress::default());
+        self.uploads
+            .lock()
+            .unwrap()
+            .insert(upload_id.to_string(), sender);
+    }
+
+    /// Records a part that was uploaded to S3.
+    pub fn record_part(&self, upload_id: &str, part_size: usize) {
+        if let Some(sender) = self.uploads.lock().unwrap().get(upload_id) {
+            sender.send_modify(|progress| {
+                progress.bytes_transferred += part_size as u64;
+                progress.parts_completed += 1;
+            });
+        }
+    }
+
+    /// Publishes the final state of an upload and stops tracking it.
+    pub fn finish(&self, upload_id: &str, failed: bool) {
+        if let Some(sender) = self.uploads.lock().unwrap().remove(upload_id) {
+            sender.send_modify(|progress| {
+                progress.complete = !failed;
+                progress.failed = failed;
+            });
+        }
+    }
+
+    /// Subscribes to progress updates for a running upload.
+    ///
+    /// The stream yields the current progress straight away, then every update until the
+    /// upload finishes. Returns `None` if no upload with that ID is running.
+    pub fn subscribe(&self, upload_id: &str) -> Option<impl Stream<Item = UploadProgress>> {
+        let receiver = self.uploads.lock().unwrap().get(upload_id)?.subscribe();
+        Some(stream::unfold(
+            (receiver, true, false),
+            |(mut receiver, first, done)| async move {
+                if done {
+                    return None;
+                }
+                // Once the sender is dropped the last value sent is the final state
+                if !first && receiver.changed().await.is_err() {
+                    let progress = *receiver.borrow_and_update();
+                    return progress
+                        .is_finished()
+                        .then_some((progress, (receiver, false, true)));
+                }
+                let progress = *receiver.borrow_and_update();
+                Some((progress, (receiver, false, progress.is_finished())))
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn streams_progress_until_complete() {
+        let registry = UploadProgressRegistry::default();
+        registry.start("abc");
+        let updates = registry.subscribe("abc").unwrap();
+        registry.record_part("abc", 10);
+        registry.record_part("abc", 5);
+        registry.finish("abc", false);
+
+        let updates: Vec<UploadProgress> = updates.collect().await;
+        assert_eq!(
+            updates.last(),
+            Some(&UploadProgress {
+                bytes_transferred: 15,
+                parts_completed: 2,
+                complete: true,
+                failed: false,
+            })
+        );
+        assert!(registry.subscribe("abc").is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_failed_uploads() {
+        let registry = UploadProgressRegistry::default();
+        registry.start("abc");
+        let updates = registry.subscribe("abc").unwrap();
+        registry.finish("abc", true);
+        let updates: Vec<UploadProgress> = updates.collect().await;
+        assert!(updates.last().unwrap().failed);
+    }
+
+    #[test]
+    fn unknown_uploads_have_no_progress() {
+        let registry = UploadProgressRegistry::default();
+        assert!(registry.subscribe("missing").is_none());
+    }
+}
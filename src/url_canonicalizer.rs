@@ -0,0 +1,84 @@
+//! URL canonicalization applied before crawling.
+//!
+//! The same page is often shared with different tracking parameters attached, e.g.
+//! `?utm_source=twitter` vs `?fbclid=...`. Crawling each variant separately wastes
+//! Browsertrix time and creates duplicate accessions, so we crawl the canonical form
+//! and store it alongside the URL the archivist originally submitted.
+
+use reqwest::Url;
+
+/// Query parameters that only exist for analytics and never change page content.
+const TRACKING_PARAMS: [&str; 10] = [
+    "fbclid", "gclid", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_ga", "ref_src",
+];
+
+/// Prefixes of tracking query parameters, e.g. `utm_source` and `utm_campaign`.
+const TRACKING_PARAM_PREFIXES: [&str; 1] = ["utm_"];
+
+fn is_tracking_param(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    TRACKING_PARAMS.contains(&key.as_str())
+        || TRACKING_PARAM_PREFIXES
+            .iter()
+            .any(|prefix| key.starts_with(prefix))
+}
+
+/// Returns the canonical form of a URL.
+///
+/// This lowercases the scheme and host, drops default ports and fragments, and
+/// removes known tracking query parameters while keeping the order of the rest.
+/// URLs that cannot be parsed are returned unchanged since request validation
+/// should already have rejected them.
+pub fn canonicalize_url(url: &str) -> String {
+    let Ok(mut parsed) = Url::parse(url.trim()) else {
+        return url.to_string();
+    };
+    // Scheme, host and default port normalization is handled by the parser
+    parsed.set_fragment(None);
+    let kept_params: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(key, _)| !is_tracking_param(key))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    if kept_params.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(kept_params);
+    }
+    parsed.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn strips_tracking_params() {
+        assert_eq!(
+            canonicalize_url("https://example.com/story?utm_source=x&id=5&fbclid=abc&UTM_Medium=y"),
+            "https://example.com/story?id=5"
+        );
+    }
+
+    #[test]
+    fn drops_empty_query_and_fragment() {
+        assert_eq!(
+            canonicalize_url("https://example.com/story?utm_source=firefox#comments"),
+            "https://example.com/story"
+        );
+    }
+
+    #[test]
+    fn normalizes_scheme_host_and_port() {
+        assert_eq!(
+            canonicalize_url("HTTPS://Example.COM:443/Path"),
+            "https://example.com/Path"
+        );
+    }
+
+    #[test]
+    fn leaves_unparseable_urls_alone() {
+        assert_eq!(canonicalize_url("not a url"), "not a url");
+    }
+}
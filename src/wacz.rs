@@ -0,0 +1,357 @@
+//! Reads the pages index and other files out of WACZ files stored in S3.
+//!
+//! A WACZ is a zip archive whose `pages/pages.jsonl` lists every page captured by
+//! a crawl. WACZ files can be several gigabytes and this service runs in a low memory
+//! container, so rather than downloading the archive we use ranged reads to fetch
+//! only the zip central directory and the entries we need.
+
+use crate::repos::s3_repo::S3Repo;
+use flate2::read::DeflateDecoder;
+use lru::LruCache;
+use serde::Deserialize;
+use std::error::Error;
+use std::io::Read;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+
+/// Path of the pages index inside a WACZ, per the WACZ 1.1.1 spec.
+pub const PAGES_ENTRY_NAME: &str = "pages/pages.jsonl";
+/// End of central directory record is 22 bytes plus a comment of up to 65535 bytes,
+/// and may be preceded by a 20 byte ZIP64 locator and 56 byte ZIP64 record.
+const MAX_TAIL_SIZE: u64 = 22 + 65535 + 20 + 56;
+/// Refuse to read indices larger than this to protect our memory budget.
+const MAX_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+/// How many accessions' page lists to keep in memory.
+const PAGES_CACHE_CAPACITY: usize = 128;
+
+const EOCD_SIGNATURE: u32 = 0x06054b50;
+const ZIP64_EOCD_LOCATOR_SIGNATURE: u32 = 0x07064b50;
+const ZIP64_EOCD_SIGNATURE: u32 = 0x06064b50;
+const CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x02014b50;
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const LOCAL_HEADER_SIZE: u64 = 30;
+
+/// A page captured in a WACZ.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WaczPage {
+    pub url: String,
+    pub title: Option<String>,
+    /// Capture timestamp as written by the crawler, usually RFC 3339
+    pub ts: Option<String>,
+    /// HTTP status the page was captured with, if the crawler recorded it
+    pub status: Option<u16>,
+}
+
+/// LRU cache of WACZ page lists keyed by S3 object key.
+///
+/// WACZ files are immutable once uploaded so entries never need invalidating.
+pub type WaczPagesCache = Arc<Mutex<LruCache<String, Arc<Vec<WaczPage>>>>>;
+
+/// Creates an empty [`WaczPagesCache`].
+pub fn new_wacz_pages_cache() -> WaczPagesCache {
+    let capacity = NonZeroUsize::new(PAGES_CACHE_CAPACITY).expect("Capacity must be non zero");
+    Arc::new(Mutex::new(LruCache::new(capacity)))
+}
+
+/// Location and size of a file inside a zip archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ZipEntry {
+    compression_method: u16,
+    compressed_size: u64,
+    local_header_offset: u64,
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    bytes
+        .get(at..at + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    bytes
+        .get(at..at + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> Option<u64> {
+    bytes
+        .get(at..at + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().expect("Slice is 8 bytes")))
+}
+
+/// Finds the offset and size of the central directory from the tail of a zip file.
+///
+/// `tail_offset` is the position of `tail` within the whole file.
+fn find_central_directory(tail: &[u8], tail_offset: u64) -> Result<(u64, u64), Box<dyn Error>> {
+    let eocd_position = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&at| read_u32(tail, at) == Some(EOCD_SIGNATURE))
+        .ok_or("Not a zip file: end of central directory not found")?;
+    let size = read_u32(tail, eocd_position + 12).ok_or("Truncated end of central directory")?;
+    let offset = read_u32(tail, eocd_position + 16).ok_or("Truncated end of central directory")?;
+    if size != u32::MAX && offset != u32::MAX {
+        return Ok((u64::from(offset), u64::from(size)));
+    }
+
+    // ZIP64, the real values live in a separate record pointed to by the locator
+    let locator_position = eocd_position
+        .checked_sub(20)
+        .filter(|&at| read_u32(tail, at) == Some(ZIP64_EOCD_LOCATOR_SIGNATURE))
+        .ok_or("ZIP64 end of central directory locator not found")?;
+    let zip64_eocd_offset =
+        read_u64(tail, locator_position + 8).ok_or("Truncated ZIP64 locator")?;
+    let zip64_eocd_position = zip64_eocd_offset
+        .checked_sub(tail_offset)
+        .and_then(|position| usize::try_from(position).ok())
+        .filter(|&at| read_u32(tail, at) == Some(ZIP64_EOCD_SIGNATURE))
+        .ok_or("ZIP64 end of central directory not found")?;
+    let size = read_u64(tail, zip64_eocd_position + 40).ok_or("Truncated ZIP64 record")?;
+    let offset = read_u64(tail, zip64_eocd_position + 48).ok_or("Truncated ZIP64 record")?;
+    Ok((offset, size))
+}
+
+/// Looks up a file by name in a zip central directory.
+fn find_entry(central_directory: &[u8], name: &str) -> Result<Option<ZipEntry>, Box<dyn Error>> {
+    let mut at = 0;
+    while read_u32(central_directory, at) == Some(CENTRAL_DIRECTORY_SIGNATURE) {
+        let truncated = "Truncated central directory entry";
+        let compression_method = read_u16(central_directory, at + 10).ok_or(truncated)?;
+        let compressed_size = read_u32(central_directory, at + 20).ok_or(truncated)?;
+        let uncompressed_size = read_u32(central_directory, at + 24).ok_or(truncated)?;
+        let name_length = usize::from(read_u16(central_directory, at + 28).ok_or(truncated)?);
+        let extra_length = usize::from(read_u16(central_directory, at + 30).ok_or(truncated)?);
+        let comment_length = usize::from(read_u16(central_directory, at + 32).ok_or(truncated)?);
+        let local_header_offset = read_u32(central_directory, at + 42).ok_or(truncated)?;
+        let name_start = at + 46;
+        let extra_start = name_start + name_length;
+        let entry_name = central_directory
+            .get(name_start..extra_start)
+            .ok_or(truncated)?;
+
+        if entry_name == name.as_bytes() {
+            let extra = central_directory
+                .get(extra_start..extra_start + extra_length)
+                .ok_or(truncated)?;
+            let mut entry = ZipEntry {
+                compression_method,
+                compressed_size: u64::from(compressed_size),
+                local_header_offset: u64::from(local_header_offset),
+            };
+            apply_zip64_extra(
+                extra,
+                uncompressed_size == u32::MAX,
+                compressed_size == u32::MAX,
+                local_header_offset == u32::MAX,
+                &mut entry,
+            )?;
+            return Ok(Some(entry));
+        }
+        at = extra_start + extra_length + comment_length;
+    }
+    Ok(None)
+}
+
+/// Reads 64 bit sizes and offsets from the ZIP64 extra field, which only contains
+/// the values whose 32 bit counterparts are saturated.
+fn apply_zip64_extra(
+    extra: &[u8],
+    has_uncompressed_size: bool,
+    has_compressed_size: bool,
+    has_offset: bool,
+    entry: &mut ZipEntry,
+) -> Result<(), Box<dyn Error>> {
+    if !(has_uncompressed_size || has_compressed_size || has_offset) {
+        return Ok(());
+    }
+    let mut at = 0;
+    while let (Some(header_id), Some(size)) = (read_u16(extra, at), read_u16(extra, at + 2)) {
+        let data_start = at + 4;
+        if header_id == 0x0001 {
+            let mut field = data_start;
+            if has_uncompressed_size {
+                field += 8;
+            }
+            if has_compressed_size {
+                entry.compressed_size = read_u64(extra, field).ok_or("Truncated ZIP64 extra")?;
+                field += 8;
+            }
+            if has_offset {
+                entry.local_header_offset =
+                    read_u64(extra, field).ok_or("Truncated ZIP64 extra")?;
+            }
+            return Ok(());
+        }
+        at = data_start + usize::from(size);
+    }
+    Err("ZIP64 extra field not found".into())
+}
+
+/// Decompresses a zip entry's data.
+fn decompress(compression_method: u16, data: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    match compression_method {
+        0 => Ok(data.to_vec()),
+        8 => {
+            let mut decompressed = Vec::new();
+            DeflateDecoder::new(data)
+                .take(MAX_ENTRY_SIZE)
+                .read_to_end(&mut decompressed)?;
+            Ok(decompressed)
+        }
+        method => Err(format!("Unsupported zip compression method {method}").into()),
+    }
+}
+
+/// Parses `pages.jsonl`, skipping the header line and anything without a URL.
+fn parse_pages_jsonl(contents: &str) -> Vec<WaczPage> {
+    contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<WaczPage>(line).ok())
+        .collect()
+}
+
+/// Reads the list of captured pages from a WACZ stored in S3.
+///
+/// # Arguments
+/// * `s3_repo` - Storage containing the WACZ
+/// * `key` - The WACZ object key
+///
+/// # Errors
+/// Returns Error if the object is not a valid WACZ, has no pages index or cannot be read
+pub async fn read_wacz_pages(
+    s3_repo: &dyn S3Repo,
+    key: &str,
+) -> Result<Vec<WaczPage>, Box<dyn Error>> {
+    let [contents] = read_wacz_entries(s3_repo, key, [PAGES_ENTRY_NAME]).await?;
+    let contents = contents.ok_or("WACZ has no pages index")?;
+    Ok(parse_pages_jsonl(&String::from_utf8_lossy(&contents)))
+}
+
+/// Reads files out of a WACZ stored in S3, fetching its central directory once.
+///
+/// # Arguments
+/// * `s3_repo` - Storage containing the WACZ
+/// * `key` - The WACZ object key
+/// * `names` - Paths of the files inside the WACZ
+///
+/// # Returns
+/// The decompressed contents of each file, `None` for files the WACZ doesn't have
+///
+/// # Errors
+/// Returns Error if the object is not a valid WACZ, a file is too large or it cannot be read
+pub async fn read_wacz_entries<const N: usize>(
+    s3_repo: &dyn S3Repo,
+    key: &str,
+    names: [&str; N],
+) -> Result<[Option<Vec<u8>>; N], Box<dyn Error>> {
+    let file_size = s3_repo.get_object_size(key).await?;
+    if file_size == 0 {
+        return Err("WACZ file is empty".into());
+    }
+    let tail_offset = file_size.saturating_sub(MAX_TAIL_SIZE);
+    let tail = s3_repo
+        .get_object_range(key, tail_offset, file_size - 1)
+        .await?;
+    let (central_directory_offset, central_directory_size) =
+        find_central_directory(&tail, tail_offset)?;
+    if central_directory_size == 0 || central_directory_size > MAX_ENTRY_SIZE {
+        return Err("Unexpected zip central directory size".into());
+    }
+
+    let central_directory = s3_repo
+        .get_object_range(
+            key,
+            central_directory_offset,
+            central_directory_offset + central_directory_size - 1,
+        )
+        .await?;
+    let mut contents: [Option<Vec<u8>>; N] = std::array::from_fn(|_| None);
+    for (name, contents) in names.into_iter().zip(contents.iter_mut()) {
+        let entry = find_entry(&central_directory, name)?;
+        if let Some(entry) = entry {
+            *contents = Some(read_entry(s3_repo, key, name, &entry).await?);
+        }
+    }
+    Ok(contents)
+}
+
+/// Reads and decompresses one file of a zip archive stored in S3.
+async fn read_entry(
+    s3_repo: &dyn S3Repo,
+    key: &str,
+    name: &str,
+    entry: &ZipEntry,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    if entry.compressed_size > MAX_ENTRY_SIZE {
+        return Err(format!("WACZ entry {name} is too large").into());
+    }
+
+    let local_header = s3_repo
+        .get_object_range(
+            key,
+            entry.local_header_offset,
+            entry.local_header_offset + LOCAL_HEADER_SIZE - 1,
+        )
+        .await?;
+    if read_u32(&local_header, 0) != Some(LOCAL_HEADER_SIGNATURE) {
+        return Err("Invalid zip local file header".into());
+    }
+    let name_length = read_u16(&local_header, 26).ok_or("Truncated local file header")?;
+    let extra_length = read_u16(&local_header, 28).ok_or("Truncated local file header")?;
+    let data_offset = entry.local_header_offset
+        + LOCAL_HEADER_SIZE
+        + u64::from(name_length)
+        + u64::from(extra_length);
+
+    if entry.compressed_size == 0 {
+        return Ok(Vec::new());
+    }
+    let data = s3_repo
+        .get_object_range(key, data_offset, data_offset + entry.compressed_size - 1)
+        .await?;
+    decompress(entry.compression_method, &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_tools::build_test_wacz;
+    use pretty_assertions::assert_eq;
+
+    fn entry_for(wacz: &[u8], name: &str) -> Option<ZipEntry> {
+        let tail_offset = (wacz.len() as u64).saturating_sub(MAX_TAIL_SIZE);
+        let (offset, size) =
+            find_central_directory(&wacz[tail_offset as usize..], tail_offset).unwrap();
+        let central_directory = &wacz[offset as usize..(offset + size) as usize];
+        find_entry(central_directory, name).unwrap()
+    }
+
+    #[test]
+    fn finds_pages_entry() {
+        let wacz = build_test_wacz();
+        let entry = entry_for(&wacz, PAGES_ENTRY_NAME).unwrap();
+        assert_eq!(entry.compression_method, 8);
+        assert!(entry_for(&wacz, "pages/missing.jsonl").is_none());
+    }
+
+    #[test]
+    fn rejects_non_zip() {
+        assert!(find_central_directory(b"definitely not a zip file", 0).is_err());
+    }
+
+    #[test]
+    fn parses_pages_skipping_header() {
+        let pages = parse_pages_jsonl(
+            "{\"format\": \"json-pages-1.0\", \"id\": \"pages\"}\n\
+             {\"url\": \"https://example.com/\", \"title\": \"Example\", \"ts\": \"2024-01-01T00:00:00Z\"}\n",
+        );
+        assert_eq!(
+            pages,
+            vec![WaczPage {
+                url: "https://example.com/".to_string(),
+                title: Some("Example".to_string()),
+                ts: Some("2024-01-01T00:00:00Z".to_string()),
+                status: None,
+            }]
+        );
+    }
+}